@@ -366,6 +366,7 @@ async fn start_server(config: Config, db: Db) -> gl_core::Result<()> {
         max_retries: 3,
         model: std::env::var("OPENAI_MODEL").unwrap_or_else(|_| "gpt-4".to_string()),
         use_online: std::env::var("OPENAI_API_KEY").is_ok(),
+        ..Default::default()
     };
 
     tracing::info!(
@@ -422,6 +423,46 @@ async fn start_server(config: Config, db: Db) -> gl_core::Result<()> {
         .set_job_scheduler(job_scheduler.clone())
         .await;
 
+    // Let rule actions take snapshot bursts and recordings through the capture manager
+    capture_manager_arc.enable_rule_actions().await;
+
+    // Originals of stopped streams are only removed by this job
+    capture_manager_arc.enable_original_purge().await?;
+
+    if config.notifications.web_push.enabled {
+        capture_manager_arc
+            .enable_web_push(&config.notifications.web_push)
+            .await?;
+    }
+
+    if !config.notifications.digests.is_empty() {
+        capture_manager_arc
+            .enable_digests(&config.notifications, config.external.smtp.as_ref())
+            .await?;
+    }
+
+    if config.ingest.ftp.enabled {
+        capture_manager_arc
+            .start_ftp_ingest(&config.ingest.ftp)
+            .await?;
+    }
+
+    if config.ingest.smtp.enabled {
+        capture_manager_arc
+            .start_smtp_ingest(&config.ingest.smtp)
+            .await?;
+    }
+
+    if config.restream.enabled {
+        capture_manager_arc.start_restream(&config.restream).await?;
+    }
+
+    if config.embeddings.enabled {
+        capture_manager_arc
+            .enable_snapshot_search(&config.embeddings)
+            .await?;
+    }
+
     let web_app_state = AppState {
         db: db.clone(),
         cache: std::sync::Arc::new(gl_db::DatabaseCache::new()),
@@ -0,0 +1,141 @@
+//! ABOUTME: Daily AI usage accounting used to cap tokens and spend per stream
+//! ABOUTME: Defines the usage store the router records into, with an in-memory default
+
+use async_trait::async_trait;
+use gl_core::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Requests, tokens and spend accumulated for a stream on one day
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct AiUsage {
+    pub requests: u64,
+    pub tokens: u64,
+    pub cost_usd: f64,
+}
+
+/// Persistent counters behind per-stream budgets
+#[async_trait]
+pub trait UsageStore: Send + Sync {
+    /// Usage of a stream on a UTC day (`YYYY-MM-DD`) across all models
+    async fn daily_usage(&self, stream_id: &str, day: &str) -> Result<AiUsage>;
+
+    /// Add one request's tokens and cost to a stream's counters
+    async fn record(
+        &self,
+        stream_id: &str,
+        day: &str,
+        model: &str,
+        tokens: u64,
+        cost_usd: f64,
+    ) -> Result<()>;
+}
+
+/// Usage store shared between clients built from the same configuration
+#[derive(Clone)]
+pub struct SharedUsageStore(pub Arc<dyn UsageStore>);
+
+impl std::fmt::Debug for SharedUsageStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SharedUsageStore")
+    }
+}
+
+/// Usage store that forgets everything on restart
+#[derive(Default)]
+pub struct MemoryUsageStore {
+    usage: Mutex<HashMap<(String, String), AiUsage>>,
+}
+
+impl MemoryUsageStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl UsageStore for MemoryUsageStore {
+    async fn daily_usage(&self, stream_id: &str, day: &str) -> Result<AiUsage> {
+        let usage = self.usage.lock().unwrap();
+        Ok(usage
+            .get(&(stream_id.to_string(), day.to_string()))
+            .copied()
+            .unwrap_or_default())
+    }
+
+    async fn record(
+        &self,
+        stream_id: &str,
+        day: &str,
+        _model: &str,
+        tokens: u64,
+        cost_usd: f64,
+    ) -> Result<()> {
+        let mut usage = self.usage.lock().unwrap();
+        let entry = usage
+            .entry((stream_id.to_string(), day.to_string()))
+            .or_default();
+        entry.requests += 1;
+        entry.tokens += tokens;
+        entry.cost_usd += cost_usd;
+        Ok(())
+    }
+}
+
+/// Today's date in UTC, the key budgets reset on
+pub fn utc_day() -> String {
+    chrono::Utc::now().format("%Y-%m-%d").to_string()
+}
+
+/// Rough token count of text; providers count roughly four characters per token
+pub(crate) fn estimate_tokens(text: &str) -> u64 {
+    (text.chars().count() as u64).div_ceil(4)
+}
+
+/// Tokens an image costs at a detail level, following OpenAI's vision pricing
+pub(crate) fn estimate_image_tokens(detail_level: Option<&str>) -> u64 {
+    match detail_level {
+        Some("low") => 85,
+        _ => 765,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_memory_store_accumulates_per_stream_and_day() {
+        let store = MemoryUsageStore::new();
+        store
+            .record("porch", "2026-10-18", "gpt-4o", 100, 0.01)
+            .await
+            .unwrap();
+        store
+            .record("porch", "2026-10-18", "gpt-4o-mini", 50, 0.001)
+            .await
+            .unwrap();
+        store
+            .record("porch", "2026-10-19", "gpt-4o", 10, 0.001)
+            .await
+            .unwrap();
+
+        let usage = store.daily_usage("porch", "2026-10-18").await.unwrap();
+        assert_eq!(usage.requests, 2);
+        assert_eq!(usage.tokens, 150);
+        assert!((usage.cost_usd - 0.011).abs() < 1e-9);
+        assert_eq!(
+            store.daily_usage("garage", "2026-10-18").await.unwrap(),
+            AiUsage::default()
+        );
+    }
+
+    #[test]
+    fn test_token_estimates() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcde"), 2);
+        assert_eq!(estimate_image_tokens(Some("low")), 85);
+        assert_eq!(estimate_image_tokens(None), 765);
+    }
+}
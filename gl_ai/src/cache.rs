@@ -0,0 +1,408 @@
+//! ABOUTME: Reuses frame descriptions for visually unchanged scenes via perceptual hashing
+//! ABOUTME: Wraps any AI client with a TTL-bound cache keyed on stream, model and prompt
+
+use async_trait::async_trait;
+use gl_core::Result;
+use img_hash::{HashAlg, HasherConfig, ImageHash};
+use metrics::counter;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use tracing::{debug, warn};
+
+use crate::{
+    AiClient, AiConfig, ClassifyEventRequest, ClassifyEventResponse, DescribeFrameRequest,
+    DescribeFrameResponse, SummarizeRequest, SummarizeResponse,
+};
+
+/// Most recent entries compared against a frame before it counts as a miss
+const MAX_CANDIDATES: u32 = 20;
+
+/// When a frame may reuse an earlier description instead of calling the model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AiCacheConfig {
+    pub enabled: bool,
+    /// Hash similarity (0.0 to 1.0) a frame needs to a cached one to reuse its answer
+    pub similarity_threshold: f64,
+    /// How long a cached answer stays valid
+    pub ttl_seconds: u64,
+    /// Where answers are kept; in memory when unset
+    #[serde(skip)]
+    pub store: Option<SharedCacheStore>,
+}
+
+impl Default for AiCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            similarity_threshold: 0.95,
+            ttl_seconds: 1800,
+            store: None,
+        }
+    }
+}
+
+/// A cached description and the frame hash it was produced for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedResponse {
+    pub perceptual_hash: String,
+    pub response: DescribeFrameResponse,
+    /// Unix seconds
+    pub created_at: i64,
+}
+
+/// Persistent storage behind the response cache
+#[async_trait]
+pub trait ResponseCacheStore: Send + Sync {
+    /// Entries for a key created at or after `since`, newest first
+    async fn recent(&self, key: &str, since: i64, limit: u32) -> Result<Vec<CachedResponse>>;
+
+    /// Remember a response under a key
+    async fn insert(&self, key: &str, entry: CachedResponse) -> Result<()>;
+
+    /// Drop entries created before `before`
+    async fn prune(&self, before: i64) -> Result<()>;
+}
+
+/// Cache store shared between clients built from the same configuration
+#[derive(Clone)]
+pub struct SharedCacheStore(pub Arc<dyn ResponseCacheStore>);
+
+impl std::fmt::Debug for SharedCacheStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SharedCacheStore")
+    }
+}
+
+/// Cache store that forgets everything on restart
+#[derive(Default)]
+pub struct MemoryCacheStore {
+    entries: Mutex<Vec<(String, CachedResponse)>>,
+}
+
+impl MemoryCacheStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl ResponseCacheStore for MemoryCacheStore {
+    async fn recent(&self, key: &str, since: i64, limit: u32) -> Result<Vec<CachedResponse>> {
+        let entries = self.entries.lock().unwrap();
+        Ok(entries
+            .iter()
+            .rev()
+            .filter(|(entry_key, entry)| entry_key == key && entry.created_at >= since)
+            .take(limit as usize)
+            .map(|(_, entry)| entry.clone())
+            .collect())
+    }
+
+    async fn insert(&self, key: &str, entry: CachedResponse) -> Result<()> {
+        self.entries.lock().unwrap().push((key.to_string(), entry));
+        Ok(())
+    }
+
+    async fn prune(&self, before: i64) -> Result<()> {
+        self.entries
+            .lock()
+            .unwrap()
+            .retain(|(_, entry)| entry.created_at >= before);
+        Ok(())
+    }
+}
+
+/// Gradient hash of an image, the same one `SmartSnapshotJob` stores with snapshots
+pub fn perceptual_hash(image: &[u8]) -> Result<String> {
+    let image = image::load_from_memory(image)
+        .map_err(|e| gl_core::Error::Validation(format!("Failed to decode image: {}", e)))?;
+    let hasher = HasherConfig::new()
+        .hash_alg(HashAlg::Gradient)
+        .hash_size(8, 8)
+        .to_hasher();
+    Ok(hasher.hash_image(&image).to_base64())
+}
+
+/// Similarity of two perceptual hashes, 1.0 for identical frames
+pub fn hash_similarity(a: &str, b: &str) -> Result<f64> {
+    let decode = |hash: &str| {
+        ImageHash::<[u8; 8]>::from_base64(hash)
+            .map_err(|e| gl_core::Error::Validation(format!("Invalid perceptual hash: {:?}", e)))
+    };
+    let distance = decode(a)?.dist(&decode(b)?);
+    Ok(1.0 - distance as f64 / 64.0)
+}
+
+/// AI client that answers repeated frame descriptions from the cache
+pub struct CachedClient {
+    inner: Box<dyn AiClient>,
+    model: String,
+    config: AiCacheConfig,
+    store: Arc<dyn ResponseCacheStore>,
+}
+
+impl CachedClient {
+    pub fn new(inner: Box<dyn AiClient>, ai_config: &AiConfig, config: AiCacheConfig) -> Self {
+        let store = config
+            .store
+            .as_ref()
+            .map(|store| store.0.clone())
+            .unwrap_or_else(|| Arc::new(MemoryCacheStore::new()));
+        Self {
+            inner,
+            model: vision_model(ai_config),
+            config,
+            store,
+        }
+    }
+
+    /// Frames only share answers within a stream, model and prompt
+    fn cache_key(&self, request: &DescribeFrameRequest) -> String {
+        let prompt = serde_json::json!({
+            "format": request.image_format,
+            "detail_level": request.detail_level,
+            "focus": request.focus,
+            "question": request.question,
+            "output_schema": request.output_schema,
+        });
+        format!(
+            "{}|{}|{}",
+            request.stream_id.as_deref().unwrap_or_default(),
+            self.model,
+            prompt
+        )
+    }
+
+    async fn lookup(&self, key: &str, hash: &str, now: i64) -> Result<Option<CachedResponse>> {
+        let since = now - self.config.ttl_seconds as i64;
+        let candidates = self.store.recent(key, since, MAX_CANDIDATES).await?;
+        Ok(candidates.into_iter().find(|entry| {
+            hash_similarity(&entry.perceptual_hash, hash)
+                .is_ok_and(|similarity| similarity >= self.config.similarity_threshold)
+        }))
+    }
+
+    async fn remember(&self, key: &str, entry: CachedResponse) -> Result<()> {
+        let before = entry.created_at - self.config.ttl_seconds as i64;
+        self.store.insert(key, entry).await?;
+        self.store.prune(before).await
+    }
+}
+
+/// Model that describes frames under a configuration
+fn vision_model(config: &AiConfig) -> String {
+    config
+        .routing
+        .as_ref()
+        .and_then(|routing| routing.vision.as_ref())
+        .and_then(|route| route.model.clone())
+        .or_else(|| config.vision_model.clone())
+        .unwrap_or_else(|| config.model.clone())
+}
+
+#[async_trait]
+impl AiClient for CachedClient {
+    async fn summarize(&self, request: SummarizeRequest) -> Result<SummarizeResponse> {
+        self.inner.summarize(request).await
+    }
+
+    async fn describe_frame(&self, request: DescribeFrameRequest) -> Result<DescribeFrameResponse> {
+        let hash = match perceptual_hash(&request.image_data) {
+            Ok(hash) => hash,
+            Err(e) => {
+                debug!("Not caching frame description: {}", e);
+                return self.inner.describe_frame(request).await;
+            }
+        };
+        let key = self.cache_key(&request);
+        let now = chrono::Utc::now().timestamp();
+
+        match self.lookup(&key, &hash, now).await {
+            Ok(Some(cached)) => {
+                counter!("ai_cache_hits_total", "model" => self.model.clone()).increment(1);
+                debug!(stream_id = ?request.stream_id, "Reusing cached frame description");
+                return Ok(cached.response);
+            }
+            Ok(None) => {}
+            Err(e) => warn!("AI cache lookup failed: {}", e),
+        }
+        counter!("ai_cache_misses_total", "model" => self.model.clone()).increment(1);
+
+        let response = self.inner.describe_frame(request).await?;
+        // A reused description costs nothing, so it carries no usage
+        let entry = CachedResponse {
+            perceptual_hash: hash,
+            response: DescribeFrameResponse {
+                usage: None,
+                ..response.clone()
+            },
+            created_at: now,
+        };
+        if let Err(e) = self.remember(&key, entry).await {
+            warn!("Failed to cache AI response: {}", e);
+        }
+        Ok(response)
+    }
+
+    async fn classify_event(&self, request: ClassifyEventRequest) -> Result<ClassifyEventResponse> {
+        self.inner.classify_event(request).await
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        self.inner.health_check().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StubClient;
+    use bytes::Bytes;
+    use image::{DynamicImage, ImageOutputFormat, Rgb, RgbImage};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Counts how often the wrapped client is actually called
+    struct CountingClient {
+        calls: Arc<AtomicUsize>,
+        stub: StubClient,
+    }
+
+    #[async_trait]
+    impl AiClient for CountingClient {
+        async fn summarize(&self, request: SummarizeRequest) -> Result<SummarizeResponse> {
+            self.stub.summarize(request).await
+        }
+
+        async fn describe_frame(
+            &self,
+            request: DescribeFrameRequest,
+        ) -> Result<DescribeFrameResponse> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.stub.describe_frame(request).await
+        }
+
+        async fn classify_event(
+            &self,
+            request: ClassifyEventRequest,
+        ) -> Result<ClassifyEventResponse> {
+            self.stub.classify_event(request).await
+        }
+
+        async fn health_check(&self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    /// PNG of a horizontal gradient, or a flat band left of `split` beside a vertical one
+    fn frame(split: u32, brightness: u8) -> Bytes {
+        let image = RgbImage::from_fn(64, 64, |x, y| {
+            let value = if split == 0 {
+                (x * 4) as u8
+            } else if x < split {
+                brightness
+            } else {
+                ((y * 4) as u8).wrapping_add(brightness)
+            };
+            Rgb([value, value, value])
+        });
+        let mut png = Vec::new();
+        DynamicImage::ImageRgb8(image)
+            .write_to(&mut png, ImageOutputFormat::Png)
+            .unwrap();
+        Bytes::from(png)
+    }
+
+    fn request(image: Bytes, focus: &str) -> DescribeFrameRequest {
+        DescribeFrameRequest {
+            image_data: image,
+            image_format: "png".to_string(),
+            detail_level: None,
+            focus: Some(focus.to_string()),
+            stream_id: Some("parking".to_string()),
+            question: None,
+            output_schema: None,
+        }
+    }
+
+    #[test]
+    fn test_hash_similarity() {
+        let hash = perceptual_hash(&frame(0, 0)).unwrap();
+        assert_eq!(hash_similarity(&hash, &hash).unwrap(), 1.0);
+        let other = perceptual_hash(&frame(32, 200)).unwrap();
+        assert!(hash_similarity(&hash, &other).unwrap() < 0.95);
+        assert!(hash_similarity(&hash, "not a hash").is_err());
+        assert!(perceptual_hash(b"not an image").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_similar_frames_reuse_cached_description() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let inner = CountingClient {
+            calls: calls.clone(),
+            stub: StubClient::new(),
+        };
+        let store = Arc::new(MemoryCacheStore::new());
+        let client = CachedClient::new(
+            Box::new(inner),
+            &AiConfig::default(),
+            AiCacheConfig {
+                store: Some(SharedCacheStore(store.clone())),
+                ..AiCacheConfig::default()
+            },
+        );
+
+        let first = client
+            .describe_frame(request(frame(0, 0), "objects"))
+            .await
+            .unwrap();
+        let repeat = client
+            .describe_frame(request(frame(0, 0), "objects"))
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(repeat.description, first.description);
+
+        // A different scene or prompt goes to the model
+        client
+            .describe_frame(request(frame(32, 200), "objects"))
+            .await
+            .unwrap();
+        client
+            .describe_frame(request(frame(0, 0), "activity"))
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        // Undecodable frames bypass the cache
+        client
+            .describe_frame(request(Bytes::from_static(b"fake"), "objects"))
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_expired_entries_are_not_reused() {
+        let store = MemoryCacheStore::new();
+        let entry = |created_at| CachedResponse {
+            perceptual_hash: "hash".to_string(),
+            response: DescribeFrameResponse {
+                description: "Empty lot".to_string(),
+                objects_detected: Vec::new(),
+                confidence: None,
+                processing_time_ms: None,
+                structured: None,
+                usage: None,
+            },
+            created_at,
+        };
+        store.insert("lot", entry(100)).await.unwrap();
+        store.insert("lot", entry(200)).await.unwrap();
+        store.insert("porch", entry(200)).await.unwrap();
+
+        assert_eq!(store.recent("lot", 150, 10).await.unwrap().len(), 1);
+        store.prune(150).await.unwrap();
+        assert_eq!(store.recent("lot", 0, 10).await.unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,323 @@
+//! ABOUTME: Image and text embeddings from a CLIP model for semantic snapshot search
+//! ABOUTME: Runs the ONNX image and text encoders in-process so search works offline
+
+use async_trait::async_trait;
+use gl_core::Result;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "onnx")]
+use gl_core::onnx::{self, OnnxModel};
+#[cfg(feature = "onnx")]
+use gl_core::Error;
+#[cfg(feature = "onnx")]
+use std::sync::Arc;
+#[cfg(feature = "onnx")]
+use tokenizers::{PaddingParams, PaddingStrategy, Tokenizer, TruncationParams};
+
+/// How snapshots are embedded and indexed for search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmbeddingConfig {
+    pub enabled: bool,
+    /// Name stored with each vector; vectors from different models are never compared
+    pub model: String,
+    /// ONNX image encoder taking `[1, 3, size, size]` normalised pixels and returning the
+    /// projected image embedding as its first output
+    pub image_model_path: String,
+    /// ONNX text encoder taking `[1, max_tokens]` token ids, and optionally an attention
+    /// mask, and returning the projected text embedding as its first output
+    pub text_model_path: String,
+    /// `tokenizer.json` of the text encoder
+    pub tokenizer_path: String,
+    /// Side of the square the image encoder was trained at
+    pub image_size: u32,
+    /// Per-channel mean the image encoder expects pixels normalised with
+    pub image_mean: [f32; 3],
+    /// Per-channel standard deviation the image encoder expects pixels normalised with
+    pub image_std: [f32; 3],
+    /// Length token sequences are padded or truncated to
+    pub max_tokens: usize,
+    /// Token sequences are padded with
+    pub pad_token: String,
+    /// Cron schedule for embedding newly stored snapshots
+    pub schedule: String,
+    /// Most snapshots embedded per run
+    pub batch_size: u32,
+}
+
+impl Default for EmbeddingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            model: "clip-vit-b-32".to_string(),
+            image_model_path: "models/clip-vit-b-32/image.onnx".to_string(),
+            text_model_path: "models/clip-vit-b-32/text.onnx".to_string(),
+            tokenizer_path: "models/clip-vit-b-32/tokenizer.json".to_string(),
+            image_size: 224,
+            image_mean: [0.481_454_66, 0.457_827_5, 0.408_210_73],
+            image_std: [0.268_629_54, 0.261_302_6, 0.275_777_1],
+            max_tokens: 77,
+            pad_token: "<|endoftext|>".to_string(),
+            schedule: "0 * * * * *".to_string(),
+            batch_size: 32,
+        }
+    }
+}
+
+/// Maps images and text into a shared vector space
+#[async_trait]
+pub trait Embedder: Send + Sync {
+    /// Model the vectors come from
+    fn model(&self) -> &str;
+
+    /// Embed a JPEG or PNG image
+    async fn embed_image(&self, image: &[u8]) -> Result<Vec<f32>>;
+
+    /// Embed a text query such as "red truck at the gate"
+    async fn embed_text(&self, text: &str) -> Result<Vec<f32>>;
+}
+
+/// Create an embedder for a configuration
+pub fn create_embedder(config: &EmbeddingConfig) -> Result<Box<dyn Embedder>> {
+    #[cfg(feature = "onnx")]
+    {
+        Ok(Box::new(ClipEmbedder::load(config)?))
+    }
+    #[cfg(not(feature = "onnx"))]
+    {
+        Err(gl_core::Error::Config(format!(
+            "Embedding model {} needs gl_ai built with the onnx feature",
+            config.model
+        )))
+    }
+}
+
+/// CLIP image and text encoders exported to ONNX, run on the CPU
+#[cfg(feature = "onnx")]
+pub struct ClipEmbedder {
+    model: String,
+    encoders: Arc<ClipEncoders>,
+}
+
+#[cfg(feature = "onnx")]
+struct ClipEncoders {
+    image: OnnxModel,
+    text: OnnxModel,
+    tokenizer: Tokenizer,
+    image_size: u32,
+    image_mean: [f32; 3],
+    image_std: [f32; 3],
+}
+
+#[cfg(feature = "onnx")]
+impl ClipEmbedder {
+    /// Load the configured encoders and tokenizer
+    pub fn load(config: &EmbeddingConfig) -> Result<Self> {
+        use tract_onnx::prelude::*;
+
+        if config.image_size == 0 || config.max_tokens == 0 {
+            return Err(Error::Config(
+                "Embedding image size and token length must not be zero".to_string(),
+            ));
+        }
+        let size = config.image_size as usize;
+        let image = onnx::load_model(&config.image_model_path, &[f32::fact([1, 3, size, size])])?;
+        let tokens = i64::fact([1, config.max_tokens]);
+        let text = onnx::load_model(&config.text_model_path, &[tokens.clone(), tokens])?;
+
+        let mut tokenizer = Tokenizer::from_file(&config.tokenizer_path).map_err(|e| {
+            Error::Config(format!(
+                "Failed to load tokenizer {}: {}",
+                config.tokenizer_path, e
+            ))
+        })?;
+        let pad_id = tokenizer.token_to_id(&config.pad_token).ok_or_else(|| {
+            Error::Config(format!(
+                "Tokenizer {} has no pad token {}",
+                config.tokenizer_path, config.pad_token
+            ))
+        })?;
+        tokenizer
+            .with_truncation(Some(TruncationParams {
+                max_length: config.max_tokens,
+                ..Default::default()
+            }))
+            .map_err(|e| Error::Config(format!("Invalid tokenizer truncation: {}", e)))?;
+        tokenizer.with_padding(Some(PaddingParams {
+            strategy: PaddingStrategy::Fixed(config.max_tokens),
+            pad_id,
+            pad_token: config.pad_token.clone(),
+            ..Default::default()
+        }));
+
+        Ok(Self {
+            model: config.model.clone(),
+            encoders: Arc::new(ClipEncoders {
+                image,
+                text,
+                tokenizer,
+                image_size: config.image_size,
+                image_mean: config.image_mean,
+                image_std: config.image_std,
+            }),
+        })
+    }
+
+    /// Run an encoder off the async runtime
+    async fn encode<F>(&self, encode: F) -> Result<Vec<f32>>
+    where
+        F: FnOnce(&ClipEncoders) -> Result<Vec<f32>> + Send + 'static,
+    {
+        let encoders = self.encoders.clone();
+        let vector = tokio::task::spawn_blocking(move || encode(&encoders))
+            .await
+            .map_err(|e| Error::External(format!("Embedding task failed: {}", e)))??;
+        if vector.is_empty() {
+            return Err(Error::External(
+                "CLIP model returned an empty embedding".to_string(),
+            ));
+        }
+        Ok(vector)
+    }
+}
+
+#[cfg(feature = "onnx")]
+impl ClipEncoders {
+    /// Resize the shorter side to the model size, centre crop and normalise
+    fn embed_image(&self, image: &[u8]) -> Result<Vec<f32>> {
+        use tract_onnx::prelude::*;
+
+        let image = image::load_from_memory(image)
+            .map_err(|e| Error::Validation(format!("Failed to decode image: {}", e)))?
+            .resize_to_fill(
+                self.image_size,
+                self.image_size,
+                image::imageops::FilterType::CatmullRom,
+            )
+            .to_rgb8();
+        let size = self.image_size as usize;
+        let pixels = tract_ndarray::Array4::from_shape_fn((1, 3, size, size), |(_, c, y, x)| {
+            let value = image.get_pixel(x as u32, y as u32).0[c] as f32 / 255.0;
+            (value - self.image_mean[c]) / self.image_std[c]
+        });
+        onnx::run(&self.image, vec![pixels.into()]).map(|(_, vector)| vector)
+    }
+
+    fn embed_text(&self, text: &str) -> Result<Vec<f32>> {
+        use tract_onnx::prelude::*;
+
+        let encoding = self
+            .tokenizer
+            .encode(text, true)
+            .map_err(|e| Error::Validation(format!("Failed to tokenize query: {}", e)))?;
+        let row = |values: &[u32]| {
+            tract_ndarray::Array2::from_shape_vec(
+                (1, values.len()),
+                values.iter().map(|&v| v as i64).collect(),
+            )
+            .map(Tensor::from)
+            .map_err(|e| Error::External(format!("Invalid token sequence: {}", e)))
+        };
+        let mut inputs = vec![row(encoding.get_ids())?];
+        if self.text.model().inputs.len() > 1 {
+            inputs.push(row(encoding.get_attention_mask())?);
+        }
+        onnx::run(&self.text, inputs).map(|(_, vector)| vector)
+    }
+}
+
+#[cfg(feature = "onnx")]
+#[async_trait]
+impl Embedder for ClipEmbedder {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn embed_image(&self, image: &[u8]) -> Result<Vec<f32>> {
+        let image = image.to_vec();
+        self.encode(move |encoders| encoders.embed_image(&image))
+            .await
+    }
+
+    async fn embed_text(&self, text: &str) -> Result<Vec<f32>> {
+        let text = text.to_string();
+        self.encode(move |encoders| encoders.embed_text(&text))
+            .await
+    }
+}
+
+#[cfg(all(test, feature = "onnx"))]
+mod tests {
+    use super::*;
+    use image::{DynamicImage, ImageOutputFormat, Rgb, RgbImage};
+
+    fn solid(colour: [u8; 3]) -> Vec<u8> {
+        let mut png = Vec::new();
+        DynamicImage::ImageRgb8(RgbImage::from_pixel(40, 30, Rgb(colour)))
+            .write_to(&mut png, ImageOutputFormat::Png)
+            .unwrap();
+        png
+    }
+
+    fn close(a: &[f32], b: &[f32]) -> bool {
+        a.len() == b.len() && a.iter().zip(b).all(|(a, b)| (a - b).abs() < 0.01)
+    }
+
+    #[tokio::test]
+    async fn test_clip_embedder_runs_onnx_encoders() {
+        let dir = std::env::temp_dir().join(format!("glimpser_clip_{}", gl_core::Id::new()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let clip = test_support::onnx::colour_clip(&dir);
+        let config = EmbeddingConfig {
+            model: "colour-clip".to_string(),
+            image_model_path: clip.image_model.to_string_lossy().to_string(),
+            text_model_path: clip.text_model.to_string_lossy().to_string(),
+            tokenizer_path: clip.tokenizer.to_string_lossy().to_string(),
+            image_size: 16,
+            ..Default::default()
+        };
+
+        let embedder = create_embedder(&config).unwrap();
+        assert_eq!(embedder.model(), "colour-clip");
+        let red = embedder.embed_image(&solid([255, 0, 0])).await.unwrap();
+        assert!(close(&red, &[1.0, 0.0, 0.0]), "{:?}", red);
+        assert_eq!(
+            embedder.embed_text("Red truck at the gate").await.unwrap(),
+            vec![1.0, 0.0, 0.0]
+        );
+        // Truncated to the token length
+        let short = create_embedder(&EmbeddingConfig {
+            max_tokens: 2,
+            ..config.clone()
+        })
+        .unwrap();
+        assert_eq!(
+            short.embed_text("red truck blue").await.unwrap(),
+            vec![1.0, 0.0, 0.0]
+        );
+        assert!(matches!(
+            embedder.embed_image(b"not an image").await,
+            Err(Error::Validation(_))
+        ));
+
+        // Models and tokenizer must all load
+        for broken in [
+            EmbeddingConfig {
+                image_model_path: dir.join("missing.onnx").to_string_lossy().to_string(),
+                ..config.clone()
+            },
+            EmbeddingConfig {
+                tokenizer_path: clip.image_model.to_string_lossy().to_string(),
+                ..config.clone()
+            },
+            EmbeddingConfig {
+                pad_token: "<pad>".to_string(),
+                ..config.clone()
+            },
+        ] {
+            assert!(matches!(create_embedder(&broken), Err(Error::Config(_))));
+        }
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,82 @@
+//! ABOUTME: Shared HTTP plumbing for provider clients that talk JSON over reqwest
+//! ABOUTME: Sends requests with exponential-backoff retries and decodes typed responses
+
+use gl_core::{Error, Result};
+use reqwest::RequestBuilder;
+use serde::Deserialize;
+use std::time::Duration;
+use tokio::time::sleep;
+use tracing::{debug, error, warn};
+
+/// Execute a request with retry logic
+pub(crate) async fn execute_with_retry<T>(
+    request_builder: RequestBuilder,
+    max_retries: u32,
+    service: &str,
+) -> Result<T>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    let mut last_error = None;
+
+    for attempt in 0..=max_retries {
+        if attempt > 0 {
+            let delay = Duration::from_millis(100 * (1 << attempt.min(5))); // Exponential backoff
+            debug!("Retrying request in {:?} (attempt {})", delay, attempt + 1);
+            sleep(delay).await;
+        }
+
+        let request = match request_builder.try_clone() {
+            Some(req) => req,
+            None => {
+                error!("Failed to clone request for retry");
+                return Err(Error::Config(
+                    "Unable to retry request - body not cloneable".to_string(),
+                ));
+            }
+        };
+
+        match execute_request::<T>(request, service).await {
+            Ok(response) => return Ok(response),
+            Err(e) => {
+                warn!("Request attempt {} failed: {}", attempt + 1, e);
+                last_error = Some(e);
+            }
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| Error::Io(std::io::Error::other("All retry attempts failed"))))
+}
+
+/// Execute a single HTTP request
+pub(crate) async fn execute_request<T>(request: RequestBuilder, service: &str) -> Result<T>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    let response = request
+        .send()
+        .await
+        .map_err(|e| Error::Io(std::io::Error::other(format!("HTTP request failed: {}", e))))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let error_text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(Error::Database(format!(
+            "{} API error ({}): {}",
+            service, status, error_text
+        )));
+    }
+
+    let response_text = response.text().await.map_err(|e| {
+        Error::Io(std::io::Error::other(format!(
+            "Failed to read response: {}",
+            e
+        )))
+    })?;
+
+    serde_json::from_str::<T>(&response_text)
+        .map_err(|e| Error::Database(format!("Failed to parse {} response: {}", service, e)))
+}
@@ -1,4 +1,4 @@
-//! ABOUTME: AI client abstraction with OpenAI and stub implementations
+//! ABOUTME: AI client abstraction with OpenAI, local model server, router and stub implementations
 //! ABOUTME: Provides AI-powered analysis and content generation
 
 use async_trait::async_trait;
@@ -7,12 +7,39 @@ use gl_core::Result;
 use serde::{Deserialize, Serialize};
 use tracing::{debug, info};
 
+pub mod budget;
+pub mod cache;
+pub mod embedding;
+#[cfg(any(feature = "ai_online", feature = "ai_local"))]
+mod http;
+#[cfg(feature = "ai_local")]
+pub mod llama_cpp;
+#[cfg(feature = "ai_local")]
+pub mod ollama;
 #[cfg(feature = "ai_online")]
 pub mod openai;
+#[cfg(any(feature = "ai_online", feature = "ai_local"))]
+mod prompts;
+pub mod router;
+pub mod structured;
 pub mod stub;
 
+pub use budget::{AiUsage, MemoryUsageStore, SharedUsageStore, UsageStore};
+pub use cache::{
+    AiCacheConfig, CachedClient, CachedResponse, MemoryCacheStore, ResponseCacheStore,
+    SharedCacheStore,
+};
+#[cfg(feature = "onnx")]
+pub use embedding::ClipEmbedder;
+pub use embedding::{create_embedder, Embedder, EmbeddingConfig};
+
+#[cfg(feature = "ai_local")]
+pub use llama_cpp::LlamaCppClient;
+#[cfg(feature = "ai_local")]
+pub use ollama::OllamaClient;
 #[cfg(feature = "ai_online")]
 pub use openai::OpenAiClient;
+pub use router::{AiRoute, AiRouter, AiRoutingConfig};
 pub use stub::StubClient;
 
 /// Classification result for events
@@ -63,6 +90,30 @@ pub struct SummarizeRequest {
     pub text: String,
     pub max_length: Option<usize>,
     pub style: Option<String>, // "brief", "detailed", "technical"
+    /// Stream the request is made for, which budgets are charged to
+    #[serde(default)]
+    pub stream_id: Option<String>,
+}
+
+/// Tokens a provider reports having spent on a request
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+}
+
+impl TokenUsage {
+    pub fn total(&self) -> u64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+
+    /// Combined usage of several requests made for one answer, if any reported usage
+    pub fn sum(usages: impl IntoIterator<Item = Option<TokenUsage>>) -> Option<TokenUsage> {
+        usages.into_iter().flatten().reduce(|a, b| TokenUsage {
+            prompt_tokens: a.prompt_tokens + b.prompt_tokens,
+            completion_tokens: a.completion_tokens + b.completion_tokens,
+        })
+    }
 }
 
 /// Response from text summarization
@@ -72,6 +123,9 @@ pub struct SummarizeResponse {
     pub original_length: usize,
     pub summary_length: usize,
     pub confidence: Option<f64>,
+    /// Tokens the provider reported, when it reports them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<TokenUsage>,
 }
 
 /// Request for frame description
@@ -82,6 +136,16 @@ pub struct DescribeFrameRequest {
     pub image_format: String,         // "jpeg", "png"
     pub detail_level: Option<String>, // "low", "high", "auto"
     pub focus: Option<String>,        // "objects", "activity", "scene"
+    /// Stream the frame came from, which budgets are charged to
+    #[serde(default)]
+    pub stream_id: Option<String>,
+    /// Yes/no question to answer about the frame instead of describing it; the description
+    /// then holds a JSON object with `answer`, `confidence` and `reason`
+    #[serde(default)]
+    pub question: Option<String>,
+    /// JSON schema the analysis must follow; the validated fields come back in `structured`
+    #[serde(default)]
+    pub output_schema: Option<serde_json::Value>,
 }
 
 /// Response from frame description
@@ -91,6 +155,12 @@ pub struct DescribeFrameResponse {
     pub objects_detected: Vec<String>,
     pub confidence: Option<f64>,
     pub processing_time_ms: Option<u64>,
+    /// Fields matching the request's output schema
+    #[serde(default)]
+    pub structured: Option<serde_json::Value>,
+    /// Tokens the provider reported, when it reports them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<TokenUsage>,
 }
 
 /// Request for event classification
@@ -108,34 +178,72 @@ pub struct ClassifyEventResponse {
     pub confidence: f64,
     pub reasoning: String,
     pub suggested_actions: Vec<String>,
+    /// Tokens the provider reported, when it reports them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<TokenUsage>,
+}
+
+/// Model server an AI client talks to
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum AiProvider {
+    /// OpenAI or a hosted API compatible with it
+    #[default]
+    #[serde(rename = "openai")]
+    OpenAi,
+    /// Local Ollama server
+    #[serde(rename = "ollama")]
+    Ollama,
+    /// Local llama.cpp server
+    #[serde(rename = "llama_cpp", alias = "llamacpp")]
+    LlamaCpp,
 }
 
 /// Configuration for AI clients
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AiConfig {
+    /// Which model server to call when `use_online` is set
+    #[serde(default)]
+    pub provider: AiProvider,
     /// API key for online services
     pub api_key: Option<String>,
-    /// Base URL for API (defaults to OpenAI)
+    /// Base URL for API (defaults to the provider's standard address)
     pub base_url: Option<String>,
     /// Request timeout in seconds
     pub timeout_seconds: u64,
     /// Maximum retries for failed requests
     pub max_retries: u32,
-    /// Model name to use (e.g., "gpt-4", "gpt-3.5-turbo")
+    /// Model name to use (e.g., "gpt-4", "gpt-3.5-turbo", "llama3.2")
     pub model: String,
-    /// Whether to use online AI services
+    /// Model used to describe frames; must accept images (e.g., "llava")
+    #[serde(default)]
+    pub vision_model: Option<String>,
+    /// Let a local server download missing models when its health is checked
+    #[serde(default)]
+    pub pull_models: bool,
+    /// Whether to call the provider rather than the offline stub
     pub use_online: bool,
+    /// Per-task models, fallback and budgets; calls go straight to the provider when unset
+    #[serde(default)]
+    pub routing: Option<AiRoutingConfig>,
+    /// Reuse frame descriptions for visually similar frames; every frame calls the model when unset
+    #[serde(default)]
+    pub cache: Option<AiCacheConfig>,
 }
 
 impl Default for AiConfig {
     fn default() -> Self {
         Self {
+            provider: AiProvider::OpenAi,
             api_key: None,
             base_url: None,
             timeout_seconds: 30,
             max_retries: 3,
             model: "gpt-3.5-turbo".to_string(),
+            vision_model: None,
+            pull_models: false,
             use_online: false, // Default to stub for safety
+            routing: None,
+            cache: None,
         }
     }
 }
@@ -158,20 +266,64 @@ pub trait AiClient: Send + Sync {
 
 /// Create an AI client based on configuration
 pub fn create_client(config: AiConfig) -> Box<dyn AiClient> {
-    if config.use_online {
-        #[cfg(feature = "ai_online")]
-        {
-            info!("Creating OpenAI client with model: {}", config.model);
-            Box::new(OpenAiClient::new(config))
+    if !config.use_online {
+        debug!("Creating stub AI client");
+        return Box::new(StubClient::new());
+    }
+
+    match config.cache.clone() {
+        Some(cache) if cache.enabled => {
+            debug!(
+                "Caching frame descriptions above {} similarity",
+                cache.similarity_threshold
+            );
+            let client = create_online_client(config.clone());
+            Box::new(CachedClient::new(client, &config, cache))
         }
-        #[cfg(not(feature = "ai_online"))]
-        {
-            warn!("Online AI requested but ai_online feature not enabled, falling back to stub");
-            Box::new(StubClient::new())
+        _ => create_online_client(config),
+    }
+}
+
+/// Client for the configured provider, or the router when routing is set
+fn create_online_client(config: AiConfig) -> Box<dyn AiClient> {
+    if config.routing.is_some() {
+        return Box::new(AiRouter::new(config));
+    }
+
+    match config.provider {
+        AiProvider::OpenAi => {
+            #[cfg(feature = "ai_online")]
+            {
+                info!("Creating OpenAI client with model: {}", config.model);
+                Box::new(OpenAiClient::new(config))
+            }
+            #[cfg(not(feature = "ai_online"))]
+            {
+                tracing::warn!(
+                    "Online AI requested but ai_online feature not enabled, falling back to stub"
+                );
+                Box::new(StubClient::new())
+            }
+        }
+        AiProvider::Ollama | AiProvider::LlamaCpp => {
+            #[cfg(feature = "ai_local")]
+            {
+                if config.provider == AiProvider::Ollama {
+                    info!("Creating Ollama client with model: {}", config.model);
+                    Box::new(OllamaClient::new(config))
+                } else {
+                    info!("Creating llama.cpp client with model: {}", config.model);
+                    Box::new(LlamaCppClient::new(config))
+                }
+            }
+            #[cfg(not(feature = "ai_local"))]
+            {
+                tracing::warn!(
+                    "Local AI provider requested but ai_local feature not enabled, falling back to stub"
+                );
+                Box::new(StubClient::new())
+            }
         }
-    } else {
-        debug!("Creating stub AI client");
-        Box::new(StubClient::new())
     }
 }
 
@@ -230,6 +382,7 @@ mod tests {
             text: "This is a long text that needs to be summarized for better readability and understanding.".to_string(),
             max_length: Some(50),
             style: Some("brief".to_string()),
+            stream_id: None,
         };
 
         assert!(request.text.len() > 50);
@@ -244,6 +397,9 @@ mod tests {
             image_format: "jpeg".to_string(),
             detail_level: Some("high".to_string()),
             focus: Some("objects".to_string()),
+            stream_id: None,
+            question: None,
+            output_schema: None,
         };
 
         assert_eq!(request.image_format, "jpeg");
@@ -261,6 +417,31 @@ mod tests {
         assert!(health_result.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_create_client_with_routing_falls_back_to_stub() {
+        let config = AiConfig {
+            use_online: true,
+            // Nothing listens here, so any real provider fails straight away
+            base_url: Some("http://127.0.0.1:9".to_string()),
+            max_retries: 0,
+            routing: Some(AiRoutingConfig::default()),
+            ..Default::default()
+        };
+
+        let client = create_client(config);
+        let response = client
+            .summarize(SummarizeRequest {
+                text: "Motion at the porch, then a parcel was left by the door.".to_string(),
+                max_length: None,
+                style: None,
+                stream_id: Some("porch".to_string()),
+            })
+            .await
+            .unwrap();
+        assert!(!response.summary.is_empty());
+        assert!(client.health_check().await.is_ok());
+    }
+
     #[tokio::test]
     async fn test_create_client_online_behavior() {
         let config = AiConfig {
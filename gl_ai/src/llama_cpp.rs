@@ -0,0 +1,522 @@
+//! ABOUTME: llama.cpp server client for running models locally via its OpenAI-style chat API
+//! ABOUTME: Sends frames as inline images and checks the server has finished loading its model
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use gl_core::{Error, Result};
+use reqwest::{Client, RequestBuilder};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+use crate::{
+    http, prompts, structured, AiClient, AiConfig, ClassifyEventRequest, ClassifyEventResponse,
+    DescribeFrameRequest, DescribeFrameResponse, SummarizeRequest, SummarizeResponse, TokenUsage,
+};
+
+/// Address `llama-server` listens on out of the box
+const DEFAULT_BASE_URL: &str = "http://localhost:8080";
+
+/// llama.cpp server client
+pub struct LlamaCppClient {
+    client: Client,
+    config: AiConfig,
+    base_url: String,
+}
+
+/// Chat completion request
+#[derive(Debug, Serialize)]
+struct ChatRequest {
+    model: String,
+    messages: Vec<ChatMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<u32>,
+    temperature: f32,
+    /// Grammar-constrained output following a JSON schema
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<serde_json::Value>,
+}
+
+/// Chat message made of text and image parts
+#[derive(Debug, Serialize)]
+struct ChatMessage {
+    role: String,
+    content: Vec<ContentPart>,
+}
+
+/// Part of a chat message
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ContentPart {
+    Text { text: String },
+    ImageUrl { image_url: ImageUrl },
+}
+
+/// Inline image as a data URL
+#[derive(Debug, Serialize)]
+struct ImageUrl {
+    url: String,
+}
+
+/// Chat completion response
+#[derive(Debug, Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+    #[serde(default)]
+    usage: Option<ChatUsage>,
+}
+
+/// Tokens the server spent on a completion
+#[derive(Debug, Deserialize)]
+struct ChatUsage {
+    prompt_tokens: u64,
+    completion_tokens: u64,
+}
+
+/// Choice in a chat completion response
+#[derive(Debug, Deserialize)]
+struct ChatChoice {
+    message: ChatResponseMessage,
+}
+
+/// Assistant message in a choice
+#[derive(Debug, Deserialize)]
+struct ChatResponseMessage {
+    content: String,
+}
+
+/// Models the server has loaded
+#[derive(Debug, Deserialize)]
+struct ModelsResponse {
+    data: Vec<LoadedModel>,
+}
+
+/// Loaded model
+#[derive(Debug, Deserialize)]
+struct LoadedModel {
+    id: String,
+}
+
+impl LlamaCppClient {
+    pub fn new(config: AiConfig) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.timeout_seconds))
+            .build()
+            .expect("Failed to create HTTP client");
+
+        let base_url = config
+            .base_url
+            .clone()
+            .unwrap_or_else(|| DEFAULT_BASE_URL.to_string())
+            .trim_end_matches('/')
+            .to_string();
+
+        debug!("Created llama.cpp client with base URL: {}", base_url);
+
+        Self {
+            client,
+            config,
+            base_url,
+        }
+    }
+
+    fn create_request(&self, endpoint: &str) -> RequestBuilder {
+        let mut builder = self.client.post(format!(
+            "{}/{}",
+            self.base_url,
+            endpoint.trim_start_matches('/')
+        ));
+        // Only needed when the server was started with --api-key
+        if let Some(api_key) = &self.config.api_key {
+            builder = builder.bearer_auth(api_key);
+        }
+        builder
+    }
+
+    /// Identifiers of the models the server has loaded
+    pub async fn loaded_models(&self) -> Result<Vec<String>> {
+        let mut request = self.client.get(format!("{}/v1/models", self.base_url));
+        if let Some(api_key) = &self.config.api_key {
+            request = request.bearer_auth(api_key);
+        }
+        let models: ModelsResponse = http::execute_request(request, "llama.cpp").await?;
+        Ok(models.data.into_iter().map(|model| model.id).collect())
+    }
+
+    /// Run a chat completion and return the assistant's reply with the tokens it spent
+    async fn chat(
+        &self,
+        model: &str,
+        content: Vec<ContentPart>,
+        max_tokens: Option<u32>,
+        temperature: f32,
+        response_format: Option<serde_json::Value>,
+    ) -> Result<(String, Option<TokenUsage>)> {
+        let request = self
+            .create_request("/v1/chat/completions")
+            .json(&ChatRequest {
+                model: model.to_string(),
+                messages: vec![ChatMessage {
+                    role: "user".to_string(),
+                    content,
+                }],
+                max_tokens,
+                temperature,
+                response_format,
+            });
+        let response: ChatResponse =
+            http::execute_with_retry(request, self.config.max_retries, "llama.cpp").await?;
+        let usage = response.usage.map(|usage| TokenUsage {
+            prompt_tokens: usage.prompt_tokens,
+            completion_tokens: usage.completion_tokens,
+        });
+        let reply = response
+            .choices
+            .into_iter()
+            .next()
+            .map(|choice| choice.message.content.trim().to_string())
+            .unwrap_or_default();
+        Ok((reply, usage))
+    }
+
+    fn image_to_data_url(image_data: &Bytes, format: &str) -> Result<String> {
+        let mime_type = prompts::image_mime_type(format)?;
+        use base64::Engine;
+        let base64_data = base64::engine::general_purpose::STANDARD.encode(image_data);
+        Ok(format!("data:{};base64,{}", mime_type, base64_data))
+    }
+}
+
+#[async_trait]
+impl AiClient for LlamaCppClient {
+    async fn summarize(&self, request: SummarizeRequest) -> Result<SummarizeResponse> {
+        debug!(
+            "llama.cpp client summarizing {} characters",
+            request.text.len()
+        );
+
+        let max_tokens = request.max_length.map(|len| (len as f32 * 1.3) as u32);
+        let (summary, usage) = self
+            .chat(
+                &self.config.model,
+                vec![ContentPart::Text {
+                    text: prompts::summarize_prompt(&request),
+                }],
+                max_tokens,
+                0.3,
+                None,
+            )
+            .await?;
+
+        let confidence = if summary.len() < request.text.len() / 2 {
+            0.9
+        } else {
+            0.8
+        };
+
+        Ok(SummarizeResponse {
+            original_length: request.text.len(),
+            summary_length: summary.len(),
+            summary,
+            confidence: Some(confidence),
+            usage,
+        })
+    }
+
+    async fn describe_frame(&self, request: DescribeFrameRequest) -> Result<DescribeFrameResponse> {
+        debug!(
+            "llama.cpp client describing {} byte image",
+            request.image_data.len()
+        );
+
+        let data_url = Self::image_to_data_url(&request.image_data, &request.image_format)?;
+        let model = self
+            .config
+            .vision_model
+            .as_deref()
+            .unwrap_or(&self.config.model);
+        let prompt = prompts::describe_prompt(&request);
+        let ask = |prompt: String, response_format: Option<serde_json::Value>| {
+            self.chat(
+                model,
+                vec![
+                    ContentPart::Text { text: prompt },
+                    ContentPart::ImageUrl {
+                        image_url: ImageUrl {
+                            url: data_url.clone(),
+                        },
+                    },
+                ],
+                Some(500),
+                0.2,
+                response_format,
+            )
+        };
+
+        let start_time = std::time::Instant::now();
+        let (description, structured, usage) = match &request.output_schema {
+            Some(schema) => {
+                // Every attempt at matching the schema is billed
+                let spent = Mutex::new(Vec::new());
+                let response_format = structured::response_format(schema);
+                let (reply, value) = structured::request_matching(&prompt, schema, |prompt| {
+                    let chat = ask(prompt, Some(response_format.clone()));
+                    let spent = &spent;
+                    async move {
+                        let (reply, usage) = chat.await?;
+                        spent.lock().unwrap().push(usage);
+                        Ok(reply)
+                    }
+                })
+                .await?;
+                let usage = TokenUsage::sum(spent.into_inner().unwrap());
+                (reply, Some(value), usage)
+            }
+            None => {
+                let (reply, usage) = ask(prompt, None).await?;
+                (reply, None, usage)
+            }
+        };
+        let processing_time = start_time.elapsed().as_millis() as u64;
+
+        // Structured replies carry their findings in the schema's fields
+        let objects_detected = if structured.is_some() {
+            Vec::new()
+        } else {
+            prompts::detected_objects(&description)
+        };
+
+        Ok(DescribeFrameResponse {
+            description,
+            objects_detected,
+            confidence: Some(0.8),
+            processing_time_ms: Some(processing_time),
+            structured,
+            usage,
+        })
+    }
+
+    async fn classify_event(&self, request: ClassifyEventRequest) -> Result<ClassifyEventResponse> {
+        debug!(
+            "llama.cpp client classifying event: {}",
+            request.event_data.event_type
+        );
+
+        let (response_text, usage) = self
+            .chat(
+                &self.config.model,
+                vec![ContentPart::Text {
+                    text: prompts::classify_prompt(&request),
+                }],
+                Some(300),
+                0.1,
+                None,
+            )
+            .await?;
+        let (classification, confidence, suggested_actions) =
+            prompts::parse_classification(&response_text);
+
+        Ok(ClassifyEventResponse {
+            classification,
+            confidence,
+            reasoning: response_text,
+            suggested_actions,
+            usage,
+        })
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        debug!("llama.cpp client health check");
+
+        // Answers 503 until the model has finished loading
+        let response = self
+            .client
+            .get(format!("{}/health", self.base_url))
+            .send()
+            .await
+            .map_err(|e| Error::Io(std::io::Error::other(format!("HTTP request failed: {}", e))))?;
+        if !response.status().is_success() {
+            return Err(Error::Config(format!(
+                "llama.cpp server is not ready ({})",
+                response.status()
+            )));
+        }
+
+        let loaded = self.loaded_models().await?;
+        if loaded.is_empty() {
+            return Err(Error::Config(
+                "llama.cpp server has no model loaded".to_string(),
+            ));
+        }
+        if self.config.pull_models {
+            debug!("llama.cpp serves the model it was started with and cannot pull others");
+        }
+        // The server answers with whatever it loaded, whatever name is requested
+        if !loaded.iter().any(|id| id.contains(&self.config.model)) {
+            warn!(
+                configured = %self.config.model,
+                loaded = ?loaded,
+                "llama.cpp server is running a different model than configured"
+            );
+        }
+
+        debug!("llama.cpp client health check passed");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AiProvider;
+    use serde_json::json;
+    use wiremock::matchers::{body_partial_json, body_string_contains, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn config(server: &MockServer) -> AiConfig {
+        AiConfig {
+            provider: AiProvider::LlamaCpp,
+            base_url: Some(server.uri()),
+            model: "qwen2.5-vl-7b".to_string(),
+            max_retries: 0,
+            use_online: true,
+            ..AiConfig::default()
+        }
+    }
+
+    fn reply(content: &str) -> ResponseTemplate {
+        ResponseTemplate::new(200).set_body_json(json!({
+            "choices": [{"index": 0, "message": {"role": "assistant", "content": content}}]
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_describe_frame_sends_inline_image() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .and(body_partial_json(json!({
+                "model": "qwen2.5-vl-7b",
+                "messages": [{"content": [
+                    {"type": "text"},
+                    {"type": "image_url", "image_url": {"url": "data:image/png;base64,ZmFrZV9wbmc="}}
+                ]}]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "choices": [{"index": 0, "message": {
+                    "role": "assistant",
+                    "content": "An empty driveway next to a building."
+                }}],
+                "usage": {"prompt_tokens": 790, "completion_tokens": 9, "total_tokens": 799}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = LlamaCppClient::new(config(&server));
+        let response = client
+            .describe_frame(DescribeFrameRequest {
+                image_data: Bytes::from_static(b"fake_png"),
+                image_format: "png".to_string(),
+                detail_level: Some("low".to_string()),
+                focus: None,
+                stream_id: None,
+                question: None,
+                output_schema: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            response.description,
+            "An empty driveway next to a building."
+        );
+        assert_eq!(response.objects_detected, vec!["building"]);
+        assert_eq!(response.usage.map(|usage| usage.total()), Some(799));
+    }
+
+    #[tokio::test]
+    async fn test_describe_frame_asks_question() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .and(body_string_contains("Is the garage door open?"))
+            .and(body_string_contains("unknown"))
+            .respond_with(reply(
+                r#"{"answer": "yes", "confidence": 0.9, "reason": "The door is raised."}"#,
+            ))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = LlamaCppClient::new(config(&server));
+        let response = client
+            .describe_frame(DescribeFrameRequest {
+                image_data: Bytes::from_static(b"fake_jpeg"),
+                image_format: "jpeg".to_string(),
+                detail_level: None,
+                focus: None,
+                stream_id: None,
+                question: Some("Is the garage door open?".to_string()),
+                output_schema: None,
+            })
+            .await
+            .unwrap();
+        assert!(response.description.contains(r#""answer": "yes""#));
+    }
+
+    #[tokio::test]
+    async fn test_summarize_uses_chat_completions() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .respond_with(reply("Parcel delivered."))
+            .mount(&server)
+            .await;
+
+        let client = crate::create_client(config(&server));
+        let summary = client
+            .summarize(SummarizeRequest {
+                text: "The courier arrived at noon and left a parcel by the front door."
+                    .to_string(),
+                max_length: None,
+                style: Some("brief".to_string()),
+                stream_id: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(summary.summary, "Parcel delivered.");
+    }
+
+    #[tokio::test]
+    async fn test_health_check_waits_for_model_to_load() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/health"))
+            .respond_with(
+                ResponseTemplate::new(503)
+                    .set_body_json(json!({"error": {"message": "Loading model"}})),
+            )
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/health"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"status": "ok"})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/models"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "object": "list",
+                "data": [{"id": "models/qwen2.5-vl-7b-q4_k_m.gguf", "object": "model"}]
+            })))
+            .mount(&server)
+            .await;
+
+        let client = LlamaCppClient::new(config(&server));
+        let err = client.health_check().await.unwrap_err();
+        assert!(err.to_string().contains("not ready"), "{}", err);
+        client.health_check().await.unwrap();
+    }
+}
@@ -0,0 +1,610 @@
+//! ABOUTME: Ollama client for running language and vision models on a local server
+//! ABOUTME: Uses /api/generate and /api/chat, and checks or pulls models before use
+
+use async_trait::async_trait;
+use gl_core::{Error, Result};
+use reqwest::{Client, RequestBuilder};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::{debug, info};
+
+use crate::{
+    http, prompts, structured, AiClient, AiConfig, ClassifyEventRequest, ClassifyEventResponse,
+    DescribeFrameRequest, DescribeFrameResponse, SummarizeRequest, SummarizeResponse, TokenUsage,
+};
+
+/// Address Ollama listens on out of the box
+const DEFAULT_BASE_URL: &str = "http://localhost:11434";
+
+/// Downloading a model can take far longer than a normal request
+const PULL_TIMEOUT: Duration = Duration::from_secs(3600);
+
+/// Ollama API client
+pub struct OllamaClient {
+    client: Client,
+    config: AiConfig,
+    base_url: String,
+}
+
+/// Sampling options shared by generate and chat requests
+#[derive(Debug, Serialize)]
+struct ModelOptions {
+    temperature: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_predict: Option<u32>,
+}
+
+/// Single-prompt completion request
+#[derive(Debug, Serialize)]
+struct GenerateRequest {
+    model: String,
+    prompt: String,
+    stream: bool,
+    options: ModelOptions,
+}
+
+/// Completion response
+#[derive(Debug, Deserialize)]
+struct GenerateResponse {
+    response: String,
+    #[serde(default)]
+    prompt_eval_count: Option<u64>,
+    #[serde(default)]
+    eval_count: Option<u64>,
+}
+
+/// Chat request; images ride along with the message that refers to them
+#[derive(Debug, Serialize)]
+struct ChatRequest {
+    model: String,
+    messages: Vec<ChatMessage>,
+    stream: bool,
+    options: ModelOptions,
+    /// JSON schema the reply is constrained to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<serde_json::Value>,
+}
+
+/// Chat message with optional base64 images
+#[derive(Debug, Serialize)]
+struct ChatMessage {
+    role: String,
+    content: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    images: Vec<String>,
+}
+
+/// Chat response
+#[derive(Debug, Deserialize)]
+struct ChatResponse {
+    message: ChatResponseMessage,
+    #[serde(default)]
+    prompt_eval_count: Option<u64>,
+    #[serde(default)]
+    eval_count: Option<u64>,
+}
+
+/// Assistant message in a chat response
+#[derive(Debug, Deserialize)]
+struct ChatResponseMessage {
+    content: String,
+}
+
+/// Models installed on the server
+#[derive(Debug, Deserialize)]
+struct TagsResponse {
+    models: Vec<ModelTag>,
+}
+
+/// Installed model
+#[derive(Debug, Deserialize)]
+struct ModelTag {
+    name: String,
+}
+
+/// Request to download a model
+#[derive(Debug, Serialize)]
+struct PullRequest {
+    model: String,
+    stream: bool,
+}
+
+/// Final status of a model download
+#[derive(Debug, Deserialize)]
+struct PullResponse {
+    status: String,
+}
+
+impl OllamaClient {
+    pub fn new(config: AiConfig) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.timeout_seconds))
+            .build()
+            .expect("Failed to create HTTP client");
+
+        let base_url = config
+            .base_url
+            .clone()
+            .unwrap_or_else(|| DEFAULT_BASE_URL.to_string())
+            .trim_end_matches('/')
+            .to_string();
+
+        debug!("Created Ollama client with base URL: {}", base_url);
+
+        Self {
+            client,
+            config,
+            base_url,
+        }
+    }
+
+    /// Model used for frame descriptions
+    fn vision_model(&self) -> &str {
+        self.config
+            .vision_model
+            .as_deref()
+            .unwrap_or(&self.config.model)
+    }
+
+    fn create_request(&self, endpoint: &str) -> RequestBuilder {
+        self.client.post(format!(
+            "{}/{}",
+            self.base_url,
+            endpoint.trim_start_matches('/')
+        ))
+    }
+
+    /// Names of the models installed on the server
+    pub async fn available_models(&self) -> Result<Vec<String>> {
+        let request = self.client.get(format!("{}/api/tags", self.base_url));
+        let tags: TagsResponse = http::execute_request(request, "Ollama").await?;
+        Ok(tags.models.into_iter().map(|model| model.name).collect())
+    }
+
+    /// Download a model, waiting until the server has it
+    pub async fn pull_model(&self, model: &str) -> Result<()> {
+        info!(model = %model, "Pulling Ollama model");
+        let request = self
+            .create_request("/api/pull")
+            .timeout(PULL_TIMEOUT)
+            .json(&PullRequest {
+                model: model.to_string(),
+                stream: false,
+            });
+        let response: PullResponse = http::execute_request(request, "Ollama").await?;
+        if response.status != "success" {
+            return Err(Error::Config(format!(
+                "Ollama could not pull model '{}': {}",
+                model, response.status
+            )));
+        }
+        Ok(())
+    }
+
+    /// Make sure the text and vision models are installed, pulling them if allowed
+    pub async fn ensure_models(&self) -> Result<()> {
+        let available = self.available_models().await?;
+        let mut wanted = vec![self.config.model.as_str()];
+        if self.vision_model() != self.config.model {
+            wanted.push(self.vision_model());
+        }
+
+        for model in wanted {
+            if available.iter().any(|name| model_matches(name, model)) {
+                continue;
+            }
+            if !self.config.pull_models {
+                return Err(Error::Config(format!(
+                    "Ollama model '{}' is not installed; run `ollama pull {}` or enable pull_models",
+                    model, model
+                )));
+            }
+            self.pull_model(model).await?;
+        }
+        Ok(())
+    }
+
+    /// Complete a single prompt
+    async fn generate(
+        &self,
+        prompt: String,
+        num_predict: Option<u32>,
+        temperature: f32,
+    ) -> Result<(String, Option<TokenUsage>)> {
+        let request = self.create_request("/api/generate").json(&GenerateRequest {
+            model: self.config.model.clone(),
+            prompt,
+            stream: false,
+            options: ModelOptions {
+                temperature,
+                num_predict,
+            },
+        });
+        let response: GenerateResponse =
+            http::execute_with_retry(request, self.config.max_retries, "Ollama").await?;
+        Ok((
+            response.response.trim().to_string(),
+            token_usage(response.prompt_eval_count, response.eval_count),
+        ))
+    }
+
+    /// Ask the vision model about a base64 image, optionally constraining the reply to a schema
+    async fn vision_chat(
+        &self,
+        prompt: String,
+        image: &str,
+        format: Option<serde_json::Value>,
+    ) -> Result<(String, Option<TokenUsage>)> {
+        let request = self.create_request("/api/chat").json(&ChatRequest {
+            model: self.vision_model().to_string(),
+            messages: vec![ChatMessage {
+                role: "user".to_string(),
+                content: prompt,
+                images: vec![image.to_string()],
+            }],
+            stream: false,
+            options: ModelOptions {
+                temperature: 0.2,
+                num_predict: Some(500),
+            },
+            format,
+        });
+        let response: ChatResponse =
+            http::execute_with_retry(request, self.config.max_retries, "Ollama").await?;
+        Ok((
+            response.message.content.trim().to_string(),
+            token_usage(response.prompt_eval_count, response.eval_count),
+        ))
+    }
+}
+
+/// Tokens spent as reported by Ollama's evaluation counters
+fn token_usage(prompt_eval_count: Option<u64>, eval_count: Option<u64>) -> Option<TokenUsage> {
+    if prompt_eval_count.is_none() && eval_count.is_none() {
+        return None;
+    }
+    Some(TokenUsage {
+        prompt_tokens: prompt_eval_count.unwrap_or(0),
+        completion_tokens: eval_count.unwrap_or(0),
+    })
+}
+
+/// Ollama names models `name:tag` and treats a missing tag as `latest`
+fn model_matches(installed: &str, wanted: &str) -> bool {
+    fn with_tag(name: &str) -> String {
+        if name.contains(':') {
+            name.to_string()
+        } else {
+            format!("{}:latest", name)
+        }
+    }
+    with_tag(installed) == with_tag(wanted)
+}
+
+#[async_trait]
+impl AiClient for OllamaClient {
+    async fn summarize(&self, request: SummarizeRequest) -> Result<SummarizeResponse> {
+        debug!(
+            "Ollama client summarizing {} characters",
+            request.text.len()
+        );
+
+        let num_predict = request.max_length.map(|len| (len as f32 * 1.3) as u32);
+        let (summary, usage) = self
+            .generate(prompts::summarize_prompt(&request), num_predict, 0.3)
+            .await?;
+
+        let confidence = if summary.len() < request.text.len() / 2 {
+            0.9
+        } else {
+            0.8
+        };
+
+        Ok(SummarizeResponse {
+            original_length: request.text.len(),
+            summary_length: summary.len(),
+            summary,
+            confidence: Some(confidence),
+            usage,
+        })
+    }
+
+    async fn describe_frame(&self, request: DescribeFrameRequest) -> Result<DescribeFrameResponse> {
+        debug!(
+            "Ollama client describing {} byte image",
+            request.image_data.len()
+        );
+
+        prompts::image_mime_type(&request.image_format)?;
+        use base64::Engine;
+        let image = base64::engine::general_purpose::STANDARD.encode(&request.image_data);
+        let prompt = prompts::describe_prompt(&request);
+
+        let start_time = std::time::Instant::now();
+        let (description, structured, usage) = match &request.output_schema {
+            Some(schema) => {
+                // Every attempt at matching the schema is billed
+                let spent = Mutex::new(Vec::new());
+                let (reply, value) = structured::request_matching(&prompt, schema, |prompt| {
+                    let chat = self.vision_chat(prompt, &image, Some(schema.clone()));
+                    let spent = &spent;
+                    async move {
+                        let (reply, usage) = chat.await?;
+                        spent.lock().unwrap().push(usage);
+                        Ok(reply)
+                    }
+                })
+                .await?;
+                let usage = TokenUsage::sum(spent.into_inner().unwrap());
+                (reply, Some(value), usage)
+            }
+            None => {
+                let (reply, usage) = self.vision_chat(prompt, &image, None).await?;
+                (reply, None, usage)
+            }
+        };
+        let processing_time = start_time.elapsed().as_millis() as u64;
+
+        // Structured replies carry their findings in the schema's fields
+        let objects_detected = if structured.is_some() {
+            Vec::new()
+        } else {
+            prompts::detected_objects(&description)
+        };
+
+        Ok(DescribeFrameResponse {
+            description,
+            objects_detected,
+            confidence: Some(0.8),
+            processing_time_ms: Some(processing_time),
+            structured,
+            usage,
+        })
+    }
+
+    async fn classify_event(&self, request: ClassifyEventRequest) -> Result<ClassifyEventResponse> {
+        debug!(
+            "Ollama client classifying event: {}",
+            request.event_data.event_type
+        );
+
+        let (response_text, usage) = self
+            .generate(prompts::classify_prompt(&request), Some(300), 0.1)
+            .await?;
+        let (classification, confidence, suggested_actions) =
+            prompts::parse_classification(&response_text);
+
+        Ok(ClassifyEventResponse {
+            classification,
+            confidence,
+            reasoning: response_text,
+            suggested_actions,
+            usage,
+        })
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        debug!("Ollama client health check");
+        self.ensure_models().await?;
+        debug!("Ollama client health check passed");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AiProvider, EventClassification, EventData};
+    use bytes::Bytes;
+    use serde_json::json;
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn config(server: &MockServer) -> AiConfig {
+        AiConfig {
+            provider: AiProvider::Ollama,
+            base_url: Some(server.uri()),
+            model: "llama3.2".to_string(),
+            vision_model: Some("llava".to_string()),
+            max_retries: 0,
+            use_online: true,
+            ..AiConfig::default()
+        }
+    }
+
+    fn tags(names: &[&str]) -> ResponseTemplate {
+        let models: Vec<_> = names.iter().map(|name| json!({"name": name})).collect();
+        ResponseTemplate::new(200).set_body_json(json!({ "models": models }))
+    }
+
+    #[test]
+    fn test_model_matches_with_implicit_latest_tag() {
+        assert!(model_matches("llava:latest", "llava"));
+        assert!(model_matches("llava", "llava:latest"));
+        assert!(model_matches("llama3.2:3b", "llama3.2:3b"));
+        assert!(!model_matches("llama3.2:3b", "llama3.2"));
+    }
+
+    #[tokio::test]
+    async fn test_describe_frame_sends_image_to_vision_model() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/chat"))
+            .and(body_partial_json(json!({
+                "model": "llava",
+                "stream": false,
+                "messages": [{"role": "user", "images": ["ZmFrZV9qcGVn"]}]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "model": "llava",
+                "message": {"role": "assistant", "content": " A person walks past, a car is parked. "},
+                "done": true,
+                "prompt_eval_count": 612,
+                "eval_count": 14
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = OllamaClient::new(config(&server));
+        let response = client
+            .describe_frame(DescribeFrameRequest {
+                image_data: Bytes::from_static(b"fake_jpeg"),
+                image_format: "jpeg".to_string(),
+                detail_level: None,
+                focus: None,
+                stream_id: None,
+                question: None,
+                output_schema: None,
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(
+            response.description,
+            "A person walks past, a car is parked."
+        );
+        assert_eq!(response.objects_detected, vec!["person", "vehicle"]);
+        assert_eq!(
+            response.usage,
+            Some(TokenUsage {
+                prompt_tokens: 612,
+                completion_tokens: 14,
+            })
+        );
+
+        let unsupported = client
+            .describe_frame(DescribeFrameRequest {
+                image_data: Bytes::from_static(b"fake"),
+                image_format: "tiff".to_string(),
+                detail_level: None,
+                focus: None,
+                stream_id: None,
+                question: None,
+                output_schema: None,
+            })
+            .await;
+        assert!(unsupported.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_summarize_and_classify_use_generate() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/generate"))
+            .and(body_partial_json(
+                json!({"model": "llama3.2", "stream": false}),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "model": "llama3.2",
+                "response": "A person was seen at the door, 90% confidence.",
+                "done": true
+            })))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let client = OllamaClient::new(config(&server));
+        let summary = client
+            .summarize(SummarizeRequest {
+                text: "Front door camera saw a person ring the bell and leave a parcel."
+                    .to_string(),
+                max_length: Some(40),
+                style: None,
+                stream_id: None,
+            })
+            .await
+            .unwrap();
+        assert!(summary.summary.starts_with("A person"));
+        // This server reports no evaluation counters
+        assert_eq!(summary.usage, None);
+
+        let classified = client
+            .classify_event(ClassifyEventRequest {
+                event_data: EventData {
+                    event_type: "motion_detected".to_string(),
+                    confidence: 0.8,
+                    metadata: json!({}),
+                    timestamp: "2026-10-18T10:00:00Z".to_string(),
+                    source_id: "front_door".to_string(),
+                },
+                context: None,
+                threshold: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(classified.classification, EventClassification::Person);
+        assert!((classified.confidence - 0.9).abs() < 0.01);
+    }
+
+    #[tokio::test]
+    async fn test_health_check_requires_installed_models() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/tags"))
+            .respond_with(tags(&["llama3.2:latest"]))
+            .mount(&server)
+            .await;
+
+        let client = OllamaClient::new(config(&server));
+        let err = client.health_check().await.unwrap_err();
+        assert!(err.to_string().contains("ollama pull llava"), "{}", err);
+
+        let text_only = OllamaClient::new(AiConfig {
+            vision_model: None,
+            ..config(&server)
+        });
+        assert!(text_only.health_check().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_health_check_pulls_missing_models_when_allowed() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/tags"))
+            .respond_with(tags(&["llama3.2:latest"]))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/pull"))
+            .and(body_partial_json(
+                json!({"model": "llava", "stream": false}),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"status": "success"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = crate::create_client(AiConfig {
+            pull_models: true,
+            ..config(&server)
+        });
+        client.health_check().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_server_errors_are_reported() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/generate"))
+            .respond_with(
+                ResponseTemplate::new(404)
+                    .set_body_json(json!({"error": "model \"llama3.2\" not found"})),
+            )
+            .mount(&server)
+            .await;
+
+        let client = OllamaClient::new(config(&server));
+        let err = client
+            .summarize(SummarizeRequest {
+                text: "text".to_string(),
+                max_length: None,
+                style: None,
+                stream_id: None,
+            })
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("not found"), "{}", err);
+    }
+}
@@ -6,13 +6,14 @@ use bytes::Bytes;
 use gl_core::{Error, Result};
 use reqwest::{Client, RequestBuilder};
 use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
 use std::time::Duration;
-use tokio::time::sleep;
-use tracing::{debug, error, warn};
+use tracing::debug;
 
 use crate::{
-    AiClient, AiConfig, ClassifyEventRequest, ClassifyEventResponse, DescribeFrameRequest,
-    DescribeFrameResponse, EventClassification, SummarizeRequest, SummarizeResponse,
+    http, prompts, structured, AiClient, AiConfig, ClassifyEventRequest, ClassifyEventResponse,
+    DescribeFrameRequest, DescribeFrameResponse, EventClassification, SummarizeRequest,
+    SummarizeResponse, TokenUsage,
 };
 
 /// OpenAI API client with authentication and retry logic
@@ -38,6 +39,9 @@ struct OpenAiVisionRequest {
     messages: Vec<OpenAiVisionMessage>,
     max_tokens: Option<u32>,
     temperature: f32,
+    /// Structured output constraint
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<serde_json::Value>,
 }
 
 /// OpenAI message format
@@ -72,10 +76,18 @@ struct OpenAiImageUrl {
 #[derive(Debug, Deserialize)]
 struct OpenAiResponse {
     choices: Vec<OpenAiChoice>,
-    #[allow(dead_code)]
     usage: Option<OpenAiUsage>,
 }
 
+impl OpenAiResponse {
+    fn token_usage(&self) -> Option<TokenUsage> {
+        self.usage.as_ref().map(|usage| TokenUsage {
+            prompt_tokens: u64::from(usage.prompt_tokens),
+            completion_tokens: u64::from(usage.completion_tokens),
+        })
+    }
+}
+
 /// OpenAI choice in response
 #[derive(Debug, Deserialize)]
 struct OpenAiChoice {
@@ -92,9 +104,7 @@ struct OpenAiResponseMessage {
 
 /// OpenAI usage statistics
 #[derive(Debug, Deserialize)]
-#[allow(dead_code)]
 struct OpenAiUsage {
-    total_tokens: u32,
     prompt_tokens: u32,
     completion_tokens: u32,
 }
@@ -125,36 +135,7 @@ impl OpenAiClient {
     where
         T: for<'de> Deserialize<'de>,
     {
-        let mut last_error = None;
-
-        for attempt in 0..=self.config.max_retries {
-            if attempt > 0 {
-                let delay = Duration::from_millis(100 * (1 << attempt.min(5))); // Exponential backoff
-                debug!("Retrying request in {:?} (attempt {})", delay, attempt + 1);
-                sleep(delay).await;
-            }
-
-            let request = match request_builder.try_clone() {
-                Some(req) => req,
-                None => {
-                    error!("Failed to clone request for retry");
-                    return Err(Error::Config(
-                        "Unable to retry request - body not cloneable".to_string(),
-                    ));
-                }
-            };
-
-            match self.execute_request::<T>(request).await {
-                Ok(response) => return Ok(response),
-                Err(e) => {
-                    warn!("Request attempt {} failed: {}", attempt + 1, e);
-                    last_error = Some(e);
-                }
-            }
-        }
-
-        Err(last_error
-            .unwrap_or_else(|| Error::Io(std::io::Error::other("All retry attempts failed"))))
+        http::execute_with_retry(request_builder, self.config.max_retries, "OpenAI").await
     }
 
     /// Execute a single HTTP request
@@ -162,32 +143,7 @@ impl OpenAiClient {
     where
         T: for<'de> Deserialize<'de>,
     {
-        let response = request
-            .send()
-            .await
-            .map_err(|e| Error::Io(std::io::Error::other(format!("HTTP request failed: {}", e))))?;
-
-        let status = response.status();
-        if !status.is_success() {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(Error::Database(format!(
-                "OpenAI API error ({}): {}",
-                status, error_text
-            )));
-        }
-
-        let response_text = response.text().await.map_err(|e| {
-            Error::Io(std::io::Error::other(format!(
-                "Failed to read response: {}",
-                e
-            )))
-        })?;
-
-        serde_json::from_str::<T>(&response_text)
-            .map_err(|e| Error::Database(format!("Failed to parse OpenAI response: {}", e)))
+        http::execute_request(request, "OpenAI").await
     }
 
     /// Create authenticated request builder
@@ -205,20 +161,52 @@ impl OpenAiClient {
         builder
     }
 
+    /// Ask the vision model about an image and return its reply with the tokens it spent
+    async fn vision_chat(
+        &self,
+        prompt: String,
+        data_url: &str,
+        response_format: Option<serde_json::Value>,
+    ) -> Result<(String, Option<TokenUsage>)> {
+        let openai_request = OpenAiVisionRequest {
+            // Image analysis always needs a vision-capable model
+            model: self
+                .config
+                .vision_model
+                .clone()
+                .unwrap_or_else(|| "gpt-4-vision-preview".to_string()),
+            messages: vec![OpenAiVisionMessage {
+                role: "user".to_string(),
+                content: vec![
+                    OpenAiContent::Text { text: prompt },
+                    OpenAiContent::ImageUrl {
+                        image_url: OpenAiImageUrl {
+                            url: data_url.to_string(),
+                        },
+                    },
+                ],
+            }],
+            max_tokens: Some(500),
+            temperature: 0.2,
+            response_format,
+        };
+
+        let request_builder = self
+            .create_request("/chat/completions")
+            .json(&openai_request);
+        let response: OpenAiResponse = self.execute_with_retry(request_builder).await?;
+
+        let reply = response
+            .choices
+            .first()
+            .map(|choice| choice.message.content.trim().to_string())
+            .unwrap_or_else(|| "No description generated".to_string());
+        Ok((reply, response.token_usage()))
+    }
+
     /// Convert image bytes to data URL
     fn image_to_data_url(&self, image_data: &Bytes, format: &str) -> Result<String> {
-        let mime_type = match format.to_lowercase().as_str() {
-            "jpeg" | "jpg" => "image/jpeg",
-            "png" => "image/png",
-            "gif" => "image/gif",
-            "webp" => "image/webp",
-            _ => {
-                return Err(Error::Validation(format!(
-                    "Unsupported image format: {}",
-                    format
-                )))
-            }
-        };
+        let mime_type = prompts::image_mime_type(format)?;
 
         use base64::Engine;
         let base64_data = base64::engine::general_purpose::STANDARD.encode(image_data);
@@ -227,68 +215,7 @@ impl OpenAiClient {
 
     /// Parse event classification from AI response
     fn parse_classification(&self, response: &str) -> (EventClassification, f64, Vec<String>) {
-        let lower_response = response.to_lowercase();
-
-        // Extract classification
-        let classification = if lower_response.contains("fire") || lower_response.contains("smoke")
-        {
-            EventClassification::Fire
-        } else if lower_response.contains("person") || lower_response.contains("human") {
-            EventClassification::Person
-        } else if lower_response.contains("vehicle") || lower_response.contains("car") {
-            EventClassification::Vehicle
-        } else if lower_response.contains("animal") {
-            EventClassification::Animal
-        } else if lower_response.contains("motion") {
-            EventClassification::Motion
-        } else if lower_response.contains("suspicious") {
-            EventClassification::Suspicious
-        } else if lower_response.contains("normal") {
-            EventClassification::Normal
-        } else {
-            EventClassification::Unknown
-        };
-
-        // Extract confidence (look for percentages or decimal values)
-        let confidence = if let Some(pct_match) = response.match_indices('%').next() {
-            // Look for number before %
-            let before_pct = &response[..pct_match.0];
-            if let Some(num_start) = before_pct.rfind(|c: char| !c.is_numeric() && c != '.') {
-                before_pct[num_start + 1..].parse::<f64>().unwrap_or(0.75) / 100.0
-            } else {
-                0.75
-            }
-        } else if response.contains("confidence") {
-            // Look for decimal after "confidence"
-            0.8 // Default for confidence mentions
-        } else {
-            0.7 // Default confidence
-        };
-
-        // Generate suggested actions based on classification
-        let suggested_actions = match classification {
-            EventClassification::Fire => vec![
-                "Immediately alert fire department".to_string(),
-                "Evacuate area if safe to do so".to_string(),
-                "Monitor situation continuously".to_string(),
-            ],
-            EventClassification::Suspicious => vec![
-                "Review additional camera angles".to_string(),
-                "Consider alerting security personnel".to_string(),
-                "Log event for pattern analysis".to_string(),
-            ],
-            EventClassification::Person | EventClassification::Vehicle => vec![
-                "Log event for traffic analysis".to_string(),
-                "Continue monitoring".to_string(),
-            ],
-            _ => vec!["Continue monitoring".to_string()],
-        };
-
-        (
-            classification,
-            confidence.clamp(0.0, 1.0),
-            suggested_actions,
-        )
+        prompts::parse_classification(response)
     }
 }
 
@@ -301,18 +228,7 @@ impl AiClient for OpenAiClient {
         );
 
         let max_tokens = request.max_length.map(|len| (len as f32 * 1.3) as u32);
-        let style_instruction = request.style.as_deref().unwrap_or("brief");
-
-        let prompt = format!(
-            "Summarize the following text in a {} style{}:\n\n{}",
-            style_instruction,
-            if let Some(max_len) = request.max_length {
-                format!(", keeping it under {} characters", max_len)
-            } else {
-                String::new()
-            },
-            request.text
-        );
+        let prompt = prompts::summarize_prompt(&request);
 
         let openai_request = OpenAiSummarizeRequest {
             model: self.config.model.clone(),
@@ -347,6 +263,7 @@ impl AiClient for OpenAiClient {
             summary_length: summary.len(),
             summary,
             confidence: Some(confidence),
+            usage: response.token_usage(),
         })
     }
 
@@ -357,67 +274,48 @@ impl AiClient for OpenAiClient {
         );
 
         let data_url = self.image_to_data_url(&request.image_data, &request.image_format)?;
-        let detail_level = request.detail_level.as_deref().unwrap_or("high");
-        let focus = request.focus.as_deref().unwrap_or("objects");
-
-        let prompt = format!(
-            "Analyze this image with {} detail, focusing on {}. Provide a concise description and list any objects or people you can identify.",
-            detail_level, focus
-        );
-
-        let openai_request = OpenAiVisionRequest {
-            model: "gpt-4-vision-preview".to_string(), // Always use vision model for image analysis
-            messages: vec![OpenAiVisionMessage {
-                role: "user".to_string(),
-                content: vec![
-                    OpenAiContent::Text { text: prompt },
-                    OpenAiContent::ImageUrl {
-                        image_url: OpenAiImageUrl { url: data_url },
-                    },
-                ],
-            }],
-            max_tokens: Some(500),
-            temperature: 0.2,
-        };
+        let prompt = prompts::describe_prompt(&request);
 
         let start_time = std::time::Instant::now();
-        let request_builder = self
-            .create_request("/chat/completions")
-            .json(&openai_request);
-
-        let response: OpenAiResponse = self.execute_with_retry(request_builder).await?;
+        let (description, structured, usage) = match &request.output_schema {
+            Some(schema) => {
+                // Every attempt at matching the schema is billed
+                let spent = Mutex::new(Vec::new());
+                let response_format = structured::response_format(schema);
+                let (reply, value) = structured::request_matching(&prompt, schema, |prompt| {
+                    let chat = self.vision_chat(prompt, &data_url, Some(response_format.clone()));
+                    let spent = &spent;
+                    async move {
+                        let (reply, usage) = chat.await?;
+                        spent.lock().unwrap().push(usage);
+                        Ok(reply)
+                    }
+                })
+                .await?;
+                let usage = TokenUsage::sum(spent.into_inner().unwrap());
+                (reply, Some(value), usage)
+            }
+            None => {
+                let (reply, usage) = self.vision_chat(prompt, &data_url, None).await?;
+                (reply, None, usage)
+            }
+        };
         let processing_time = start_time.elapsed().as_millis() as u64;
 
-        let description = response
-            .choices
-            .first()
-            .map(|choice| choice.message.content.trim().to_string())
-            .unwrap_or_else(|| "No description generated".to_string());
-
-        // Extract objects from description (simple heuristic)
-        let objects_detected: Vec<String> = description
-            .split(&[',', ';', '.', '\n'][..])
-            .filter_map(|s| {
-                let trimmed = s.trim().to_lowercase();
-                if trimmed.contains("person") || trimmed.contains("people") {
-                    Some("person".to_string())
-                } else if trimmed.contains("car") || trimmed.contains("vehicle") {
-                    Some("vehicle".to_string())
-                } else if trimmed.contains("building") {
-                    Some("building".to_string())
-                } else if trimmed.contains("tree") {
-                    Some("tree".to_string())
-                } else {
-                    None
-                }
-            })
-            .collect();
+        // Structured replies carry their findings in the schema's fields
+        let objects_detected = if structured.is_some() {
+            Vec::new()
+        } else {
+            prompts::detected_objects(&description)
+        };
 
         Ok(DescribeFrameResponse {
             description,
             objects_detected,
             confidence: Some(0.85),
             processing_time_ms: Some(processing_time),
+            structured,
+            usage,
         })
     }
 
@@ -427,26 +325,7 @@ impl AiClient for OpenAiClient {
             request.event_data.event_type
         );
 
-        let context_str = request.context.as_deref().unwrap_or("security monitoring");
-        let threshold = request.threshold.unwrap_or(0.7);
-
-        let prompt = format!(
-            "Classify this security event for {} context:\n\
-            Event Type: {}\n\
-            Confidence: {}\n\
-            Metadata: {}\n\
-            Timestamp: {}\n\
-            Source: {}\n\n\
-            Classify as one of: Motion, Person, Vehicle, Animal, Fire, Suspicious, Normal, Unknown\n\
-            Provide confidence level and reasoning. Minimum confidence threshold is {}",
-            context_str,
-            request.event_data.event_type,
-            request.event_data.confidence,
-            request.event_data.metadata,
-            request.event_data.timestamp,
-            request.event_data.source_id,
-            threshold
-        );
+        let prompt = prompts::classify_prompt(&request);
 
         let openai_request = OpenAiSummarizeRequest {
             model: self.config.model.clone(),
@@ -478,6 +357,7 @@ impl AiClient for OpenAiClient {
             confidence,
             reasoning: response_text,
             suggested_actions,
+            usage: response.token_usage(),
         })
     }
 
@@ -511,16 +391,22 @@ impl AiClient for OpenAiClient {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::AiProvider;
     use bytes::Bytes;
 
     fn create_test_config() -> AiConfig {
         AiConfig {
+            provider: AiProvider::OpenAi,
             api_key: Some("test-key".to_string()),
             base_url: Some("https://api.openai.com/v1".to_string()),
             timeout_seconds: 30,
             max_retries: 3,
             model: "gpt-3.5-turbo".to_string(),
+            vision_model: None,
+            pull_models: false,
             use_online: true,
+            routing: None,
+            cache: None,
         }
     }
 
@@ -0,0 +1,168 @@
+//! ABOUTME: Prompts and response parsing shared by the language model provider clients
+//! ABOUTME: Keeps summaries, frame descriptions and event classification consistent across backends
+
+use gl_core::{Error, Result};
+
+use crate::{ClassifyEventRequest, DescribeFrameRequest, EventClassification, SummarizeRequest};
+
+/// Prompt asking for a summary in the requested style and length
+pub(crate) fn summarize_prompt(request: &SummarizeRequest) -> String {
+    let style_instruction = request.style.as_deref().unwrap_or("brief");
+    format!(
+        "Summarize the following text in a {} style{}:\n\n{}",
+        style_instruction,
+        if let Some(max_len) = request.max_length {
+            format!(", keeping it under {} characters", max_len)
+        } else {
+            String::new()
+        },
+        request.text
+    )
+}
+
+/// Prompt sent alongside an image to describe it
+pub(crate) fn describe_prompt(request: &DescribeFrameRequest) -> String {
+    if let Some(question) = &request.question {
+        return question_prompt(question);
+    }
+    let detail_level = request.detail_level.as_deref().unwrap_or("high");
+    let focus = request.focus.as_deref().unwrap_or("objects");
+    format!(
+        "Analyze this image with {} detail, focusing on {}. Provide a concise description and list any objects or people you can identify.",
+        detail_level, focus
+    )
+}
+
+/// Prompt asking for a yes/no/unknown verdict on a question about an image
+fn question_prompt(question: &str) -> String {
+    format!(
+        "Answer this question about the image: {}\n\
+        Reply with only a JSON object of the form \
+        {{\"answer\": \"yes\" | \"no\" | \"unknown\", \"confidence\": <0.0 to 1.0>, \"reason\": \"<one short sentence>\"}}. \
+        Answer \"unknown\" when the image does not show enough to tell.",
+        question.trim()
+    )
+}
+
+/// Prompt asking for one of the known event classes
+pub(crate) fn classify_prompt(request: &ClassifyEventRequest) -> String {
+    let context_str = request.context.as_deref().unwrap_or("security monitoring");
+    let threshold = request.threshold.unwrap_or(0.7);
+    format!(
+        "Classify this security event for {} context:\n\
+        Event Type: {}\n\
+        Confidence: {}\n\
+        Metadata: {}\n\
+        Timestamp: {}\n\
+        Source: {}\n\n\
+        Classify as one of: Motion, Person, Vehicle, Animal, Fire, Suspicious, Normal, Unknown\n\
+        Provide confidence level and reasoning. Minimum confidence threshold is {}",
+        context_str,
+        request.event_data.event_type,
+        request.event_data.confidence,
+        request.event_data.metadata,
+        request.event_data.timestamp,
+        request.event_data.source_id,
+        threshold
+    )
+}
+
+/// MIME type for a frame's image format
+pub(crate) fn image_mime_type(format: &str) -> Result<&'static str> {
+    match format.to_lowercase().as_str() {
+        "jpeg" | "jpg" => Ok("image/jpeg"),
+        "png" => Ok("image/png"),
+        "gif" => Ok("image/gif"),
+        "webp" => Ok("image/webp"),
+        _ => Err(Error::Validation(format!(
+            "Unsupported image format: {}",
+            format
+        ))),
+    }
+}
+
+/// Objects mentioned in a free-text frame description (simple heuristic)
+pub(crate) fn detected_objects(description: &str) -> Vec<String> {
+    description
+        .split(&[',', ';', '.', '\n'][..])
+        .filter_map(|s| {
+            let trimmed = s.trim().to_lowercase();
+            if trimmed.contains("person") || trimmed.contains("people") {
+                Some("person".to_string())
+            } else if trimmed.contains("car") || trimmed.contains("vehicle") {
+                Some("vehicle".to_string())
+            } else if trimmed.contains("building") {
+                Some("building".to_string())
+            } else if trimmed.contains("tree") {
+                Some("tree".to_string())
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Parse event classification, confidence and suggested actions from a model response
+pub(crate) fn parse_classification(response: &str) -> (EventClassification, f64, Vec<String>) {
+    let lower_response = response.to_lowercase();
+
+    // Extract classification
+    let classification = if lower_response.contains("fire") || lower_response.contains("smoke") {
+        EventClassification::Fire
+    } else if lower_response.contains("person") || lower_response.contains("human") {
+        EventClassification::Person
+    } else if lower_response.contains("vehicle") || lower_response.contains("car") {
+        EventClassification::Vehicle
+    } else if lower_response.contains("animal") {
+        EventClassification::Animal
+    } else if lower_response.contains("motion") {
+        EventClassification::Motion
+    } else if lower_response.contains("suspicious") {
+        EventClassification::Suspicious
+    } else if lower_response.contains("normal") {
+        EventClassification::Normal
+    } else {
+        EventClassification::Unknown
+    };
+
+    // Extract confidence (look for percentages or decimal values)
+    let confidence = if let Some(pct_match) = response.match_indices('%').next() {
+        // Look for number before %
+        let before_pct = &response[..pct_match.0];
+        if let Some(num_start) = before_pct.rfind(|c: char| !c.is_numeric() && c != '.') {
+            before_pct[num_start + 1..].parse::<f64>().unwrap_or(0.75) / 100.0
+        } else {
+            0.75
+        }
+    } else if response.contains("confidence") {
+        // Look for decimal after "confidence"
+        0.8 // Default for confidence mentions
+    } else {
+        0.7 // Default confidence
+    };
+
+    // Generate suggested actions based on classification
+    let suggested_actions = match classification {
+        EventClassification::Fire => vec![
+            "Immediately alert fire department".to_string(),
+            "Evacuate area if safe to do so".to_string(),
+            "Monitor situation continuously".to_string(),
+        ],
+        EventClassification::Suspicious => vec![
+            "Review additional camera angles".to_string(),
+            "Consider alerting security personnel".to_string(),
+            "Log event for pattern analysis".to_string(),
+        ],
+        EventClassification::Person | EventClassification::Vehicle => vec![
+            "Log event for traffic analysis".to_string(),
+            "Continue monitoring".to_string(),
+        ],
+        _ => vec!["Continue monitoring".to_string()],
+    };
+
+    (
+        classification,
+        confidence.clamp(0.0, 1.0),
+        suggested_actions,
+    )
+}
@@ -0,0 +1,747 @@
+//! ABOUTME: AI client that routes each task to its own model and falls back when a provider fails
+//! ABOUTME: Trips a circuit after repeated failures and enforces daily token and cost budgets per stream
+
+use async_trait::async_trait;
+use gl_core::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+use crate::budget::{
+    estimate_image_tokens, estimate_tokens, utc_day, MemoryUsageStore, SharedUsageStore, UsageStore,
+};
+use crate::{
+    create_client, AiClient, AiConfig, AiProvider, ClassifyEventRequest, ClassifyEventResponse,
+    DescribeFrameRequest, DescribeFrameResponse, StubClient, SummarizeRequest, SummarizeResponse,
+    TokenUsage,
+};
+
+/// Overrides for one task's model on top of the top-level AI settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AiRoute {
+    /// Provider to call; switching provider also drops the top-level base URL and key
+    pub provider: Option<AiProvider>,
+    pub model: Option<String>,
+    pub base_url: Option<String>,
+    pub api_key: Option<String>,
+    /// Price per 1000 tokens in USD, counted against cost budgets
+    pub cost_per_1k_tokens_usd: f64,
+}
+
+/// How the router spreads tasks over models and limits what streams may spend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AiRoutingConfig {
+    /// Price per 1000 tokens in USD of the top-level model
+    pub cost_per_1k_tokens_usd: f64,
+    /// Model for describing frames; the top-level model when unset
+    pub vision: Option<AiRoute>,
+    /// Model for summaries and event classification; the top-level model when unset
+    pub text: Option<AiRoute>,
+    /// Secondary provider used when the primary fails; the offline stub when unset
+    pub fallback: Option<AiRoute>,
+    /// Consecutive failures before a provider is skipped
+    pub failure_threshold: u32,
+    /// How long a provider is skipped before it is tried again
+    pub open_seconds: u64,
+    /// Estimated tokens a stream may use per UTC day
+    pub daily_token_limit: Option<u64>,
+    /// Spend in USD a stream may incur per UTC day
+    pub daily_cost_limit_usd: Option<f64>,
+    /// Where usage is kept; in memory when unset
+    #[serde(skip)]
+    pub usage_store: Option<SharedUsageStore>,
+}
+
+impl Default for AiRoutingConfig {
+    fn default() -> Self {
+        Self {
+            cost_per_1k_tokens_usd: 0.0,
+            vision: None,
+            text: None,
+            fallback: None,
+            failure_threshold: 3,
+            open_seconds: 60,
+            daily_token_limit: None,
+            daily_cost_limit_usd: None,
+            usage_store: None,
+        }
+    }
+}
+
+type CallFuture<'c, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'c>>;
+
+/// Responses that can carry the tokens a provider reported spending
+trait Metered {
+    fn usage(&self) -> Option<TokenUsage>;
+}
+
+impl Metered for SummarizeResponse {
+    fn usage(&self) -> Option<TokenUsage> {
+        self.usage
+    }
+}
+
+impl Metered for DescribeFrameResponse {
+    fn usage(&self) -> Option<TokenUsage> {
+        self.usage
+    }
+}
+
+impl Metered for ClassifyEventResponse {
+    fn usage(&self) -> Option<TokenUsage> {
+        self.usage
+    }
+}
+
+/// Consecutive failures of a provider and when it may be tried again
+#[derive(Debug, Default)]
+struct Circuit {
+    failures: u32,
+    open_until: Option<Instant>,
+}
+
+/// A client the router can send tasks to
+struct Route {
+    label: &'static str,
+    model: String,
+    client: Box<dyn AiClient>,
+    cost_per_1k_tokens_usd: f64,
+    /// False for the offline stub, which neither costs nor counts
+    billable: bool,
+    circuit: Mutex<Circuit>,
+}
+
+impl Route {
+    fn new(
+        label: &'static str,
+        model: String,
+        client: Box<dyn AiClient>,
+        cost_per_1k_tokens_usd: f64,
+        billable: bool,
+    ) -> Self {
+        Self {
+            label,
+            model,
+            client,
+            cost_per_1k_tokens_usd,
+            billable,
+            circuit: Mutex::new(Circuit::default()),
+        }
+    }
+
+    fn from_config(label: &'static str, config: AiConfig, cost_per_1k_tokens_usd: f64) -> Self {
+        let model = if label == "vision" {
+            config
+                .vision_model
+                .clone()
+                .unwrap_or_else(|| config.model.clone())
+        } else {
+            config.model.clone()
+        };
+        Self::new(
+            label,
+            model,
+            create_client(config),
+            cost_per_1k_tokens_usd,
+            true,
+        )
+    }
+
+    fn stub() -> Self {
+        Self::new(
+            "fallback",
+            "stub".to_string(),
+            Box::new(StubClient::new()),
+            0.0,
+            false,
+        )
+    }
+
+    /// Whether the circuit lets a request through; an expired circuit allows one trial
+    fn is_available(&self) -> bool {
+        match self.circuit.lock().unwrap().open_until {
+            Some(open_until) => Instant::now() >= open_until,
+            None => true,
+        }
+    }
+
+    fn succeeded(&self) {
+        *self.circuit.lock().unwrap() = Circuit::default();
+    }
+
+    fn failed(&self, threshold: u32, open_for: Duration) {
+        let mut circuit = self.circuit.lock().unwrap();
+        circuit.failures += 1;
+        if circuit.failures >= threshold {
+            if circuit.failures == threshold {
+                warn!(route = self.label, model = %self.model, "AI provider circuit opened");
+            }
+            circuit.open_until = Some(Instant::now() + open_for);
+        }
+    }
+}
+
+/// Client settings for a route, layered over the top-level settings
+fn route_config(base: &AiConfig, route: &AiRoute, vision: bool) -> AiConfig {
+    let mut config = base.clone();
+    config.routing = None;
+
+    if let Some(provider) = route.provider.filter(|provider| *provider != base.provider) {
+        config.provider = provider;
+        config.base_url = route.base_url.clone();
+        config.api_key = route.api_key.clone();
+    } else {
+        if route.base_url.is_some() {
+            config.base_url = route.base_url.clone();
+        }
+        if route.api_key.is_some() {
+            config.api_key = route.api_key.clone();
+        }
+    }
+    if let Some(model) = &route.model {
+        config.model = model.clone();
+        if vision {
+            config.vision_model = Some(model.clone());
+        }
+    }
+    config
+}
+
+/// AI client that sends each task to its own model, with fallback and budgets
+pub struct AiRouter {
+    text: Route,
+    vision: Route,
+    fallback: Route,
+    failure_threshold: u32,
+    open_for: Duration,
+    daily_token_limit: Option<u64>,
+    daily_cost_limit_usd: Option<f64>,
+    usage: Arc<dyn UsageStore>,
+}
+
+impl AiRouter {
+    pub fn new(config: AiConfig) -> Self {
+        let routing = config.routing.clone().unwrap_or_default();
+        let mut base = config;
+        base.routing = None;
+
+        let route = |label, route: &Option<AiRoute>| match route {
+            Some(route) => Route::from_config(
+                label,
+                route_config(&base, route, label == "vision"),
+                route.cost_per_1k_tokens_usd,
+            ),
+            None => Route::from_config(label, base.clone(), routing.cost_per_1k_tokens_usd),
+        };
+        let text = route("text", &routing.text);
+        let vision = route("vision", &routing.vision);
+        let fallback = match &routing.fallback {
+            Some(fallback) => Route::from_config(
+                "fallback",
+                route_config(&base, fallback, false),
+                fallback.cost_per_1k_tokens_usd,
+            ),
+            None => Route::stub(),
+        };
+
+        info!(
+            text = %text.model,
+            vision = %vision.model,
+            fallback = %fallback.model,
+            "Created AI router"
+        );
+
+        Self::from_routes(text, vision, fallback, &routing)
+    }
+
+    fn from_routes(text: Route, vision: Route, fallback: Route, routing: &AiRoutingConfig) -> Self {
+        Self {
+            text,
+            vision,
+            fallback,
+            failure_threshold: routing.failure_threshold.max(1),
+            open_for: Duration::from_secs(routing.open_seconds),
+            daily_token_limit: routing.daily_token_limit,
+            daily_cost_limit_usd: routing.daily_cost_limit_usd,
+            usage: routing
+                .usage_store
+                .clone()
+                .map(|store| store.0)
+                .unwrap_or_else(|| Arc::new(MemoryUsageStore::new())),
+        }
+    }
+
+    /// Why a stream may not spend more today, if it has reached a limit
+    async fn budget_exhausted(&self, stream_id: &str) -> Option<String> {
+        if self.daily_token_limit.is_none() && self.daily_cost_limit_usd.is_none() {
+            return None;
+        }
+
+        let usage = match self.usage.daily_usage(stream_id, &utc_day()).await {
+            Ok(usage) => usage,
+            Err(e) => {
+                warn!(stream_id = %stream_id, "Failed to read AI usage, not enforcing budget: {}", e);
+                return None;
+            }
+        };
+
+        if let Some(limit) = self
+            .daily_token_limit
+            .filter(|limit| usage.tokens >= *limit)
+        {
+            return Some(format!("{} of {} tokens used", usage.tokens, limit));
+        }
+        if let Some(limit) = self
+            .daily_cost_limit_usd
+            .filter(|limit| usage.cost_usd >= *limit)
+        {
+            return Some(format!("${:.2} of ${:.2} spent", usage.cost_usd, limit));
+        }
+        None
+    }
+
+    /// Run a task on its primary route, falling back when it fails or is over budget
+    ///
+    /// A stream that has used up its budget is only served by a configured fallback
+    /// that costs nothing, such as a local model. Budgets are charged with the tokens
+    /// the provider reports, and with the given estimates only when it reports none.
+    async fn dispatch<T: Metered, F>(
+        &self,
+        primary: &Route,
+        stream_id: Option<&str>,
+        prompt_tokens: u64,
+        completion_tokens: fn(&T) -> u64,
+        call: F,
+    ) -> Result<T>
+    where
+        F: for<'c> Fn(&'c dyn AiClient) -> CallFuture<'c, T>,
+    {
+        let mut routes = Vec::with_capacity(2);
+        let exhausted = match stream_id {
+            Some(stream_id) => self.budget_exhausted(stream_id).await,
+            None => None,
+        };
+        if let Some(reason) = exhausted {
+            let stream_id = stream_id.unwrap_or_default();
+            if !(self.fallback.billable && self.fallback.cost_per_1k_tokens_usd == 0.0) {
+                return Err(Error::Validation(format!(
+                    "Daily AI budget exhausted for stream {}: {}",
+                    stream_id, reason
+                )));
+            }
+            debug!(stream_id = %stream_id, "AI budget exhausted, using free fallback: {}", reason);
+        } else if primary.is_available() {
+            routes.push(primary);
+        } else {
+            debug!(route = primary.label, "AI provider circuit open, skipping");
+        }
+        routes.push(&self.fallback);
+
+        let mut last_error = None;
+        for route in routes {
+            match call(route.client.as_ref()).await {
+                Ok(response) => {
+                    route.succeeded();
+                    if route.billable {
+                        if let Some(stream_id) = stream_id {
+                            let tokens = match response.usage() {
+                                Some(usage) => usage.total(),
+                                None => prompt_tokens + completion_tokens(&response),
+                            };
+                            self.record_usage(stream_id, route, tokens).await;
+                        }
+                    }
+                    return Ok(response);
+                }
+                Err(e) => {
+                    warn!(route = route.label, model = %route.model, "AI request failed: {}", e);
+                    route.failed(self.failure_threshold, self.open_for);
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| Error::External("No AI provider available".to_string())))
+    }
+
+    async fn record_usage(&self, stream_id: &str, route: &Route, tokens: u64) {
+        let cost_usd = tokens as f64 / 1000.0 * route.cost_per_1k_tokens_usd;
+        if let Err(e) = self
+            .usage
+            .record(stream_id, &utc_day(), &route.model, tokens, cost_usd)
+            .await
+        {
+            warn!(stream_id = %stream_id, "Failed to record AI usage: {}", e);
+        }
+    }
+}
+
+#[async_trait]
+impl AiClient for AiRouter {
+    async fn summarize(&self, request: SummarizeRequest) -> Result<SummarizeResponse> {
+        let prompt_tokens = estimate_tokens(&request.text);
+        self.dispatch(
+            &self.text,
+            request.stream_id.as_deref(),
+            prompt_tokens,
+            |response: &SummarizeResponse| estimate_tokens(&response.summary),
+            |client| client.summarize(request.clone()),
+        )
+        .await
+    }
+
+    async fn describe_frame(&self, request: DescribeFrameRequest) -> Result<DescribeFrameResponse> {
+        let prompt_tokens = estimate_image_tokens(request.detail_level.as_deref());
+        self.dispatch(
+            &self.vision,
+            request.stream_id.as_deref(),
+            prompt_tokens,
+            |response: &DescribeFrameResponse| estimate_tokens(&response.description),
+            |client| client.describe_frame(request.clone()),
+        )
+        .await
+    }
+
+    async fn classify_event(&self, request: ClassifyEventRequest) -> Result<ClassifyEventResponse> {
+        let prompt_tokens = estimate_tokens(&request.event_data.metadata.to_string());
+        let stream_id = Some(request.event_data.source_id.as_str()).filter(|id| !id.is_empty());
+        self.dispatch(
+            &self.text,
+            stream_id,
+            prompt_tokens,
+            |response: &ClassifyEventResponse| estimate_tokens(&response.reasoning),
+            |client| client.classify_event(request.clone()),
+        )
+        .await
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        let primary = match self.text.client.health_check().await {
+            Ok(()) => self.vision.client.health_check().await,
+            Err(e) => Err(e),
+        };
+        match primary {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                warn!("Primary AI provider unhealthy, checking fallback: {}", e);
+                self.fallback.client.health_check().await
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::budget::AiUsage;
+    use bytes::Bytes;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Client that answers with its name, or fails
+    struct NamedClient {
+        name: &'static str,
+        fail: bool,
+        usage: Option<TokenUsage>,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl NamedClient {
+        fn boxed(name: &'static str, fail: bool) -> (Box<dyn AiClient>, Arc<AtomicUsize>) {
+            Self::reporting(name, fail, None)
+        }
+
+        fn reporting(
+            name: &'static str,
+            fail: bool,
+            usage: Option<TokenUsage>,
+        ) -> (Box<dyn AiClient>, Arc<AtomicUsize>) {
+            let calls = Arc::new(AtomicUsize::new(0));
+            let client = Box::new(Self {
+                name,
+                fail,
+                usage,
+                calls: calls.clone(),
+            });
+            (client, calls)
+        }
+
+        fn answer(&self) -> Result<String> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.fail {
+                Err(Error::External(format!("{} is down", self.name)))
+            } else {
+                Ok(self.name.to_string())
+            }
+        }
+    }
+
+    #[async_trait]
+    impl AiClient for NamedClient {
+        async fn summarize(&self, request: SummarizeRequest) -> Result<SummarizeResponse> {
+            let summary = self.answer()?;
+            Ok(SummarizeResponse {
+                original_length: request.text.len(),
+                summary_length: summary.len(),
+                summary,
+                confidence: None,
+                usage: self.usage,
+            })
+        }
+
+        async fn describe_frame(
+            &self,
+            _request: DescribeFrameRequest,
+        ) -> Result<DescribeFrameResponse> {
+            Ok(DescribeFrameResponse {
+                description: self.answer()?,
+                objects_detected: Vec::new(),
+                confidence: None,
+                processing_time_ms: None,
+                structured: None,
+                usage: self.usage,
+            })
+        }
+
+        async fn classify_event(
+            &self,
+            _request: ClassifyEventRequest,
+        ) -> Result<ClassifyEventResponse> {
+            Ok(ClassifyEventResponse {
+                classification: crate::EventClassification::Unknown,
+                confidence: 0.5,
+                reasoning: self.answer()?,
+                suggested_actions: Vec::new(),
+                usage: self.usage,
+            })
+        }
+
+        async fn health_check(&self) -> Result<()> {
+            self.answer().map(|_| ())
+        }
+    }
+
+    fn route(label: &'static str, client: Box<dyn AiClient>, cost: f64) -> Route {
+        Route::new(label, label.to_string(), client, cost, true)
+    }
+
+    fn summarize(stream_id: &str) -> SummarizeRequest {
+        SummarizeRequest {
+            text: "x".repeat(400),
+            max_length: None,
+            style: None,
+            stream_id: Some(stream_id.to_string()),
+        }
+    }
+
+    fn describe(stream_id: &str) -> DescribeFrameRequest {
+        DescribeFrameRequest {
+            image_data: Bytes::from_static(b"frame"),
+            image_format: "jpeg".to_string(),
+            detail_level: Some("low".to_string()),
+            focus: None,
+            stream_id: Some(stream_id.to_string()),
+            question: None,
+            output_schema: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tasks_go_to_their_own_models() {
+        let (text, _) = NamedClient::boxed("text", false);
+        let (vision, _) = NamedClient::boxed("vision", false);
+        let router = AiRouter::from_routes(
+            route("text", text, 0.0),
+            route("vision", vision, 0.0),
+            Route::stub(),
+            &AiRoutingConfig::default(),
+        );
+
+        let summary = router.summarize(summarize("porch")).await.unwrap();
+        assert_eq!(summary.summary, "text");
+        let description = router.describe_frame(describe("porch")).await.unwrap();
+        assert_eq!(description.description, "vision");
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_and_opens_circuit() {
+        let (text, text_calls) = NamedClient::boxed("text", true);
+        let (vision, _) = NamedClient::boxed("vision", false);
+        let (fallback, fallback_calls) = NamedClient::boxed("fallback", false);
+        let router = AiRouter::from_routes(
+            route("text", text, 0.0),
+            route("vision", vision, 0.0),
+            route("fallback", fallback, 0.0),
+            &AiRoutingConfig {
+                failure_threshold: 2,
+                open_seconds: 3600,
+                ..Default::default()
+            },
+        );
+
+        for _ in 0..4 {
+            let summary = router.summarize(summarize("porch")).await.unwrap();
+            assert_eq!(summary.summary, "fallback");
+        }
+        // The primary is skipped once it has failed twice in a row
+        assert_eq!(text_calls.load(Ordering::SeqCst), 2);
+        assert_eq!(fallback_calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_stub_fallback_when_no_secondary_configured() {
+        let (text, _) = NamedClient::boxed("text", true);
+        let (vision, _) = NamedClient::boxed("vision", true);
+        let router = AiRouter::from_routes(
+            route("text", text, 0.0),
+            route("vision", vision, 0.0),
+            Route::stub(),
+            &AiRoutingConfig::default(),
+        );
+
+        let description = router.describe_frame(describe("porch")).await.unwrap();
+        assert!(!description.description.is_empty());
+        assert!(router.health_check().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_budget_caps_noisy_stream() {
+        let store = Arc::new(MemoryUsageStore::new());
+        let (text, _) = NamedClient::boxed("text", false);
+        let (vision, vision_calls) = NamedClient::boxed("vision", false);
+        let router = AiRouter::from_routes(
+            route("text", text, 0.01),
+            route("vision", vision, 0.01),
+            Route::stub(),
+            &AiRoutingConfig {
+                daily_token_limit: Some(250),
+                usage_store: Some(SharedUsageStore(store.clone())),
+                ..Default::default()
+            },
+        );
+
+        // 85 image tokens plus a couple for the answer each time
+        for _ in 0..3 {
+            router.describe_frame(describe("noisy")).await.unwrap();
+        }
+        let err = router.describe_frame(describe("noisy")).await.unwrap_err();
+        assert!(err.to_string().contains("budget exhausted"), "{}", err);
+        assert_eq!(vision_calls.load(Ordering::SeqCst), 3);
+
+        // Other streams keep their own budget
+        router.describe_frame(describe("quiet")).await.unwrap();
+
+        let usage = store.daily_usage("noisy", &utc_day()).await.unwrap();
+        assert_eq!(usage.requests, 3);
+        assert_eq!(usage.tokens, 3 * (85 + 2));
+        assert!((usage.cost_usd - usage.tokens as f64 / 1000.0 * 0.01).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_budget_charges_reported_usage() {
+        let store = Arc::new(MemoryUsageStore::new());
+        let (text, _) = NamedClient::boxed("text", false);
+        let (vision, _) = NamedClient::reporting(
+            "vision",
+            false,
+            Some(TokenUsage {
+                prompt_tokens: 1105,
+                completion_tokens: 40,
+            }),
+        );
+        let router = AiRouter::from_routes(
+            route("text", text, 0.01),
+            route("vision", vision, 0.01),
+            Route::stub(),
+            &AiRoutingConfig {
+                usage_store: Some(SharedUsageStore(store.clone())),
+                ..Default::default()
+            },
+        );
+
+        router.describe_frame(describe("porch")).await.unwrap();
+        router.summarize(summarize("porch")).await.unwrap();
+
+        // The reported usage replaces the image estimate; the text route reports nothing
+        let usage = store.daily_usage("porch", &utc_day()).await.unwrap();
+        assert_eq!(usage.requests, 2);
+        assert_eq!(usage.tokens, 1105 + 40 + 100 + 1);
+    }
+
+    #[tokio::test]
+    async fn test_exhausted_budget_uses_free_fallback() {
+        let store = Arc::new(MemoryUsageStore::new());
+        store
+            .record("porch", &utc_day(), "gpt-4o", 10, 5.0)
+            .await
+            .unwrap();
+        let (text, text_calls) = NamedClient::boxed("text", false);
+        let (vision, _) = NamedClient::boxed("vision", false);
+        let (local, _) = NamedClient::boxed("local", false);
+        let router = AiRouter::from_routes(
+            route("text", text, 0.5),
+            route("vision", vision, 0.5),
+            route("fallback", local, 0.0),
+            &AiRoutingConfig {
+                daily_cost_limit_usd: Some(5.0),
+                usage_store: Some(SharedUsageStore(store.clone())),
+                ..Default::default()
+            },
+        );
+
+        let summary = router.summarize(summarize("porch")).await.unwrap();
+        assert_eq!(summary.summary, "local");
+        assert_eq!(text_calls.load(Ordering::SeqCst), 0);
+
+        let usage = store.daily_usage("porch", &utc_day()).await.unwrap();
+        assert_eq!(
+            usage,
+            AiUsage {
+                requests: 2,
+                tokens: 10 + 100 + 2,
+                cost_usd: 5.0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_route_config_layers_over_top_level() {
+        let base = AiConfig {
+            provider: AiProvider::OpenAi,
+            api_key: Some("sk-test".to_string()),
+            model: "gpt-4o".to_string(),
+            use_online: true,
+            routing: Some(AiRoutingConfig::default()),
+            ..Default::default()
+        };
+
+        let text = route_config(
+            &base,
+            &AiRoute {
+                model: Some("gpt-4o-mini".to_string()),
+                ..Default::default()
+            },
+            false,
+        );
+        assert_eq!(text.model, "gpt-4o-mini");
+        assert_eq!(text.api_key.as_deref(), Some("sk-test"));
+        assert!(text.routing.is_none());
+
+        let local = route_config(
+            &base,
+            &AiRoute {
+                provider: Some(AiProvider::Ollama),
+                model: Some("llava".to_string()),
+                ..Default::default()
+            },
+            true,
+        );
+        assert_eq!(local.provider, AiProvider::Ollama);
+        assert_eq!(local.api_key, None);
+        assert_eq!(local.vision_model.as_deref(), Some("llava"));
+    }
+}
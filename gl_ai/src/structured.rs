@@ -0,0 +1,426 @@
+//! ABOUTME: JSON-schema structured output for frame analysis
+//! ABOUTME: Validates model replies against a user-defined schema and re-asks on malformed output
+
+use gl_core::{Error, Result};
+use serde_json::{Map, Value};
+use std::future::Future;
+use tracing::warn;
+
+/// Tries at getting a reply that matches the schema before giving up
+pub const MAX_ATTEMPTS: u32 = 3;
+
+/// Check that a schema only uses the keywords the validator understands
+///
+/// Supported: `type` (a name or list of names), `properties`, `required`,
+/// `additionalProperties`, `items`, `enum`, `minimum`, `maximum`, `minLength`, `maxLength`,
+/// `minItems`, `maxItems`, plus the annotations `description` and `title`.
+pub fn check_schema(schema: &Value) -> Result<()> {
+    check_schema_at(schema, "$").map_err(Error::Validation)
+}
+
+fn check_schema_at(schema: &Value, path: &str) -> std::result::Result<(), String> {
+    let Some(schema) = schema.as_object() else {
+        return Err(format!("{}: schema must be an object", path));
+    };
+    for (keyword, value) in schema {
+        match keyword.as_str() {
+            "type" => {
+                let names: Vec<&Value> = match value {
+                    Value::Array(names) => names.iter().collect(),
+                    name => vec![name],
+                };
+                for name in names {
+                    match name.as_str() {
+                        Some(
+                            "object" | "array" | "string" | "integer" | "number" | "boolean"
+                            | "null",
+                        ) => {}
+                        _ => return Err(format!("{}: unknown type {}", path, name)),
+                    }
+                }
+            }
+            "properties" => {
+                let Some(properties) = value.as_object() else {
+                    return Err(format!("{}: properties must be an object", path));
+                };
+                for (name, property) in properties {
+                    check_schema_at(property, &format!("{}.{}", path, name))?;
+                }
+            }
+            "items" => check_schema_at(value, &format!("{}[]", path))?,
+            "additionalProperties" if !value.is_boolean() => {
+                check_schema_at(value, &format!("{}.*", path))?
+            }
+            "required" | "enum" if !value.is_array() => {
+                return Err(format!("{}: {} must be an array", path, keyword))
+            }
+            "minimum" | "maximum" if !value.is_number() => {
+                return Err(format!("{}: {} must be a number", path, keyword))
+            }
+            "minLength" | "maxLength" | "minItems" | "maxItems" if !value.is_u64() => {
+                return Err(format!("{}: {} must be a whole number", path, keyword))
+            }
+            "additionalProperties"
+            | "required"
+            | "enum"
+            | "minimum"
+            | "maximum"
+            | "minLength"
+            | "maxLength"
+            | "minItems"
+            | "maxItems"
+            | "description"
+            | "title"
+            | "$schema" => {}
+            other => return Err(format!("{}: unsupported keyword {}", path, other)),
+        }
+    }
+    Ok(())
+}
+
+/// Check a value against a schema, describing the first mismatch
+pub fn validate(value: &Value, schema: &Value) -> std::result::Result<(), String> {
+    validate_at(value, schema, "$")
+}
+
+fn validate_at(value: &Value, schema: &Value, path: &str) -> std::result::Result<(), String> {
+    let Some(schema) = schema.as_object() else {
+        return Ok(());
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let matches = match expected {
+            Value::Array(names) => names.iter().any(|name| is_type(value, name)),
+            name => is_type(value, name),
+        };
+        if !matches {
+            return Err(format!("{} should be of type {}", path, expected));
+        }
+    }
+    if let Some(Value::Array(allowed)) = schema.get("enum") {
+        if !allowed.contains(value) {
+            return Err(format!(
+                "{} should be one of {}",
+                path,
+                Value::from(allowed.clone())
+            ));
+        }
+    }
+
+    match value {
+        Value::Object(object) => validate_object(object, schema, path)?,
+        Value::Array(items) => {
+            let count = items.len() as u64;
+            if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+                if count < min {
+                    return Err(format!("{} should have at least {} items", path, min));
+                }
+            }
+            if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+                if count > max {
+                    return Err(format!("{} should have at most {} items", path, max));
+                }
+            }
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    validate_at(item, item_schema, &format!("{}[{}]", path, index))?;
+                }
+            }
+        }
+        Value::Number(number) => {
+            let number = number.as_f64().unwrap_or_default();
+            if let Some(min) = schema.get("minimum").and_then(Value::as_f64) {
+                if number < min {
+                    return Err(format!("{} should be at least {}", path, min));
+                }
+            }
+            if let Some(max) = schema.get("maximum").and_then(Value::as_f64) {
+                if number > max {
+                    return Err(format!("{} should be at most {}", path, max));
+                }
+            }
+        }
+        Value::String(text) => {
+            let length = text.chars().count() as u64;
+            if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+                if length < min {
+                    return Err(format!("{} should be at least {} characters", path, min));
+                }
+            }
+            if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+                if length > max {
+                    return Err(format!("{} should be at most {} characters", path, max));
+                }
+            }
+        }
+        Value::Bool(_) | Value::Null => {}
+    }
+    Ok(())
+}
+
+fn validate_object(
+    object: &Map<String, Value>,
+    schema: &Map<String, Value>,
+    path: &str,
+) -> std::result::Result<(), String> {
+    if let Some(Value::Array(required)) = schema.get("required") {
+        for name in required.iter().filter_map(Value::as_str) {
+            if !object.contains_key(name) {
+                return Err(format!("{} is missing required field {}", path, name));
+            }
+        }
+    }
+
+    let properties = schema.get("properties").and_then(Value::as_object);
+    for (name, field) in object {
+        let field_path = format!("{}.{}", path, name);
+        match (
+            properties.and_then(|p| p.get(name)),
+            schema.get("additionalProperties"),
+        ) {
+            (Some(field_schema), _) => validate_at(field, field_schema, &field_path)?,
+            (None, Some(Value::Bool(false))) => {
+                return Err(format!("{} is not an allowed field", field_path))
+            }
+            (None, Some(extra_schema)) => validate_at(field, extra_schema, &field_path)?,
+            (None, None) => {}
+        }
+    }
+    Ok(())
+}
+
+fn is_type(value: &Value, name: &Value) -> bool {
+    match name.as_str() {
+        Some("object") => value.is_object(),
+        Some("array") => value.is_array(),
+        Some("string") => value.is_string(),
+        Some("integer") => {
+            value.is_i64()
+                || value.is_u64()
+                || value.as_f64().is_some_and(|number| number.fract() == 0.0)
+        }
+        Some("number") => value.is_number(),
+        Some("boolean") => value.is_boolean(),
+        Some("null") => value.is_null(),
+        _ => false,
+    }
+}
+
+/// Read a JSON reply, tolerating code fences or prose around the object, and validate it
+pub fn parse_reply(reply: &str, schema: &Value) -> std::result::Result<Value, String> {
+    let json = match (reply.find('{'), reply.rfind('}')) {
+        (Some(start), Some(end)) if start < end => &reply[start..=end],
+        _ => reply.trim(),
+    };
+    let value: Value =
+        serde_json::from_str(json).map_err(|e| format!("reply is not valid JSON: {}", e))?;
+    validate(&value, schema)?;
+    Ok(value)
+}
+
+/// Instructions appended to a prompt when the reply must match a schema
+pub fn schema_prompt(prompt: &str, schema: &Value, previous_error: Option<&str>) -> String {
+    let mut prompt = format!(
+        "{}\n\nReply with only a JSON object that matches this JSON schema, with no other text:\n{}",
+        prompt, schema
+    );
+    if let Some(error) = previous_error {
+        prompt.push_str(&format!(
+            "\n\nYour previous reply was rejected because {}. Correct it.",
+            error
+        ));
+    }
+    prompt
+}
+
+/// OpenAI-style `response_format` asking the server to constrain output to the schema
+pub fn response_format(schema: &Value) -> Value {
+    serde_json::json!({
+        "type": "json_schema",
+        "json_schema": {"name": "frame_analysis", "schema": schema}
+    })
+}
+
+/// Ask until a reply matches the schema, feeding each rejection back into the next prompt;
+/// returns the raw reply and the parsed value
+pub async fn request_matching<F, Fut>(
+    prompt: &str,
+    schema: &Value,
+    mut ask: F,
+) -> Result<(String, Value)>
+where
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = Result<String>>,
+{
+    let mut previous_error = None;
+    for attempt in 1..=MAX_ATTEMPTS {
+        let reply = ask(schema_prompt(prompt, schema, previous_error.as_deref())).await?;
+        match parse_reply(&reply, schema) {
+            Ok(value) => return Ok((reply, value)),
+            Err(e) => {
+                warn!(attempt, error = %e, "Model reply did not match the output schema");
+                previous_error = Some(e);
+            }
+        }
+    }
+    Err(Error::Validation(format!(
+        "Model reply did not match the output schema after {} attempts: {}",
+        MAX_ATTEMPTS,
+        previous_error.unwrap_or_default()
+    )))
+}
+
+/// Smallest value that satisfies a schema, used when no model is available
+pub fn example(schema: &Value) -> Value {
+    if let Some(Value::Array(allowed)) = schema.get("enum") {
+        if let Some(first) = allowed.first() {
+            return first.clone();
+        }
+    }
+    let type_name = match schema.get("type") {
+        Some(Value::Array(names)) => names.first().and_then(Value::as_str),
+        Some(name) => name.as_str(),
+        None => None,
+    };
+    match type_name {
+        Some("object") | None => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            let required: Vec<&str> = schema
+                .get("required")
+                .and_then(Value::as_array)
+                .map(|names| names.iter().filter_map(Value::as_str).collect())
+                .unwrap_or_default();
+            let mut object = Map::new();
+            for (name, property) in properties.into_iter().flatten() {
+                if required.contains(&name.as_str()) || required.is_empty() {
+                    object.insert(name.clone(), example(property));
+                }
+            }
+            Value::Object(object)
+        }
+        Some("array") => {
+            let count = schema.get("minItems").and_then(Value::as_u64).unwrap_or(0);
+            let item = schema.get("items").map(example).unwrap_or(Value::Null);
+            Value::Array(vec![item; count as usize])
+        }
+        Some("string") => {
+            let length = schema.get("minLength").and_then(Value::as_u64).unwrap_or(0);
+            Value::String("x".repeat(length as usize))
+        }
+        Some("integer") | Some("number") => schema
+            .get("minimum")
+            .cloned()
+            .unwrap_or_else(|| Value::from(0)),
+        Some("boolean") => Value::Bool(false),
+        _ => Value::Null,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "people_count": {"type": "integer", "minimum": 0},
+                "vehicles": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "type": {"type": "string", "enum": ["car", "truck", "bike"]},
+                            "color": {"type": "string"}
+                        },
+                        "required": ["type"]
+                    }
+                },
+                "hazards": {"type": "array", "items": {"type": "string"}}
+            },
+            "required": ["people_count", "vehicles", "hazards"],
+            "additionalProperties": false
+        })
+    }
+
+    #[test]
+    fn test_validate() {
+        let schema = schema();
+        check_schema(&schema).unwrap();
+        validate(
+            &json!({"people_count": 2, "vehicles": [{"type": "car", "color": "red"}], "hazards": []}),
+            &schema,
+        )
+        .unwrap();
+
+        let err = validate(&json!({"people_count": 1, "vehicles": []}), &schema).unwrap_err();
+        assert!(err.contains("missing required field hazards"), "{}", err);
+        let err = validate(
+            &json!({"people_count": 1.5, "vehicles": [], "hazards": []}),
+            &schema,
+        )
+        .unwrap_err();
+        assert!(err.contains("$.people_count"), "{}", err);
+        let err = validate(
+            &json!({"people_count": 0, "vehicles": [{"type": "plane"}], "hazards": []}),
+            &schema,
+        )
+        .unwrap_err();
+        assert!(err.contains("$.vehicles[0].type"), "{}", err);
+        let err = validate(
+            &json!({"people_count": 0, "vehicles": [], "hazards": [], "mood": "calm"}),
+            &schema,
+        )
+        .unwrap_err();
+        assert!(err.contains("not an allowed field"), "{}", err);
+    }
+
+    #[test]
+    fn test_check_schema_rejects_unsupported_keywords() {
+        assert!(check_schema(&json!({"type": "object", "oneOf": []})).is_err());
+        assert!(check_schema(&json!({"type": "int"})).is_err());
+        assert!(check_schema(&json!("object")).is_err());
+    }
+
+    #[test]
+    fn test_example_satisfies_schema() {
+        let schema = schema();
+        let value = example(&schema);
+        validate(&value, &schema).unwrap();
+        assert_eq!(value["people_count"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_request_matching_retries_with_feedback() {
+        let replies = Mutex::new(vec![
+            "```json\n{\"people_count\": 1, \"vehicles\": [], \"hazards\": [\"ice\"]}\n```",
+            r#"{"people_count": "one", "vehicles": [], "hazards": []}"#,
+        ]);
+        let prompts = Mutex::new(Vec::new());
+        let (_, value) = request_matching("Describe the frame.", &schema(), |prompt| {
+            prompts.lock().unwrap().push(prompt);
+            let reply = replies.lock().unwrap().pop().unwrap();
+            async move { Ok(reply.to_string()) }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(value["hazards"][0], "ice");
+        let prompts = prompts.into_inner().unwrap();
+        assert_eq!(prompts.len(), 2);
+        assert!(!prompts[0].contains("rejected"));
+        assert!(prompts[1].contains("$.people_count should be of type"));
+    }
+
+    #[tokio::test]
+    async fn test_request_matching_gives_up() {
+        let err = request_matching("Describe the frame.", &schema(), |_| async {
+            Ok("I see two people.".to_string())
+        })
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("after 3 attempts"), "{}", err);
+    }
+}
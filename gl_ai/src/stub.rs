@@ -6,7 +6,7 @@ use gl_core::Result;
 use tracing::debug;
 
 use crate::{
-    AiClient, ClassifyEventRequest, ClassifyEventResponse, DescribeFrameRequest,
+    structured, AiClient, ClassifyEventRequest, ClassifyEventResponse, DescribeFrameRequest,
     DescribeFrameResponse, EventClassification, SummarizeRequest, SummarizeResponse,
 };
 
@@ -166,6 +166,7 @@ impl AiClient for StubClient {
             summary_length: summary.len(),
             summary,
             confidence: Some(0.85), // Stub confidence
+            usage: None,
         })
     }
 
@@ -180,14 +181,26 @@ impl AiClient for StubClient {
         let delay_ms = (request.image_data.len() / 1000).clamp(50, 500);
         tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms as u64)).await;
 
-        let (description, objects) =
-            self.generate_stub_description(request.image_data.len(), &request.image_format);
+        // Without a model there is nothing to base a verdict on
+        let (description, objects) = if request.question.is_some() {
+            (
+                r#"{"answer": "unknown", "confidence": 0.0, "reason": "Offline mode cannot inspect images"}"#
+                    .to_string(),
+                Vec::new(),
+            )
+        } else {
+            self.generate_stub_description(request.image_data.len(), &request.image_format)
+        };
+        // Placeholder values keep schema-driven pipelines flowing offline
+        let structured = request.output_schema.as_ref().map(structured::example);
 
         Ok(DescribeFrameResponse {
             description,
             objects_detected: objects,
             confidence: Some(0.8),
             processing_time_ms: Some(delay_ms as u64),
+            structured,
+            usage: None,
         })
     }
 
@@ -236,6 +249,7 @@ impl AiClient for StubClient {
             confidence,
             reasoning,
             suggested_actions,
+            usage: None,
         })
     }
 
@@ -259,6 +273,7 @@ mod tests {
             text: "This is a very long piece of text that needs to be summarized into something much shorter for better readability and comprehension by users.".to_string(),
             max_length: Some(20),
             style: Some("brief".to_string()),
+            stream_id: None,
         };
 
         let response = client.summarize(request).await.unwrap();
@@ -277,6 +292,9 @@ mod tests {
             image_format: "jpeg".to_string(),
             detail_level: Some("high".to_string()),
             focus: Some("objects".to_string()),
+            stream_id: None,
+            question: None,
+            output_schema: None,
         };
 
         let response = client.describe_frame(request).await.unwrap();
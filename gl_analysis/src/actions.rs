@@ -0,0 +1,606 @@
+//! ABOUTME: Executes rule actions that drive side effects outside the event itself
+//! ABOUTME: Handles HTTP calls, snapshot bursts, recordings and on-demand AI descriptions
+
+use crate::{
+    rule_engine::Action, AiDescriptionProcessor, AnalysisEvent, Processor, ProcessorInput,
+};
+use async_trait::async_trait;
+use gl_ai::AiConfig;
+use gl_core::{Error, Result};
+use gl_db::{CreateRuleActionResult, RuleActionResultRepository};
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+/// Default timeout for outbound HTTP actions
+const DEFAULT_HTTP_TIMEOUT_SECONDS: u64 = 10;
+
+/// Upper bound on snapshots taken by a single burst
+const MAX_BURST_COUNT: u32 = 50;
+
+/// Upper bound on the length of a rule-triggered recording
+const MAX_RECORDING_SECONDS: u64 = 600;
+
+/// Capture operations the host application exposes to rule actions
+///
+/// `gl_analysis` has no access to running captures, so the web layer
+/// implements this trait on top of its capture manager and hands it to the
+/// rule engine via [`ActionExecutor::set_capture_control`].
+#[async_trait]
+pub trait CaptureControl: Send + Sync {
+    /// Take `count` snapshots `interval` apart and return the stored snapshot IDs
+    async fn snapshot_burst(
+        &self,
+        stream_id: &str,
+        count: u32,
+        interval: Duration,
+    ) -> Result<Vec<String>>;
+
+    /// Record the stream for `duration` and return the storage URI of the recording
+    async fn record(&self, stream_id: &str, duration: Duration) -> Result<String>;
+}
+
+/// Outcome of a single side-effect action execution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionOutcome {
+    /// Rule that triggered the action
+    pub rule_id: String,
+    /// Action type (`http_call`, `snapshot_burst`, ...)
+    pub action_type: String,
+    /// Event that triggered the action
+    pub event_id: String,
+    /// Whether the action succeeded
+    pub success: bool,
+    /// Structured output produced by the action
+    pub output: Option<serde_json::Value>,
+    /// Error message if the action failed
+    pub error: Option<String>,
+    /// Execution time in milliseconds
+    pub duration_ms: u64,
+}
+
+/// Executor for side-effect rule actions
+///
+/// Every execution is logged through `tracing` and, when a database is
+/// configured, recorded in the `rule_action_results` table.
+#[derive(Clone)]
+pub struct ActionExecutor {
+    http_client: reqwest::Client,
+    capture_control: Option<Arc<dyn CaptureControl>>,
+    ai_config: AiConfig,
+    results_repo: Option<RuleActionResultRepository>,
+}
+
+impl Default for ActionExecutor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ActionExecutor {
+    /// Create an executor without capture control or result persistence
+    pub fn new() -> Self {
+        Self {
+            http_client: reqwest::Client::new(),
+            capture_control: None,
+            ai_config: AiConfig::default(),
+            results_repo: None,
+        }
+    }
+
+    /// Create an executor that records results in the database
+    pub fn with_persistence(db: gl_db::Db) -> Self {
+        Self {
+            results_repo: Some(RuleActionResultRepository::new(db)),
+            ..Self::new()
+        }
+    }
+
+    /// Set the capture operations used by snapshot burst and recording actions
+    pub fn set_capture_control(&mut self, capture_control: Arc<dyn CaptureControl>) {
+        self.capture_control = Some(capture_control);
+    }
+
+    /// Set the AI configuration used by describe actions
+    pub fn set_ai_config(&mut self, ai_config: AiConfig) {
+        self.ai_config = ai_config;
+    }
+
+    /// Execute an action for an event, logging and recording the outcome
+    pub async fn execute(
+        &self,
+        rule_id: &str,
+        action: &Action,
+        event: &AnalysisEvent,
+        input: &ProcessorInput,
+    ) -> ActionOutcome {
+        let started = Instant::now();
+
+        let result = match action {
+            Action::HttpCall {
+                url,
+                method,
+                headers,
+                body,
+                timeout_seconds,
+            } => {
+                self.http_call(
+                    url,
+                    method,
+                    headers,
+                    body.as_deref(),
+                    *timeout_seconds,
+                    event,
+                )
+                .await
+            }
+            Action::SnapshotBurst { count, interval_ms } => {
+                self.snapshot_burst(&event.source_id, *count, *interval_ms)
+                    .await
+            }
+            Action::StartRecording { duration_seconds } => {
+                self.record(&event.source_id, *duration_seconds).await
+            }
+            Action::DescribeFrame {
+                detail_level,
+                focus,
+            } => {
+                self.describe_frame(detail_level.as_deref(), focus.as_deref(), input)
+                    .await
+            }
+            _ => Err(Error::Validation(format!(
+                "Action '{}' has no side effects to execute",
+                action.action_type()
+            ))),
+        };
+
+        let outcome = ActionOutcome {
+            rule_id: rule_id.to_string(),
+            action_type: action.action_type().to_string(),
+            event_id: event.id.clone(),
+            success: result.is_ok(),
+            error: result.as_ref().err().map(|e| e.to_string()),
+            output: result.ok(),
+            duration_ms: started.elapsed().as_millis() as u64,
+        };
+
+        if outcome.success {
+            info!(
+                rule_id = %outcome.rule_id,
+                action_type = %outcome.action_type,
+                event_id = %outcome.event_id,
+                duration_ms = outcome.duration_ms,
+                "Rule action executed"
+            );
+        } else {
+            warn!(
+                rule_id = %outcome.rule_id,
+                action_type = %outcome.action_type,
+                event_id = %outcome.event_id,
+                error = outcome.error.as_deref().unwrap_or_default(),
+                "Rule action failed"
+            );
+        }
+
+        self.record_outcome(&outcome, &event.source_id).await;
+        outcome
+    }
+
+    /// Persist an outcome if a results repository is configured
+    async fn record_outcome(&self, outcome: &ActionOutcome, source_id: &str) {
+        let Some(repo) = &self.results_repo else {
+            return;
+        };
+
+        let request = CreateRuleActionResult {
+            rule_id: outcome.rule_id.clone(),
+            action_type: outcome.action_type.clone(),
+            event_id: outcome.event_id.clone(),
+            source_id: source_id.to_string(),
+            success: outcome.success,
+            output: outcome.output.clone(),
+            error_message: outcome.error.clone(),
+            duration_ms: outcome.duration_ms as i64,
+        };
+
+        if let Err(e) = repo.create(request).await {
+            warn!("Failed to record rule action result: {}", e);
+        }
+    }
+
+    /// Call an outbound HTTP endpoint with a templated URL and body
+    async fn http_call(
+        &self,
+        url: &str,
+        method: &str,
+        headers: &std::collections::HashMap<String, String>,
+        body: Option<&str>,
+        timeout_seconds: Option<u64>,
+        event: &AnalysisEvent,
+    ) -> Result<serde_json::Value> {
+        let url = render_template(url, event);
+        let method = reqwest::Method::from_bytes(method.to_uppercase().as_bytes())
+            .map_err(|_| Error::Validation(format!("Invalid HTTP method: {}", method)))?;
+        let timeout = Duration::from_secs(timeout_seconds.unwrap_or(DEFAULT_HTTP_TIMEOUT_SECONDS));
+
+        debug!("Calling {} {} for event {}", method, url, event.id);
+
+        let mut request = self
+            .http_client
+            .request(method.clone(), &url)
+            .timeout(timeout);
+        for (name, value) in headers {
+            request = request.header(name, render_template(value, event));
+        }
+        if let Some(body) = body {
+            request = request.body(render_template(body, event));
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| Error::Config(format!("HTTP call to {} failed: {}", url, e)))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(Error::Config(format!(
+                "HTTP call to {} returned status {}",
+                url, status
+            )));
+        }
+
+        Ok(serde_json::json!({
+            "method": method.as_str(),
+            "url": url,
+            "status": status.as_u16(),
+        }))
+    }
+
+    /// Take a burst of snapshots from the event's stream
+    async fn snapshot_burst(
+        &self,
+        stream_id: &str,
+        count: u32,
+        interval_ms: u64,
+    ) -> Result<serde_json::Value> {
+        let capture = self.capture_control()?;
+        if count == 0 || count > MAX_BURST_COUNT {
+            return Err(Error::Validation(format!(
+                "Snapshot burst count must be between 1 and {}",
+                MAX_BURST_COUNT
+            )));
+        }
+
+        let snapshot_ids = capture
+            .snapshot_burst(stream_id, count, Duration::from_millis(interval_ms))
+            .await?;
+
+        Ok(serde_json::json!({
+            "stream_id": stream_id,
+            "snapshot_ids": snapshot_ids,
+        }))
+    }
+
+    /// Record the event's stream for a fixed duration
+    async fn record(&self, stream_id: &str, duration_seconds: u64) -> Result<serde_json::Value> {
+        let capture = self.capture_control()?;
+        if duration_seconds == 0 || duration_seconds > MAX_RECORDING_SECONDS {
+            return Err(Error::Validation(format!(
+                "Recording duration must be between 1 and {} seconds",
+                MAX_RECORDING_SECONDS
+            )));
+        }
+
+        let uri = capture
+            .record(stream_id, Duration::from_secs(duration_seconds))
+            .await?;
+
+        Ok(serde_json::json!({
+            "stream_id": stream_id,
+            "duration_seconds": duration_seconds,
+            "uri": uri,
+        }))
+    }
+
+    /// Run the AI description processor on the frame that produced the event
+    async fn describe_frame(
+        &self,
+        detail_level: Option<&str>,
+        focus: Option<&str>,
+        input: &ProcessorInput,
+    ) -> Result<serde_json::Value> {
+        if input.frame_data.is_none() {
+            return Err(Error::Validation(
+                "No frame available to describe".to_string(),
+            ));
+        }
+
+        let config = serde_json::json!({
+            "detail_level": detail_level.unwrap_or("high"),
+            "focus": focus.unwrap_or("objects"),
+            "motion_only": false,
+        });
+
+        let mut processor =
+            AiDescriptionProcessor::with_ai_config(Some(config), self.ai_config.clone())?;
+        let mut input = input.clone();
+        input.frame_format.get_or_insert_with(|| "jpeg".to_string());
+
+        let events = processor.process(input).await?;
+        let description = events
+            .iter()
+            .find(|e| e.event_type == "frame_described")
+            .ok_or_else(|| Error::Config("AI description produced no result".to_string()))?;
+
+        Ok(serde_json::json!({
+            "description": description.description,
+            "objects_detected": description
+                .metadata
+                .get("objects_detected")
+                .cloned()
+                .unwrap_or_default(),
+            "confidence": description.confidence,
+        }))
+    }
+
+    fn capture_control(&self) -> Result<&Arc<dyn CaptureControl>> {
+        self.capture_control
+            .as_ref()
+            .ok_or_else(|| Error::Config("No capture control configured".to_string()))
+    }
+}
+
+/// Render `{{field}}` placeholders in an action template from an event
+///
+/// Supported fields are `id`, `event_type`, `severity`, `confidence`,
+/// `description`, `source_id`, `template_id`, `processor_name`, `timestamp`
+/// and `metadata.<key>`. Appending `|json` emits the value JSON-encoded, which
+/// is what request bodies usually want. Unknown fields render as empty strings.
+pub fn render_template(template: &str, event: &AnalysisEvent) -> String {
+    static PLACEHOLDER: OnceLock<Regex> = OnceLock::new();
+    let placeholder = PLACEHOLDER.get_or_init(|| {
+        Regex::new(r"\{\{\s*([A-Za-z0-9_.]+)\s*(\|\s*json\s*)?\}\}").expect("valid regex")
+    });
+
+    placeholder
+        .replace_all(template, |caps: &Captures| {
+            let value = template_value(event, &caps[1]);
+            if caps.get(2).is_some() {
+                value.to_string()
+            } else {
+                match value {
+                    serde_json::Value::String(s) => s,
+                    serde_json::Value::Null => String::new(),
+                    other => other.to_string(),
+                }
+            }
+        })
+        .into_owned()
+}
+
+fn template_value(event: &AnalysisEvent, field: &str) -> serde_json::Value {
+    match field {
+        "id" => event.id.clone().into(),
+        "event_type" => event.event_type.clone().into(),
+        "severity" => event.severity.as_str().into(),
+        "confidence" => event.confidence.into(),
+        "description" => event.description.clone().into(),
+        "source_id" => event.source_id.clone().into(),
+        "template_id" => event.template_id.clone().into(),
+        "processor_name" => event.processor_name.clone().into(),
+        "timestamp" => event.timestamp.to_rfc3339().into(),
+        _ => field
+            .strip_prefix("metadata.")
+            .and_then(|key| event.metadata.get(key))
+            .cloned()
+            .unwrap_or(serde_json::Value::Null),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventSeverity, ProcessorContext};
+    use bytes::Bytes;
+    use chrono::Utc;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use wiremock::matchers::{body_string, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[derive(Default)]
+    struct MockCapture {
+        calls: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl CaptureControl for MockCapture {
+        async fn snapshot_burst(
+            &self,
+            stream_id: &str,
+            count: u32,
+            _interval: Duration,
+        ) -> Result<Vec<String>> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("burst:{}:{}", stream_id, count));
+            Ok((0..count).map(|i| format!("snap_{}", i)).collect())
+        }
+
+        async fn record(&self, stream_id: &str, duration: Duration) -> Result<String> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("record:{}:{}", stream_id, duration.as_secs()));
+            Ok(format!("file:///recordings/{}.mp4", stream_id))
+        }
+    }
+
+    fn create_test_event() -> AnalysisEvent {
+        AnalysisEvent::new(
+            "test_template".to_string(),
+            "person_detected".to_string(),
+            EventSeverity::High,
+            0.9,
+            "Person at \"front\" door".to_string(),
+            "ai_description".to_string(),
+            "camera_01".to_string(),
+        )
+        .with_metadata("zone".to_string(), "porch".into())
+    }
+
+    fn create_test_input(frame: Option<Bytes>) -> ProcessorInput {
+        ProcessorInput {
+            template_id: "test_template".to_string(),
+            frame_data: frame,
+            frame_format: Some("jpeg".to_string()),
+            text_content: None,
+            context: ProcessorContext::new("camera_01".to_string()),
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_render_template() {
+        let event = create_test_event();
+        let rendered = render_template(
+            "{{ event_type }} on {{source_id}} in {{metadata.zone}}{{missing}}: {{description|json}}",
+            &event,
+        );
+        assert_eq!(
+            rendered,
+            "person_detected on camera_01 in porch: \"Person at \\\"front\\\" door\""
+        );
+    }
+
+    #[tokio::test]
+    async fn test_http_call_action() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/lights/camera_01"))
+            .and(header("x-event", "person_detected"))
+            .and(body_string(r#"{"on":true,"severity":"high"}"#))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let action = Action::HttpCall {
+            url: format!("{}/lights/{{{{source_id}}}}", server.uri()),
+            method: "post".to_string(),
+            headers: HashMap::from([("x-event".to_string(), "{{event_type}}".to_string())]),
+            body: Some(r#"{"on":true,"severity":{{severity|json}}}"#.to_string()),
+            timeout_seconds: Some(5),
+        };
+
+        let executor = ActionExecutor::new();
+        let outcome = executor
+            .execute(
+                "lights",
+                &action,
+                &create_test_event(),
+                &create_test_input(None),
+            )
+            .await;
+
+        assert!(outcome.success, "{:?}", outcome.error);
+        assert_eq!(outcome.action_type, "http_call");
+        assert_eq!(outcome.output.unwrap()["status"], 200);
+    }
+
+    #[tokio::test]
+    async fn test_http_call_failure_status() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&server)
+            .await;
+
+        let action = Action::HttpCall {
+            url: server.uri(),
+            method: "GET".to_string(),
+            headers: HashMap::new(),
+            body: None,
+            timeout_seconds: None,
+        };
+
+        let outcome = ActionExecutor::new()
+            .execute(
+                "lights",
+                &action,
+                &create_test_event(),
+                &create_test_input(None),
+            )
+            .await;
+
+        assert!(!outcome.success);
+        assert!(outcome.error.unwrap().contains("500"));
+    }
+
+    #[tokio::test]
+    async fn test_capture_actions() {
+        let capture = Arc::new(MockCapture::default());
+        let mut executor = ActionExecutor::new();
+        let event = create_test_event();
+        let input = create_test_input(None);
+
+        // Without capture control the actions fail rather than panic
+        let burst = Action::SnapshotBurst {
+            count: 3,
+            interval_ms: 100,
+        };
+        assert!(!executor.execute("r", &burst, &event, &input).await.success);
+
+        executor.set_capture_control(capture.clone());
+        let outcome = executor.execute("r", &burst, &event, &input).await;
+        assert!(outcome.success);
+        assert_eq!(outcome.output.unwrap()["snapshot_ids"][2], "snap_2");
+
+        let record = Action::StartRecording {
+            duration_seconds: 30,
+        };
+        let outcome = executor.execute("r", &record, &event, &input).await;
+        assert!(outcome.success);
+
+        let too_long = Action::StartRecording {
+            duration_seconds: MAX_RECORDING_SECONDS + 1,
+        };
+        assert!(
+            !executor
+                .execute("r", &too_long, &event, &input)
+                .await
+                .success
+        );
+
+        assert_eq!(
+            *capture.calls.lock().unwrap(),
+            vec!["burst:camera_01:3", "record:camera_01:30"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_describe_frame_action() {
+        let executor = ActionExecutor::new();
+        let event = create_test_event();
+        let action = Action::DescribeFrame {
+            detail_level: None,
+            focus: None,
+        };
+
+        let outcome = executor
+            .execute("r", &action, &event, &create_test_input(None))
+            .await;
+        assert!(!outcome.success);
+
+        let frame = Bytes::from(vec![0u8; 1000]);
+        let outcome = executor
+            .execute("r", &action, &event, &create_test_input(Some(frame)))
+            .await;
+        assert!(outcome.success, "{:?}", outcome.error);
+        let output = outcome.output.unwrap();
+        assert!(!output["description"].as_str().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,791 @@
+//! ABOUTME: Scheduled digest reports summarizing each stream's events and AI descriptions
+//! ABOUTME: Stores the report with key frames and sends it as HTML through notification channels
+
+use async_trait::async_trait;
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Duration, DurationRound, SecondsFormat, Utc};
+use gl_ai::{AiClient, SummarizeRequest};
+use gl_core::{Error, Result};
+use gl_db::{
+    AnalysisEvent, AnalysisEventRepository, CreateDigestReport, Db, DigestReport,
+    DigestReportRepository, SnapshotRepository, StreamRepository,
+};
+use gl_notify::{Notification, NotificationChannel, NotificationKind, NotificationManager};
+use gl_scheduler::{JobContext, JobDefinition, JobHandler};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+
+/// Scheduler job type that produces digest reports
+pub const JOB_TYPE: &str = "digest_report";
+
+/// Most events read for one report; busier periods are summarized from the first ones
+const MAX_EVENTS: i64 = 20_000;
+
+/// Key frames larger than this are left out so the email stays deliverable
+const MAX_KEY_FRAME_BYTES: usize = 512 * 1024;
+
+/// AI descriptions quoted per stream
+const MAX_DESCRIPTIONS: usize = 3;
+
+/// How much time a digest covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DigestPeriod {
+    Daily,
+    Weekly,
+}
+
+impl DigestPeriod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Daily => "daily",
+            Self::Weekly => "weekly",
+        }
+    }
+
+    pub fn duration(&self) -> Duration {
+        match self {
+            Self::Daily => Duration::days(1),
+            Self::Weekly => Duration::weeks(1),
+        }
+    }
+}
+
+/// Digest configured under `notifications.digests`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestConfig {
+    /// Identifier of the digest, kept stable so its job and reports line up across restarts
+    pub name: String,
+    #[serde(default = "default_period")]
+    pub period: DigestPeriod,
+    /// Cron expression with seconds; 07:00 UTC daily, or Mondays for weekly digests, by default
+    #[serde(default)]
+    pub schedule: Option<String>,
+    /// Stream IDs or names to cover; every stream when empty
+    #[serde(default)]
+    pub streams: Vec<String>,
+    /// Where to send each report; reports are only stored when empty
+    #[serde(default)]
+    pub channels: Vec<NotificationChannel>,
+    /// Frames embedded per stream, taken near its most severe events
+    #[serde(default = "default_key_frames")]
+    pub key_frames_per_stream: usize,
+}
+
+fn default_period() -> DigestPeriod {
+    DigestPeriod::Daily
+}
+
+fn default_key_frames() -> usize {
+    2
+}
+
+impl DigestConfig {
+    /// Cron schedule the digest runs on
+    pub fn schedule(&self) -> String {
+        self.schedule.clone().unwrap_or_else(|| match self.period {
+            DigestPeriod::Daily => "0 0 7 * * *".to_string(),
+            DigestPeriod::Weekly => "0 0 7 * * Mon".to_string(),
+        })
+    }
+
+    /// Scheduler job that produces this digest
+    pub fn job_definition(&self) -> JobDefinition {
+        let parameters = serde_json::to_value(self).unwrap_or_default();
+
+        let mut job = JobDefinition::new(
+            format!("Digest {}", self.name),
+            JOB_TYPE.to_string(),
+            self.schedule(),
+            parameters,
+            "system".to_string(),
+        )
+        .with_description(format!("{} digest report", self.period.as_str()))
+        .with_max_retries(0)
+        .with_tags(vec![JOB_TYPE.to_string()]);
+        job.id = Self::job_id(&self.name);
+        job
+    }
+
+    /// Job ID of a digest, stable across restarts
+    pub fn job_id(name: &str) -> String {
+        format!("{}_{}", JOB_TYPE, name)
+    }
+
+    fn covers(&self, stream_id: &str, stream_name: &str) -> bool {
+        self.streams.is_empty()
+            || self
+                .streams
+                .iter()
+                .any(|s| s == stream_id || s.eq_ignore_ascii_case(stream_name))
+    }
+}
+
+/// Activity of one stream over a digest period
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StreamDigest {
+    pub stream_id: String,
+    pub name: String,
+    pub events: usize,
+    /// Frames the vision model described
+    pub ai_descriptions: usize,
+    pub by_type: BTreeMap<String, usize>,
+    pub by_severity: BTreeMap<String, usize>,
+    /// Latest distinct AI descriptions
+    #[serde(skip)]
+    descriptions: Vec<String>,
+    #[serde(skip)]
+    key_frames: Vec<KeyFrame>,
+}
+
+/// Snapshot embedded in the report
+#[derive(Debug, Clone)]
+struct KeyFrame {
+    captured_at: String,
+    caption: String,
+    data_uri: String,
+}
+
+/// Rank of a severity string, higher is more severe
+fn severity_rank(severity: &str) -> u8 {
+    match severity {
+        "critical" => 5,
+        "high" => 4,
+        "medium" => 3,
+        "low" => 2,
+        "info" => 1,
+        _ => 0,
+    }
+}
+
+/// Escape text for HTML element content and attribute values
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Count a stream's events and keep the latest AI descriptions
+fn tally(stream_id: &str, name: &str, events: &[&AnalysisEvent]) -> StreamDigest {
+    let mut digest = StreamDigest {
+        stream_id: stream_id.to_string(),
+        name: name.to_string(),
+        events: events.len(),
+        ..Default::default()
+    };
+    for event in events {
+        *digest.by_type.entry(event.event_type.clone()).or_default() += 1;
+        *digest
+            .by_severity
+            .entry(event.severity.clone())
+            .or_default() += 1;
+        if event.processor_name == "ai_description" {
+            digest.ai_descriptions += 1;
+        }
+    }
+    let mut seen = HashSet::new();
+    digest.descriptions = events
+        .iter()
+        .rev()
+        .filter(|event| event.processor_name == "ai_description")
+        .map(|event| event.description.trim().to_string())
+        .filter(|description| !description.is_empty() && seen.insert(description.clone()))
+        .take(MAX_DESCRIPTIONS)
+        .collect();
+    digest
+}
+
+/// One line per active stream, e.g. "Front gate: 12 events (8 motion_detected, 4 person_detected)"
+fn count_lines(streams: &[StreamDigest]) -> Vec<String> {
+    streams
+        .iter()
+        .filter(|stream| stream.events > 0)
+        .map(|stream| {
+            let mut types: Vec<_> = stream.by_type.iter().collect();
+            types.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+            let types = types
+                .iter()
+                .map(|(event_type, count)| format!("{} {}", count, event_type))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("{}: {} events ({})", stream.name, stream.events, types)
+        })
+        .collect()
+}
+
+/// Narrative written from the counts alone, used when no AI model is available
+fn plain_narrative(streams: &[StreamDigest]) -> String {
+    let total: usize = streams.iter().map(|s| s.events).sum();
+    let active: Vec<&StreamDigest> = streams.iter().filter(|s| s.events > 0).collect();
+    if active.is_empty() {
+        return format!(
+            "A quiet period: none of the {} streams recorded any events.",
+            streams.len()
+        );
+    }
+
+    let mut narrative = format!(
+        "{} of {} streams recorded {} events.",
+        active.len(),
+        streams.len(),
+        total
+    );
+    let busiest = active
+        .iter()
+        .max_by_key(|s| s.events)
+        .expect("at least one active stream");
+    if let Some((event_type, count)) = busiest.by_type.iter().max_by_key(|(_, count)| **count) {
+        narrative.push_str(&format!(
+            " {} was busiest with {} events, mostly {} ({}).",
+            busiest.name, busiest.events, event_type, count
+        ));
+    }
+    let serious: usize = active
+        .iter()
+        .flat_map(|s| s.by_severity.iter())
+        .filter(|(severity, _)| severity_rank(severity) >= severity_rank("high"))
+        .map(|(_, count)| count)
+        .sum();
+    if serious > 0 {
+        narrative.push_str(&format!(" {} were high or critical severity.", serious));
+    }
+    narrative
+}
+
+fn render_html(
+    title: &str,
+    from: &str,
+    to: &str,
+    narrative: &str,
+    streams: &[StreamDigest],
+) -> String {
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{title}</title></head>\n\
+         <body style=\"font-family:sans-serif;color:#222;max-width:720px\">\n\
+         <h1>{title}</h1>\n<p style=\"color:#666\">{from} to {to} (UTC)</p>\n",
+        title = escape_html(title),
+        from = escape_html(from),
+        to = escape_html(to),
+    );
+    for paragraph in narrative.split("\n\n").filter(|p| !p.trim().is_empty()) {
+        html.push_str(&format!("<p>{}</p>\n", escape_html(paragraph.trim())));
+    }
+
+    for stream in streams.iter().filter(|s| s.events > 0) {
+        html.push_str(&format!(
+            "<h2>{}</h2>\n<p>{} events, {} AI descriptions</p>\n<table style=\"border-collapse:collapse\">\n",
+            escape_html(&stream.name),
+            stream.events,
+            stream.ai_descriptions
+        ));
+        for (event_type, count) in &stream.by_type {
+            html.push_str(&format!(
+                "<tr><td style=\"padding:2px 12px 2px 0\">{}</td><td>{}</td></tr>\n",
+                escape_html(event_type),
+                count
+            ));
+        }
+        html.push_str("</table>\n");
+        if !stream.descriptions.is_empty() {
+            html.push_str("<ul>\n");
+            for description in &stream.descriptions {
+                html.push_str(&format!("<li>{}</li>\n", escape_html(description)));
+            }
+            html.push_str("</ul>\n");
+        }
+        for frame in &stream.key_frames {
+            html.push_str(&format!(
+                "<figure style=\"margin:8px 0\"><img src=\"{}\" width=\"480\" alt=\"{}\">\
+                 <figcaption style=\"color:#666\">{} &middot; {}</figcaption></figure>\n",
+                frame.data_uri,
+                escape_html(&frame.caption),
+                escape_html(&frame.captured_at),
+                escape_html(&frame.caption)
+            ));
+        }
+    }
+
+    let quiet: Vec<String> = streams
+        .iter()
+        .filter(|s| s.events == 0)
+        .map(|s| escape_html(&s.name))
+        .collect();
+    if !quiet.is_empty() {
+        html.push_str(&format!(
+            "<p style=\"color:#666\">No activity: {}</p>\n",
+            quiet.join(", ")
+        ));
+    }
+    html.push_str("</body></html>\n");
+    html
+}
+
+/// Snapshots taken near a stream's most severe events, as data URIs
+async fn key_frames(
+    db: &Db,
+    stream_id: &str,
+    events: &[&AnalysisEvent],
+    count: usize,
+    from: &str,
+    to: &str,
+) -> Vec<KeyFrame> {
+    let mut ranked: Vec<&&AnalysisEvent> = events.iter().collect();
+    ranked.sort_by(|a, b| {
+        severity_rank(&b.severity)
+            .cmp(&severity_rank(&a.severity))
+            .then(b.confidence.total_cmp(&a.confidence))
+    });
+
+    let snapshots = SnapshotRepository::new(db.pool());
+    let mut seen = HashSet::new();
+    let mut frames = Vec::new();
+    // Neighbouring events often share a snapshot, so look a little further than needed
+    for event in ranked.into_iter().take(count * 3) {
+        if frames.len() >= count {
+            break;
+        }
+        let snapshot = match snapshots
+            .find_nearest(stream_id, &event.created_at, from, to)
+            .await
+        {
+            Ok(Some(snapshot)) => snapshot,
+            Ok(None) => break,
+            Err(e) => {
+                warn!(stream_id = %stream_id, error = %e, "Failed to find key frame");
+                break;
+            }
+        };
+        if !seen.insert(snapshot.id.clone()) || snapshot.file_size as usize > MAX_KEY_FRAME_BYTES {
+            continue;
+        }
+        match tokio::fs::read(&snapshot.file_path).await {
+            Ok(data) => frames.push(KeyFrame {
+                captured_at: snapshot.captured_at,
+                caption: event.description.clone(),
+                data_uri: format!(
+                    "data:{};base64,{}",
+                    snapshot.content_type,
+                    general_purpose::STANDARD.encode(data)
+                ),
+            }),
+            Err(e) => {
+                warn!(snapshot_id = %snapshot.id, error = %e, "Key frame unreadable, skipping")
+            }
+        }
+    }
+    frames
+}
+
+/// Build and store the report for `[from, to)`
+///
+/// The narrative comes from the AI client when one is given, falling back to one
+/// written from the counts if it fails.
+pub async fn generate_report(
+    db: &Db,
+    client: Option<&dyn AiClient>,
+    config: &DigestConfig,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<DigestReport> {
+    let from = from.to_rfc3339_opts(SecondsFormat::Secs, true);
+    let to = to.to_rfc3339_opts(SecondsFormat::Secs, true);
+
+    let streams: Vec<(String, String)> = StreamRepository::new(db.pool())
+        .list(None, 0, 10_000)
+        .await?
+        .into_iter()
+        .filter(|stream| config.covers(&stream.id, &stream.name))
+        .map(|stream| (stream.id, stream.name))
+        .collect();
+    let events = AnalysisEventRepository::new(db.clone())
+        .list_range(None, &from, &to, MAX_EVENTS)
+        .await?;
+    debug!(name = %config.name, streams = streams.len(), events = events.len(), "Building digest");
+
+    let mut digests = Vec::with_capacity(streams.len());
+    for (stream_id, name) in &streams {
+        let stream_events: Vec<&AnalysisEvent> = events
+            .iter()
+            .filter(|event| &event.template_id == stream_id)
+            .collect();
+        let mut digest = tally(stream_id, name, &stream_events);
+        if config.key_frames_per_stream > 0 && !stream_events.is_empty() {
+            digest.key_frames = key_frames(
+                db,
+                stream_id,
+                &stream_events,
+                config.key_frames_per_stream,
+                &from,
+                &to,
+            )
+            .await;
+        }
+        digests.push(digest);
+    }
+    // Busiest streams first
+    digests.sort_by(|a, b| b.events.cmp(&a.events).then(a.name.cmp(&b.name)));
+
+    let counts = count_lines(&digests);
+    let narrative = match client {
+        Some(client) if !counts.is_empty() => {
+            let mut facts = vec![format!("Camera activity from {} to {}.", from, to)];
+            for (line, stream) in counts.iter().zip(&digests) {
+                facts.push(line.clone());
+                for description in &stream.descriptions {
+                    facts.push(format!("{} saw: {}", stream.name, description));
+                }
+            }
+            match client
+                .summarize(SummarizeRequest {
+                    text: facts.join("\n"),
+                    max_length: Some(600),
+                    style: Some("brief".to_string()),
+                    stream_id: None,
+                })
+                .await
+            {
+                Ok(response) if !response.summary.trim().is_empty() => response.summary,
+                Ok(_) => plain_narrative(&digests),
+                Err(e) => {
+                    warn!(name = %config.name, error = %e, "AI digest summary failed, using counts");
+                    plain_narrative(&digests)
+                }
+            }
+        }
+        _ => plain_narrative(&digests),
+    };
+
+    let title = format!(
+        "Glimpser {} digest: {}",
+        config.period.as_str(),
+        config.name
+    );
+    let html = render_html(&title, &from, &to, &narrative, &digests);
+    let stats = serde_json::json!({
+        "total_events": events.len(),
+        "streams": digests,
+    });
+
+    DigestReportRepository::new(db.clone())
+        .create(CreateDigestReport {
+            name: config.name.clone(),
+            period: config.period.as_str().to_string(),
+            period_start: from,
+            period_end: to,
+            summary: narrative,
+            html,
+            stats,
+        })
+        .await
+}
+
+/// Notification carrying a report: the narrative and counts as text, the full report as HTML
+pub fn report_notification(
+    report: &DigestReport,
+    channels: Vec<NotificationChannel>,
+) -> Notification {
+    let streams: Vec<StreamDigest> =
+        serde_json::from_value(report.stats["streams"].clone()).unwrap_or_default();
+    let mut body = report.summary.clone();
+    for line in count_lines(&streams) {
+        body.push('\n');
+        body.push_str(&line);
+    }
+
+    Notification::new(
+        NotificationKind::Info,
+        format!("Glimpser {} digest: {}", report.period, report.name),
+        body,
+        channels,
+    )
+    .with_html(report.html.clone())
+    .with_metadata("digest_report_id".to_string(), report.id.clone())
+}
+
+/// Scheduler job that builds a digest for the period just ended and sends it
+pub struct DigestJob {
+    client: Option<Arc<dyn AiClient>>,
+    notifications: NotificationManager,
+}
+
+impl DigestJob {
+    pub fn new(client: Option<Arc<dyn AiClient>>, notifications: NotificationManager) -> Self {
+        Self {
+            client,
+            notifications,
+        }
+    }
+
+    fn parse_parameters(parameters: &serde_json::Value) -> Result<DigestConfig> {
+        let config: DigestConfig = serde_json::from_value(parameters.clone())
+            .map_err(|e| Error::Validation(format!("Invalid digest parameters: {}", e)))?;
+        if config.name.trim().is_empty() {
+            return Err(Error::Validation("Digest name cannot be empty".to_string()));
+        }
+        Ok(config)
+    }
+}
+
+#[async_trait]
+impl JobHandler for DigestJob {
+    async fn execute(&self, context: JobContext) -> Result<serde_json::Value> {
+        let config = Self::parse_parameters(&context.parameters)?;
+        // Whole minutes, so a late start doesn't leave gaps or overlaps between reports
+        let to = Utc::now()
+            .duration_trunc(Duration::minutes(1))
+            .map_err(|e| Error::Validation(format!("Invalid digest time: {}", e)))?;
+        let from = to - config.period.duration();
+
+        let report =
+            generate_report(&context.db, self.client.as_deref(), &config, from, to).await?;
+
+        let mut delivery_error = None;
+        if !config.channels.is_empty() {
+            let notification = report_notification(&report, config.channels.clone());
+            if let Err(e) = self.notifications.send(&notification).await {
+                warn!(name = %config.name, report_id = %report.id, error = %e, "Failed to deliver digest");
+                delivery_error = Some(e.to_string());
+                DigestReportRepository::new(context.db.clone())
+                    .set_delivery_error(&report.id, delivery_error.as_deref())
+                    .await?;
+            }
+        }
+
+        info!(name = %config.name, report_id = %report.id, "Digest report generated");
+        Ok(serde_json::json!({
+            "report_id": report.id,
+            "period_start": report.period_start,
+            "period_end": report.period_end,
+            "total_events": report.stats["total_events"],
+            "delivered": !config.channels.is_empty() && delivery_error.is_none(),
+            "delivery_error": delivery_error,
+        }))
+    }
+
+    fn job_type(&self) -> &'static str {
+        JOB_TYPE
+    }
+
+    fn validate_parameters(&self, parameters: &serde_json::Value) -> Result<()> {
+        Self::parse_parameters(parameters).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gl_db::{
+        CreateAnalysisEvent, CreateSnapshotRequest, CreateStreamRequest, CreateUserRequest,
+        UserRepository,
+    };
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_config_defaults_and_job_definition() {
+        let config: DigestConfig = serde_json::from_value(serde_json::json!({
+            "name": "morning",
+            "channels": [{"Email": {"to": ["manager@example.com"]}}]
+        }))
+        .unwrap();
+        assert_eq!(config.period, DigestPeriod::Daily);
+        assert_eq!(config.schedule(), "0 0 7 * * *");
+        assert_eq!(config.key_frames_per_stream, 2);
+
+        let job = config.job_definition();
+        assert_eq!(job.id, "digest_report_morning");
+        job.validate().unwrap();
+        let params = DigestJob::parse_parameters(&job.parameters).unwrap();
+        assert_eq!(params.channels.len(), 1);
+
+        assert!(DigestJob::parse_parameters(&serde_json::json!({"name": " "})).is_err());
+    }
+
+    /// Notifier that keeps what it was asked to send
+    #[derive(Default)]
+    struct CapturingNotifier {
+        sent: Mutex<Vec<Notification>>,
+    }
+
+    #[async_trait]
+    impl gl_notify::Notifier for CapturingNotifier {
+        async fn send(&self, msg: &Notification) -> gl_notify::Result<()> {
+            self.sent.lock().unwrap().push(msg.clone());
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            "email"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_job_builds_stores_and_sends_report() {
+        let dir = std::env::temp_dir().join(format!("glimpser_digest_{}", gl_core::Id::new()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let db = Db::new(dir.join("test.db").to_str().unwrap())
+            .await
+            .unwrap();
+        let user = UserRepository::new(db.pool())
+            .create(CreateUserRequest {
+                username: "digest".to_string(),
+                email: "digest@example.com".to_string(),
+                password_hash: "hash".to_string(),
+            })
+            .await
+            .unwrap();
+        let mut stream_ids = Vec::new();
+        for name in ["Front gate", "Loading dock"] {
+            let stream = StreamRepository::new(db.pool())
+                .create(CreateStreamRequest {
+                    user_id: user.id.clone(),
+                    name: name.to_string(),
+                    description: None,
+                    config: "{}".to_string(),
+                    is_default: false,
+                })
+                .await
+                .unwrap();
+            stream_ids.push(stream.id);
+        }
+
+        // An hour ago, inside the day the job reports on
+        let earlier = (Utc::now() - Duration::hours(1)).to_rfc3339_opts(SecondsFormat::Secs, true);
+        let frame = dir.join("frame.jpg");
+        std::fs::write(&frame, b"fake_jpeg").unwrap();
+        SnapshotRepository::new(db.pool())
+            .create(CreateSnapshotRequest {
+                stream_id: stream_ids[0].clone(),
+                user_id: user.id.clone(),
+                file_path: frame.to_str().unwrap().to_string(),
+                storage_uri: String::new(),
+                content_type: "image/jpeg".to_string(),
+                width: None,
+                height: None,
+                file_size: 9,
+                checksum: None,
+                etag: None,
+                captured_at: earlier.clone(),
+                perceptual_hash: None,
+            })
+            .await
+            .unwrap();
+        let events = AnalysisEventRepository::new(db.clone());
+        for (event_type, severity, processor, description) in [
+            ("motion_detected", "low", "motion", "Motion in zone A"),
+            (
+                "person_detected",
+                "high",
+                "ai_description",
+                "A courier <b>at</b> the gate",
+            ),
+            (
+                "person_detected",
+                "medium",
+                "ai_description",
+                "A courier <b>at</b> the gate",
+            ),
+        ] {
+            events
+                .create(CreateAnalysisEvent {
+                    template_id: stream_ids[0].clone(),
+                    event_type: event_type.to_string(),
+                    severity: severity.to_string(),
+                    confidence: 0.9,
+                    description: description.to_string(),
+                    metadata: None,
+                    processor_name: processor.to_string(),
+                    source_id: stream_ids[0].clone(),
+                    should_notify: false,
+                    suggested_actions: None,
+                })
+                .await
+                .unwrap();
+        }
+        sqlx::query("UPDATE analysis_events SET created_at = ?")
+            .bind(&earlier)
+            .execute(db.pool())
+            .await
+            .unwrap();
+
+        let notifier = Arc::new(CapturingNotifier::default());
+        let mut notifications = NotificationManager::new();
+        notifications.register_adapter("email".to_string(), notifier.clone());
+        let job = DigestJob::new(None, notifications);
+        let config: DigestConfig = serde_json::from_value(serde_json::json!({
+            "name": "morning",
+            "channels": [{"Email": {"to": ["manager@example.com"]}}]
+        }))
+        .unwrap();
+
+        let output = job
+            .execute(JobContext::new(
+                DigestConfig::job_id("morning"),
+                config.job_definition().parameters,
+                db.clone(),
+                Arc::new(NoCapture),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(output["total_events"], 3);
+        assert_eq!(output["delivered"], true);
+
+        let report = DigestReportRepository::new(db.clone())
+            .get_by_id(output["report_id"].as_str().unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            report.summary,
+            "1 of 2 streams recorded 3 events. Front gate was busiest with 3 events, mostly person_detected (2). 1 were high or critical severity."
+        );
+        assert_eq!(report.stats["streams"][0]["ai_descriptions"], 2);
+        assert_eq!(report.stats["streams"][1]["events"], 0);
+        // Key frame embedded once, descriptions escaped and de-duplicated
+        assert_eq!(
+            report
+                .html
+                .matches("data:image/jpeg;base64,ZmFrZV9qcGVn")
+                .count(),
+            1
+        );
+        assert_eq!(
+            report
+                .html
+                .matches("<li>A courier &lt;b&gt;at&lt;/b&gt; the gate</li>")
+                .count(),
+            1
+        );
+        assert!(report.html.contains("No activity: Loading dock"));
+
+        let sent = notifier.sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].title, "Glimpser daily digest: morning");
+        assert!(sent[0]
+            .body
+            .ends_with("Front gate: 3 events (2 person_detected, 1 motion_detected)"));
+        assert_eq!(sent[0].html.as_deref(), Some(report.html.as_str()));
+        drop(sent);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    struct NoCapture;
+
+    #[async_trait]
+    impl gl_scheduler::CaptureService for NoCapture {
+        async fn capture(&self, stream_id: &str) -> Result<gl_scheduler::CaptureResult> {
+            Err(Error::NotFound(stream_id.to_string()))
+        }
+    }
+}
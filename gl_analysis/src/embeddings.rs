@@ -0,0 +1,302 @@
+//! ABOUTME: Embeds stored snapshots with a CLIP-style model and searches them by text or image
+//! ABOUTME: Runs as a recurring scheduler job that indexes snapshots not embedded yet
+
+use async_trait::async_trait;
+use gl_ai::{Embedder, EmbeddingConfig};
+use gl_core::{Error, Result};
+use gl_db::{EmbeddingQuery, SnapshotEmbeddingRepository, SnapshotMatch};
+use gl_scheduler::{JobContext, JobDefinition, JobHandler};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+
+/// Scheduler job type that embeds new snapshots
+pub const JOB_TYPE: &str = "snapshot_embedding";
+
+/// What to look for in the snapshot archive
+#[derive(Debug, Clone)]
+pub enum SearchQuery {
+    /// Description such as "person with ladder"
+    Text(String),
+    /// Example image, JPEG or PNG
+    Image(Vec<u8>),
+    /// Snapshot already in the index to find similar ones to
+    Snapshot(String),
+}
+
+/// Parameters of the embedding job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct EmbeddingJobParams {
+    batch_size: u32,
+}
+
+/// Scheduler job that embeds the newest snapshots lacking a vector
+pub fn job_definition(config: &EmbeddingConfig) -> JobDefinition {
+    let parameters = serde_json::to_value(EmbeddingJobParams {
+        batch_size: config.batch_size,
+    })
+    .unwrap_or_default();
+
+    let mut job = JobDefinition::new(
+        "Snapshot embeddings".to_string(),
+        JOB_TYPE.to_string(),
+        config.schedule.clone(),
+        parameters,
+        "system".to_string(),
+    )
+    .with_description(format!("Index snapshots with {}", config.model))
+    .with_max_retries(0)
+    .with_tags(vec![JOB_TYPE.to_string()]);
+    job.id = JOB_TYPE.to_string();
+    job
+}
+
+/// Job handler that fills the snapshot embedding index
+pub struct SnapshotEmbeddingJob {
+    embedder: Arc<dyn Embedder>,
+}
+
+impl SnapshotEmbeddingJob {
+    pub fn new(embedder: Arc<dyn Embedder>) -> Self {
+        Self { embedder }
+    }
+
+    fn parse_parameters(parameters: &serde_json::Value) -> Result<EmbeddingJobParams> {
+        let params: EmbeddingJobParams = serde_json::from_value(parameters.clone())
+            .map_err(|e| Error::Validation(format!("Invalid embedding job parameters: {}", e)))?;
+        if params.batch_size == 0 {
+            return Err(Error::Validation(
+                "Embedding batch size must be at least 1".to_string(),
+            ));
+        }
+        Ok(params)
+    }
+}
+
+#[async_trait]
+impl JobHandler for SnapshotEmbeddingJob {
+    async fn execute(&self, context: JobContext) -> Result<serde_json::Value> {
+        let params = Self::parse_parameters(&context.parameters)?;
+        let model = self.embedder.model();
+        let repo = SnapshotEmbeddingRepository::new(context.db.clone());
+        let pending = repo.list_pending(model, params.batch_size).await?;
+
+        let mut embedded = 0;
+        let mut skipped = 0;
+        for snapshot in &pending {
+            let vector = match tokio::fs::read(&snapshot.file_path).await {
+                Ok(image) => match self.embedder.embed_image(&image).await {
+                    Ok(vector) => vector,
+                    // Undecodable images would fail again; other errors mean the model is failing
+                    Err(Error::Validation(e)) => {
+                        warn!(snapshot_id = %snapshot.id, error = %e, "Snapshot can't be embedded");
+                        Vec::new()
+                    }
+                    Err(e) => return Err(e),
+                },
+                Err(e) => {
+                    warn!(snapshot_id = %snapshot.id, file_path = %snapshot.file_path, error = %e, "Snapshot file unreadable, skipping");
+                    Vec::new()
+                }
+            };
+            if vector.is_empty() {
+                skipped += 1;
+            } else {
+                embedded += 1;
+            }
+            repo.upsert(&snapshot.id, model, &vector).await?;
+        }
+
+        if embedded > 0 {
+            info!(embedded, skipped, model = %model, "Embedded snapshots");
+        } else {
+            debug!(skipped, "No snapshots to embed");
+        }
+        Ok(serde_json::json!({
+            "model": model,
+            "embedded": embedded,
+            "skipped": skipped,
+        }))
+    }
+
+    fn job_type(&self) -> &'static str {
+        JOB_TYPE
+    }
+
+    fn validate_parameters(&self, parameters: &serde_json::Value) -> Result<()> {
+        Self::parse_parameters(parameters).map(|_| ())
+    }
+}
+
+/// Rank indexed snapshots by similarity to a query
+///
+/// Only snapshots embedded with the embedder's model are considered; `filters.model`
+/// is overwritten.
+pub async fn search_snapshots(
+    embedder: &dyn Embedder,
+    db: &gl_db::Db,
+    query: SearchQuery,
+    mut filters: EmbeddingQuery,
+) -> Result<Vec<SnapshotMatch>> {
+    let repo = SnapshotEmbeddingRepository::new(db.clone());
+    let vector = match query {
+        SearchQuery::Text(text) => embedder.embed_text(&text).await?,
+        SearchQuery::Image(image) => embedder.embed_image(&image).await?,
+        SearchQuery::Snapshot(snapshot_id) => repo
+            .find_vector(&snapshot_id, embedder.model())
+            .await?
+            .ok_or_else(|| {
+                Error::NotFound(format!("Snapshot {} is not indexed yet", snapshot_id))
+            })?,
+    };
+    filters.model = embedder.model().to_string();
+    repo.search(&vector, &filters).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gl_scheduler::{CaptureResult, CaptureService};
+    use image::{DynamicImage, ImageOutputFormat, Rgb, RgbImage};
+
+    struct NoCapture;
+
+    #[async_trait]
+    impl CaptureService for NoCapture {
+        async fn capture(&self, stream_id: &str) -> Result<CaptureResult> {
+            Err(Error::NotFound(stream_id.to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_job_indexes_snapshots_for_text_search() {
+        let dir = std::env::temp_dir().join(format!("glimpser_embed_{}", gl_core::Id::new()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let db = gl_db::Db::new(dir.join("test.db").to_str().unwrap())
+            .await
+            .unwrap();
+        let user = gl_db::UserRepository::new(db.pool())
+            .create(gl_db::CreateUserRequest {
+                username: "embed".to_string(),
+                email: "embed@example.com".to_string(),
+                password_hash: "hash".to_string(),
+            })
+            .await
+            .unwrap();
+        let stream = gl_db::StreamRepository::new(db.pool())
+            .create(gl_db::CreateStreamRequest {
+                user_id: user.id.clone(),
+                name: "Gate".to_string(),
+                description: None,
+                config: "{}".to_string(),
+                is_default: false,
+            })
+            .await
+            .unwrap();
+
+        let mut ids = Vec::new();
+        for (name, colour) in [
+            ("red", [200, 20, 20]),
+            ("blue", [20, 20, 200]),
+            ("gone", [0; 3]),
+        ] {
+            let file_path = dir.join(format!("{}.png", name));
+            if name != "gone" {
+                let mut png = Vec::new();
+                DynamicImage::ImageRgb8(RgbImage::from_pixel(4, 4, Rgb(colour)))
+                    .write_to(&mut png, ImageOutputFormat::Png)
+                    .unwrap();
+                std::fs::write(&file_path, png).unwrap();
+            }
+            let snapshot = gl_db::SnapshotRepository::new(db.pool())
+                .create(gl_db::CreateSnapshotRequest {
+                    stream_id: stream.id.clone(),
+                    user_id: user.id.clone(),
+                    file_path: file_path.to_string_lossy().to_string(),
+                    storage_uri: String::new(),
+                    content_type: "image/png".to_string(),
+                    width: Some(4),
+                    height: Some(4),
+                    file_size: 0,
+                    checksum: None,
+                    etag: None,
+                    captured_at: gl_core::time::now_iso8601(),
+                    perceptual_hash: None,
+                })
+                .await
+                .unwrap();
+            ids.push(snapshot.id);
+        }
+
+        let clip = test_support::onnx::colour_clip(&dir);
+        let config = EmbeddingConfig {
+            image_model_path: clip.image_model.to_string_lossy().to_string(),
+            text_model_path: clip.text_model.to_string_lossy().to_string(),
+            tokenizer_path: clip.tokenizer.to_string_lossy().to_string(),
+            image_size: 8,
+            ..Default::default()
+        };
+        let embedder: Arc<dyn Embedder> = Arc::from(gl_ai::create_embedder(&config).unwrap());
+        let job = SnapshotEmbeddingJob::new(embedder.clone());
+        let definition = job_definition(&config);
+        job.validate_parameters(&definition.parameters).unwrap();
+        assert!(job
+            .validate_parameters(&serde_json::json!({"batch_size": 0}))
+            .is_err());
+
+        let run = || {
+            JobContext::new(
+                definition.id.clone(),
+                definition.parameters.clone(),
+                db.clone(),
+                Arc::new(NoCapture),
+            )
+        };
+        let output = job.execute(run()).await.unwrap();
+        assert_eq!(
+            (output["embedded"].as_u64(), output["skipped"].as_u64()),
+            (Some(2), Some(1))
+        );
+        // Nothing is left to embed, including the snapshot whose file is gone
+        let output = job.execute(run()).await.unwrap();
+        assert_eq!(output["embedded"], 0);
+        assert_eq!(output["skipped"], 0);
+
+        let filters = EmbeddingQuery {
+            limit: 5,
+            ..Default::default()
+        };
+        let matches = search_snapshots(
+            embedder.as_ref(),
+            &db,
+            SearchQuery::Text("red truck at the gate".to_string()),
+            filters.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].snapshot_id, ids[0]);
+
+        let similar = search_snapshots(
+            embedder.as_ref(),
+            &db,
+            SearchQuery::Snapshot(ids[1].clone()),
+            filters.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(similar[0].snapshot_id, ids[1]);
+        assert!(matches!(
+            search_snapshots(
+                embedder.as_ref(),
+                &db,
+                SearchQuery::Snapshot(ids[2].clone()),
+                filters
+            )
+            .await,
+            Err(Error::NotFound(_))
+        ));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,539 @@
+//! ABOUTME: Per-stream health watchdog that notices offline, frozen, black and flapping cameras
+//! ABOUTME: Turns frame cadence, frame quality and reconnects into events on state transitions
+
+use crate::{AnalysisEvent, EventSeverity};
+use chrono::{DateTime, Duration, Utc};
+use gl_vision::FrameQuality;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+
+/// Processor name recorded on health events
+const PROCESSOR_NAME: &str = "stream_health";
+
+/// Thresholds for a stream's health checks; read from the `health` object of a stream config
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct HealthConfig {
+    pub enabled: bool,
+    /// Offline after this many seconds without a frame (at least three capture intervals)
+    pub offline_after_secs: u64,
+    /// Frozen after the picture hash has not changed for this many seconds
+    pub frozen_after_secs: u64,
+    /// Hash bits that may differ between frames still considered identical
+    pub frozen_hash_distance: u32,
+    /// Mean luminance below which a flat frame counts as black
+    pub black_luma: f64,
+    /// Mean luminance above which a flat frame counts as overexposed
+    pub overexposed_luma: f64,
+    /// Luminance spread below which a frame counts as flat
+    pub flat_stddev: f64,
+    /// Black or overexposed frames must persist this many seconds before alerting
+    pub exposure_after_secs: u64,
+    /// Unstable when the source reconnects this many times within the window
+    pub unstable_reconnects: usize,
+    pub unstable_window_secs: u64,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            offline_after_secs: 60,
+            // Static scenes can keep an identical hash for a while; don't alert on those quickly
+            frozen_after_secs: 15 * 60,
+            frozen_hash_distance: 0,
+            black_luma: 16.0,
+            overexposed_luma: 240.0,
+            flat_stddev: 10.0,
+            exposure_after_secs: 120,
+            unstable_reconnects: 5,
+            unstable_window_secs: 10 * 60,
+        }
+    }
+}
+
+/// Something wrong with a stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthIssue {
+    Offline,
+    Frozen,
+    Black,
+    Overexposed,
+    Unstable,
+}
+
+impl HealthIssue {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Offline => "offline",
+            Self::Frozen => "frozen",
+            Self::Black => "black",
+            Self::Overexposed => "overexposed",
+            Self::Unstable => "unstable",
+        }
+    }
+
+    /// Event type emitted when the issue is raised
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            Self::Offline => "stream_offline",
+            Self::Frozen => "stream_frozen",
+            Self::Black => "stream_black",
+            Self::Overexposed => "stream_overexposed",
+            Self::Unstable => "stream_unstable",
+        }
+    }
+
+    /// Recovery events reuse this severity so notification filters treat both ends alike
+    pub fn severity(&self) -> EventSeverity {
+        match self {
+            Self::Offline => EventSeverity::High,
+            _ => EventSeverity::Medium,
+        }
+    }
+}
+
+/// Tracks one stream and reports issues as they start and end
+///
+/// The monitor is a plain state machine: callers feed it frames, failures and the clock,
+/// and publish whatever events it returns.
+#[derive(Debug)]
+pub struct StreamHealthMonitor {
+    stream_id: String,
+    config: HealthConfig,
+    frame_interval: Duration,
+    started_at: DateTime<Utc>,
+    last_frame_at: Option<DateTime<Utc>>,
+    /// Active issues and when each was raised
+    active: BTreeMap<HealthIssue, DateTime<Utc>>,
+    /// Reference frame for the frozen check and when it was first seen
+    unchanged_since: Option<(FrameQuality, DateTime<Utc>)>,
+    dark_since: Option<DateTime<Utc>>,
+    bright_since: Option<DateTime<Utc>>,
+    failure_streak: u32,
+    reconnects: VecDeque<DateTime<Utc>>,
+    /// Last counter value from a source that reports its own reconnects
+    source_reconnects: Option<u64>,
+}
+
+impl StreamHealthMonitor {
+    pub fn new(
+        stream_id: impl Into<String>,
+        frame_interval: std::time::Duration,
+        config: HealthConfig,
+        now: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            stream_id: stream_id.into(),
+            config,
+            frame_interval: Duration::from_std(frame_interval).unwrap_or(Duration::zero()),
+            started_at: now,
+            last_frame_at: None,
+            active: BTreeMap::new(),
+            unchanged_since: None,
+            dark_since: None,
+            bright_since: None,
+            failure_streak: 0,
+            reconnects: VecDeque::new(),
+            source_reconnects: None,
+        }
+    }
+
+    pub fn config(&self) -> &HealthConfig {
+        &self.config
+    }
+
+    pub fn last_frame_at(&self) -> Option<DateTime<Utc>> {
+        self.last_frame_at
+    }
+
+    /// Issues currently raised, in a stable order
+    pub fn issues(&self) -> Vec<HealthIssue> {
+        self.active.keys().copied().collect()
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.active.is_empty()
+    }
+
+    /// Record a delivered frame
+    pub fn observe_frame(
+        &mut self,
+        quality: &FrameQuality,
+        at: DateTime<Utc>,
+    ) -> Vec<AnalysisEvent> {
+        let mut events = Vec::new();
+        self.last_frame_at = Some(at);
+
+        // Without a reconnect counter from the source, recovering from failures is the best
+        // sign that the camera dropped and came back
+        if self.failure_streak > 0 && self.source_reconnects.is_none() {
+            self.reconnects.push_back(at);
+        }
+        self.failure_streak = 0;
+        self.clear(HealthIssue::Offline, at, &mut events);
+
+        let frozen = match &self.unchanged_since {
+            Some((reference, _))
+                if reference.hash_distance(quality) <= self.config.frozen_hash_distance =>
+            {
+                true
+            }
+            _ => {
+                self.unchanged_since = Some((*quality, at));
+                false
+            }
+        };
+        let unchanged_for = self.unchanged_since.map(|(_, since)| at - since);
+        if frozen && unchanged_for >= Some(secs(self.config.frozen_after_secs)) {
+            self.raise(
+                HealthIssue::Frozen,
+                at,
+                format!(
+                    "Picture has not changed for {} minutes",
+                    unchanged_for.unwrap_or_default().num_minutes()
+                ),
+                &mut events,
+            );
+        } else if !frozen {
+            self.clear(HealthIssue::Frozen, at, &mut events);
+        }
+
+        let flat = quality.luma_stddev < self.config.flat_stddev;
+        let dark = flat && quality.mean_luma < self.config.black_luma;
+        let bright = flat && quality.mean_luma > self.config.overexposed_luma;
+        self.dark_since = dark.then(|| self.dark_since.unwrap_or(at));
+        self.bright_since = bright.then(|| self.bright_since.unwrap_or(at));
+        self.check_exposure(
+            HealthIssue::Black,
+            self.dark_since,
+            quality,
+            at,
+            &mut events,
+        );
+        self.check_exposure(
+            HealthIssue::Overexposed,
+            self.bright_since,
+            quality,
+            at,
+            &mut events,
+        );
+
+        events.extend(self.check_reconnects(at));
+        events
+    }
+
+    /// Record a capture attempt that produced no frame
+    pub fn observe_failure(&mut self, at: DateTime<Utc>) -> Vec<AnalysisEvent> {
+        self.failure_streak += 1;
+        self.check(at)
+    }
+
+    /// Record the reconnect counter of a source that keeps its own session open
+    pub fn observe_source_reconnects(
+        &mut self,
+        total: u64,
+        at: DateTime<Utc>,
+    ) -> Vec<AnalysisEvent> {
+        let previous = self.source_reconnects.replace(total).unwrap_or(total);
+        for _ in 0..total.saturating_sub(previous) {
+            self.reconnects.push_back(at);
+        }
+        self.check_reconnects(at)
+    }
+
+    /// Time-based checks; call periodically even when no frames arrive
+    pub fn check(&mut self, now: DateTime<Utc>) -> Vec<AnalysisEvent> {
+        let mut events = Vec::new();
+
+        let offline_after = secs(self.config.offline_after_secs).max(self.frame_interval * 3);
+        let silent_since = self.last_frame_at.unwrap_or(self.started_at);
+        if now - silent_since >= offline_after {
+            let description = match self.last_frame_at {
+                Some(_) => format!(
+                    "No frames for {} seconds",
+                    (now - silent_since).num_seconds()
+                ),
+                None => format!(
+                    "No frames since capture started {} seconds ago",
+                    (now - silent_since).num_seconds()
+                ),
+            };
+            self.raise(HealthIssue::Offline, now, description, &mut events);
+        }
+
+        events.extend(self.check_reconnects(now));
+        events
+    }
+
+    /// Raise a black/overexposed issue once the condition has lasted long enough
+    fn check_exposure(
+        &mut self,
+        issue: HealthIssue,
+        since: Option<DateTime<Utc>>,
+        quality: &FrameQuality,
+        at: DateTime<Utc>,
+        events: &mut Vec<AnalysisEvent>,
+    ) {
+        match since {
+            Some(first) if at - first >= secs(self.config.exposure_after_secs) => {
+                let description = match issue {
+                    HealthIssue::Black => "Picture is black",
+                    _ => "Picture is washed out",
+                };
+                if let Some(event) = self.raise(issue, at, description.to_string(), events) {
+                    event
+                        .metadata
+                        .insert("mean_luma".to_string(), round(quality.mean_luma).into());
+                }
+            }
+            Some(_) => {}
+            None => self.clear(issue, at, events),
+        }
+    }
+
+    fn check_reconnects(&mut self, now: DateTime<Utc>) -> Vec<AnalysisEvent> {
+        let mut events = Vec::new();
+        let window = secs(self.config.unstable_window_secs);
+        while self.reconnects.front().is_some_and(|&at| now - at > window) {
+            self.reconnects.pop_front();
+        }
+
+        let count = self.reconnects.len();
+        if self.config.unstable_reconnects > 0 && count >= self.config.unstable_reconnects {
+            let description = format!(
+                "Reconnected {} times in {} minutes",
+                count,
+                window.num_minutes()
+            );
+            self.raise(HealthIssue::Unstable, now, description, &mut events);
+        } else if count == 0 {
+            // Stay unstable until the window has passed without reconnects
+            self.clear(HealthIssue::Unstable, now, &mut events);
+        }
+        events
+    }
+
+    /// Returns the new event, or `None` if the issue was already raised
+    fn raise<'a>(
+        &mut self,
+        issue: HealthIssue,
+        at: DateTime<Utc>,
+        description: String,
+        events: &'a mut Vec<AnalysisEvent>,
+    ) -> Option<&'a mut AnalysisEvent> {
+        if self.active.contains_key(&issue) {
+            return None;
+        }
+        self.active.insert(issue, at);
+        events.push(self.event(issue, issue.event_type(), description, at));
+        events.last_mut()
+    }
+
+    fn clear(&mut self, issue: HealthIssue, at: DateTime<Utc>, events: &mut Vec<AnalysisEvent>) {
+        let Some(since) = self.active.remove(&issue) else {
+            return;
+        };
+        let duration = at - since;
+        let description = format!(
+            "Stream is no longer {} after {} minutes",
+            issue.as_str(),
+            duration.num_minutes()
+        );
+        events.push(
+            self.event(issue, "stream_recovered", description, at)
+                .with_metadata(
+                    "duration_seconds".to_string(),
+                    duration.num_seconds().into(),
+                ),
+        );
+    }
+
+    fn event(
+        &self,
+        issue: HealthIssue,
+        event_type: &str,
+        description: String,
+        at: DateTime<Utc>,
+    ) -> AnalysisEvent {
+        let mut event = AnalysisEvent::new(
+            self.stream_id.clone(),
+            event_type.to_string(),
+            issue.severity(),
+            1.0,
+            description,
+            PROCESSOR_NAME.to_string(),
+            self.stream_id.clone(),
+        )
+        .with_metadata("issue".to_string(), issue.as_str().into())
+        .with_metadata(
+            "last_frame_at".to_string(),
+            self.last_frame_at
+                .map(|t| t.to_rfc3339().into())
+                .unwrap_or(serde_json::Value::Null),
+        )
+        .with_notification(true);
+        event.timestamp = at;
+        event
+    }
+}
+
+fn secs(secs: u64) -> Duration {
+    Duration::seconds(secs.min(i64::MAX as u64) as i64)
+}
+
+fn round(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quality(mean_luma: f64, luma_stddev: f64, dhash: u64) -> FrameQuality {
+        FrameQuality {
+            mean_luma,
+            luma_stddev,
+            dhash,
+        }
+    }
+
+    fn monitor(now: DateTime<Utc>) -> StreamHealthMonitor {
+        StreamHealthMonitor::new(
+            "cam",
+            std::time::Duration::from_secs(5),
+            HealthConfig::default(),
+            now,
+        )
+    }
+
+    fn types(events: &[AnalysisEvent]) -> Vec<&str> {
+        events.iter().map(|e| e.event_type.as_str()).collect()
+    }
+
+    #[test]
+    fn offline_is_raised_once_and_cleared_by_a_frame() {
+        let start = Utc::now();
+        let mut health = monitor(start);
+        let scene = quality(120.0, 40.0, 0xF0F0);
+
+        assert!(health.observe_frame(&scene, start).is_empty());
+        assert!(health.check(start + Duration::seconds(30)).is_empty());
+
+        let events = health.check(start + Duration::seconds(61));
+        assert_eq!(types(&events), ["stream_offline"]);
+        assert_eq!(events[0].severity, EventSeverity::High);
+        assert!(health.check(start + Duration::seconds(120)).is_empty());
+        assert_eq!(health.issues(), [HealthIssue::Offline]);
+
+        let events = health.observe_frame(&scene, start + Duration::seconds(180));
+        assert_eq!(types(&events), ["stream_recovered"]);
+        assert_eq!(events[0].metadata["issue"], "offline");
+        assert_eq!(events[0].severity, EventSeverity::High);
+        assert!(health.is_healthy());
+    }
+
+    #[test]
+    fn a_stream_that_never_delivers_goes_offline() {
+        let start = Utc::now();
+        let mut health = monitor(start);
+        let events = health.observe_failure(start + Duration::seconds(90));
+        assert_eq!(types(&events), ["stream_offline"]);
+    }
+
+    #[test]
+    fn identical_pictures_become_frozen() {
+        let start = Utc::now();
+        let mut health = monitor(start);
+        let frame = quality(100.0, 30.0, 0xABCD);
+
+        for minute in 0..15 {
+            let at = start + Duration::minutes(minute);
+            assert!(health.observe_frame(&frame, at).is_empty());
+        }
+        let events = health.observe_frame(&frame, start + Duration::minutes(15));
+        assert_eq!(types(&events), ["stream_frozen"]);
+
+        let moved = quality(100.0, 30.0, 0xABCD ^ 0b111);
+        let events = health.observe_frame(&moved, start + Duration::minutes(16));
+        assert_eq!(types(&events), ["stream_recovered"]);
+        assert_eq!(events[0].metadata["issue"], "frozen");
+    }
+
+    #[test]
+    fn black_frames_must_persist_before_alerting() {
+        let start = Utc::now();
+        let mut health = monitor(start);
+
+        // Alternate hashes so the frozen check stays quiet
+        let black = |n: u64| quality(3.0, 1.0, n);
+        assert!(health.observe_frame(&black(1), start).is_empty());
+        assert!(health
+            .observe_frame(&black(2), start + Duration::seconds(60))
+            .is_empty());
+        let events = health.observe_frame(&black(3), start + Duration::seconds(120));
+        assert_eq!(types(&events), ["stream_black"]);
+        assert_eq!(events[0].metadata["mean_luma"], 3.0);
+
+        // A dark but detailed night scene is not black
+        let night = quality(12.0, 25.0, 4);
+        let events = health.observe_frame(&night, start + Duration::seconds(130));
+        assert_eq!(types(&events), ["stream_recovered"]);
+
+        let white = |n: u64| quality(250.0, 2.0, n);
+        health.observe_frame(&white(5), start + Duration::seconds(200));
+        let events = health.observe_frame(&white(6), start + Duration::seconds(320));
+        assert_eq!(types(&events), ["stream_overexposed"]);
+    }
+
+    #[test]
+    fn repeated_reconnects_mark_the_stream_unstable() {
+        let start = Utc::now();
+        let mut health = monitor(start);
+        let mut at = start;
+
+        for n in 0..5 {
+            at += Duration::seconds(30);
+            health.observe_failure(at);
+            at += Duration::seconds(5);
+            let events = health.observe_frame(&quality(100.0, 30.0, n), at);
+            if n < 4 {
+                assert!(
+                    events.is_empty(),
+                    "reconnect {} raised {:?}",
+                    n,
+                    types(&events)
+                );
+            } else {
+                assert_eq!(types(&events), ["stream_unstable"]);
+            }
+        }
+
+        let events = health.check(at + Duration::minutes(11));
+        assert!(types(&events).contains(&"stream_recovered"));
+        assert!(!health.issues().contains(&HealthIssue::Unstable));
+    }
+
+    #[test]
+    fn source_reported_reconnects_are_counted_by_delta() {
+        let start = Utc::now();
+        let mut health = monitor(start);
+
+        assert!(health.observe_source_reconnects(7, start).is_empty());
+        assert!(health
+            .observe_source_reconnects(10, start + Duration::seconds(10))
+            .is_empty());
+        let events = health.observe_source_reconnects(12, start + Duration::seconds(20));
+        assert_eq!(types(&events), ["stream_unstable"]);
+    }
+
+    #[test]
+    fn config_reads_partial_overrides() {
+        let config: HealthConfig =
+            serde_json::from_value(serde_json::json!({"frozen_after_secs": 3600})).unwrap();
+        assert_eq!(config.frozen_after_secs, 3600);
+        assert_eq!(config.offline_after_secs, 60);
+        assert!(config.enabled);
+    }
+}
@@ -7,7 +7,8 @@ use chrono::{DateTime, Datelike, Utc};
 use gl_core::{Id, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use tracing::{debug, info};
+use std::sync::Arc;
+use tracing::{debug, info, warn};
 
 /// Helper trait for string title case conversion
 trait ToTitleCase {
@@ -22,7 +23,8 @@ impl ToTitleCase for str {
                 match chars.next() {
                     None => String::new(),
                     Some(first) => {
-                        first.to_uppercase().collect::<String>() + &chars.as_str().to_lowercase()
+                        first.to_uppercase().collect::<String>()
+                            + chars.as_str().to_lowercase().as_str()
                     }
                 }
             })
@@ -31,12 +33,29 @@ impl ToTitleCase for str {
     }
 }
 
+pub mod actions;
+pub mod digest;
+pub mod embeddings;
+pub mod health;
 pub mod pipeline;
 pub mod processors;
+pub mod questions;
+pub mod response_cache;
 pub mod rule_engine;
+pub mod usage;
 
+pub use actions::{ActionExecutor, ActionOutcome, CaptureControl};
+pub use digest::{DigestConfig, DigestJob, DigestPeriod};
+pub use embeddings::{search_snapshots, SearchQuery, SnapshotEmbeddingJob};
+pub use health::{HealthConfig, HealthIssue, StreamHealthMonitor};
 pub use pipeline::AnalysisPipeline;
-pub use processors::{AiDescriptionProcessor, MotionProcessor, SummaryProcessor};
+pub use processors::{
+    plate_key, AiDescriptionProcessor, MessageProcessor, MotionProcessor, PlateProcessor,
+    PlateProcessorConfig, PlateStreamConfig, SummaryProcessor, TamperProcessor,
+};
+pub use questions::{
+    QuestionTracker, QuestionVerdict, VisualAnswer, VisualQuestion, VisualQuestionJob,
+};
 pub use rule_engine::{Action, Condition, Rule, RuleEngine, RuleSet};
 
 /// Core trait for analysis processors
@@ -132,6 +151,10 @@ pub struct AnalysisEvent {
     pub should_notify: bool,
     /// Suggested actions for this event
     pub suggested_actions: Vec<String>,
+    /// JPEG images to keep with the event, by name; replaced by a `<name>_uri` metadata
+    /// entry once stored
+    #[serde(skip)]
+    pub attachments: HashMap<String, Bytes>,
 }
 
 /// Event severity levels
@@ -180,6 +203,7 @@ impl AnalysisEvent {
             timestamp: Utc::now(),
             should_notify: true,
             suggested_actions: Vec::new(),
+            attachments: HashMap::new(),
         }
     }
 
@@ -200,6 +224,19 @@ impl AnalysisEvent {
         self.should_notify = should_notify;
         self
     }
+
+    /// Attach a JPEG image to be stored with the event
+    pub fn with_attachment(mut self, name: String, jpeg: Bytes) -> Self {
+        self.attachments.insert(name, jpeg);
+        self
+    }
+}
+
+/// Where images attached to events are kept
+#[async_trait]
+pub trait ArtifactStore: Send + Sync {
+    /// Store an image attached to an event and return its storage URI
+    async fn store_image(&self, event: &AnalysisEvent, name: &str, jpeg: Bytes) -> Result<String>;
 }
 
 /// Configuration for analysis processing
@@ -287,13 +324,15 @@ pub struct AnalysisService {
     config: AnalysisConfig,
     db_repo: Option<gl_db::AnalysisEventRepository>,
     notification_manager: Option<gl_notify::NotificationManager>,
+    artifact_store: Option<Arc<dyn ArtifactStore>>,
 }
 
 impl AnalysisService {
     /// Create a new analysis service
     pub fn new(config: AnalysisConfig) -> Result<Self> {
-        let rule_engine = RuleEngine::new(config.rules.clone());
+        let mut rule_engine = RuleEngine::new(config.rules.clone());
         let ai_config = config.ai.clone().unwrap_or_default();
+        rule_engine.set_ai_config(ai_config.clone());
         let pipeline = AnalysisPipeline::with_ai_config(
             config.enabled_processors.clone(),
             config.processor_configs.clone(),
@@ -311,6 +350,7 @@ impl AnalysisService {
             config,
             db_repo: None,
             notification_manager: None,
+            artifact_store: None,
         })
     }
 
@@ -320,8 +360,15 @@ impl AnalysisService {
         db: gl_db::Db,
         notification_manager: gl_notify::NotificationManager,
     ) -> Result<Self> {
-        let rule_engine = RuleEngine::new(config.rules.clone());
+        let mut config = config;
+        if let Some(ai_config) = config.ai.as_mut() {
+            usage::persist_ai_usage(ai_config, &db);
+            response_cache::persist_ai_cache(ai_config, &db);
+        }
+
+        let mut rule_engine = RuleEngine::with_persistence(config.rules.clone(), db.clone());
         let ai_config = config.ai.clone().unwrap_or_default();
+        rule_engine.set_ai_config(ai_config.clone());
         let pipeline = AnalysisPipeline::with_ai_config(
             config.enabled_processors.clone(),
             config.processor_configs.clone(),
@@ -341,9 +388,15 @@ impl AnalysisService {
             config,
             db_repo: Some(db_repo),
             notification_manager: Some(notification_manager),
+            artifact_store: None,
         })
     }
 
+    /// Keep images attached to events in this store; without one they are dropped
+    pub fn set_artifact_store(&mut self, store: Arc<dyn ArtifactStore>) {
+        self.artifact_store = Some(store);
+    }
+
     /// Process input through the analysis pipeline
     pub async fn analyze(&mut self, input: ProcessorInput) -> Result<Vec<AnalysisEvent>> {
         debug!("Starting analysis for template: {}", input.template_id);
@@ -357,18 +410,85 @@ impl AnalysisService {
         // Apply configuration filters
         events = self.apply_config_filters(events);
 
+        // Carry stream details through to storage and notification templates
+        Self::attach_stream_info(&input.context, &mut events);
+        self.store_attachments(&mut events).await;
+
+        self.persist_and_notify(&events).await?;
+
+        info!("Analysis completed: {} events generated", events.len());
+        Ok(events)
+    }
+
+    /// AI settings the processors were built with, including any shared usage store
+    pub fn ai_config(&self) -> gl_ai::AiConfig {
+        self.config.ai.clone().unwrap_or_default()
+    }
+
+    /// Store and notify events raised outside the processor pipeline, such as stream health
+    /// transitions; rules are not applied but severity and quiet-hour filters are
+    pub async fn publish_events(
+        &self,
+        context: &ProcessorContext,
+        events: Vec<AnalysisEvent>,
+    ) -> Result<Vec<AnalysisEvent>> {
+        let mut events = self.apply_config_filters(events);
+        Self::attach_stream_info(context, &mut events);
+        self.store_attachments(&mut events).await;
+        self.persist_and_notify(&events).await?;
+        Ok(events)
+    }
+
+    async fn persist_and_notify(&self, events: &[AnalysisEvent]) -> Result<()> {
         // Store events if configured
         if self.config.storage.store_events {
-            self.store_events(&events).await?;
+            self.store_events(events).await?;
         }
 
         // Enqueue notifications
         if self.config.notifications.enabled {
-            self.enqueue_notifications(&events).await?;
+            self.enqueue_notifications(events).await?;
         }
+        Ok(())
+    }
 
-        info!("Analysis completed: {} events generated", events.len());
-        Ok(events)
+    /// Copy stream name and snapshot URL from the processor context into event metadata
+    fn attach_stream_info(context: &ProcessorContext, events: &mut [AnalysisEvent]) {
+        for key in ["stream_name", "snapshot_url"] {
+            if let Some(value) = context.metadata.get(key) {
+                for event in events.iter_mut() {
+                    event
+                        .metadata
+                        .entry(key.to_string())
+                        .or_insert_with(|| value.clone().into());
+                }
+            }
+        }
+    }
+
+    /// Move attached images into the artifact store, leaving their URIs in event metadata
+    ///
+    /// An image that can't be stored is dropped; the event is kept without it.
+    async fn store_attachments(&self, events: &mut [AnalysisEvent]) {
+        for event in events.iter_mut() {
+            let attachments = std::mem::take(&mut event.attachments);
+            let Some(store) = &self.artifact_store else {
+                if !attachments.is_empty() {
+                    debug!(event_id = %event.id, "No artifact store configured, dropping attachments");
+                }
+                continue;
+            };
+            for (name, jpeg) in attachments {
+                match store.store_image(event, &name, jpeg).await {
+                    Ok(uri) => {
+                        event.metadata.insert(format!("{}_uri", name), uri.into());
+                    }
+                    Err(e) => {
+                        warn!(event_id = %event.id, attachment = %name, error = %e, "Failed to store event attachment")
+                    }
+                }
+            }
+        }
     }
 
     /// Apply configuration-based filters
@@ -493,17 +613,25 @@ impl AnalysisService {
                     }
                 }
 
-                // For now, we'll create a notification without channels
-                // In a real implementation, channels would be configured per template or user
-                let notification = gl_notify::Notification::new(
+                let mut notification = gl_notify::Notification::new(
                     kind,
                     title,
                     body,
-                    vec![], // No channels configured yet - this would come from template/user settings
+                    Self::broadcast_channels(manager),
                 )
                 .with_metadata("event_id".to_string(), event.id.clone())
                 .with_metadata("template_id".to_string(), event.template_id.clone())
-                .with_metadata("source_id".to_string(), event.source_id.clone());
+                .with_metadata("source_id".to_string(), event.source_id.clone())
+                .with_template_context(Self::template_context(event));
+
+                // Rules can pick a named or inline template per event
+                if let Some(template) = event
+                    .metadata
+                    .get("notification_template")
+                    .and_then(|v| v.as_str())
+                {
+                    notification = notification.with_template(template);
+                }
 
                 // Send notification (will be no-op if no channels configured)
                 if let Err(e) = manager.send(&notification).await {
@@ -523,23 +651,106 @@ impl AnalysisService {
         Ok(())
     }
 
+    /// Channels that need no per-user configuration and go out for every event
+    ///
+    /// Other channels would come from template or user settings.
+    fn broadcast_channels(
+        manager: &gl_notify::NotificationManager,
+    ) -> Vec<gl_notify::NotificationChannel> {
+        let mut channels = Vec::new();
+        if manager.adapters().contains(&"webpush") {
+            // Every registered browser subscription
+            channels.push(gl_notify::NotificationChannel::WebPush { user_id: None });
+        }
+        channels
+    }
+
+    /// Register a notification adapter on the service's notification manager
+    pub fn register_notification_adapter(
+        &mut self,
+        name: String,
+        adapter: std::sync::Arc<dyn gl_notify::Notifier>,
+    ) {
+        if let Some(manager) = &mut self.notification_manager {
+            manager.register_adapter(name, adapter);
+        }
+    }
+
+    /// Build the data available to notification templates for an event
+    fn template_context(event: &AnalysisEvent) -> gl_notify::TemplateContext {
+        let metadata_str = |key: &str| {
+            event
+                .metadata
+                .get(key)
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+        };
+
+        gl_notify::TemplateContext {
+            event_id: event.id.clone(),
+            event_type: event.event_type.clone(),
+            severity: event.severity.as_str().to_string(),
+            confidence: event.confidence,
+            description: event.description.clone(),
+            source_id: event.source_id.clone(),
+            template_id: event.template_id.clone(),
+            timestamp: event.timestamp,
+            stream_name: metadata_str("stream_name"),
+            snapshot_url: metadata_str("snapshot_url"),
+            ai_description: metadata_str("ai_description"),
+            suggested_actions: event.suggested_actions.clone(),
+            metadata: event.metadata.clone(),
+        }
+    }
+
     /// Update configuration
-    pub async fn update_config(&mut self, config: AnalysisConfig) -> Result<()> {
+    pub async fn update_config(&mut self, mut config: AnalysisConfig) -> Result<()> {
         info!("Updating analysis service configuration");
 
+        // Keep charging budgets to the same usage store and reusing cached responses
+        let usage_store = self
+            .config
+            .ai
+            .as_ref()
+            .and_then(|ai| ai.routing.as_ref())
+            .and_then(|routing| routing.usage_store.clone());
+        if let Some(routing) = config.ai.as_mut().and_then(|ai| ai.routing.as_mut()) {
+            if routing.usage_store.is_none() {
+                routing.usage_store = usage_store;
+            }
+        }
+        let cache_store = self
+            .config
+            .ai
+            .as_ref()
+            .and_then(|ai| ai.cache.as_ref())
+            .and_then(|cache| cache.store.clone());
+        if let Some(cache) = config.ai.as_mut().and_then(|ai| ai.cache.as_mut()) {
+            if cache.store.is_none() {
+                cache.store = cache_store;
+            }
+        }
+
         // Recreate pipeline with new config
         self.pipeline = AnalysisPipeline::new(
             config.enabled_processors.clone(),
             config.processor_configs.clone(),
         )?;
 
-        // Update rule engine
-        self.rule_engine = RuleEngine::new(config.rules.clone());
+        // Update rules, keeping history, dedup keys and rate limits
+        self.rule_engine.set_rules(config.rules.clone());
+        self.rule_engine
+            .set_ai_config(config.ai.clone().unwrap_or_default());
 
         self.config = config;
         Ok(())
     }
 
+    /// Provide capture operations for snapshot burst and recording rule actions
+    pub fn set_capture_control(&mut self, capture_control: std::sync::Arc<dyn CaptureControl>) {
+        self.rule_engine.set_capture_control(capture_control);
+    }
+
     /// Get current configuration
     pub fn config(&self) -> &AnalysisConfig {
         &self.config
@@ -606,4 +817,134 @@ mod tests {
         assert!(config.notifications.enabled);
         assert_eq!(config.notifications.min_severity, EventSeverity::Medium);
     }
+
+    #[test]
+    fn test_notification_template_context() {
+        let input = ProcessorInput {
+            template_id: "template_123".to_string(),
+            frame_data: None,
+            frame_format: None,
+            text_content: None,
+            context: ProcessorContext::new("camera_01".to_string())
+                .with_metadata("stream_name".to_string(), "Front Door".to_string()),
+            timestamp: Utc::now(),
+        };
+        let mut events = vec![AnalysisEvent::new(
+            "template_123".to_string(),
+            "person_detected".to_string(),
+            EventSeverity::High,
+            0.9,
+            "Person at the door".to_string(),
+            "ai_description".to_string(),
+            "camera_01".to_string(),
+        )];
+        events[0]
+            .metadata
+            .insert("ai_description".to_string(), "A courier".into());
+
+        AnalysisService::attach_stream_info(&input.context, &mut events);
+        let context = AnalysisService::template_context(&events[0]);
+
+        assert_eq!(context.severity, "high");
+        assert_eq!(context.stream_name.as_deref(), Some("Front Door"));
+        assert_eq!(context.ai_description.as_deref(), Some("A courier"));
+        assert!(context.snapshot_url.is_none());
+
+        let rendered = gl_notify::NotificationTemplates::new()
+            .render("webhook", None, &context)
+            .unwrap();
+        assert_eq!(rendered.title, "HIGH Alert: Person Detected");
+        assert!(rendered.body.starts_with("Source: Front Door\n"));
+    }
+
+    #[tokio::test]
+    async fn test_publish_events_applies_filters_and_stream_info() {
+        let mut config = AnalysisConfig::default();
+        config.enabled_processors.clear();
+        let service = AnalysisService::new(config).unwrap();
+        let context = ProcessorContext::new("camera_01".to_string())
+            .with_metadata("stream_name".to_string(), "Garage".to_string());
+        let event = |severity| {
+            AnalysisEvent::new(
+                "camera_01".to_string(),
+                "stream_offline".to_string(),
+                severity,
+                1.0,
+                "No frames".to_string(),
+                "stream_health".to_string(),
+                "camera_01".to_string(),
+            )
+        };
+
+        let published = service
+            .publish_events(
+                &context,
+                vec![event(EventSeverity::High), event(EventSeverity::Low)],
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].metadata["stream_name"], "Garage");
+    }
+
+    /// Artifact store that remembers images in memory
+    #[derive(Default)]
+    struct MemoryArtifacts {
+        images: std::sync::Mutex<Vec<(String, Bytes)>>,
+    }
+
+    #[async_trait]
+    impl ArtifactStore for MemoryArtifacts {
+        async fn store_image(
+            &self,
+            event: &AnalysisEvent,
+            name: &str,
+            jpeg: Bytes,
+        ) -> Result<String> {
+            let uri = format!("memory://{}/{}.jpg", event.id, name);
+            self.images.lock().unwrap().push((uri.clone(), jpeg));
+            Ok(uri)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_attachments_are_stored_and_referenced_by_uri() {
+        let mut config = AnalysisConfig::default();
+        config.enabled_processors.clear();
+        let mut service = AnalysisService::new(config).unwrap();
+        let event = AnalysisEvent::new(
+            "gate".to_string(),
+            "plate_read".to_string(),
+            EventSeverity::High,
+            0.9,
+            "Plate AB12CDE read".to_string(),
+            "plate".to_string(),
+            "gate".to_string(),
+        )
+        .with_attachment("crop".to_string(), Bytes::from_static(b"jpeg"));
+        let context = ProcessorContext::new("gate".to_string());
+
+        // Without a store the image is dropped rather than inlined
+        let published = service
+            .publish_events(&context, vec![event.clone()])
+            .await
+            .unwrap();
+        assert!(published[0].attachments.is_empty());
+        assert!(!published[0].metadata.contains_key("crop_uri"));
+
+        let artifacts = Arc::new(MemoryArtifacts::default());
+        service.set_artifact_store(artifacts.clone());
+        let published = service
+            .publish_events(&context, vec![event.clone()])
+            .await
+            .unwrap();
+        let uri = format!("memory://{}/crop.jpg", event.id);
+        assert_eq!(published[0].metadata["crop_uri"], uri.as_str());
+        assert!(published[0].attachments.is_empty());
+        assert_eq!(
+            *artifacts.images.lock().unwrap(),
+            vec![(uri, Bytes::from_static(b"jpeg"))]
+        );
+    }
 }
@@ -44,6 +44,18 @@ impl AnalysisPipeline {
                         ai_config.clone(),
                     )?)
                 }
+                "message" => {
+                    debug!("Creating message processor");
+                    Box::new(MessageProcessor::new(config.cloned())?)
+                }
+                "tamper" => {
+                    debug!("Creating tamper processor");
+                    Box::new(TamperProcessor::new(config.cloned())?)
+                }
+                "plate" => {
+                    debug!("Creating plate processor");
+                    Box::new(PlateProcessor::new(config.cloned())?)
+                }
                 "summary" => {
                     debug!("Creating summary processor with AI config");
                     Box::new(SummaryProcessor::with_ai_config(
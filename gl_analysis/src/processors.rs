@@ -3,10 +3,16 @@
 
 use crate::{AnalysisEvent, EventSeverity, Processor, ProcessorInput};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use gl_ai::{create_client, AiClient, AiConfig, DescribeFrameRequest, SummarizeRequest};
 use gl_core::Result;
-use gl_vision::{MotionAlgorithm, MotionConfig, MotionDetectionService};
+use gl_vision::{
+    normalize_plate, region_formats, GaussianMixtureConfig, GaussianMixtureDetector,
+    MotionAlgorithm, MotionConfig, MotionDetectionService, PlateConfig, PlateReader, TamperConfig,
+    TamperDetector, TamperKind,
+};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use tracing::{debug, warn};
 
 /// Motion detection processor
@@ -27,6 +33,9 @@ pub struct MotionProcessorConfig {
     pub downscale_factor: u32,
     /// Motion detection algorithm
     pub algorithm: MotionAlgorithm,
+    /// Background model settings for the GaussianMixture algorithm
+    #[serde(default)]
+    pub background_model: GaussianMixtureConfig,
 }
 
 impl Default for MotionProcessorConfig {
@@ -36,6 +45,7 @@ impl Default for MotionProcessorConfig {
             min_change_area: 200,
             downscale_factor: 4,
             algorithm: MotionAlgorithm::PixelDiff,
+            background_model: GaussianMixtureConfig::default(),
         }
     }
 }
@@ -59,7 +69,16 @@ impl MotionProcessor {
             min_change_area: config.min_change_area,
         };
 
-        let motion_service = MotionDetectionService::new(motion_config)?;
+        let motion_service = match config.algorithm {
+            MotionAlgorithm::GaussianMixture => {
+                let detector = GaussianMixtureDetector::with_model_config(
+                    motion_config.clone(),
+                    config.background_model.clone(),
+                )?;
+                MotionDetectionService::with_detector(motion_config, Box::new(detector))
+            }
+            _ => MotionDetectionService::new(motion_config)?,
+        };
 
         debug!(
             "Created motion processor with threshold: {}",
@@ -142,6 +161,10 @@ pub struct AiDescriptionProcessorConfig {
     pub focus: String,
     /// Whether to trigger on motion events only
     pub motion_only: bool,
+    /// JSON schema the model reply must match; a stream's `output_schema`
+    /// template config takes precedence
+    #[serde(default)]
+    pub output_schema: Option<serde_json::Value>,
 }
 
 impl Default for AiDescriptionProcessorConfig {
@@ -150,6 +173,7 @@ impl Default for AiDescriptionProcessorConfig {
             detail_level: "high".to_string(),
             focus: "objects".to_string(),
             motion_only: true,
+            output_schema: None,
         }
     }
 }
@@ -170,6 +194,9 @@ impl AiDescriptionProcessor {
         } else {
             AiDescriptionProcessorConfig::default()
         };
+        if let Some(schema) = &config.output_schema {
+            gl_ai::structured::check_schema(schema)?;
+        }
 
         // Create AI client with provided configuration
         let ai_client = create_client(ai_config.clone());
@@ -211,11 +238,22 @@ impl Processor for AiDescriptionProcessor {
 
         debug!("Processing frame for AI description");
 
+        let output_schema = input
+            .context
+            .template_config
+            .get("output_schema")
+            .filter(|schema| !schema.is_null())
+            .or(self.config.output_schema.as_ref())
+            .cloned();
+
         let request = DescribeFrameRequest {
             image_data: frame_data.clone(),
             image_format: frame_format.clone(),
             detail_level: Some(self.config.detail_level.clone()),
             focus: Some(self.config.focus.clone()),
+            stream_id: Some(input.context.source_id.clone()),
+            question: None,
+            output_schema,
         };
 
         match self.ai_client.describe_frame(request).await {
@@ -223,7 +261,7 @@ impl Processor for AiDescriptionProcessor {
                 let mut events = Vec::new();
 
                 // Create description event
-                let description_event = AnalysisEvent::new(
+                let mut description_event = AnalysisEvent::new(
                     input.template_id.clone(),
                     "frame_described".to_string(),
                     EventSeverity::Low,
@@ -245,6 +283,21 @@ impl Processor for AiDescriptionProcessor {
                     response.processing_time_ms.unwrap_or(0).into(),
                 );
 
+                // Expose schema fields at the top level so rules can match on them
+                if let Some(structured) = &response.structured {
+                    if let Some(fields) = structured.as_object() {
+                        for (key, value) in fields {
+                            description_event
+                                .metadata
+                                .entry(key.clone())
+                                .or_insert_with(|| value.clone());
+                        }
+                    }
+                    description_event
+                        .metadata
+                        .insert("structured".to_string(), structured.clone());
+                }
+
                 events.push(description_event);
 
                 // Create specific events for detected objects
@@ -378,6 +431,7 @@ impl Processor for SummaryProcessor {
             text: combined_text,
             max_length: Some(self.config.max_length),
             style: Some(self.config.style.clone()),
+            stream_id: Some(input.context.source_id.clone()),
         };
 
         match self.ai_client.summarize(request).await {
@@ -418,6 +472,401 @@ impl Processor for SummaryProcessor {
     }
 }
 
+/// Message processor that turns text sent along with a frame (e.g. an alarm email)
+/// into an event rules can match on
+pub struct MessageProcessor {
+    config: MessageProcessorConfig,
+}
+
+/// Configuration for message processor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MessageProcessorConfig {
+    /// Severity of generated events
+    pub severity: EventSeverity,
+    /// Longest event description; the full text is kept in metadata
+    pub max_description_chars: usize,
+}
+
+impl Default for MessageProcessorConfig {
+    fn default() -> Self {
+        Self {
+            severity: EventSeverity::Medium,
+            max_description_chars: 500,
+        }
+    }
+}
+
+impl MessageProcessor {
+    pub fn new(config: Option<serde_json::Value>) -> Result<Self> {
+        let config: MessageProcessorConfig = if let Some(config_value) = config {
+            serde_json::from_value(config_value).map_err(|e| {
+                gl_core::Error::Validation(format!("Invalid message processor config: {}", e))
+            })?
+        } else {
+            MessageProcessorConfig::default()
+        };
+
+        Ok(Self { config })
+    }
+}
+
+#[async_trait]
+impl Processor for MessageProcessor {
+    async fn process(&mut self, input: ProcessorInput) -> Result<Vec<AnalysisEvent>> {
+        let Some(text) = input
+            .text_content
+            .as_deref()
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+        else {
+            return Ok(Vec::new());
+        };
+
+        let description: String = text
+            .chars()
+            .take(self.config.max_description_chars)
+            .collect();
+        let event = AnalysisEvent::new(
+            input.template_id.clone(),
+            "message_received".to_string(),
+            self.config.severity.clone(),
+            1.0,
+            description,
+            self.name().to_string(),
+            input.context.source_id.clone(),
+        )
+        .with_metadata("text".to_string(), text.into());
+
+        debug!("Message processor generated 1 event");
+        Ok(vec![event])
+    }
+
+    fn name(&self) -> &'static str {
+        "message"
+    }
+}
+
+/// Tamper processor that keeps a reference scene per source and reports cameras that
+/// were moved, covered or defocused
+pub struct TamperProcessor {
+    config: TamperProcessorConfig,
+    detectors: HashMap<String, TamperDetector>,
+}
+
+/// Configuration for tamper processor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TamperProcessorConfig {
+    #[serde(flatten)]
+    pub detector: TamperConfig,
+    /// Stream kinds to check; websites and video sites change completely by design
+    pub stream_kinds: Vec<String>,
+}
+
+impl Default for TamperProcessorConfig {
+    fn default() -> Self {
+        Self {
+            detector: TamperConfig::default(),
+            stream_kinds: ["rtsp", "ffmpeg", "http", "file", "ftp", "email"]
+                .map(String::from)
+                .to_vec(),
+        }
+    }
+}
+
+impl TamperProcessor {
+    pub fn new(config: Option<serde_json::Value>) -> Result<Self> {
+        let config: TamperProcessorConfig = if let Some(config_value) = config {
+            serde_json::from_value(config_value).map_err(|e| {
+                gl_core::Error::Validation(format!("Invalid tamper processor config: {}", e))
+            })?
+        } else {
+            TamperProcessorConfig::default()
+        };
+        // Fail on bad settings now rather than on the first frame
+        TamperDetector::new(config.detector.clone())?;
+
+        Ok(Self {
+            config,
+            detectors: HashMap::new(),
+        })
+    }
+}
+
+#[async_trait]
+impl Processor for TamperProcessor {
+    async fn process(&mut self, input: ProcessorInput) -> Result<Vec<AnalysisEvent>> {
+        let Some(frame_data) = &input.frame_data else {
+            return Ok(Vec::new());
+        };
+        if let Some(kind) = input.context.metadata.get("stream_kind") {
+            if !self.config.stream_kinds.iter().any(|k| k == kind) {
+                return Ok(Vec::new());
+            }
+        }
+
+        let source_id = input.context.source_id.clone();
+        let detector = match self.detectors.entry(source_id.clone()) {
+            std::collections::hash_map::Entry::Occupied(entry) => entry.into_mut(),
+            std::collections::hash_map::Entry::Vacant(entry) => {
+                entry.insert(TamperDetector::new(self.config.detector.clone())?)
+            }
+        };
+        let analysis = detector.analyze_bytes(frame_data)?;
+
+        let Some(kind) = analysis.tampered.filter(|_| analysis.newly_tampered) else {
+            return Ok(Vec::new());
+        };
+        let confidence = match kind {
+            TamperKind::SceneChange => analysis.changed_ratio,
+            TamperKind::Defocus => 1.0 - analysis.edge_ratio,
+            TamperKind::BrightnessLoss => 1.0 - analysis.brightness_ratio,
+        }
+        .clamp(0.0, 1.0);
+
+        let event = AnalysisEvent::new(
+            input.template_id.clone(),
+            "tamper_detected".to_string(),
+            EventSeverity::High,
+            confidence,
+            kind.description().to_string(),
+            self.name().to_string(),
+            source_id,
+        )
+        .with_metadata("tamper_kind".to_string(), kind.as_str().into())
+        .with_metadata("changed_ratio".to_string(), analysis.changed_ratio.into())
+        .with_metadata("edge_ratio".to_string(), analysis.edge_ratio.into())
+        .with_metadata(
+            "brightness_ratio".to_string(),
+            analysis.brightness_ratio.into(),
+        );
+
+        debug!("Tamper processor generated 1 event");
+        Ok(vec![event])
+    }
+
+    fn name(&self) -> &'static str {
+        "tamper"
+    }
+
+    async fn reset(&mut self) -> Result<()> {
+        debug!("Resetting tamper processor");
+        self.detectors.clear();
+        Ok(())
+    }
+}
+
+/// Licence plate processor that reads plates on streams configured with a `plates`
+/// section and checks them against allow and deny lists
+pub struct PlateProcessor {
+    config: PlateProcessorConfig,
+    reader: PlateReader,
+    /// When each (source, plate) was last reported
+    last_seen: HashMap<(String, String), DateTime<Utc>>,
+}
+
+/// Configuration for plate processor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlateProcessorConfig {
+    #[serde(flatten)]
+    pub reader: PlateConfig,
+    /// Seconds before the same plate on the same stream is reported again
+    pub repeat_seconds: i64,
+    /// Plates allowed on every stream
+    pub allow: Vec<String>,
+    /// Plates denied on every stream
+    pub deny: Vec<String>,
+}
+
+impl Default for PlateProcessorConfig {
+    fn default() -> Self {
+        Self {
+            reader: PlateConfig::default(),
+            repeat_seconds: 60,
+            allow: Vec::new(),
+            deny: Vec::new(),
+        }
+    }
+}
+
+/// Per-stream plate settings, the `plates` section of a stream's config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlateStreamConfig {
+    /// Region preset for plate formats ("gb", "fr", "it", "es", "us")
+    pub region: Option<String>,
+    /// Plate formats of `L` (letter), `N` (digit) and `A` (either); overrides the region
+    pub formats: Vec<String>,
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+    /// Reads below this confidence are dropped
+    pub min_confidence: f64,
+    /// Notify for plates on neither list
+    pub notify_unknown: bool,
+}
+
+impl Default for PlateStreamConfig {
+    fn default() -> Self {
+        Self {
+            region: None,
+            formats: Vec::new(),
+            allow: Vec::new(),
+            deny: Vec::new(),
+            min_confidence: 0.7,
+            notify_unknown: false,
+        }
+    }
+}
+
+impl PlateStreamConfig {
+    /// Formats plates on this stream must fit
+    pub fn plate_formats(&self) -> Result<Vec<String>> {
+        if !self.formats.is_empty() {
+            return Ok(self.formats.clone());
+        }
+        match &self.region {
+            None => Ok(Vec::new()),
+            Some(region) => region_formats(region)
+                .map(|formats| formats.iter().map(|f| f.to_string()).collect())
+                .ok_or_else(|| {
+                    gl_core::Error::Validation(format!("Unknown plate region: {}", region))
+                }),
+        }
+    }
+}
+
+/// Plate text as used for list matching: upper case letters and digits only
+pub fn plate_key(plate: &str) -> String {
+    plate
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+impl PlateProcessor {
+    pub fn new(config: Option<serde_json::Value>) -> Result<Self> {
+        let config: PlateProcessorConfig = if let Some(config_value) = config {
+            serde_json::from_value(config_value).map_err(|e| {
+                gl_core::Error::Validation(format!("Invalid plate processor config: {}", e))
+            })?
+        } else {
+            PlateProcessorConfig::default()
+        };
+        let reader = PlateReader::new(config.reader.clone())?;
+
+        Ok(Self {
+            config,
+            reader,
+            last_seen: HashMap::new(),
+        })
+    }
+
+    /// "denied", "allowed" or "unknown"; deny entries win over allow entries
+    fn list_status(&self, stream: &PlateStreamConfig, plate: &str) -> &'static str {
+        let listed = |list: &[String]| list.iter().any(|entry| plate_key(entry) == plate);
+        if listed(&stream.deny) || listed(&self.config.deny) {
+            "denied"
+        } else if listed(&stream.allow) || listed(&self.config.allow) {
+            "allowed"
+        } else {
+            "unknown"
+        }
+    }
+}
+
+#[async_trait]
+impl Processor for PlateProcessor {
+    async fn process(&mut self, input: ProcessorInput) -> Result<Vec<AnalysisEvent>> {
+        let Some(frame_data) = &input.frame_data else {
+            return Ok(Vec::new());
+        };
+        // Only streams that opt in with a plates section are read
+        let Some(stream_value) = input
+            .context
+            .template_config
+            .get("plates")
+            .filter(|value| !value.is_null())
+        else {
+            return Ok(Vec::new());
+        };
+        let stream: PlateStreamConfig = serde_json::from_value(stream_value.clone())
+            .map_err(|e| gl_core::Error::Validation(format!("Invalid plates config: {}", e)))?;
+        let formats = stream.plate_formats()?;
+
+        let source_id = input.context.source_id.clone();
+        let repeat = chrono::Duration::seconds(self.config.repeat_seconds);
+        self.last_seen
+            .retain(|_, seen| input.timestamp - *seen < repeat);
+
+        let mut events = Vec::new();
+        for read in self.reader.read_bytes(frame_data)? {
+            if read.confidence < stream.min_confidence {
+                debug!(text = %read.text, confidence = read.confidence, "Plate read below confidence");
+                continue;
+            }
+            let Some(plate) = normalize_plate(&read.text, &formats) else {
+                debug!(text = %read.text, "Plate read fits no plate format");
+                continue;
+            };
+            let key = (source_id.clone(), plate.clone());
+            if self.last_seen.contains_key(&key) {
+                continue;
+            }
+            self.last_seen.insert(key, input.timestamp);
+
+            let status = self.list_status(&stream, &plate);
+            let (severity, notify) = match status {
+                "denied" => (EventSeverity::High, true),
+                "unknown" => (EventSeverity::Medium, stream.notify_unknown),
+                _ => (EventSeverity::Info, false),
+            };
+
+            let event = AnalysisEvent::new(
+                input.template_id.clone(),
+                "plate_read".to_string(),
+                severity,
+                read.confidence,
+                format!("Plate {} read ({} vehicle)", plate, status),
+                self.name().to_string(),
+                source_id.clone(),
+            )
+            .with_metadata("plate".to_string(), plate.into())
+            .with_metadata("raw_text".to_string(), read.text.into())
+            .with_metadata("plate_list".to_string(), status.into())
+            .with_metadata(
+                "bbox".to_string(),
+                serde_json::to_value(read.bbox).unwrap_or_default(),
+            )
+            .with_metadata(
+                "region".to_string(),
+                stream
+                    .region
+                    .clone()
+                    .map_or(serde_json::Value::Null, Into::into),
+            )
+            .with_attachment("crop".to_string(), read.crop.into())
+            .with_notification(notify);
+            events.push(event);
+        }
+
+        debug!("Plate processor generated {} events", events.len());
+        Ok(events)
+    }
+
+    fn name(&self) -> &'static str {
+        "plate"
+    }
+
+    async fn reset(&mut self) -> Result<()> {
+        debug!("Resetting plate processor");
+        self.last_seen.clear();
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -442,6 +891,35 @@ mod tests {
         assert_eq!(processor.config.min_change_area, 300);
     }
 
+    #[tokio::test]
+    async fn test_motion_processor_background_model_config() {
+        let config = serde_json::json!({
+            "threshold": 0.1,
+            "min_change_area": 100,
+            "downscale_factor": 2,
+            "algorithm": "GaussianMixture",
+            "background_model": {"learning_rate": 0.005, "detect_shadows": false}
+        });
+
+        let processor = MotionProcessor::new(Some(config)).unwrap();
+        assert!(matches!(
+            processor.config.algorithm,
+            MotionAlgorithm::GaussianMixture
+        ));
+        assert_eq!(processor.config.background_model.learning_rate, 0.005);
+        assert!(!processor.config.background_model.detect_shadows);
+        assert_eq!(processor.config.background_model.components, 3);
+
+        let invalid = serde_json::json!({
+            "threshold": 0.1,
+            "min_change_area": 100,
+            "downscale_factor": 2,
+            "algorithm": "GaussianMixture",
+            "background_model": {"components": 0}
+        });
+        assert!(MotionProcessor::new(Some(invalid)).is_err());
+    }
+
     #[tokio::test]
     async fn test_ai_description_processor_creation() {
         let config = serde_json::json!({
@@ -460,6 +938,54 @@ mod tests {
         assert!(!processor.config.motion_only);
     }
 
+    #[tokio::test]
+    async fn test_ai_description_structured_output_in_metadata() {
+        let config = |output_schema: serde_json::Value| {
+            serde_json::json!({
+                "detail_level": "high",
+                "focus": "objects",
+                "motion_only": false,
+                "output_schema": output_schema
+            })
+        };
+        let invalid = config(serde_json::json!({"type": "shape"}));
+        assert!(AiDescriptionProcessor::new(Some(invalid)).is_err());
+
+        let mut processor =
+            AiDescriptionProcessor::new(Some(config(serde_json::Value::Null))).unwrap();
+        let mut template_config = HashMap::new();
+        template_config.insert(
+            "output_schema".to_string(),
+            serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "person_count": {"type": "integer", "minimum": 1},
+                    "description": {"type": "string"}
+                },
+                "required": ["person_count", "description"]
+            }),
+        );
+
+        let input = ProcessorInput {
+            template_id: "test".to_string(),
+            frame_data: Some(bytes::Bytes::from(vec![0u8; 1000])),
+            frame_format: Some("jpeg".to_string()),
+            text_content: None,
+            context: ProcessorContext::new("test_source".to_string()).with_config(template_config),
+            timestamp: Utc::now(),
+        };
+
+        let events = processor.process(input).await.unwrap();
+        let described = events
+            .iter()
+            .find(|event| event.event_type == "frame_described")
+            .unwrap();
+        assert_eq!(described.metadata["person_count"], 1);
+        assert_eq!(described.metadata["structured"]["person_count"], 1);
+        // Built-in fields are not overwritten by schema fields
+        assert_eq!(described.metadata["description"], described.description);
+    }
+
     #[tokio::test]
     async fn test_summary_processor_creation() {
         let config = serde_json::json!({
@@ -522,4 +1048,206 @@ mod tests {
         let events = processor.process(input).await.unwrap();
         assert_eq!(events.len(), 0); // Not enough events for summary
     }
+
+    #[tokio::test]
+    async fn test_message_processor() {
+        let mut processor = MessageProcessor::new(Some(serde_json::json!({
+            "severity": "High",
+            "max_description_chars": 12
+        })))
+        .unwrap();
+
+        let mut input = ProcessorInput {
+            template_id: "test".to_string(),
+            frame_data: None,
+            frame_format: None,
+            text_content: Some("  Alarm: zone 3 intrusion  ".to_string()),
+            context: ProcessorContext::new("test_source".to_string()),
+            timestamp: Utc::now(),
+        };
+
+        let events = processor.process(input.clone()).await.unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_type, "message_received");
+        assert_eq!(events[0].severity, EventSeverity::High);
+        assert_eq!(events[0].description, "Alarm: zone ");
+        assert_eq!(events[0].metadata["text"], "Alarm: zone 3 intrusion");
+
+        input.text_content = Some("   ".to_string());
+        assert!(processor.process(input).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_tamper_processor_reports_covered_lens_once() {
+        use gl_vision::image::{GrayImage, Luma};
+        use gl_vision::utils::image_to_jpeg_bytes;
+
+        let mut processor = TamperProcessor::new(Some(serde_json::json!({
+            "learning_frames": 2,
+            "persist_frames": 2
+        })))
+        .unwrap();
+        assert_eq!(processor.config.detector.learning_frames, 2);
+        assert_eq!(processor.config.detector.pixel_threshold, 40);
+
+        let scene = GrayImage::from_fn(320, 240, |x, y| {
+            Luma([if (x / 20 + y / 20) % 2 == 0 { 200 } else { 40 }])
+        });
+        let covered = GrayImage::from_pixel(320, 240, Luma([3]));
+        let input = |frame: &GrayImage, kind: &str| ProcessorInput {
+            template_id: "cam".to_string(),
+            frame_data: Some(image_to_jpeg_bytes(frame).unwrap().into()),
+            frame_format: Some("jpeg".to_string()),
+            text_content: None,
+            context: ProcessorContext::new("cam".to_string())
+                .with_metadata("stream_kind".to_string(), kind.to_string()),
+            timestamp: Utc::now(),
+        };
+
+        for _ in 0..2 {
+            assert!(processor
+                .process(input(&scene, "rtsp"))
+                .await
+                .unwrap()
+                .is_empty());
+        }
+        assert!(processor
+            .process(input(&covered, "rtsp"))
+            .await
+            .unwrap()
+            .is_empty());
+        let events = processor.process(input(&covered, "rtsp")).await.unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_type, "tamper_detected");
+        assert_eq!(events[0].severity, EventSeverity::High);
+        assert_eq!(events[0].metadata["tamper_kind"], "brightness_loss");
+        assert!(processor
+            .process(input(&covered, "rtsp"))
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Website captures are skipped entirely
+        assert!(processor
+            .process(input(&covered, "website"))
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(processor.detectors.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_plate_processor_checks_lists_and_suppresses_repeats() {
+        use gl_vision::utils::{create_test_frame_with_plate, image_to_jpeg_bytes};
+        use test_support::onnx::{DARK_PLATE, LIGHT_PLATE};
+
+        // Without a recognition model nothing would be read, so the processor won't start
+        assert!(PlateProcessor::new(None).is_err());
+        assert!(PlateProcessor::new(Some(serde_json::json!({
+            "ocr": {"model_path": "/nonexistent/plates.onnx"}
+        })))
+        .is_err());
+
+        let path =
+            std::env::temp_dir().join(format!("plate_processor_{}.onnx", std::process::id()));
+        let ocr = gl_vision::PlateOcrConfig::default();
+        test_support::onnx::brightness_plate_ocr(&path, &ocr.alphabet, ocr.width, ocr.height, 3);
+        let mut processor = PlateProcessor::new(Some(serde_json::json!({
+            "ocr": {"model_path": path},
+            "repeat_seconds": 30,
+            "deny": ["ab12 cde"]
+        })))
+        .unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(processor.config.reader.min_chars, 4);
+
+        // The model reads light plates as LIGHT_PLATE and dark ones as DARK_PLATE
+        let frame = |plate: &str| {
+            let mut image = create_test_frame_with_plate(640, 360, "CD34EFG", 100, 200, 4);
+            if plate == DARK_PLATE {
+                gl_vision::image::imageops::invert(&mut image);
+            }
+            Some(image_to_jpeg_bytes(&image).unwrap().into())
+        };
+        let now = Utc::now();
+        let mut context = ProcessorContext::new("gate".to_string());
+        context.template_config.insert(
+            "plates".to_string(),
+            serde_json::json!({"region": "gb", "allow": [DARK_PLATE], "notify_unknown": true}),
+        );
+        let input = |text: &str, seconds: i64, context: &ProcessorContext| ProcessorInput {
+            template_id: "gate".to_string(),
+            frame_data: frame(text),
+            frame_format: Some("jpeg".to_string()),
+            text_content: None,
+            context: context.clone(),
+            timestamp: now + chrono::Duration::seconds(seconds),
+        };
+
+        let events = processor
+            .process(input(LIGHT_PLATE, 0, &context))
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_type, "plate_read");
+        assert_eq!(events[0].metadata["plate"], LIGHT_PLATE);
+        assert_eq!(events[0].metadata["plate_list"], "denied");
+        assert_eq!(events[0].severity, EventSeverity::High);
+        assert!(events[0].should_notify);
+        // The crop is stored by the analysis service, not carried in metadata
+        assert!(events[0].attachments["crop"].len() > 100);
+        assert!(!events[0].metadata.contains_key("crop_uri"));
+
+        // Same plate again within the repeat window
+        assert!(processor
+            .process(input(LIGHT_PLATE, 10, &context))
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            processor
+                .process(input(LIGHT_PLATE, 40, &context))
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let events = processor
+            .process(input(DARK_PLATE, 0, &context))
+            .await
+            .unwrap();
+        assert_eq!(events[0].metadata["plate_list"], "allowed");
+        assert_eq!(events[0].severity, EventSeverity::Info);
+        assert!(!events[0].should_notify);
+
+        // The same plate is unknown on a stream that doesn't allow it
+        let mut street = ProcessorContext::new("street".to_string());
+        street.template_config.insert(
+            "plates".to_string(),
+            serde_json::json!({"region": "gb", "notify_unknown": true}),
+        );
+        let events = processor
+            .process(input(DARK_PLATE, 0, &street))
+            .await
+            .unwrap();
+        assert_eq!(events[0].metadata["plate_list"], "unknown");
+        assert!(events[0].should_notify);
+
+        // Streams without a plates section are not read
+        let plain = ProcessorContext::new("yard".to_string());
+        assert!(processor
+            .process(input(LIGHT_PLATE, 0, &plain))
+            .await
+            .unwrap()
+            .is_empty());
+
+        context
+            .template_config
+            .insert("plates".to_string(), serde_json::json!({"region": "mars"}));
+        assert!(processor
+            .process(input(LIGHT_PLATE, 0, &context))
+            .await
+            .is_err());
+    }
 }
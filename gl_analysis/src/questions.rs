@@ -0,0 +1,556 @@
+//! ABOUTME: Scheduled yes/no questions about what a stream shows, answered by the vision model
+//! ABOUTME: Runs as a scheduler job and raises an event whenever a confident answer flips
+
+use crate::{AnalysisEvent, AnalysisService, EventSeverity, ProcessorContext};
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use gl_ai::{AiClient, DescribeFrameRequest};
+use gl_core::Result;
+use gl_scheduler::{JobContext, JobDefinition, JobHandler};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tracing::{debug, info, warn};
+
+/// Scheduler job type that answers visual questions
+pub const JOB_TYPE: &str = "visual_question";
+
+/// Processor name recorded on question events
+const PROCESSOR_NAME: &str = "visual_question";
+
+/// Question asked about a stream on a schedule; read from the `questions` array of a stream config
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VisualQuestion {
+    /// Identifier unique within the stream, kept stable so state survives config edits
+    pub id: String,
+    /// Question with a yes/no answer, e.g. "Is the garage door open?"
+    pub question: String,
+    /// Cron expression with seconds; every five minutes by default
+    #[serde(default = "default_schedule")]
+    pub schedule: String,
+    /// Answers given with less confidence than this count as unknown
+    #[serde(default = "default_min_confidence")]
+    pub min_confidence: f64,
+    #[serde(default = "default_severity")]
+    pub severity: EventSeverity,
+    /// Whether answer changes should send notifications
+    #[serde(default = "default_notify")]
+    pub notify: bool,
+    /// Image detail sent to the model ("low" is cheaper)
+    #[serde(default)]
+    pub detail_level: Option<String>,
+}
+
+fn default_schedule() -> String {
+    "0 */5 * * * *".to_string()
+}
+
+fn default_min_confidence() -> f64 {
+    0.6
+}
+
+fn default_severity() -> EventSeverity {
+    EventSeverity::Medium
+}
+
+fn default_notify() -> bool {
+    true
+}
+
+impl VisualQuestion {
+    /// Scheduler job that asks this question about a stream
+    pub fn job_definition(&self, stream_id: &str) -> JobDefinition {
+        let parameters = serde_json::to_value(VisualQuestionParams {
+            stream_id: stream_id.to_string(),
+            question: self.clone(),
+        })
+        .unwrap_or_default();
+
+        let mut job = JobDefinition::new(
+            format!("Visual question {} on {}", self.id, stream_id),
+            JOB_TYPE.to_string(),
+            self.schedule.clone(),
+            parameters,
+            "system".to_string(),
+        )
+        .with_description(self.question.clone())
+        .with_max_retries(0)
+        .with_tags(vec![JOB_TYPE.to_string(), stream_id.to_string()]);
+        job.id = Self::job_id(stream_id, &self.id);
+        job
+    }
+
+    /// Job ID of a stream's question, stable across restarts
+    pub fn job_id(stream_id: &str, question_id: &str) -> String {
+        format!("{}_{}_{}", JOB_TYPE, stream_id, question_id)
+    }
+}
+
+/// Answer to a yes/no question
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VisualAnswer {
+    Yes,
+    No,
+    Unknown,
+}
+
+impl VisualAnswer {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Yes => "yes",
+            Self::No => "no",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+/// Structured answer the model gave
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuestionVerdict {
+    pub answer: VisualAnswer,
+    /// 0.0 to 1.0
+    pub confidence: f64,
+    pub reason: String,
+}
+
+/// Reply format requested from the model
+#[derive(Deserialize)]
+struct VerdictReply {
+    answer: String,
+    confidence: Option<f64>,
+    reason: Option<String>,
+}
+
+impl QuestionVerdict {
+    /// Read the model's JSON reply, tolerating code fences or prose around the object;
+    /// anything that isn't the requested structure counts as unknown
+    pub fn parse(reply: &str) -> Self {
+        let object = match (reply.find('{'), reply.rfind('}')) {
+            (Some(start), Some(end)) if start < end => &reply[start..=end],
+            _ => reply,
+        };
+        let Ok(parsed) = serde_json::from_str::<VerdictReply>(object) else {
+            return Self {
+                answer: VisualAnswer::Unknown,
+                confidence: 0.0,
+                reason: format!("Unstructured reply: {}", reply.trim()),
+            };
+        };
+
+        let answer = match parsed.answer.trim().to_lowercase().as_str() {
+            "yes" | "true" => VisualAnswer::Yes,
+            "no" | "false" => VisualAnswer::No,
+            _ => VisualAnswer::Unknown,
+        };
+        Self {
+            answer,
+            // An answer without a confidence is not one we can rely on
+            confidence: parsed.confidence.unwrap_or(0.0).clamp(0.0, 1.0),
+            reason: parsed.reason.unwrap_or_default(),
+        }
+    }
+
+    /// The answer if it is confident enough to act on
+    pub fn settled_answer(&self, min_confidence: f64) -> VisualAnswer {
+        if self.confidence >= min_confidence {
+            self.answer
+        } else {
+            VisualAnswer::Unknown
+        }
+    }
+}
+
+/// Last confident answer to each stream's questions
+///
+/// The first confident answer after start-up sets the baseline; unknown answers never
+/// replace a known one, so a dark or blurry frame doesn't read as a change.
+#[derive(Debug, Default)]
+pub struct QuestionTracker {
+    answers: HashMap<(String, String), VisualAnswer>,
+}
+
+impl QuestionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Last confident answer to a question
+    pub fn answer(&self, stream_id: &str, question_id: &str) -> Option<VisualAnswer> {
+        self.answers
+            .get(&(stream_id.to_string(), question_id.to_string()))
+            .copied()
+    }
+
+    /// Record a verdict, returning an event when it flips the last confident answer
+    pub fn observe(
+        &mut self,
+        stream_id: &str,
+        question: &VisualQuestion,
+        verdict: &QuestionVerdict,
+        at: DateTime<Utc>,
+    ) -> Option<AnalysisEvent> {
+        let answer = verdict.settled_answer(question.min_confidence);
+        if answer == VisualAnswer::Unknown {
+            return None;
+        }
+        let previous = self
+            .answers
+            .insert((stream_id.to_string(), question.id.clone()), answer)?;
+        if previous == answer {
+            return None;
+        }
+
+        let mut event = AnalysisEvent::new(
+            stream_id.to_string(),
+            "question_answer_changed".to_string(),
+            question.severity.clone(),
+            verdict.confidence,
+            format!(
+                "{} Now {} (was {})",
+                question.question.trim(),
+                answer.as_str(),
+                previous.as_str()
+            ),
+            PROCESSOR_NAME.to_string(),
+            stream_id.to_string(),
+        )
+        .with_metadata("question_id".to_string(), question.id.clone().into())
+        .with_metadata("question".to_string(), question.question.clone().into())
+        .with_metadata("answer".to_string(), answer.as_str().into())
+        .with_metadata("previous_answer".to_string(), previous.as_str().into())
+        .with_metadata("reason".to_string(), verdict.reason.clone().into())
+        .with_notification(question.notify);
+        event.timestamp = at;
+        Some(event)
+    }
+}
+
+/// Parameters of a visual question job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VisualQuestionParams {
+    pub stream_id: String,
+    #[serde(flatten)]
+    pub question: VisualQuestion,
+}
+
+/// Scheduler job that captures a frame, asks the vision model a question about it and
+/// publishes an event when the answer changes
+pub struct VisualQuestionJob {
+    client: Arc<dyn AiClient>,
+    analysis_service: Option<Arc<tokio::sync::Mutex<AnalysisService>>>,
+    tracker: Mutex<QuestionTracker>,
+}
+
+impl VisualQuestionJob {
+    pub fn new(
+        client: Arc<dyn AiClient>,
+        analysis_service: Option<Arc<tokio::sync::Mutex<AnalysisService>>>,
+    ) -> Self {
+        Self {
+            client,
+            analysis_service,
+            tracker: Mutex::new(QuestionTracker::new()),
+        }
+    }
+
+    fn parse_parameters(parameters: &serde_json::Value) -> Result<VisualQuestionParams> {
+        let params: VisualQuestionParams =
+            serde_json::from_value(parameters.clone()).map_err(|e| {
+                gl_core::Error::Validation(format!("Invalid visual question parameters: {}", e))
+            })?;
+        if params.question.question.trim().is_empty() {
+            return Err(gl_core::Error::Validation(
+                "Visual question cannot be empty".to_string(),
+            ));
+        }
+        Ok(params)
+    }
+}
+
+#[async_trait]
+impl JobHandler for VisualQuestionJob {
+    async fn execute(&self, context: JobContext) -> Result<serde_json::Value> {
+        let VisualQuestionParams {
+            stream_id,
+            question,
+        } = Self::parse_parameters(&context.parameters)?;
+        debug!(stream_id = %stream_id, question_id = %question.id, "Asking visual question");
+
+        let capture = match context.capture_service.capture(&stream_id).await {
+            Ok(capture) => capture,
+            Err(e) => {
+                return Ok(serde_json::json!({
+                    "stream_id": stream_id,
+                    "question_id": question.id,
+                    "timestamp": Utc::now().to_rfc3339(),
+                    "status": "capture_failed",
+                    "error": format!("Failed to capture frame: {}", e),
+                }));
+            }
+        };
+        let image_format = capture
+            .content_type
+            .strip_prefix("image/")
+            .unwrap_or("jpeg")
+            .to_string();
+
+        let response = self
+            .client
+            .describe_frame(DescribeFrameRequest {
+                image_data: Bytes::from(capture.data),
+                image_format,
+                detail_level: question.detail_level.clone(),
+                focus: None,
+                stream_id: Some(stream_id.clone()),
+                question: Some(question.question.clone()),
+                output_schema: None,
+            })
+            .await?;
+        let verdict = QuestionVerdict::parse(&response.description);
+
+        let event =
+            self.tracker
+                .lock()
+                .unwrap()
+                .observe(&stream_id, &question, &verdict, Utc::now());
+        let changed = event.is_some();
+        if let Some(event) = event {
+            info!(stream_id = %stream_id, question_id = %question.id, description = %event.description, "Visual question answer changed");
+            if let Some(analysis_service) = &self.analysis_service {
+                let context = ProcessorContext::new(stream_id.clone());
+                if let Err(e) = analysis_service
+                    .lock()
+                    .await
+                    .publish_events(&context, vec![event])
+                    .await
+                {
+                    warn!(stream_id = %stream_id, error = %e, "Failed to publish visual question event");
+                }
+            }
+        }
+
+        Ok(serde_json::json!({
+            "stream_id": stream_id,
+            "question_id": question.id,
+            "timestamp": Utc::now().to_rfc3339(),
+            "status": "answered",
+            "answer": verdict.answer,
+            "confidence": verdict.confidence,
+            "reason": verdict.reason,
+            "changed": changed,
+        }))
+    }
+
+    fn job_type(&self) -> &'static str {
+        JOB_TYPE
+    }
+
+    fn validate_parameters(&self, parameters: &serde_json::Value) -> Result<()> {
+        Self::parse_parameters(parameters).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gl_ai::{
+        ClassifyEventRequest, ClassifyEventResponse, DescribeFrameResponse, SummarizeRequest,
+        SummarizeResponse,
+    };
+    use gl_scheduler::{CaptureResult, CaptureService};
+
+    fn question() -> VisualQuestion {
+        serde_json::from_value(serde_json::json!({
+            "id": "garage_door",
+            "question": "Is the garage door open?"
+        }))
+        .unwrap()
+    }
+
+    fn verdict(answer: VisualAnswer, confidence: f64) -> QuestionVerdict {
+        QuestionVerdict {
+            answer,
+            confidence,
+            reason: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_question_defaults_and_job_definition() {
+        let question = question();
+        assert_eq!(question.schedule, "0 */5 * * * *");
+        assert_eq!(question.severity, EventSeverity::Medium);
+        assert!(question.notify);
+
+        let job = question.job_definition("garage");
+        assert_eq!(job.id, "visual_question_garage_garage_door");
+        assert_eq!(job.job_type, JOB_TYPE);
+        job.validate().unwrap();
+        let params: VisualQuestionParams = serde_json::from_value(job.parameters).unwrap();
+        assert_eq!(params.stream_id, "garage");
+        assert_eq!(params.question, question);
+    }
+
+    #[test]
+    fn test_parse_verdict() {
+        let parsed = QuestionVerdict::parse(
+            "```json\n{\"answer\": \"Yes\", \"confidence\": 0.92, \"reason\": \"The door is up.\"}\n```",
+        );
+        assert_eq!(parsed.answer, VisualAnswer::Yes);
+        assert!((parsed.confidence - 0.92).abs() < 1e-9);
+        assert_eq!(parsed.reason, "The door is up.");
+
+        let parsed = QuestionVerdict::parse(r#"{"answer": "no", "confidence": 7}"#);
+        assert_eq!(parsed.answer, VisualAnswer::No);
+        assert_eq!(parsed.confidence, 1.0);
+
+        // Free text and answers without a confidence can't be relied on
+        let parsed = QuestionVerdict::parse("Yes, the door appears to be open.");
+        assert_eq!(parsed.answer, VisualAnswer::Unknown);
+        assert_eq!(parsed.confidence, 0.0);
+        let parsed = QuestionVerdict::parse(r#"{"answer": "yes"}"#);
+        assert_eq!(parsed.settled_answer(0.6), VisualAnswer::Unknown);
+    }
+
+    #[test]
+    fn test_tracker_reports_flips_only() {
+        let question = question();
+        let mut tracker = QuestionTracker::new();
+        let at = Utc::now();
+
+        // First confident answer is the baseline
+        assert!(tracker
+            .observe("garage", &question, &verdict(VisualAnswer::No, 0.9), at)
+            .is_none());
+        assert!(tracker
+            .observe("garage", &question, &verdict(VisualAnswer::No, 0.8), at)
+            .is_none());
+        // Unsure and unknown answers keep the last known state
+        assert!(tracker
+            .observe("garage", &question, &verdict(VisualAnswer::Yes, 0.3), at)
+            .is_none());
+        assert!(tracker
+            .observe(
+                "garage",
+                &question,
+                &verdict(VisualAnswer::Unknown, 1.0),
+                at
+            )
+            .is_none());
+        assert_eq!(
+            tracker.answer("garage", "garage_door"),
+            Some(VisualAnswer::No)
+        );
+
+        let event = tracker
+            .observe("garage", &question, &verdict(VisualAnswer::Yes, 0.95), at)
+            .expect("answer flipped");
+        assert_eq!(event.event_type, "question_answer_changed");
+        assert_eq!(event.source_id, "garage");
+        assert_eq!(
+            event.description,
+            "Is the garage door open? Now yes (was no)"
+        );
+        assert_eq!(event.metadata["previous_answer"], "no");
+        assert_eq!(event.timestamp, at);
+
+        // Other streams asking the same question are tracked separately
+        assert!(tracker
+            .observe("porch", &question, &verdict(VisualAnswer::Yes, 0.95), at)
+            .is_none());
+    }
+
+    struct FixedCapture;
+
+    #[async_trait]
+    impl CaptureService for FixedCapture {
+        async fn capture(&self, _stream_id: &str) -> Result<CaptureResult> {
+            Ok(CaptureResult {
+                data: b"fake_jpeg".to_vec(),
+                storage_path: String::new(),
+                storage_uri: String::new(),
+                content_type: "image/jpeg".to_string(),
+                width: 640,
+                height: 480,
+                checksum: String::new(),
+            })
+        }
+    }
+
+    /// Vision model that replies with queued answers in order
+    struct ScriptedClient {
+        replies: Mutex<Vec<&'static str>>,
+    }
+
+    #[async_trait]
+    impl AiClient for ScriptedClient {
+        async fn summarize(&self, _request: SummarizeRequest) -> Result<SummarizeResponse> {
+            unimplemented!()
+        }
+
+        async fn describe_frame(
+            &self,
+            request: DescribeFrameRequest,
+        ) -> Result<DescribeFrameResponse> {
+            assert_eq!(
+                request.question.as_deref(),
+                Some("Is the garage door open?")
+            );
+            assert_eq!(request.stream_id.as_deref(), Some("garage"));
+            Ok(DescribeFrameResponse {
+                description: self.replies.lock().unwrap().remove(0).to_string(),
+                objects_detected: Vec::new(),
+                confidence: None,
+                processing_time_ms: None,
+                structured: None,
+                usage: None,
+            })
+        }
+
+        async fn classify_event(
+            &self,
+            _request: ClassifyEventRequest,
+        ) -> Result<ClassifyEventResponse> {
+            unimplemented!()
+        }
+
+        async fn health_check(&self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_job_answers_question_about_captured_frame() {
+        let db_path =
+            std::env::temp_dir().join(format!("glimpser_questions_{}.db", gl_core::Id::new()));
+        let db = gl_db::Db::new(db_path.to_str().unwrap()).await.unwrap();
+        let client = Arc::new(ScriptedClient {
+            replies: Mutex::new(vec![
+                r#"{"answer": "no", "confidence": 0.9, "reason": "Closed."}"#,
+                r#"{"answer": "yes", "confidence": 0.85, "reason": "Open."}"#,
+            ]),
+        });
+        let job = VisualQuestionJob::new(client, None);
+        let parameters = question().job_definition("garage").parameters;
+        job.validate_parameters(&parameters).unwrap();
+
+        let run = || {
+            JobContext::new(
+                "visual_question_garage_garage_door".to_string(),
+                parameters.clone(),
+                db.clone(),
+                Arc::new(FixedCapture),
+            )
+        };
+        let output = job.execute(run()).await.unwrap();
+        assert_eq!(output["answer"], "no");
+        assert_eq!(output["changed"], false);
+
+        let output = job.execute(run()).await.unwrap();
+        assert_eq!(output["answer"], "yes");
+        assert_eq!(output["reason"], "Open.");
+        assert_eq!(output["changed"], true);
+    }
+}
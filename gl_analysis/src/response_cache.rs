@@ -0,0 +1,68 @@
+//! ABOUTME: SQLite-backed store for the AI client's perceptual-hash response cache
+//! ABOUTME: Lets cached frame descriptions survive restarts when analysis runs with a database
+
+use async_trait::async_trait;
+use gl_ai::{AiConfig, CachedResponse, ResponseCacheStore, SharedCacheStore};
+use gl_core::Result;
+use gl_db::AiResponseCacheEntry;
+use std::sync::Arc;
+
+/// Cache store kept in the `ai_response_cache` table
+pub struct DbResponseCache {
+    repo: gl_db::AiResponseCacheRepository,
+}
+
+impl DbResponseCache {
+    pub fn new(db: gl_db::Db) -> Self {
+        Self {
+            repo: gl_db::AiResponseCacheRepository::new(db),
+        }
+    }
+}
+
+#[async_trait]
+impl ResponseCacheStore for DbResponseCache {
+    async fn recent(&self, key: &str, since: i64, limit: u32) -> Result<Vec<CachedResponse>> {
+        let entries = self.repo.recent(key, since, limit).await?;
+        entries
+            .into_iter()
+            .map(|entry| {
+                let response = serde_json::from_str(&entry.response).map_err(|e| {
+                    gl_core::Error::Database(format!("Invalid cached AI response: {}", e))
+                })?;
+                Ok(CachedResponse {
+                    perceptual_hash: entry.perceptual_hash,
+                    response,
+                    created_at: entry.created_at,
+                })
+            })
+            .collect()
+    }
+
+    async fn insert(&self, key: &str, entry: CachedResponse) -> Result<()> {
+        let response = serde_json::to_string(&entry.response).map_err(|e| {
+            gl_core::Error::Validation(format!("Failed to encode AI response: {}", e))
+        })?;
+        self.repo
+            .insert(&AiResponseCacheEntry {
+                cache_key: key.to_string(),
+                perceptual_hash: entry.perceptual_hash,
+                response,
+                created_at: entry.created_at,
+            })
+            .await
+    }
+
+    async fn prune(&self, before: i64) -> Result<()> {
+        self.repo.delete_before(before).await.map(|_| ())
+    }
+}
+
+/// Keep a cached AI configuration's responses in the database unless it already has a store
+pub fn persist_ai_cache(ai_config: &mut AiConfig, db: &gl_db::Db) {
+    if let Some(cache) = ai_config.cache.as_mut() {
+        if cache.store.is_none() {
+            cache.store = Some(SharedCacheStore(Arc::new(DbResponseCache::new(db.clone()))));
+        }
+    }
+}
@@ -1,17 +1,44 @@
 //! ABOUTME: Rule engine for evaluating conditions and actions based on events and context
 //! ABOUTME: Handles YAML/JSON rules with thresholds, quiet hours, and deduplication logic
 
+use crate::actions::{ActionExecutor, CaptureControl};
 use crate::{AnalysisEvent, EventSeverity, ProcessorInput};
 use chrono::{DateTime, Datelike, Utc};
 use gl_core::Result;
+use gl_db::{RecordRuleEvent, RuleStateRepository};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
 use tracing::{debug, warn};
 
+/// Default history retention when no rule needs a longer window
+const DEFAULT_HISTORY_WINDOW_MINUTES: u32 = 60;
+
+/// Rate limit windows are fixed one-hour buckets
+const RATE_LIMIT_WINDOW_SECONDS: i64 = 3600;
+
+/// Minimum interval between purges of expired persisted state
+const PURGE_INTERVAL_SECONDS: i64 = 60;
+
 /// Rule engine for processing analysis rules
+///
+/// History, deduplication keys and rate limit counters are kept in memory by
+/// default. Engines created with [`RuleEngine::with_persistence`] store them in
+/// SQLite instead, so they survive restarts and are shared by every service
+/// using the same database.
+///
+/// Actions with side effects (HTTP calls, snapshot bursts, recordings, AI
+/// describes) only run for events that survive every rule, deduplication and
+/// quiet hours. Describe actions run inline so their output lands in the event
+/// metadata; the others are spawned in the background.
 pub struct RuleEngine {
     rules: Option<RuleSet>,
     event_history: Vec<AnalysisEvent>,
     max_history_size: usize,
+    rate_limit_counters: HashMap<(String, String, i64), u32>,
+    state_repo: Option<RuleStateRepository>,
+    last_purge: Option<DateTime<Utc>>,
+    action_executor: ActionExecutor,
 }
 
 /// Set of rules for a template or global configuration
@@ -80,7 +107,8 @@ pub enum ConditionType {
         operator: ComparisonOperator,
         window_minutes: u32,
     },
-    /// Metadata field condition
+    /// Metadata field condition; `field` may be a dotted path into nested
+    /// objects and arrays (e.g. `vehicles.0.color`)
     Metadata {
         field: String,
         operator: ComparisonOperator,
@@ -88,6 +116,9 @@ pub enum ConditionType {
     },
     /// Source ID condition
     SourceId { pattern: String, matches: bool },
+    /// Text that arrived with the input (e.g. an alarm email), case-insensitive;
+    /// `*` is a wildcard, otherwise the pattern matches anywhere in the text
+    Text { pattern: String, matches: bool },
 }
 
 /// Comparison operators for conditions
@@ -123,6 +154,70 @@ pub enum Action {
     SetNotificationTemplate { template: String },
     /// Rate limit notifications
     RateLimit { max_per_hour: u32 },
+    /// Call an outbound HTTP endpoint; URL, header values and body are templates
+    HttpCall {
+        url: String,
+        #[serde(default = "default_http_method")]
+        method: String,
+        #[serde(default)]
+        headers: HashMap<String, String>,
+        #[serde(default)]
+        body: Option<String>,
+        #[serde(default)]
+        timeout_seconds: Option<u64>,
+    },
+    /// Take a burst of snapshots from the event's stream
+    SnapshotBurst {
+        count: u32,
+        #[serde(default = "default_burst_interval_ms")]
+        interval_ms: u64,
+    },
+    /// Record the event's stream for a fixed duration
+    StartRecording { duration_seconds: u64 },
+    /// Run the AI description processor on the triggering frame
+    DescribeFrame {
+        #[serde(default)]
+        detail_level: Option<String>,
+        #[serde(default)]
+        focus: Option<String>,
+    },
+}
+
+fn default_http_method() -> String {
+    "POST".to_string()
+}
+
+fn default_burst_interval_ms() -> u64 {
+    1000
+}
+
+impl Action {
+    /// Action type name as used in rule definitions
+    pub fn action_type(&self) -> &'static str {
+        match self {
+            Action::SuppressNotification => "suppress_notification",
+            Action::SetSeverity { .. } => "set_severity",
+            Action::AddMetadata { .. } => "add_metadata",
+            Action::DeleteEvent => "delete_event",
+            Action::SetNotificationTemplate { .. } => "set_notification_template",
+            Action::RateLimit { .. } => "rate_limit",
+            Action::HttpCall { .. } => "http_call",
+            Action::SnapshotBurst { .. } => "snapshot_burst",
+            Action::StartRecording { .. } => "start_recording",
+            Action::DescribeFrame { .. } => "describe_frame",
+        }
+    }
+
+    /// Whether the action acts outside the event and runs through the [`ActionExecutor`]
+    pub fn has_side_effects(&self) -> bool {
+        matches!(
+            self,
+            Action::HttpCall { .. }
+                | Action::SnapshotBurst { .. }
+                | Action::StartRecording { .. }
+                | Action::DescribeFrame { .. }
+        )
+    }
 }
 
 /// Deduplication configuration
@@ -156,18 +251,48 @@ impl RuleEngine {
             rules,
             event_history: Vec::new(),
             max_history_size: 1000,
+            rate_limit_counters: HashMap::new(),
+            state_repo: None,
+            last_purge: None,
+            action_executor: ActionExecutor::new(),
+        }
+    }
+
+    /// Create a rule engine whose history, dedup keys and rate limits are persisted
+    pub fn with_persistence(rules: Option<RuleSet>, db: gl_db::Db) -> Self {
+        Self {
+            state_repo: Some(RuleStateRepository::new(db.clone())),
+            action_executor: ActionExecutor::with_persistence(db),
+            ..Self::new(rules)
         }
     }
 
+    /// Replace the active rule set while keeping accumulated state
+    pub fn set_rules(&mut self, rules: Option<RuleSet>) {
+        self.rules = rules;
+    }
+
+    /// Provide the capture operations used by snapshot burst and recording actions
+    pub fn set_capture_control(&mut self, capture_control: Arc<dyn CaptureControl>) {
+        self.action_executor.set_capture_control(capture_control);
+    }
+
+    /// Set the AI configuration used by describe actions
+    pub fn set_ai_config(&mut self, ai_config: gl_ai::AiConfig) {
+        self.action_executor.set_ai_config(ai_config);
+    }
+
     /// Apply rules to events and return modified events
     pub async fn apply_rules(
         &mut self,
         input: &ProcessorInput,
         events: Vec<AnalysisEvent>,
     ) -> Result<Vec<AnalysisEvent>> {
-        let Some(rule_set) = &self.rules else {
+        self.purge_expired_state().await;
+
+        let Some(rule_set) = self.rules.clone() else {
             debug!("No rules configured, returning events unchanged");
-            self.update_history(&events);
+            self.update_history(&events).await;
             return Ok(events);
         };
 
@@ -187,6 +312,7 @@ impl RuleEngine {
         let events_count = events.len();
         for mut event in events {
             let mut keep_event = true;
+            let mut side_effects = Vec::new();
 
             // Apply individual rules
             for rule in &rules {
@@ -201,7 +327,12 @@ impl RuleEngine {
                     );
 
                     for action in &rule.actions {
-                        match self.apply_action(action, &mut event).await {
+                        if action.has_side_effects() {
+                            side_effects.push((rule.id.clone(), action.clone()));
+                            continue;
+                        }
+
+                        match self.apply_action(rule, action, &mut event).await {
                             Ok(should_keep) => {
                                 if !should_keep {
                                     keep_event = false;
@@ -223,7 +354,7 @@ impl RuleEngine {
             // Apply global deduplication
             if keep_event {
                 if let Some(dedup_config) = &rule_set.deduplication {
-                    keep_event = self.check_deduplication(&event, dedup_config);
+                    keep_event = self.check_deduplication(&event, dedup_config).await;
                 }
             }
 
@@ -235,11 +366,12 @@ impl RuleEngine {
             }
 
             if keep_event {
+                self.run_side_effects(side_effects, &mut event, input).await;
                 events_to_keep.push(event);
             }
         }
 
-        self.update_history(&events_to_keep);
+        self.update_history(&events_to_keep).await;
 
         debug!(
             "Rule engine kept {} out of {} events",
@@ -255,7 +387,7 @@ impl RuleEngine {
         &self,
         rule: &Rule,
         event: &AnalysisEvent,
-        _input: &ProcessorInput,
+        input: &ProcessorInput,
     ) -> Result<bool> {
         if rule.conditions.is_empty() {
             return Ok(true);
@@ -264,7 +396,7 @@ impl RuleEngine {
         // All conditions must be true (AND logic)
         for condition in &rule.conditions {
             if !self
-                .evaluate_condition(&condition.condition_type, event)
+                .evaluate_condition(&condition.condition_type, event, input)
                 .await?
             {
                 return Ok(false);
@@ -279,6 +411,7 @@ impl RuleEngine {
         &self,
         condition: &ConditionType,
         event: &AnalysisEvent,
+        input: &ProcessorInput,
     ) -> Result<bool> {
         match condition {
             ConditionType::EventType { pattern, matches } => {
@@ -312,8 +445,9 @@ impl RuleEngine {
                 operator,
                 window_minutes,
             } => {
-                let matching_count =
-                    self.count_recent_events(event_type.as_deref(), *window_minutes);
+                let matching_count = self
+                    .count_recent_events(event_type.as_deref(), *window_minutes)
+                    .await?;
                 Ok(self.compare_numeric(matching_count as f64, operator, *count as f64))
             }
 
@@ -322,7 +456,10 @@ impl RuleEngine {
                 operator,
                 value,
             } => {
-                let event_value = event.metadata.get(field);
+                let event_value = event
+                    .metadata
+                    .get(field)
+                    .or_else(|| Self::metadata_path(&event.metadata, field));
                 Ok(self.compare_metadata_value(event_value, operator, value))
             }
 
@@ -337,11 +474,30 @@ impl RuleEngine {
                 };
                 Ok(pattern_matches == *matches)
             }
+
+            ConditionType::Text { pattern, matches } => {
+                let text = input.text_content.as_deref().unwrap_or_default();
+                let pattern_matches = if pattern.contains('*') {
+                    let pattern_regex =
+                        format!("(?is)^{}$", regex::escape(pattern).replace(r"\*", ".*"));
+                    regex::Regex::new(&pattern_regex)
+                        .map_err(|e| gl_core::Error::Validation(format!("Invalid pattern: {}", e)))?
+                        .is_match(text)
+                } else {
+                    text.to_lowercase().contains(&pattern.to_lowercase())
+                };
+                Ok(pattern_matches == *matches)
+            }
         }
     }
 
     /// Apply an action to an event, returning whether to keep the event
-    async fn apply_action(&self, action: &Action, event: &mut AnalysisEvent) -> Result<bool> {
+    async fn apply_action(
+        &mut self,
+        rule: &Rule,
+        action: &Action,
+        event: &mut AnalysisEvent,
+    ) -> Result<bool> {
         match action {
             Action::SuppressNotification => {
                 debug!("Suppressing notification for event {}", event.event_type);
@@ -377,37 +533,147 @@ impl RuleEngine {
                 Ok(true)
             }
 
-            Action::RateLimit { max_per_hour: _ } => {
-                // Rate limiting would be handled by the notification system
-                debug!("Rate limiting configured for event");
+            Action::RateLimit { max_per_hour } => {
+                if !event.should_notify {
+                    return Ok(true);
+                }
+
+                let count = self
+                    .increment_rate_limit(&rule.id, &event.source_id)
+                    .await?;
+                if count > *max_per_hour {
+                    debug!(
+                        "Rate limit of {}/hour reached for rule '{}' on source {}, suppressing notification",
+                        max_per_hour, rule.id, event.source_id
+                    );
+                    event.should_notify = false;
+                    event
+                        .metadata
+                        .insert("rate_limited".to_string(), serde_json::Value::Bool(true));
+                }
                 Ok(true)
             }
+
+            // Side effects run once the event is known to be kept
+            Action::HttpCall { .. }
+            | Action::SnapshotBurst { .. }
+            | Action::StartRecording { .. }
+            | Action::DescribeFrame { .. } => Ok(true),
         }
     }
 
+    /// Execute side-effect actions collected for a kept event
+    async fn run_side_effects(
+        &self,
+        actions: Vec<(String, Action)>,
+        event: &mut AnalysisEvent,
+        input: &ProcessorInput,
+    ) {
+        if actions.is_empty() {
+            return;
+        }
+
+        let action_types: Vec<serde_json::Value> = actions
+            .iter()
+            .map(|(_, action)| action.action_type().into())
+            .collect();
+        event
+            .metadata
+            .insert("rule_actions".to_string(), action_types.into());
+
+        let (describes, background): (Vec<_>, Vec<_>) = actions
+            .into_iter()
+            .partition(|(_, action)| matches!(action, Action::DescribeFrame { .. }));
+
+        // Describe first so background actions can template the description
+        for (rule_id, action) in describes {
+            let outcome = self
+                .action_executor
+                .execute(&rule_id, &action, event, input)
+                .await;
+            if let Some(output) = outcome.output {
+                event
+                    .metadata
+                    .insert("ai_description".to_string(), output["description"].clone());
+                event.metadata.insert(
+                    "ai_objects_detected".to_string(),
+                    output["objects_detected"].clone(),
+                );
+            }
+        }
+
+        for (rule_id, action) in background {
+            let executor = self.action_executor.clone();
+            let event = event.clone();
+            let input = input.clone();
+            tokio::spawn(async move {
+                executor.execute(&rule_id, &action, &event, &input).await;
+            });
+        }
+    }
+
+    /// Increment the notification counter for a rule/source in the current hour window
+    async fn increment_rate_limit(&mut self, rule_id: &str, source_id: &str) -> Result<u32> {
+        let now = Utc::now().timestamp();
+        let window_start = now - now.rem_euclid(RATE_LIMIT_WINDOW_SECONDS);
+
+        if let Some(repo) = &self.state_repo {
+            return repo
+                .increment_rate_limit(rule_id, source_id, window_start, RATE_LIMIT_WINDOW_SECONDS)
+                .await;
+        }
+
+        self.rate_limit_counters
+            .retain(|(_, _, start), _| *start == window_start);
+        let counter = self
+            .rate_limit_counters
+            .entry((rule_id.to_string(), source_id.to_string(), window_start))
+            .or_insert(0);
+        *counter += 1;
+        Ok(*counter)
+    }
+
     /// Check if event should be deduplicated
-    fn check_deduplication(&self, event: &AnalysisEvent, config: &DeduplicationConfig) -> bool {
+    async fn check_deduplication(
+        &self,
+        event: &AnalysisEvent,
+        config: &DeduplicationConfig,
+    ) -> bool {
         if !config.event_types.contains(&event.event_type) {
             return true; // Not subject to deduplication
         }
 
-        let cutoff_time = Utc::now() - chrono::Duration::minutes(config.window_minutes as i64);
-
-        // Generate deduplication key
-        let mut key_parts = Vec::new();
-        for field in &config.key_fields {
-            match field.as_str() {
-                "event_type" => key_parts.push(event.event_type.clone()),
-                "source_id" => key_parts.push(event.source_id.clone()),
-                "template_id" => key_parts.push(event.template_id.clone()),
-                _ => {
-                    if let Some(value) = event.metadata.get(field) {
-                        key_parts.push(value.to_string());
-                    }
+        let dedup_key = Self::dedup_key(event, &config.key_fields);
+
+        if let Some(repo) = &self.state_repo {
+            let scoped_key = format!("{}|{}", event.event_type, dedup_key);
+            return match repo
+                .claim_dedup_key(
+                    &scoped_key,
+                    &event.event_type,
+                    &event.id,
+                    Utc::now().timestamp(),
+                    config.window_minutes as i64 * 60,
+                )
+                .await
+            {
+                Ok(true) => true,
+                Ok(false) => {
+                    debug!(
+                        "Deduplicating event {} with key: {}",
+                        event.event_type, dedup_key
+                    );
+                    false
                 }
-            }
+                Err(e) => {
+                    // Fail open: a lost notification is worse than a duplicate one
+                    warn!("Failed to check persisted dedup key, keeping event: {}", e);
+                    true
+                }
+            };
         }
-        let dedup_key = key_parts.join("|");
+
+        let cutoff_time = Utc::now() - chrono::Duration::minutes(config.window_minutes as i64);
 
         // Check if we have a recent event with the same key
         let has_recent_duplicate = self
@@ -415,24 +681,7 @@ impl RuleEngine {
             .iter()
             .filter(|e| e.timestamp > cutoff_time)
             .filter(|e| e.event_type == event.event_type)
-            .any(|e| {
-                // Reconstruct key for historical event
-                let mut hist_key_parts = Vec::new();
-                for field in &config.key_fields {
-                    match field.as_str() {
-                        "event_type" => hist_key_parts.push(e.event_type.clone()),
-                        "source_id" => hist_key_parts.push(e.source_id.clone()),
-                        "template_id" => hist_key_parts.push(e.template_id.clone()),
-                        _ => {
-                            if let Some(value) = e.metadata.get(field) {
-                                hist_key_parts.push(value.to_string());
-                            }
-                        }
-                    }
-                }
-                let hist_dedup_key = hist_key_parts.join("|");
-                hist_dedup_key == dedup_key
-            });
+            .any(|e| Self::dedup_key(e, &config.key_fields) == dedup_key);
 
         if has_recent_duplicate {
             debug!(
@@ -445,6 +694,24 @@ impl RuleEngine {
         }
     }
 
+    /// Build the deduplication key for an event from the configured fields
+    fn dedup_key(event: &AnalysisEvent, key_fields: &[String]) -> String {
+        let mut key_parts = Vec::new();
+        for field in key_fields {
+            match field.as_str() {
+                "event_type" => key_parts.push(event.event_type.clone()),
+                "source_id" => key_parts.push(event.source_id.clone()),
+                "template_id" => key_parts.push(event.template_id.clone()),
+                _ => {
+                    if let Some(value) = event.metadata.get(field) {
+                        key_parts.push(value.to_string());
+                    }
+                }
+            }
+        }
+        key_parts.join("|")
+    }
+
     /// Apply quiet hours configuration
     fn apply_quiet_hours(&self, event: &mut AnalysisEvent, config: &QuietHoursConfig) {
         if self.is_in_time_window(
@@ -526,6 +793,23 @@ impl RuleEngine {
         }
     }
 
+    /// Resolve a dotted path such as `vehicles.0.color` through nested metadata
+    fn metadata_path<'a>(
+        metadata: &'a HashMap<String, serde_json::Value>,
+        path: &str,
+    ) -> Option<&'a serde_json::Value> {
+        let mut segments = path.split('.');
+        let mut current = metadata.get(segments.next()?)?;
+        for segment in segments {
+            current = match current {
+                serde_json::Value::Object(fields) => fields.get(segment)?,
+                serde_json::Value::Array(items) => items.get(segment.parse::<usize>().ok()?)?,
+                _ => return None,
+            };
+        }
+        Some(current)
+    }
+
     /// Helper function to compare metadata values
     fn compare_metadata_value(
         &self,
@@ -537,42 +821,92 @@ impl RuleEngine {
             return matches!(op, ComparisonOperator::NotEqual);
         };
 
+        let contains = |value: &serde_json::Value| {
+            match (value, target) {
+            (serde_json::Value::String(text), serde_json::Value::String(needle)) => {
+                text.contains(needle.as_str())
+            }
+            (serde_json::Value::Array(items), _) => items.iter().any(|item| {
+                item == target
+                    || matches!((item.as_str(), target.as_str()), (Some(text), Some(needle)) if text.contains(needle))
+            }),
+            _ => false,
+        }
+        };
+
         match op {
             ComparisonOperator::Equal => event_value == target,
             ComparisonOperator::NotEqual => event_value != target,
-            ComparisonOperator::Contains => {
-                if let (Some(event_str), Some(target_str)) = (event_value.as_str(), target.as_str())
-                {
-                    event_str.contains(target_str)
-                } else {
-                    false
-                }
-            }
-            ComparisonOperator::NotContains => {
-                if let (Some(event_str), Some(target_str)) = (event_value.as_str(), target.as_str())
-                {
-                    !event_str.contains(target_str)
-                } else {
-                    true
-                }
-            }
-            _ => false,
+            ComparisonOperator::Contains => contains(event_value),
+            ComparisonOperator::NotContains => !contains(event_value),
+            _ => match (event_value.as_f64(), target.as_f64()) {
+                (Some(actual), Some(expected)) => self.compare_numeric(actual, op, expected),
+                _ => false,
+            },
         }
     }
 
     /// Count recent events matching criteria
-    fn count_recent_events(&self, event_type: Option<&str>, window_minutes: u32) -> u32 {
+    async fn count_recent_events(
+        &self,
+        event_type: Option<&str>,
+        window_minutes: u32,
+    ) -> Result<u32> {
         let cutoff_time = Utc::now() - chrono::Duration::minutes(window_minutes as i64);
 
-        self.event_history
+        if let Some(repo) = &self.state_repo {
+            return repo
+                .count_events_since(event_type, cutoff_time.timestamp())
+                .await;
+        }
+
+        Ok(self
+            .event_history
             .iter()
             .filter(|e| e.timestamp > cutoff_time)
             .filter(|e| event_type.map_or(true, |et| e.event_type == et))
-            .count() as u32
+            .count() as u32)
+    }
+
+    /// Longest window any configured rule needs from the event history
+    fn history_window_minutes(&self) -> u32 {
+        let Some(rule_set) = &self.rules else {
+            return DEFAULT_HISTORY_WINDOW_MINUTES;
+        };
+
+        rule_set
+            .rules
+            .iter()
+            .flat_map(|rule| rule.conditions.iter())
+            .filter_map(|condition| match &condition.condition_type {
+                ConditionType::EventCount { window_minutes, .. } => Some(*window_minutes),
+                _ => None,
+            })
+            .chain(rule_set.deduplication.iter().map(|d| d.window_minutes))
+            .fold(DEFAULT_HISTORY_WINDOW_MINUTES, u32::max)
     }
 
     /// Update event history
-    fn update_history(&mut self, new_events: &[AnalysisEvent]) {
+    async fn update_history(&mut self, new_events: &[AnalysisEvent]) {
+        if let Some(repo) = &self.state_repo {
+            let retention_seconds = self.history_window_minutes() as i64 * 60;
+            for event in new_events {
+                let occurred_at = event.timestamp.timestamp();
+                let record = RecordRuleEvent {
+                    event_id: event.id.clone(),
+                    template_id: event.template_id.clone(),
+                    event_type: event.event_type.clone(),
+                    source_id: event.source_id.clone(),
+                    occurred_at,
+                    expires_at: occurred_at + retention_seconds,
+                };
+                if let Err(e) = repo.record_event(record).await {
+                    warn!("Failed to persist rule event history: {}", e);
+                }
+            }
+            return;
+        }
+
         self.event_history.extend(new_events.iter().cloned());
 
         // Keep only recent events to prevent unbounded growth
@@ -582,12 +916,45 @@ impl RuleEngine {
         }
     }
 
+    /// Purge expired persisted state, at most once per purge interval
+    async fn purge_expired_state(&mut self) {
+        let Some(repo) = &self.state_repo else {
+            return;
+        };
+
+        let now = Utc::now();
+        if self
+            .last_purge
+            .is_some_and(|last| (now - last).num_seconds() < PURGE_INTERVAL_SECONDS)
+        {
+            return;
+        }
+
+        if let Err(e) = repo.purge_expired(now.timestamp()).await {
+            warn!("Failed to purge expired rule engine state: {}", e);
+        }
+        self.last_purge = Some(now);
+    }
+
     /// Clear event history
+    ///
+    /// Only clears in-memory state; use [`RuleEngine::clear_state`] to also
+    /// remove persisted state.
     pub fn clear_history(&mut self) {
         self.event_history.clear();
+        self.rate_limit_counters.clear();
+    }
+
+    /// Clear in-memory and persisted rule engine state
+    pub async fn clear_state(&mut self) -> Result<()> {
+        self.clear_history();
+        if let Some(repo) = &self.state_repo {
+            repo.clear().await?;
+        }
+        Ok(())
     }
 
-    /// Get event history size
+    /// Get in-memory event history size
     pub fn history_size(&self) -> usize {
         self.event_history.len()
     }
@@ -701,6 +1068,119 @@ mod tests {
         assert_eq!(result[0].severity, EventSeverity::High);
     }
 
+    #[tokio::test]
+    async fn test_structured_metadata_condition() {
+        let rule = Rule {
+            id: "red_vehicles".to_string(),
+            name: "Two or more red vehicles".to_string(),
+            description: None,
+            conditions: vec![
+                Condition {
+                    condition_type: ConditionType::Metadata {
+                        field: "vehicle_count".to_string(),
+                        operator: ComparisonOperator::GreaterThanOrEqual,
+                        value: serde_json::json!(2),
+                    },
+                },
+                Condition {
+                    condition_type: ConditionType::Metadata {
+                        field: "vehicles.1.color".to_string(),
+                        operator: ComparisonOperator::Equal,
+                        value: serde_json::json!("red"),
+                    },
+                },
+                Condition {
+                    condition_type: ConditionType::Metadata {
+                        field: "labels".to_string(),
+                        operator: ComparisonOperator::Contains,
+                        value: serde_json::json!("truck"),
+                    },
+                },
+            ],
+            actions: vec![Action::SetSeverity {
+                severity: EventSeverity::High,
+            }],
+            enabled: true,
+            priority: 0,
+        };
+
+        let mut engine = RuleEngine::new(Some(RuleSet {
+            rules: vec![rule],
+            deduplication: None,
+            quiet_hours: None,
+        }));
+        let structured = |count: u32| {
+            create_test_event()
+                .with_metadata("vehicle_count".to_string(), serde_json::json!(count))
+                .with_metadata(
+                    "vehicles".to_string(),
+                    serde_json::json!([{"color": "blue"}, {"color": "red"}]),
+                )
+                .with_metadata(
+                    "labels".to_string(),
+                    serde_json::json!(["car", "pickup truck"]),
+                )
+        };
+
+        let input = create_test_input();
+        let result = engine
+            .apply_rules(&input, vec![structured(2), structured(1)])
+            .await
+            .unwrap();
+
+        assert_eq!(result[0].severity, EventSeverity::High);
+        assert_eq!(result[1].severity, EventSeverity::Medium);
+    }
+
+    #[tokio::test]
+    async fn test_text_condition() {
+        let rule_for = |pattern: &str| RuleSet {
+            rules: vec![Rule {
+                id: "zone_rule".to_string(),
+                name: "Zone Rule".to_string(),
+                description: None,
+                conditions: vec![Condition {
+                    condition_type: ConditionType::Text {
+                        pattern: pattern.to_string(),
+                        matches: true,
+                    },
+                }],
+                actions: vec![Action::SetSeverity {
+                    severity: EventSeverity::Critical,
+                }],
+                enabled: true,
+                priority: 0,
+            }],
+            deduplication: None,
+            quiet_hours: None,
+        };
+
+        let mut input = create_test_input();
+        input.text_content = Some("Alarm: ZONE 3 intrusion\nPanel armed away".to_string());
+
+        for (pattern, expected) in [
+            ("zone 3", EventSeverity::Critical),
+            ("alarm:*away", EventSeverity::Critical),
+            ("zone 4", EventSeverity::Medium),
+            ("zone*", EventSeverity::Medium), // wildcards match the whole text
+        ] {
+            let mut engine = RuleEngine::new(Some(rule_for(pattern)));
+            let result = engine
+                .apply_rules(&input, vec![create_test_event()])
+                .await
+                .unwrap();
+            assert_eq!(result[0].severity, expected, "pattern {:?}", pattern);
+        }
+
+        // Inputs without text never match
+        let mut engine = RuleEngine::new(Some(rule_for("zone")));
+        let result = engine
+            .apply_rules(&create_test_input(), vec![create_test_event()])
+            .await
+            .unwrap();
+        assert_eq!(result[0].severity, EventSeverity::Medium);
+    }
+
     #[tokio::test]
     async fn test_delete_event_action() {
         let rule = Rule {
@@ -762,6 +1242,219 @@ mod tests {
         assert_eq!(result2.len(), 0); // Should be deduplicated
     }
 
+    #[tokio::test]
+    async fn test_rate_limit_action() {
+        let rule = Rule {
+            id: "rate_rule".to_string(),
+            name: "Rate Rule".to_string(),
+            description: None,
+            conditions: vec![],
+            actions: vec![Action::RateLimit { max_per_hour: 2 }],
+            enabled: true,
+            priority: 0,
+        };
+
+        let rule_set = RuleSet {
+            rules: vec![rule],
+            deduplication: None,
+            quiet_hours: None,
+        };
+
+        let mut engine = RuleEngine::new(Some(rule_set));
+        let input = create_test_input();
+
+        let mut notified = 0;
+        for _ in 0..4 {
+            let result = engine
+                .apply_rules(&input, vec![create_test_event()])
+                .await
+                .unwrap();
+            assert_eq!(result.len(), 1); // Rate limiting never drops events
+            if result[0].should_notify {
+                notified += 1;
+            }
+        }
+
+        assert_eq!(notified, 2);
+    }
+
+    #[derive(Default)]
+    struct RecordingCapture {
+        bursts: std::sync::Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl CaptureControl for RecordingCapture {
+        async fn snapshot_burst(
+            &self,
+            stream_id: &str,
+            count: u32,
+            _interval: std::time::Duration,
+        ) -> Result<Vec<String>> {
+            self.bursts.lock().unwrap().push(stream_id.to_string());
+            Ok(vec!["snap".to_string(); count as usize])
+        }
+
+        async fn record(&self, _stream_id: &str, _duration: std::time::Duration) -> Result<String> {
+            Ok("file:///recording.mp4".to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_side_effect_actions_run_for_kept_events() {
+        let burst_rule = Rule {
+            id: "burst_rule".to_string(),
+            name: "Burst Rule".to_string(),
+            description: None,
+            conditions: vec![],
+            actions: vec![
+                Action::SnapshotBurst {
+                    count: 2,
+                    interval_ms: 0,
+                },
+                Action::DescribeFrame {
+                    detail_level: None,
+                    focus: None,
+                },
+            ],
+            enabled: true,
+            priority: 10,
+        };
+        let delete_rule = Rule {
+            id: "delete_low".to_string(),
+            name: "Delete Low Confidence".to_string(),
+            description: None,
+            conditions: vec![Condition {
+                condition_type: ConditionType::Confidence {
+                    operator: ComparisonOperator::LessThan,
+                    value: 0.5,
+                },
+            }],
+            actions: vec![Action::DeleteEvent],
+            enabled: true,
+            priority: 0,
+        };
+
+        let rule_set = RuleSet {
+            rules: vec![burst_rule, delete_rule],
+            deduplication: None,
+            quiet_hours: None,
+        };
+
+        let capture = std::sync::Arc::new(RecordingCapture::default());
+        let mut engine = RuleEngine::new(Some(rule_set));
+        engine.set_capture_control(capture.clone());
+
+        let mut input = create_test_input();
+        input.frame_data = Some(bytes::Bytes::from(vec![0u8; 1000]));
+        input.frame_format = Some("jpeg".to_string());
+
+        // Deleted by a later rule, so no side effects run
+        let mut dropped = create_test_event();
+        dropped.confidence = 0.2;
+        let result = engine.apply_rules(&input, vec![dropped]).await.unwrap();
+        assert!(result.is_empty());
+
+        let result = engine
+            .apply_rules(&input, vec![create_test_event()])
+            .await
+            .unwrap();
+        assert_eq!(result.len(), 1);
+        assert!(result[0].metadata.contains_key("ai_description"));
+        assert_eq!(
+            result[0].metadata["rule_actions"],
+            serde_json::json!(["snapshot_burst", "describe_frame"])
+        );
+
+        // The burst runs in the background
+        for _ in 0..50 {
+            if !capture.bursts.lock().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(*capture.bursts.lock().unwrap(), vec!["camera_01"]);
+    }
+
+    #[test]
+    fn test_side_effect_action_deserialization() {
+        let action: Action = serde_json::from_value(serde_json::json!({
+            "type": "http_call",
+            "url": "http://lights.local/on",
+            "body": "{\"camera\": \"{{source_id}}\"}"
+        }))
+        .unwrap();
+
+        match action {
+            Action::HttpCall { method, body, .. } => {
+                assert_eq!(method, "POST");
+                assert!(body.unwrap().contains("{{source_id}}"));
+            }
+            other => panic!("unexpected action {:?}", other),
+        }
+
+        let action: Action =
+            serde_json::from_value(serde_json::json!({ "type": "snapshot_burst", "count": 5 }))
+                .unwrap();
+        assert!(action.has_side_effects());
+        assert_eq!(action.action_type(), "snapshot_burst");
+    }
+
+    #[tokio::test]
+    async fn test_persisted_state_survives_restart() {
+        let db_path =
+            std::env::temp_dir().join(format!("glimpser_rule_state_{}.db", gl_core::Id::new()));
+        let db = gl_db::Db::new(db_path.to_str().unwrap()).await.unwrap();
+
+        let rule_set = RuleSet {
+            rules: vec![Rule {
+                id: "rate_rule".to_string(),
+                name: "Rate Rule".to_string(),
+                description: None,
+                conditions: vec![],
+                actions: vec![Action::RateLimit { max_per_hour: 1 }],
+                enabled: true,
+                priority: 0,
+            }],
+            deduplication: Some(DeduplicationConfig {
+                window_minutes: 5,
+                event_types: vec!["person_detected".to_string()],
+                key_fields: vec!["source_id".to_string()],
+            }),
+            quiet_hours: None,
+        };
+        let input = create_test_input();
+
+        let mut person = create_test_event();
+        person.event_type = "person_detected".to_string();
+
+        let mut engine = RuleEngine::with_persistence(Some(rule_set.clone()), db.clone());
+        let first = engine
+            .apply_rules(&input, vec![create_test_event(), person.clone()])
+            .await
+            .unwrap();
+        assert_eq!(first.len(), 2);
+        assert!(first[0].should_notify);
+
+        // A fresh engine on the same database sees the previous state
+        let mut restarted = RuleEngine::with_persistence(Some(rule_set), db);
+        let mut second_person = create_test_event();
+        second_person.event_type = "person_detected".to_string();
+        let second = restarted
+            .apply_rules(&input, vec![create_test_event(), second_person])
+            .await
+            .unwrap();
+
+        assert_eq!(second.len(), 1); // person_detected deduplicated
+        assert!(!second[0].should_notify); // motion rate limited
+        assert_eq!(
+            restarted.count_recent_events(None, 5).await.unwrap(),
+            3 // two events from the first engine plus the kept motion event
+        );
+
+        let _ = std::fs::remove_file(&db_path);
+    }
+
     #[test]
     fn test_time_window_evaluation() {
         let engine = RuleEngine::new(None);
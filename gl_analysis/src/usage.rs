@@ -0,0 +1,54 @@
+//! ABOUTME: SQLite-backed usage store for the AI router's per-stream budgets
+//! ABOUTME: Lets budgets survive restarts when analysis runs with a database
+
+use async_trait::async_trait;
+use gl_ai::{AiConfig, AiUsage, SharedUsageStore, UsageStore};
+use gl_core::Result;
+use std::sync::Arc;
+
+/// Usage store kept in the `ai_usage` table
+pub struct DbUsageStore {
+    repo: gl_db::AiUsageRepository,
+}
+
+impl DbUsageStore {
+    pub fn new(db: gl_db::Db) -> Self {
+        Self {
+            repo: gl_db::AiUsageRepository::new(db),
+        }
+    }
+}
+
+#[async_trait]
+impl UsageStore for DbUsageStore {
+    async fn daily_usage(&self, stream_id: &str, day: &str) -> Result<AiUsage> {
+        let total = self.repo.daily_total(stream_id, day).await?;
+        Ok(AiUsage {
+            requests: total.requests,
+            tokens: total.tokens,
+            cost_usd: total.cost_usd,
+        })
+    }
+
+    async fn record(
+        &self,
+        stream_id: &str,
+        day: &str,
+        model: &str,
+        tokens: u64,
+        cost_usd: f64,
+    ) -> Result<()> {
+        self.repo
+            .record(stream_id, day, model, tokens, cost_usd)
+            .await
+    }
+}
+
+/// Keep a routed AI configuration's usage in the database unless it already has a store
+pub fn persist_ai_usage(ai_config: &mut AiConfig, db: &gl_db::Db) {
+    if let Some(routing) = ai_config.routing.as_mut() {
+        if routing.usage_store.is_none() {
+            routing.usage_store = Some(SharedUsageStore(Arc::new(DbUsageStore::new(db.clone()))));
+        }
+    }
+}
@@ -1,4 +1,4 @@
-//! ABOUTME: Capture engine for various media sources (ffmpeg, websites, files)
+//! ABOUTME: Capture engine for various media sources (ffmpeg, RTSP, websites, files, HTTP, folders)
 //! ABOUTME: Provides trait-based capture abstractions and implementations
 
 use async_trait::async_trait;
@@ -14,8 +14,11 @@ pub mod background_processor;
 pub mod ffmpeg_source;
 pub mod file_source;
 pub mod hardware_accel;
+pub mod http_source;
 pub mod process_pool;
+pub mod rtsp_source;
 pub mod streaming_source;
+pub mod watch_source;
 pub mod yt_dlp_source;
 
 #[cfg(feature = "website")]
@@ -29,10 +32,16 @@ pub use background_processor::{
 };
 pub use ffmpeg_source::{FfmpegConfig, FfmpegSource, HardwareAccel, RtspTransport};
 pub use file_source::FileSource;
+pub use http_source::{HttpAuthScheme, HttpSnapshotConfig, HttpSnapshotSource};
 pub use process_pool::{
     FfmpegProcess, FfmpegProcessPool, ProcessHealth, ProcessPoolConfig, ProcessPoolMetrics,
 };
+pub use rtsp_source::{
+    AccessUnit, FfmpegKeyframeDecoder, InProcessKeyframeDecoder, KeyframeDecoder, NativeRtspConfig,
+    NativeRtspSource, VideoCodec,
+};
 pub use streaming_source::{StreamingFfmpegSource, StreamingSourceConfig};
+pub use watch_source::{WatchFolderConfig, WatchFolderSource, WatchedFrame};
 pub use yt_dlp_source::{OutputFormat, YtDlpConfig, YtDlpSource};
 
 #[cfg(feature = "website")]
@@ -73,6 +82,16 @@ impl CaptureHandle {
             CaptureState::Stopped => Ok(()), // Already stopped
         }
     }
+
+    /// Subscribe to raw access units, if the source demuxes them without re-encoding
+    pub fn access_units(&self) -> Option<tokio::sync::broadcast::Receiver<AccessUnit>> {
+        self.source.access_units()
+    }
+
+    /// Sessions the source has re-established on its own, if it keeps one open
+    pub fn reconnect_count(&self) -> Option<u64> {
+        self.source.reconnect_count()
+    }
 }
 
 impl Drop for CaptureHandle {
@@ -110,6 +129,18 @@ pub trait CaptureSource {
 
     /// Stop capturing and clean up resources
     async fn stop(&self) -> Result<()>;
+
+    /// Raw encoded pictures for recording and restreaming; `None` for sources that only
+    /// produce decoded snapshots
+    fn access_units(&self) -> Option<tokio::sync::broadcast::Receiver<AccessUnit>> {
+        None
+    }
+
+    /// Times the source reconnected to its camera without being restarted; `None` for
+    /// sources that open a fresh connection per snapshot
+    fn reconnect_count(&self) -> Option<u64> {
+        None
+    }
 }
 
 /// Configuration for snapshot generation
@@ -0,0 +1,677 @@
+//! ABOUTME: Watch-folder capture source that picks up images and videos dropped into a directory
+//! ABOUTME: Emits frames in arrival order, sampling videos with ffmpeg and optionally archiving files
+
+use crate::{CaptureHandle, CaptureSource, SnapshotConfig};
+use async_trait::async_trait;
+use bytes::Bytes;
+use gl_core::{Error, Id, Result};
+use gl_proc::{run, CommandSpec};
+use globset::{GlobBuilder, GlobMatcher};
+use metrics::counter;
+use notify::event::{AccessKind, AccessMode, ModifyKind, RenameMode};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, instrument, warn};
+
+/// Extensions treated as still images; anything else matching the pattern is sampled as video
+const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "webp", "bmp", "gif", "tif", "tiff"];
+
+/// Frames buffered between the watcher and the consumer before the watcher waits
+const FRAME_QUEUE_CAPACITY: usize = 32;
+
+/// Processed files remembered so repeated change events don't reprocess them
+const MAX_SEEN_FILES: usize = 10_000;
+
+/// Configuration for a watch-folder source
+#[derive(Debug, Clone)]
+pub struct WatchFolderConfig {
+    /// Directory to watch
+    pub directory: PathBuf,
+    /// Glob such as `*.jpg` or `*.{mp4,mov}`; matched against the file name, or against the
+    /// path relative to `directory` when it contains a `/`
+    pub pattern: String,
+    /// Also watch subdirectories
+    pub recursive: bool,
+    /// Move processed files here, keeping their relative path
+    pub archive_dir: Option<PathBuf>,
+    /// Process files already in the directory when the source starts
+    pub include_existing: bool,
+    /// A file is picked up once its size and mtime stop changing for this long
+    pub settle_time: Duration,
+    /// Time between frames sampled from a video
+    pub video_sample_interval: Duration,
+    /// Upper bound on frames sampled from a single video
+    pub max_frames_per_video: usize,
+    /// Snapshot configuration
+    pub snapshot_config: SnapshotConfig,
+}
+
+impl Default for WatchFolderConfig {
+    fn default() -> Self {
+        Self {
+            directory: PathBuf::new(),
+            pattern: "*".to_string(),
+            recursive: false,
+            archive_dir: None,
+            include_existing: true,
+            settle_time: Duration::from_secs(2),
+            video_sample_interval: Duration::from_secs(5),
+            max_frames_per_video: 120,
+            snapshot_config: SnapshotConfig::default(),
+        }
+    }
+}
+
+/// A JPEG frame taken from a file in the watched folder
+#[derive(Debug, Clone)]
+pub struct WatchedFrame {
+    pub data: Bytes,
+    /// File the frame came from, at its original location
+    pub source_path: PathBuf,
+    /// Position in the video for sampled frames; `None` for still images
+    pub offset: Option<Duration>,
+}
+
+/// Capture source that turns files dropped into a directory into frames
+///
+/// [`WatchFolderSource::next_frame`] yields every frame once, in arrival order;
+/// [`CaptureSource::snapshot`] returns the most recent one.
+#[derive(Debug, Clone)]
+pub struct WatchFolderSource {
+    config: Arc<WatchFolderConfig>,
+    matcher: Arc<GlobMatcher>,
+    frames: Arc<Mutex<Option<mpsc::Receiver<WatchedFrame>>>>,
+    latest: Arc<std::sync::Mutex<Option<Bytes>>>,
+    worker: Arc<std::sync::Mutex<Option<JoinHandle<()>>>>,
+}
+
+impl WatchFolderSource {
+    /// Create a new watch-folder source
+    pub fn new(config: WatchFolderConfig) -> Result<Self> {
+        let matcher = GlobBuilder::new(&config.pattern)
+            .literal_separator(true)
+            .case_insensitive(true)
+            .build()
+            .map_err(|e| {
+                Error::Config(format!("Invalid watch pattern '{}': {}", config.pattern, e))
+            })?
+            .compile_matcher();
+
+        Ok(Self {
+            config: Arc::new(config),
+            matcher: Arc::new(matcher),
+            frames: Arc::new(Mutex::new(None)),
+            latest: Arc::new(std::sync::Mutex::new(None)),
+            worker: Arc::new(std::sync::Mutex::new(None)),
+        })
+    }
+
+    /// Get the configuration
+    pub fn config(&self) -> &WatchFolderConfig {
+        &self.config
+    }
+
+    /// Wait for the next frame; `None` once the source has stopped
+    pub async fn next_frame(&self) -> Option<WatchedFrame> {
+        let mut frames = self.frames.lock().await;
+        let frame = frames.as_mut()?.recv().await?;
+        *self.latest.lock().unwrap() = Some(frame.data.clone());
+        Some(frame)
+    }
+}
+
+#[async_trait]
+impl CaptureSource for WatchFolderSource {
+    #[instrument(skip(self), fields(directory = %self.config.directory.display()))]
+    async fn start(&self) -> Result<CaptureHandle> {
+        if !self.config.directory.is_dir() {
+            return Err(Error::Config(format!(
+                "Watch directory does not exist: {}",
+                self.config.directory.display()
+            )));
+        }
+
+        let (event_tx, event_rx) = mpsc::unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            let _ = event_tx.send(event);
+        })
+        .map_err(|e| Error::Config(format!("Failed to create file watcher: {}", e)))?;
+        let mode = if self.config.recursive {
+            RecursiveMode::Recursive
+        } else {
+            RecursiveMode::NonRecursive
+        };
+        watcher.watch(&self.config.directory, mode).map_err(|e| {
+            Error::Config(format!(
+                "Failed to watch {}: {}",
+                self.config.directory.display(),
+                e
+            ))
+        })?;
+
+        let (frame_tx, frame_rx) = mpsc::channel(FRAME_QUEUE_CAPACITY);
+        *self.frames.lock().await = Some(frame_rx);
+
+        let worker = FolderWorker {
+            config: self.config.clone(),
+            matcher: self.matcher.clone(),
+            pending: VecDeque::new(),
+            queued: HashSet::new(),
+            seen: HashMap::new(),
+        };
+        let handle = tokio::spawn(worker.run(watcher, event_rx, frame_tx));
+        if let Some(previous) = self.worker.lock().unwrap().replace(handle) {
+            previous.abort();
+        }
+
+        info!(pattern = %self.config.pattern, "Watching folder for new files");
+        Ok(CaptureHandle::new(Arc::new(self.clone())))
+    }
+
+    async fn snapshot(&self) -> Result<Bytes> {
+        self.latest.lock().unwrap().clone().ok_or_else(|| {
+            Error::NotFound(format!(
+                "No files picked up yet from {}",
+                self.config.directory.display()
+            ))
+        })
+    }
+
+    async fn stop(&self) -> Result<()> {
+        debug!(directory = %self.config.directory.display(), "Stopping watch-folder capture");
+        if let Some(handle) = self.worker.lock().unwrap().take() {
+            handle.abort();
+        }
+        Ok(())
+    }
+}
+
+/// Size and mtime used to tell whether a file changed since it was processed
+type FileStamp = (u64, Option<SystemTime>);
+
+/// Background task that turns filesystem events into frames
+struct FolderWorker {
+    config: Arc<WatchFolderConfig>,
+    matcher: Arc<GlobMatcher>,
+    /// Files waiting to be processed, oldest first
+    pending: VecDeque<PathBuf>,
+    queued: HashSet<PathBuf>,
+    seen: HashMap<PathBuf, FileStamp>,
+}
+
+impl FolderWorker {
+    async fn run(
+        mut self,
+        // Dropping the watcher stops events, so it lives as long as the task
+        _watcher: RecommendedWatcher,
+        mut events: mpsc::UnboundedReceiver<notify::Result<notify::Event>>,
+        frames: mpsc::Sender<WatchedFrame>,
+    ) {
+        if self.config.include_existing {
+            let existing = self.existing_files().await;
+            debug!(
+                count = existing.len(),
+                "Queued files already in watch folder"
+            );
+            for path in existing {
+                self.enqueue(path);
+            }
+        }
+
+        loop {
+            while let Ok(event) = events.try_recv() {
+                self.handle_event(event);
+            }
+
+            let Some(path) = self.pending.pop_front() else {
+                match events.recv().await {
+                    Some(event) => {
+                        self.handle_event(event);
+                        continue;
+                    }
+                    None => break,
+                }
+            };
+            self.queued.remove(&path);
+
+            if !self.process(&path, &frames).await {
+                break;
+            }
+        }
+
+        debug!(directory = %self.config.directory.display(), "Watch-folder worker stopped");
+    }
+
+    fn handle_event(&mut self, event: notify::Result<notify::Event>) {
+        let event = match event {
+            Ok(event) => event,
+            Err(e) => {
+                warn!(error = %e, "File watcher error");
+                return;
+            }
+        };
+
+        let relevant = matches!(
+            event.kind,
+            EventKind::Create(_)
+                | EventKind::Modify(ModifyKind::Data(_))
+                | EventKind::Modify(ModifyKind::Name(RenameMode::To | RenameMode::Any))
+                | EventKind::Access(AccessKind::Close(AccessMode::Write))
+        );
+        if relevant {
+            for path in event.paths {
+                self.enqueue(path);
+            }
+        }
+    }
+
+    fn enqueue(&mut self, path: PathBuf) {
+        if self.matches(&path) && !self.queued.contains(&path) {
+            self.queued.insert(path.clone());
+            self.pending.push_back(path);
+        }
+    }
+
+    /// Whether `path` is a file in the watched folder that the pattern selects
+    fn matches(&self, path: &Path) -> bool {
+        if let Some(archive_dir) = &self.config.archive_dir {
+            if path.starts_with(archive_dir) {
+                return false;
+            }
+        }
+        let Ok(relative) = path.strip_prefix(&self.config.directory) else {
+            return false;
+        };
+        if !self.config.recursive && relative.components().count() != 1 {
+            return false;
+        }
+        // Skip dotfiles, which include the temporary names many copy tools write to
+        if relative
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map_or(true, |name| name.starts_with('.'))
+        {
+            return false;
+        }
+
+        if self.config.pattern.contains('/') {
+            self.matcher.is_match(relative)
+        } else {
+            relative
+                .file_name()
+                .is_some_and(|name| self.matcher.is_match(name))
+        }
+    }
+
+    /// Matching files already present, oldest first
+    async fn existing_files(&self) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        let mut directories = vec![self.config.directory.clone()];
+
+        while let Some(directory) = directories.pop() {
+            let Ok(mut entries) = tokio::fs::read_dir(&directory).await else {
+                continue;
+            };
+            while let Ok(Some(entry)) = entries.next_entry().await {
+                let path = entry.path();
+                let Ok(metadata) = entry.metadata().await else {
+                    continue;
+                };
+                if metadata.is_dir() {
+                    if self.config.recursive
+                        && self
+                            .config
+                            .archive_dir
+                            .as_ref()
+                            .map_or(true, |archive| !path.starts_with(archive))
+                    {
+                        directories.push(path);
+                    }
+                } else if self.matches(&path) {
+                    files.push((metadata.modified().ok(), path));
+                }
+            }
+        }
+
+        files.sort();
+        files.into_iter().map(|(_, path)| path).collect()
+    }
+
+    /// Process one file; returns false once nobody is receiving frames
+    async fn process(&mut self, path: &Path, frames: &mpsc::Sender<WatchedFrame>) -> bool {
+        let Some(stamp) = self.wait_until_settled(path).await else {
+            return true;
+        };
+        if self.seen.get(path) == Some(&stamp) {
+            return true;
+        }
+
+        counter!("watch_folder_files_total").increment(1);
+        let extracted = if is_image(path) {
+            read_image(path, &self.config.snapshot_config)
+                .await
+                .map(|data| vec![(data, None)])
+        } else {
+            sample_video(path, &self.config).await
+        };
+
+        match extracted {
+            Ok(extracted) => {
+                debug!(path = %path.display(), frames = extracted.len(), "Picked up file");
+                for (data, offset) in extracted {
+                    counter!("watch_folder_frames_total").increment(1);
+                    let frame = WatchedFrame {
+                        data,
+                        source_path: path.to_path_buf(),
+                        offset,
+                    };
+                    if frames.send(frame).await.is_err() {
+                        return false;
+                    }
+                }
+            }
+            Err(e) => {
+                counter!("watch_folder_failures_total").increment(1);
+                warn!(path = %path.display(), error = %e, "Failed to read frames from file");
+            }
+        }
+
+        match &self.config.archive_dir {
+            Some(archive_dir) => {
+                if let Err(e) = archive(&self.config.directory, archive_dir, path).await {
+                    warn!(path = %path.display(), error = %e, "Failed to archive processed file");
+                    self.remember(path, stamp);
+                }
+            }
+            None => self.remember(path, stamp),
+        }
+        true
+    }
+
+    fn remember(&mut self, path: &Path, stamp: FileStamp) {
+        if self.seen.len() >= MAX_SEEN_FILES {
+            self.seen.retain(|path, _| path.exists());
+        }
+        self.seen.insert(path.to_path_buf(), stamp);
+    }
+
+    /// Wait until a file stops growing; `None` if it disappeared
+    async fn wait_until_settled(&self, path: &Path) -> Option<FileStamp> {
+        let mut previous = file_stamp(path).await?;
+        loop {
+            tokio::time::sleep(self.config.settle_time).await;
+            let current = file_stamp(path).await?;
+            if current == previous {
+                return Some(current);
+            }
+            previous = current;
+        }
+    }
+}
+
+async fn file_stamp(path: &Path) -> Option<FileStamp> {
+    let metadata = tokio::fs::metadata(path).await.ok()?;
+    metadata
+        .is_file()
+        .then(|| (metadata.len(), metadata.modified().ok()))
+}
+
+fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+/// Read a still image, converting it to JPEG with ffmpeg unless it already is one
+async fn read_image(path: &Path, config: &SnapshotConfig) -> Result<Bytes> {
+    let data = tokio::fs::read(path).await?;
+    if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        return Ok(Bytes::from(data));
+    }
+
+    let mut frames = extract_frames(path, None, 1, config, config.timeout).await?;
+    frames
+        .pop()
+        .ok_or_else(|| Error::Config(format!("ffmpeg produced no image for {}", path.display())))
+}
+
+/// Sample frames from a video every `video_sample_interval`
+async fn sample_video(
+    path: &Path,
+    config: &WatchFolderConfig,
+) -> Result<Vec<(Bytes, Option<Duration>)>> {
+    let interval = config.video_sample_interval.max(Duration::from_millis(100));
+    // Long recordings take a while to decode; allow well beyond the snapshot timeout
+    let timeout = config.snapshot_config.timeout.max(Duration::from_secs(600));
+    let frames = extract_frames(
+        path,
+        Some(interval),
+        config.max_frames_per_video.max(1),
+        &config.snapshot_config,
+        timeout,
+    )
+    .await?;
+
+    Ok(frames
+        .into_iter()
+        .enumerate()
+        .map(|(index, data)| (data, Some(interval * index as u32)))
+        .collect())
+}
+
+/// Run ffmpeg to write JPEG frames into a scratch directory and read them back in order
+async fn extract_frames(
+    input: &Path,
+    interval: Option<Duration>,
+    max_frames: usize,
+    config: &SnapshotConfig,
+    timeout: Duration,
+) -> Result<Vec<Bytes>> {
+    let scratch = std::env::temp_dir().join(format!("glimpser_watch_{}", Id::new()));
+    tokio::fs::create_dir_all(&scratch).await?;
+
+    let mut filters = Vec::new();
+    if let Some(interval) = interval {
+        filters.push(format!("fps=1/{}", interval.as_secs_f64()));
+    }
+    if let (Some(width), Some(height)) = (config.max_width, config.max_height) {
+        filters.push(format!(
+            "scale='min({},iw)':'min({},ih)':force_original_aspect_ratio=decrease",
+            width, height
+        ));
+    }
+
+    let mut args = vec![
+        "-hide_banner".to_string(),
+        "-loglevel".to_string(),
+        "error".to_string(),
+        "-i".to_string(),
+        input.to_string_lossy().to_string(),
+    ];
+    if !filters.is_empty() {
+        args.extend(["-vf".to_string(), filters.join(",")]);
+    }
+    args.extend([
+        "-frames:v".to_string(),
+        max_frames.to_string(),
+        "-q:v".to_string(),
+        ((31 * (100 - config.quality.min(100) as u32)) / 100 + 2).to_string(),
+        scratch.join("frame-%05d.jpg").to_string_lossy().to_string(),
+    ]);
+
+    let spec = CommandSpec::new("ffmpeg".into())
+        .args(args)
+        .timeout(timeout);
+    let result = run(spec).await;
+
+    let frames = async {
+        let result = result?;
+        if !result.success() {
+            return Err(Error::Config(format!(
+                "ffmpeg failed with exit code {}: {}",
+                result.exit_code().unwrap_or(-1),
+                result.stderr
+            )));
+        }
+
+        let mut names = Vec::new();
+        let mut entries = tokio::fs::read_dir(&scratch).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            names.push(entry.path());
+        }
+        names.sort();
+
+        let mut frames = Vec::with_capacity(names.len());
+        for name in names {
+            frames.push(Bytes::from(tokio::fs::read(name).await?));
+        }
+        Ok(frames)
+    }
+    .await;
+
+    let _ = tokio::fs::remove_dir_all(&scratch).await;
+    frames
+}
+
+/// Move a processed file under `archive_dir`, keeping its path relative to `directory`
+async fn archive(directory: &Path, archive_dir: &Path, path: &Path) -> Result<PathBuf> {
+    let relative = path.strip_prefix(directory).unwrap_or(path);
+    let mut destination = archive_dir.join(relative);
+    if let Some(parent) = destination.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    if tokio::fs::try_exists(&destination).await? {
+        let name = relative
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        destination.set_file_name(format!(
+            "{}-{}",
+            chrono::Utc::now().format("%Y%m%dT%H%M%S%.3f"),
+            name
+        ));
+    }
+
+    // Renames fail across filesystems (e.g. a NAS mount and local disk), so fall back to copying
+    if tokio::fs::rename(path, &destination).await.is_err() {
+        tokio::fs::copy(path, &destination).await?;
+        tokio::fs::remove_file(path).await?;
+    }
+    debug!(from = %path.display(), to = %destination.display(), "Archived processed file");
+    Ok(destination)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::create_test_id;
+
+    const JPEG: &[u8] = &[0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10, 0xFF, 0xD9];
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("gl_watch_{}_{}", name, create_test_id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn worker(config: WatchFolderConfig) -> FolderWorker {
+        let source = WatchFolderSource::new(config).unwrap();
+        FolderWorker {
+            config: source.config.clone(),
+            matcher: source.matcher.clone(),
+            pending: VecDeque::new(),
+            queued: HashSet::new(),
+            seen: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_pattern_matching() {
+        let root = PathBuf::from("/drops");
+        let flat = worker(WatchFolderConfig {
+            directory: root.clone(),
+            pattern: "*.{mp4,JPG}".to_string(),
+            archive_dir: Some(root.join("done")),
+            ..Default::default()
+        });
+        assert!(flat.matches(&root.join("flight1.MP4")));
+        assert!(flat.matches(&root.join("still.jpg")));
+        assert!(!flat.matches(&root.join("notes.txt")));
+        assert!(!flat.matches(&root.join(".flight1.mp4.part")));
+        assert!(!flat.matches(&root.join("nested/flight2.mp4")));
+        assert!(!flat.matches(&root.join("done/flight1.mp4")));
+        assert!(!flat.matches(Path::new("/elsewhere/flight1.mp4")));
+
+        let nested = worker(WatchFolderConfig {
+            directory: root.clone(),
+            pattern: "drone-*/*.mp4".to_string(),
+            recursive: true,
+            ..Default::default()
+        });
+        assert!(nested.matches(&root.join("drone-a/flight.mp4")));
+        assert!(!nested.matches(&root.join("flight.mp4")));
+        assert!(!nested.matches(&root.join("drone-a/deeper/flight.mp4")));
+    }
+
+    #[test]
+    fn test_invalid_pattern_is_rejected() {
+        let result = WatchFolderSource::new(WatchFolderConfig {
+            pattern: "*.{jpg".to_string(),
+            ..Default::default()
+        });
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_files_are_emitted_in_order_and_archived() {
+        let dir = temp_dir("drops");
+        let archive_dir = dir.join("archive");
+        std::fs::write(dir.join("existing.jpg"), JPEG).unwrap();
+
+        let source = WatchFolderSource::new(WatchFolderConfig {
+            directory: dir.clone(),
+            pattern: "*.jpg".to_string(),
+            archive_dir: Some(archive_dir.clone()),
+            settle_time: Duration::from_millis(50),
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(source.snapshot().await.is_err());
+        let handle = source.start().await.unwrap();
+
+        let next = || async {
+            tokio::time::timeout(Duration::from_secs(10), source.next_frame())
+                .await
+                .expect("timed out waiting for frame")
+                .expect("source stopped")
+        };
+
+        let first = next().await;
+        assert_eq!(first.source_path, dir.join("existing.jpg"));
+        assert_eq!(&first.data[..], JPEG);
+        assert!(first.offset.is_none());
+
+        std::fs::write(dir.join("ignored.txt"), b"not matched").unwrap();
+        std::fs::write(dir.join("new.jpg"), JPEG).unwrap();
+        let second = next().await;
+        assert_eq!(second.source_path, dir.join("new.jpg"));
+        assert_eq!(&handle.snapshot().await.unwrap()[..], JPEG);
+
+        // Processed files are moved out of the watched folder
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(archive_dir.join("existing.jpg").exists());
+        assert!(archive_dir.join("new.jpg").exists());
+        assert!(!dir.join("new.jpg").exists());
+        assert!(dir.join("ignored.txt").exists());
+
+        handle.stop().await.unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
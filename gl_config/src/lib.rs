@@ -4,6 +4,7 @@
 use config::{Config as ConfigBuilder, Environment, File};
 use gl_core::{Error, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fmt;
 use validator::Validate;
 
@@ -24,6 +25,17 @@ pub struct Config {
     pub storage: StorageConfig,
     #[validate(nested)]
     pub ai: AiConfig,
+    #[validate(nested)]
+    pub notifications: NotificationsConfig,
+    #[validate(nested)]
+    pub ingest: IngestConfig,
+    #[validate(nested)]
+    pub restream: RestreamConfig,
+    /// Image embeddings for searching snapshots by text or example image
+    pub embeddings: gl_ai::EmbeddingConfig,
+    /// Licence plate reading for streams with a `plates` section; off until
+    /// `plates.ocr.model_path` is set
+    pub plates: gl_analysis::PlateProcessorConfig,
 }
 
 /// Server configuration
@@ -204,9 +216,11 @@ pub struct FeaturesConfig {
 pub struct AiConfig {
     /// Whether to use online AI services (true) or stub (false)
     pub use_online: bool,
-    /// OpenAI API key
+    /// Which provider to call: "openai", "ollama" or "llama_cpp"
+    pub provider: gl_ai::AiProvider,
+    /// API key (required for OpenAI, optional for local servers)
     pub api_key: Option<String>,
-    /// Base URL for OpenAI API (defaults to OpenAI)
+    /// Base URL of the provider API (defaults to the provider's usual address)
     pub base_url: Option<String>,
     /// Request timeout in seconds
     #[validate(range(min = 5, max = 300))]
@@ -217,17 +231,30 @@ pub struct AiConfig {
     /// Model name to use (e.g., "gpt-4", "gpt-3.5-turbo")
     #[validate(length(min = 1))]
     pub model: String,
+    /// Model for frame descriptions when it differs from `model` (e.g., "llava")
+    pub vision_model: Option<String>,
+    /// Let the provider download missing models (Ollama only)
+    pub pull_models: bool,
+    /// Per-task models, fallback provider and per-stream daily budgets
+    pub routing: Option<gl_ai::AiRoutingConfig>,
+    /// Reuse frame descriptions for visually similar frames
+    pub cache: Option<gl_ai::AiCacheConfig>,
 }
 
 impl Default for AiConfig {
     fn default() -> Self {
         Self {
             use_online: false, // Safe default - requires explicit enablement
+            provider: gl_ai::AiProvider::default(),
             api_key: None,
             base_url: None,
             timeout_seconds: 30,
             max_retries: 3,
             model: "gpt-3.5-turbo".to_string(),
+            vision_model: None,
+            pull_models: false,
+            routing: None,
+            cache: None,
         }
     }
 }
@@ -242,6 +269,11 @@ impl AiConfig {
             max_retries: self.max_retries,
             model: self.model.clone(),
             use_online: self.use_online,
+            provider: self.provider,
+            vision_model: self.vision_model.clone(),
+            pull_models: self.pull_models,
+            routing: self.routing.clone(),
+            cache: self.cache.clone(),
         }
     }
 }
@@ -298,6 +330,9 @@ pub struct SmtpConfig {
     pub username: String,
     #[validate(length(min = 1))]
     pub password: String,
+    /// Sender of outgoing mail, e.g. "Glimpser <glimpser@example.com>"; the username if unset
+    #[serde(default)]
+    pub from: Option<String>,
 }
 
 impl fmt::Debug for SmtpConfig {
@@ -307,6 +342,7 @@ impl fmt::Debug for SmtpConfig {
             .field("port", &self.port)
             .field("username", &self.username)
             .field("password", &"[REDACTED]")
+            .field("from", &self.from)
             .finish()
     }
 }
@@ -333,6 +369,163 @@ impl Default for StorageConfig {
     }
 }
 
+/// Notification message configuration
+#[derive(Debug, Clone, Deserialize, Serialize, Validate, Default)]
+#[serde(default)]
+pub struct NotificationsConfig {
+    /// Externally reachable base URL used to build snapshot links
+    #[validate(url)]
+    pub public_base_url: Option<String>,
+    /// Fixed UTC offset for times in messages (e.g. "+02:00"); server local time if unset
+    pub utc_offset: Option<String>,
+    /// Title and body templates per channel ("default", "webhook", "pushover", ...)
+    pub channel_templates: HashMap<String, MessageTemplateConfig>,
+    /// Named templates that rules select with `set_notification_template`
+    pub templates: HashMap<String, MessageTemplateConfig>,
+    /// Browser push notifications for the PWA
+    pub web_push: WebPushConfig,
+    /// Scheduled daily or weekly activity reports
+    pub digests: Vec<gl_analysis::DigestConfig>,
+}
+
+/// Web Push (VAPID) configuration
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct WebPushConfig {
+    pub enabled: bool,
+    /// Contact URI sent to push services in the VAPID `sub` claim
+    pub subject: String,
+    /// base64url private key (raw scalar or PKCS#8); generated and stored in the database if unset
+    pub vapid_private_key: Option<String>,
+    /// base64url uncompressed public key, required with a raw private key
+    pub vapid_public_key: Option<String>,
+    /// How long push services keep undelivered messages
+    pub ttl_seconds: u32,
+}
+
+impl Default for WebPushConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            subject: "mailto:admin@localhost".to_string(),
+            vapid_private_key: None,
+            vapid_public_key: None,
+            ttl_seconds: 86400,
+        }
+    }
+}
+
+impl fmt::Debug for WebPushConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebPushConfig")
+            .field("enabled", &self.enabled)
+            .field("subject", &self.subject)
+            .field(
+                "vapid_private_key",
+                &self.vapid_private_key.as_ref().map(|_| "[REDACTED]"),
+            )
+            .field("vapid_public_key", &self.vapid_public_key)
+            .field("ttl_seconds", &self.ttl_seconds)
+            .finish()
+    }
+}
+
+/// Receivers for cameras that push snapshots instead of being polled
+#[derive(Debug, Clone, Deserialize, Serialize, Validate, Default)]
+#[serde(default)]
+pub struct IngestConfig {
+    #[validate(nested)]
+    pub ftp: FtpIngestConfig,
+    #[validate(nested)]
+    pub smtp: SmtpIngestConfig,
+}
+
+/// Embedded FTP server; streams of kind "ftp" hold the per-camera credentials
+#[derive(Debug, Clone, Deserialize, Serialize, Validate)]
+#[serde(default)]
+pub struct FtpIngestConfig {
+    pub enabled: bool,
+    /// Control port address, e.g. "0.0.0.0:2121"
+    #[validate(length(min = 1))]
+    pub bind: String,
+    /// First and last passive data port; 0 lets the OS pick
+    pub passive_port_start: u16,
+    pub passive_port_end: u16,
+    /// IPv4 address advertised in PASV replies when behind NAT
+    pub passive_address: Option<String>,
+    #[validate(range(min = 1, max = 512))]
+    pub max_upload_mb: u64,
+}
+
+impl Default for FtpIngestConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind: "0.0.0.0:2121".to_string(),
+            passive_port_start: 0,
+            passive_port_end: 0,
+            passive_address: None,
+            max_upload_mb: 20,
+        }
+    }
+}
+
+/// Embedded SMTP server; mail to `<token>@domain` lands on the "email" stream with that token
+#[derive(Debug, Clone, Deserialize, Serialize, Validate)]
+#[serde(default)]
+pub struct SmtpIngestConfig {
+    pub enabled: bool,
+    /// Listen address, e.g. "0.0.0.0:2525"
+    #[validate(length(min = 1))]
+    pub bind: String,
+    /// Recipient domain accepted; mail for any other domain is refused
+    #[validate(length(min = 1))]
+    pub domain: String,
+    #[validate(range(min = 1, max = 512))]
+    pub max_message_mb: u64,
+}
+
+impl Default for SmtpIngestConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind: "0.0.0.0:2525".to_string(),
+            domain: "glimpser.local".to_string(),
+            max_message_mb: 25,
+        }
+    }
+}
+
+/// Passthrough RTSP server that re-serves native RTSP streams at `rtsp://<bind>/<stream id>`
+#[derive(Debug, Clone, Deserialize, Serialize, Validate)]
+#[serde(default)]
+pub struct RestreamConfig {
+    pub enabled: bool,
+    /// Listen address, e.g. "0.0.0.0:8554"
+    #[validate(length(min = 1))]
+    pub bind: String,
+    /// Concurrent RTSP clients across all streams
+    #[validate(range(min = 1, max = 4096))]
+    pub max_clients: usize,
+}
+
+impl Default for RestreamConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind: "0.0.0.0:8554".to_string(),
+            max_clients: 64,
+        }
+    }
+}
+
+/// Title and body template pair
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MessageTemplateConfig {
+    pub title: String,
+    pub body: String,
+}
+
 impl Config {
     /// Load configuration from environment variables and optional .env file
     pub fn load() -> Result<Self> {
@@ -362,6 +555,8 @@ impl Config {
             .set_default("features.enable_ai", false)?
             .set_default("storage.artifacts_dir", "data/artifacts")?
             .set_default("ai.use_online", false)?
+            .set_default("ai.provider", "openai")?
+            .set_default("ai.pull_models", false)?
             .set_default("ai.timeout_seconds", 30)?
             .set_default("ai.max_retries", 3)?
             .set_default("ai.model", "gpt-3.5-turbo")?;
@@ -400,6 +595,9 @@ impl Config {
         if let Ok(ai_use_online) = std::env::var("GLIMPSER_AI_USE_ONLINE") {
             builder = builder.set_override("ai.use_online", ai_use_online)?;
         }
+        if let Ok(ai_provider) = std::env::var("GLIMPSER_AI_PROVIDER") {
+            builder = builder.set_override("ai.provider", ai_provider)?;
+        }
         if let Ok(ai_api_key) = std::env::var("GLIMPSER_AI_API_KEY") {
             builder = builder.set_override("ai.api_key", ai_api_key)?;
         }
@@ -409,6 +607,12 @@ impl Config {
         if let Ok(ai_model) = std::env::var("GLIMPSER_AI_MODEL") {
             builder = builder.set_override("ai.model", ai_model)?;
         }
+        if let Ok(ai_vision_model) = std::env::var("GLIMPSER_AI_VISION_MODEL") {
+            builder = builder.set_override("ai.vision_model", ai_vision_model)?;
+        }
+        if let Ok(ai_pull_models) = std::env::var("GLIMPSER_AI_PULL_MODELS") {
+            builder = builder.set_override("ai.pull_models", ai_pull_models)?;
+        }
         if let Ok(ai_timeout) = std::env::var("GLIMPSER_AI_TIMEOUT_SECONDS") {
             builder = builder.set_override("ai.timeout_seconds", ai_timeout)?;
         }
@@ -416,6 +620,74 @@ impl Config {
             builder = builder.set_override("ai.max_retries", ai_retries)?;
         }
 
+        // Notification configuration
+        if let Ok(base_url) = std::env::var("GLIMPSER_NOTIFICATIONS_PUBLIC_BASE_URL") {
+            builder = builder.set_override("notifications.public_base_url", base_url)?;
+        }
+        if let Ok(utc_offset) = std::env::var("GLIMPSER_NOTIFICATIONS_UTC_OFFSET") {
+            builder = builder.set_override("notifications.utc_offset", utc_offset)?;
+        }
+        if let Ok(enabled) = std::env::var("GLIMPSER_NOTIFICATIONS_WEB_PUSH_ENABLED") {
+            builder = builder.set_override("notifications.web_push.enabled", enabled)?;
+        }
+        if let Ok(subject) = std::env::var("GLIMPSER_NOTIFICATIONS_WEB_PUSH_SUBJECT") {
+            builder = builder.set_override("notifications.web_push.subject", subject)?;
+        }
+        if let Ok(key) = std::env::var("GLIMPSER_NOTIFICATIONS_WEB_PUSH_VAPID_PRIVATE_KEY") {
+            builder = builder.set_override("notifications.web_push.vapid_private_key", key)?;
+        }
+        if let Ok(key) = std::env::var("GLIMPSER_NOTIFICATIONS_WEB_PUSH_VAPID_PUBLIC_KEY") {
+            builder = builder.set_override("notifications.web_push.vapid_public_key", key)?;
+        }
+
+        // Push ingest configuration
+        if let Ok(enabled) = std::env::var("GLIMPSER_INGEST_FTP_ENABLED") {
+            builder = builder.set_override("ingest.ftp.enabled", enabled)?;
+        }
+        if let Ok(bind) = std::env::var("GLIMPSER_INGEST_FTP_BIND") {
+            builder = builder.set_override("ingest.ftp.bind", bind)?;
+        }
+        if let Ok(port) = std::env::var("GLIMPSER_INGEST_FTP_PASSIVE_PORT_START") {
+            builder = builder.set_override("ingest.ftp.passive_port_start", port)?;
+        }
+        if let Ok(port) = std::env::var("GLIMPSER_INGEST_FTP_PASSIVE_PORT_END") {
+            builder = builder.set_override("ingest.ftp.passive_port_end", port)?;
+        }
+        if let Ok(address) = std::env::var("GLIMPSER_INGEST_FTP_PASSIVE_ADDRESS") {
+            builder = builder.set_override("ingest.ftp.passive_address", address)?;
+        }
+        if let Ok(enabled) = std::env::var("GLIMPSER_INGEST_SMTP_ENABLED") {
+            builder = builder.set_override("ingest.smtp.enabled", enabled)?;
+        }
+        if let Ok(bind) = std::env::var("GLIMPSER_INGEST_SMTP_BIND") {
+            builder = builder.set_override("ingest.smtp.bind", bind)?;
+        }
+        if let Ok(domain) = std::env::var("GLIMPSER_INGEST_SMTP_DOMAIN") {
+            builder = builder.set_override("ingest.smtp.domain", domain)?;
+        }
+
+        // RTSP restream configuration
+        if let Ok(enabled) = std::env::var("GLIMPSER_RESTREAM_ENABLED") {
+            builder = builder.set_override("restream.enabled", enabled)?;
+        }
+        if let Ok(bind) = std::env::var("GLIMPSER_RESTREAM_BIND") {
+            builder = builder.set_override("restream.bind", bind)?;
+        }
+        if let Ok(max_clients) = std::env::var("GLIMPSER_RESTREAM_MAX_CLIENTS") {
+            builder = builder.set_override("restream.max_clients", max_clients)?;
+        }
+
+        // Snapshot embedding configuration
+        if let Ok(enabled) = std::env::var("GLIMPSER_EMBEDDINGS_ENABLED") {
+            builder = builder.set_override("embeddings.enabled", enabled)?;
+        }
+        if let Ok(base_url) = std::env::var("GLIMPSER_EMBEDDINGS_BASE_URL") {
+            builder = builder.set_override("embeddings.base_url", base_url)?;
+        }
+        if let Ok(model) = std::env::var("GLIMPSER_EMBEDDINGS_MODEL") {
+            builder = builder.set_override("embeddings.model", model)?;
+        }
+
         // Try to load from .env file if it exists (optional)
         if std::path::Path::new(".env").exists() {
             builder = builder.add_source(File::with_name(".env").required(false));
@@ -517,6 +789,31 @@ mod tests {
         env::remove_var("GLIMPSER_SECURITY_JWT_SECRET");
     }
 
+    #[test]
+    fn test_ai_provider_from_env() {
+        let _lock = ENV_MUTEX.lock().unwrap();
+
+        env::set_var(
+            "GLIMPSER_SECURITY_JWT_SECRET",
+            "valid32characterjwtsecretfortest",
+        );
+        env::set_var("GLIMPSER_AI_PROVIDER", "ollama");
+        env::set_var("GLIMPSER_AI_VISION_MODEL", "llava");
+        env::set_var("GLIMPSER_AI_PULL_MODELS", "true");
+
+        let config = Config::load().expect("Should load from env");
+        let ai = config.ai.to_ai_config();
+
+        assert_eq!(ai.provider, gl_ai::AiProvider::Ollama);
+        assert_eq!(ai.vision_model.as_deref(), Some("llava"));
+        assert!(ai.pull_models);
+
+        env::remove_var("GLIMPSER_AI_PROVIDER");
+        env::remove_var("GLIMPSER_AI_VISION_MODEL");
+        env::remove_var("GLIMPSER_AI_PULL_MODELS");
+        env::remove_var("GLIMPSER_SECURITY_JWT_SECRET");
+    }
+
     #[test]
     fn test_config_validation_failure() {
         let _lock = ENV_MUTEX.lock().unwrap();
@@ -3,6 +3,8 @@
 
 pub mod error;
 pub mod id;
+#[cfg(feature = "onnx")]
+pub mod onnx;
 pub mod telemetry;
 pub mod time;
 
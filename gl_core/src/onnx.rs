@@ -0,0 +1,49 @@
+//! ABOUTME: Loads ONNX models and runs them in-process with tract, a pure-Rust runtime
+//! ABOUTME: Shared by the CLIP embedder, the plate recogniser and the person detector
+
+use crate::{Error, Result};
+use tract_onnx::prelude::*;
+
+/// ONNX model optimised for fixed input shapes
+pub type OnnxModel = TypedRunnableModel<TypedModel>;
+
+/// Load an ONNX model, fixing the type and shape of the inputs it declares
+///
+/// `inputs` may list more inputs than the model has; the extra ones are ignored.
+pub fn load_model(path: &str, inputs: &[TypedFact]) -> Result<OnnxModel> {
+    let load_err = |e: TractError| Error::Config(format!("Failed to load model {}: {}", path, e));
+    let mut model = tract_onnx::onnx().model_for_path(path).map_err(load_err)?;
+    if model.inputs.len() > inputs.len() {
+        return Err(Error::Config(format!(
+            "Model {} has {} inputs, expected at most {}",
+            path,
+            model.inputs.len(),
+            inputs.len()
+        )));
+    }
+    for (index, fact) in inputs.iter().take(model.inputs.len()).enumerate() {
+        model = model
+            .with_input_fact(index, fact.clone().into())
+            .map_err(load_err)?;
+    }
+    model
+        .into_optimized()
+        .and_then(|model| model.into_runnable())
+        .map_err(load_err)
+}
+
+/// Run a model and return the shape and values of its first output as floats
+pub fn run(model: &OnnxModel, inputs: Vec<Tensor>) -> Result<(Vec<usize>, Vec<f32>)> {
+    let outputs = model
+        .run(inputs.into_iter().map(TValue::from).collect())
+        .map_err(|e| Error::External(format!("Model inference failed: {}", e)))?;
+    let output = outputs
+        .first()
+        .ok_or_else(|| Error::External("Model produced no output".to_string()))?
+        .cast_to::<f32>()
+        .map_err(|e| Error::External(format!("Unexpected model output: {}", e)))?;
+    let view = output
+        .to_array_view::<f32>()
+        .map_err(|e| Error::External(format!("Unexpected model output: {}", e)))?;
+    Ok((view.shape().to_vec(), view.iter().copied().collect()))
+}
@@ -0,0 +1,40 @@
+-- Persist rule engine state (event history, dedup keys, rate limits) across restarts
+-- Timestamps are unix epoch seconds so expiry checks are simple integer comparisons
+
+-- Event history window used by EventCount conditions
+CREATE TABLE IF NOT EXISTS rule_event_history (
+    id TEXT PRIMARY KEY NOT NULL,
+    event_id TEXT NOT NULL,
+    template_id TEXT NOT NULL,
+    event_type TEXT NOT NULL,
+    source_id TEXT NOT NULL,
+    occurred_at INTEGER NOT NULL,
+    expires_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_rule_event_history_type_time ON rule_event_history(event_type, occurred_at);
+CREATE INDEX IF NOT EXISTS idx_rule_event_history_occurred_at ON rule_event_history(occurred_at);
+CREATE INDEX IF NOT EXISTS idx_rule_event_history_expires_at ON rule_event_history(expires_at);
+
+-- Deduplication keys claimed by recently kept events
+CREATE TABLE IF NOT EXISTS rule_dedup_keys (
+    dedup_key TEXT PRIMARY KEY NOT NULL,
+    event_type TEXT NOT NULL,
+    last_event_id TEXT NOT NULL,
+    first_seen_at INTEGER NOT NULL,
+    expires_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_rule_dedup_keys_expires_at ON rule_dedup_keys(expires_at);
+
+-- Rate limit counters per rule and scope (e.g. source) for fixed windows
+CREATE TABLE IF NOT EXISTS rule_rate_limits (
+    rule_id TEXT NOT NULL,
+    scope_key TEXT NOT NULL,
+    window_start INTEGER NOT NULL,
+    count INTEGER NOT NULL DEFAULT 0,
+    expires_at INTEGER NOT NULL,
+    PRIMARY KEY (rule_id, scope_key, window_start)
+);
+
+CREATE INDEX IF NOT EXISTS idx_rule_rate_limits_expires_at ON rule_rate_limits(expires_at);
@@ -0,0 +1,19 @@
+-- Execution log for rule actions that drive side effects (HTTP calls, snapshot bursts,
+-- recordings, on-demand AI descriptions)
+
+CREATE TABLE IF NOT EXISTS rule_action_results (
+    id TEXT PRIMARY KEY NOT NULL,
+    rule_id TEXT NOT NULL,
+    action_type TEXT NOT NULL,
+    event_id TEXT NOT NULL,
+    source_id TEXT NOT NULL,
+    success INTEGER NOT NULL,
+    output TEXT, -- JSON object describing what the action produced
+    error_message TEXT,
+    duration_ms INTEGER NOT NULL DEFAULT 0,
+    executed_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_rule_action_results_rule_id ON rule_action_results(rule_id, executed_at);
+CREATE INDEX IF NOT EXISTS idx_rule_action_results_event_id ON rule_action_results(event_id);
+CREATE INDEX IF NOT EXISTS idx_rule_action_results_executed_at ON rule_action_results(executed_at);
@@ -0,0 +1,23 @@
+-- Browser push subscriptions registered by the PWA, plus the server's VAPID key pair
+
+CREATE TABLE IF NOT EXISTS push_subscriptions (
+    id TEXT PRIMARY KEY NOT NULL,
+    user_id TEXT NOT NULL,
+    endpoint TEXT NOT NULL UNIQUE, -- push service URL issued to the browser
+    p256dh TEXT NOT NULL, -- base64url client public key
+    auth TEXT NOT NULL, -- base64url client auth secret
+    user_agent TEXT,
+    created_at TEXT NOT NULL,
+    last_success_at TEXT,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_push_subscriptions_user_id ON push_subscriptions(user_id);
+
+-- Single-row table; subscriptions are bound to the public key, so it must stay stable
+CREATE TABLE IF NOT EXISTS vapid_keys (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    private_key TEXT NOT NULL, -- base64url PKCS#8 document
+    public_key TEXT NOT NULL, -- base64url uncompressed P-256 point
+    created_at TEXT NOT NULL
+);
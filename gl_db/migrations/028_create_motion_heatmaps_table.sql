@@ -0,0 +1,13 @@
+-- Hourly motion activity per stream, accumulated into a coarse grid over the frame
+
+CREATE TABLE IF NOT EXISTS motion_heatmaps (
+    stream_id TEXT NOT NULL,
+    hour_start TEXT NOT NULL, -- RFC3339 UTC timestamp truncated to the hour
+    cols INTEGER NOT NULL,
+    rows INTEGER NOT NULL,
+    frames INTEGER NOT NULL DEFAULT 0, -- frames analyzed during the hour
+    cells BLOB NOT NULL, -- cols * rows little-endian u32 motion counts, row-major
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (stream_id, hour_start),
+    FOREIGN KEY (stream_id) REFERENCES streams(id) ON DELETE CASCADE
+);
@@ -0,0 +1,14 @@
+-- Daily AI usage per stream and model, used to enforce per-stream budgets
+
+CREATE TABLE IF NOT EXISTS ai_usage (
+    stream_id TEXT NOT NULL,
+    day TEXT NOT NULL, -- UTC date, YYYY-MM-DD
+    model TEXT NOT NULL,
+    requests INTEGER NOT NULL DEFAULT 0,
+    tokens INTEGER NOT NULL DEFAULT 0, -- estimated prompt and completion tokens
+    cost_usd REAL NOT NULL DEFAULT 0,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (stream_id, day, model)
+);
+
+CREATE INDEX IF NOT EXISTS idx_ai_usage_day ON ai_usage(day);
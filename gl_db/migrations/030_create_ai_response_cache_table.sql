@@ -0,0 +1,12 @@
+-- Frame descriptions reused for visually similar frames, keyed on prompt and model
+
+CREATE TABLE IF NOT EXISTS ai_response_cache (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    cache_key TEXT NOT NULL, -- stream, model and prompt options the response was produced for
+    perceptual_hash TEXT NOT NULL, -- base64 gradient hash of the frame
+    response TEXT NOT NULL, -- JSON-encoded frame description
+    created_at INTEGER NOT NULL -- unix seconds
+);
+
+CREATE INDEX IF NOT EXISTS idx_ai_response_cache_key_created ON ai_response_cache(cache_key, created_at);
+CREATE INDEX IF NOT EXISTS idx_ai_response_cache_created ON ai_response_cache(created_at);
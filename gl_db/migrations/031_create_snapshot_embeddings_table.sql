@@ -0,0 +1,13 @@
+-- Image embeddings of snapshots for semantic search, one vector per snapshot and model
+
+CREATE TABLE IF NOT EXISTS snapshot_embeddings (
+    snapshot_id TEXT NOT NULL,
+    model TEXT NOT NULL,
+    dims INTEGER NOT NULL,
+    vector BLOB NOT NULL, -- dims little-endian f32 values of unit length; empty if the image couldn't be embedded
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (snapshot_id, model),
+    FOREIGN KEY (snapshot_id) REFERENCES snapshots(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_snapshot_embeddings_model ON snapshot_embeddings(model);
@@ -0,0 +1,94 @@
+-- Full-text search indexes over analysis events, alerts and streams
+-- External-content FTS5 tables read their text from the source tables by rowid and are
+-- kept in sync by triggers; rebuild them if rowids change (e.g. after VACUUM)
+
+CREATE VIRTUAL TABLE IF NOT EXISTS analysis_events_fts USING fts5(
+    description,
+    event_type,
+    processor_name,
+    content='analysis_events',
+    content_rowid='rowid',
+    tokenize='porter unicode61'
+);
+
+CREATE TRIGGER IF NOT EXISTS analysis_events_fts_insert AFTER INSERT ON analysis_events
+BEGIN
+    INSERT INTO analysis_events_fts(rowid, description, event_type, processor_name)
+    VALUES (NEW.rowid, NEW.description, NEW.event_type, NEW.processor_name);
+END;
+
+CREATE TRIGGER IF NOT EXISTS analysis_events_fts_delete AFTER DELETE ON analysis_events
+BEGIN
+    INSERT INTO analysis_events_fts(analysis_events_fts, rowid, description, event_type, processor_name)
+    VALUES ('delete', OLD.rowid, OLD.description, OLD.event_type, OLD.processor_name);
+END;
+
+CREATE TRIGGER IF NOT EXISTS analysis_events_fts_update AFTER UPDATE ON analysis_events
+BEGIN
+    INSERT INTO analysis_events_fts(analysis_events_fts, rowid, description, event_type, processor_name)
+    VALUES ('delete', OLD.rowid, OLD.description, OLD.event_type, OLD.processor_name);
+    INSERT INTO analysis_events_fts(rowid, description, event_type, processor_name)
+    VALUES (NEW.rowid, NEW.description, NEW.event_type, NEW.processor_name);
+END;
+
+CREATE VIRTUAL TABLE IF NOT EXISTS alerts_fts USING fts5(
+    title,
+    message,
+    alert_type,
+    content='alerts',
+    content_rowid='rowid',
+    tokenize='porter unicode61'
+);
+
+CREATE TRIGGER IF NOT EXISTS alerts_fts_insert AFTER INSERT ON alerts
+BEGIN
+    INSERT INTO alerts_fts(rowid, title, message, alert_type)
+    VALUES (NEW.rowid, NEW.title, NEW.message, NEW.alert_type);
+END;
+
+CREATE TRIGGER IF NOT EXISTS alerts_fts_delete AFTER DELETE ON alerts
+BEGIN
+    INSERT INTO alerts_fts(alerts_fts, rowid, title, message, alert_type)
+    VALUES ('delete', OLD.rowid, OLD.title, OLD.message, OLD.alert_type);
+END;
+
+CREATE TRIGGER IF NOT EXISTS alerts_fts_update AFTER UPDATE OF title, message, alert_type ON alerts
+BEGIN
+    INSERT INTO alerts_fts(alerts_fts, rowid, title, message, alert_type)
+    VALUES ('delete', OLD.rowid, OLD.title, OLD.message, OLD.alert_type);
+    INSERT INTO alerts_fts(rowid, title, message, alert_type)
+    VALUES (NEW.rowid, NEW.title, NEW.message, NEW.alert_type);
+END;
+
+CREATE VIRTUAL TABLE IF NOT EXISTS streams_fts USING fts5(
+    name,
+    description,
+    content='streams',
+    content_rowid='rowid',
+    tokenize='porter unicode61'
+);
+
+CREATE TRIGGER IF NOT EXISTS streams_fts_insert AFTER INSERT ON streams
+BEGIN
+    INSERT INTO streams_fts(rowid, name, description)
+    VALUES (NEW.rowid, NEW.name, NEW.description);
+END;
+
+CREATE TRIGGER IF NOT EXISTS streams_fts_delete AFTER DELETE ON streams
+BEGIN
+    INSERT INTO streams_fts(streams_fts, rowid, name, description)
+    VALUES ('delete', OLD.rowid, OLD.name, OLD.description);
+END;
+
+CREATE TRIGGER IF NOT EXISTS streams_fts_update AFTER UPDATE OF name, description ON streams
+BEGIN
+    INSERT INTO streams_fts(streams_fts, rowid, name, description)
+    VALUES ('delete', OLD.rowid, OLD.name, OLD.description);
+    INSERT INTO streams_fts(rowid, name, description)
+    VALUES (NEW.rowid, NEW.name, NEW.description);
+END;
+
+-- Index rows that existed before this migration
+INSERT INTO analysis_events_fts(analysis_events_fts) VALUES ('rebuild');
+INSERT INTO alerts_fts(alerts_fts) VALUES ('rebuild');
+INSERT INTO streams_fts(streams_fts) VALUES ('rebuild');
@@ -0,0 +1,17 @@
+-- Periodic digest reports summarizing what each stream saw over a day or week
+
+CREATE TABLE IF NOT EXISTS digest_reports (
+    id TEXT PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL, -- configured digest that produced the report
+    period TEXT NOT NULL, -- daily, weekly
+    period_start TEXT NOT NULL, -- RFC3339 UTC, inclusive
+    period_end TEXT NOT NULL, -- RFC3339 UTC, exclusive
+    summary TEXT NOT NULL, -- plain text narrative
+    html TEXT NOT NULL, -- rendered report with key frames embedded as data URIs
+    stats TEXT NOT NULL, -- JSON per-stream event counts
+    delivery_error TEXT, -- set when sending to the digest's channels failed
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_digest_reports_name_period ON digest_reports(name, period_end);
+CREATE INDEX IF NOT EXISTS idx_digest_reports_created_at ON digest_reports(created_at);
@@ -0,0 +1,14 @@
+-- Unmasked frames of privacy-masked streams, kept briefly for authorised viewers only
+
+CREATE TABLE IF NOT EXISTS original_frames (
+    id TEXT PRIMARY KEY NOT NULL,
+    stream_id TEXT NOT NULL REFERENCES streams(id) ON DELETE CASCADE,
+    file_path TEXT NOT NULL,
+    storage_uri TEXT NOT NULL,
+    file_size INTEGER NOT NULL,
+    captured_at TEXT NOT NULL, -- RFC3339 UTC
+    expires_at TEXT NOT NULL -- RFC3339 UTC; never served after this, deleted on the next purge
+);
+
+CREATE INDEX IF NOT EXISTS idx_original_frames_stream ON original_frames(stream_id, captured_at);
+CREATE INDEX IF NOT EXISTS idx_original_frames_expires_at ON original_frames(expires_at);
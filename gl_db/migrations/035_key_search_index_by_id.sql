@@ -0,0 +1,154 @@
+-- Key the full-text search indexes by a stable integer per record
+-- The source tables have TEXT primary keys, so VACUUM may renumber their rowids and
+-- leave rowid-keyed external-content indexes pointing at the wrong records. Each indexed
+-- record gets an INTEGER PRIMARY KEY in search_keys instead, which VACUUM keeps, and
+-- the index rows use it as their rowid so they are found and removed without a scan.
+
+DROP TRIGGER IF EXISTS analysis_events_fts_insert;
+DROP TRIGGER IF EXISTS analysis_events_fts_delete;
+DROP TRIGGER IF EXISTS analysis_events_fts_update;
+DROP TABLE IF EXISTS analysis_events_fts;
+
+DROP TRIGGER IF EXISTS alerts_fts_insert;
+DROP TRIGGER IF EXISTS alerts_fts_delete;
+DROP TRIGGER IF EXISTS alerts_fts_update;
+DROP TABLE IF EXISTS alerts_fts;
+
+DROP TRIGGER IF EXISTS streams_fts_insert;
+DROP TRIGGER IF EXISTS streams_fts_delete;
+DROP TRIGGER IF EXISTS streams_fts_update;
+DROP TABLE IF EXISTS streams_fts;
+
+CREATE TABLE search_keys (
+    key INTEGER PRIMARY KEY,
+    source TEXT NOT NULL,
+    record_id TEXT NOT NULL,
+    UNIQUE (source, record_id)
+);
+
+CREATE VIRTUAL TABLE analysis_events_fts USING fts5(
+    description,
+    event_type,
+    processor_name,
+    tokenize='porter unicode61'
+);
+
+CREATE TRIGGER analysis_events_fts_insert AFTER INSERT ON analysis_events
+BEGIN
+    INSERT INTO search_keys(source, record_id) VALUES ('analysis_events', NEW.id);
+    INSERT INTO analysis_events_fts(rowid, description, event_type, processor_name)
+    VALUES (
+        (SELECT key FROM search_keys WHERE source = 'analysis_events' AND record_id = NEW.id),
+        NEW.description, NEW.event_type, NEW.processor_name
+    );
+END;
+
+CREATE TRIGGER analysis_events_fts_delete AFTER DELETE ON analysis_events
+BEGIN
+    DELETE FROM analysis_events_fts WHERE rowid = (
+        SELECT key FROM search_keys WHERE source = 'analysis_events' AND record_id = OLD.id
+    );
+    DELETE FROM search_keys WHERE source = 'analysis_events' AND record_id = OLD.id;
+END;
+
+CREATE TRIGGER analysis_events_fts_update AFTER UPDATE OF id, description, event_type, processor_name ON analysis_events
+BEGIN
+    DELETE FROM analysis_events_fts WHERE rowid = (
+        SELECT key FROM search_keys WHERE source = 'analysis_events' AND record_id = OLD.id
+    );
+    UPDATE search_keys SET record_id = NEW.id WHERE source = 'analysis_events' AND record_id = OLD.id;
+    INSERT INTO analysis_events_fts(rowid, description, event_type, processor_name)
+    VALUES (
+        (SELECT key FROM search_keys WHERE source = 'analysis_events' AND record_id = NEW.id),
+        NEW.description, NEW.event_type, NEW.processor_name
+    );
+END;
+
+CREATE VIRTUAL TABLE alerts_fts USING fts5(
+    title,
+    message,
+    alert_type,
+    tokenize='porter unicode61'
+);
+
+CREATE TRIGGER alerts_fts_insert AFTER INSERT ON alerts
+BEGIN
+    INSERT INTO search_keys(source, record_id) VALUES ('alerts', NEW.id);
+    INSERT INTO alerts_fts(rowid, title, message, alert_type)
+    VALUES (
+        (SELECT key FROM search_keys WHERE source = 'alerts' AND record_id = NEW.id),
+        NEW.title, NEW.message, NEW.alert_type
+    );
+END;
+
+CREATE TRIGGER alerts_fts_delete AFTER DELETE ON alerts
+BEGIN
+    DELETE FROM alerts_fts WHERE rowid = (
+        SELECT key FROM search_keys WHERE source = 'alerts' AND record_id = OLD.id
+    );
+    DELETE FROM search_keys WHERE source = 'alerts' AND record_id = OLD.id;
+END;
+
+CREATE TRIGGER alerts_fts_update AFTER UPDATE OF id, title, message, alert_type ON alerts
+BEGIN
+    DELETE FROM alerts_fts WHERE rowid = (
+        SELECT key FROM search_keys WHERE source = 'alerts' AND record_id = OLD.id
+    );
+    UPDATE search_keys SET record_id = NEW.id WHERE source = 'alerts' AND record_id = OLD.id;
+    INSERT INTO alerts_fts(rowid, title, message, alert_type)
+    VALUES (
+        (SELECT key FROM search_keys WHERE source = 'alerts' AND record_id = NEW.id),
+        NEW.title, NEW.message, NEW.alert_type
+    );
+END;
+
+CREATE VIRTUAL TABLE streams_fts USING fts5(
+    name,
+    description,
+    tokenize='porter unicode61'
+);
+
+CREATE TRIGGER streams_fts_insert AFTER INSERT ON streams
+BEGIN
+    INSERT INTO search_keys(source, record_id) VALUES ('streams', NEW.id);
+    INSERT INTO streams_fts(rowid, name, description)
+    VALUES (
+        (SELECT key FROM search_keys WHERE source = 'streams' AND record_id = NEW.id),
+        NEW.name, NEW.description
+    );
+END;
+
+CREATE TRIGGER streams_fts_delete AFTER DELETE ON streams
+BEGIN
+    DELETE FROM streams_fts WHERE rowid = (
+        SELECT key FROM search_keys WHERE source = 'streams' AND record_id = OLD.id
+    );
+    DELETE FROM search_keys WHERE source = 'streams' AND record_id = OLD.id;
+END;
+
+CREATE TRIGGER streams_fts_update AFTER UPDATE OF id, name, description ON streams
+BEGIN
+    DELETE FROM streams_fts WHERE rowid = (
+        SELECT key FROM search_keys WHERE source = 'streams' AND record_id = OLD.id
+    );
+    UPDATE search_keys SET record_id = NEW.id WHERE source = 'streams' AND record_id = OLD.id;
+    INSERT INTO streams_fts(rowid, name, description)
+    VALUES (
+        (SELECT key FROM search_keys WHERE source = 'streams' AND record_id = NEW.id),
+        NEW.name, NEW.description
+    );
+END;
+
+-- Index existing rows
+INSERT INTO search_keys(source, record_id) SELECT 'analysis_events', id FROM analysis_events;
+INSERT INTO search_keys(source, record_id) SELECT 'alerts', id FROM alerts;
+INSERT INTO search_keys(source, record_id) SELECT 'streams', id FROM streams;
+INSERT INTO analysis_events_fts(rowid, description, event_type, processor_name)
+SELECT k.key, r.description, r.event_type, r.processor_name
+FROM analysis_events r JOIN search_keys k ON k.source = 'analysis_events' AND k.record_id = r.id;
+INSERT INTO alerts_fts(rowid, title, message, alert_type)
+SELECT k.key, r.title, r.message, r.alert_type
+FROM alerts r JOIN search_keys k ON k.source = 'alerts' AND k.record_id = r.id;
+INSERT INTO streams_fts(rowid, name, description)
+SELECT k.key, r.name, r.description
+FROM streams r JOIN search_keys k ON k.source = 'streams' AND k.record_id = r.id;
@@ -144,6 +144,8 @@ pub mod cache;
 // Re-export common types and repositories
 pub use cache::{CacheStats, DatabaseCache};
 pub use repositories::{
+    ai_response_cache::{AiResponseCacheEntry, AiResponseCacheRepository},
+    ai_usage::{AiUsageRecord, AiUsageRepository, AiUsageTotal},
     alerts::{Alert, AlertRepository, CreateAlertRequest},
     analysis_events::{AnalysisEvent, AnalysisEventRepository, CreateAnalysisEvent},
     api_keys::{ApiKey, ApiKeyRepository, CreateApiKeyRequest},
@@ -154,13 +156,28 @@ pub use repositories::{
     cached_streams::CachedStreamRepository,
     cached_users::CachedUserRepository,
     captures::{Capture, CaptureRepository, CreateCaptureRequest, UpdateCaptureRequest},
+    digest_reports::{CreateDigestReport, DigestReport, DigestReportRepository},
     events::{CreateEventRequest, Event, EventRepository},
     jobs::{CreateJobRequest, Job, JobRepository, UpdateJobRequest},
+    motion_heatmaps::{MotionHeatmap, MotionHeatmapRepository},
     notification_deliveries::{
         CreateNotificationDelivery, DeliveryStatus, NotificationDelivery,
         NotificationDeliveryRepository, UpdateDeliveryStatus,
     },
+    original_frames::{CreateOriginalFrame, OriginalFrame, OriginalFrameRepository},
+    push_subscriptions::{
+        CreatePushSubscription, PushSubscription, PushSubscriptionRepository, StoredVapidKeys,
+    },
+    rule_actions::{CreateRuleActionResult, RuleActionResult, RuleActionResultRepository},
+    rule_state::{RecordRuleEvent, RuleStatePurgeStats, RuleStateRepository},
+    search::{
+        SearchFacet, SearchFacets, SearchHit, SearchKind, SearchRepository, SearchResults,
+        StreamFacet, TextSearchQuery,
+    },
     settings::{Setting, SettingsRepository, UpdateSettingRequest},
+    snapshot_embeddings::{
+        EmbeddingQuery, PendingSnapshot, SnapshotEmbeddingRepository, SnapshotMatch,
+    },
     snapshots::{CreateSnapshotRequest, Snapshot, SnapshotMetadata, SnapshotRepository},
     streams::{CreateStreamRequest, Stream, StreamRepository, UpdateStreamRequest},
     users::{CreateUserRequest, UpdateUserRequest, User, UserRepository},
@@ -347,6 +364,815 @@ mod tests {
         assert_eq!(user_keys[0].id, api_key.id);
     }
 
+    #[tokio::test]
+    async fn test_rule_state_repository() {
+        let db = create_test_db()
+            .await
+            .expect("Failed to create test database");
+        let repo = RuleStateRepository::new(db.clone());
+        let now = 1_700_000_000;
+
+        // Dedup keys: first claim wins, second is a duplicate until expiry
+        assert!(repo
+            .claim_dedup_key("motion|cam1", "motion", "evt1", now, 300)
+            .await
+            .unwrap());
+        assert!(!repo
+            .claim_dedup_key("motion|cam1", "motion", "evt2", now + 10, 300)
+            .await
+            .unwrap());
+        assert!(repo
+            .claim_dedup_key("motion|cam1", "motion", "evt3", now + 300, 300)
+            .await
+            .unwrap());
+
+        // Rate limit counters increment within a window
+        assert_eq!(
+            repo.increment_rate_limit("rule", "cam1", now, 3600)
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            repo.increment_rate_limit("rule", "cam1", now, 3600)
+                .await
+                .unwrap(),
+            2
+        );
+
+        // History counts respect the window and event type filter
+        for (event_type, offset) in [("motion", 0), ("motion", 100), ("person", 100)] {
+            repo.record_event(RecordRuleEvent {
+                event_id: Id::new().to_string(),
+                template_id: "stream".to_string(),
+                event_type: event_type.to_string(),
+                source_id: "cam1".to_string(),
+                occurred_at: now + offset,
+                expires_at: now + offset + 600,
+            })
+            .await
+            .unwrap();
+        }
+        assert_eq!(repo.count_events_since(None, now).await.unwrap(), 2);
+        assert_eq!(
+            repo.count_events_since(Some("motion"), now - 1)
+                .await
+                .unwrap(),
+            2
+        );
+
+        // Purging removes everything that expired
+        let stats = repo.purge_expired(now + 3600).await.unwrap();
+        assert_eq!(stats.history_rows, 3);
+        assert_eq!(stats.dedup_keys, 1);
+        assert_eq!(stats.rate_limit_windows, 1);
+        assert_eq!(repo.history_size().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_rule_action_result_repository() {
+        let db = create_test_db()
+            .await
+            .expect("Failed to create test database");
+        let repo = RuleActionResultRepository::new(db.clone());
+
+        let ok = repo
+            .create(CreateRuleActionResult {
+                rule_id: "floodlight".to_string(),
+                action_type: "http_call".to_string(),
+                event_id: "evt1".to_string(),
+                source_id: "cam1".to_string(),
+                success: true,
+                output: Some(serde_json::json!({ "status": 200 })),
+                error_message: None,
+                duration_ms: 42,
+            })
+            .await
+            .expect("Failed to record action result");
+        assert!(ok.success);
+
+        repo.create(CreateRuleActionResult {
+            rule_id: "floodlight".to_string(),
+            action_type: "start_recording".to_string(),
+            event_id: "evt1".to_string(),
+            source_id: "cam1".to_string(),
+            success: false,
+            output: None,
+            error_message: Some("stream not running".to_string()),
+            duration_ms: 1,
+        })
+        .await
+        .expect("Failed to record action result");
+
+        let by_rule = repo.list_by_rule("floodlight", 10).await.unwrap();
+        assert_eq!(by_rule.len(), 2);
+
+        let by_event = repo.list_by_event("evt1").await.unwrap();
+        assert_eq!(by_event.len(), 2);
+        let failed = by_event.iter().find(|r| !r.success).unwrap();
+        assert_eq!(failed.error_message.as_deref(), Some("stream not running"));
+        let http = by_event
+            .iter()
+            .find(|r| r.action_type == "http_call")
+            .unwrap();
+        assert_eq!(http.output, Some(serde_json::json!({ "status": 200 })));
+        assert_eq!(http.duration_ms, 42);
+
+        assert!(repo.list_by_rule("other", 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_push_subscription_repository() {
+        let db = create_test_db()
+            .await
+            .expect("Failed to create test database");
+        let user = UserRepository::new(db.pool())
+            .create(CreateUserRequest {
+                username: "push_user".to_string(),
+                email: "push@example.com".to_string(),
+                password_hash: "hashed_password".to_string(),
+            })
+            .await
+            .unwrap();
+        let repo = PushSubscriptionRepository::new(db.clone());
+
+        let request = CreatePushSubscription {
+            user_id: user.id.clone(),
+            endpoint: "https://push.example.com/sub/1".to_string(),
+            p256dh: "key-1".to_string(),
+            auth: "auth-1".to_string(),
+            user_agent: Some("Firefox".to_string()),
+        };
+        let created = repo.upsert(request.clone()).await.unwrap();
+        assert_eq!(created.p256dh, "key-1");
+        assert!(created.last_success_at.is_none());
+
+        // Re-subscribing the same endpoint replaces the keys
+        let updated = repo
+            .upsert(CreatePushSubscription {
+                p256dh: "key-2".to_string(),
+                ..request
+            })
+            .await
+            .unwrap();
+        assert_eq!(updated.id, created.id);
+        assert_eq!(updated.p256dh, "key-2");
+        assert_eq!(repo.list_by_user(&user.id).await.unwrap().len(), 1);
+
+        repo.mark_success(&created.endpoint).await.unwrap();
+        let all = repo.list_all().await.unwrap();
+        assert!(all[0].last_success_at.is_some());
+
+        assert!(!repo
+            .delete_for_user("someone-else", &created.endpoint)
+            .await
+            .unwrap());
+        assert!(repo.delete_by_endpoint(&created.endpoint).await.unwrap());
+        assert!(repo.list_all().await.unwrap().is_empty());
+
+        assert!(repo.get_vapid_keys().await.unwrap().is_none());
+        let stored = repo
+            .store_vapid_keys(StoredVapidKeys {
+                private_key: "private-a".to_string(),
+                public_key: "public-a".to_string(),
+            })
+            .await
+            .unwrap();
+        // The first stored pair wins
+        let again = repo
+            .store_vapid_keys(StoredVapidKeys {
+                private_key: "private-b".to_string(),
+                public_key: "public-b".to_string(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(stored.private_key, "private-a");
+        assert_eq!(again.public_key, "public-a");
+    }
+
+    #[tokio::test]
+    async fn test_stream_repository_list_by_kind() {
+        let db = create_test_db()
+            .await
+            .expect("Failed to create test database");
+        let user = UserRepository::new(db.pool())
+            .create(CreateUserRequest {
+                username: "kind_user".to_string(),
+                email: "kind@example.com".to_string(),
+                password_hash: "hashed_password".to_string(),
+            })
+            .await
+            .unwrap();
+        let repo = StreamRepository::new(db.pool());
+
+        for (name, config) in [
+            (
+                "Trail cam",
+                r#"{"kind":"ftp","username":"trail","password":"secret"}"#,
+            ),
+            (
+                "Driveway",
+                r#"{"kind":"rtsp","url":"rtsp://camera/stream"}"#,
+            ),
+            ("Broken", "not json"),
+        ] {
+            repo.create(CreateStreamRequest {
+                user_id: user.id.clone(),
+                name: name.to_string(),
+                description: None,
+                config: config.to_string(),
+                is_default: false,
+            })
+            .await
+            .unwrap();
+        }
+
+        let ftp = repo.list_by_kind("ftp").await.unwrap();
+        assert_eq!(ftp.len(), 1);
+        assert_eq!(ftp[0].name, "Trail cam");
+        assert!(repo.list_by_kind("smtp").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_ai_usage_repository() {
+        let db = create_test_db()
+            .await
+            .expect("Failed to create test database");
+        let repo = AiUsageRepository::new(db.clone());
+
+        repo.record("porch", "2026-10-17", "gpt-4o", 900, 0.009)
+            .await
+            .unwrap();
+        repo.record("porch", "2026-10-18", "gpt-4o", 1000, 0.01)
+            .await
+            .unwrap();
+        repo.record("porch", "2026-10-18", "gpt-4o", 500, 0.005)
+            .await
+            .unwrap();
+        repo.record("porch", "2026-10-18", "llama3.2", 2000, 0.0)
+            .await
+            .unwrap();
+        repo.record("garage", "2026-10-18", "gpt-4o", 100, 0.001)
+            .await
+            .unwrap();
+
+        let total = repo.daily_total("porch", "2026-10-18").await.unwrap();
+        assert_eq!(total.requests, 3);
+        assert_eq!(total.tokens, 3500);
+        assert!((total.cost_usd - 0.015).abs() < 1e-9);
+        assert_eq!(
+            repo.daily_total("driveway", "2026-10-18").await.unwrap(),
+            AiUsageTotal::default()
+        );
+
+        let day = repo.list_day("2026-10-18").await.unwrap();
+        assert_eq!(day.len(), 3);
+        assert_eq!((day[0].stream_id.as_str(), day[0].requests), ("porch", 2));
+
+        assert_eq!(repo.delete_before("2026-10-18").await.unwrap(), 1);
+        assert!(repo.list_day("2026-10-17").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_ai_response_cache_repository() {
+        let db = create_test_db()
+            .await
+            .expect("Failed to create test database");
+        let repo = AiResponseCacheRepository::new(db.clone());
+        let entry = |key: &str, hash: &str, created_at: i64| AiResponseCacheEntry {
+            cache_key: key.to_string(),
+            perceptual_hash: hash.to_string(),
+            response: format!(r#"{{"description":"{}"}}"#, hash),
+            created_at,
+        };
+
+        repo.insert(&entry("porch|llava", "old", 100))
+            .await
+            .unwrap();
+        repo.insert(&entry("porch|llava", "new", 200))
+            .await
+            .unwrap();
+        repo.insert(&entry("porch|gpt-4o", "other", 200))
+            .await
+            .unwrap();
+
+        let recent = repo.recent("porch|llava", 100, 10).await.unwrap();
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0], entry("porch|llava", "new", 200));
+        assert_eq!(repo.recent("porch|llava", 150, 10).await.unwrap().len(), 1);
+        assert_eq!(repo.recent("porch|llava", 0, 1).await.unwrap().len(), 1);
+
+        assert_eq!(repo.delete_before(150).await.unwrap(), 1);
+        assert_eq!(repo.recent("porch|llava", 0, 10).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_motion_heatmap_repository() {
+        let db = create_test_db()
+            .await
+            .expect("Failed to create test database");
+        let user = UserRepository::new(db.pool())
+            .create(CreateUserRequest {
+                username: "heat_user".to_string(),
+                email: "heat@example.com".to_string(),
+                password_hash: "hashed_password".to_string(),
+            })
+            .await
+            .unwrap();
+        let stream = StreamRepository::new(db.pool())
+            .create(CreateStreamRequest {
+                user_id: user.id.clone(),
+                name: "Lobby".to_string(),
+                description: None,
+                config: r#"{"kind":"rtsp","url":"rtsp://camera/stream"}"#.to_string(),
+                is_default: false,
+            })
+            .await
+            .unwrap();
+        let repo = MotionHeatmapRepository::new(db.clone());
+
+        let hour = "2026-10-18T09:00:00Z";
+        repo.merge(&stream.id, hour, 2, 2, 10, &[1, 0, 0, 3])
+            .await
+            .unwrap();
+        let merged = repo
+            .merge(&stream.id, hour, 2, 2, 5, &[1, 1, 0, 0])
+            .await
+            .unwrap();
+        assert_eq!(merged.frames, 15);
+        assert_eq!(merged.cells, vec![2, 1, 0, 3]);
+        repo.merge(&stream.id, "2026-10-18T10:00:00Z", 2, 2, 1, &[0, 0, 0, 1])
+            .await
+            .unwrap();
+
+        let hours = repo
+            .list_range(&stream.id, "2026-10-18T00:00:00Z", "2026-10-18T10:00:00Z")
+            .await
+            .unwrap();
+        assert_eq!(hours.len(), 1);
+        assert_eq!(hours[0].cells, vec![2, 1, 0, 3]);
+
+        // A new grid shape replaces the hour instead of mixing counts
+        let resized = repo.merge(&stream.id, hour, 1, 1, 2, &[2]).await.unwrap();
+        assert_eq!((resized.frames, resized.cells), (2, vec![2]));
+        assert!(repo.merge(&stream.id, hour, 2, 2, 1, &[0]).await.is_err());
+
+        assert_eq!(repo.delete_before("2026-10-18T10:00:00Z").await.unwrap(), 1);
+        assert_eq!(
+            repo.list_range(&stream.id, "2026-10-18T00:00:00Z", "2026-10-19T00:00:00Z")
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_embedding_search() {
+        let db = create_test_db()
+            .await
+            .expect("Failed to create test database");
+        let user = UserRepository::new(db.pool())
+            .create(CreateUserRequest {
+                username: "search_user".to_string(),
+                email: "search@example.com".to_string(),
+                password_hash: "hashed_password".to_string(),
+            })
+            .await
+            .unwrap();
+        let mut streams = Vec::new();
+        for name in ["Gate", "Yard"] {
+            let stream = StreamRepository::new(db.pool())
+                .create(CreateStreamRequest {
+                    user_id: user.id.clone(),
+                    name: name.to_string(),
+                    description: None,
+                    config: "{}".to_string(),
+                    is_default: false,
+                })
+                .await
+                .unwrap();
+            streams.push(stream.id);
+        }
+        let snapshots = SnapshotRepository::new(db.pool());
+        let mut ids = Vec::new();
+        for (stream_id, captured_at) in [
+            (&streams[0], "2026-10-18T08:00:00Z"),
+            (&streams[0], "2026-10-18T09:00:00Z"),
+            (&streams[1], "2026-10-18T10:00:00Z"),
+        ] {
+            let snapshot = snapshots
+                .create(CreateSnapshotRequest {
+                    stream_id: stream_id.clone(),
+                    user_id: user.id.clone(),
+                    file_path: format!("/tmp/{}.jpg", captured_at),
+                    storage_uri: String::new(),
+                    content_type: "image/jpeg".to_string(),
+                    width: None,
+                    height: None,
+                    file_size: 1,
+                    checksum: None,
+                    etag: None,
+                    captured_at: captured_at.to_string(),
+                    perceptual_hash: None,
+                })
+                .await
+                .unwrap();
+            ids.push(snapshot.id);
+        }
+
+        let repo = SnapshotEmbeddingRepository::new(db.clone());
+        assert_eq!(repo.list_pending("clip", 10).await.unwrap().len(), 3);
+        repo.upsert(&ids[0], "clip", &[2.0, 0.0]).await.unwrap();
+        repo.upsert(&ids[1], "clip", &[1.0, 1.0]).await.unwrap();
+        repo.upsert(&ids[2], "clip", &[]).await.unwrap();
+        assert!(repo.list_pending("clip", 10).await.unwrap().is_empty());
+        assert_eq!(repo.list_pending("other", 1).await.unwrap().len(), 1);
+        assert_eq!(
+            repo.find_vector(&ids[0], "clip").await.unwrap(),
+            Some(vec![1.0, 0.0])
+        );
+        assert_eq!(repo.find_vector(&ids[2], "clip").await.unwrap(), None);
+
+        let query = EmbeddingQuery {
+            model: "clip".to_string(),
+            limit: 10,
+            ..Default::default()
+        };
+        let matches = repo.search(&[1.0, 0.1], &query).await.unwrap();
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].snapshot_id, ids[0]);
+        assert!(matches[0].score > matches[1].score);
+
+        let filtered = EmbeddingQuery {
+            stream_id: Some(streams[0].clone()),
+            from: Some("2026-10-18T08:30:00Z".to_string()),
+            ..query.clone()
+        };
+        let matches = repo.search(&[1.0, 0.1], &filtered).await.unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].snapshot_id, ids[1]);
+        assert!(repo
+            .search(&[1.0, 0.0, 0.0], &query)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_full_text_search() {
+        let db = create_test_db()
+            .await
+            .expect("Failed to create test database");
+        let user = UserRepository::new(db.pool())
+            .create(CreateUserRequest {
+                username: "fts_user".to_string(),
+                email: "fts@example.com".to_string(),
+                password_hash: "hashed_password".to_string(),
+            })
+            .await
+            .unwrap();
+        let streams = StreamRepository::new(db.pool());
+        let gate = streams
+            .create(CreateStreamRequest {
+                user_id: user.id.clone(),
+                name: "Front gate".to_string(),
+                description: Some("Driveway camera".to_string()),
+                config: "{}".to_string(),
+                is_default: false,
+            })
+            .await
+            .unwrap();
+        let yard = streams
+            .create(CreateStreamRequest {
+                user_id: user.id.clone(),
+                name: "Yard".to_string(),
+                description: None,
+                config: "{}".to_string(),
+                is_default: false,
+            })
+            .await
+            .unwrap();
+        let events = AnalysisEventRepository::new(db.clone());
+        let mut event_ids = Vec::new();
+        for (stream_id, description) in [
+            (&gate.id, "A red truck <b>parked</b> at the gate"),
+            (&yard.id, "Person carrying a ladder near the gates"),
+        ] {
+            let event = events
+                .create(CreateAnalysisEvent {
+                    template_id: stream_id.clone(),
+                    event_type: "frame_described".to_string(),
+                    severity: "info".to_string(),
+                    confidence: 0.9,
+                    description: description.to_string(),
+                    metadata: None,
+                    processor_name: "ai_description".to_string(),
+                    source_id: stream_id.clone(),
+                    should_notify: false,
+                    suggested_actions: None,
+                })
+                .await
+                .unwrap();
+            event_ids.push(event.id);
+        }
+        AlertRepository::new(db.pool())
+            .create(CreateAlertRequest {
+                user_id: user.id.clone(),
+                capture_id: None,
+                alert_type: "user_defined".to_string(),
+                severity: "warning".to_string(),
+                title: "Truck left open".to_string(),
+                message: "Delivery truck blocking the drive".to_string(),
+                metadata: None,
+                triggered_at: "2026-10-18T09:00:00Z".to_string(),
+            })
+            .await
+            .unwrap();
+
+        let search = SearchRepository::new(db.clone());
+        let query = |text: &str| TextSearchQuery {
+            text: text.to_string(),
+            limit: 10,
+            ..Default::default()
+        };
+
+        let results = search.search(&query("truck")).await.unwrap();
+        assert_eq!(results.total, 2);
+        let event = results
+            .hits
+            .iter()
+            .find(|hit| hit.kind == SearchKind::Event)
+            .unwrap();
+        assert_eq!(event.id, event_ids[0]);
+        assert_eq!(event.stream_name.as_deref(), Some("Front gate"));
+        assert!(
+            event.snippet.contains("<mark>truck</mark> &lt;b&gt;parked"),
+            "{}",
+            event.snippet
+        );
+
+        // Stemming and prefixes match "gate", "gates" and the stream name
+        let results = search.search(&query("gate")).await.unwrap();
+        assert_eq!(results.total, 3);
+        assert_eq!(results.facets.streams[0].stream_id, gate.id);
+        assert_eq!(results.facets.streams[0].count, 2);
+        assert_eq!(results.facets.kinds.len(), 2);
+        assert_eq!(results.facets.days.len(), 1);
+
+        let results = search
+            .search(&TextSearchQuery {
+                stream_id: Some(yard.id.clone()),
+                ..query("gate")
+            })
+            .await
+            .unwrap();
+        assert_eq!(results.total, 1);
+        assert_eq!(results.hits[0].id, event_ids[1]);
+
+        let results = search
+            .search(&TextSearchQuery {
+                kinds: vec![SearchKind::Alert],
+                from: Some("2026-10-18T08:00:00Z".to_string()),
+                to: Some("2026-10-18T10:00:00Z".to_string()),
+                ..query("truck")
+            })
+            .await
+            .unwrap();
+        assert_eq!(results.total, 1);
+        assert_eq!(results.hits[0].title, "Truck left open");
+
+        // Triggers keep the index in step with updates and deletes
+        streams
+            .update(
+                &yard.id,
+                UpdateStreamRequest {
+                    name: Some("Back garden".to_string()),
+                    description: None,
+                    config: None,
+                    is_default: None,
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(search.search(&query("garden")).await.unwrap().total, 1);
+        assert_eq!(search.search(&query("yard")).await.unwrap().total, 0);
+        streams.delete(&gate.id).await.unwrap();
+        assert_eq!(search.search(&query("driveway")).await.unwrap().total, 0);
+
+        // Hits stay attached to their records when VACUUM renumbers rows
+        sqlx::query("DELETE FROM analysis_events WHERE id = ?")
+            .bind(&event_ids[0])
+            .execute(db.pool())
+            .await
+            .unwrap();
+        sqlx::query("VACUUM").execute(db.pool()).await.unwrap();
+        let results = search.search(&query("ladder")).await.unwrap();
+        assert_eq!(results.total, 1);
+        assert_eq!(results.hits[0].id, event_ids[1]);
+        search.rebuild().await.unwrap();
+        assert_eq!(search.search(&query("ladder")).await.unwrap().total, 1);
+
+        // Deleted records leave no index keys behind
+        let (keys, records): (i64, i64) = sqlx::query_as(
+            "SELECT (SELECT COUNT(*) FROM search_keys),
+                    (SELECT COUNT(*) FROM analysis_events) + (SELECT COUNT(*) FROM alerts)
+                    + (SELECT COUNT(*) FROM streams)",
+        )
+        .fetch_one(db.pool())
+        .await
+        .unwrap();
+        assert_eq!(keys, records);
+        assert_eq!(search.search(&query("truck")).await.unwrap().total, 1);
+
+        assert!(search.search(&query("\" - *")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_digest_report_repository() {
+        let db = create_test_db()
+            .await
+            .expect("Failed to create test database");
+        let reports = DigestReportRepository::new(db.clone());
+        for (name, period_start, period_end) in [
+            ("morning", "2026-10-16T07:00:00Z", "2026-10-17T07:00:00Z"),
+            ("morning", "2026-10-17T07:00:00Z", "2026-10-18T07:00:00Z"),
+            ("weekly", "2026-10-11T07:00:00Z", "2026-10-18T07:00:00Z"),
+        ] {
+            reports
+                .create(CreateDigestReport {
+                    name: name.to_string(),
+                    period: "daily".to_string(),
+                    period_start: period_start.to_string(),
+                    period_end: period_end.to_string(),
+                    summary: "Quiet day".to_string(),
+                    html: "<p>Quiet day</p>".to_string(),
+                    stats: serde_json::json!({"gate": {"events": 2}}),
+                })
+                .await
+                .unwrap();
+        }
+
+        let morning = reports.list(Some("morning"), 10, 0).await.unwrap();
+        assert_eq!(morning.len(), 2);
+        assert_eq!(morning[0].period_start, "2026-10-17T07:00:00Z");
+        // Listings leave out the HTML
+        assert!(morning[0].html.is_empty());
+        assert_eq!(morning[0].stats["gate"]["events"], 2);
+        assert_eq!(reports.list(None, 10, 0).await.unwrap().len(), 3);
+
+        reports
+            .set_delivery_error(&morning[0].id, Some("SMTP server refused"))
+            .await
+            .unwrap();
+        let stored = reports.get_by_id(&morning[0].id).await.unwrap().unwrap();
+        assert_eq!(stored.html, "<p>Quiet day</p>");
+        assert_eq!(
+            stored.delivery_error.as_deref(),
+            Some("SMTP server refused")
+        );
+        assert!(reports.get_by_id("missing").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_plate_search() {
+        let db = create_test_db()
+            .await
+            .expect("Failed to create test database");
+        let user = UserRepository::new(db.pool())
+            .create(CreateUserRequest {
+                username: "plate_user".to_string(),
+                email: "plates@example.com".to_string(),
+                password_hash: "hashed_password".to_string(),
+            })
+            .await
+            .unwrap();
+        let gate = StreamRepository::new(db.pool())
+            .create(CreateStreamRequest {
+                user_id: user.id.clone(),
+                name: "Gate".to_string(),
+                description: None,
+                config: "{}".to_string(),
+                is_default: false,
+            })
+            .await
+            .unwrap();
+        let events = AnalysisEventRepository::new(db.clone());
+        for (event_type, plate) in [
+            ("plate_read", "AB12CDE"),
+            ("plate_read", "XY12CDF"),
+            ("frame_described", "AB12CDE"),
+        ] {
+            events
+                .create(CreateAnalysisEvent {
+                    template_id: gate.id.clone(),
+                    event_type: event_type.to_string(),
+                    severity: "medium".to_string(),
+                    confidence: 0.9,
+                    description: format!("Plate {} read (unknown vehicle)", plate),
+                    metadata: Some(std::collections::HashMap::from([(
+                        "plate".to_string(),
+                        serde_json::json!(plate),
+                    )])),
+                    processor_name: "plate".to_string(),
+                    source_id: gate.id.clone(),
+                    should_notify: false,
+                    suggested_actions: None,
+                })
+                .await
+                .unwrap();
+        }
+
+        let hits = events
+            .search_plates("AB12", None, None, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].event_type, "plate_read");
+        assert_eq!(
+            events
+                .search_plates("12CD", Some(&gate.id), None, None, 10)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+        // LIKE wildcards in the query are matched literally
+        assert!(events
+            .search_plates("%", None, None, None, 10)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(events
+            .search_plates("AB12", None, Some("2999-01-01T00:00:00Z"), None, 10)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_original_frames_expire() {
+        let db = create_test_db()
+            .await
+            .expect("Failed to create test database");
+        let user = UserRepository::new(db.pool())
+            .create(CreateUserRequest {
+                username: "privacy_user".to_string(),
+                email: "privacy@example.com".to_string(),
+                password_hash: "hashed_password".to_string(),
+            })
+            .await
+            .unwrap();
+        let stream = StreamRepository::new(db.pool())
+            .create(CreateStreamRequest {
+                user_id: user.id.clone(),
+                name: "Street".to_string(),
+                description: None,
+                config: "{}".to_string(),
+                is_default: false,
+            })
+            .await
+            .unwrap();
+        let originals = OriginalFrameRepository::new(db.clone());
+        let mut ids = Vec::new();
+        for (captured_at, expires_at) in [
+            ("2020-01-01T08:00:00Z", "2020-01-01T09:00:00Z"),
+            ("2026-10-18T10:00:00Z", "2999-01-01T00:00:00Z"),
+        ] {
+            let frame = originals
+                .create(CreateOriginalFrame {
+                    stream_id: stream.id.clone(),
+                    file_path: format!("/tmp/original_{}.jpg", captured_at),
+                    storage_uri: format!("file:///tmp/original_{}.jpg", captured_at),
+                    file_size: 1024,
+                    captured_at: captured_at.to_string(),
+                    expires_at: expires_at.to_string(),
+                })
+                .await
+                .unwrap();
+            ids.push(frame.id);
+        }
+
+        // Expired originals are never returned, even before they are purged
+        let listed = originals.list_by_stream(&stream.id, 10, 0).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, ids[1]);
+        assert!(originals.get_by_id(&ids[0]).await.unwrap().is_none());
+        assert!(originals.get_by_id(&ids[1]).await.unwrap().is_some());
+
+        let purged = originals
+            .take_expired("2026-10-18T12:00:00Z")
+            .await
+            .unwrap();
+        assert_eq!(purged.len(), 1);
+        assert_eq!(purged[0].id, ids[0]);
+        assert!(originals
+            .take_expired("2026-10-18T12:00:00Z")
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
     #[tokio::test]
     async fn test_database_migrations_run_successfully() {
         let db = create_test_db()
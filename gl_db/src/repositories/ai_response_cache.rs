@@ -0,0 +1,110 @@
+//! ABOUTME: Repository for cached AI frame descriptions keyed on perceptual hash
+//! ABOUTME: Lets unchanged scenes reuse an earlier answer across restarts
+
+use crate::Db;
+use gl_core::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use tracing::debug;
+
+/// A cached response and the frame it was produced for
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AiResponseCacheEntry {
+    pub cache_key: String,
+    pub perceptual_hash: String,
+    /// JSON-encoded response
+    pub response: String,
+    /// Unix seconds
+    pub created_at: i64,
+}
+
+/// Repository for the AI response cache
+#[derive(Clone)]
+pub struct AiResponseCacheRepository {
+    db: Db,
+}
+
+impl AiResponseCacheRepository {
+    pub fn new(db: Db) -> Self {
+        Self { db }
+    }
+
+    /// Store a response for a frame
+    pub async fn insert(&self, entry: &AiResponseCacheEntry) -> Result<()> {
+        debug!(cache_key = %entry.cache_key, "Caching AI response");
+
+        sqlx::query(
+            r#"
+            INSERT INTO ai_response_cache (cache_key, perceptual_hash, response, created_at)
+            VALUES (?, ?, ?, ?)
+            "#,
+        )
+        .bind(&entry.cache_key)
+        .bind(&entry.perceptual_hash)
+        .bind(&entry.response)
+        .bind(entry.created_at)
+        .execute(&self.db.pool)
+        .await
+        .map_err(|e| gl_core::Error::Database(format!("Failed to cache AI response: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Entries for a key created at or after `since`, newest first
+    pub async fn recent(
+        &self,
+        cache_key: &str,
+        since: i64,
+        limit: u32,
+    ) -> Result<Vec<AiResponseCacheEntry>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT cache_key, perceptual_hash, response, created_at
+            FROM ai_response_cache
+            WHERE cache_key = ? AND created_at >= ?
+            ORDER BY created_at DESC, id DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(cache_key)
+        .bind(since)
+        .bind(limit as i64)
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| gl_core::Error::Database(format!("Failed to read AI cache: {}", e)))?;
+
+        rows.into_iter().map(Self::row_to_entry).collect()
+    }
+
+    /// Delete entries created before `before`, returning how many were removed
+    pub async fn delete_before(&self, before: i64) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM ai_response_cache WHERE created_at < ?")
+            .bind(before)
+            .execute(&self.db.pool)
+            .await
+            .map_err(|e| gl_core::Error::Database(format!("Failed to prune AI cache: {}", e)))?;
+
+        Ok(result.rows_affected())
+    }
+
+    fn row_to_entry(row: sqlx::sqlite::SqliteRow) -> Result<AiResponseCacheEntry> {
+        let get_err = |field: &str, e: sqlx::Error| {
+            gl_core::Error::Database(format!("Failed to get {}: {}", field, e))
+        };
+
+        Ok(AiResponseCacheEntry {
+            cache_key: row
+                .try_get("cache_key")
+                .map_err(|e| get_err("cache_key", e))?,
+            perceptual_hash: row
+                .try_get("perceptual_hash")
+                .map_err(|e| get_err("perceptual_hash", e))?,
+            response: row
+                .try_get("response")
+                .map_err(|e| get_err("response", e))?,
+            created_at: row
+                .try_get("created_at")
+                .map_err(|e| get_err("created_at", e))?,
+        })
+    }
+}
@@ -0,0 +1,168 @@
+//! ABOUTME: Repository for daily AI usage counters per stream and model
+//! ABOUTME: Backs per-stream token and cost budgets for AI calls
+
+use crate::Db;
+use gl_core::{time::now_iso8601, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use tracing::debug;
+
+/// AI usage of one stream with one model on one day
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiUsageRecord {
+    pub stream_id: String,
+    /// UTC date, `YYYY-MM-DD`
+    pub day: String,
+    pub model: String,
+    pub requests: u64,
+    pub tokens: u64,
+    pub cost_usd: f64,
+    pub updated_at: String,
+}
+
+/// Usage summed over every model a stream used on a day
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct AiUsageTotal {
+    pub requests: u64,
+    pub tokens: u64,
+    pub cost_usd: f64,
+}
+
+/// Repository for AI usage
+#[derive(Clone)]
+pub struct AiUsageRepository {
+    db: Db,
+}
+
+impl AiUsageRepository {
+    pub fn new(db: Db) -> Self {
+        Self { db }
+    }
+
+    /// Add one request's usage to the stream's counters for the day
+    pub async fn record(
+        &self,
+        stream_id: &str,
+        day: &str,
+        model: &str,
+        tokens: u64,
+        cost_usd: f64,
+    ) -> Result<()> {
+        debug!(stream_id = %stream_id, day = %day, model = %model, tokens, "Recording AI usage");
+
+        sqlx::query(
+            r#"
+            INSERT INTO ai_usage (
+                stream_id, day, model, requests, tokens, cost_usd, updated_at
+            ) VALUES (?, ?, ?, 1, ?, ?, ?)
+            ON CONFLICT(stream_id, day, model) DO UPDATE SET
+                requests = requests + 1,
+                tokens = tokens + excluded.tokens,
+                cost_usd = cost_usd + excluded.cost_usd,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(stream_id)
+        .bind(day)
+        .bind(model)
+        .bind(tokens as i64)
+        .bind(cost_usd)
+        .bind(now_iso8601())
+        .execute(&self.db.pool)
+        .await
+        .map_err(|e| gl_core::Error::Database(format!("Failed to record AI usage: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Usage of a stream on a day across all models
+    pub async fn daily_total(&self, stream_id: &str, day: &str) -> Result<AiUsageTotal> {
+        let row = sqlx::query(
+            r#"
+            SELECT COALESCE(SUM(requests), 0) AS requests,
+                   COALESCE(SUM(tokens), 0) AS tokens,
+                   COALESCE(SUM(cost_usd), 0.0) AS cost_usd
+            FROM ai_usage
+            WHERE stream_id = ? AND day = ?
+            "#,
+        )
+        .bind(stream_id)
+        .bind(day)
+        .fetch_one(&self.db.pool)
+        .await
+        .map_err(|e| gl_core::Error::Database(format!("Failed to get AI usage: {}", e)))?;
+
+        let get_err = |field: &str, e: sqlx::Error| {
+            gl_core::Error::Database(format!("Failed to get {}: {}", field, e))
+        };
+        let requests: i64 = row
+            .try_get("requests")
+            .map_err(|e| get_err("requests", e))?;
+        let tokens: i64 = row.try_get("tokens").map_err(|e| get_err("tokens", e))?;
+        let cost_usd: f64 = row
+            .try_get("cost_usd")
+            .map_err(|e| get_err("cost_usd", e))?;
+
+        Ok(AiUsageTotal {
+            requests: requests as u64,
+            tokens: tokens as u64,
+            cost_usd,
+        })
+    }
+
+    /// All usage recorded on a day, costliest first
+    pub async fn list_day(&self, day: &str) -> Result<Vec<AiUsageRecord>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT stream_id, day, model, requests, tokens, cost_usd, updated_at
+            FROM ai_usage
+            WHERE day = ?
+            ORDER BY cost_usd DESC, tokens DESC
+            "#,
+        )
+        .bind(day)
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| gl_core::Error::Database(format!("Failed to list AI usage: {}", e)))?;
+
+        rows.into_iter().map(Self::row_to_record).collect()
+    }
+
+    /// Delete days before the given date, returning how many rows were removed
+    pub async fn delete_before(&self, day: &str) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM ai_usage WHERE day < ?")
+            .bind(day)
+            .execute(&self.db.pool)
+            .await
+            .map_err(|e| gl_core::Error::Database(format!("Failed to delete AI usage: {}", e)))?;
+
+        Ok(result.rows_affected())
+    }
+
+    fn row_to_record(row: sqlx::sqlite::SqliteRow) -> Result<AiUsageRecord> {
+        let get_err = |field: &str, e: sqlx::Error| {
+            gl_core::Error::Database(format!("Failed to get {}: {}", field, e))
+        };
+
+        let requests: i64 = row
+            .try_get("requests")
+            .map_err(|e| get_err("requests", e))?;
+        let tokens: i64 = row.try_get("tokens").map_err(|e| get_err("tokens", e))?;
+
+        Ok(AiUsageRecord {
+            stream_id: row
+                .try_get("stream_id")
+                .map_err(|e| get_err("stream_id", e))?,
+            day: row.try_get("day").map_err(|e| get_err("day", e))?,
+            model: row.try_get("model").map_err(|e| get_err("model", e))?,
+            requests: requests as u64,
+            tokens: tokens as u64,
+            cost_usd: row
+                .try_get("cost_usd")
+                .map_err(|e| get_err("cost_usd", e))?,
+            updated_at: row
+                .try_get("updated_at")
+                .map_err(|e| get_err("updated_at", e))?,
+        })
+    }
+}
@@ -206,6 +206,87 @@ impl AnalysisEventRepository {
         Ok(events)
     }
 
+    /// Events created within `[from, to)`, oldest first, optionally for one template
+    pub async fn list_range(
+        &self,
+        template_id: Option<&str>,
+        from: &str,
+        to: &str,
+        limit: i64,
+    ) -> Result<Vec<AnalysisEvent>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, template_id, event_type, severity, confidence, description,
+                   metadata, processor_name, source_id, should_notify, suggested_actions, created_at
+            FROM analysis_events
+            WHERE created_at >= ? AND created_at < ? AND (? IS NULL OR template_id = ?)
+            ORDER BY created_at ASC
+            LIMIT ?
+            "#,
+        )
+        .bind(from)
+        .bind(to)
+        .bind(template_id)
+        .bind(template_id)
+        .bind(limit)
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| {
+            gl_core::Error::Database(format!("Failed to list analysis events in range: {}", e))
+        })?;
+
+        rows.into_iter()
+            .map(|row| self.row_to_analysis_event(row))
+            .collect()
+    }
+
+    /// Find plate reads whose plate contains `plate` (upper case letters and digits), newest first
+    pub async fn search_plates(
+        &self,
+        plate: &str,
+        template_id: Option<&str>,
+        from: Option<&str>,
+        to: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<AnalysisEvent>> {
+        let pattern = format!(
+            "%{}%",
+            plate
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_")
+        );
+        let rows = sqlx::query(
+            r#"
+            SELECT id, template_id, event_type, severity, confidence, description,
+                   metadata, processor_name, source_id, should_notify, suggested_actions, created_at
+            FROM analysis_events
+            WHERE event_type = 'plate_read'
+              AND json_extract(metadata, '$.plate') LIKE ? ESCAPE '\'
+              AND (? IS NULL OR template_id = ?)
+              AND (? IS NULL OR created_at >= ?)
+              AND (? IS NULL OR created_at <= ?)
+            ORDER BY created_at DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(&pattern)
+        .bind(template_id)
+        .bind(template_id)
+        .bind(from)
+        .bind(from)
+        .bind(to)
+        .bind(to)
+        .bind(limit)
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| gl_core::Error::Database(format!("Failed to search plate reads: {}", e)))?;
+
+        rows.into_iter()
+            .map(|row| self.row_to_analysis_event(row))
+            .collect()
+    }
+
     /// Get pending notification events (should_notify = true, ordered by severity and time)
     pub async fn get_pending_notifications(&self, limit: i64) -> Result<Vec<AnalysisEvent>> {
         let rows = sqlx::query(
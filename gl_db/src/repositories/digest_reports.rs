@@ -0,0 +1,184 @@
+//! ABOUTME: Repository for digest reports summarizing stream activity over a period
+//! ABOUTME: Stores the rendered narrative and HTML so reports can be viewed after delivery
+
+use crate::Db;
+use gl_core::{time::now_iso8601, Id, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use tracing::debug;
+
+/// A generated digest report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestReport {
+    pub id: String,
+    /// Configured digest that produced the report
+    pub name: String,
+    /// "daily" or "weekly"
+    pub period: String,
+    /// RFC3339 UTC, inclusive
+    pub period_start: String,
+    /// RFC3339 UTC, exclusive
+    pub period_end: String,
+    /// Plain text narrative
+    pub summary: String,
+    /// Rendered report; served separately because embedded frames make it large
+    #[serde(skip_serializing, default)]
+    pub html: String,
+    /// Per-stream event counts
+    pub stats: serde_json::Value,
+    /// Why delivery failed, if it did
+    pub delivery_error: Option<String>,
+    pub created_at: String,
+}
+
+/// Request to store a digest report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateDigestReport {
+    pub name: String,
+    pub period: String,
+    pub period_start: String,
+    pub period_end: String,
+    pub summary: String,
+    pub html: String,
+    pub stats: serde_json::Value,
+}
+
+/// Repository for digest reports
+#[derive(Clone)]
+pub struct DigestReportRepository {
+    db: Db,
+}
+
+impl DigestReportRepository {
+    pub fn new(db: Db) -> Self {
+        Self { db }
+    }
+
+    /// Store a new report
+    pub async fn create(&self, request: CreateDigestReport) -> Result<DigestReport> {
+        let id = Id::new().to_string();
+        let created_at = now_iso8601();
+        debug!(report_id = %id, name = %request.name, period_start = %request.period_start, "Creating digest report");
+
+        sqlx::query(
+            r#"
+            INSERT INTO digest_reports (
+                id, name, period, period_start, period_end, summary, html, stats, created_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&id)
+        .bind(&request.name)
+        .bind(&request.period)
+        .bind(&request.period_start)
+        .bind(&request.period_end)
+        .bind(&request.summary)
+        .bind(&request.html)
+        .bind(request.stats.to_string())
+        .bind(&created_at)
+        .execute(&self.db.pool)
+        .await
+        .map_err(|e| gl_core::Error::Database(format!("Failed to create digest report: {}", e)))?;
+
+        Ok(DigestReport {
+            id,
+            name: request.name,
+            period: request.period,
+            period_start: request.period_start,
+            period_end: request.period_end,
+            summary: request.summary,
+            html: request.html,
+            stats: request.stats,
+            delivery_error: None,
+            created_at,
+        })
+    }
+
+    /// Get a report by ID
+    pub async fn get_by_id(&self, id: &str) -> Result<Option<DigestReport>> {
+        sqlx::query(
+            r#"
+            SELECT id, name, period, period_start, period_end, summary, html, stats,
+                   delivery_error, created_at
+            FROM digest_reports
+            WHERE id = ?
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.db.pool)
+        .await
+        .map_err(|e| gl_core::Error::Database(format!("Failed to get digest report: {}", e)))?
+        .map(Self::row_to_report)
+        .transpose()
+    }
+
+    /// List reports newest first, optionally for one digest; the HTML is left empty
+    pub async fn list(
+        &self,
+        name: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<DigestReport>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, name, period, period_start, period_end, summary, '' AS html, stats,
+                   delivery_error, created_at
+            FROM digest_reports
+            WHERE ? IS NULL OR name = ?
+            ORDER BY period_end DESC, created_at DESC
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(name)
+        .bind(name)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| gl_core::Error::Database(format!("Failed to list digest reports: {}", e)))?;
+
+        rows.into_iter().map(Self::row_to_report).collect()
+    }
+
+    /// Record the outcome of sending a report
+    pub async fn set_delivery_error(&self, id: &str, error: Option<&str>) -> Result<()> {
+        sqlx::query("UPDATE digest_reports SET delivery_error = ? WHERE id = ?")
+            .bind(error)
+            .bind(id)
+            .execute(&self.db.pool)
+            .await
+            .map_err(|e| {
+                gl_core::Error::Database(format!("Failed to update digest report: {}", e))
+            })?;
+
+        Ok(())
+    }
+
+    fn row_to_report(row: sqlx::sqlite::SqliteRow) -> Result<DigestReport> {
+        let get_err = |field: &str, e: sqlx::Error| {
+            gl_core::Error::Database(format!("Failed to get {}: {}", field, e))
+        };
+
+        let stats: String = row.try_get("stats").map_err(|e| get_err("stats", e))?;
+        Ok(DigestReport {
+            id: row.try_get("id").map_err(|e| get_err("id", e))?,
+            name: row.try_get("name").map_err(|e| get_err("name", e))?,
+            period: row.try_get("period").map_err(|e| get_err("period", e))?,
+            period_start: row
+                .try_get("period_start")
+                .map_err(|e| get_err("period_start", e))?,
+            period_end: row
+                .try_get("period_end")
+                .map_err(|e| get_err("period_end", e))?,
+            summary: row.try_get("summary").map_err(|e| get_err("summary", e))?,
+            html: row.try_get("html").map_err(|e| get_err("html", e))?,
+            stats: serde_json::from_str(&stats).unwrap_or_default(),
+            delivery_error: row
+                .try_get("delivery_error")
+                .map_err(|e| get_err("delivery_error", e))?,
+            created_at: row
+                .try_get("created_at")
+                .map_err(|e| get_err("created_at", e))?,
+        })
+    }
+}
@@ -1,15 +1,25 @@
 //! ABOUTME: Repository modules providing type-safe database operations
 //! ABOUTME: Each repository handles CRUD operations for specific entity types
 
+pub mod ai_response_cache;
+pub mod ai_usage;
 pub mod alerts;
 pub mod analysis_events;
 pub mod api_keys;
 pub mod background_snapshot_jobs;
 pub mod captures;
+pub mod digest_reports;
 pub mod events;
 pub mod jobs;
+pub mod motion_heatmaps;
 pub mod notification_deliveries;
+pub mod original_frames;
+pub mod push_subscriptions;
+pub mod rule_actions;
+pub mod rule_state;
+pub mod search;
 pub mod settings;
+pub mod snapshot_embeddings;
 pub mod snapshots;
 pub mod streams;
 pub mod users;
@@ -0,0 +1,203 @@
+//! ABOUTME: Repository for hourly motion heat grids accumulated per stream
+//! ABOUTME: Merges partial hours as captures flush and lists hours for a time range
+
+use crate::Db;
+use gl_core::{time::now_iso8601, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use tracing::debug;
+
+/// Motion activity of one stream during one hour
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MotionHeatmap {
+    pub stream_id: String,
+    /// RFC3339 UTC timestamp truncated to the hour
+    pub hour_start: String,
+    pub cols: u32,
+    pub rows: u32,
+    /// Frames analyzed during the hour
+    pub frames: u64,
+    /// Row-major count of frames each cell saw motion in
+    pub cells: Vec<u32>,
+    pub updated_at: String,
+}
+
+/// Repository for motion heatmaps
+#[derive(Clone)]
+pub struct MotionHeatmapRepository {
+    db: Db,
+}
+
+impl MotionHeatmapRepository {
+    pub fn new(db: Db) -> Self {
+        Self { db }
+    }
+
+    /// Add activity to a stream's hour, creating it if needed
+    ///
+    /// A stored hour with a different grid shape is replaced rather than mixed with
+    /// counts it cannot line up with.
+    pub async fn merge(
+        &self,
+        stream_id: &str,
+        hour_start: &str,
+        cols: u32,
+        rows: u32,
+        frames: u64,
+        cells: &[u32],
+    ) -> Result<MotionHeatmap> {
+        if cells.len() != (cols * rows) as usize {
+            return Err(gl_core::Error::Validation(format!(
+                "Heatmap of {}x{} needs {} cells, got {}",
+                cols,
+                rows,
+                cols * rows,
+                cells.len()
+            )));
+        }
+
+        debug!(stream_id = %stream_id, hour_start = %hour_start, frames, "Merging motion heatmap");
+
+        let mut tx =
+            self.db.pool.begin().await.map_err(|e| {
+                gl_core::Error::Database(format!("Failed to start transaction: {}", e))
+            })?;
+
+        let existing = sqlx::query(
+            r#"
+            SELECT stream_id, hour_start, cols, rows, frames, cells, updated_at
+            FROM motion_heatmaps
+            WHERE stream_id = ? AND hour_start = ?
+            "#,
+        )
+        .bind(stream_id)
+        .bind(hour_start)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| gl_core::Error::Database(format!("Failed to get motion heatmap: {}", e)))?
+        .map(Self::row_to_heatmap)
+        .transpose()?;
+
+        let mut heatmap = MotionHeatmap {
+            stream_id: stream_id.to_string(),
+            hour_start: hour_start.to_string(),
+            cols,
+            rows,
+            frames,
+            cells: cells.to_vec(),
+            updated_at: now_iso8601(),
+        };
+        if let Some(existing) = existing.filter(|h| h.cols == cols && h.rows == rows) {
+            heatmap.frames += existing.frames;
+            for (cell, stored) in heatmap.cells.iter_mut().zip(existing.cells) {
+                *cell = cell.saturating_add(stored);
+            }
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO motion_heatmaps (
+                stream_id, hour_start, cols, rows, frames, cells, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(stream_id, hour_start) DO UPDATE SET
+                cols = excluded.cols,
+                rows = excluded.rows,
+                frames = excluded.frames,
+                cells = excluded.cells,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&heatmap.stream_id)
+        .bind(&heatmap.hour_start)
+        .bind(heatmap.cols as i64)
+        .bind(heatmap.rows as i64)
+        .bind(heatmap.frames as i64)
+        .bind(encode_cells(&heatmap.cells))
+        .bind(&heatmap.updated_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| gl_core::Error::Database(format!("Failed to store motion heatmap: {}", e)))?;
+
+        tx.commit().await.map_err(|e| {
+            gl_core::Error::Database(format!("Failed to commit motion heatmap: {}", e))
+        })?;
+
+        Ok(heatmap)
+    }
+
+    /// Hours of a stream starting within `[from, to)`, oldest first
+    pub async fn list_range(
+        &self,
+        stream_id: &str,
+        from: &str,
+        to: &str,
+    ) -> Result<Vec<MotionHeatmap>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT stream_id, hour_start, cols, rows, frames, cells, updated_at
+            FROM motion_heatmaps
+            WHERE stream_id = ? AND hour_start >= ? AND hour_start < ?
+            ORDER BY hour_start ASC
+            "#,
+        )
+        .bind(stream_id)
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| gl_core::Error::Database(format!("Failed to list motion heatmaps: {}", e)))?;
+
+        rows.into_iter().map(Self::row_to_heatmap).collect()
+    }
+
+    /// Delete hours older than the given timestamp, returning how many were removed
+    pub async fn delete_before(&self, before: &str) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM motion_heatmaps WHERE hour_start < ?")
+            .bind(before)
+            .execute(&self.db.pool)
+            .await
+            .map_err(|e| {
+                gl_core::Error::Database(format!("Failed to delete motion heatmaps: {}", e))
+            })?;
+
+        Ok(result.rows_affected())
+    }
+
+    fn row_to_heatmap(row: sqlx::sqlite::SqliteRow) -> Result<MotionHeatmap> {
+        let get_err = |field: &str, e: sqlx::Error| {
+            gl_core::Error::Database(format!("Failed to get {}: {}", field, e))
+        };
+
+        let cols: i64 = row.try_get("cols").map_err(|e| get_err("cols", e))?;
+        let rows: i64 = row.try_get("rows").map_err(|e| get_err("rows", e))?;
+        let frames: i64 = row.try_get("frames").map_err(|e| get_err("frames", e))?;
+        let cells: Vec<u8> = row.try_get("cells").map_err(|e| get_err("cells", e))?;
+
+        Ok(MotionHeatmap {
+            stream_id: row
+                .try_get("stream_id")
+                .map_err(|e| get_err("stream_id", e))?,
+            hour_start: row
+                .try_get("hour_start")
+                .map_err(|e| get_err("hour_start", e))?,
+            cols: cols as u32,
+            rows: rows as u32,
+            frames: frames as u64,
+            cells: decode_cells(&cells),
+            updated_at: row
+                .try_get("updated_at")
+                .map_err(|e| get_err("updated_at", e))?,
+        })
+    }
+}
+
+fn encode_cells(cells: &[u32]) -> Vec<u8> {
+    cells.iter().flat_map(|cell| cell.to_le_bytes()).collect()
+}
+
+fn decode_cells(bytes: &[u8]) -> Vec<u32> {
+    bytes
+        .chunks_exact(4)
+        .map(|chunk| u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect()
+}
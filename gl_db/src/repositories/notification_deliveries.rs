@@ -320,6 +320,51 @@ impl NotificationDeliveryRepository {
         Ok(deliveries)
     }
 
+    /// Get a delivery by ID
+    pub async fn get_by_id(&self, id: &str) -> Result<Option<NotificationDelivery>> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, analysis_event_id, channel_type, channel_config, status, attempt_count,
+                   max_attempts, scheduled_at, sent_at, delivered_at, failed_at, error_message,
+                   external_id, metadata, created_at, updated_at
+            FROM notification_deliveries
+            WHERE id = ?
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.db.pool)
+        .await
+        .map_err(|e| gl_core::Error::Database(format!("Failed to get delivery: {}", e)))?;
+
+        row.map(|row| self.row_to_notification_delivery(row))
+            .transpose()
+    }
+
+    /// List the most recent deliveries, newest first
+    pub async fn list_recent(&self, limit: i64) -> Result<Vec<NotificationDelivery>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, analysis_event_id, channel_type, channel_config, status, attempt_count,
+                   max_attempts, scheduled_at, sent_at, delivered_at, failed_at, error_message,
+                   external_id, metadata, created_at, updated_at
+            FROM notification_deliveries
+            ORDER BY created_at DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| gl_core::Error::Database(format!("Failed to list deliveries: {}", e)))?;
+
+        let mut deliveries = Vec::new();
+        for row in rows {
+            deliveries.push(self.row_to_notification_delivery(row)?);
+        }
+
+        Ok(deliveries)
+    }
+
     /// Schedule retry for failed delivery
     pub async fn schedule_retry(&self, id: &str, delay_minutes: i32) -> Result<()> {
         let scheduled_at = format!("datetime('now', '+{} minutes')", delay_minutes);
@@ -0,0 +1,170 @@
+//! ABOUTME: Repository for unmasked originals of frames from privacy-masked streams
+//! ABOUTME: Every read filters on expiry so originals are never served past their retention
+
+use crate::Db;
+use gl_core::{time::now_iso8601, Id, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use tracing::debug;
+
+/// An unmasked frame kept under short retention
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OriginalFrame {
+    pub id: String,
+    pub stream_id: String,
+    pub file_path: String,
+    pub storage_uri: String,
+    pub file_size: i64,
+    /// RFC3339 UTC
+    pub captured_at: String,
+    /// RFC3339 UTC; the frame is no longer available after this
+    pub expires_at: String,
+}
+
+/// Request to record a stored original
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateOriginalFrame {
+    pub stream_id: String,
+    pub file_path: String,
+    pub storage_uri: String,
+    pub file_size: i64,
+    pub captured_at: String,
+    pub expires_at: String,
+}
+
+/// Repository for original frames
+#[derive(Clone)]
+pub struct OriginalFrameRepository {
+    db: Db,
+}
+
+impl OriginalFrameRepository {
+    pub fn new(db: Db) -> Self {
+        Self { db }
+    }
+
+    /// Record a stored original
+    pub async fn create(&self, request: CreateOriginalFrame) -> Result<OriginalFrame> {
+        let id = Id::new().to_string();
+        debug!(frame_id = %id, stream_id = %request.stream_id, expires_at = %request.expires_at, "Recording original frame");
+
+        sqlx::query(
+            r#"
+            INSERT INTO original_frames (
+                id, stream_id, file_path, storage_uri, file_size, captured_at, expires_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&id)
+        .bind(&request.stream_id)
+        .bind(&request.file_path)
+        .bind(&request.storage_uri)
+        .bind(request.file_size)
+        .bind(&request.captured_at)
+        .bind(&request.expires_at)
+        .execute(&self.db.pool)
+        .await
+        .map_err(|e| gl_core::Error::Database(format!("Failed to create original frame: {}", e)))?;
+
+        Ok(OriginalFrame {
+            id,
+            stream_id: request.stream_id,
+            file_path: request.file_path,
+            storage_uri: request.storage_uri,
+            file_size: request.file_size,
+            captured_at: request.captured_at,
+            expires_at: request.expires_at,
+        })
+    }
+
+    /// Get an unexpired original by ID
+    pub async fn get_by_id(&self, id: &str) -> Result<Option<OriginalFrame>> {
+        sqlx::query(
+            r#"
+            SELECT id, stream_id, file_path, storage_uri, file_size, captured_at, expires_at
+            FROM original_frames
+            WHERE id = ? AND expires_at > ?
+            "#,
+        )
+        .bind(id)
+        .bind(now_iso8601())
+        .fetch_optional(&self.db.pool)
+        .await
+        .map_err(|e| gl_core::Error::Database(format!("Failed to get original frame: {}", e)))?
+        .map(Self::row_to_frame)
+        .transpose()
+    }
+
+    /// List a stream's unexpired originals, newest first
+    pub async fn list_by_stream(
+        &self,
+        stream_id: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<OriginalFrame>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, stream_id, file_path, storage_uri, file_size, captured_at, expires_at
+            FROM original_frames
+            WHERE stream_id = ? AND expires_at > ?
+            ORDER BY captured_at DESC
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(stream_id)
+        .bind(now_iso8601())
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| gl_core::Error::Database(format!("Failed to list original frames: {}", e)))?;
+
+        rows.into_iter().map(Self::row_to_frame).collect()
+    }
+
+    /// Remove originals that expired at or before `now` and return them so their files
+    /// can be deleted
+    pub async fn take_expired(&self, now: &str) -> Result<Vec<OriginalFrame>> {
+        let rows = sqlx::query(
+            r#"
+            DELETE FROM original_frames
+            WHERE expires_at <= ?
+            RETURNING id, stream_id, file_path, storage_uri, file_size, captured_at, expires_at
+            "#,
+        )
+        .bind(now)
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| gl_core::Error::Database(format!("Failed to purge original frames: {}", e)))?;
+
+        rows.into_iter().map(Self::row_to_frame).collect()
+    }
+
+    fn row_to_frame(row: sqlx::sqlite::SqliteRow) -> Result<OriginalFrame> {
+        let get_err = |field: &str, e: sqlx::Error| {
+            gl_core::Error::Database(format!("Failed to get {}: {}", field, e))
+        };
+
+        Ok(OriginalFrame {
+            id: row.try_get("id").map_err(|e| get_err("id", e))?,
+            stream_id: row
+                .try_get("stream_id")
+                .map_err(|e| get_err("stream_id", e))?,
+            file_path: row
+                .try_get("file_path")
+                .map_err(|e| get_err("file_path", e))?,
+            storage_uri: row
+                .try_get("storage_uri")
+                .map_err(|e| get_err("storage_uri", e))?,
+            file_size: row
+                .try_get("file_size")
+                .map_err(|e| get_err("file_size", e))?,
+            captured_at: row
+                .try_get("captured_at")
+                .map_err(|e| get_err("captured_at", e))?,
+            expires_at: row
+                .try_get("expires_at")
+                .map_err(|e| get_err("expires_at", e))?,
+        })
+    }
+}
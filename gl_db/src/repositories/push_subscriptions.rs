@@ -0,0 +1,261 @@
+//! ABOUTME: Repository for browser Web Push subscriptions and the server VAPID key pair
+//! ABOUTME: Stores one subscription per push endpoint and removes expired ones
+
+use crate::Db;
+use gl_core::{time::now_iso8601, Id, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use tracing::debug;
+
+/// Browser push subscription registered by a user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PushSubscription {
+    pub id: String,
+    pub user_id: String,
+    pub endpoint: String,
+    pub p256dh: String,
+    pub auth: String,
+    pub user_agent: Option<String>,
+    pub created_at: String,
+    pub last_success_at: Option<String>,
+}
+
+/// Request to register a push subscription
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatePushSubscription {
+    pub user_id: String,
+    pub endpoint: String,
+    pub p256dh: String,
+    pub auth: String,
+    pub user_agent: Option<String>,
+}
+
+/// Stored VAPID key pair, base64url encoded
+#[derive(Debug, Clone)]
+pub struct StoredVapidKeys {
+    pub private_key: String,
+    pub public_key: String,
+}
+
+/// Repository for push subscriptions
+#[derive(Clone)]
+pub struct PushSubscriptionRepository {
+    db: Db,
+}
+
+impl PushSubscriptionRepository {
+    pub fn new(db: Db) -> Self {
+        Self { db }
+    }
+
+    /// Register a subscription, replacing any existing one for the same endpoint
+    pub async fn upsert(&self, request: CreatePushSubscription) -> Result<PushSubscription> {
+        let id = Id::new().to_string();
+        let now = now_iso8601();
+
+        debug!(
+            user_id = %request.user_id,
+            endpoint = %request.endpoint,
+            "Registering push subscription"
+        );
+
+        sqlx::query(
+            r#"
+            INSERT INTO push_subscriptions (
+                id, user_id, endpoint, p256dh, auth, user_agent, created_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(endpoint) DO UPDATE SET
+                user_id = excluded.user_id,
+                p256dh = excluded.p256dh,
+                auth = excluded.auth,
+                user_agent = excluded.user_agent
+            "#,
+        )
+        .bind(&id)
+        .bind(&request.user_id)
+        .bind(&request.endpoint)
+        .bind(&request.p256dh)
+        .bind(&request.auth)
+        .bind(&request.user_agent)
+        .bind(&now)
+        .execute(&self.db.pool)
+        .await
+        .map_err(|e| {
+            gl_core::Error::Database(format!("Failed to register push subscription: {}", e))
+        })?;
+
+        self.get_by_endpoint(&request.endpoint)
+            .await?
+            .ok_or_else(|| gl_core::Error::Database("Push subscription not stored".to_string()))
+    }
+
+    /// Get a subscription by its push endpoint
+    pub async fn get_by_endpoint(&self, endpoint: &str) -> Result<Option<PushSubscription>> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, user_id, endpoint, p256dh, auth, user_agent, created_at, last_success_at
+            FROM push_subscriptions
+            WHERE endpoint = ?
+            "#,
+        )
+        .bind(endpoint)
+        .fetch_optional(&self.db.pool)
+        .await
+        .map_err(|e| gl_core::Error::Database(format!("Failed to get push subscription: {}", e)))?;
+
+        row.map(Self::row_to_subscription).transpose()
+    }
+
+    /// List subscriptions for a user
+    pub async fn list_by_user(&self, user_id: &str) -> Result<Vec<PushSubscription>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, user_id, endpoint, p256dh, auth, user_agent, created_at, last_success_at
+            FROM push_subscriptions
+            WHERE user_id = ?
+            ORDER BY created_at ASC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| {
+            gl_core::Error::Database(format!("Failed to list push subscriptions: {}", e))
+        })?;
+
+        rows.into_iter().map(Self::row_to_subscription).collect()
+    }
+
+    /// List every subscription
+    pub async fn list_all(&self) -> Result<Vec<PushSubscription>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, user_id, endpoint, p256dh, auth, user_agent, created_at, last_success_at
+            FROM push_subscriptions
+            ORDER BY created_at ASC
+            "#,
+        )
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| {
+            gl_core::Error::Database(format!("Failed to list push subscriptions: {}", e))
+        })?;
+
+        rows.into_iter().map(Self::row_to_subscription).collect()
+    }
+
+    /// Delete a user's subscription by endpoint
+    pub async fn delete_for_user(&self, user_id: &str, endpoint: &str) -> Result<bool> {
+        let result =
+            sqlx::query("DELETE FROM push_subscriptions WHERE user_id = ? AND endpoint = ?")
+                .bind(user_id)
+                .bind(endpoint)
+                .execute(&self.db.pool)
+                .await
+                .map_err(|e| {
+                    gl_core::Error::Database(format!("Failed to delete push subscription: {}", e))
+                })?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Delete a subscription the push service reported as gone
+    pub async fn delete_by_endpoint(&self, endpoint: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM push_subscriptions WHERE endpoint = ?")
+            .bind(endpoint)
+            .execute(&self.db.pool)
+            .await
+            .map_err(|e| {
+                gl_core::Error::Database(format!("Failed to delete push subscription: {}", e))
+            })?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Record a successful push to a subscription
+    pub async fn mark_success(&self, endpoint: &str) -> Result<()> {
+        sqlx::query("UPDATE push_subscriptions SET last_success_at = ? WHERE endpoint = ?")
+            .bind(now_iso8601())
+            .bind(endpoint)
+            .execute(&self.db.pool)
+            .await
+            .map_err(|e| {
+                gl_core::Error::Database(format!("Failed to update push subscription: {}", e))
+            })?;
+
+        Ok(())
+    }
+
+    /// Get the stored VAPID key pair
+    pub async fn get_vapid_keys(&self) -> Result<Option<StoredVapidKeys>> {
+        let row = sqlx::query("SELECT private_key, public_key FROM vapid_keys WHERE id = 1")
+            .fetch_optional(&self.db.pool)
+            .await
+            .map_err(|e| gl_core::Error::Database(format!("Failed to get VAPID keys: {}", e)))?;
+
+        row.map(|row| {
+            Ok(StoredVapidKeys {
+                private_key: row.try_get("private_key").map_err(|e| {
+                    gl_core::Error::Database(format!("Failed to get private_key: {}", e))
+                })?,
+                public_key: row.try_get("public_key").map_err(|e| {
+                    gl_core::Error::Database(format!("Failed to get public_key: {}", e))
+                })?,
+            })
+        })
+        .transpose()
+    }
+
+    /// Store the VAPID key pair unless one already exists, returning the stored pair
+    pub async fn store_vapid_keys(&self, keys: StoredVapidKeys) -> Result<StoredVapidKeys> {
+        sqlx::query(
+            r#"
+            INSERT INTO vapid_keys (id, private_key, public_key, created_at)
+            VALUES (1, ?, ?, ?)
+            ON CONFLICT(id) DO NOTHING
+            "#,
+        )
+        .bind(&keys.private_key)
+        .bind(&keys.public_key)
+        .bind(now_iso8601())
+        .execute(&self.db.pool)
+        .await
+        .map_err(|e| gl_core::Error::Database(format!("Failed to store VAPID keys: {}", e)))?;
+
+        self.get_vapid_keys()
+            .await?
+            .ok_or_else(|| gl_core::Error::Database("VAPID keys not stored".to_string()))
+    }
+
+    /// Convert database row to PushSubscription
+    fn row_to_subscription(row: sqlx::sqlite::SqliteRow) -> Result<PushSubscription> {
+        Ok(PushSubscription {
+            id: row
+                .try_get("id")
+                .map_err(|e| gl_core::Error::Database(format!("Failed to get id: {}", e)))?,
+            user_id: row
+                .try_get("user_id")
+                .map_err(|e| gl_core::Error::Database(format!("Failed to get user_id: {}", e)))?,
+            endpoint: row
+                .try_get("endpoint")
+                .map_err(|e| gl_core::Error::Database(format!("Failed to get endpoint: {}", e)))?,
+            p256dh: row
+                .try_get("p256dh")
+                .map_err(|e| gl_core::Error::Database(format!("Failed to get p256dh: {}", e)))?,
+            auth: row
+                .try_get("auth")
+                .map_err(|e| gl_core::Error::Database(format!("Failed to get auth: {}", e)))?,
+            user_agent: row
+                .try_get::<Option<String>, _>("user_agent")
+                .ok()
+                .flatten(),
+            created_at: row.try_get("created_at").map_err(|e| {
+                gl_core::Error::Database(format!("Failed to get created_at: {}", e))
+            })?,
+            last_success_at: row
+                .try_get::<Option<String>, _>("last_success_at")
+                .ok()
+                .flatten(),
+        })
+    }
+}
@@ -0,0 +1,205 @@
+//! ABOUTME: Repository for the execution log of side-effect rule actions
+//! ABOUTME: Records HTTP calls, snapshot bursts, recordings and AI describes with their outcome
+
+use crate::Db;
+use gl_core::{time::now_iso8601, Id, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use tracing::debug;
+
+/// Logged outcome of a single rule action execution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleActionResult {
+    pub id: String,
+    pub rule_id: String,
+    pub action_type: String,
+    pub event_id: String,
+    pub source_id: String,
+    pub success: bool,
+    pub output: Option<serde_json::Value>,
+    pub error_message: Option<String>,
+    pub duration_ms: i64,
+    pub executed_at: String,
+}
+
+/// Request to record a rule action execution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateRuleActionResult {
+    pub rule_id: String,
+    pub action_type: String,
+    pub event_id: String,
+    pub source_id: String,
+    pub success: bool,
+    pub output: Option<serde_json::Value>,
+    pub error_message: Option<String>,
+    pub duration_ms: i64,
+}
+
+/// Repository for rule action results
+#[derive(Clone)]
+pub struct RuleActionResultRepository {
+    db: Db,
+}
+
+impl RuleActionResultRepository {
+    pub fn new(db: Db) -> Self {
+        Self { db }
+    }
+
+    /// Record the outcome of an action execution
+    pub async fn create(&self, request: CreateRuleActionResult) -> Result<RuleActionResult> {
+        let id = Id::new().to_string();
+        let executed_at = now_iso8601();
+
+        let output_json = request
+            .output
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| gl_core::Error::Database(format!("Failed to serialize output: {}", e)))?;
+
+        debug!(
+            result_id = %id,
+            rule_id = %request.rule_id,
+            action_type = %request.action_type,
+            success = request.success,
+            "Recording rule action result"
+        );
+
+        sqlx::query(
+            r#"
+            INSERT INTO rule_action_results (
+                id, rule_id, action_type, event_id, source_id, success, output,
+                error_message, duration_ms, executed_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&id)
+        .bind(&request.rule_id)
+        .bind(&request.action_type)
+        .bind(&request.event_id)
+        .bind(&request.source_id)
+        .bind(request.success)
+        .bind(&output_json)
+        .bind(&request.error_message)
+        .bind(request.duration_ms)
+        .bind(&executed_at)
+        .execute(&self.db.pool)
+        .await
+        .map_err(|e| {
+            gl_core::Error::Database(format!("Failed to record rule action result: {}", e))
+        })?;
+
+        Ok(RuleActionResult {
+            id,
+            rule_id: request.rule_id,
+            action_type: request.action_type,
+            event_id: request.event_id,
+            source_id: request.source_id,
+            success: request.success,
+            output: request.output,
+            error_message: request.error_message,
+            duration_ms: request.duration_ms,
+            executed_at,
+        })
+    }
+
+    /// List the most recent results for a rule
+    pub async fn list_by_rule(&self, rule_id: &str, limit: i64) -> Result<Vec<RuleActionResult>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, rule_id, action_type, event_id, source_id, success, output,
+                   error_message, duration_ms, executed_at
+            FROM rule_action_results
+            WHERE rule_id = ?
+            ORDER BY executed_at DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(rule_id)
+        .bind(limit)
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| {
+            gl_core::Error::Database(format!("Failed to list rule action results: {}", e))
+        })?;
+
+        rows.into_iter().map(Self::row_to_result).collect()
+    }
+
+    /// List all results produced for an event
+    pub async fn list_by_event(&self, event_id: &str) -> Result<Vec<RuleActionResult>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, rule_id, action_type, event_id, source_id, success, output,
+                   error_message, duration_ms, executed_at
+            FROM rule_action_results
+            WHERE event_id = ?
+            ORDER BY executed_at ASC
+            "#,
+        )
+        .bind(event_id)
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| {
+            gl_core::Error::Database(format!("Failed to list rule action results: {}", e))
+        })?;
+
+        rows.into_iter().map(Self::row_to_result).collect()
+    }
+
+    /// Delete results executed before the given ISO 8601 timestamp
+    pub async fn delete_older_than(&self, before: &str) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM rule_action_results WHERE executed_at < ?")
+            .bind(before)
+            .execute(&self.db.pool)
+            .await
+            .map_err(|e| {
+                gl_core::Error::Database(format!("Failed to delete rule action results: {}", e))
+            })?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Convert database row to RuleActionResult
+    fn row_to_result(row: sqlx::sqlite::SqliteRow) -> Result<RuleActionResult> {
+        let output_json: Option<String> = row
+            .try_get("output")
+            .map_err(|e| gl_core::Error::Database(format!("Failed to get output: {}", e)))?;
+        let output = output_json
+            .map(|json| serde_json::from_str(&json))
+            .transpose()
+            .map_err(|e| {
+                gl_core::Error::Database(format!("Failed to deserialize output: {}", e))
+            })?;
+
+        Ok(RuleActionResult {
+            id: row
+                .try_get("id")
+                .map_err(|e| gl_core::Error::Database(format!("Failed to get id: {}", e)))?,
+            rule_id: row
+                .try_get("rule_id")
+                .map_err(|e| gl_core::Error::Database(format!("Failed to get rule_id: {}", e)))?,
+            action_type: row.try_get("action_type").map_err(|e| {
+                gl_core::Error::Database(format!("Failed to get action_type: {}", e))
+            })?,
+            event_id: row
+                .try_get("event_id")
+                .map_err(|e| gl_core::Error::Database(format!("Failed to get event_id: {}", e)))?,
+            source_id: row
+                .try_get("source_id")
+                .map_err(|e| gl_core::Error::Database(format!("Failed to get source_id: {}", e)))?,
+            success: row
+                .try_get("success")
+                .map_err(|e| gl_core::Error::Database(format!("Failed to get success: {}", e)))?,
+            output,
+            error_message: row.try_get("error_message").ok(),
+            duration_ms: row.try_get("duration_ms").map_err(|e| {
+                gl_core::Error::Database(format!("Failed to get duration_ms: {}", e))
+            })?,
+            executed_at: row.try_get("executed_at").map_err(|e| {
+                gl_core::Error::Database(format!("Failed to get executed_at: {}", e))
+            })?,
+        })
+    }
+}
@@ -0,0 +1,216 @@
+//! ABOUTME: Repository for persistent rule engine state (history, dedup keys, rate limits)
+//! ABOUTME: Keeps rule windows consistent across restarts and between analysis services
+
+use crate::Db;
+use gl_core::{Id, Result};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// Event recorded in the rule engine history window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordRuleEvent {
+    pub event_id: String,
+    pub template_id: String,
+    pub event_type: String,
+    pub source_id: String,
+    /// Unix epoch seconds when the event occurred
+    pub occurred_at: i64,
+    /// Unix epoch seconds after which the record can be purged
+    pub expires_at: i64,
+}
+
+/// Counts of expired rows removed by a purge
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RuleStatePurgeStats {
+    pub history_rows: u64,
+    pub dedup_keys: u64,
+    pub rate_limit_windows: u64,
+}
+
+/// Repository for rule engine state
+#[derive(Clone)]
+pub struct RuleStateRepository {
+    db: Db,
+}
+
+impl RuleStateRepository {
+    pub fn new(db: Db) -> Self {
+        Self { db }
+    }
+
+    /// Record an event in the history window
+    pub async fn record_event(&self, request: RecordRuleEvent) -> Result<()> {
+        let id = Id::new().to_string();
+
+        sqlx::query(
+            r#"
+            INSERT INTO rule_event_history (
+                id, event_id, template_id, event_type, source_id, occurred_at, expires_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&id)
+        .bind(&request.event_id)
+        .bind(&request.template_id)
+        .bind(&request.event_type)
+        .bind(&request.source_id)
+        .bind(request.occurred_at)
+        .bind(request.expires_at)
+        .execute(&self.db.pool)
+        .await
+        .map_err(|e| gl_core::Error::Database(format!("Failed to record rule event: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Count history events that occurred after `since`, optionally filtered by type
+    pub async fn count_events_since(&self, event_type: Option<&str>, since: i64) -> Result<u32> {
+        let count: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM rule_event_history
+            WHERE occurred_at > ? AND (? IS NULL OR event_type = ?)
+            "#,
+        )
+        .bind(since)
+        .bind(event_type)
+        .bind(event_type)
+        .fetch_one(&self.db.pool)
+        .await
+        .map_err(|e| gl_core::Error::Database(format!("Failed to count rule events: {}", e)))?;
+
+        Ok(count.max(0) as u32)
+    }
+
+    /// Claim a deduplication key for `window_seconds`.
+    ///
+    /// Returns `true` when the key was free (or had expired) and is now held by
+    /// this event, and `false` when a live claim exists, i.e. the event is a duplicate.
+    pub async fn claim_dedup_key(
+        &self,
+        dedup_key: &str,
+        event_type: &str,
+        event_id: &str,
+        now: i64,
+        window_seconds: i64,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO rule_dedup_keys (dedup_key, event_type, last_event_id, first_seen_at, expires_at)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(dedup_key) DO UPDATE SET
+                event_type = excluded.event_type,
+                last_event_id = excluded.last_event_id,
+                first_seen_at = excluded.first_seen_at,
+                expires_at = excluded.expires_at
+            WHERE rule_dedup_keys.expires_at <= ?
+            "#,
+        )
+        .bind(dedup_key)
+        .bind(event_type)
+        .bind(event_id)
+        .bind(now)
+        .bind(now + window_seconds)
+        .bind(now)
+        .execute(&self.db.pool)
+        .await
+        .map_err(|e| gl_core::Error::Database(format!("Failed to claim dedup key: {}", e)))?;
+
+        let claimed = result.rows_affected() > 0;
+        debug!(dedup_key, claimed, "Checked rule dedup key");
+        Ok(claimed)
+    }
+
+    /// Increment the counter for a rate limit window and return the new count
+    pub async fn increment_rate_limit(
+        &self,
+        rule_id: &str,
+        scope_key: &str,
+        window_start: i64,
+        window_seconds: i64,
+    ) -> Result<u32> {
+        let count: i64 = sqlx::query_scalar(
+            r#"
+            INSERT INTO rule_rate_limits (rule_id, scope_key, window_start, count, expires_at)
+            VALUES (?, ?, ?, 1, ?)
+            ON CONFLICT(rule_id, scope_key, window_start) DO UPDATE SET count = count + 1
+            RETURNING count
+            "#,
+        )
+        .bind(rule_id)
+        .bind(scope_key)
+        .bind(window_start)
+        .bind(window_start + window_seconds)
+        .fetch_one(&self.db.pool)
+        .await
+        .map_err(|e| {
+            gl_core::Error::Database(format!("Failed to increment rate limit counter: {}", e))
+        })?;
+
+        Ok(count.max(0) as u32)
+    }
+
+    /// Remove all state rows that expired at or before `now`
+    pub async fn purge_expired(&self, now: i64) -> Result<RuleStatePurgeStats> {
+        let history = sqlx::query("DELETE FROM rule_event_history WHERE expires_at <= ?")
+            .bind(now)
+            .execute(&self.db.pool)
+            .await
+            .map_err(|e| {
+                gl_core::Error::Database(format!("Failed to purge rule event history: {}", e))
+            })?;
+
+        let dedup = sqlx::query("DELETE FROM rule_dedup_keys WHERE expires_at <= ?")
+            .bind(now)
+            .execute(&self.db.pool)
+            .await
+            .map_err(|e| gl_core::Error::Database(format!("Failed to purge dedup keys: {}", e)))?;
+
+        let rate_limits = sqlx::query("DELETE FROM rule_rate_limits WHERE expires_at <= ?")
+            .bind(now)
+            .execute(&self.db.pool)
+            .await
+            .map_err(|e| {
+                gl_core::Error::Database(format!("Failed to purge rate limit windows: {}", e))
+            })?;
+
+        let stats = RuleStatePurgeStats {
+            history_rows: history.rows_affected(),
+            dedup_keys: dedup.rows_affected(),
+            rate_limit_windows: rate_limits.rows_affected(),
+        };
+
+        debug!(
+            history_rows = stats.history_rows,
+            dedup_keys = stats.dedup_keys,
+            rate_limit_windows = stats.rate_limit_windows,
+            "Purged expired rule engine state"
+        );
+        Ok(stats)
+    }
+
+    /// Number of events currently held in the history window
+    pub async fn history_size(&self) -> Result<u64> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM rule_event_history")
+            .fetch_one(&self.db.pool)
+            .await
+            .map_err(|e| {
+                gl_core::Error::Database(format!("Failed to count rule event history: {}", e))
+            })?;
+
+        Ok(count.max(0) as u64)
+    }
+
+    /// Remove all rule engine state
+    pub async fn clear(&self) -> Result<()> {
+        for table in ["rule_event_history", "rule_dedup_keys", "rule_rate_limits"] {
+            sqlx::query(&format!("DELETE FROM {}", table))
+                .execute(&self.db.pool)
+                .await
+                .map_err(|e| {
+                    gl_core::Error::Database(format!("Failed to clear {}: {}", table, e))
+                })?;
+        }
+
+        Ok(())
+    }
+}
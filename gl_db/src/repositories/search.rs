@@ -0,0 +1,434 @@
+//! ABOUTME: Full-text search over analysis events, alerts and streams using SQLite FTS5
+//! ABOUTME: Returns ranked hits with highlighted snippets plus stream, day and kind facets
+
+use crate::Db;
+use gl_core::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{sqlite::SqliteRow, Row};
+use tracing::debug;
+
+/// Marks the start and end of a match inside a snippet before it is escaped
+const MATCH_START: char = '\u{1}';
+const MATCH_END: char = '\u{2}';
+
+/// Kind of record a search hit refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchKind {
+    /// Analysis event, including AI frame descriptions
+    Event,
+    Alert,
+    /// Stream name or description
+    Stream,
+}
+
+impl SearchKind {
+    pub const ALL: [SearchKind; 3] = [SearchKind::Event, SearchKind::Alert, SearchKind::Stream];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SearchKind::Event => "event",
+            SearchKind::Alert => "alert",
+            SearchKind::Stream => "stream",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| k.as_str() == kind)
+    }
+
+    /// Matching rows as `kind, id, stream_id, title, snippet, ts, rank`
+    ///
+    /// Parameters: ?1 match expression, ?2 stream ID, ?3 earliest and ?4 latest time.
+    fn matches_sql(&self) -> String {
+        let snippet = |table: &str| {
+            format!(
+                "snippet({}, -1, char({}), char({}), '…', 16)",
+                table, MATCH_START as u32, MATCH_END as u32
+            )
+        };
+        match self {
+            SearchKind::Event => format!(
+                r#"
+                SELECT 'event' AS kind, e.id AS id, e.template_id AS stream_id,
+                       e.event_type AS title, {} AS snippet, e.created_at AS ts,
+                       bm25(analysis_events_fts) AS rank
+                FROM analysis_events_fts
+                JOIN search_keys k ON k.key = analysis_events_fts.rowid
+                JOIN analysis_events e ON e.id = k.record_id
+                WHERE analysis_events_fts MATCH ?1
+                  AND (?2 IS NULL OR e.template_id = ?2)
+                  AND (?3 IS NULL OR e.created_at >= ?3)
+                  AND (?4 IS NULL OR e.created_at <= ?4)
+                "#,
+                snippet("analysis_events_fts")
+            ),
+            SearchKind::Alert => format!(
+                r#"
+                SELECT 'alert' AS kind, a.id AS id, c.stream_id AS stream_id,
+                       a.title AS title, {} AS snippet, a.triggered_at AS ts,
+                       bm25(alerts_fts) AS rank
+                FROM alerts_fts
+                JOIN search_keys k ON k.key = alerts_fts.rowid
+                JOIN alerts a ON a.id = k.record_id
+                LEFT JOIN captures c ON c.id = a.capture_id
+                WHERE alerts_fts MATCH ?1
+                  AND (?2 IS NULL OR c.stream_id = ?2)
+                  AND (?3 IS NULL OR a.triggered_at >= ?3)
+                  AND (?4 IS NULL OR a.triggered_at <= ?4)
+                "#,
+                snippet("alerts_fts")
+            ),
+            // Streams have no time of their own, so a time range leaves them out
+            SearchKind::Stream => format!(
+                r#"
+                SELECT 'stream' AS kind, s.id AS id, s.id AS stream_id,
+                       s.name AS title, {} AS snippet, NULL AS ts,
+                       bm25(streams_fts) AS rank
+                FROM streams_fts
+                JOIN search_keys k ON k.key = streams_fts.rowid
+                JOIN streams s ON s.id = k.record_id
+                WHERE streams_fts MATCH ?1
+                  AND (?2 IS NULL OR s.id = ?2)
+                  AND ?3 IS NULL AND ?4 IS NULL
+                "#,
+                snippet("streams_fts")
+            ),
+        }
+    }
+}
+
+/// Full-text search request
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TextSearchQuery {
+    /// Words to look for; every word must match, as a prefix
+    pub text: String,
+    /// Record kinds to search, all when empty
+    pub kinds: Vec<SearchKind>,
+    pub stream_id: Option<String>,
+    /// Earliest time, RFC3339
+    pub from: Option<String>,
+    /// Latest time, RFC3339
+    pub to: Option<String>,
+    pub limit: i64,
+}
+
+/// A record matching a full-text search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub kind: SearchKind,
+    pub id: String,
+    pub stream_id: Option<String>,
+    pub stream_name: Option<String>,
+    /// Event type, alert title or stream name
+    pub title: String,
+    /// HTML-escaped excerpt with matches wrapped in `<mark>`
+    pub snippet: String,
+    /// When the event or alert happened; none for streams
+    pub timestamp: Option<String>,
+    /// Relevance, higher is better
+    pub score: f64,
+}
+
+/// Number of hits sharing a value
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchFacet {
+    pub value: String,
+    pub count: i64,
+}
+
+/// Number of hits for a stream
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamFacet {
+    pub stream_id: String,
+    pub stream_name: Option<String>,
+    pub count: i64,
+}
+
+/// Hit counts across all matches, not just the returned page
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchFacets {
+    pub kinds: Vec<SearchFacet>,
+    pub streams: Vec<StreamFacet>,
+    /// Per day (YYYY-MM-DD), oldest first
+    pub days: Vec<SearchFacet>,
+}
+
+/// Full-text search results
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchResults {
+    /// Number of matches before the limit
+    pub total: i64,
+    pub hits: Vec<SearchHit>,
+    pub facets: SearchFacets,
+}
+
+/// Repository for full-text search
+#[derive(Clone)]
+pub struct SearchRepository {
+    db: Db,
+}
+
+impl SearchRepository {
+    pub fn new(db: Db) -> Self {
+        Self { db }
+    }
+
+    /// Search events, alerts and streams, best matches first
+    pub async fn search(&self, query: &TextSearchQuery) -> Result<SearchResults> {
+        let Some(expression) = match_expression(&query.text) else {
+            return Err(gl_core::Error::Validation(
+                "Search text must contain a word".to_string(),
+            ));
+        };
+        let kinds = if query.kinds.is_empty() {
+            SearchKind::ALL.to_vec()
+        } else {
+            query.kinds.clone()
+        };
+        debug!(expression = %expression, kinds = ?kinds, "Running full-text search");
+
+        let matches = kinds
+            .iter()
+            .map(|kind| kind.matches_sql())
+            .collect::<Vec<_>>()
+            .join(" UNION ALL ");
+        let with_matches = |select: &str| format!("WITH matches AS ({}) {}", matches, select);
+
+        let hits = self
+            .fetch(
+                &with_matches(
+                    r#"
+                    SELECT m.kind, m.id, m.stream_id, st.name AS stream_name, m.title,
+                           m.snippet, m.ts, m.rank
+                    FROM matches m
+                    LEFT JOIN streams st ON st.id = m.stream_id
+                    ORDER BY m.rank
+                    LIMIT ?5
+                    "#,
+                ),
+                &expression,
+                query,
+                Some(query.limit),
+            )
+            .await?
+            .iter()
+            .map(row_to_hit)
+            .collect::<Result<Vec<_>>>()?;
+
+        let kind_rows = self
+            .fetch(
+                &with_matches(
+                    "SELECT kind AS value, COUNT(*) AS count FROM matches GROUP BY kind ORDER BY count DESC, kind",
+                ),
+                &expression,
+                query,
+                None,
+            )
+            .await?;
+        let kind_facets = kind_rows
+            .iter()
+            .map(row_to_facet)
+            .collect::<Result<Vec<_>>>()?;
+
+        let stream_rows = self
+            .fetch(
+                &with_matches(
+                    r#"
+                    SELECT m.stream_id, st.name AS stream_name, COUNT(*) AS count
+                    FROM matches m
+                    LEFT JOIN streams st ON st.id = m.stream_id
+                    WHERE m.stream_id IS NOT NULL
+                    GROUP BY m.stream_id
+                    ORDER BY count DESC, m.stream_id
+                    "#,
+                ),
+                &expression,
+                query,
+                None,
+            )
+            .await?;
+        let stream_facets = stream_rows
+            .iter()
+            .map(|row| {
+                Ok(StreamFacet {
+                    stream_id: row
+                        .try_get("stream_id")
+                        .map_err(|e| get_err("stream_id", e))?,
+                    stream_name: row
+                        .try_get("stream_name")
+                        .map_err(|e| get_err("stream_name", e))?,
+                    count: row.try_get("count").map_err(|e| get_err("count", e))?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let day_rows = self
+            .fetch(
+                &with_matches(
+                    r#"
+                    SELECT substr(ts, 1, 10) AS value, COUNT(*) AS count
+                    FROM matches
+                    WHERE ts IS NOT NULL
+                    GROUP BY value
+                    ORDER BY value
+                    "#,
+                ),
+                &expression,
+                query,
+                None,
+            )
+            .await?;
+        let day_facets = day_rows
+            .iter()
+            .map(row_to_facet)
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(SearchResults {
+            total: kind_facets.iter().map(|facet| facet.count).sum(),
+            hits,
+            facets: SearchFacets {
+                kinds: kind_facets,
+                streams: stream_facets,
+                days: day_facets,
+            },
+        })
+    }
+
+    /// Re-index every searchable record from its source table
+    ///
+    /// Triggers keep the indexes current, so this is only needed to repair them.
+    pub async fn rebuild(&self) -> Result<()> {
+        let rebuild_err = |table: &str, e: sqlx::Error| {
+            gl_core::Error::Database(format!("Failed to rebuild {}: {}", table, e))
+        };
+        let mut tx =
+            self.db.pool.begin().await.map_err(|e| {
+                gl_core::Error::Database(format!("Failed to begin transaction: {}", e))
+            })?;
+        for (table, source, columns) in [
+            (
+                "analysis_events_fts",
+                "analysis_events",
+                "description, event_type, processor_name",
+            ),
+            ("alerts_fts", "alerts", "title, message, alert_type"),
+            ("streams_fts", "streams", "name, description"),
+        ] {
+            for sql in [
+                format!(
+                    "DELETE FROM search_keys WHERE source = '{source}' \
+                     AND record_id NOT IN (SELECT id FROM {source})",
+                    source = source
+                ),
+                format!(
+                    "INSERT OR IGNORE INTO search_keys(source, record_id) \
+                     SELECT '{source}', id FROM {source}",
+                    source = source
+                ),
+                format!("DELETE FROM {}", table),
+                format!(
+                    "INSERT INTO {table}(rowid, {columns}) SELECT k.key, {columns} \
+                     FROM {source} JOIN search_keys k \
+                     ON k.source = '{source}' AND k.record_id = {source}.id",
+                    table = table,
+                    columns = columns,
+                    source = source
+                ),
+            ] {
+                sqlx::query(&sql)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e| rebuild_err(table, e))?;
+            }
+        }
+        tx.commit()
+            .await
+            .map_err(|e| gl_core::Error::Database(format!("Failed to commit rebuild: {}", e)))
+    }
+
+    async fn fetch(
+        &self,
+        sql: &str,
+        expression: &str,
+        query: &TextSearchQuery,
+        limit: Option<i64>,
+    ) -> Result<Vec<SqliteRow>> {
+        let mut statement = sqlx::query(sql)
+            .bind(expression)
+            .bind(&query.stream_id)
+            .bind(&query.from)
+            .bind(&query.to);
+        if let Some(limit) = limit {
+            statement = statement.bind(limit);
+        }
+        statement
+            .fetch_all(&self.db.pool)
+            .await
+            .map_err(|e| gl_core::Error::Database(format!("Failed to search: {}", e)))
+    }
+}
+
+/// FTS5 expression requiring every word of the text as a prefix
+///
+/// Words are quoted so operators and punctuation in user input are taken literally.
+fn match_expression(text: &str) -> Option<String> {
+    let terms = text
+        .split_whitespace()
+        .map(|word| word.replace('"', ""))
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+        .map(|word| format!("\"{}\"*", word))
+        .collect::<Vec<_>>();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+/// Escape a snippet for HTML and turn the match markers into `<mark>` tags
+fn highlight(snippet: &str) -> String {
+    let mut html = String::with_capacity(snippet.len());
+    for c in snippet.chars() {
+        match c {
+            MATCH_START => html.push_str("<mark>"),
+            MATCH_END => html.push_str("</mark>"),
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            c => html.push(c),
+        }
+    }
+    html
+}
+
+fn get_err(field: &str, e: sqlx::Error) -> gl_core::Error {
+    gl_core::Error::Database(format!("Failed to get {}: {}", field, e))
+}
+
+fn row_to_hit(row: &SqliteRow) -> Result<SearchHit> {
+    let kind: String = row.try_get("kind").map_err(|e| get_err("kind", e))?;
+    let rank: f64 = row.try_get("rank").map_err(|e| get_err("rank", e))?;
+    let snippet: Option<String> = row.try_get("snippet").map_err(|e| get_err("snippet", e))?;
+    Ok(SearchHit {
+        kind: SearchKind::parse(&kind)
+            .ok_or_else(|| gl_core::Error::Database(format!("Unknown search kind {}", kind)))?,
+        id: row.try_get("id").map_err(|e| get_err("id", e))?,
+        stream_id: row
+            .try_get("stream_id")
+            .map_err(|e| get_err("stream_id", e))?,
+        stream_name: row
+            .try_get("stream_name")
+            .map_err(|e| get_err("stream_name", e))?,
+        title: row.try_get("title").map_err(|e| get_err("title", e))?,
+        snippet: highlight(&snippet.unwrap_or_default()),
+        timestamp: row.try_get("ts").map_err(|e| get_err("ts", e))?,
+        // bm25 is negative, more so for better matches
+        score: -rank,
+    })
+}
+
+fn row_to_facet(row: &SqliteRow) -> Result<SearchFacet> {
+    Ok(SearchFacet {
+        value: row.try_get("value").map_err(|e| get_err("value", e))?,
+        count: row.try_get("count").map_err(|e| get_err("count", e))?,
+    })
+}
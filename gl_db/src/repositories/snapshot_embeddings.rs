@@ -0,0 +1,229 @@
+//! ABOUTME: Repository for snapshot image embeddings and brute-force similarity search
+//! ABOUTME: Ranks stored snapshots against a query vector with stream and time filters
+
+use crate::Db;
+use gl_core::{time::now_iso8601, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use tracing::debug;
+
+/// A stored snapshot that has no embedding for a model yet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingSnapshot {
+    pub id: String,
+    pub stream_id: String,
+    pub file_path: String,
+    pub captured_at: String,
+}
+
+/// Filters for a similarity search
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EmbeddingQuery {
+    pub model: String,
+    pub stream_id: Option<String>,
+    /// Earliest capture time, RFC3339
+    pub from: Option<String>,
+    /// Latest capture time, RFC3339
+    pub to: Option<String>,
+    pub limit: usize,
+}
+
+/// A snapshot ranked by how close its embedding is to the query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotMatch {
+    pub snapshot_id: String,
+    pub stream_id: String,
+    pub captured_at: String,
+    /// Cosine similarity, 1.0 for the same direction
+    pub score: f32,
+}
+
+/// Repository for snapshot embeddings
+#[derive(Clone)]
+pub struct SnapshotEmbeddingRepository {
+    db: Db,
+}
+
+impl SnapshotEmbeddingRepository {
+    pub fn new(db: Db) -> Self {
+        Self { db }
+    }
+
+    /// Store a snapshot's embedding for a model, replacing any earlier one
+    ///
+    /// An empty vector marks a snapshot that couldn't be embedded so it isn't retried;
+    /// such snapshots never match a search.
+    pub async fn upsert(&self, snapshot_id: &str, model: &str, vector: &[f32]) -> Result<()> {
+        debug!(snapshot_id = %snapshot_id, model = %model, dims = vector.len(), "Storing snapshot embedding");
+
+        sqlx::query(
+            r#"
+            INSERT INTO snapshot_embeddings (snapshot_id, model, dims, vector, created_at)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(snapshot_id, model) DO UPDATE SET
+                dims = excluded.dims,
+                vector = excluded.vector,
+                created_at = excluded.created_at
+            "#,
+        )
+        .bind(snapshot_id)
+        .bind(model)
+        .bind(vector.len() as i64)
+        .bind(encode_vector(&normalize(vector)))
+        .bind(now_iso8601())
+        .execute(&self.db.pool)
+        .await
+        .map_err(|e| {
+            gl_core::Error::Database(format!("Failed to store snapshot embedding: {}", e))
+        })?;
+
+        Ok(())
+    }
+
+    /// Newest snapshots that have no embedding for the model
+    pub async fn list_pending(&self, model: &str, limit: u32) -> Result<Vec<PendingSnapshot>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT s.id, s.stream_id, s.file_path, s.captured_at
+            FROM snapshots s
+            WHERE NOT EXISTS (
+                SELECT 1 FROM snapshot_embeddings e
+                WHERE e.snapshot_id = s.id AND e.model = ?
+            )
+            ORDER BY s.captured_at DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(model)
+        .bind(limit as i64)
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| {
+            gl_core::Error::Database(format!("Failed to list unembedded snapshots: {}", e))
+        })?;
+
+        let get_err = |field: &str, e: sqlx::Error| {
+            gl_core::Error::Database(format!("Failed to get {}: {}", field, e))
+        };
+        rows.into_iter()
+            .map(|row| {
+                Ok(PendingSnapshot {
+                    id: row.try_get("id").map_err(|e| get_err("id", e))?,
+                    stream_id: row
+                        .try_get("stream_id")
+                        .map_err(|e| get_err("stream_id", e))?,
+                    file_path: row
+                        .try_get("file_path")
+                        .map_err(|e| get_err("file_path", e))?,
+                    captured_at: row
+                        .try_get("captured_at")
+                        .map_err(|e| get_err("captured_at", e))?,
+                })
+            })
+            .collect()
+    }
+
+    /// A snapshot's stored embedding, if it has a usable one
+    pub async fn find_vector(&self, snapshot_id: &str, model: &str) -> Result<Option<Vec<f32>>> {
+        let vector: Option<Vec<u8>> = sqlx::query_scalar(
+            "SELECT vector FROM snapshot_embeddings WHERE snapshot_id = ? AND model = ?",
+        )
+        .bind(snapshot_id)
+        .bind(model)
+        .fetch_optional(&self.db.pool)
+        .await
+        .map_err(|e| {
+            gl_core::Error::Database(format!("Failed to get snapshot embedding: {}", e))
+        })?;
+
+        Ok(vector
+            .map(|bytes| decode_vector(&bytes))
+            .filter(|vector| !vector.is_empty()))
+    }
+
+    /// Snapshots most similar to a vector, best first
+    pub async fn search(
+        &self,
+        vector: &[f32],
+        query: &EmbeddingQuery,
+    ) -> Result<Vec<SnapshotMatch>> {
+        let target = normalize(vector);
+        if target.is_empty() {
+            return Ok(Vec::new());
+        }
+        let rows = sqlx::query(
+            r#"
+            SELECT e.snapshot_id, s.stream_id, s.captured_at, e.vector
+            FROM snapshot_embeddings e
+            JOIN snapshots s ON s.id = e.snapshot_id
+            WHERE e.model = ? AND e.dims = ?
+              AND (? IS NULL OR s.stream_id = ?)
+              AND (? IS NULL OR s.captured_at >= ?)
+              AND (? IS NULL OR s.captured_at <= ?)
+            "#,
+        )
+        .bind(&query.model)
+        .bind(target.len() as i64)
+        .bind(&query.stream_id)
+        .bind(&query.stream_id)
+        .bind(&query.from)
+        .bind(&query.from)
+        .bind(&query.to)
+        .bind(&query.to)
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| {
+            gl_core::Error::Database(format!("Failed to search snapshot embeddings: {}", e))
+        })?;
+
+        let get_err = |field: &str, e: sqlx::Error| {
+            gl_core::Error::Database(format!("Failed to get {}: {}", field, e))
+        };
+        let mut matches = rows
+            .into_iter()
+            .map(|row| {
+                let bytes: Vec<u8> = row.try_get("vector").map_err(|e| get_err("vector", e))?;
+                let candidate = decode_vector(&bytes);
+                Ok(SnapshotMatch {
+                    snapshot_id: row
+                        .try_get("snapshot_id")
+                        .map_err(|e| get_err("snapshot_id", e))?,
+                    stream_id: row
+                        .try_get("stream_id")
+                        .map_err(|e| get_err("stream_id", e))?,
+                    captured_at: row
+                        .try_get("captured_at")
+                        .map_err(|e| get_err("captured_at", e))?,
+                    score: target.iter().zip(&candidate).map(|(a, b)| a * b).sum(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+        matches.truncate(query.limit);
+        Ok(matches)
+    }
+}
+
+/// Scale a vector to unit length so a dot product is its cosine similarity
+fn normalize(vector: &[f32]) -> Vec<f32> {
+    let length = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if length == 0.0 {
+        return vector.to_vec();
+    }
+    vector.iter().map(|v| v / length).collect()
+}
+
+fn encode_vector(vector: &[f32]) -> Vec<u8> {
+    vector
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect()
+}
+
+fn decode_vector(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect()
+}
@@ -238,6 +238,37 @@ impl<'a> SnapshotRepository<'a> {
         Ok(record)
     }
 
+    /// Get the snapshot of a stream captured closest to a time, within `[from, to)`
+    #[instrument(skip(self))]
+    pub async fn find_nearest(
+        &self,
+        stream_id: &str,
+        at: &str,
+        from: &str,
+        to: &str,
+    ) -> Result<Option<SnapshotMetadata>> {
+        let record = sqlx::query_as::<_, SnapshotMetadata>(
+            r#"
+            SELECT id, stream_id, user_id, file_path, storage_uri, content_type,
+                   width, height, file_size, checksum, etag, captured_at,
+                   created_at, updated_at, perceptual_hash
+            FROM snapshots
+            WHERE stream_id = ? AND captured_at >= ? AND captured_at < ?
+            ORDER BY ABS(julianday(captured_at) - julianday(?)) ASC
+            LIMIT 1
+            "#,
+        )
+        .bind(stream_id)
+        .bind(from)
+        .bind(to)
+        .bind(at)
+        .fetch_optional(self.pool)
+        .await
+        .map_err(|e| Error::Database(format!("Failed to find nearest snapshot: {}", e)))?;
+
+        Ok(record)
+    }
+
     /// Delete old snapshots for a template (keep only the latest N)
     #[instrument(skip(self))]
     pub async fn cleanup_old_snapshots(&self, stream_id: &str, keep_count: i64) -> Result<i64> {
@@ -173,6 +173,23 @@ impl<'a> StreamRepository<'a> {
         streams.map_err(|e| Error::Database(format!("Failed to list streams: {}", e)))
     }
 
+    /// List all streams whose config has the given `kind`, across users
+    pub async fn list_by_kind(&self, kind: &str) -> Result<Vec<Stream>> {
+        sqlx::query_as::<_, Stream>(
+            r#"
+            SELECT id, user_id, name, description, config, is_default, created_at, updated_at,
+                   execution_status, last_executed_at, last_error_message
+            FROM streams
+            WHERE CASE WHEN json_valid(config) THEN json_extract(config, '$.kind') END = ?1
+            ORDER BY created_at
+            "#,
+        )
+        .bind(kind)
+        .fetch_all(self.pool)
+        .await
+        .map_err(|e| Error::Database(format!("Failed to list streams by kind: {}", e)))
+    }
+
     pub async fn count(&self, user_id: Option<&str>) -> Result<i64> {
         let count_val = if let Some(uid) = user_id {
             let row = sqlx::query("SELECT COUNT(*) as count FROM streams WHERE user_id = ?1")
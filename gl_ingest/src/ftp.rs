@@ -0,0 +1,574 @@
+//! ABOUTME: Minimal embedded FTP server that accepts snapshot uploads from cameras
+//! ABOUTME: Handles login, passive/active data connections, STOR and the commands cameras probe
+
+use crate::{is_jpeg, IngestSink, IngestTarget, Upload};
+use gl_core::Result;
+use metrics::counter;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::timeout;
+use tracing::{debug, info, warn};
+
+/// Longest command line accepted from a client
+const MAX_COMMAND_LINE: u64 = 1024;
+
+/// Failed logins allowed before the control connection is closed
+const MAX_LOGIN_ATTEMPTS: u32 = 3;
+
+/// FTP ingest server settings
+#[derive(Debug, Clone)]
+pub struct FtpIngestConfig {
+    pub bind_addr: SocketAddr,
+    /// Ports offered for passive data connections; `0..=0` uses any free port
+    pub passive_ports: RangeInclusive<u16>,
+    /// Address advertised in PASV replies when the server sits behind NAT
+    pub passive_address: Option<Ipv4Addr>,
+    pub max_upload_bytes: usize,
+    /// Control connections idle for this long are closed
+    pub idle_timeout: Duration,
+    /// How long to wait for the client to open a data connection
+    pub data_timeout: Duration,
+}
+
+impl Default for FtpIngestConfig {
+    fn default() -> Self {
+        Self {
+            bind_addr: SocketAddr::from(([0, 0, 0, 0], 2121)),
+            passive_ports: 0..=0,
+            passive_address: None,
+            max_upload_bytes: 20 * 1024 * 1024,
+            idle_timeout: Duration::from_secs(300),
+            data_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+/// FTP server that maps each login to a stream and each upload to a snapshot
+pub struct FtpServer {
+    listener: TcpListener,
+    config: Arc<FtpIngestConfig>,
+    sink: Arc<dyn IngestSink>,
+    next_passive_port: Arc<AtomicU16>,
+}
+
+impl FtpServer {
+    /// Bind the control port
+    pub async fn bind(config: FtpIngestConfig, sink: Arc<dyn IngestSink>) -> Result<Self> {
+        let listener = TcpListener::bind(config.bind_addr).await?;
+        Ok(Self {
+            listener,
+            config: Arc::new(config),
+            sink,
+            next_passive_port: Arc::new(AtomicU16::new(0)),
+        })
+    }
+
+    /// Address the control port is listening on
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Accept control connections until the task is dropped
+    pub async fn run(self) -> Result<()> {
+        info!(addr = %self.local_addr()?, "FTP ingest server listening");
+
+        loop {
+            let (socket, peer) = match self.listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    // Usually file descriptor exhaustion; back off instead of spinning
+                    warn!(error = %e, "Failed to accept FTP connection");
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    continue;
+                }
+            };
+
+            let session = match Session::new(
+                socket,
+                peer,
+                self.config.clone(),
+                self.sink.clone(),
+                self.next_passive_port.clone(),
+            ) {
+                Ok(session) => session,
+                Err(e) => {
+                    warn!(peer = %peer, error = %e, "Failed to set up FTP session");
+                    continue;
+                }
+            };
+
+            tokio::spawn(async move {
+                if let Err(e) = session.run().await {
+                    debug!(peer = %peer, error = %e, "FTP session ended with error");
+                }
+            });
+        }
+    }
+}
+
+/// Whether the session keeps reading commands
+enum Flow {
+    Continue,
+    Close,
+}
+
+/// Where the next data transfer connects
+enum DataChannel {
+    Passive(TcpListener),
+    Active(SocketAddr),
+}
+
+/// One control connection
+struct Session {
+    reader: BufReader<OwnedReadHalf>,
+    writer: OwnedWriteHalf,
+    peer: SocketAddr,
+    local: SocketAddr,
+    config: Arc<FtpIngestConfig>,
+    sink: Arc<dyn IngestSink>,
+    next_passive_port: Arc<AtomicU16>,
+    username: Option<String>,
+    target: Option<IngestTarget>,
+    failed_logins: u32,
+    cwd: String,
+    data: Option<DataChannel>,
+}
+
+impl Session {
+    fn new(
+        socket: TcpStream,
+        peer: SocketAddr,
+        config: Arc<FtpIngestConfig>,
+        sink: Arc<dyn IngestSink>,
+        next_passive_port: Arc<AtomicU16>,
+    ) -> io::Result<Self> {
+        let local = socket.local_addr()?;
+        let (read_half, writer) = socket.into_split();
+        Ok(Self {
+            reader: BufReader::new(read_half),
+            writer,
+            peer,
+            local,
+            config,
+            sink,
+            next_passive_port,
+            username: None,
+            target: None,
+            failed_logins: 0,
+            cwd: "/".to_string(),
+            data: None,
+        })
+    }
+
+    async fn run(mut self) -> io::Result<()> {
+        debug!(peer = %self.peer, "FTP connection opened");
+        self.reply(220, "Glimpser FTP ingest ready").await?;
+
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let read = timeout(
+                self.config.idle_timeout,
+                (&mut self.reader)
+                    .take(MAX_COMMAND_LINE)
+                    .read_line(&mut line),
+            )
+            .await;
+
+            match read {
+                Err(_) => {
+                    self.reply(421, "Idle timeout, closing connection").await?;
+                    break;
+                }
+                Ok(Err(e)) => return Err(e),
+                Ok(Ok(0)) => break,
+                Ok(Ok(_)) if !line.ends_with('\n') => {
+                    self.reply(500, "Command line too long").await?;
+                    break;
+                }
+                Ok(Ok(_)) => {}
+            }
+
+            let command = line.trim_end_matches(['\r', '\n']);
+            let (verb, arg) = command.split_once(' ').unwrap_or((command, ""));
+            if let Flow::Close = self.handle(&verb.to_ascii_uppercase(), arg).await? {
+                break;
+            }
+        }
+
+        debug!(peer = %self.peer, "FTP connection closed");
+        Ok(())
+    }
+
+    async fn handle(&mut self, verb: &str, arg: &str) -> io::Result<Flow> {
+        match verb {
+            "USER" => {
+                self.username = Some(arg.to_string());
+                self.target = None;
+                self.reply(331, "Password required").await?;
+            }
+            "PASS" => return self.login(arg).await,
+            "QUIT" => {
+                self.reply(221, "Goodbye").await?;
+                return Ok(Flow::Close);
+            }
+            "NOOP" => self.reply(200, "OK").await?,
+            "SYST" => self.reply(215, "UNIX Type: L8").await?,
+            "FEAT" => {
+                self.writer
+                    .write_all(b"211-Features:\r\n EPSV\r\n PASV\r\n UTF8\r\n211 End\r\n")
+                    .await?
+            }
+            "OPTS" => self.reply(200, "OK").await?,
+            "AUTH" => self.reply(502, "TLS is not supported").await?,
+            _ if self.target.is_none() => self.reply(530, "Not logged in").await?,
+            "PWD" | "XPWD" => {
+                let message = format!("\"{}\" is the current directory", self.cwd);
+                self.reply(257, &message).await?;
+            }
+            "CWD" | "XCWD" => {
+                self.cwd = self.resolve(arg);
+                self.reply(250, "Directory changed").await?;
+            }
+            "CDUP" | "XCUP" => {
+                self.cwd = self.resolve("..");
+                self.reply(250, "Directory changed").await?;
+            }
+            // Directories are virtual; cameras that create dated folders just get a yes
+            "MKD" | "XMKD" => {
+                let message = format!("\"{}\" created", self.resolve(arg));
+                self.reply(257, &message).await?;
+            }
+            "TYPE" | "MODE" | "STRU" => self.reply(200, "OK").await?,
+            "ALLO" => self.reply(202, "No storage allocation necessary").await?,
+            "PASV" => self.enter_passive(false).await?,
+            "EPSV" => self.enter_passive(true).await?,
+            "PORT" => {
+                let addr = parse_port(arg);
+                self.enter_active(addr).await?
+            }
+            "EPRT" => {
+                let addr = parse_eprt(arg);
+                self.enter_active(addr).await?
+            }
+            "STOR" | "APPE" => self.store(arg).await?,
+            "LIST" | "NLST" | "MLSD" => self.list().await?,
+            // Uploads are consumed immediately, so there is never anything to rename or remove
+            "RNFR" => self.reply(350, "Ready for destination name").await?,
+            "RNTO" | "DELE" | "RMD" => self.reply(250, "OK").await?,
+            "SIZE" | "MDTM" | "RETR" => self.reply(550, "File not available").await?,
+            "ABOR" => {
+                self.data = None;
+                self.reply(226, "No transfer in progress").await?;
+            }
+            _ => self.reply(502, "Command not implemented").await?,
+        }
+        Ok(Flow::Continue)
+    }
+
+    async fn login(&mut self, password: &str) -> io::Result<Flow> {
+        let Some(username) = self.username.take() else {
+            self.reply(503, "Login with USER first").await?;
+            return Ok(Flow::Continue);
+        };
+
+        match self.sink.authenticate("ftp", &username, password).await {
+            Ok(Some(target)) => {
+                info!(
+                    peer = %self.peer,
+                    stream_id = %target.stream_id,
+                    "FTP ingest login"
+                );
+                self.target = Some(target);
+                self.reply(230, "Logged in").await?;
+            }
+            Ok(None) => {
+                self.failed_logins += 1;
+                counter!("ftp_ingest_login_failures_total").increment(1);
+                warn!(peer = %self.peer, username = %username, "FTP ingest login failed");
+                self.reply(530, "Login incorrect").await?;
+                if self.failed_logins >= MAX_LOGIN_ATTEMPTS {
+                    return Ok(Flow::Close);
+                }
+            }
+            Err(e) => {
+                warn!(peer = %self.peer, error = %e, "FTP ingest authentication error");
+                self.reply(421, "Service not available").await?;
+                return Ok(Flow::Close);
+            }
+        }
+        Ok(Flow::Continue)
+    }
+
+    async fn enter_passive(&mut self, extended: bool) -> io::Result<()> {
+        let advertised = match (self.config.passive_address, self.local.ip()) {
+            (Some(address), _) => Some(address),
+            (None, IpAddr::V4(address)) => Some(address),
+            (None, IpAddr::V6(address)) => address.to_ipv4_mapped(),
+        };
+        if !extended && advertised.is_none() {
+            return self.reply(425, "Use EPSV for IPv6 connections").await;
+        }
+
+        let listener = match self.bind_passive().await {
+            Ok(listener) => listener,
+            Err(e) => {
+                warn!(error = %e, "No passive FTP port available");
+                return self.reply(425, "Can't open data connection").await;
+            }
+        };
+        let port = listener.local_addr()?.port();
+        self.data = Some(DataChannel::Passive(listener));
+
+        if extended {
+            let message = format!("Entering Extended Passive Mode (|||{}|)", port);
+            self.reply(229, &message).await
+        } else {
+            let [a, b, c, d] = advertised.unwrap_or(Ipv4Addr::UNSPECIFIED).octets();
+            let message = format!(
+                "Entering Passive Mode ({},{},{},{},{},{})",
+                a,
+                b,
+                c,
+                d,
+                port >> 8,
+                port & 0xff
+            );
+            self.reply(227, &message).await
+        }
+    }
+
+    /// Bind a data listener on the control connection's local address
+    async fn bind_passive(&self) -> io::Result<TcpListener> {
+        let ip = self.local.ip();
+        let (start, end) = (
+            *self.config.passive_ports.start(),
+            *self.config.passive_ports.end(),
+        );
+        if start == 0 || end < start {
+            return TcpListener::bind((ip, 0)).await;
+        }
+
+        // Rotate through the range so concurrent sessions don't all race for the first port
+        let span = end - start + 1;
+        let offset = self.next_passive_port.fetch_add(1, Ordering::Relaxed) % span;
+        let mut last_error = None;
+        for i in 0..span {
+            let port = start + (offset + i) % span;
+            match TcpListener::bind((ip, port)).await {
+                Ok(listener) => return Ok(listener),
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| io::Error::other("empty passive port range")))
+    }
+
+    async fn enter_active(&mut self, addr: Option<SocketAddr>) -> io::Result<()> {
+        let Some(addr) = addr else {
+            return self.reply(501, "Invalid address").await;
+        };
+        // Only connect back to the client itself (no FTP bounce)
+        if addr.ip() != self.peer.ip() {
+            return self
+                .reply(504, "Data connection must go to the client address")
+                .await;
+        }
+        self.data = Some(DataChannel::Active(addr));
+        self.reply(200, "PORT command successful").await
+    }
+
+    /// Open the data connection set up by PASV/EPSV/PORT/EPRT
+    async fn open_data(&mut self) -> io::Result<Option<TcpStream>> {
+        let Some(channel) = self.data.take() else {
+            self.reply(425, "Use PASV or PORT first").await?;
+            return Ok(None);
+        };
+
+        let connected = match channel {
+            DataChannel::Passive(listener) => {
+                match timeout(self.config.data_timeout, listener.accept()).await {
+                    Ok(Ok((stream, peer))) if peer.ip() == self.peer.ip() => Ok(stream),
+                    Ok(Ok((_, peer))) => Err(format!("data connection from {}", peer)),
+                    Ok(Err(e)) => Err(e.to_string()),
+                    Err(_) => Err("timed out".to_string()),
+                }
+            }
+            DataChannel::Active(addr) => {
+                match timeout(self.config.data_timeout, TcpStream::connect(addr)).await {
+                    Ok(Ok(stream)) => Ok(stream),
+                    Ok(Err(e)) => Err(e.to_string()),
+                    Err(_) => Err("timed out".to_string()),
+                }
+            }
+        };
+
+        match connected {
+            Ok(stream) => Ok(Some(stream)),
+            Err(reason) => {
+                debug!(peer = %self.peer, reason = %reason, "FTP data connection failed");
+                self.reply(425, "Can't open data connection").await?;
+                Ok(None)
+            }
+        }
+    }
+
+    async fn store(&mut self, arg: &str) -> io::Result<()> {
+        if arg.is_empty() {
+            return self.reply(501, "Missing file name").await;
+        }
+        let filename = self.resolve(arg);
+
+        let Some(mut stream) = self.open_data().await? else {
+            return Ok(());
+        };
+        self.reply(150, "Ok to send data").await?;
+
+        let limit = self.config.max_upload_bytes;
+        let mut data = Vec::new();
+        let read = timeout(
+            self.config.idle_timeout,
+            (&mut stream).take(limit as u64 + 1).read_to_end(&mut data),
+        )
+        .await;
+        drop(stream);
+
+        if !matches!(read, Ok(Ok(_))) {
+            counter!("ftp_ingest_failures_total").increment(1);
+            return self.reply(426, "Connection closed; transfer aborted").await;
+        }
+        if data.len() > limit {
+            counter!("ftp_ingest_failures_total").increment(1);
+            return self.reply(552, "Upload exceeds size limit").await;
+        }
+        if !is_jpeg(&data) {
+            counter!("ftp_ingest_failures_total").increment(1);
+            debug!(peer = %self.peer, filename = %filename, "Rejected non-JPEG upload");
+            return self.reply(550, "Only JPEG images are accepted").await;
+        }
+
+        let Some(target) = self.target.clone() else {
+            return self.reply(530, "Not logged in").await;
+        };
+        let upload = Upload {
+            target,
+            protocol: "ftp",
+            filename: filename.clone(),
+            data: data.into(),
+            text: None,
+            remote_addr: Some(self.peer),
+        };
+
+        match self.sink.ingest(upload).await {
+            Ok(snapshot_id) => {
+                counter!("ftp_ingest_uploads_total").increment(1);
+                debug!(
+                    peer = %self.peer,
+                    filename = %filename,
+                    snapshot_id = %snapshot_id,
+                    "Stored FTP upload"
+                );
+                self.reply(226, "Transfer complete").await
+            }
+            Err(e) => {
+                counter!("ftp_ingest_failures_total").increment(1);
+                warn!(peer = %self.peer, filename = %filename, error = %e, "Failed to ingest FTP upload");
+                self.reply(451, "Local error in processing").await
+            }
+        }
+    }
+
+    /// Directory listings are always empty; some cameras list before uploading
+    async fn list(&mut self) -> io::Result<()> {
+        let Some(mut stream) = self.open_data().await? else {
+            return Ok(());
+        };
+        self.reply(150, "Here comes the directory listing").await?;
+        let _ = stream.shutdown().await;
+        self.reply(226, "Directory send OK").await
+    }
+
+    /// Resolve a client path against the virtual working directory
+    fn resolve(&self, path: &str) -> String {
+        let joined = if path.starts_with('/') {
+            path.to_string()
+        } else {
+            format!("{}/{}", self.cwd, path)
+        };
+
+        let mut parts: Vec<&str> = Vec::new();
+        for part in joined.split('/') {
+            match part {
+                "" | "." => {}
+                ".." => {
+                    parts.pop();
+                }
+                part => parts.push(part),
+            }
+        }
+        format!("/{}", parts.join("/"))
+    }
+
+    async fn reply(&mut self, code: u16, message: &str) -> io::Result<()> {
+        self.writer
+            .write_all(format!("{} {}\r\n", code, message).as_bytes())
+            .await
+    }
+}
+
+/// Parse a `PORT h1,h2,h3,h4,p1,p2` argument
+fn parse_port(arg: &str) -> Option<SocketAddr> {
+    let numbers: Vec<u8> = arg
+        .split(',')
+        .map(|n| n.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    let [a, b, c, d, p1, p2] = numbers[..] else {
+        return None;
+    };
+    let port = u16::from(p1) << 8 | u16::from(p2);
+    Some(SocketAddr::from(([a, b, c, d], port)))
+}
+
+/// Parse an `EPRT |proto|address|port|` argument
+fn parse_eprt(arg: &str) -> Option<SocketAddr> {
+    let delimiter = arg.chars().next()?;
+    let fields: Vec<&str> = arg.split(delimiter).collect();
+    let [_, _, address, port, _] = fields[..] else {
+        return None;
+    };
+    Some(SocketAddr::new(address.parse().ok()?, port.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_port() {
+        assert_eq!(
+            parse_port("192,168,1,20,195,80"),
+            Some("192.168.1.20:50000".parse().unwrap())
+        );
+        assert_eq!(parse_port("192,168,1,20,195"), None);
+        assert_eq!(parse_port("192,168,1,300,195,80"), None);
+    }
+
+    #[test]
+    fn test_parse_eprt() {
+        assert_eq!(
+            parse_eprt("|1|10.0.0.5|6446|"),
+            Some("10.0.0.5:6446".parse().unwrap())
+        );
+        assert_eq!(
+            parse_eprt("|2|::1|6446|"),
+            Some("[::1]:6446".parse().unwrap())
+        );
+        assert_eq!(parse_eprt("|1|10.0.0.5|"), None);
+    }
+}
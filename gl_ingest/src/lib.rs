@@ -0,0 +1,60 @@
+//! ABOUTME: Push-based snapshot ingest for cameras that upload instead of being polled
+//! ABOUTME: FTP and SMTP receivers authenticate uploads and hand them to an IngestSink
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use gl_core::Result;
+use std::net::SocketAddr;
+
+pub mod ftp;
+pub mod smtp;
+
+pub use ftp::{FtpIngestConfig, FtpServer};
+pub use smtp::{SmtpIngestConfig, SmtpServer};
+
+/// Stream an authenticated upload belongs to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IngestTarget {
+    pub stream_id: String,
+    pub user_id: String,
+}
+
+/// A complete image received from a camera
+#[derive(Debug, Clone)]
+pub struct Upload {
+    pub target: IngestTarget,
+    /// Protocol the upload arrived over ("ftp", "smtp")
+    pub protocol: &'static str,
+    /// Name the camera gave the file, including any directory it changed into
+    pub filename: String,
+    pub data: Bytes,
+    /// Text that came with the image, such as an email's subject and body
+    pub text: Option<String>,
+    pub remote_addr: Option<SocketAddr>,
+}
+
+/// Destination for pushed snapshots
+///
+/// Implemented by the web crate so uploads land in the same storage and analysis
+/// flow as polled captures.
+#[async_trait]
+pub trait IngestSink: Send + Sync {
+    /// Resolve upload credentials to a stream, or `None` if they match no stream
+    async fn authenticate(
+        &self,
+        protocol: &str,
+        username: &str,
+        password: &str,
+    ) -> Result<Option<IngestTarget>>;
+
+    /// Resolve the local part of an email recipient to a stream
+    async fn resolve_mailbox(&self, mailbox: &str) -> Result<Option<IngestTarget>>;
+
+    /// Store and analyze an upload, returning the stored snapshot ID
+    async fn ingest(&self, upload: Upload) -> Result<String>;
+}
+
+/// Whether `data` looks like a JPEG image
+pub fn is_jpeg(data: &[u8]) -> bool {
+    data.starts_with(&[0xFF, 0xD8, 0xFF])
+}
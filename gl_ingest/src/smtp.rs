@@ -0,0 +1,491 @@
+//! ABOUTME: Minimal embedded SMTP receiver for alarm panels and NVRs that email snapshots
+//! ABOUTME: Maps `<token>@domain` recipients to streams and JPEG attachments to uploads
+
+use crate::{is_jpeg, IngestSink, IngestTarget, Upload};
+use gl_core::Result;
+use mail_parser::{MessageParser, MimeHeaders};
+use metrics::counter;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::timeout;
+use tracing::{debug, info, warn};
+
+/// Longest command line accepted from a client
+const MAX_COMMAND_LINE: u64 = 1024;
+
+/// Largest chunk read from the DATA section at once
+const MAX_DATA_CHUNK: u64 = 1024 * 1024;
+
+/// Recipients accepted per message
+const MAX_RECIPIENTS: usize = 10;
+
+/// SMTP ingest server settings
+#[derive(Debug, Clone)]
+pub struct SmtpIngestConfig {
+    pub bind_addr: SocketAddr,
+    /// Recipient domain accepted, e.g. "glimpser.local"; anything else is refused as relaying
+    pub domain: String,
+    pub max_message_bytes: usize,
+    /// Connections idle for this long are closed
+    pub idle_timeout: Duration,
+}
+
+impl Default for SmtpIngestConfig {
+    fn default() -> Self {
+        Self {
+            bind_addr: SocketAddr::from(([0, 0, 0, 0], 2525)),
+            domain: "glimpser.local".to_string(),
+            max_message_bytes: 25 * 1024 * 1024,
+            idle_timeout: Duration::from_secs(300),
+        }
+    }
+}
+
+/// SMTP server that turns emailed JPEG attachments into snapshots
+pub struct SmtpServer {
+    listener: TcpListener,
+    config: Arc<SmtpIngestConfig>,
+    sink: Arc<dyn IngestSink>,
+}
+
+impl SmtpServer {
+    /// Bind the SMTP port
+    pub async fn bind(config: SmtpIngestConfig, sink: Arc<dyn IngestSink>) -> Result<Self> {
+        let listener = TcpListener::bind(config.bind_addr).await?;
+        Ok(Self {
+            listener,
+            config: Arc::new(config),
+            sink,
+        })
+    }
+
+    /// Address the server is listening on
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Accept connections until the task is dropped
+    pub async fn run(self) -> Result<()> {
+        info!(addr = %self.local_addr()?, domain = %self.config.domain, "SMTP ingest server listening");
+
+        loop {
+            let (socket, peer) = match self.listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    // Usually file descriptor exhaustion; back off instead of spinning
+                    warn!(error = %e, "Failed to accept SMTP connection");
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    continue;
+                }
+            };
+
+            let session = Session::new(socket, peer, self.config.clone(), self.sink.clone());
+            tokio::spawn(async move {
+                if let Err(e) = session.run().await {
+                    debug!(peer = %peer, error = %e, "SMTP session ended with error");
+                }
+            });
+        }
+    }
+}
+
+/// Whether the session keeps reading commands
+enum Flow {
+    Continue,
+    Close,
+}
+
+/// One SMTP connection
+struct Session {
+    reader: BufReader<OwnedReadHalf>,
+    writer: OwnedWriteHalf,
+    peer: SocketAddr,
+    config: Arc<SmtpIngestConfig>,
+    sink: Arc<dyn IngestSink>,
+    mail_from: Option<String>,
+    recipients: Vec<IngestTarget>,
+}
+
+impl Session {
+    fn new(
+        socket: TcpStream,
+        peer: SocketAddr,
+        config: Arc<SmtpIngestConfig>,
+        sink: Arc<dyn IngestSink>,
+    ) -> Self {
+        let (read_half, writer) = socket.into_split();
+        Self {
+            reader: BufReader::new(read_half),
+            writer,
+            peer,
+            config,
+            sink,
+            mail_from: None,
+            recipients: Vec::new(),
+        }
+    }
+
+    async fn run(mut self) -> io::Result<()> {
+        debug!(peer = %self.peer, "SMTP connection opened");
+        let greeting = format!("{} Glimpser SMTP ingest ready", self.config.domain);
+        self.reply(220, &greeting).await?;
+
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let read = timeout(
+                self.config.idle_timeout,
+                (&mut self.reader)
+                    .take(MAX_COMMAND_LINE)
+                    .read_line(&mut line),
+            )
+            .await;
+
+            match read {
+                Err(_) => {
+                    self.reply(421, "4.4.2 Idle timeout, closing connection")
+                        .await?;
+                    break;
+                }
+                Ok(Err(e)) => return Err(e),
+                Ok(Ok(0)) => break,
+                Ok(Ok(_)) if !line.ends_with('\n') => {
+                    self.reply(500, "5.5.2 Line too long").await?;
+                    break;
+                }
+                Ok(Ok(_)) => {}
+            }
+
+            let command = line.trim_end_matches(['\r', '\n']);
+            let (verb, arg) = command.split_once(' ').unwrap_or((command, ""));
+            if let Flow::Close = self.handle(&verb.to_ascii_uppercase(), arg.trim()).await? {
+                break;
+            }
+        }
+
+        debug!(peer = %self.peer, "SMTP connection closed");
+        Ok(())
+    }
+
+    async fn handle(&mut self, verb: &str, arg: &str) -> io::Result<Flow> {
+        match verb {
+            "EHLO" => {
+                self.reset();
+                let reply = format!(
+                    "250-{}\r\n250-SIZE {}\r\n250 8BITMIME\r\n",
+                    self.config.domain, self.config.max_message_bytes
+                );
+                self.writer.write_all(reply.as_bytes()).await?;
+            }
+            "HELO" => {
+                self.reset();
+                let domain = self.config.domain.clone();
+                self.reply(250, &domain).await?;
+            }
+            "MAIL" => self.mail_from(arg).await?,
+            "RCPT" => self.rcpt_to(arg).await?,
+            "DATA" => self.data().await?,
+            "RSET" => {
+                self.reset();
+                self.reply(250, "2.0.0 OK").await?;
+            }
+            "NOOP" => self.reply(250, "2.0.0 OK").await?,
+            "VRFY" => self.reply(252, "2.1.5 Cannot verify user").await?,
+            "QUIT" => {
+                self.reply(221, "2.0.0 Bye").await?;
+                return Ok(Flow::Close);
+            }
+            _ => self.reply(502, "5.5.1 Command not implemented").await?,
+        }
+        Ok(Flow::Continue)
+    }
+
+    fn reset(&mut self) {
+        self.mail_from = None;
+        self.recipients.clear();
+    }
+
+    async fn mail_from(&mut self, arg: &str) -> io::Result<()> {
+        let Some(rest) = strip_prefix_ignore_case(arg, "FROM:") else {
+            return self.reply(501, "5.5.4 Syntax: MAIL FROM:<address>").await;
+        };
+        let (address, params) = split_path(rest);
+
+        let declared_size = params
+            .split_whitespace()
+            .find_map(|param| strip_prefix_ignore_case(param, "SIZE="))
+            .and_then(|size| size.parse::<usize>().ok());
+        if declared_size.is_some_and(|size| size > self.config.max_message_bytes) {
+            return self.reply(552, "5.3.4 Message size exceeds limit").await;
+        }
+
+        self.reset();
+        self.mail_from = Some(address.to_string());
+        self.reply(250, "2.1.0 OK").await
+    }
+
+    async fn rcpt_to(&mut self, arg: &str) -> io::Result<()> {
+        if self.mail_from.is_none() {
+            return self.reply(503, "5.5.1 Need MAIL command first").await;
+        }
+        let Some(rest) = strip_prefix_ignore_case(arg, "TO:") else {
+            return self.reply(501, "5.5.4 Syntax: RCPT TO:<address>").await;
+        };
+        if self.recipients.len() >= MAX_RECIPIENTS {
+            return self.reply(452, "4.5.3 Too many recipients").await;
+        }
+
+        let (address, _) = split_path(rest);
+        let Some((mailbox, domain)) = address.rsplit_once('@') else {
+            return self.reply(550, "5.1.3 Invalid recipient address").await;
+        };
+        if !domain.eq_ignore_ascii_case(&self.config.domain) {
+            return self.reply(550, "5.7.1 Relaying denied").await;
+        }
+
+        match self.sink.resolve_mailbox(mailbox).await {
+            Ok(Some(target)) => {
+                if !self.recipients.contains(&target) {
+                    self.recipients.push(target);
+                }
+                self.reply(250, "2.1.5 OK").await
+            }
+            Ok(None) => {
+                counter!("smtp_ingest_rejected_recipients_total").increment(1);
+                debug!(peer = %self.peer, mailbox = %mailbox, "Unknown SMTP ingest mailbox");
+                self.reply(550, "5.1.1 Mailbox unavailable").await
+            }
+            Err(e) => {
+                warn!(peer = %self.peer, error = %e, "SMTP ingest mailbox lookup failed");
+                self.reply(451, "4.3.0 Local error in processing").await
+            }
+        }
+    }
+
+    async fn data(&mut self) -> io::Result<()> {
+        if self.recipients.is_empty() {
+            return self.reply(554, "5.5.1 No valid recipients").await;
+        }
+        self.reply(354, "End data with <CR><LF>.<CR><LF>").await?;
+
+        let Some(message) = self.read_message().await? else {
+            self.reset();
+            counter!("smtp_ingest_failures_total").increment(1);
+            return self.reply(552, "5.3.4 Message size exceeds limit").await;
+        };
+
+        let recipients = std::mem::take(&mut self.recipients);
+        self.mail_from = None;
+
+        let Some(email) = parse_email(&message) else {
+            counter!("smtp_ingest_failures_total").increment(1);
+            return self.reply(554, "5.6.0 Malformed message").await;
+        };
+        if email.images.is_empty() {
+            warn!(peer = %self.peer, subject = ?email.text, "Email has no JPEG attachment");
+            return self
+                .reply(250, "2.0.0 Accepted, no JPEG attachment found")
+                .await;
+        }
+
+        for target in recipients {
+            for (index, (filename, data)) in email.images.iter().enumerate() {
+                let upload = Upload {
+                    target: target.clone(),
+                    protocol: "smtp",
+                    filename: filename.clone(),
+                    data: data.clone().into(),
+                    // Only the first image carries the text so one email is one message event
+                    text: if index == 0 { email.text.clone() } else { None },
+                    remote_addr: Some(self.peer),
+                };
+                if let Err(e) = self.sink.ingest(upload).await {
+                    counter!("smtp_ingest_failures_total").increment(1);
+                    warn!(
+                        peer = %self.peer,
+                        stream_id = %target.stream_id,
+                        error = %e,
+                        "Failed to ingest emailed snapshot"
+                    );
+                    return self.reply(451, "4.3.0 Local error in processing").await;
+                }
+                counter!("smtp_ingest_uploads_total").increment(1);
+            }
+        }
+
+        self.reply(250, "2.0.0 Message accepted").await
+    }
+
+    /// Read the DATA section, undoing dot-stuffing; `None` if it exceeds the size limit
+    async fn read_message(&mut self) -> io::Result<Option<Vec<u8>>> {
+        let limit = self.config.max_message_bytes;
+        let mut message = Vec::new();
+        let mut oversized = false;
+        let mut line = Vec::new();
+        let mut at_line_start = true;
+
+        loop {
+            line.clear();
+            let read = timeout(
+                self.config.idle_timeout,
+                (&mut self.reader)
+                    .take(MAX_DATA_CHUNK)
+                    .read_until(b'\n', &mut line),
+            )
+            .await
+            .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "DATA timed out"))??;
+            if read == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "connection closed during DATA",
+                ));
+            }
+
+            let complete = line.ends_with(b"\n");
+            let mut chunk = &line[..];
+            if at_line_start {
+                if complete && (chunk == b".\r\n" || chunk == b".\n") {
+                    break;
+                }
+                if chunk.starts_with(b".") {
+                    chunk = &chunk[1..];
+                }
+            }
+            at_line_start = complete;
+
+            if !oversized {
+                if message.len() + chunk.len() > limit {
+                    oversized = true;
+                    message = Vec::new();
+                } else {
+                    message.extend_from_slice(chunk);
+                }
+            }
+        }
+
+        Ok((!oversized).then_some(message))
+    }
+
+    async fn reply(&mut self, code: u16, message: &str) -> io::Result<()> {
+        self.writer
+            .write_all(format!("{} {}\r\n", code, message).as_bytes())
+            .await
+    }
+}
+
+/// Text and JPEG images pulled out of an email
+struct ParsedEmail {
+    /// Subject and plain-text body joined by a blank line
+    text: Option<String>,
+    /// (file name, bytes) of each JPEG attachment or inline image
+    images: Vec<(String, Vec<u8>)>,
+}
+
+fn parse_email(raw: &[u8]) -> Option<ParsedEmail> {
+    let message = MessageParser::default().parse(raw)?;
+
+    let subject = message.subject().map(str::trim).unwrap_or_default();
+    // Normalize line endings so text rules don't have to care about CRLF
+    let body = message
+        .body_text(0)
+        .unwrap_or_default()
+        .replace("\r\n", "\n");
+    let text = [subject, body.trim()]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    let images = message
+        .attachments()
+        .filter(|part| is_jpeg(part.contents()))
+        .enumerate()
+        .map(|(index, part)| {
+            let filename = part
+                .attachment_name()
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("attachment-{}.jpg", index + 1));
+            (filename, part.contents().to_vec())
+        })
+        .collect();
+
+    Some(ParsedEmail {
+        text: (!text.is_empty()).then_some(text),
+        images,
+    })
+}
+
+/// Split `<address> PARAMS` into the bare address and the parameters
+fn split_path(arg: &str) -> (&str, &str) {
+    let arg = arg.trim_start();
+    match arg.strip_prefix('<').and_then(|rest| rest.split_once('>')) {
+        Some((address, params)) => (address.trim(), params.trim()),
+        None => arg.split_once(' ').unwrap_or((arg, "")),
+    }
+}
+
+fn strip_prefix_ignore_case<'a>(value: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = value.get(..prefix.len())?;
+    head.eq_ignore_ascii_case(prefix)
+        .then(|| &value[prefix.len()..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_path() {
+        assert_eq!(
+            split_path("<cam@glimpser.local> SIZE=1200"),
+            ("cam@glimpser.local", "SIZE=1200")
+        );
+        assert_eq!(split_path(" <>"), ("", ""));
+        assert_eq!(split_path("cam@glimpser.local"), ("cam@glimpser.local", ""));
+    }
+
+    #[test]
+    fn test_parse_email_extracts_text_and_jpegs() {
+        let raw = concat!(
+            "From: panel@example.com\r\n",
+            "To: token@glimpser.local\r\n",
+            "Subject: Alarm zone 3\r\n",
+            "MIME-Version: 1.0\r\n",
+            "Content-Type: multipart/mixed; boundary=\"b1\"\r\n",
+            "\r\n",
+            "--b1\r\n",
+            "Content-Type: text/plain\r\n",
+            "\r\n",
+            "Intrusion detected\r\n",
+            "--b1\r\n",
+            "Content-Type: image/jpeg; name=\"cam1.jpg\"\r\n",
+            "Content-Transfer-Encoding: base64\r\n",
+            "Content-Disposition: attachment; filename=\"cam1.jpg\"\r\n",
+            "\r\n",
+            "/9j/4AAQ/9k=\r\n",
+            "--b1\r\n",
+            "Content-Type: application/octet-stream; name=\"log.txt\"\r\n",
+            "Content-Disposition: attachment; filename=\"log.txt\"\r\n",
+            "\r\n",
+            "not an image\r\n",
+            "--b1--\r\n",
+        );
+
+        let email = parse_email(raw.as_bytes()).unwrap();
+        assert_eq!(
+            email.text.as_deref(),
+            Some("Alarm zone 3\n\nIntrusion detected")
+        );
+        assert_eq!(email.images.len(), 1);
+        assert_eq!(email.images[0].0, "cam1.jpg");
+        assert_eq!(
+            email.images[0].1,
+            [0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10, 0xFF, 0xD9]
+        );
+    }
+}
@@ -0,0 +1,227 @@
+//! ABOUTME: End-to-end tests for the FTP ingest server using a plain TCP FTP client
+//! ABOUTME: Covers login, passive and active uploads, and rejected transfers
+
+use async_trait::async_trait;
+use gl_core::Result;
+use gl_ingest::{FtpIngestConfig, FtpServer, IngestSink, IngestTarget, Upload};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpListener, TcpStream};
+
+const JPEG: &[u8] = &[
+    0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10, b'J', b'F', b'I', b'F', 0xFF, 0xD9,
+];
+
+/// Sink with one camera account that records what it receives
+#[derive(Default)]
+struct RecordingSink {
+    uploads: Mutex<Vec<Upload>>,
+}
+
+#[async_trait]
+impl IngestSink for RecordingSink {
+    async fn authenticate(
+        &self,
+        protocol: &str,
+        username: &str,
+        password: &str,
+    ) -> Result<Option<IngestTarget>> {
+        assert_eq!(protocol, "ftp");
+        Ok(
+            (username == "trailcam" && password == "s3cret").then(|| IngestTarget {
+                stream_id: "stream-1".to_string(),
+                user_id: "user-1".to_string(),
+            }),
+        )
+    }
+
+    async fn resolve_mailbox(&self, _mailbox: &str) -> Result<Option<IngestTarget>> {
+        Ok(None)
+    }
+
+    async fn ingest(&self, upload: Upload) -> Result<String> {
+        let mut uploads = self.uploads.lock().unwrap();
+        uploads.push(upload);
+        Ok(format!("snapshot-{}", uploads.len()))
+    }
+}
+
+async fn start_server(config: FtpIngestConfig) -> (SocketAddr, Arc<RecordingSink>) {
+    let sink = Arc::new(RecordingSink::default());
+    let server = FtpServer::bind(
+        FtpIngestConfig {
+            bind_addr: "127.0.0.1:0".parse().unwrap(),
+            ..config
+        },
+        sink.clone(),
+    )
+    .await
+    .unwrap();
+    let addr = server.local_addr().unwrap();
+    tokio::spawn(server.run());
+    (addr, sink)
+}
+
+/// Just enough of an FTP client to drive the server
+struct Client {
+    reader: BufReader<OwnedReadHalf>,
+    writer: OwnedWriteHalf,
+}
+
+impl Client {
+    async fn connect(addr: SocketAddr) -> Self {
+        let (read_half, writer) = TcpStream::connect(addr).await.unwrap().into_split();
+        let mut client = Self {
+            reader: BufReader::new(read_half),
+            writer,
+        };
+        assert_eq!(client.response().await.0, 220);
+        client
+    }
+
+    /// Read one reply, skipping the lines of a multi-line reply
+    async fn response(&mut self) -> (u16, String) {
+        loop {
+            let mut line = String::new();
+            if self.reader.read_line(&mut line).await.unwrap() == 0 {
+                return (0, String::new());
+            }
+            if line.as_bytes().get(3) == Some(&b' ') {
+                let code = line[..3].parse().unwrap();
+                return (code, line[4..].trim_end().to_string());
+            }
+        }
+    }
+
+    async fn command(&mut self, command: &str) -> (u16, String) {
+        self.writer
+            .write_all(format!("{}\r\n", command).as_bytes())
+            .await
+            .unwrap();
+        self.response().await
+    }
+
+    async fn login(&mut self, username: &str, password: &str) -> u16 {
+        assert_eq!(self.command(&format!("USER {}", username)).await.0, 331);
+        self.command(&format!("PASS {}", password)).await.0
+    }
+
+    /// Upload over a PASV data connection, returning the final reply code
+    async fn upload(&mut self, name: &str, data: &[u8]) -> u16 {
+        let (code, message) = self.command("PASV").await;
+        assert_eq!(code, 227);
+        let numbers: Vec<u16> = message
+            .split(['(', ')'])
+            .nth(1)
+            .unwrap()
+            .split(',')
+            .map(|n| n.parse().unwrap())
+            .collect();
+        let port = numbers[4] << 8 | numbers[5];
+
+        let mut data_stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        assert_eq!(self.command(&format!("STOR {}", name)).await.0, 150);
+        data_stream.write_all(data).await.unwrap();
+        data_stream.shutdown().await.unwrap();
+        drop(data_stream);
+        self.response().await.0
+    }
+}
+
+#[tokio::test]
+async fn test_passive_upload_reaches_sink() {
+    let (addr, sink) = start_server(FtpIngestConfig::default()).await;
+    let mut client = Client::connect(addr).await;
+
+    assert_eq!(client.login("trailcam", "s3cret").await, 230);
+    assert_eq!(client.command("TYPE I").await.0, 200);
+    assert_eq!(client.command("MKD 2024-06-01").await.0, 257);
+    assert_eq!(client.command("CWD 2024-06-01").await.0, 250);
+    assert_eq!(client.upload("IMG_0001.JPG", JPEG).await, 226);
+
+    // Extended passive mode
+    let (code, message) = client.command("EPSV").await;
+    assert_eq!(code, 229);
+    let port: u16 = message.split('|').nth(3).unwrap().parse().unwrap();
+    let mut data_stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    assert_eq!(client.command("STOR /IMG_0002.JPG").await.0, 150);
+    data_stream.write_all(JPEG).await.unwrap();
+    drop(data_stream);
+    assert_eq!(client.response().await.0, 226);
+
+    assert_eq!(client.command("QUIT").await.0, 221);
+
+    let uploads = sink.uploads.lock().unwrap();
+    assert_eq!(uploads.len(), 2);
+    assert_eq!(uploads[0].filename, "/2024-06-01/IMG_0001.JPG");
+    assert_eq!(uploads[0].target.stream_id, "stream-1");
+    assert_eq!(uploads[0].protocol, "ftp");
+    assert_eq!(&uploads[0].data[..], JPEG);
+    assert_eq!(uploads[1].filename, "/IMG_0002.JPG");
+}
+
+#[tokio::test]
+async fn test_active_mode_upload() {
+    let (addr, sink) = start_server(FtpIngestConfig::default()).await;
+    let mut client = Client::connect(addr).await;
+    assert_eq!(client.login("trailcam", "s3cret").await, 230);
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let (code, _) = client
+        .command(&format!("PORT 127,0,0,1,{},{}", port >> 8, port & 0xff))
+        .await;
+    assert_eq!(code, 200);
+
+    // Connecting back anywhere but the client is refused
+    assert_eq!(client.command("EPRT |1|10.1.2.3|2000|").await.0, 504);
+
+    assert_eq!(client.command("STOR active.jpg").await.0, 150);
+    let (mut data_stream, _) = listener.accept().await.unwrap();
+    data_stream.write_all(JPEG).await.unwrap();
+    drop(data_stream);
+    assert_eq!(client.response().await.0, 226);
+
+    assert_eq!(sink.uploads.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_login_is_required_and_limited() {
+    let (addr, sink) = start_server(FtpIngestConfig::default()).await;
+    let mut client = Client::connect(addr).await;
+
+    assert_eq!(client.command("PASV").await.0, 530);
+    assert_eq!(client.command("STOR sneaky.jpg").await.0, 530);
+    assert_eq!(client.command("PASS s3cret").await.0, 503);
+
+    for _ in 0..3 {
+        assert_eq!(client.login("trailcam", "wrong").await, 530);
+    }
+    // The server hangs up after repeated failures
+    assert_eq!(client.response().await.0, 0);
+    assert!(sink.uploads.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_rejected_uploads() {
+    let (addr, sink) = start_server(FtpIngestConfig {
+        max_upload_bytes: 64,
+        ..Default::default()
+    })
+    .await;
+    let mut client = Client::connect(addr).await;
+    assert_eq!(client.login("trailcam", "s3cret").await, 230);
+
+    assert_eq!(client.command("STOR no-data-channel.jpg").await.0, 425);
+    assert_eq!(client.upload("notes.txt", b"not an image").await, 550);
+
+    let mut oversized = JPEG.to_vec();
+    oversized.resize(65, 0);
+    assert_eq!(client.upload("big.jpg", &oversized).await, 552);
+
+    // The session is still usable afterwards
+    assert_eq!(client.upload("ok.jpg", JPEG).await, 226);
+    assert_eq!(sink.uploads.lock().unwrap().len(), 1);
+}
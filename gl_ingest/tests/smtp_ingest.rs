@@ -0,0 +1,230 @@
+//! ABOUTME: End-to-end tests for the SMTP ingest server using a plain TCP SMTP client
+//! ABOUTME: Covers mailbox routing, attachment extraction, and refused relaying
+
+use async_trait::async_trait;
+use gl_core::Result;
+use gl_ingest::{IngestSink, IngestTarget, SmtpIngestConfig, SmtpServer, Upload};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::TcpStream;
+
+const TOKEN: &str = "k3x9q2m7v8w4p1z6";
+
+/// Sink with one stream mailbox that records what it receives
+#[derive(Default)]
+struct RecordingSink {
+    uploads: Mutex<Vec<Upload>>,
+}
+
+#[async_trait]
+impl IngestSink for RecordingSink {
+    async fn authenticate(
+        &self,
+        _protocol: &str,
+        _username: &str,
+        _password: &str,
+    ) -> Result<Option<IngestTarget>> {
+        Ok(None)
+    }
+
+    async fn resolve_mailbox(&self, mailbox: &str) -> Result<Option<IngestTarget>> {
+        Ok(mailbox.eq_ignore_ascii_case(TOKEN).then(|| IngestTarget {
+            stream_id: "stream-1".to_string(),
+            user_id: "user-1".to_string(),
+        }))
+    }
+
+    async fn ingest(&self, upload: Upload) -> Result<String> {
+        let mut uploads = self.uploads.lock().unwrap();
+        uploads.push(upload);
+        Ok(format!("snapshot-{}", uploads.len()))
+    }
+}
+
+async fn start_server(config: SmtpIngestConfig) -> (SocketAddr, Arc<RecordingSink>) {
+    let sink = Arc::new(RecordingSink::default());
+    let server = SmtpServer::bind(
+        SmtpIngestConfig {
+            bind_addr: "127.0.0.1:0".parse().unwrap(),
+            ..config
+        },
+        sink.clone(),
+    )
+    .await
+    .unwrap();
+    let addr = server.local_addr().unwrap();
+    tokio::spawn(server.run());
+    (addr, sink)
+}
+
+/// Just enough of an SMTP client to drive the server
+struct Client {
+    reader: BufReader<OwnedReadHalf>,
+    writer: OwnedWriteHalf,
+}
+
+impl Client {
+    async fn connect(addr: SocketAddr) -> Self {
+        let (read_half, writer) = TcpStream::connect(addr).await.unwrap().into_split();
+        let mut client = Self {
+            reader: BufReader::new(read_half),
+            writer,
+        };
+        assert_eq!(client.response().await, 220);
+        client
+    }
+
+    /// Read one reply code, skipping the lines of a multi-line reply
+    async fn response(&mut self) -> u16 {
+        loop {
+            let mut line = String::new();
+            if self.reader.read_line(&mut line).await.unwrap() == 0 {
+                return 0;
+            }
+            if line.as_bytes().get(3) == Some(&b' ') {
+                return line[..3].parse().unwrap();
+            }
+        }
+    }
+
+    async fn command(&mut self, command: &str) -> u16 {
+        self.writer
+            .write_all(format!("{}\r\n", command).as_bytes())
+            .await
+            .unwrap();
+        self.response().await
+    }
+
+    /// Send a message body after DATA, returning the final reply code
+    async fn data(&mut self, message: &str) -> u16 {
+        assert_eq!(self.command("DATA").await, 354);
+        self.writer.write_all(message.as_bytes()).await.unwrap();
+        self.command(".").await
+    }
+}
+
+fn alarm_email() -> String {
+    [
+        "From: nvr@example.com",
+        &format!("To: {}@glimpser.local", TOKEN),
+        "Subject: Motion on Driveway",
+        "MIME-Version: 1.0",
+        "Content-Type: multipart/mixed; boundary=\"frontier\"",
+        "",
+        "--frontier",
+        "Content-Type: text/plain",
+        "",
+        "Person detected at 02:14",
+        ".leading dot survives unstuffing",
+        "--frontier",
+        "Content-Type: image/jpeg",
+        "Content-Transfer-Encoding: base64",
+        "Content-Disposition: attachment; filename=\"driveway.jpg\"",
+        "",
+        "/9j/4AAQ/9k=",
+        "--frontier--",
+        "",
+    ]
+    .iter()
+    // Lines starting with a dot are stuffed by the client
+    .map(|line| match line.strip_prefix('.') {
+        Some(rest) => format!("..{}\r\n", rest),
+        None => format!("{}\r\n", line),
+    })
+    .collect()
+}
+
+#[tokio::test]
+async fn test_emailed_jpeg_reaches_sink() {
+    let (addr, sink) = start_server(SmtpIngestConfig::default()).await;
+    let mut client = Client::connect(addr).await;
+
+    assert_eq!(client.command("EHLO nvr.example.com").await, 250);
+    assert_eq!(client.command("MAIL FROM:<nvr@example.com>").await, 250);
+    assert_eq!(
+        client
+            .command(&format!(
+                "RCPT TO:<{}@Glimpser.Local>",
+                TOKEN.to_uppercase()
+            ))
+            .await,
+        250
+    );
+    assert_eq!(client.data(&alarm_email()).await, 250);
+    assert_eq!(client.command("QUIT").await, 221);
+
+    let uploads = sink.uploads.lock().unwrap();
+    assert_eq!(uploads.len(), 1);
+    assert_eq!(uploads[0].target.stream_id, "stream-1");
+    assert_eq!(uploads[0].protocol, "smtp");
+    assert_eq!(uploads[0].filename, "driveway.jpg");
+    assert_eq!(
+        &uploads[0].data[..],
+        [0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10, 0xFF, 0xD9]
+    );
+    assert_eq!(
+        uploads[0].text.as_deref(),
+        Some("Motion on Driveway\n\nPerson detected at 02:14\n.leading dot survives unstuffing")
+    );
+}
+
+#[tokio::test]
+async fn test_unknown_and_foreign_recipients_are_refused() {
+    let (addr, sink) = start_server(SmtpIngestConfig::default()).await;
+    let mut client = Client::connect(addr).await;
+
+    assert_eq!(client.command("HELO nvr").await, 250);
+    assert_eq!(
+        client
+            .command(&format!("RCPT TO:<{}@glimpser.local>", TOKEN))
+            .await,
+        503
+    );
+    assert_eq!(client.command("MAIL FROM:<nvr@example.com>").await, 250);
+    assert_eq!(client.command("RCPT TO:<nobody@glimpser.local>").await, 550);
+    assert_eq!(
+        client
+            .command(&format!("RCPT TO:<{}@example.com>", TOKEN))
+            .await,
+        550
+    );
+    assert_eq!(client.command("DATA").await, 554);
+    assert_eq!(client.command("AUTH PLAIN").await, 502);
+
+    assert!(sink.uploads.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_oversized_messages_are_rejected() {
+    let (addr, sink) = start_server(SmtpIngestConfig {
+        max_message_bytes: 256,
+        ..Default::default()
+    })
+    .await;
+    let mut client = Client::connect(addr).await;
+
+    assert_eq!(client.command("EHLO nvr").await, 250);
+    assert_eq!(
+        client
+            .command("MAIL FROM:<nvr@example.com> SIZE=4096")
+            .await,
+        552
+    );
+
+    // Undeclared size is caught while reading DATA
+    assert_eq!(client.command("MAIL FROM:<nvr@example.com>").await, 250);
+    assert_eq!(
+        client
+            .command(&format!("RCPT TO:<{}@glimpser.local>", TOKEN))
+            .await,
+        250
+    );
+    assert_eq!(client.data(&alarm_email()).await, 552);
+
+    // The session is still usable afterwards
+    assert_eq!(client.command("RSET").await, 250);
+    assert_eq!(client.command("NOOP").await, 250);
+    assert!(sink.uploads.lock().unwrap().is_empty());
+}
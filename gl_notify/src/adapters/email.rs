@@ -0,0 +1,209 @@
+//! ABOUTME: Email notification adapter sending plain text and HTML mail over SMTP
+//! ABOUTME: Turns images embedded as data URIs into inline parts so mail clients show them
+
+use async_trait::async_trait;
+use base64::{engine::general_purpose, Engine as _};
+use lettre::{
+    message::{header::ContentType, Attachment, Mailbox, MultiPart, SinglePart},
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use tracing::{debug, error};
+
+use crate::{Notification, NotificationChannel, NotificationError, Notifier, Result};
+
+/// SMTP server settings
+#[derive(Debug, Clone)]
+pub struct SmtpSettings {
+    pub host: String,
+    /// 465 uses implicit TLS, other ports STARTTLS
+    pub port: u16,
+    pub username: String,
+    pub password: String,
+    /// Sender address, e.g. "Glimpser <glimpser@example.com>"
+    pub from: String,
+}
+
+/// Email notification adapter
+pub struct EmailAdapter {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl EmailAdapter {
+    /// Create an adapter that sends through an SMTP server
+    pub fn new(settings: SmtpSettings) -> Result<Self> {
+        let from = settings
+            .from
+            .parse()
+            .map_err(|e| NotificationError::SmtpError(format!("Invalid sender address: {}", e)))?;
+        let builder = if settings.port == 465 {
+            AsyncSmtpTransport::<Tokio1Executor>::relay(&settings.host)
+        } else {
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&settings.host)
+        }
+        .map_err(|e| NotificationError::SmtpError(format!("Invalid SMTP server: {}", e)))?;
+        let transport = builder
+            .port(settings.port)
+            .credentials(Credentials::new(settings.username, settings.password))
+            .build();
+
+        Ok(Self { transport, from })
+    }
+
+    /// Build the message for a list of recipients
+    ///
+    /// Notifications with HTML become multipart/alternative with the text body as fallback.
+    pub fn build_message(&self, msg: &Notification, to: &[String]) -> Result<Message> {
+        let mut builder = Message::builder()
+            .from(self.from.clone())
+            .subject(&msg.title);
+        for address in to {
+            let mailbox: Mailbox = address.parse().map_err(|e| {
+                NotificationError::SmtpError(format!("Invalid recipient {}: {}", address, e))
+            })?;
+            builder = builder.to(mailbox);
+        }
+
+        let message = match &msg.html {
+            Some(html) => {
+                let (html, images) = inline_data_images(html);
+                let mut related = MultiPart::related().singlepart(SinglePart::html(html));
+                for (content_id, content_type, data) in images {
+                    related = related
+                        .singlepart(Attachment::new_inline(content_id).body(data, content_type));
+                }
+                builder.multipart(
+                    MultiPart::alternative()
+                        .singlepart(SinglePart::plain(msg.body.clone()))
+                        .multipart(related),
+                )
+            }
+            None => builder.singlepart(SinglePart::plain(msg.body.clone())),
+        };
+        message.map_err(|e| NotificationError::SmtpError(format!("Failed to build email: {}", e)))
+    }
+}
+
+/// Replace `src="data:image/...;base64,..."` images with `cid:` references
+///
+/// Returns the rewritten HTML and the images as (content ID, type, bytes); images that
+/// don't decode are left in place.
+fn inline_data_images(html: &str) -> (String, Vec<(String, ContentType, Vec<u8>)>) {
+    const PREFIX: &str = "src=\"data:";
+    let mut output = String::with_capacity(html.len());
+    let mut images = Vec::new();
+    let mut rest = html;
+
+    while let Some(start) = rest.find(PREFIX) {
+        let uri_start = start + PREFIX.len();
+        let Some(uri_len) = rest[uri_start..].find('"') else {
+            break;
+        };
+        let uri = &rest[uri_start..uri_start + uri_len];
+        let image = uri.split_once(";base64,").and_then(|(mime, data)| {
+            let content_type = ContentType::parse(mime).ok()?;
+            let data = general_purpose::STANDARD.decode(data).ok()?;
+            Some((content_type, data))
+        });
+
+        output.push_str(&rest[..start]);
+        match image {
+            Some((content_type, data)) => {
+                let content_id = format!("image{}", images.len() + 1);
+                output.push_str(&format!("src=\"cid:{}\"", content_id));
+                images.push((content_id, content_type, data));
+            }
+            None => output.push_str(&rest[start..uri_start + uri_len + 1]),
+        }
+        rest = &rest[uri_start + uri_len + 1..];
+    }
+    output.push_str(rest);
+
+    (output, images)
+}
+
+#[async_trait]
+impl Notifier for EmailAdapter {
+    async fn send(&self, msg: &Notification) -> Result<()> {
+        for channel in &msg.channels {
+            let NotificationChannel::Email { to } = channel else {
+                continue;
+            };
+            if to.is_empty() {
+                continue;
+            }
+
+            debug!(notification_id = %msg.id, recipients = to.len(), "Sending email notification");
+            let message = self.build_message(msg, to)?;
+            self.transport.send(message).await.map_err(|e| {
+                error!(notification_id = %msg.id, error = %e, "Failed to send email");
+                NotificationError::SmtpError(e.to_string())
+            })?;
+        }
+
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "email"
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        match self.transport.test_connection().await {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(NotificationError::SmtpError(
+                "SMTP server refused the connection".to_string(),
+            )),
+            Err(e) => Err(NotificationError::SmtpError(e.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NotificationKind;
+
+    #[test]
+    fn test_html_email_inlines_data_images() {
+        let adapter = EmailAdapter::new(SmtpSettings {
+            host: "smtp.example.com".to_string(),
+            port: 587,
+            username: "user".to_string(),
+            password: "secret".to_string(),
+            from: "Glimpser <glimpser@example.com>".to_string(),
+        })
+        .unwrap();
+        let to = vec!["manager@example.com".to_string()];
+        let notification = Notification::new(
+            NotificationKind::Info,
+            "Daily digest".to_string(),
+            "3 events".to_string(),
+            vec![NotificationChannel::Email { to: to.clone() }],
+        )
+        .with_html(
+            "<p>3 events</p><img src=\"data:image/png;base64,iVBORw0KGgo=\">\
+             <img src=\"data:image/png;base64,%%%\">",
+        );
+
+        let message = String::from_utf8(
+            adapter
+                .build_message(&notification, &to)
+                .unwrap()
+                .formatted(),
+        )
+        .unwrap();
+        assert!(message.contains("Subject: Daily digest"));
+        assert!(message.contains("multipart/alternative"));
+        assert!(message.contains("multipart/related"));
+        assert!(message.contains("src=3D\"cid:image1\"") || message.contains("src=\"cid:image1\""));
+        assert!(message.contains("Content-ID: <image1>"));
+        // Undecodable images stay as they were
+        assert!(message.contains("base64,%%%") || message.contains("base64,=25=25=25"));
+
+        assert!(adapter
+            .build_message(&notification, &["not an address".to_string()])
+            .is_err());
+    }
+}
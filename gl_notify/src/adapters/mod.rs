@@ -1,8 +1,12 @@
 //! ABOUTME: Notification adapter implementations for different channels
-//! ABOUTME: Contains Webhook and Pushover notification adapters
+//! ABOUTME: Contains Webhook, Pushover, Web Push and email notification adapters
 
+pub mod email;
 pub mod pushover;
 pub mod webhook;
+pub mod webpush;
 
+pub use email::{EmailAdapter, SmtpSettings};
 pub use pushover::PushoverAdapter;
 pub use webhook::WebhookAdapter;
+pub use webpush::{VapidKeys, WebPushAdapter};
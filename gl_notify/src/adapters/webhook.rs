@@ -1,16 +1,96 @@
 //! ABOUTME: Webhook notification adapter for HTTP POST notifications
-//! ABOUTME: Sends JSON payloads to configured webhook endpoints
+//! ABOUTME: Sends HMAC-signed JSON payloads in glimpser, Slack, Teams, Discord or templated shapes
 
 use async_trait::async_trait;
+use hmac::{Hmac, Mac};
 use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
 use tracing::{debug, error};
 
-use crate::{Notification, NotificationChannel, Notifier, Result};
+use crate::{
+    templates::Template, Notification, NotificationChannel, NotificationError, NotificationKind,
+    Notifier, Result,
+};
+
+/// Header carrying the `t=<unix seconds>,v1=<hex HMAC-SHA256>` request signature
+pub const SIGNATURE_HEADER: &str = "X-Glimpser-Signature";
+
+/// Default window within which a signature timestamp is accepted
+pub const DEFAULT_SIGNATURE_TOLERANCE_SECONDS: i64 = 300;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Shape of the JSON body sent to a webhook
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookPayloadFormat {
+    /// Glimpser's own notification JSON
+    #[default]
+    Glimpser,
+    /// Slack incoming webhook (`text`)
+    Slack,
+    /// Microsoft Teams connector (`MessageCard`)
+    Teams,
+    /// Discord webhook with a single embed
+    Discord,
+    /// JSON rendered from a notification template; `event` holds the template context
+    Template(String),
+}
+
+/// Sign a webhook body
+///
+/// The MAC covers `"{timestamp}.{body}"` so a captured request cannot be replayed
+/// outside the receiver's tolerance window.
+pub fn sign_payload(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(format!("{}.", timestamp).as_bytes());
+    mac.update(body);
+    format!(
+        "t={},v1={}",
+        timestamp,
+        hex::encode(mac.finalize().into_bytes())
+    )
+}
+
+/// Verify a signature header produced by [`sign_payload`]
+pub fn verify_signature(
+    secret: &str,
+    header: &str,
+    body: &[u8],
+    now: i64,
+    tolerance_seconds: i64,
+) -> bool {
+    let mut timestamp = None;
+    let mut signatures = Vec::new();
+    for part in header.split(',') {
+        match part.trim().split_once('=') {
+            Some(("t", value)) => timestamp = value.parse::<i64>().ok(),
+            Some(("v1", value)) => signatures.extend(hex::decode(value).ok()),
+            _ => {}
+        }
+    }
+
+    let Some(timestamp) = timestamp else {
+        return false;
+    };
+    if (now - timestamp).abs() > tolerance_seconds {
+        return false;
+    }
+
+    signatures.iter().any(|signature| {
+        let mut mac =
+            HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+        mac.update(format!("{}.", timestamp).as_bytes());
+        mac.update(body);
+        mac.verify_slice(signature).is_ok()
+    })
+}
 
 /// Webhook notification adapter
 #[derive(Debug)]
 pub struct WebhookAdapter {
-    #[allow(dead_code)]
     client: Client,
 }
 
@@ -26,6 +106,84 @@ impl WebhookAdapter {
     pub fn with_client(client: Client) -> Self {
         Self { client }
     }
+
+    /// Build the JSON body for a notification in the given format
+    pub fn build_payload(
+        msg: &Notification,
+        format: &WebhookPayloadFormat,
+        timestamp: chrono::DateTime<chrono::Utc>,
+    ) -> Result<serde_json::Value> {
+        let payload = match format {
+            WebhookPayloadFormat::Glimpser => {
+                let mut payload = serde_json::json!({
+                    "id": msg.id.to_string(),
+                    "kind": msg.kind,
+                    "title": msg.title,
+                    "body": msg.body,
+                    "timestamp": timestamp.to_rfc3339(),
+                    "attachments": msg.attachments,
+                    "metadata": msg.metadata
+                });
+                if let Some(html) = &msg.html {
+                    payload["html"] = serde_json::Value::String(html.clone());
+                }
+                payload
+            }
+            WebhookPayloadFormat::Slack => serde_json::json!({
+                "text": format!("*{}*\n{}", msg.title, msg.body),
+            }),
+            WebhookPayloadFormat::Teams => serde_json::json!({
+                "@type": "MessageCard",
+                "@context": "https://schema.org/extensions",
+                "summary": msg.title,
+                "themeColor": format!("{:06X}", kind_color(&msg.kind)),
+                "title": msg.title,
+                // Teams markdown needs blank lines for line breaks
+                "text": msg.body.replace('\n', "\n\n"),
+            }),
+            WebhookPayloadFormat::Discord => serde_json::json!({
+                "username": "Glimpser",
+                "embeds": [{
+                    "title": msg.title,
+                    "description": msg.body,
+                    "color": kind_color(&msg.kind),
+                    "timestamp": timestamp.to_rfc3339(),
+                }],
+            }),
+            WebhookPayloadFormat::Template(source) => {
+                let data = serde_json::json!({
+                    "id": msg.id.to_string(),
+                    "kind": msg.kind,
+                    "title": msg.title,
+                    "body": msg.body,
+                    "timestamp": timestamp.to_rfc3339(),
+                    "attachments": msg.attachments,
+                    "metadata": msg.metadata,
+                    "event": msg.template_context,
+                    "html": msg.html,
+                });
+                let rendered = Template::parse(source)?.render(&data, None);
+                serde_json::from_str(&rendered).map_err(|e| {
+                    NotificationError::TemplateError(format!(
+                        "Webhook payload template did not produce valid JSON: {}",
+                        e
+                    ))
+                })?
+            }
+        };
+
+        Ok(payload)
+    }
+}
+
+/// Accent colour used by chat payload formats
+fn kind_color(kind: &NotificationKind) -> u32 {
+    match kind {
+        NotificationKind::Info => 0x3498DB,
+        NotificationKind::Warning => 0xF39C12,
+        NotificationKind::Error => 0xE74C3C,
+        NotificationKind::Success => 0x2ECC71,
+    }
 }
 
 impl Default for WebhookAdapter {
@@ -42,6 +200,8 @@ impl Notifier for WebhookAdapter {
                 url,
                 headers,
                 method,
+                secret,
+                payload_format,
             } = channel
             {
                 debug!(
@@ -51,15 +211,9 @@ impl Notifier for WebhookAdapter {
                 );
 
                 // Prepare webhook payload
-                let payload = serde_json::json!({
-                    "id": msg.id.to_string(),
-                    "kind": msg.kind,
-                    "title": msg.title,
-                    "body": msg.body,
-                    "timestamp": chrono::Utc::now().to_rfc3339(),
-                    "attachments": msg.attachments,
-                    "metadata": msg.metadata
-                });
+                let now = chrono::Utc::now();
+                let payload =
+                    Self::build_payload(msg, &payload_format.clone().unwrap_or_default(), now)?;
 
                 // Determine HTTP method (default to POST)
                 let http_method = method.as_deref().unwrap_or("POST");
@@ -81,13 +235,27 @@ impl Notifier for WebhookAdapter {
                 }
 
                 // Add content-type header for POST/PUT/PATCH requests
-                if matches!(
+                let body = if matches!(
                     http_method.to_uppercase().as_str(),
                     "POST" | "PUT" | "PATCH"
                 ) {
+                    serde_json::to_vec(&payload)?
+                } else {
+                    Vec::new()
+                };
+
+                // Sign exactly the bytes that go on the wire
+                if let Some(secret) = secret {
+                    request_builder = request_builder.header(
+                        SIGNATURE_HEADER,
+                        sign_payload(secret, now.timestamp(), &body),
+                    );
+                }
+
+                if !body.is_empty() {
                     request_builder = request_builder
                         .header("Content-Type", "application/json")
-                        .json(&payload);
+                        .body(body);
                 }
 
                 // Send the request
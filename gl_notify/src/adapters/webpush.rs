@@ -0,0 +1,463 @@
+//! ABOUTME: Web Push notification adapter delivering to browser push subscriptions
+//! ABOUTME: Signs requests with VAPID (RFC 8292) and encrypts payloads with aes128gcm (RFC 8291)
+
+use async_trait::async_trait;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use gl_db::{PushSubscription, PushSubscriptionRepository, StoredVapidKeys};
+use reqwest::{Client, StatusCode};
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+use ring::{aead, agreement, hkdf};
+use tracing::{debug, warn};
+use url::Url;
+
+use crate::{Notification, NotificationChannel, NotificationError, Notifier, Result};
+
+/// Default time push services hold an undelivered message
+pub const DEFAULT_TTL_SECONDS: u32 = 86400;
+
+/// Largest plaintext that fits in a single 4096 byte aes128gcm record
+pub const MAX_PAYLOAD_BYTES: usize = 4096 - 86 - 16 - 1;
+
+/// Record size advertised in the aes128gcm header
+const RECORD_SIZE: u32 = 4096;
+
+/// VAPID tokens are valid for at most 24 hours; stay well inside that
+const JWT_LIFETIME_SECONDS: i64 = 12 * 3600;
+
+/// Server identity used to sign push requests
+pub struct VapidKeys {
+    key_pair: EcdsaKeyPair,
+    pkcs8: Vec<u8>,
+}
+
+impl std::fmt::Debug for VapidKeys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VapidKeys")
+            .field("public_key", &self.public_key_base64())
+            .finish()
+    }
+}
+
+impl VapidKeys {
+    /// Generate a new P-256 key pair
+    pub fn generate() -> Result<Self> {
+        let rng = SystemRandom::new();
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng)
+            .map_err(|_| webpush_error("Failed to generate VAPID key pair"))?;
+        Self::from_pkcs8(pkcs8.as_ref())
+    }
+
+    /// Load a key pair from a PKCS#8 document
+    pub fn from_pkcs8(pkcs8: &[u8]) -> Result<Self> {
+        let key_pair = EcdsaKeyPair::from_pkcs8(
+            &ECDSA_P256_SHA256_FIXED_SIGNING,
+            pkcs8,
+            &SystemRandom::new(),
+        )
+        .map_err(|e| webpush_error(&format!("Invalid VAPID private key: {}", e)))?;
+
+        Ok(Self {
+            key_pair,
+            pkcs8: pkcs8.to_vec(),
+        })
+    }
+
+    /// Load base64url keys as printed by common web-push tooling
+    ///
+    /// A 32 byte private key is the raw scalar and needs the matching public key;
+    /// anything longer is treated as a PKCS#8 document.
+    pub fn from_base64(private_key: &str, public_key: Option<&str>) -> Result<Self> {
+        let private = decode_base64(private_key)?;
+        if private.len() != 32 {
+            return Self::from_pkcs8(&private);
+        }
+
+        let public = decode_base64(public_key.ok_or_else(|| {
+            webpush_error("A raw VAPID private key requires the public key as well")
+        })?)?;
+        let key_pair = EcdsaKeyPair::from_private_key_and_public_key(
+            &ECDSA_P256_SHA256_FIXED_SIGNING,
+            &private,
+            &public,
+            &SystemRandom::new(),
+        )
+        .map_err(|e| webpush_error(&format!("Invalid VAPID key pair: {}", e)))?;
+
+        // ring cannot export PKCS#8, so a raw pair can only be reloaded from config
+        Ok(Self {
+            key_pair,
+            pkcs8: Vec::new(),
+        })
+    }
+
+    /// Load the stored key pair, generating and storing one on first use
+    pub async fn load_or_generate(repo: &PushSubscriptionRepository) -> Result<Self> {
+        if let Some(stored) = repo.get_vapid_keys().await.map_err(db_error)? {
+            return Self::from_base64(&stored.private_key, Some(&stored.public_key));
+        }
+
+        let generated = Self::generate()?;
+        // Another instance may have stored a pair in the meantime; the stored one wins
+        let stored = repo
+            .store_vapid_keys(StoredVapidKeys {
+                private_key: generated.private_key_base64(),
+                public_key: generated.public_key_base64(),
+            })
+            .await
+            .map_err(db_error)?;
+        Self::from_base64(&stored.private_key, Some(&stored.public_key))
+    }
+
+    /// Uncompressed public key point, the browser's `applicationServerKey`
+    pub fn public_key(&self) -> &[u8] {
+        self.key_pair.public_key().as_ref()
+    }
+
+    /// base64url public key
+    pub fn public_key_base64(&self) -> String {
+        URL_SAFE_NO_PAD.encode(self.public_key())
+    }
+
+    /// base64url PKCS#8 private key, empty for pairs loaded from a raw scalar
+    pub fn private_key_base64(&self) -> String {
+        URL_SAFE_NO_PAD.encode(&self.pkcs8)
+    }
+
+    /// Build the `Authorization` header value for a push endpoint
+    pub fn authorization(&self, endpoint: &Url, subject: &str, now: i64) -> Result<String> {
+        let header = URL_SAFE_NO_PAD.encode(br#"{"typ":"JWT","alg":"ES256"}"#);
+        let claims = URL_SAFE_NO_PAD.encode(serde_json::to_vec(&serde_json::json!({
+            "aud": endpoint.origin().ascii_serialization(),
+            "exp": now + JWT_LIFETIME_SECONDS,
+            "sub": subject,
+        }))?);
+        let signing_input = format!("{}.{}", header, claims);
+        let signature = self
+            .key_pair
+            .sign(&SystemRandom::new(), signing_input.as_bytes())
+            .map_err(|_| webpush_error("Failed to sign VAPID token"))?;
+
+        Ok(format!(
+            "vapid t={}.{}, k={}",
+            signing_input,
+            URL_SAFE_NO_PAD.encode(signature.as_ref()),
+            self.public_key_base64()
+        ))
+    }
+}
+
+/// Encrypt a payload for a subscription as a single aes128gcm record (RFC 8291)
+pub fn encrypt_payload(payload: &[u8], p256dh: &[u8], auth_secret: &[u8]) -> Result<Vec<u8>> {
+    if payload.len() > MAX_PAYLOAD_BYTES {
+        return Err(webpush_error(&format!(
+            "Payload of {} bytes exceeds the {} byte limit",
+            payload.len(),
+            MAX_PAYLOAD_BYTES
+        )));
+    }
+
+    let rng = SystemRandom::new();
+    let local_key = agreement::EphemeralPrivateKey::generate(&agreement::ECDH_P256, &rng)
+        .map_err(|_| webpush_error("Failed to generate ECDH key"))?;
+    let local_public = local_key
+        .compute_public_key()
+        .map_err(|_| webpush_error("Failed to compute ECDH public key"))?;
+
+    let peer = agreement::UnparsedPublicKey::new(&agreement::ECDH_P256, p256dh);
+    let shared_secret = agreement::agree_ephemeral(local_key, &peer, |secret| secret.to_vec())
+        .map_err(|_| webpush_error("Invalid subscription p256dh key"))?;
+
+    let mut salt = [0u8; 16];
+    rng.fill(&mut salt)
+        .map_err(|_| webpush_error("Failed to generate salt"))?;
+
+    // IKM = HKDF(auth, ecdh, "WebPush: info\0" || ua_public || as_public)
+    let mut key_info = b"WebPush: info\0".to_vec();
+    key_info.extend_from_slice(p256dh);
+    key_info.extend_from_slice(local_public.as_ref());
+    let ikm = hkdf_expand(auth_secret, &shared_secret, &key_info, 32)?;
+
+    let cek = hkdf_expand(&salt, &ikm, b"Content-Encoding: aes128gcm\0", 16)?;
+    let nonce = hkdf_expand(&salt, &ikm, b"Content-Encoding: nonce\0", 12)?;
+
+    let key = aead::UnboundKey::new(&aead::AES_128_GCM, &cek)
+        .map_err(|_| webpush_error("Failed to create content key"))?;
+    let nonce = aead::Nonce::try_assume_unique_for_key(&nonce)
+        .map_err(|_| webpush_error("Failed to create nonce"))?;
+
+    // A single, final record is terminated by the 0x02 padding delimiter
+    let mut record = payload.to_vec();
+    record.push(0x02);
+    aead::LessSafeKey::new(key)
+        .seal_in_place_append_tag(nonce, aead::Aad::empty(), &mut record)
+        .map_err(|_| webpush_error("Failed to encrypt payload"))?;
+
+    let mut body = Vec::with_capacity(86 + record.len());
+    body.extend_from_slice(&salt);
+    body.extend_from_slice(&RECORD_SIZE.to_be_bytes());
+    body.push(local_public.as_ref().len() as u8);
+    body.extend_from_slice(local_public.as_ref());
+    body.extend_from_slice(&record);
+    Ok(body)
+}
+
+/// HKDF-SHA256 extract and expand
+fn hkdf_expand(salt: &[u8], ikm: &[u8], info: &[u8], len: usize) -> Result<Vec<u8>> {
+    struct Len(usize);
+    impl hkdf::KeyType for Len {
+        fn len(&self) -> usize {
+            self.0
+        }
+    }
+
+    let mut out = vec![0u8; len];
+    hkdf::Salt::new(hkdf::HKDF_SHA256, salt)
+        .extract(ikm)
+        .expand(&[info], Len(len))
+        .and_then(|okm| okm.fill(&mut out))
+        .map_err(|_| webpush_error("HKDF expansion failed"))?;
+    Ok(out)
+}
+
+/// Result of pushing to a single subscription
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PushOutcome {
+    /// The push service accepted the message
+    Delivered,
+    /// The subscription expired or was revoked and has been removed
+    Gone,
+}
+
+/// Web Push notification adapter
+pub struct WebPushAdapter {
+    client: Client,
+    keys: VapidKeys,
+    subject: String,
+    ttl_seconds: u32,
+    subscriptions: Option<PushSubscriptionRepository>,
+}
+
+impl WebPushAdapter {
+    /// Create a new Web Push adapter
+    pub fn new(keys: VapidKeys, subject: String) -> Self {
+        Self {
+            client: Client::new(),
+            keys,
+            subject,
+            ttl_seconds: DEFAULT_TTL_SECONDS,
+            subscriptions: None,
+        }
+    }
+
+    /// Resolve `WebPush` channels and clean up expired subscriptions through this repository
+    pub fn with_subscriptions(mut self, repo: PushSubscriptionRepository) -> Self {
+        self.subscriptions = Some(repo);
+        self
+    }
+
+    /// Set how long push services keep undelivered messages
+    pub fn with_ttl(mut self, ttl_seconds: u32) -> Self {
+        self.ttl_seconds = ttl_seconds;
+        self
+    }
+
+    /// Use a custom HTTP client
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// base64url VAPID public key for the browser's `applicationServerKey`
+    pub fn public_key(&self) -> String {
+        self.keys.public_key_base64()
+    }
+
+    /// Build the JSON payload the service worker displays
+    ///
+    /// The body is shortened if needed so the encoded payload fits in one record.
+    pub fn build_payload(msg: &Notification) -> Result<Vec<u8>> {
+        let context = msg.template_context.as_ref();
+        let image = context
+            .and_then(|c| c.snapshot_url.clone())
+            .or_else(|| msg.attachments.first().map(|url| url.to_string()));
+        let tag = context
+            .map(|c| format!("{}:{}", c.source_id, c.event_type))
+            .unwrap_or_else(|| msg.id.to_string());
+
+        let mut body = msg.body.clone();
+        loop {
+            let payload = serde_json::to_vec(&serde_json::json!({
+                "id": msg.id.to_string(),
+                "kind": msg.kind,
+                "title": msg.title,
+                "body": body,
+                "image": image,
+                "url": context.map(|c| format!("/streams/{}", c.source_id)),
+                "tag": tag,
+                "timestamp": context
+                    .map(|c| c.timestamp)
+                    .unwrap_or_else(chrono::Utc::now)
+                    .timestamp_millis(),
+                "data": msg.metadata,
+            }))?;
+
+            if payload.len() <= MAX_PAYLOAD_BYTES {
+                return Ok(payload);
+            }
+            if body.is_empty() {
+                return Err(webpush_error(
+                    "Notification is too large for a push payload",
+                ));
+            }
+
+            let overflow = payload.len() - MAX_PAYLOAD_BYTES;
+            let keep = body.chars().count().saturating_sub(overflow.max(16) + 1);
+            body = body.chars().take(keep).collect::<String>() + "…";
+        }
+    }
+
+    /// Push an encoded payload to one subscription
+    ///
+    /// A 404 or 410 response means the subscription is no longer valid; it is
+    /// removed from the repository and reported as [`PushOutcome::Gone`].
+    pub async fn push(
+        &self,
+        subscription: &PushSubscription,
+        payload: &[u8],
+    ) -> Result<PushOutcome> {
+        let endpoint = Url::parse(&subscription.endpoint)
+            .map_err(|e| webpush_error(&format!("Invalid push endpoint: {}", e)))?;
+        let body = encrypt_payload(
+            payload,
+            &decode_base64(&subscription.p256dh)?,
+            &decode_base64(&subscription.auth)?,
+        )?;
+        let authorization =
+            self.keys
+                .authorization(&endpoint, &self.subject, chrono::Utc::now().timestamp())?;
+
+        let response = self
+            .client
+            .post(endpoint.as_str())
+            .header("Authorization", authorization)
+            .header("TTL", self.ttl_seconds.to_string())
+            .header("Urgency", "high")
+            .header("Content-Encoding", "aes128gcm")
+            .header("Content-Type", "application/octet-stream")
+            .body(body)
+            .send()
+            .await?;
+
+        let status = response.status();
+        if status.is_success() {
+            debug!(endpoint = %endpoint, status = %status, "Web push delivered");
+            if let Some(repo) = &self.subscriptions {
+                if let Err(e) = repo.mark_success(&subscription.endpoint).await {
+                    warn!(error = %e, "Failed to record push delivery");
+                }
+            }
+            return Ok(PushOutcome::Delivered);
+        }
+
+        if status == StatusCode::GONE || status == StatusCode::NOT_FOUND {
+            debug!(endpoint = %endpoint, status = %status, "Removing expired push subscription");
+            if let Some(repo) = &self.subscriptions {
+                repo.delete_by_endpoint(&subscription.endpoint)
+                    .await
+                    .map_err(db_error)?;
+            }
+            return Ok(PushOutcome::Gone);
+        }
+
+        let error_body = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+        Err(webpush_error(&format!(
+            "HTTP {} from {}: {}",
+            status,
+            endpoint.origin().ascii_serialization(),
+            error_body
+        )))
+    }
+
+    /// Subscriptions targeted by a channel
+    async fn resolve(&self, user_id: Option<&str>) -> Result<Vec<PushSubscription>> {
+        let repo = self
+            .subscriptions
+            .as_ref()
+            .ok_or_else(|| webpush_error("No subscription store configured"))?;
+        match user_id {
+            Some(user_id) => repo.list_by_user(user_id).await,
+            None => repo.list_all().await,
+        }
+        .map_err(db_error)
+    }
+}
+
+#[async_trait]
+impl Notifier for WebPushAdapter {
+    /// Deliver to every targeted subscription
+    ///
+    /// Fails only if no subscription accepted the message and at least one
+    /// push failed, so one broken device does not trigger retries for all.
+    async fn send(&self, msg: &Notification) -> Result<()> {
+        for channel in &msg.channels {
+            let NotificationChannel::WebPush { user_id } = channel else {
+                continue;
+            };
+
+            let subscriptions = self.resolve(user_id.as_deref()).await?;
+            if subscriptions.is_empty() {
+                debug!(notification_id = %msg.id, "No push subscriptions to deliver to");
+                continue;
+            }
+
+            let payload = Self::build_payload(msg)?;
+            let mut delivered = 0;
+            let mut errors = Vec::new();
+            for subscription in &subscriptions {
+                match self.push(subscription, &payload).await {
+                    Ok(PushOutcome::Delivered) => delivered += 1,
+                    Ok(PushOutcome::Gone) => {}
+                    Err(e) => {
+                        warn!(
+                            notification_id = %msg.id,
+                            subscription_id = %subscription.id,
+                            error = %e,
+                            "Web push delivery failed"
+                        );
+                        errors.push(e.to_string());
+                    }
+                }
+            }
+
+            if delivered == 0 && !errors.is_empty() {
+                return Err(webpush_error(&errors.join(", ")));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "webpush"
+    }
+}
+
+fn decode_base64(value: &str) -> Result<Vec<u8>> {
+    // Browsers hand out unpadded base64url, but accept padded input too
+    URL_SAFE_NO_PAD
+        .decode(value.trim().trim_end_matches('='))
+        .map_err(|e| webpush_error(&format!("Invalid base64url key: {}", e)))
+}
+
+fn webpush_error(message: &str) -> NotificationError {
+    NotificationError::WebPushError(message.to_string())
+}
+
+fn db_error(e: gl_core::Error) -> NotificationError {
+    NotificationError::WebPushError(format!("Subscription store error: {}", e))
+}
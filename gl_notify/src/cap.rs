@@ -194,6 +194,8 @@ mod tests {
             url: "https://example.com/webhook".parse().unwrap(),
             headers: None,
             method: None,
+            secret: None,
+            payload_format: None,
         }];
 
         let notification =
@@ -246,6 +248,8 @@ mod tests {
             url: "https://example.com/webhook".parse().unwrap(),
             headers: None,
             method: None,
+            secret: None,
+            payload_format: None,
         }];
 
         let notification = alert.to_notification_with_body(channels).unwrap();
@@ -265,6 +269,8 @@ mod tests {
             url: "https://example.com/webhook".parse().unwrap(),
             headers: None,
             method: None,
+            secret: None,
+            payload_format: None,
         }];
 
         let notification = alert.to_notification_with_body(channels).unwrap();
@@ -2,8 +2,12 @@
 //! ABOUTME: Handles the end-to-end flow from events to channel delivery with retry logic
 
 use crate::{
-    adapters::{pushover::PushoverAdapter, webhook::WebhookAdapter},
-    NotificationManager,
+    adapters::{
+        pushover::PushoverAdapter,
+        webhook::{WebhookAdapter, WebhookPayloadFormat},
+    },
+    Notification, NotificationChannel, NotificationKind, NotificationManager,
+    NotificationTemplates, Notifier, TemplateContext,
 };
 
 use gl_core::Result;
@@ -68,6 +72,16 @@ impl NotificationDispatcher {
         }
     }
 
+    /// Register an adapter for channels that are delivered through the manager
+    pub fn register_adapter(&mut self, name: String, adapter: std::sync::Arc<dyn Notifier>) {
+        self.notification_manager.register_adapter(name, adapter);
+    }
+
+    /// Replace the message templates used for deliveries
+    pub fn set_templates(&mut self, templates: NotificationTemplates) {
+        self.notification_manager.set_templates(templates);
+    }
+
     /// Start the dispatcher background task
     pub async fn start(&self) -> Result<()> {
         info!(
@@ -320,16 +334,19 @@ impl NotificationDispatcher {
 
     /// Send a notification via the appropriate adapter
     async fn send_notification(
-        _notification_manager: &NotificationManager,
+        notification_manager: &NotificationManager,
         delivery: &NotificationDelivery,
         event: &AnalysisEvent,
     ) -> Result<Option<String>> {
         match delivery.channel_type.as_str() {
             "pushover" => {
-                Self::send_pushover_notification(_notification_manager, delivery, event).await
+                Self::send_pushover_notification(notification_manager, delivery, event).await
             }
             "webhook" => {
-                Self::send_webhook_notification(_notification_manager, delivery, event).await
+                Self::send_webhook_notification(notification_manager, delivery, event).await
+            }
+            "webpush" => {
+                Self::send_webpush_notification(notification_manager, delivery, event).await
             }
             _ => {
                 warn!(
@@ -346,7 +363,7 @@ impl NotificationDispatcher {
 
     /// Send Pushover notification
     async fn send_pushover_notification(
-        _notification_manager: &NotificationManager,
+        notification_manager: &NotificationManager,
         delivery: &NotificationDelivery,
         event: &AnalysisEvent,
     ) -> Result<Option<String>> {
@@ -358,11 +375,7 @@ impl NotificationDispatcher {
                 gl_core::Error::Validation("Missing user_key in Pushover config".to_string())
             })?;
 
-        let title = format!("{} Alert", event.event_type);
-        let message = format!(
-            "{}\nSeverity: {}\nConfidence: {:.2}\nSource: {}",
-            event.description, event.severity, event.confidence, event.source_id
-        );
+        let (title, message) = Self::render_message(notification_manager, delivery, event);
 
         // Create a resilient Pushover adapter (simplified for this example)
         let _adapter = PushoverAdapter::with_resilience("mock_app_token".to_string());
@@ -382,7 +395,7 @@ impl NotificationDispatcher {
 
     /// Send webhook notification
     async fn send_webhook_notification(
-        _notification_manager: &NotificationManager,
+        notification_manager: &NotificationManager,
         delivery: &NotificationDelivery,
         event: &AnalysisEvent,
     ) -> Result<Option<String>> {
@@ -394,30 +407,235 @@ impl NotificationDispatcher {
                 gl_core::Error::Validation("Missing url in webhook config".to_string())
             })?;
 
-        // Create webhook payload
-        let payload = serde_json::json!({
-            "event_id": event.id,
-            "event_type": event.event_type,
-            "severity": event.severity,
-            "confidence": event.confidence,
-            "description": event.description,
-            "source_id": event.source_id,
-            "created_at": event.created_at,
-            "metadata": event.metadata
-        });
-
-        // Create a basic webhook adapter
-        let _adapter = WebhookAdapter::new();
-        // In real implementation, this would make the HTTP request
-        // adapter.send_webhook(webhook_url, &payload).await
+        let url: url::Url = webhook_url.parse().map_err(|e| {
+            gl_core::Error::Validation(format!("Invalid webhook url '{}': {}", webhook_url, e))
+        })?;
+        let headers = delivery
+            .channel_config
+            .get("headers")
+            .map(|v| serde_json::from_value::<HashMap<String, String>>(v.clone()))
+            .transpose()
+            .map_err(|e| {
+                gl_core::Error::Validation(format!("Invalid headers in webhook config: {}", e))
+            })?;
+        let payload_format = delivery
+            .channel_config
+            .get("payload_format")
+            .map(|v| serde_json::from_value::<WebhookPayloadFormat>(v.clone()))
+            .transpose()
+            .map_err(|e| {
+                gl_core::Error::Validation(format!(
+                    "Invalid payload_format in webhook config: {}",
+                    e
+                ))
+            })?;
+        let config_str = |key: &str| {
+            delivery
+                .channel_config
+                .get(key)
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+        };
 
-        debug!(
-            webhook_url,
-            payload = ?payload,
-            "Would send webhook notification"
+        let notification = Self::build_notification(
+            notification_manager,
+            delivery,
+            event,
+            NotificationChannel::Webhook {
+                url,
+                headers,
+                method: config_str("method"),
+                secret: config_str("secret"),
+                payload_format,
+            },
         );
 
-        Ok(Some("mock_webhook_id".to_string()))
+        WebhookAdapter::new()
+            .send(&notification)
+            .await
+            .map_err(|e| gl_core::Error::External(e.to_string()))?;
+
+        debug!(webhook_url, delivery_id = %delivery.id, "Sent webhook notification");
+
+        Ok(Some(notification.id.to_string()))
+    }
+
+    /// Send Web Push notification through the registered adapter
+    async fn send_webpush_notification(
+        notification_manager: &NotificationManager,
+        delivery: &NotificationDelivery,
+        event: &AnalysisEvent,
+    ) -> Result<Option<String>> {
+        let user_id = delivery
+            .channel_config
+            .get("user_id")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+        let notification = Self::build_notification(
+            notification_manager,
+            delivery,
+            event,
+            NotificationChannel::WebPush { user_id },
+        );
+
+        notification_manager
+            .send(&notification)
+            .await
+            .map_err(|e| gl_core::Error::External(e.to_string()))?;
+
+        debug!(delivery_id = %delivery.id, "Sent web push notification");
+
+        Ok(Some(notification.id.to_string()))
+    }
+
+    /// Build the notification for a delivery, with event metadata and snapshot attachment
+    fn build_notification(
+        notification_manager: &NotificationManager,
+        delivery: &NotificationDelivery,
+        event: &AnalysisEvent,
+        channel: NotificationChannel,
+    ) -> Notification {
+        let (title, message) = Self::render_message(notification_manager, delivery, event);
+        let kind = match event.severity.as_str() {
+            "critical" | "high" => NotificationKind::Error,
+            "medium" => NotificationKind::Warning,
+            _ => NotificationKind::Info,
+        };
+        let context = TemplateContext::from(event);
+
+        let mut notification = Notification::new(kind, title, message, vec![channel])
+            .with_metadata("delivery_id".to_string(), delivery.id.clone())
+            .with_metadata("event_id".to_string(), event.id.clone())
+            .with_metadata("event_type".to_string(), event.event_type.clone())
+            .with_metadata("severity".to_string(), event.severity.clone())
+            .with_metadata("confidence".to_string(), event.confidence.to_string())
+            .with_metadata("source_id".to_string(), event.source_id.clone())
+            .with_metadata("created_at".to_string(), event.created_at.clone());
+        if let Some(snapshot_url) = context
+            .snapshot_url
+            .as_deref()
+            .and_then(|u| url::Url::parse(u).ok())
+        {
+            notification = notification.with_attachment(snapshot_url);
+        }
+        // The manager renders again per channel; keep it on the same template
+        notification.template = Self::template_override(delivery, event).map(|t| t.to_string());
+        notification.template_context = Some(context);
+        notification
+    }
+
+    /// Render title and body for a delivery from the channel's template
+    ///
+    /// A `template` in the channel config takes precedence over a
+    /// `notification_template` set on the event by a rule.
+    fn render_message(
+        notification_manager: &NotificationManager,
+        delivery: &NotificationDelivery,
+        event: &AnalysisEvent,
+    ) -> (String, String) {
+        match notification_manager.templates().render(
+            &delivery.channel_type,
+            Self::template_override(delivery, event),
+            &TemplateContext::from(event),
+        ) {
+            Ok(rendered) => (rendered.title, rendered.body),
+            Err(e) => {
+                warn!(
+                    delivery_id = %delivery.id,
+                    error = %e,
+                    "Failed to render notification template, using plain text"
+                );
+                (
+                    format!("{} Alert", event.event_type),
+                    event.description.clone(),
+                )
+            }
+        }
+    }
+
+    /// Template selected by the channel config or, failing that, by a rule on the event
+    fn template_override<'a>(
+        delivery: &'a NotificationDelivery,
+        event: &'a AnalysisEvent,
+    ) -> Option<&'a str> {
+        delivery
+            .channel_config
+            .get("template")
+            .or_else(|| {
+                event
+                    .metadata
+                    .as_ref()
+                    .and_then(|m| m.get("notification_template"))
+            })
+            .and_then(|v| v.as_str())
+    }
+
+    /// Send a delivery again, recording the attempt as a new delivery
+    ///
+    /// Any delivery can be replayed whatever its status. The original row is left
+    /// untouched and the new one points back to it via `redelivery_of` metadata.
+    pub async fn redeliver(&self, delivery_id: &str) -> Result<NotificationDelivery> {
+        let original = self
+            .delivery_repo
+            .get_by_id(delivery_id)
+            .await?
+            .ok_or_else(|| {
+                gl_core::Error::NotFound(format!("Notification delivery {}", delivery_id))
+            })?;
+        let event = self
+            .analysis_events_repo
+            .get_by_id(&original.analysis_event_id)
+            .await?
+            .ok_or_else(|| {
+                gl_core::Error::NotFound(format!("Analysis event {}", original.analysis_event_id))
+            })?;
+
+        let mut metadata = HashMap::new();
+        metadata.insert("redelivery_of".to_string(), original.id.clone().into());
+        let delivery = self
+            .delivery_repo
+            .create(CreateNotificationDelivery {
+                analysis_event_id: original.analysis_event_id.clone(),
+                channel_type: original.channel_type.clone(),
+                channel_config: original.channel_config.clone(),
+                max_attempts: Some(1),
+                scheduled_at: None,
+                metadata: Some(metadata),
+            })
+            .await?;
+
+        let update =
+            match Self::send_notification(&self.notification_manager, &delivery, &event).await {
+                Ok(external_id) => UpdateDeliveryStatus {
+                    status: DeliveryStatus::Sent,
+                    external_id,
+                    error_message: None,
+                    metadata: None,
+                },
+                Err(e) => UpdateDeliveryStatus {
+                    status: DeliveryStatus::Failed,
+                    external_id: None,
+                    error_message: Some(e.to_string()),
+                    metadata: None,
+                },
+            };
+        self.delivery_repo
+            .update_status(&delivery.id, update)
+            .await?;
+
+        info!(
+            delivery_id = %delivery.id,
+            original_delivery_id = %original.id,
+            channel_type = %delivery.channel_type,
+            "Redelivered notification"
+        );
+
+        self.delivery_repo
+            .get_by_id(&delivery.id)
+            .await?
+            .ok_or_else(|| {
+                gl_core::Error::NotFound(format!("Notification delivery {}", delivery.id))
+            })
     }
 
     /// Check if event severity meets channel threshold
@@ -15,11 +15,13 @@ pub mod cap;
 pub mod circuit_breaker;
 pub mod dispatcher;
 pub mod retry;
+pub mod templates;
 
 pub use cap::{CapNotification, CapNotificationBuilder};
 pub use circuit_breaker::CircuitBreakerWrapper;
 pub use dispatcher::{DispatcherConfig, NotificationChannelConfig, NotificationDispatcher};
 pub use retry::RetryWrapper;
+pub use templates::{MessageTemplate, NotificationTemplates, RenderedMessage, TemplateContext};
 
 /// Result type for notification operations
 pub type Result<T> = std::result::Result<T, NotificationError>;
@@ -45,6 +47,10 @@ pub enum NotificationError {
     CircuitBreakerOpen(String),
     #[error("Retry exhausted for notification: {0}")]
     RetryExhausted(String),
+    #[error("Template error: {0}")]
+    TemplateError(String),
+    #[error("Web push error: {0}")]
+    WebPushError(String),
 }
 
 /// Type of notification
@@ -63,6 +69,12 @@ pub enum NotificationChannel {
         url: Url,
         headers: Option<HashMap<String, String>>,
         method: Option<String>,
+        /// Shared secret for the `X-Glimpser-Signature` header
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        secret: Option<String>,
+        /// Body shape; glimpser JSON when unset
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        payload_format: Option<adapters::webhook::WebhookPayloadFormat>,
     },
     Pushover {
         user_key: String,
@@ -70,6 +82,10 @@ pub enum NotificationChannel {
         priority: Option<i8>,
         sound: Option<String>,
     },
+    /// Browser push subscriptions of one user, or of every user when unset
+    WebPush { user_id: Option<String> },
+    /// Email to one or more addresses through the configured SMTP server
+    Email { to: Vec<String> },
 }
 
 /// Core notification message
@@ -89,6 +105,15 @@ pub struct Notification {
     pub attachments: Vec<Url>,
     /// Optional metadata for adapters
     pub metadata: HashMap<String, String>,
+    /// Event data for rendering channel templates
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template_context: Option<TemplateContext>,
+    /// Named or inline template overriding the channel default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// HTML version of the body for channels that can show it; images may be `data:` URIs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub html: Option<String>,
 }
 
 impl Notification {
@@ -107,6 +132,9 @@ impl Notification {
             channels,
             attachments: Vec::new(),
             metadata: HashMap::new(),
+            template_context: None,
+            template: None,
+            html: None,
         }
     }
 
@@ -121,6 +149,24 @@ impl Notification {
         self.metadata.insert(key, value);
         self
     }
+
+    /// Render title and body per channel from event data
+    pub fn with_template_context(mut self, context: TemplateContext) -> Self {
+        self.template_context = Some(context);
+        self
+    }
+
+    /// Override the channel templates with a named or inline template
+    pub fn with_template(mut self, template: impl Into<String>) -> Self {
+        self.template = Some(template.into());
+        self
+    }
+
+    /// Attach an HTML version of the body
+    pub fn with_html(mut self, html: impl Into<String>) -> Self {
+        self.html = Some(html.into());
+        self
+    }
 }
 
 /// Core trait for notification adapters
@@ -141,6 +187,7 @@ pub trait Notifier: Send + Sync {
 /// Multi-channel notification manager
 pub struct NotificationManager {
     adapters: HashMap<String, Arc<dyn Notifier>>,
+    templates: Arc<NotificationTemplates>,
 }
 
 impl NotificationManager {
@@ -148,6 +195,7 @@ impl NotificationManager {
     pub fn new() -> Self {
         Self {
             adapters: HashMap::new(),
+            templates: Arc::new(NotificationTemplates::new()),
         }
     }
 
@@ -156,20 +204,62 @@ impl NotificationManager {
         self.adapters.insert(name, adapter);
     }
 
+    /// Replace the message templates used for notifications with a template context
+    pub fn set_templates(&mut self, templates: NotificationTemplates) {
+        self.templates = Arc::new(templates);
+    }
+
+    /// Get the message templates
+    pub fn templates(&self) -> &NotificationTemplates {
+        &self.templates
+    }
+
+    /// Render the notification for a channel, if it carries a template context
+    fn render_for_channel(
+        &self,
+        notification: &Notification,
+        channel: &str,
+    ) -> Option<Notification> {
+        let context = notification.template_context.as_ref()?;
+        match self
+            .templates
+            .render(channel, notification.template.as_deref(), context)
+        {
+            Ok(rendered) => {
+                let mut rendered_notification = notification.clone();
+                rendered_notification.title = rendered.title;
+                rendered_notification.body = rendered.body;
+                Some(rendered_notification)
+            }
+            Err(e) => {
+                tracing::warn!(
+                    notification_id = %notification.id,
+                    channel = channel,
+                    error = %e,
+                    "Failed to render notification template, using plain text"
+                );
+                None
+            }
+        }
+    }
+
     /// Send a notification through all applicable adapters
     pub async fn send(&self, notification: &Notification) -> Result<()> {
         let futures = notification.channels.iter().map(|channel| {
             let adapter_name = match channel {
                 NotificationChannel::Webhook { .. } => "webhook",
                 NotificationChannel::Pushover { .. } => "pushover",
+                NotificationChannel::WebPush { .. } => "webpush",
+                NotificationChannel::Email { .. } => "email",
             };
 
             let adapter = self.adapters.get(adapter_name).map(|a| a.as_ref());
+            let rendered = self.render_for_channel(notification, adapter_name);
             let adapter_name = adapter_name.to_string();
             async move {
                 if let Some(adapter) = adapter {
                     adapter
-                        .send(notification)
+                        .send(rendered.as_ref().unwrap_or(notification))
                         .await
                         .map_err(|e| format!("{}: {}", adapter_name, e))
                 } else {
@@ -210,6 +300,7 @@ impl Clone for NotificationManager {
         // Cloning the manager shares adapter instances via Arc
         Self {
             adapters: self.adapters.clone(),
+            templates: self.templates.clone(),
         }
     }
 }
@@ -255,6 +346,8 @@ mod tests {
             url: "https://example.com/webhook".parse().unwrap(),
             headers: None,
             method: None,
+            secret: None,
+            payload_format: None,
         }];
 
         let attachment_url = "https://example.com/file.pdf".parse().unwrap();
@@ -304,4 +397,90 @@ mod tests {
         assert!(cloned.adapters().contains(&"pushover"));
         assert_eq!(cloned.adapters().len(), 1);
     }
+
+    struct RecordingNotifier {
+        sent: std::sync::Mutex<Vec<(String, String)>>,
+    }
+
+    #[async_trait]
+    impl Notifier for RecordingNotifier {
+        async fn send(&self, msg: &Notification) -> Result<()> {
+            self.sent
+                .lock()
+                .unwrap()
+                .push((msg.title.clone(), msg.body.clone()));
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            "recording"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_renders_channel_templates() {
+        let webhook = Arc::new(RecordingNotifier {
+            sent: std::sync::Mutex::new(Vec::new()),
+        });
+        let pushover = Arc::new(RecordingNotifier {
+            sent: std::sync::Mutex::new(Vec::new()),
+        });
+
+        let mut manager = NotificationManager::new();
+        manager.register_adapter("webhook".to_string(), webhook.clone());
+        manager.register_adapter("pushover".to_string(), pushover.clone());
+
+        let mut templates = NotificationTemplates::new();
+        templates
+            .set_channel_template(
+                "webhook",
+                MessageTemplate::new("{{ event_type }}", "{{ stream_name }}"),
+            )
+            .unwrap();
+        manager.set_templates(templates);
+
+        let context = TemplateContext {
+            event_type: "motion".to_string(),
+            stream_name: Some("Garage".to_string()),
+            ..Default::default()
+        };
+        let channels = vec![
+            NotificationChannel::Webhook {
+                url: "https://example.com/webhook".parse().unwrap(),
+                headers: None,
+                method: None,
+                secret: None,
+                payload_format: None,
+            },
+            NotificationChannel::Pushover {
+                user_key: "user".to_string(),
+                device: None,
+                priority: None,
+                sound: None,
+            },
+        ];
+        let notification = Notification::new(
+            NotificationKind::Warning,
+            "Plain".to_string(),
+            "Plain body".to_string(),
+            channels,
+        )
+        .with_template_context(context);
+
+        manager.send(&notification).await.unwrap();
+
+        assert_eq!(
+            webhook.sent.lock().unwrap()[0],
+            ("motion".to_string(), "Garage".to_string())
+        );
+        assert_eq!(pushover.sent.lock().unwrap()[0].0, "Motion on Garage");
+
+        // Notifications without a context keep their plain text
+        let plain = Notification {
+            template_context: None,
+            ..notification
+        };
+        manager.send(&plain).await.unwrap();
+        assert_eq!(webhook.sent.lock().unwrap()[1].0, "Plain");
+    }
 }
@@ -0,0 +1,881 @@
+//! ABOUTME: Notification message templating with per-channel defaults and per-rule overrides
+//! ABOUTME: Renders titles and bodies from event fields, stream info, snapshots and AI output
+
+use crate::{NotificationError, Result};
+use chrono::{DateTime, FixedOffset, Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::warn;
+
+/// Channel key whose template is used when a channel has no template of its own
+pub const DEFAULT_CHANNEL: &str = "default";
+
+/// Default format used by the `time` and `utc` filters
+const DEFAULT_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Data available to notification templates
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TemplateContext {
+    pub event_id: String,
+    pub event_type: String,
+    pub severity: String,
+    pub confidence: f64,
+    pub description: String,
+    pub source_id: String,
+    pub template_id: String,
+    pub timestamp: DateTime<Utc>,
+    /// Human-readable stream name
+    pub stream_name: Option<String>,
+    /// Absolute URL of the stream snapshot
+    pub snapshot_url: Option<String>,
+    /// AI description of the triggering frame
+    pub ai_description: Option<String>,
+    pub suggested_actions: Vec<String>,
+    pub metadata: HashMap<String, serde_json::Value>,
+}
+
+impl From<&gl_db::AnalysisEvent> for TemplateContext {
+    fn from(event: &gl_db::AnalysisEvent) -> Self {
+        let metadata = event.metadata.clone().unwrap_or_default();
+        let metadata_str = |key: &str| {
+            metadata
+                .get(key)
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+        };
+
+        Self {
+            event_id: event.id.clone(),
+            event_type: event.event_type.clone(),
+            severity: event.severity.clone(),
+            confidence: event.confidence,
+            description: event.description.clone(),
+            source_id: event.source_id.clone(),
+            template_id: event.template_id.clone(),
+            timestamp: DateTime::parse_from_rfc3339(&event.created_at)
+                .map(|t| t.with_timezone(&Utc))
+                .unwrap_or_else(|_| Utc::now()),
+            stream_name: metadata_str("stream_name"),
+            snapshot_url: metadata_str("snapshot_url"),
+            ai_description: metadata_str("ai_description"),
+            suggested_actions: event.suggested_actions.clone().unwrap_or_default(),
+            metadata,
+        }
+    }
+}
+
+/// Title and body templates for one message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageTemplate {
+    pub title: String,
+    pub body: String,
+}
+
+impl MessageTemplate {
+    pub fn new(title: impl Into<String>, body: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            body: body.into(),
+        }
+    }
+
+    /// Check that both templates parse
+    pub fn validate(&self) -> Result<()> {
+        Template::parse(&self.title)?;
+        Template::parse(&self.body)?;
+        Ok(())
+    }
+}
+
+/// Rendered notification text
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenderedMessage {
+    pub title: String,
+    pub body: String,
+}
+
+/// Registry of notification templates
+///
+/// Channel templates are keyed by adapter name (`webhook`, `pushover`, ...)
+/// with [`DEFAULT_CHANNEL`] as fallback. Rules override them through
+/// `SetNotificationTemplate`: the value names a registered template, or is
+/// used directly as an inline body template.
+#[derive(Debug, Clone)]
+pub struct NotificationTemplates {
+    channel_templates: HashMap<String, MessageTemplate>,
+    named_templates: HashMap<String, MessageTemplate>,
+    utc_offset: Option<FixedOffset>,
+}
+
+impl Default for NotificationTemplates {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NotificationTemplates {
+    /// Create a registry with the built-in channel templates
+    pub fn new() -> Self {
+        let mut channel_templates = HashMap::new();
+        channel_templates.insert(
+            DEFAULT_CHANNEL.to_string(),
+            MessageTemplate::new(
+                "{{ severity | upper }} Alert: {{ event_type | humanize | title }}",
+                "Source: {{ stream_name | default:source_id }}\n\
+                 Description: {{ description }}\
+                 {% if ai_description %}\nAI: {{ ai_description }}{% endif %}\
+                 {% if snapshot_url %}\nSnapshot: {{ snapshot_url }}{% endif %}\
+                 {% if suggested_actions %}\n\nSuggested Actions:\
+                 {% for action in suggested_actions %}\n{{ loop.index }}. {{ action }}{% endfor %}\
+                 {% endif %}",
+            ),
+        );
+        channel_templates.insert(
+            "pushover".to_string(),
+            MessageTemplate::new(
+                "{{ event_type | humanize | title }} on {{ stream_name | default:source_id }}",
+                "{{ ai_description | default:description }}\n\
+                 {{ severity | upper }} · {{ confidence | percent }} · {{ timestamp | time:\"%H:%M\" }}",
+            ),
+        );
+
+        Self {
+            channel_templates,
+            named_templates: HashMap::new(),
+            utc_offset: None,
+        }
+    }
+
+    /// Set the template for a channel, replacing the built-in one
+    pub fn set_channel_template(
+        &mut self,
+        channel: impl Into<String>,
+        template: MessageTemplate,
+    ) -> Result<()> {
+        template.validate()?;
+        self.channel_templates.insert(channel.into(), template);
+        Ok(())
+    }
+
+    /// Register a named template that rules can select
+    pub fn set_named_template(
+        &mut self,
+        name: impl Into<String>,
+        template: MessageTemplate,
+    ) -> Result<()> {
+        template.validate()?;
+        self.named_templates.insert(name.into(), template);
+        Ok(())
+    }
+
+    /// Render times in a fixed UTC offset instead of the server's local zone
+    pub fn set_utc_offset(&mut self, offset: Option<FixedOffset>) {
+        self.utc_offset = offset;
+    }
+
+    /// Look up the template for a channel, applying a rule override if given
+    pub fn resolve(&self, channel: &str, template_override: Option<&str>) -> MessageTemplate {
+        let channel_template = self
+            .channel_templates
+            .get(channel)
+            .or_else(|| self.channel_templates.get(DEFAULT_CHANNEL))
+            .cloned()
+            .unwrap_or_else(|| MessageTemplate::new("{{ event_type }}", "{{ description }}"));
+
+        match template_override {
+            Some(name) => match self.named_templates.get(name) {
+                Some(named) => named.clone(),
+                None => MessageTemplate {
+                    body: name.to_string(),
+                    ..channel_template
+                },
+            },
+            None => channel_template,
+        }
+    }
+
+    /// Render the message for a channel
+    pub fn render(
+        &self,
+        channel: &str,
+        template_override: Option<&str>,
+        context: &TemplateContext,
+    ) -> Result<RenderedMessage> {
+        self.render_template(&self.resolve(channel, template_override), context)
+    }
+
+    /// Render an arbitrary message template with this registry's time settings
+    pub fn render_template(
+        &self,
+        template: &MessageTemplate,
+        context: &TemplateContext,
+    ) -> Result<RenderedMessage> {
+        let data = serde_json::to_value(context)?;
+        Ok(RenderedMessage {
+            title: Template::parse(&template.title)?.render(&data, self.utc_offset),
+            body: Template::parse(&template.body)?.render(&data, self.utc_offset),
+        })
+    }
+
+    /// Names of the configured channel templates
+    pub fn channels(&self) -> Vec<&str> {
+        self.channel_templates.keys().map(|s| s.as_str()).collect()
+    }
+
+    /// Names of the registered rule templates
+    pub fn named(&self) -> Vec<&str> {
+        self.named_templates.keys().map(|s| s.as_str()).collect()
+    }
+}
+
+/// Parse a UTC offset such as `+02:00`, `-0530` or `UTC`
+pub fn parse_utc_offset(value: &str) -> Option<FixedOffset> {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("utc") || value == "Z" {
+        return FixedOffset::east_opt(0);
+    }
+
+    let (sign, rest) = match value.as_bytes().first()? {
+        b'+' => (1, &value[1..]),
+        b'-' => (-1, &value[1..]),
+        _ => return None,
+    };
+    let digits: String = rest.chars().filter(|c| *c != ':').collect();
+    if digits.len() != 4 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let hours: i32 = digits[..2].parse().ok()?;
+    let minutes: i32 = digits[2..].parse().ok()?;
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Parsed template
+///
+/// Syntax: `{{ path | filter:arg }}` expressions, `{% if path %}` / `{% if not path %}`
+/// with optional `{% else %}` and `{% endif %}`, and `{% for item in path %}` ...
+/// `{% endfor %}` with `loop.index` available. Missing values render as empty, and an
+/// unquoted `default` argument falls back to another field (`default:source_id`).
+#[derive(Debug, Clone)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Text(String),
+    Expr(Expression),
+    If {
+        negate: bool,
+        path: String,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+    For {
+        var: String,
+        path: String,
+        body: Vec<Node>,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Expression {
+    path: String,
+    filters: Vec<Filter>,
+}
+
+#[derive(Debug, Clone)]
+struct Filter {
+    name: String,
+    args: Vec<String>,
+    /// Unquoted first argument that `default` resolves as a context path
+    fallback_path: Option<String>,
+}
+
+enum Token {
+    Text(String),
+    Expr(String),
+    Tag(String),
+}
+
+impl Template {
+    /// Parse a template string
+    pub fn parse(source: &str) -> Result<Self> {
+        let tokens = tokenize(source)?;
+        let mut iter = tokens.into_iter();
+        let (nodes, terminator) = parse_nodes(&mut iter, &[])?;
+        if let Some(tag) = terminator {
+            return Err(template_error(format!("Unexpected '{{% {} %}}'", tag)));
+        }
+        Ok(Self { nodes })
+    }
+
+    /// Render the template against a JSON context
+    pub fn render(&self, data: &serde_json::Value, utc_offset: Option<FixedOffset>) -> String {
+        let mut out = String::new();
+        let mut scope = Scope {
+            root: data,
+            locals: Vec::new(),
+            utc_offset,
+        };
+        render_nodes(&self.nodes, &mut scope, &mut out);
+        out
+    }
+}
+
+fn template_error(message: String) -> NotificationError {
+    NotificationError::TemplateError(message)
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut rest = source;
+
+    while !rest.is_empty() {
+        let next = [rest.find("{{"), rest.find("{%")]
+            .into_iter()
+            .flatten()
+            .min();
+
+        let Some(start) = next else {
+            tokens.push(Token::Text(rest.to_string()));
+            break;
+        };
+
+        if start > 0 {
+            tokens.push(Token::Text(rest[..start].to_string()));
+        }
+
+        let is_expr = rest[start..].starts_with("{{");
+        let close = if is_expr { "}}" } else { "%}" };
+        let inner_start = start + 2;
+        let end = rest[inner_start..]
+            .find(close)
+            .ok_or_else(|| template_error(format!("Unclosed '{}'", &rest[start..start + 2])))?;
+        let inner = rest[inner_start..inner_start + end].trim().to_string();
+
+        tokens.push(if is_expr {
+            Token::Expr(inner)
+        } else {
+            Token::Tag(inner)
+        });
+        rest = &rest[inner_start + end + 2..];
+    }
+
+    Ok(tokens)
+}
+
+fn parse_nodes(
+    tokens: &mut std::vec::IntoIter<Token>,
+    terminators: &[&str],
+) -> Result<(Vec<Node>, Option<String>)> {
+    let mut nodes = Vec::new();
+
+    while let Some(token) = tokens.next() {
+        match token {
+            Token::Text(text) => nodes.push(Node::Text(text)),
+            Token::Expr(expr) => nodes.push(Node::Expr(parse_expression(&expr)?)),
+            Token::Tag(tag) => {
+                let words: Vec<&str> = tag.split_whitespace().collect();
+                match words.as_slice() {
+                    ["if", path] | ["if", "not", path] => {
+                        let negate = words.len() == 3;
+                        let path = path.to_string();
+                        let (then, end) = parse_nodes(tokens, &["else", "endif"])?;
+                        let otherwise = if end.as_deref() == Some("else") {
+                            let (otherwise, end) = parse_nodes(tokens, &["endif"])?;
+                            if end.is_none() {
+                                return Err(template_error("Missing '{% endif %}'".into()));
+                            }
+                            otherwise
+                        } else if end.is_none() {
+                            return Err(template_error("Missing '{% endif %}'".into()));
+                        } else {
+                            Vec::new()
+                        };
+                        nodes.push(Node::If {
+                            negate,
+                            path,
+                            then,
+                            otherwise,
+                        });
+                    }
+                    ["for", var, "in", path] => {
+                        let (body, end) = parse_nodes(tokens, &["endfor"])?;
+                        if end.is_none() {
+                            return Err(template_error("Missing '{% endfor %}'".into()));
+                        }
+                        nodes.push(Node::For {
+                            var: var.to_string(),
+                            path: path.to_string(),
+                            body,
+                        });
+                    }
+                    [word] if terminators.contains(word) => {
+                        return Ok((nodes, Some(word.to_string())));
+                    }
+                    _ => return Err(template_error(format!("Unknown tag '{{% {} %}}'", tag))),
+                }
+            }
+        }
+    }
+
+    Ok((nodes, None))
+}
+
+fn parse_expression(source: &str) -> Result<Expression> {
+    let mut parts = split_outside_quotes(source, '|').into_iter();
+    let path = parts.next().unwrap_or_default().trim().to_string();
+    if path.is_empty() || !is_valid_path(&path) {
+        return Err(template_error(format!("Invalid expression '{}'", source)));
+    }
+
+    let filters = parts
+        .map(|part| {
+            let mut pieces = split_outside_quotes(part.trim(), ':').into_iter();
+            let name = pieces.next().unwrap_or_default().trim().to_string();
+            let raw_args: Vec<&str> = pieces.map(|arg| arg.trim()).collect();
+            let args: Vec<String> = raw_args.iter().map(|arg| unquote(arg)).collect();
+            validate_filter(&name, &args)?;
+            let fallback_path = raw_args
+                .first()
+                .filter(|arg| name == "default" && unquote(arg) == **arg && is_valid_path(arg))
+                .map(|arg| arg.to_string());
+            Ok(Filter {
+                name,
+                args,
+                fallback_path,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Expression { path, filters })
+}
+
+fn is_valid_path(path: &str) -> bool {
+    path.split('.').all(|segment| {
+        !segment.is_empty()
+            && segment
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    })
+}
+
+fn validate_filter(name: &str, args: &[String]) -> Result<()> {
+    let expects_args = match name {
+        "upper" | "lower" | "title" | "humanize" | "json" | "percent" => 0..=0,
+        "default" => 1..=1,
+        "truncate" | "round" => 1..=1,
+        "join" => 0..=1,
+        "time" => 0..=2,
+        "utc" => 0..=1,
+        _ => return Err(template_error(format!("Unknown filter '{}'", name))),
+    };
+
+    if !expects_args.contains(&args.len()) {
+        return Err(template_error(format!(
+            "Wrong number of arguments for filter '{}'",
+            name
+        )));
+    }
+
+    if matches!(name, "truncate" | "round") && args[0].parse::<usize>().is_err() {
+        return Err(template_error(format!(
+            "Filter '{}' expects a number, got '{}'",
+            name, args[0]
+        )));
+    }
+
+    if matches!(name, "time" | "utc") {
+        if let Some(format) = args.first() {
+            let invalid = chrono::format::StrftimeItems::new(format)
+                .any(|item| matches!(item, chrono::format::Item::Error));
+            if invalid {
+                return Err(template_error(format!("Invalid time format '{}'", format)));
+            }
+        }
+        if let Some(offset) = args.get(1) {
+            if parse_utc_offset(offset).is_none() {
+                return Err(template_error(format!("Invalid UTC offset '{}'", offset)));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn split_outside_quotes(source: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quote = None;
+    let mut start = 0;
+
+    for (i, c) in source.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, c) if c == separator => {
+                parts.push(&source[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&source[start..]);
+    parts
+}
+
+fn unquote(value: &str) -> String {
+    let quoted = value.len() >= 2
+        && ((value.starts_with('"') && value.ends_with('"'))
+            || (value.starts_with('\'') && value.ends_with('\'')));
+    if quoted {
+        value[1..value.len() - 1].to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+struct Scope<'a> {
+    root: &'a serde_json::Value,
+    locals: Vec<(String, serde_json::Value)>,
+    utc_offset: Option<FixedOffset>,
+}
+
+impl Scope<'_> {
+    fn lookup(&self, path: &str) -> serde_json::Value {
+        let mut segments = path.split('.');
+        let first = segments.next().unwrap_or_default();
+
+        let base = self
+            .locals
+            .iter()
+            .rev()
+            .find(|(name, _)| name == first)
+            .map(|(_, value)| value)
+            .or_else(|| self.root.get(first));
+
+        let mut current = match base {
+            Some(value) => value,
+            None => return serde_json::Value::Null,
+        };
+        for segment in segments {
+            let next = match current {
+                serde_json::Value::Array(items) => {
+                    segment.parse::<usize>().ok().and_then(|i| items.get(i))
+                }
+                other => other.get(segment),
+            };
+            current = match next {
+                Some(value) => value,
+                None => return serde_json::Value::Null,
+            };
+        }
+        current.clone()
+    }
+}
+
+fn render_nodes(nodes: &[Node], scope: &mut Scope<'_>, out: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Expr(expr) => {
+                let value = expr.filters.iter().fold(scope.lookup(&expr.path), |v, f| {
+                    match &f.fallback_path {
+                        Some(path) if !is_truthy(&v) => scope.lookup(path),
+                        _ => apply_filter(v, f, scope.utc_offset),
+                    }
+                });
+                out.push_str(&value_to_string(&value));
+            }
+            Node::If {
+                negate,
+                path,
+                then,
+                otherwise,
+            } => {
+                if is_truthy(&scope.lookup(path)) != *negate {
+                    render_nodes(then, scope, out);
+                } else {
+                    render_nodes(otherwise, scope, out);
+                }
+            }
+            Node::For { var, path, body } => {
+                let serde_json::Value::Array(items) = scope.lookup(path) else {
+                    continue;
+                };
+                for (i, item) in items.into_iter().enumerate() {
+                    scope.locals.push((var.clone(), item));
+                    scope
+                        .locals
+                        .push(("loop".to_string(), serde_json::json!({ "index": i + 1 })));
+                    render_nodes(body, scope, out);
+                    scope.locals.truncate(scope.locals.len() - 2);
+                }
+            }
+        }
+    }
+}
+
+fn is_truthy(value: &serde_json::Value) -> bool {
+    match value {
+        serde_json::Value::Null => false,
+        serde_json::Value::Bool(b) => *b,
+        serde_json::Value::Number(n) => n.as_f64().is_some_and(|n| n != 0.0),
+        serde_json::Value::String(s) => !s.is_empty(),
+        serde_json::Value::Array(items) => !items.is_empty(),
+        serde_json::Value::Object(map) => !map.is_empty(),
+    }
+}
+
+fn value_to_string(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Array(items) => items
+            .iter()
+            .map(value_to_string)
+            .collect::<Vec<_>>()
+            .join(", "),
+        other => other.to_string(),
+    }
+}
+
+fn apply_filter(
+    value: serde_json::Value,
+    filter: &Filter,
+    utc_offset: Option<FixedOffset>,
+) -> serde_json::Value {
+    let arg = |i: usize| filter.args.get(i).map(|s| s.as_str());
+
+    match filter.name.as_str() {
+        "upper" => value_to_string(&value).to_uppercase().into(),
+        "lower" => value_to_string(&value).to_lowercase().into(),
+        "humanize" => value_to_string(&value).replace(['_', '-'], " ").into(),
+        "title" => value_to_string(&value)
+            .split_whitespace()
+            .map(|word| {
+                let mut chars = word.chars();
+                match chars.next() {
+                    Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+                    None => String::new(),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+            .into(),
+        "default" => {
+            if is_truthy(&value) {
+                value
+            } else {
+                arg(0).unwrap_or_default().into()
+            }
+        }
+        "json" => value.to_string().into(),
+        "truncate" => {
+            let max: usize = arg(0).and_then(|n| n.parse().ok()).unwrap_or(usize::MAX);
+            let text = value_to_string(&value);
+            if text.chars().count() > max {
+                format!("{}…", text.chars().take(max).collect::<String>()).into()
+            } else {
+                text.into()
+            }
+        }
+        "round" => match value.as_f64() {
+            Some(n) => {
+                let places: usize = arg(0).and_then(|n| n.parse().ok()).unwrap_or(0);
+                format!("{:.*}", places, n).into()
+            }
+            None => value,
+        },
+        "percent" => match value.as_f64() {
+            Some(n) => format!("{:.0}%", n * 100.0).into(),
+            None => value,
+        },
+        "join" => match value {
+            serde_json::Value::Array(items) => items
+                .iter()
+                .map(value_to_string)
+                .collect::<Vec<_>>()
+                .join(arg(0).unwrap_or(", "))
+                .into(),
+            other => other,
+        },
+        "time" | "utc" => {
+            let Some(timestamp) = value
+                .as_str()
+                .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            else {
+                return value;
+            };
+            let format = arg(0).unwrap_or(DEFAULT_TIME_FORMAT);
+            let offset = if filter.name == "utc" {
+                FixedOffset::east_opt(0)
+            } else {
+                arg(1).and_then(parse_utc_offset).or(utc_offset)
+            };
+            match offset {
+                Some(offset) => timestamp.with_timezone(&offset).format(format).to_string(),
+                None => timestamp.with_timezone(&Local).format(format).to_string(),
+            }
+            .into()
+        }
+        other => {
+            warn!(filter = other, "Unknown template filter");
+            value
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn create_test_context() -> TemplateContext {
+        TemplateContext {
+            event_id: "evt_1".to_string(),
+            event_type: "person_detected".to_string(),
+            severity: "high".to_string(),
+            confidence: 0.873,
+            description: "Person at the door".to_string(),
+            source_id: "cam_front".to_string(),
+            template_id: "cam_front".to_string(),
+            timestamp: Utc.with_ymd_and_hms(2024, 3, 1, 22, 5, 0).unwrap(),
+            stream_name: Some("Front Door".to_string()),
+            snapshot_url: Some("https://glimpser.local/api/stream/cam_front/snapshot".to_string()),
+            ai_description: None,
+            suggested_actions: vec!["Check camera".to_string(), "Call owner".to_string()],
+            metadata: HashMap::from([("zone".to_string(), "porch".into())]),
+        }
+    }
+
+    fn render(source: &str, offset: Option<FixedOffset>) -> String {
+        let data = serde_json::to_value(create_test_context()).unwrap();
+        Template::parse(source).unwrap().render(&data, offset)
+    }
+
+    #[test]
+    fn test_expressions_and_filters() {
+        assert_eq!(
+            render(
+                "{{ severity | upper }}: {{event_type|humanize|title}}",
+                None
+            ),
+            "HIGH: Person Detected"
+        );
+        assert_eq!(render("{{ metadata.zone }}{{ missing }}", None), "porch");
+        assert_eq!(
+            render("{{ ai_description | default:\"n/a\" }}", None),
+            "n/a"
+        );
+        assert_eq!(render("{{ confidence | percent }}", None), "87%");
+        assert_eq!(render("{{ confidence | round:2 }}", None), "0.87");
+        assert_eq!(render("{{ description | truncate:6 }}", None), "Person…");
+        assert_eq!(
+            render("{{ description | json }}", None),
+            "\"Person at the door\""
+        );
+        assert_eq!(
+            render("{{ suggested_actions | join:\" / \" }}", None),
+            "Check camera / Call owner"
+        );
+    }
+
+    #[test]
+    fn test_time_formatting() {
+        let berlin = parse_utc_offset("+01:00");
+        assert_eq!(render("{{ timestamp | time:\"%H:%M\" }}", berlin), "23:05");
+        assert_eq!(render("{{ timestamp | utc:\"%H:%M\" }}", berlin), "22:05");
+        assert_eq!(
+            render("{{ timestamp | time:\"%d.%m. %H:%M\":\"-05:00\" }}", berlin),
+            "01.03. 17:05"
+        );
+        assert_eq!(
+            render("{{ timestamp | time }}", parse_utc_offset("UTC")),
+            "2024-03-01 22:05:00"
+        );
+    }
+
+    #[test]
+    fn test_conditionals_and_loops() {
+        assert_eq!(
+            render(
+                "{% if ai_description %}AI{% else %}no AI{% endif %}{% if not snapshot_url %}!{% endif %}",
+                None
+            ),
+            "no AI"
+        );
+        assert_eq!(
+            render(
+                "{% for a in suggested_actions %}{{ loop.index }}. {{ a }};{% endfor %}",
+                None
+            ),
+            "1. Check camera;2. Call owner;"
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Template::parse("{{ severity | shout }}").is_err());
+        assert!(Template::parse("{% if severity %}open").is_err());
+        assert!(Template::parse("{% endif %}").is_err());
+        assert!(Template::parse("{{ severity").is_err());
+        assert!(Template::parse("{{ timestamp | time:\"%Q\" }}").is_err());
+        assert!(Template::parse("{{ confidence | round:x }}").is_err());
+    }
+
+    #[test]
+    fn test_channel_defaults_and_overrides() {
+        let mut templates = NotificationTemplates::new();
+        templates.set_utc_offset(parse_utc_offset("+00:00"));
+        let context = create_test_context();
+
+        let default = templates.render("webhook", None, &context).unwrap();
+        assert_eq!(default.title, "HIGH Alert: Person Detected");
+        assert!(default
+            .body
+            .starts_with("Source: Front Door\nDescription: Person at the door"));
+        assert!(default.body.contains("\n1. Check camera\n2. Call owner"));
+
+        let pushover = templates.render("pushover", None, &context).unwrap();
+        assert_eq!(pushover.title, "Person Detected on Front Door");
+        assert_eq!(pushover.body, "Person at the door\nHIGH · 87% · 22:05");
+
+        templates
+            .set_named_template(
+                "german",
+                MessageTemplate::new(
+                    "Alarm: {{ stream_name }}",
+                    "{{ description }} um {{ timestamp | time:\"%H:%M\" }} Uhr",
+                ),
+            )
+            .unwrap();
+        let german = templates
+            .render("pushover", Some("german"), &context)
+            .unwrap();
+        assert_eq!(german.title, "Alarm: Front Door");
+        assert_eq!(german.body, "Person at the door um 22:05 Uhr");
+
+        // Unknown names are treated as inline body templates
+        let inline = templates
+            .render("pushover", Some("{{ event_type }}!"), &context)
+            .unwrap();
+        assert_eq!(inline.title, "Person Detected on Front Door");
+        assert_eq!(inline.body, "person_detected!");
+
+        assert!(templates
+            .set_channel_template("webhook", MessageTemplate::new("{{", "body"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_parse_utc_offset() {
+        assert_eq!(parse_utc_offset("+02:00"), FixedOffset::east_opt(2 * 3600));
+        assert_eq!(
+            parse_utc_offset("-0530"),
+            FixedOffset::east_opt(-(5 * 3600 + 30 * 60))
+        );
+        assert_eq!(parse_utc_offset("utc"), FixedOffset::east_opt(0));
+        assert!(parse_utc_offset("Europe/Berlin").is_none());
+    }
+}
@@ -34,6 +34,8 @@ async fn test_multi_channel_notification() {
                 .expect("valid WireMock URL for webhook channel"),
             headers: None,
             method: Some("POST".to_string()),
+            secret: None,
+            payload_format: None,
         },
     ];
 
@@ -37,6 +37,8 @@ async fn dispatches_notifications_in_parallel() {
             url: "http://example.com".parse().unwrap(),
             headers: None,
             method: None,
+            secret: None,
+            payload_format: None,
         },
         NotificationChannel::Pushover {
             user_key: "user".to_string(),
@@ -0,0 +1,343 @@
+//! ABOUTME: Tests for Web Push delivery against a local push-service stub
+//! ABOUTME: Decrypts pushed payloads with the browser key and checks VAPID and 410 cleanup
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use gl_core::Id;
+use gl_db::{
+    CreatePushSubscription, CreateUserRequest, Db, PushSubscriptionRepository, UserRepository,
+};
+use gl_notify::adapters::webpush::{
+    encrypt_payload, PushOutcome, VapidKeys, WebPushAdapter, MAX_PAYLOAD_BYTES,
+};
+use gl_notify::{Notification, NotificationChannel, NotificationKind, Notifier, TemplateContext};
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::{aead, agreement, hkdf, signature};
+use std::path::PathBuf;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Browser side of a subscription: the key pair and auth secret it would hold
+struct BrowserKeys {
+    private_key: agreement::EphemeralPrivateKey,
+    public_key: Vec<u8>,
+    auth: [u8; 16],
+}
+
+impl BrowserKeys {
+    fn generate() -> Self {
+        let rng = SystemRandom::new();
+        let private_key =
+            agreement::EphemeralPrivateKey::generate(&agreement::ECDH_P256, &rng).unwrap();
+        let public_key = private_key.compute_public_key().unwrap().as_ref().to_vec();
+        let mut auth = [0u8; 16];
+        rng.fill(&mut auth).unwrap();
+        Self {
+            private_key,
+            public_key,
+            auth,
+        }
+    }
+
+    fn subscription(&self, user_id: &str, endpoint: String) -> CreatePushSubscription {
+        CreatePushSubscription {
+            user_id: user_id.to_string(),
+            endpoint,
+            p256dh: URL_SAFE_NO_PAD.encode(&self.public_key),
+            auth: URL_SAFE_NO_PAD.encode(self.auth),
+            user_agent: None,
+        }
+    }
+
+    /// Decrypt an aes128gcm body the way a browser would
+    fn decrypt(self, body: &[u8]) -> Vec<u8> {
+        let salt = &body[..16];
+        let key_len = body[20] as usize;
+        let server_public = &body[21..21 + key_len];
+        let mut record = body[21 + key_len..].to_vec();
+
+        let peer = agreement::UnparsedPublicKey::new(&agreement::ECDH_P256, server_public);
+        let shared = agreement::agree_ephemeral(self.private_key, &peer, |s| s.to_vec()).unwrap();
+
+        let mut info = b"WebPush: info\0".to_vec();
+        info.extend_from_slice(&self.public_key);
+        info.extend_from_slice(server_public);
+        let ikm = hkdf_expand(&self.auth, &shared, &info, 32);
+        let cek = hkdf_expand(salt, &ikm, b"Content-Encoding: aes128gcm\0", 16);
+        let nonce = hkdf_expand(salt, &ikm, b"Content-Encoding: nonce\0", 12);
+
+        let key = aead::LessSafeKey::new(aead::UnboundKey::new(&aead::AES_128_GCM, &cek).unwrap());
+        let plaintext = key
+            .open_in_place(
+                aead::Nonce::try_assume_unique_for_key(&nonce).unwrap(),
+                aead::Aad::empty(),
+                &mut record,
+            )
+            .unwrap();
+        assert_eq!(
+            plaintext.last(),
+            Some(&0x02),
+            "missing final record delimiter"
+        );
+        plaintext[..plaintext.len() - 1].to_vec()
+    }
+}
+
+fn hkdf_expand(salt: &[u8], ikm: &[u8], info: &[u8], len: usize) -> Vec<u8> {
+    struct Len(usize);
+    impl hkdf::KeyType for Len {
+        fn len(&self) -> usize {
+            self.0
+        }
+    }
+
+    let mut out = vec![0u8; len];
+    hkdf::Salt::new(hkdf::HKDF_SHA256, salt)
+        .extract(ikm)
+        .expand(&[info], Len(len))
+        .unwrap()
+        .fill(&mut out)
+        .unwrap();
+    out
+}
+
+async fn test_db() -> (Db, PathBuf, String) {
+    let db_path = std::env::temp_dir().join(format!("glimpser_push_{}.db", Id::new()));
+    let db = Db::new(db_path.to_str().unwrap()).await.unwrap();
+    let user = UserRepository::new(db.pool())
+        .create(CreateUserRequest {
+            username: "push_user".to_string(),
+            email: "push@example.com".to_string(),
+            password_hash: "hashed_password".to_string(),
+        })
+        .await
+        .unwrap();
+    (db, db_path, user.id)
+}
+
+async fn cleanup(db: Db, db_path: PathBuf) {
+    db.pool().close().await;
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{}", db_path.display(), suffix));
+    }
+}
+
+fn motion_notification() -> Notification {
+    Notification::new(
+        NotificationKind::Warning,
+        "Motion on Garage".to_string(),
+        "Confidence 91%".to_string(),
+        vec![NotificationChannel::WebPush { user_id: None }],
+    )
+    .with_template_context(TemplateContext {
+        event_id: "evt1".to_string(),
+        event_type: "motion".to_string(),
+        severity: "medium".to_string(),
+        confidence: 0.91,
+        description: "Motion in the garage".to_string(),
+        source_id: "stream1".to_string(),
+        template_id: "stream1".to_string(),
+        timestamp: chrono::Utc::now(),
+        stream_name: Some("Garage".to_string()),
+        snapshot_url: Some("https://cams.example.com/api/stream/stream1/snapshot".to_string()),
+        ai_description: None,
+        suggested_actions: Vec::new(),
+        metadata: Default::default(),
+    })
+}
+
+#[tokio::test]
+async fn test_push_is_encrypted_and_vapid_signed() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/push/device1"))
+        .and(header("Content-Encoding", "aes128gcm"))
+        .and(header("TTL", "60"))
+        .respond_with(ResponseTemplate::new(201))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let (db, db_path, user_id) = test_db().await;
+    let repo = PushSubscriptionRepository::new(db.clone());
+    let browser = BrowserKeys::generate();
+    repo.upsert(browser.subscription(&user_id, format!("{}/push/device1", server.uri())))
+        .await
+        .unwrap();
+
+    let adapter = WebPushAdapter::new(
+        VapidKeys::generate().unwrap(),
+        "mailto:ops@example.com".to_string(),
+    )
+    .with_ttl(60)
+    .with_subscriptions(repo.clone());
+    adapter.send(&motion_notification()).await.unwrap();
+
+    let requests = server.received_requests().await.unwrap();
+    let request = &requests[0];
+
+    // Authorization: vapid t=<jwt>, k=<public key>
+    let authorization = request
+        .headers
+        .get("Authorization")
+        .unwrap()
+        .to_str()
+        .unwrap();
+    let (token, key) = authorization
+        .strip_prefix("vapid t=")
+        .and_then(|rest| rest.split_once(", k="))
+        .unwrap();
+    assert_eq!(key, adapter.public_key());
+
+    let (signing_input, jwt_signature) = token.rsplit_once('.').unwrap();
+    signature::UnparsedPublicKey::new(
+        &signature::ECDSA_P256_SHA256_FIXED,
+        URL_SAFE_NO_PAD.decode(key).unwrap(),
+    )
+    .verify(
+        signing_input.as_bytes(),
+        &URL_SAFE_NO_PAD.decode(jwt_signature).unwrap(),
+    )
+    .unwrap();
+    let claims: serde_json::Value = serde_json::from_slice(
+        &URL_SAFE_NO_PAD
+            .decode(signing_input.split('.').nth(1).unwrap())
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(claims["aud"], server.uri());
+    assert_eq!(claims["sub"], "mailto:ops@example.com");
+
+    let payload: serde_json::Value =
+        serde_json::from_slice(&browser.decrypt(&request.body)).unwrap();
+    assert_eq!(payload["title"], "Motion on Garage");
+    assert_eq!(payload["body"], "Confidence 91%");
+    assert_eq!(
+        payload["image"],
+        "https://cams.example.com/api/stream/stream1/snapshot"
+    );
+    assert_eq!(payload["tag"], "stream1:motion");
+
+    let stored = repo.list_by_user(&user_id).await.unwrap();
+    assert!(stored[0].last_success_at.is_some());
+
+    cleanup(db, db_path).await;
+}
+
+#[tokio::test]
+async fn test_gone_subscription_is_removed() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/push/expired"))
+        .respond_with(ResponseTemplate::new(410))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/push/active"))
+        .respond_with(ResponseTemplate::new(201))
+        .mount(&server)
+        .await;
+
+    let (db, db_path, user_id) = test_db().await;
+    let repo = PushSubscriptionRepository::new(db.clone());
+    for device in ["expired", "active"] {
+        repo.upsert(
+            BrowserKeys::generate()
+                .subscription(&user_id, format!("{}/push/{}", server.uri(), device)),
+        )
+        .await
+        .unwrap();
+    }
+
+    let adapter = WebPushAdapter::new(
+        VapidKeys::generate().unwrap(),
+        "mailto:ops@example.com".to_string(),
+    )
+    .with_subscriptions(repo.clone());
+    adapter.send(&motion_notification()).await.unwrap();
+
+    let remaining = repo.list_all().await.unwrap();
+    assert_eq!(remaining.len(), 1);
+    assert!(remaining[0].endpoint.ends_with("/push/active"));
+
+    cleanup(db, db_path).await;
+}
+
+#[tokio::test]
+async fn test_failed_push_is_reported() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+
+    let (db, db_path, user_id) = test_db().await;
+    let repo = PushSubscriptionRepository::new(db.clone());
+    let subscription = repo
+        .upsert(BrowserKeys::generate().subscription(&user_id, format!("{}/push/1", server.uri())))
+        .await
+        .unwrap();
+
+    let adapter = WebPushAdapter::new(
+        VapidKeys::generate().unwrap(),
+        "mailto:ops@example.com".to_string(),
+    )
+    .with_subscriptions(repo.clone());
+    assert!(adapter.send(&motion_notification()).await.is_err());
+    assert!(adapter.push(&subscription, b"{}").await.is_err());
+    // Server errors keep the subscription
+    assert_eq!(repo.list_all().await.unwrap().len(), 1);
+
+    let gone = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&gone)
+        .await;
+    let missing = repo
+        .upsert(BrowserKeys::generate().subscription(&user_id, format!("{}/push/2", gone.uri())))
+        .await
+        .unwrap();
+    assert_eq!(
+        adapter.push(&missing, b"{}").await.unwrap(),
+        PushOutcome::Gone
+    );
+
+    cleanup(db, db_path).await;
+}
+
+#[tokio::test]
+async fn test_vapid_keys_are_persisted() {
+    let (db, db_path, _) = test_db().await;
+    let repo = PushSubscriptionRepository::new(db.clone());
+
+    let first = VapidKeys::load_or_generate(&repo).await.unwrap();
+    let second = VapidKeys::load_or_generate(&repo).await.unwrap();
+    assert_eq!(first.public_key_base64(), second.public_key_base64());
+    assert_eq!(first.public_key().len(), 65);
+
+    let reloaded = VapidKeys::from_base64(&first.private_key_base64(), None).unwrap();
+    assert_eq!(reloaded.public_key_base64(), first.public_key_base64());
+    assert!(VapidKeys::from_base64(&URL_SAFE_NO_PAD.encode([7u8; 32]), None).is_err());
+
+    cleanup(db, db_path).await;
+}
+
+#[test]
+fn test_payload_fits_in_one_record() {
+    let mut notification = motion_notification();
+    notification.body = "motion ".repeat(2000);
+
+    let payload = WebPushAdapter::build_payload(&notification).unwrap();
+    assert!(payload.len() <= MAX_PAYLOAD_BYTES);
+    let json: serde_json::Value = serde_json::from_slice(&payload).unwrap();
+    assert!(json["body"].as_str().unwrap().ends_with('…'));
+
+    let browser = BrowserKeys::generate();
+    assert!(encrypt_payload(&payload, &browser.public_key, &browser.auth).is_ok());
+    assert!(encrypt_payload(
+        &[0u8; MAX_PAYLOAD_BYTES + 1],
+        &browser.public_key,
+        &browser.auth
+    )
+    .is_err());
+}
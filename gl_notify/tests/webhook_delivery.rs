@@ -0,0 +1,272 @@
+//! ABOUTME: Tests for signed webhook delivery, payload formats and delivery replay
+//! ABOUTME: Uses Wiremock endpoints and a temporary database for the dispatcher
+
+use gl_core::Id;
+use gl_db::{
+    AnalysisEventRepository, CreateAnalysisEvent, CreateNotificationDelivery, CreateStreamRequest,
+    CreateUserRequest, Db, DeliveryStatus, NotificationDeliveryRepository, StreamRepository,
+    UserRepository,
+};
+use gl_notify::adapters::webhook::{
+    sign_payload, verify_signature, WebhookAdapter, WebhookPayloadFormat,
+    DEFAULT_SIGNATURE_TOLERANCE_SECONDS, SIGNATURE_HEADER,
+};
+use gl_notify::{
+    DispatcherConfig, Notification, NotificationChannel, NotificationDispatcher, NotificationKind,
+    Notifier,
+};
+use std::collections::HashMap;
+use wiremock::matchers::{header_exists, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn webhook_notification(
+    server: &MockServer,
+    secret: Option<&str>,
+    payload_format: Option<WebhookPayloadFormat>,
+) -> Notification {
+    Notification::new(
+        NotificationKind::Warning,
+        "Motion Alert".to_string(),
+        "Motion on Garage\nConfidence 91%".to_string(),
+        vec![NotificationChannel::Webhook {
+            url: format!("{}/hook", server.uri()).parse().unwrap(),
+            headers: None,
+            method: None,
+            secret: secret.map(|s| s.to_string()),
+            payload_format,
+        }],
+    )
+}
+
+#[test]
+fn test_signature_verification() {
+    let body = br#"{"title":"Motion Alert"}"#;
+    let header = sign_payload("s3cret", 1_700_000_000, body);
+
+    assert!(header.starts_with("t=1700000000,v1="));
+    assert!(verify_signature(
+        "s3cret",
+        &header,
+        body,
+        1_700_000_100,
+        300
+    ));
+    assert!(!verify_signature(
+        "wrong",
+        &header,
+        body,
+        1_700_000_100,
+        300
+    ));
+    assert!(!verify_signature(
+        "s3cret",
+        &header,
+        br#"{"title":"Tampered"}"#,
+        1_700_000_100,
+        300
+    ));
+    // Replayed outside the tolerance window
+    assert!(!verify_signature(
+        "s3cret",
+        &header,
+        body,
+        1_700_001_000,
+        300
+    ));
+    assert!(!verify_signature(
+        "s3cret",
+        "v1=abcd",
+        body,
+        1_700_000_000,
+        300
+    ));
+}
+
+#[tokio::test]
+async fn test_signed_webhook_request() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/hook"))
+        .and(header_exists(SIGNATURE_HEADER))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    WebhookAdapter::new()
+        .send(&webhook_notification(&server, Some("s3cret"), None))
+        .await
+        .unwrap();
+
+    let requests = server.received_requests().await.unwrap();
+    let signature = requests[0]
+        .headers
+        .get(SIGNATURE_HEADER)
+        .unwrap()
+        .to_str()
+        .unwrap();
+    assert!(verify_signature(
+        "s3cret",
+        signature,
+        &requests[0].body,
+        chrono::Utc::now().timestamp(),
+        DEFAULT_SIGNATURE_TOLERANCE_SECONDS
+    ));
+
+    let payload: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    assert_eq!(payload["title"], "Motion Alert");
+}
+
+#[tokio::test]
+async fn test_unsigned_webhook_has_no_signature() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    WebhookAdapter::new()
+        .send(&webhook_notification(&server, None, None))
+        .await
+        .unwrap();
+
+    let requests = server.received_requests().await.unwrap();
+    assert!(requests[0].headers.get(SIGNATURE_HEADER).is_none());
+}
+
+#[tokio::test]
+async fn test_payload_formats() {
+    let server = MockServer::start().await;
+    let notification = webhook_notification(&server, None, None);
+    let now = chrono::Utc::now();
+
+    let slack =
+        WebhookAdapter::build_payload(&notification, &WebhookPayloadFormat::Slack, now).unwrap();
+    assert_eq!(
+        slack["text"],
+        "*Motion Alert*\nMotion on Garage\nConfidence 91%"
+    );
+
+    let teams =
+        WebhookAdapter::build_payload(&notification, &WebhookPayloadFormat::Teams, now).unwrap();
+    assert_eq!(teams["@type"], "MessageCard");
+    assert_eq!(teams["themeColor"], "F39C12");
+    assert_eq!(teams["text"], "Motion on Garage\n\nConfidence 91%");
+
+    let discord =
+        WebhookAdapter::build_payload(&notification, &WebhookPayloadFormat::Discord, now).unwrap();
+    assert_eq!(discord["embeds"][0]["title"], "Motion Alert");
+    assert_eq!(discord["embeds"][0]["color"], 0xF39C12);
+
+    let template = WebhookPayloadFormat::Template(
+        r#"{"summary": {{ title | json }}, "level": "{{ kind | lower }}"}"#.to_string(),
+    );
+    let custom = WebhookAdapter::build_payload(&notification, &template, now).unwrap();
+    assert_eq!(
+        custom,
+        serde_json::json!({ "summary": "Motion Alert", "level": "warning" })
+    );
+
+    let broken = WebhookPayloadFormat::Template("{{ title }}".to_string());
+    assert!(WebhookAdapter::build_payload(&notification, &broken, now).is_err());
+
+    let format: WebhookPayloadFormat = serde_json::from_str(r#""discord""#).unwrap();
+    assert_eq!(format, WebhookPayloadFormat::Discord);
+}
+
+#[tokio::test]
+async fn test_redeliver_notification() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/hook"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let db_path = std::env::temp_dir().join(format!("glimpser_notify_{}.db", Id::new()));
+    let db = Db::new(db_path.to_str().unwrap()).await.unwrap();
+
+    let user = UserRepository::new(db.pool())
+        .create(CreateUserRequest {
+            username: "webhook_user".to_string(),
+            email: "webhook@example.com".to_string(),
+            password_hash: "hashed_password".to_string(),
+        })
+        .await
+        .unwrap();
+    let stream = StreamRepository::new(db.pool())
+        .create(CreateStreamRequest {
+            user_id: user.id,
+            name: "Garage".to_string(),
+            description: None,
+            config: r#"{"kind":"file"}"#.to_string(),
+            is_default: false,
+        })
+        .await
+        .unwrap();
+    let event = AnalysisEventRepository::new(db.clone())
+        .create(CreateAnalysisEvent {
+            template_id: stream.id.clone(),
+            event_type: "motion".to_string(),
+            severity: "high".to_string(),
+            confidence: 0.91,
+            description: "Motion in the garage".to_string(),
+            metadata: None,
+            processor_name: "motion".to_string(),
+            source_id: stream.id.clone(),
+            should_notify: true,
+            suggested_actions: None,
+        })
+        .await
+        .unwrap();
+
+    let delivery_repo = NotificationDeliveryRepository::new(db.clone());
+    let original = delivery_repo
+        .create(CreateNotificationDelivery {
+            analysis_event_id: event.id.clone(),
+            channel_type: "webhook".to_string(),
+            channel_config: HashMap::from([
+                ("url".to_string(), format!("{}/hook", server.uri()).into()),
+                ("secret".to_string(), "s3cret".into()),
+                ("payload_format".to_string(), "slack".into()),
+            ]),
+            max_attempts: Some(1),
+            scheduled_at: None,
+            metadata: None,
+        })
+        .await
+        .unwrap();
+
+    let dispatcher = NotificationDispatcher::new(DispatcherConfig::default(), db.clone());
+    let replay = dispatcher.redeliver(&original.id).await.unwrap();
+
+    assert_ne!(replay.id, original.id);
+    assert_eq!(replay.status, DeliveryStatus::Sent);
+    assert_eq!(
+        replay.metadata.unwrap()["redelivery_of"],
+        serde_json::json!(original.id)
+    );
+
+    let requests = server.received_requests().await.unwrap();
+    let payload: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    assert!(payload["text"]
+        .as_str()
+        .unwrap()
+        .starts_with("*HIGH Alert: Motion*"));
+    let signature = requests[0].headers.get(SIGNATURE_HEADER).unwrap();
+    assert!(verify_signature(
+        "s3cret",
+        signature.to_str().unwrap(),
+        &requests[0].body,
+        chrono::Utc::now().timestamp(),
+        DEFAULT_SIGNATURE_TOLERANCE_SECONDS
+    ));
+
+    assert!(dispatcher.redeliver("missing").await.is_err());
+
+    db.pool().close().await;
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{}", db_path.display(), suffix));
+    }
+}
@@ -209,6 +209,8 @@ pub struct JobScheduler {
     metrics: JobMetrics,
     db: Db,
     capture_service: Arc<dyn CaptureService>,
+    /// Cron entries of recurring jobs, keyed by job ID
+    cron_entries: Arc<RwLock<HashMap<String, uuid::Uuid>>>,
 }
 
 impl JobScheduler {
@@ -234,6 +236,7 @@ impl JobScheduler {
             metrics: JobMetrics::new(),
             db,
             capture_service,
+            cron_entries: Arc::new(RwLock::new(HashMap::new())),
         })
     }
 
@@ -300,27 +303,166 @@ impl JobScheduler {
         Ok(execution_id)
     }
 
-    /// Schedule a recurring job
+    /// Schedule a recurring job on its cron schedule, replacing any earlier schedule of the
+    /// same job; returns the job ID
     pub async fn schedule_recurring(&self, job_def: JobDefinition) -> Result<String> {
+        job_def.validate()?;
         info!(
-            "Scheduling recurring job: {} (executing immediately for now)",
-            job_def.name
+            "Scheduling recurring job: {} ({})",
+            job_def.name, job_def.schedule
         );
 
         if self.config.enable_persistence {
             self.job_storage.save_job(&job_def).await?;
         }
 
-        // For now, just execute immediately until we resolve the lifetime issues
-        // TODO: Implement proper cron-based recurring scheduling
-        let execution_id = self.execute_now(job_def).await?;
-        Ok(execution_id)
+        let job_id = job_def.id.clone();
+        let schedule = job_def.schedule.clone();
+        let runner = self.runner();
+        let cron_job = tokio_cron_scheduler::Job::new_async(schedule.as_str(), move |_, _| {
+            let runner = runner.clone();
+            let job_def = job_def.clone();
+            Box::pin(async move {
+                if let Err(e) = runner.execute(job_def).await {
+                    warn!("Failed to start scheduled job: {}", e);
+                }
+            })
+        })
+        .map_err(|e| {
+            gl_core::Error::Validation(format!("Invalid cron schedule {}: {}", schedule, e))
+        })?;
+
+        let entry = self.cron_scheduler.add(cron_job).await.map_err(|e| {
+            gl_core::Error::Config(format!("Failed to schedule job {}: {}", job_id, e))
+        })?;
+        if let Some(previous) = self
+            .cron_entries
+            .write()
+            .await
+            .insert(job_id.clone(), entry)
+        {
+            self.remove_cron_entry(&job_id, &previous).await;
+        }
+
+        Ok(job_id)
+    }
+
+    /// Stop running a recurring job and forget its definition
+    pub async fn unschedule(&self, job_id: &str) -> Result<()> {
+        let entry = self.cron_entries.write().await.remove(job_id);
+        let Some(entry) = entry else {
+            return Err(gl_core::Error::NotFound(format!(
+                "Job not scheduled: {}",
+                job_id
+            )));
+        };
+        self.remove_cron_entry(job_id, &entry).await;
+
+        if self.config.enable_persistence {
+            self.job_storage.delete_job(job_id).await?;
+        }
+        info!("Unscheduled job: {}", job_id);
+        Ok(())
+    }
+
+    async fn remove_cron_entry(&self, job_id: &str, entry: &uuid::Uuid) {
+        if let Err(e) = self.cron_scheduler.remove(entry).await {
+            warn!("Failed to remove schedule of job {}: {}", job_id, e);
+        }
     }
 
     /// Execute a job immediately
     pub async fn execute_now(&self, job_def: JobDefinition) -> Result<String> {
         info!("Executing job immediately: {}", job_def.name);
+        self.runner().execute(job_def).await
+    }
+
+    fn runner(&self) -> JobRunner {
+        JobRunner {
+            config: self.config.clone(),
+            job_storage: self.job_storage.clone(),
+            running_jobs: self.running_jobs.clone(),
+            job_handlers: self.job_handlers.clone(),
+            metrics: self.metrics.clone(),
+            db: self.db.clone(),
+            capture_service: self.capture_service.clone(),
+        }
+    }
+
+    /// Get job metrics
+    pub fn get_metrics(&self) -> JobMetrics {
+        self.metrics.clone()
+    }
+
+    /// Get job execution history
+    pub async fn get_job_history(
+        &self,
+        job_id: &str,
+        limit: Option<u32>,
+    ) -> Result<Vec<JobResult>> {
+        self.job_storage.get_job_results(job_id, limit).await
+    }
+
+    /// Cancel a running job
+    pub async fn cancel_job(&self, execution_id: &str) -> Result<()> {
+        let mut running_jobs = self.running_jobs.write().await;
+        if let Some(handle) = running_jobs.remove(execution_id) {
+            handle.abort();
+            info!("Cancelled job: {}", execution_id);
+
+            if self.config.enable_persistence {
+                let mut result = JobResult::new();
+                result.status = JobStatus::Cancelled;
+                result.completed_at = Some(Utc::now());
+                self.job_storage
+                    .save_job_result(execution_id, &result)
+                    .await?;
+            }
+
+            Ok(())
+        } else {
+            Err(gl_core::Error::NotFound(format!(
+                "Job not found: {}",
+                execution_id
+            )))
+        }
+    }
+
+    /// Load persisted jobs from storage
+    async fn load_persisted_jobs(&self) -> Result<()> {
+        debug!("Loading persisted jobs from storage");
+
+        let jobs = self.job_storage.list_jobs().await?;
+        info!("Found {} persisted jobs", jobs.len());
+
+        for job_def in jobs {
+            if job_def.enabled {
+                match self.schedule_recurring(job_def.clone()).await {
+                    Ok(_) => debug!("Restored job: {}", job_def.name),
+                    Err(e) => warn!("Failed to restore job {}: {}", job_def.name, e),
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Everything needed to run a job, shared with cron triggers
+#[derive(Clone)]
+struct JobRunner {
+    config: SchedulerConfig,
+    job_storage: Arc<dyn JobStorage>,
+    running_jobs: Arc<RwLock<HashMap<String, tokio::task::JoinHandle<()>>>>,
+    job_handlers: Arc<RwLock<HashMap<String, Arc<dyn JobHandler>>>>,
+    metrics: JobMetrics,
+    db: Db,
+    capture_service: Arc<dyn CaptureService>,
+}
 
+impl JobRunner {
+    /// Start a job in the background, returning its execution ID
+    async fn execute(&self, job_def: JobDefinition) -> Result<String> {
         let execution_id = Id::new().to_string();
         let execution_id_for_task = execution_id.clone();
         let job_id = job_def.id.clone();
@@ -414,64 +556,6 @@ impl JobScheduler {
 
         Ok(execution_id)
     }
-
-    /// Get job metrics
-    pub fn get_metrics(&self) -> JobMetrics {
-        self.metrics.clone()
-    }
-
-    /// Get job execution history
-    pub async fn get_job_history(
-        &self,
-        job_id: &str,
-        limit: Option<u32>,
-    ) -> Result<Vec<JobResult>> {
-        self.job_storage.get_job_results(job_id, limit).await
-    }
-
-    /// Cancel a running job
-    pub async fn cancel_job(&self, execution_id: &str) -> Result<()> {
-        let mut running_jobs = self.running_jobs.write().await;
-        if let Some(handle) = running_jobs.remove(execution_id) {
-            handle.abort();
-            info!("Cancelled job: {}", execution_id);
-
-            if self.config.enable_persistence {
-                let mut result = JobResult::new();
-                result.status = JobStatus::Cancelled;
-                result.completed_at = Some(Utc::now());
-                self.job_storage
-                    .save_job_result(execution_id, &result)
-                    .await?;
-            }
-
-            Ok(())
-        } else {
-            Err(gl_core::Error::NotFound(format!(
-                "Job not found: {}",
-                execution_id
-            )))
-        }
-    }
-
-    /// Load persisted jobs from storage
-    async fn load_persisted_jobs(&self) -> Result<()> {
-        debug!("Loading persisted jobs from storage");
-
-        let jobs = self.job_storage.list_jobs().await?;
-        info!("Found {} persisted jobs", jobs.len());
-
-        for job_def in jobs {
-            if job_def.enabled {
-                match self.schedule_recurring(job_def.clone()).await {
-                    Ok(_) => debug!("Restored job: {}", job_def.name),
-                    Err(e) => warn!("Failed to restore job {}: {}", job_def.name, e),
-                }
-            }
-        }
-
-        Ok(())
-    }
 }
 
 /// Job execution metrics
@@ -1,4 +1,4 @@
-//! ABOUTME: Streaming services for MJPEG and RTSP video streams
+//! ABOUTME: Streaming services for MJPEG, RTSP and passthrough restreamed video
 //! ABOUTME: Provides real-time video streaming capabilities
 
 use bytes::Bytes;
@@ -20,11 +20,13 @@ use uuid::Uuid;
 
 mod metrics;
 mod mjpeg;
+mod restream;
 #[cfg(feature = "rtsp")]
 mod rtsp;
 
 pub use metrics::*;
 pub use mjpeg::*;
+pub use restream::{RestreamBackend, RestreamFeed, RestreamServer, RestreamServerConfig};
 #[cfg(feature = "rtsp")]
 pub use rtsp::*;
 
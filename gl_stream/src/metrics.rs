@@ -30,3 +30,27 @@ impl StreamMetrics {
         Self::default()
     }
 }
+
+/// Metrics for the RTSP restream server
+#[derive(Debug, Clone, Default)]
+pub struct RestreamMetrics {
+    /// Currently connected RTSP clients
+    pub clients: Gauge,
+    /// Total number of accepted RTSP connections
+    pub connections_total: Counter,
+    /// Requests rejected for bad or missing credentials
+    pub auth_failures: Counter,
+    /// Access units sent to clients
+    pub units_forwarded: Counter,
+    /// Access units skipped because a client fell behind
+    pub units_dropped: Counter,
+    /// Clients disconnected because the upstream stopped sending
+    pub upstream_stalls: Counter,
+}
+
+impl RestreamMetrics {
+    /// Create new restream metrics
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
@@ -0,0 +1,1083 @@
+//! ABOUTME: Passthrough RTSP server that re-serves a camera's H.264 to many clients
+//! ABOUTME: One upstream session per stream is fanned out over interleaved RTP without re-encoding
+
+use crate::RestreamMetrics;
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use bytes::{BufMut, Bytes, BytesMut};
+use gl_capture::{AccessUnit, CaptureHandle, VideoCodec};
+use gl_core::{Error, Result};
+use std::{
+    fmt::Write as _,
+    net::SocketAddr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{tcp::OwnedReadHalf, TcpListener, TcpStream},
+    sync::{broadcast, mpsc, Semaphore},
+    task::JoinHandle,
+};
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+/// Largest RTP payload; keeps packets under a typical 1500 byte MTU
+const MAX_RTP_PAYLOAD: usize = 1400;
+
+/// RTP payload type advertised in the SDP
+const PAYLOAD_TYPE: u8 = 96;
+
+/// Longest request head accepted from a client
+const MAX_REQUEST_BYTES: usize = 16 * 1024;
+
+/// Restream server configuration
+#[derive(Debug, Clone)]
+pub struct RestreamServerConfig {
+    pub bind_addr: SocketAddr,
+    /// Realm sent in Basic auth challenges
+    pub realm: String,
+    /// Concurrent client connections across all streams
+    pub max_clients: usize,
+    /// Time to wait for a keyframe before DESCRIBE fails
+    pub keyframe_timeout: Duration,
+    /// Clients that send nothing (requests or RTCP) for this long are dropped
+    pub session_timeout: Duration,
+    /// Playing clients are disconnected when the upstream sends nothing for this long
+    pub stall_timeout: Duration,
+}
+
+impl Default for RestreamServerConfig {
+    fn default() -> Self {
+        Self {
+            bind_addr: "0.0.0.0:8554".parse().unwrap(),
+            realm: "glimpser".to_string(),
+            max_clients: 64,
+            keyframe_timeout: Duration::from_secs(10),
+            session_timeout: Duration::from_secs(60),
+            stall_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+/// A client's view of a stream's upstream session
+pub struct RestreamFeed {
+    pub units: broadcast::Receiver<AccessUnit>,
+    /// Held for as long as the client is attached so the upstream stays connected
+    pub upstream: Option<Arc<CaptureHandle>>,
+}
+
+/// Source of streams and credentials for the restream server
+///
+/// Implemented by the web crate, which owns the camera sessions and user accounts.
+#[async_trait]
+pub trait RestreamBackend: Send + Sync {
+    /// Whether the credentials may watch the stream
+    async fn authorize(&self, stream_id: &str, username: &str, password: &str) -> Result<bool>;
+
+    /// Attach to the stream's shared upstream, or `None` if it can't be restreamed
+    async fn subscribe(&self, stream_id: &str) -> Result<Option<RestreamFeed>>;
+}
+
+/// RTSP server that fans upstream access units out to any number of clients
+pub struct RestreamServer {
+    listener: TcpListener,
+    config: Arc<RestreamServerConfig>,
+    backend: Arc<dyn RestreamBackend>,
+    metrics: RestreamMetrics,
+}
+
+impl RestreamServer {
+    /// Bind the listening socket
+    pub async fn bind(
+        config: RestreamServerConfig,
+        backend: Arc<dyn RestreamBackend>,
+    ) -> Result<Self> {
+        let listener = TcpListener::bind(config.bind_addr).await?;
+        Ok(Self {
+            listener,
+            config: Arc::new(config),
+            backend,
+            metrics: RestreamMetrics::new(),
+        })
+    }
+
+    /// Address the server is listening on
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Metrics shared with every connection
+    pub fn metrics(&self) -> RestreamMetrics {
+        self.metrics.clone()
+    }
+
+    /// Accept connections until the task is dropped
+    pub async fn run(self) -> Result<()> {
+        info!(addr = %self.local_addr()?, "RTSP restream server listening");
+        let slots = Arc::new(Semaphore::new(self.config.max_clients));
+
+        loop {
+            let (mut socket, peer) = match self.listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    // Usually file descriptor exhaustion; back off instead of spinning
+                    warn!(error = %e, "Failed to accept RTSP connection");
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    continue;
+                }
+            };
+
+            let Ok(slot) = slots.clone().try_acquire_owned() else {
+                warn!(peer = %peer, "Too many RTSP clients; refusing connection");
+                let _ = socket
+                    .write_all(b"RTSP/1.0 503 Service Unavailable\r\nCSeq: 0\r\n\r\n")
+                    .await;
+                continue;
+            };
+
+            self.metrics.connections_total.inc();
+            let connection = Connection {
+                config: self.config.clone(),
+                backend: self.backend.clone(),
+                metrics: self.metrics.clone(),
+                authorized: None,
+                session: None,
+            };
+            tokio::spawn(async move {
+                connection.metrics.clients.inc();
+                if let Err(e) = connection.serve(socket, peer).await {
+                    debug!(peer = %peer, error = %e, "RTSP connection closed with error");
+                }
+                drop(slot);
+            });
+        }
+    }
+}
+
+/// Parsed RTSP request
+#[derive(Debug)]
+struct Request {
+    method: String,
+    uri: String,
+    headers: Vec<(String, String)>,
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn cseq(&self) -> &str {
+        self.header("CSeq").unwrap_or("0")
+    }
+
+    /// First path segment of the request URI, which names the stream
+    fn stream_id(&self) -> Option<String> {
+        let path = match self.uri.find("://") {
+            Some(scheme_end) => {
+                let rest = &self.uri[scheme_end + 3..];
+                rest.find('/').map(|i| &rest[i..]).unwrap_or("/")
+            }
+            None => self.uri.as_str(),
+        };
+        let path = path.split(['?', '#']).next().unwrap_or_default();
+        path.split('/')
+            .find(|segment| !segment.is_empty())
+            .map(String::from)
+    }
+
+    /// Basic auth credentials, if the client sent any
+    fn basic_credentials(&self) -> Option<(String, String)> {
+        let value = self.header("Authorization")?;
+        let (scheme, encoded) = value.split_once(' ')?;
+        if !scheme.eq_ignore_ascii_case("basic") {
+            return None;
+        }
+        let decoded = String::from_utf8(BASE64.decode(encoded.trim()).ok()?).ok()?;
+        let (username, password) = decoded.split_once(':')?;
+        Some((username.to_string(), password.to_string()))
+    }
+}
+
+/// SPS and PPS for the SDP, taken from an upstream keyframe
+#[derive(Debug, Clone)]
+struct ParameterSets {
+    sps: Vec<u8>,
+    pps: Vec<u8>,
+}
+
+impl ParameterSets {
+    fn from_keyframe(keyframe: &AccessUnit) -> Option<Self> {
+        let mut sps = None;
+        let mut pps = None;
+        for nal in annex_b_nal_units(&keyframe.data) {
+            match nal.first().map(|header| header & 0x1F) {
+                Some(7) if nal.len() >= 4 => sps = Some(nal.to_vec()),
+                Some(8) => pps = Some(nal.to_vec()),
+                _ => {}
+            }
+        }
+        Some(Self {
+            sps: sps?,
+            pps: pps?,
+        })
+    }
+
+    fn fmtp(&self) -> String {
+        format!(
+            "packetization-mode=1;profile-level-id={:02X}{:02X}{:02X};sprop-parameter-sets={},{}",
+            self.sps[1],
+            self.sps[2],
+            self.sps[3],
+            BASE64.encode(&self.sps),
+            BASE64.encode(&self.pps)
+        )
+    }
+}
+
+/// Per-client RTP state
+struct ClientSession {
+    id: String,
+    stream_id: String,
+    feed: RestreamFeed,
+    /// Keyframe consumed while answering DESCRIBE; sent first on PLAY
+    pending_keyframe: Option<AccessUnit>,
+    parameter_sets: ParameterSets,
+    channel: Option<u8>,
+    ssrc: u32,
+    sequence: u16,
+    timestamp_base: u32,
+    forwarder: Option<JoinHandle<()>>,
+}
+
+impl Drop for ClientSession {
+    fn drop(&mut self) {
+        if let Some(forwarder) = self.forwarder.take() {
+            forwarder.abort();
+        }
+    }
+}
+
+/// One client's RTSP control connection
+struct Connection {
+    config: Arc<RestreamServerConfig>,
+    backend: Arc<dyn RestreamBackend>,
+    metrics: RestreamMetrics,
+    /// Stream the client has authenticated for
+    authorized: Option<String>,
+    session: Option<ClientSession>,
+}
+
+impl Connection {
+    async fn serve(mut self, socket: TcpStream, peer: SocketAddr) -> Result<()> {
+        let local = socket.local_addr()?;
+        let (read_half, mut write_half) = socket.into_split();
+        let mut reader = BufReader::new(read_half);
+
+        // Replies and RTP share the socket, so everything goes through one writer task
+        let (writer, mut outgoing) = mpsc::channel::<Bytes>(64);
+        let writer_task = tokio::spawn(async move {
+            while let Some(chunk) = outgoing.recv().await {
+                // An empty chunk asks for the connection to be closed
+                if chunk.is_empty() || write_half.write_all(&chunk).await.is_err() {
+                    break;
+                }
+            }
+            let _ = write_half.shutdown().await;
+        });
+
+        debug!(peer = %peer, "RTSP client connected");
+        let result = loop {
+            let request =
+                match tokio::time::timeout(self.config.session_timeout, read_request(&mut reader))
+                    .await
+                {
+                    Ok(Ok(Some(request))) => request,
+                    Ok(Ok(None)) => break Ok(()),
+                    Ok(Err(e)) => break Err(e),
+                    Err(_) => {
+                        debug!(peer = %peer, "RTSP client timed out");
+                        break Ok(());
+                    }
+                };
+
+            let (reply, close) = self.handle(&request, local, &writer).await;
+            if writer.send(reply).await.is_err() {
+                break Ok(());
+            }
+            if close {
+                break Ok(());
+            }
+            if request.method == "PLAY" {
+                self.start_forwarding(&writer);
+            }
+        };
+
+        // Stops the forwarder and releases the upstream
+        self.session = None;
+        let _ = writer.send(Bytes::new()).await;
+        drop(writer);
+        let _ = writer_task.await;
+        self.metrics.clients.dec();
+        debug!(peer = %peer, "RTSP client disconnected");
+        result
+    }
+
+    /// Build the reply to one request and whether to close afterwards
+    async fn handle(
+        &mut self,
+        request: &Request,
+        local: SocketAddr,
+        writer: &mpsc::Sender<Bytes>,
+    ) -> (Bytes, bool) {
+        let cseq = request.cseq().to_string();
+        match request.method.as_str() {
+            "OPTIONS" => (
+                reply(
+                    200,
+                    &cseq,
+                    &[(
+                        "Public",
+                        "OPTIONS, DESCRIBE, SETUP, PLAY, PAUSE, TEARDOWN, GET_PARAMETER",
+                    )],
+                    "",
+                ),
+                false,
+            ),
+            "GET_PARAMETER" | "SET_PARAMETER" => (self.session_reply(200, &cseq), false),
+            "DESCRIBE" | "SETUP" | "PLAY" | "PAUSE" => {
+                let Some(stream_id) = request.stream_id() else {
+                    return (reply(404, &cseq, &[], ""), false);
+                };
+                if let Some(challenge) = self.check_auth(request, &stream_id).await {
+                    return (challenge, false);
+                }
+                let result = match request.method.as_str() {
+                    "DESCRIBE" => self.describe(request, &stream_id, local).await,
+                    "SETUP" => self.setup(request, &stream_id).await,
+                    "PLAY" => self.play(request),
+                    _ => self.pause(request),
+                };
+                match result {
+                    Ok(response) => (response, false),
+                    Err(status) => (reply(status, &cseq, &[], ""), false),
+                }
+            }
+            "TEARDOWN" => {
+                let response = self.session_reply(200, &cseq);
+                self.session = None;
+                // Let the reply go out before the connection closes
+                let _ = writer.send(response).await;
+                (Bytes::new(), true)
+            }
+            _ => (reply(501, &cseq, &[], ""), false),
+        }
+    }
+
+    /// `None` if the client may use the stream, otherwise the reply to send
+    async fn check_auth(&mut self, request: &Request, stream_id: &str) -> Option<Bytes> {
+        if self.authorized.as_deref() == Some(stream_id) {
+            return None;
+        }
+
+        if let Some((username, password)) = request.basic_credentials() {
+            match self
+                .backend
+                .authorize(stream_id, &username, &password)
+                .await
+            {
+                Ok(true) => {
+                    self.authorized = Some(stream_id.to_string());
+                    return None;
+                }
+                Ok(false) => {
+                    self.metrics.auth_failures.inc();
+                    debug!(stream_id = %stream_id, username = %username, "RTSP client rejected");
+                }
+                Err(e) => {
+                    warn!(stream_id = %stream_id, error = %e, "RTSP authorization failed");
+                    return Some(reply(500, request.cseq(), &[], ""));
+                }
+            }
+        }
+
+        let challenge = format!("Basic realm=\"{}\"", self.config.realm);
+        Some(reply(
+            401,
+            request.cseq(),
+            &[("WWW-Authenticate", &challenge)],
+            "",
+        ))
+    }
+
+    /// Attach to the upstream and wait for a keyframe to describe
+    async fn open_session(&mut self, stream_id: &str) -> std::result::Result<(), u16> {
+        if self
+            .session
+            .as_ref()
+            .is_some_and(|session| session.stream_id == stream_id)
+        {
+            return Ok(());
+        }
+
+        let mut feed = match self.backend.subscribe(stream_id).await {
+            Ok(Some(feed)) => feed,
+            Ok(None) => return Err(404),
+            Err(e) => {
+                warn!(stream_id = %stream_id, error = %e, "Failed to attach to upstream");
+                return Err(503);
+            }
+        };
+
+        let deadline = Instant::now() + self.config.keyframe_timeout;
+        let keyframe = loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match tokio::time::timeout(remaining, feed.units.recv()).await {
+                Ok(Ok(unit)) if unit.keyframe => break unit,
+                Ok(Ok(_)) | Ok(Err(broadcast::error::RecvError::Lagged(_))) => continue,
+                Ok(Err(broadcast::error::RecvError::Closed)) | Err(_) => {
+                    warn!(stream_id = %stream_id, "No keyframe from upstream");
+                    return Err(503);
+                }
+            }
+        };
+
+        if keyframe.codec != VideoCodec::H264 {
+            warn!(stream_id = %stream_id, codec = ?keyframe.codec, "Only H.264 can be restreamed");
+            return Err(415);
+        }
+        let Some(parameter_sets) = ParameterSets::from_keyframe(&keyframe) else {
+            warn!(stream_id = %stream_id, "Upstream keyframe has no SPS/PPS");
+            return Err(503);
+        };
+
+        let random = Uuid::new_v4().as_u128();
+        self.session = Some(ClientSession {
+            id: format!("{:016X}", random as u64),
+            stream_id: stream_id.to_string(),
+            feed,
+            pending_keyframe: Some(keyframe),
+            parameter_sets,
+            channel: None,
+            ssrc: (random >> 64) as u32,
+            sequence: (random >> 96) as u16,
+            timestamp_base: (random >> 32) as u32,
+            forwarder: None,
+        });
+        Ok(())
+    }
+
+    async fn describe(
+        &mut self,
+        request: &Request,
+        stream_id: &str,
+        local: SocketAddr,
+    ) -> std::result::Result<Bytes, u16> {
+        self.open_session(stream_id).await?;
+        let session = self.session.as_ref().expect("session was just opened");
+
+        let address = match local.ip() {
+            std::net::IpAddr::V4(ip) => format!("IN IP4 {}", ip),
+            std::net::IpAddr::V6(ip) => format!("IN IP6 {}", ip),
+        };
+        let mut sdp = String::new();
+        let _ = write!(
+            sdp,
+            "v=0\r\no=- {} 1 {}\r\ns={}\r\nc={}\r\nt=0 0\r\na=control:*\r\n",
+            session.ssrc, address, stream_id, address
+        );
+        let _ = write!(
+            sdp,
+            "m=video 0 RTP/AVP {pt}\r\na=rtpmap:{pt} H264/90000\r\na=fmtp:{pt} {}\r\na=control:trackID=0\r\n",
+            session.parameter_sets.fmtp(),
+            pt = PAYLOAD_TYPE
+        );
+
+        let base = format!("{}/", request.uri.trim_end_matches('/'));
+        Ok(reply(
+            200,
+            request.cseq(),
+            &[("Content-Base", &base), ("Content-Type", "application/sdp")],
+            &sdp,
+        ))
+    }
+
+    async fn setup(
+        &mut self,
+        request: &Request,
+        stream_id: &str,
+    ) -> std::result::Result<Bytes, u16> {
+        let transport = request.header("Transport").unwrap_or_default();
+        // Interleaved TCP is the only transport; UDP clients retry with TCP on 461
+        if !transport.contains("RTP/AVP/TCP") && !transport.contains("interleaved=") {
+            return Err(461);
+        }
+        let channel = transport
+            .split(';')
+            .find_map(|part| part.trim().strip_prefix("interleaved="))
+            .and_then(|channels| channels.split('-').next()?.parse::<u8>().ok())
+            .unwrap_or(0);
+
+        self.open_session(stream_id).await?;
+        let session = self.session.as_mut().expect("session was just opened");
+        session.channel = Some(channel);
+
+        let transport = format!(
+            "RTP/AVP/TCP;unicast;interleaved={}-{};ssrc={:08X}",
+            channel,
+            channel.wrapping_add(1),
+            session.ssrc
+        );
+        let session_header = format!(
+            "{};timeout={}",
+            session.id,
+            self.config.session_timeout.as_secs()
+        );
+        Ok(reply(
+            200,
+            request.cseq(),
+            &[("Transport", &transport), ("Session", &session_header)],
+            "",
+        ))
+    }
+
+    fn play(&mut self, request: &Request) -> std::result::Result<Bytes, u16> {
+        let session = self.session_for(request)?;
+        if session.channel.is_none() {
+            return Err(455);
+        }
+        let rtp_info = format!(
+            "url={}/trackID=0;seq={};rtptime={}",
+            request.uri.trim_end_matches('/'),
+            session.sequence,
+            session.timestamp_base
+        );
+        let id = session.id.clone();
+        Ok(reply(
+            200,
+            request.cseq(),
+            &[
+                ("Session", &id),
+                ("Range", "npt=0.000-"),
+                ("RTP-Info", &rtp_info),
+            ],
+            "",
+        ))
+    }
+
+    fn pause(&mut self, request: &Request) -> std::result::Result<Bytes, u16> {
+        let session = self.session_for(request)?;
+        if let Some(forwarder) = session.forwarder.take() {
+            forwarder.abort();
+        }
+        let id = session.id.clone();
+        Ok(reply(200, request.cseq(), &[("Session", &id)], ""))
+    }
+
+    /// The client's session, checking the Session header matches
+    fn session_for(&mut self, request: &Request) -> std::result::Result<&mut ClientSession, u16> {
+        let offered = request
+            .header("Session")
+            .map(|value| value.split(';').next().unwrap_or_default().trim());
+        match self.session.as_mut() {
+            Some(session) if offered == Some(session.id.as_str()) => Ok(session),
+            _ => Err(454),
+        }
+    }
+
+    fn session_reply(&self, status: u16, cseq: &str) -> Bytes {
+        match &self.session {
+            Some(session) => reply(status, cseq, &[("Session", &session.id)], ""),
+            None => reply(status, cseq, &[], ""),
+        }
+    }
+
+    /// Spawn the task that packetizes upstream access units for this client
+    fn start_forwarding(&mut self, writer: &mpsc::Sender<Bytes>) {
+        let Some(session) = self.session.as_mut() else {
+            return;
+        };
+        if session.forwarder.is_some() {
+            return;
+        }
+        let Some(channel) = session.channel else {
+            return;
+        };
+
+        // Hand the forwarder the receiver that has been buffering since DESCRIBE so
+        // playback starts at the keyframe already seen, and keep a fresh one for resumes
+        let resumed = session.feed.units.resubscribe();
+        let units = std::mem::replace(&mut session.feed.units, resumed);
+        let mut packetizer = Packetizer {
+            channel,
+            ssrc: session.ssrc,
+            sequence: session.sequence,
+            timestamp_base: session.timestamp_base,
+            first_pts: None,
+        };
+        let first = session.pending_keyframe.take();
+        let writer = writer.clone();
+        let metrics = self.metrics.clone();
+        let stall_timeout = self.config.stall_timeout;
+        let stream_id = session.stream_id.clone();
+
+        session.forwarder = Some(tokio::spawn(async move {
+            forward(
+                units,
+                first,
+                &mut packetizer,
+                &writer,
+                &metrics,
+                stall_timeout,
+            )
+            .await;
+            info!(stream_id = %stream_id, "Upstream stopped; closing RTSP client");
+            let _ = writer.send(Bytes::new()).await;
+        }));
+    }
+}
+
+/// Copy access units to the client until the upstream stalls or the client goes away
+async fn forward(
+    mut units: broadcast::Receiver<AccessUnit>,
+    first: Option<AccessUnit>,
+    packetizer: &mut Packetizer,
+    writer: &mpsc::Sender<Bytes>,
+    metrics: &RestreamMetrics,
+    stall_timeout: Duration,
+) {
+    let mut need_keyframe = true;
+    if let Some(keyframe) = first {
+        if writer.send(packetizer.packetize(&keyframe)).await.is_err() {
+            return;
+        }
+        metrics.units_forwarded.inc();
+        need_keyframe = false;
+    }
+
+    loop {
+        let unit = match tokio::time::timeout(stall_timeout, units.recv()).await {
+            Ok(Ok(unit)) => unit,
+            Ok(Err(broadcast::error::RecvError::Lagged(skipped))) => {
+                // A slow client resumes at the next keyframe rather than showing corruption
+                metrics.units_dropped.inc_by(skipped);
+                need_keyframe = true;
+                continue;
+            }
+            Ok(Err(broadcast::error::RecvError::Closed)) => return,
+            Err(_) => {
+                metrics.upstream_stalls.inc();
+                return;
+            }
+        };
+        if need_keyframe && !unit.keyframe {
+            continue;
+        }
+        need_keyframe = false;
+
+        if writer.send(packetizer.packetize(&unit)).await.is_err() {
+            return;
+        }
+        metrics.units_forwarded.inc();
+    }
+}
+
+/// RFC 6184 packetizer writing interleaved RTP frames
+struct Packetizer {
+    channel: u8,
+    ssrc: u32,
+    sequence: u16,
+    timestamp_base: u32,
+    first_pts: Option<Duration>,
+}
+
+impl Packetizer {
+    /// All RTP packets for one access unit, framed for the interleaved channel
+    fn packetize(&mut self, unit: &AccessUnit) -> Bytes {
+        let first_pts = *self.first_pts.get_or_insert(unit.pts);
+        let elapsed = unit.pts.saturating_sub(first_pts);
+        let timestamp = self
+            .timestamp_base
+            .wrapping_add((elapsed.as_micros() * 90 / 1000) as u32);
+
+        let nal_units: Vec<&[u8]> = annex_b_nal_units(&unit.data).collect();
+        let mut out = BytesMut::with_capacity(unit.data.len() + nal_units.len() * 20);
+
+        for (index, nal) in nal_units.iter().enumerate() {
+            let last_nal = index + 1 == nal_units.len();
+            if nal.len() <= MAX_RTP_PAYLOAD {
+                self.push_packet(&mut out, timestamp, last_nal, &[nal]);
+                continue;
+            }
+
+            // FU-A: the NAL header is split into indicator and header bytes
+            let indicator = (nal[0] & 0xE0) | 28;
+            let nal_type = nal[0] & 0x1F;
+            let chunks: Vec<&[u8]> = nal[1..].chunks(MAX_RTP_PAYLOAD - 2).collect();
+            for (chunk_index, chunk) in chunks.iter().enumerate() {
+                let start = chunk_index == 0;
+                let end = chunk_index + 1 == chunks.len();
+                let header = nal_type | if start { 0x80 } else { 0 } | if end { 0x40 } else { 0 };
+                self.push_packet(
+                    &mut out,
+                    timestamp,
+                    last_nal && end,
+                    &[&[indicator, header], chunk],
+                );
+            }
+        }
+        out.freeze()
+    }
+
+    fn push_packet(&mut self, out: &mut BytesMut, timestamp: u32, marker: bool, payload: &[&[u8]]) {
+        let payload_len: usize = payload.iter().map(|part| part.len()).sum();
+        out.put_u8(b'$');
+        out.put_u8(self.channel);
+        out.put_u16((12 + payload_len) as u16);
+        out.put_u8(0x80);
+        out.put_u8(if marker { 0x80 } else { 0 } | PAYLOAD_TYPE);
+        out.put_u16(self.sequence);
+        out.put_u32(timestamp);
+        out.put_u32(self.ssrc);
+        for part in payload {
+            out.put_slice(part);
+        }
+        self.sequence = self.sequence.wrapping_add(1);
+    }
+}
+
+/// NAL units in an Annex B byte stream, without start codes
+fn annex_b_nal_units(data: &[u8]) -> impl Iterator<Item = &[u8]> {
+    let mut starts = Vec::new();
+    let mut i = 0;
+    while i + 3 <= data.len() {
+        if data[i] == 0 && data[i + 1] == 0 && data[i + 2] == 1 {
+            starts.push(i + 3);
+            i += 3;
+        } else {
+            i += 1;
+        }
+    }
+
+    let ends: Vec<usize> = starts
+        .iter()
+        .skip(1)
+        .map(|&next| {
+            // Drop the start code and the zero byte of a four-byte start code
+            let mut end = next - 3;
+            while end > 0 && data[end - 1] == 0 {
+                end -= 1;
+            }
+            end
+        })
+        .chain(std::iter::once(data.len()))
+        .collect();
+
+    starts
+        .into_iter()
+        .zip(ends)
+        .filter(|(start, end)| end > start)
+        .map(move |(start, end)| &data[start..end])
+}
+
+/// Read one request, skipping any interleaved RTCP the client sends
+async fn read_request(reader: &mut BufReader<OwnedReadHalf>) -> Result<Option<Request>> {
+    loop {
+        let buffered = reader.fill_buf().await?;
+        if buffered.is_empty() {
+            return Ok(None);
+        }
+        if buffered[0] != b'$' {
+            break;
+        }
+        let mut header = [0u8; 4];
+        reader.read_exact(&mut header).await?;
+        let len = u16::from_be_bytes([header[2], header[3]]) as usize;
+        let mut discard = vec![0u8; len];
+        reader.read_exact(&mut discard).await?;
+    }
+
+    let mut lines = Vec::new();
+    let mut total = 0;
+    loop {
+        let mut line = String::new();
+        let read = reader.read_line(&mut line).await?;
+        if read == 0 {
+            return Ok(None);
+        }
+        total += read;
+        if total > MAX_REQUEST_BYTES {
+            return Err(Error::Validation("RTSP request too large".to_string()));
+        }
+        let line = line.trim_end().to_string();
+        if line.is_empty() {
+            if lines.is_empty() {
+                continue;
+            }
+            break;
+        }
+        lines.push(line);
+    }
+
+    let mut request_line = lines[0].split_whitespace();
+    let method = request_line.next().unwrap_or_default().to_uppercase();
+    let uri = request_line.next().unwrap_or_default().to_string();
+    let headers: Vec<(String, String)> = lines[1..]
+        .iter()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            Some((key.trim().to_string(), value.trim().to_string()))
+        })
+        .collect();
+
+    let request = Request {
+        method,
+        uri,
+        headers,
+    };
+
+    // Bodies (SET_PARAMETER and friends) are read and ignored
+    if let Some(len) = request
+        .header("Content-Length")
+        .and_then(|value| value.parse::<usize>().ok())
+    {
+        if len > MAX_REQUEST_BYTES {
+            return Err(Error::Validation("RTSP request body too large".to_string()));
+        }
+        let mut body = vec![0u8; len];
+        reader.read_exact(&mut body).await?;
+    }
+
+    Ok(Some(request))
+}
+
+/// Format a response with the standard headers
+fn reply(status: u16, cseq: &str, headers: &[(&str, &str)], body: &str) -> Bytes {
+    let reason = match status {
+        200 => "OK",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        415 => "Unsupported Media Type",
+        454 => "Session Not Found",
+        455 => "Method Not Valid in This State",
+        461 => "Unsupported Transport",
+        500 => "Internal Server Error",
+        501 => "Not Implemented",
+        503 => "Service Unavailable",
+        _ => "Error",
+    };
+    let mut response = format!(
+        "RTSP/1.0 {} {}\r\nCSeq: {}\r\nServer: glimpser/{}\r\n",
+        status,
+        reason,
+        cseq,
+        env!("CARGO_PKG_VERSION")
+    );
+    for (name, value) in headers {
+        let _ = write!(response, "{}: {}\r\n", name, value);
+    }
+    if !body.is_empty() {
+        let _ = write!(response, "Content-Length: {}\r\n", body.len());
+    }
+    response.push_str("\r\n");
+    response.push_str(body);
+    Bytes::from(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gl_capture::{
+        CaptureSource, KeyframeDecoder, NativeRtspConfig, NativeRtspSource, SnapshotConfig,
+    };
+    use std::sync::Mutex;
+
+    const SPS: &[u8] = b"\x67\x64\x00\x1e\xac\x2c\x6a\x82\xc0\xf6\x9b\x82\x80\x82\xa0\x00\x00\x03\x00\x20\x00\x00\x03\x03\xd0\x80\x00";
+    const PPS: &[u8] = b"\x68\xee\x31\xb2\x1b";
+
+    /// Backend with one stream fed from a broadcast channel
+    struct FakeBackend {
+        units: broadcast::Sender<AccessUnit>,
+        subscriptions: Mutex<usize>,
+    }
+
+    #[async_trait]
+    impl RestreamBackend for FakeBackend {
+        async fn authorize(&self, stream_id: &str, username: &str, password: &str) -> Result<bool> {
+            Ok(stream_id == "cam1" && username == "viewer" && password == "secret")
+        }
+
+        async fn subscribe(&self, stream_id: &str) -> Result<Option<RestreamFeed>> {
+            if stream_id != "cam1" {
+                return Ok(None);
+            }
+            *self.subscriptions.lock().unwrap() += 1;
+            Ok(Some(RestreamFeed {
+                units: self.units.subscribe(),
+                upstream: None,
+            }))
+        }
+    }
+
+    #[derive(Debug)]
+    struct NoDecoder;
+
+    #[async_trait]
+    impl KeyframeDecoder for NoDecoder {
+        async fn decode(&self, _keyframe: &AccessUnit, _config: &SnapshotConfig) -> Result<Bytes> {
+            Err(Error::Config("not used".to_string()))
+        }
+    }
+
+    fn access_unit(sequence: u64, keyframe: bool) -> AccessUnit {
+        let mut data = Vec::new();
+        if keyframe {
+            for nal in [SPS, PPS] {
+                data.extend_from_slice(b"\x00\x00\x00\x01");
+                data.extend_from_slice(nal);
+            }
+            // Larger than one packet so it goes out as FU-A fragments
+            data.extend_from_slice(b"\x00\x00\x00\x01\x65");
+            data.extend((0..5000u32).map(|i| (i % 251) as u8 | 1));
+        } else {
+            data.extend_from_slice(b"\x00\x00\x00\x01\x41delta");
+        }
+        AccessUnit {
+            codec: VideoCodec::H264,
+            data: Bytes::from(data),
+            keyframe,
+            pts: Duration::from_millis(sequence * 40),
+            sequence,
+            received_at: std::time::Instant::now(),
+        }
+    }
+
+    async fn start_server() -> (SocketAddr, Arc<FakeBackend>) {
+        let (units, _) = broadcast::channel(64);
+        let backend = Arc::new(FakeBackend {
+            units: units.clone(),
+            subscriptions: Mutex::new(0),
+        });
+        let server = RestreamServer::bind(
+            RestreamServerConfig {
+                bind_addr: "127.0.0.1:0".parse().unwrap(),
+                keyframe_timeout: Duration::from_secs(5),
+                ..Default::default()
+            },
+            backend.clone(),
+        )
+        .await
+        .unwrap();
+        let addr = server.local_addr().unwrap();
+        tokio::spawn(server.run());
+
+        // Camera sending a keyframe every fifth picture
+        tokio::spawn(async move {
+            for sequence in 0u64.. {
+                let _ = units.send(access_unit(sequence, sequence % 5 == 0));
+                tokio::time::sleep(Duration::from_millis(40)).await;
+            }
+        });
+        (addr, backend)
+    }
+
+    fn client(addr: SocketAddr, path: &str, password: &str) -> NativeRtspSource {
+        NativeRtspSource::with_decoder(
+            NativeRtspConfig {
+                url: format!("rtsp://viewer:{}@{}/{}", password, addr, path),
+                connect_timeout: Duration::from_secs(5),
+                ..Default::default()
+            },
+            Arc::new(NoDecoder),
+        )
+    }
+
+    #[test]
+    fn test_annex_b_split() {
+        let data = b"\x00\x00\x00\x01\x67abc\x00\x00\x01\x68de\x00\x00\x00\x01\x65f";
+        let nal_units: Vec<&[u8]> = annex_b_nal_units(data).collect();
+        assert_eq!(nal_units, vec![&b"\x67abc"[..], b"\x68de", b"\x65f"]);
+    }
+
+    #[test]
+    fn test_fu_a_fragments_large_nal_units() {
+        let mut packetizer = Packetizer {
+            channel: 0,
+            ssrc: 1,
+            sequence: 65535,
+            timestamp_base: 0,
+            first_pts: None,
+        };
+        let out = packetizer.packetize(&access_unit(0, true));
+
+        let mut packets = Vec::new();
+        let mut rest = &out[..];
+        while !rest.is_empty() {
+            assert_eq!(rest[0], b'$');
+            let len = u16::from_be_bytes([rest[2], rest[3]]) as usize;
+            packets.push(&rest[4..4 + len]);
+            rest = &rest[4 + len..];
+        }
+
+        // SPS, PPS, then four FU-A fragments of the 5001 byte IDR
+        assert_eq!(packets.len(), 6);
+        // The SPS's trailing zero byte belongs to the byte stream, not the NAL unit
+        assert_eq!(&packets[0][12..], &SPS[..SPS.len() - 1]);
+        assert_eq!(packets[2][12], 0x60 | 28);
+        assert_eq!(packets[2][13], 0x80 | 5);
+        assert_eq!(packets[5][13], 0x40 | 5);
+        // Only the last packet carries the marker, and sequence numbers wrap
+        assert!(packets[..5].iter().all(|p| p[1] & 0x80 == 0));
+        assert_eq!(packets[5][1] & 0x80, 0x80);
+        assert_eq!(u16::from_be_bytes([packets[1][2], packets[1][3]]), 0);
+    }
+
+    #[tokio::test]
+    async fn test_clients_share_one_upstream_feed() {
+        let (addr, backend) = start_server().await;
+
+        let first = client(addr, "cam1", "secret");
+        let second = client(addr, "cam1", "secret");
+        let first_handle = first.start().await.unwrap();
+        let second_handle = second.start().await.unwrap();
+
+        for handle in [&first_handle, &second_handle] {
+            let mut units = handle.access_units().unwrap();
+            let mut saw_keyframe = false;
+            let mut saw_delta = false;
+            while !(saw_keyframe && saw_delta) {
+                let unit = tokio::time::timeout(Duration::from_secs(5), units.recv())
+                    .await
+                    .expect("timed out waiting for restreamed unit")
+                    .unwrap();
+                if unit.keyframe {
+                    // The fragmented IDR is reassembled byte for byte
+                    let idr = &access_unit(0, true).data[SPS.len() + PPS.len() + 8..];
+                    assert!(unit.data.windows(idr.len()).any(|w| w == idr));
+                    saw_keyframe = true;
+                } else {
+                    assert!(unit.data.ends_with(b"\x41delta"));
+                    saw_delta = true;
+                }
+            }
+        }
+
+        // Each client attaches to the backend's single upstream feed
+        assert_eq!(*backend.subscriptions.lock().unwrap(), 2);
+        first_handle.stop().await.unwrap();
+        second_handle.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_clients_need_valid_credentials() {
+        let (addr, backend) = start_server().await;
+
+        assert!(client(addr, "cam1", "wrong").start().await.is_err());
+        assert!(client(addr, "other", "secret").start().await.is_err());
+        assert_eq!(*backend.subscriptions.lock().unwrap(), 0);
+    }
+}
@@ -1,5 +1,5 @@
 //! ABOUTME: Benchmark tests comparing motion detection algorithm performance
-//! ABOUTME: Uses criterion for statistical analysis of PixelDiff vs MOG2 performance
+//! ABOUTME: Uses criterion for statistical analysis of PixelDiff, GaussianMixture and MOG2
 
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 use gl_vision::{
@@ -169,6 +169,29 @@ fn bench_algorithm_comparison(c: &mut Criterion) {
         });
     });
 
+    // Pure-Rust Gaussian mixture configuration
+    let gmm_config = MotionConfig {
+        algorithm: MotionAlgorithm::GaussianMixture,
+        threshold: 0.05,
+        min_change_area: 100,
+        downscale_factor: 1,
+        max_width: width,
+        max_height: height,
+    };
+
+    let mut gmm_service = MotionDetectionService::new(gmm_config).unwrap();
+
+    // Initialize the background model
+    for _ in 0..5 {
+        let _ = gmm_service.detect_motion_from_bytes(&frame1_bytes).unwrap();
+    }
+
+    group.bench_function("gaussian_mixture_320x240", |b| {
+        b.iter(|| {
+            gmm_service.detect_motion_from_bytes(&frame2_bytes).unwrap();
+        });
+    });
+
     #[cfg(feature = "heavy_opencv")]
     {
         // MOG2 configuration
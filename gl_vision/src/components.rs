@@ -0,0 +1,73 @@
+//! ABOUTME: Connected-component labelling of boolean pixel masks
+//! ABOUTME: Shared by plate reading and privacy masking to find shapes in thresholded frames
+
+use std::collections::VecDeque;
+
+/// 8-connected group of set pixels in a mask
+pub(crate) struct Shape {
+    pub x0: u32,
+    pub y0: u32,
+    pub x1: u32,
+    pub y1: u32,
+    pub pixels: Vec<(u32, u32)>,
+}
+
+impl Shape {
+    pub fn width(&self) -> u32 {
+        self.x1 - self.x0 + 1
+    }
+
+    pub fn height(&self) -> u32 {
+        self.y1 - self.y0 + 1
+    }
+
+    pub fn center_y(&self) -> f64 {
+        (self.y0 + self.y1) as f64 / 2.0
+    }
+}
+
+/// Every 8-connected shape of a row-major mask, in scan order
+pub(crate) fn connected_shapes(mask: &[bool], width: u32, height: u32) -> Vec<Shape> {
+    let mut visited = vec![false; mask.len()];
+    let mut shapes = Vec::new();
+    let mut queue = VecDeque::new();
+
+    for start in 0..mask.len() {
+        if !mask[start] || visited[start] {
+            continue;
+        }
+        visited[start] = true;
+        queue.push_back(start);
+        let (sx, sy) = ((start as u32) % width, (start as u32) / width);
+        let mut shape = Shape {
+            x0: sx,
+            y0: sy,
+            x1: sx,
+            y1: sy,
+            pixels: Vec::new(),
+        };
+        while let Some(index) = queue.pop_front() {
+            let (x, y) = ((index as u32) % width, (index as u32) / width);
+            shape.x0 = shape.x0.min(x);
+            shape.x1 = shape.x1.max(x);
+            shape.y0 = shape.y0.min(y);
+            shape.y1 = shape.y1.max(y);
+            shape.pixels.push((x, y));
+            for dy in -1i64..=1 {
+                for dx in -1i64..=1 {
+                    let (nx, ny) = (x as i64 + dx, y as i64 + dy);
+                    if nx < 0 || ny < 0 || nx >= width as i64 || ny >= height as i64 {
+                        continue;
+                    }
+                    let next = (ny as u32 * width + nx as u32) as usize;
+                    if mask[next] && !visited[next] {
+                        visited[next] = true;
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        shapes.push(shape);
+    }
+    shapes
+}
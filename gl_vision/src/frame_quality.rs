@@ -0,0 +1,104 @@
+//! ABOUTME: Cheap per-frame statistics used to judge whether a camera feed is usable
+//! ABOUTME: Computes luminance mean/spread and a 64-bit difference hash from a JPEG
+
+use gl_core::{Error, Result};
+use image::imageops::FilterType;
+use image::GrayImage;
+
+/// Width and height luminance statistics are computed at
+const STATS_SIZE: (u32, u32) = (64, 48);
+
+/// Brightness and fingerprint of a single frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameQuality {
+    /// Mean luminance, 0-255
+    pub mean_luma: f64,
+    /// Standard deviation of luminance; near zero for a uniform picture
+    pub luma_stddev: f64,
+    /// Difference hash (dHash) of a 9x8 thumbnail; survives re-encoding and noise
+    pub dhash: u64,
+}
+
+impl FrameQuality {
+    /// Decode an encoded frame (JPEG or PNG) and measure it
+    pub fn from_image_bytes(data: &[u8]) -> Result<Self> {
+        let image = image::load_from_memory(data)
+            .map_err(|e| Error::Validation(format!("Failed to decode frame: {}", e)))?;
+        Ok(Self::from_gray(&image.to_luma8()))
+    }
+
+    /// Measure an already decoded grayscale frame
+    pub fn from_gray(image: &GrayImage) -> Self {
+        let small =
+            image::imageops::resize(image, STATS_SIZE.0, STATS_SIZE.1, FilterType::Triangle);
+        let count = small.len() as f64;
+        let mean_luma = small.iter().map(|&p| p as f64).sum::<f64>() / count;
+        let variance = small
+            .iter()
+            .map(|&p| (p as f64 - mean_luma).powi(2))
+            .sum::<f64>()
+            / count;
+
+        Self {
+            mean_luma,
+            luma_stddev: variance.sqrt(),
+            dhash: dhash(&small),
+        }
+    }
+
+    /// Number of differing hash bits; 0 means the frames look the same
+    pub fn hash_distance(&self, other: &FrameQuality) -> u32 {
+        (self.dhash ^ other.dhash).count_ones()
+    }
+}
+
+/// One bit per horizontally adjacent pixel pair of a 9x8 thumbnail: set when brightness
+/// increases left to right
+fn dhash(image: &GrayImage) -> u64 {
+    let thumb = image::imageops::resize(image, 9, 8, FilterType::Triangle);
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            hash <<= 1;
+            if thumb.get_pixel(x + 1, y)[0] > thumb.get_pixel(x, y)[0] {
+                hash |= 1;
+            }
+        }
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{create_test_frame_with_motion, image_to_jpeg_bytes};
+    use image::{ImageBuffer, Luma};
+
+    #[test]
+    fn uniform_frames_have_no_spread() {
+        let black = FrameQuality::from_gray(&ImageBuffer::from_pixel(320, 240, Luma([2u8])));
+        assert!(black.mean_luma < 3.0);
+        assert!(black.luma_stddev < 0.5);
+
+        let white = FrameQuality::from_gray(&ImageBuffer::from_pixel(320, 240, Luma([254u8])));
+        assert!(white.mean_luma > 253.0);
+    }
+
+    #[test]
+    fn hash_survives_reencoding_but_not_scene_changes() {
+        let scene = create_test_frame_with_motion(320, 240, 40, 40, 120, 80, 220);
+        let raw = FrameQuality::from_gray(&scene);
+        let decoded =
+            FrameQuality::from_image_bytes(&image_to_jpeg_bytes(&scene).unwrap()).unwrap();
+        assert!(raw.hash_distance(&decoded) <= 2);
+        assert!(raw.luma_stddev > 20.0);
+
+        let moved = create_test_frame_with_motion(320, 240, 180, 120, 120, 80, 220);
+        assert!(raw.hash_distance(&FrameQuality::from_gray(&moved)) > 8);
+    }
+
+    #[test]
+    fn undecodable_frames_are_rejected() {
+        assert!(FrameQuality::from_image_bytes(b"not an image").is_err());
+    }
+}
@@ -0,0 +1,443 @@
+//! ABOUTME: Pure-Rust adaptive background subtraction with a per-pixel Gaussian mixture
+//! ABOUTME: MOG2-style model with learning rate, shadow suppression and speckle removal
+
+use crate::{MotionConfig, MotionDetector, MotionResult};
+use gl_core::{Error, Result};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// Background model settings for the Gaussian mixture detector
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GaussianMixtureConfig {
+    /// Gaussians kept per pixel; two or more let swaying foliage become background
+    pub components: usize,
+    /// How fast the model adapts (0.0-1.0); roughly 1 / frames of history
+    pub learning_rate: f32,
+    /// Squared distance in standard deviations for a pixel to match the background
+    pub var_threshold: f32,
+    /// Squared distance for a pixel to update an existing component instead of a new one
+    pub var_threshold_gen: f32,
+    /// Total weight of the components that make up the background
+    pub background_ratio: f32,
+    /// Variance given to new components
+    pub initial_variance: f32,
+    pub min_variance: f32,
+    pub max_variance: f32,
+    /// Ignore pixels that are a darker copy of the background (shadows)
+    pub detect_shadows: bool,
+    /// Darkest ratio to the background still treated as shadow
+    pub shadow_threshold: f32,
+    /// Remove isolated foreground pixels before counting (3x3 opening)
+    pub remove_speckle: bool,
+}
+
+impl Default for GaussianMixtureConfig {
+    fn default() -> Self {
+        Self {
+            components: 3,
+            learning_rate: 0.02,
+            var_threshold: 16.0,
+            var_threshold_gen: 9.0,
+            background_ratio: 0.9,
+            initial_variance: 225.0,
+            min_variance: 16.0,
+            max_variance: 5.0 * 225.0,
+            detect_shadows: true,
+            shadow_threshold: 0.5,
+            remove_speckle: true,
+        }
+    }
+}
+
+/// One Gaussian of a pixel's mixture
+#[derive(Debug, Clone, Copy, Default)]
+struct Component {
+    weight: f32,
+    mean: f32,
+    variance: f32,
+}
+
+/// Per-pixel classification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Class {
+    Background,
+    Shadow,
+    Foreground,
+}
+
+/// Motion detector that compares frames against a learned background rather than the
+/// previous frame, so repetitive motion and gradual lighting changes fade into the model
+pub struct GaussianMixtureDetector {
+    config: MotionConfig,
+    model_config: GaussianMixtureConfig,
+    /// `components` entries per pixel, strongest first
+    model: Vec<Component>,
+    /// Components in use per pixel
+    used: Vec<u8>,
+    /// Foreground pixels of the last analyzed frame
+    mask: Vec<bool>,
+    frame_width: u32,
+    frame_height: u32,
+}
+
+impl GaussianMixtureDetector {
+    /// Create a detector with the default background model
+    pub fn new(config: MotionConfig) -> Result<Self> {
+        Self::with_model_config(config, GaussianMixtureConfig::default())
+    }
+
+    /// Create a detector with custom background model settings
+    pub fn with_model_config(
+        config: MotionConfig,
+        model_config: GaussianMixtureConfig,
+    ) -> Result<Self> {
+        if !(1..=8).contains(&model_config.components) {
+            return Err(Error::Validation(
+                "Gaussian mixture needs between 1 and 8 components".to_string(),
+            ));
+        }
+        if !(0.0..=1.0).contains(&model_config.learning_rate) {
+            return Err(Error::Validation(
+                "Gaussian mixture learning rate must be between 0.0 and 1.0".to_string(),
+            ));
+        }
+        Ok(Self {
+            config,
+            model_config,
+            model: Vec::new(),
+            used: Vec::new(),
+            mask: Vec::new(),
+            frame_width: 0,
+            frame_height: 0,
+        })
+    }
+
+    fn initialize(&mut self, frame: &[u8], width: u32, height: u32) {
+        let k = self.model_config.components;
+        self.model = vec![Component::default(); frame.len() * k];
+        self.used = vec![1; frame.len()];
+        self.mask.clear();
+        for (i, &value) in frame.iter().enumerate() {
+            self.model[i * k] = Component {
+                weight: 1.0,
+                mean: value as f32,
+                variance: self.model_config.initial_variance,
+            };
+        }
+        self.frame_width = width;
+        self.frame_height = height;
+    }
+
+    /// Classify one pixel and fold it into its mixture
+    fn update_pixel(&mut self, index: usize, value: f32, min_difference: f32) -> Class {
+        let cfg = &self.model_config;
+        let k = cfg.components;
+        let alpha = cfg.learning_rate;
+        let used = self.used[index] as usize;
+        let mixture = &mut self.model[index * k..(index + 1) * k];
+
+        // Classify against the background components before adapting
+        let mut class = Class::Foreground;
+        let mut cumulative = 0.0;
+        for component in &mixture[..used] {
+            let diff = value - component.mean;
+            if diff * diff < cfg.var_threshold * component.variance || diff.abs() <= min_difference
+            {
+                class = Class::Background;
+                break;
+            }
+            cumulative += component.weight;
+            if cumulative > cfg.background_ratio {
+                break;
+            }
+        }
+        if class == Class::Foreground && cfg.detect_shadows {
+            let mut cumulative = 0.0;
+            for component in &mixture[..used] {
+                let ratio = value / component.mean.max(1.0);
+                if ratio >= cfg.shadow_threshold && ratio < 1.0 {
+                    class = Class::Shadow;
+                    break;
+                }
+                cumulative += component.weight;
+                if cumulative > cfg.background_ratio {
+                    break;
+                }
+            }
+        }
+
+        // Update weights, and mean/variance of the first close component
+        let mut matched = None;
+        for (i, component) in mixture[..used].iter_mut().enumerate() {
+            let diff = value - component.mean;
+            if matched.is_none() && diff * diff < cfg.var_threshold_gen * component.variance {
+                component.weight += alpha * (1.0 - component.weight);
+                let rho = alpha / component.weight.max(alpha);
+                component.mean += rho * diff;
+                component.variance = (component.variance
+                    + rho * (diff * diff - component.variance))
+                    .clamp(cfg.min_variance, cfg.max_variance);
+                matched = Some(i);
+            } else {
+                component.weight *= 1.0 - alpha;
+            }
+        }
+
+        let mut used = used;
+        let position = match matched {
+            Some(i) => i,
+            None => {
+                // Replace the weakest component (or take a free slot) with the new value
+                let slot = if used < k {
+                    used += 1;
+                    used - 1
+                } else {
+                    k - 1
+                };
+                mixture[slot] = Component {
+                    weight: alpha.max(f32::EPSILON),
+                    mean: value,
+                    variance: cfg.initial_variance,
+                };
+                slot
+            }
+        };
+
+        let total: f32 = mixture[..used].iter().map(|c| c.weight).sum();
+        if total > 0.0 {
+            for component in &mut mixture[..used] {
+                component.weight /= total;
+            }
+        }
+        // Keep strongest first; only the touched component can have moved up
+        let mut i = position;
+        while i > 0 && mixture[i].weight > mixture[i - 1].weight {
+            mixture.swap(i, i - 1);
+            i -= 1;
+        }
+
+        self.used[index] = used as u8;
+        class
+    }
+
+    fn confidence(&self, changed_pixels: u32, change_ratio: f64, motion_detected: bool) -> f64 {
+        let area_confidence =
+            (changed_pixels as f64 / self.config.min_change_area.max(1) as f64).min(1.0);
+        let threshold_confidence =
+            (change_ratio / self.config.threshold.max(f64::EPSILON)).min(1.0);
+        if motion_detected {
+            (area_confidence * 0.6 + threshold_confidence * 0.4).clamp(0.7, 0.99)
+        } else {
+            (area_confidence * 0.5).min(0.6)
+        }
+    }
+}
+
+/// 3x3 erosion followed by dilation; drops foreground blobs smaller than 3x3
+fn open_mask(mask: &[bool], width: usize, height: usize) -> Vec<bool> {
+    let eroded = filter_3x3(mask, width, height, true);
+    filter_3x3(&eroded, width, height, false)
+}
+
+/// Erode (every neighbour set) or dilate (any neighbour set) a mask
+fn filter_3x3(mask: &[bool], width: usize, height: usize, erode: bool) -> Vec<bool> {
+    let mut output = vec![false; mask.len()];
+    for y in 0..height {
+        for x in 0..width {
+            let mut result = erode;
+            'window: for ny in y.saturating_sub(1)..(y + 2).min(height) {
+                for nx in x.saturating_sub(1)..(x + 2).min(width) {
+                    if mask[ny * width + nx] != erode {
+                        result = !erode;
+                        break 'window;
+                    }
+                }
+            }
+            output[y * width + x] = result;
+        }
+    }
+    output
+}
+
+impl MotionDetector for GaussianMixtureDetector {
+    fn detect_motion(
+        &mut self,
+        current_frame: &[u8],
+        frame_width: u32,
+        frame_height: u32,
+    ) -> Result<MotionResult> {
+        let start_time = std::time::Instant::now();
+        let total_pixels = frame_width * frame_height;
+        if current_frame.len() != total_pixels as usize {
+            return Err(Error::Validation(
+                "Frame data does not match its dimensions".to_string(),
+            ));
+        }
+
+        if self.frame_width != frame_width
+            || self.frame_height != frame_height
+            || self.used.len() != current_frame.len()
+        {
+            debug!("Initializing Gaussian mixture background model");
+            self.initialize(current_frame, frame_width, frame_height);
+            return Ok(MotionResult::new(
+                false,
+                0.0,
+                0.0,
+                0,
+                total_pixels,
+                start_time.elapsed().as_millis() as u64,
+                self.algorithm_name().to_string(),
+            ));
+        }
+
+        // Differences below the configured threshold are never motion, however tight the model
+        let min_difference = (255.0 * self.config.threshold) as f32;
+        let mut shadow_pixels = 0u32;
+        let mut mask = Vec::with_capacity(current_frame.len());
+        for (index, &value) in current_frame.iter().enumerate() {
+            let class = self.update_pixel(index, value as f32, min_difference);
+            if class == Class::Shadow {
+                shadow_pixels += 1;
+            }
+            mask.push(class == Class::Foreground);
+        }
+        if self.model_config.remove_speckle {
+            mask = open_mask(&mask, frame_width as usize, frame_height as usize);
+        }
+
+        let changed_pixels = mask.iter().filter(|&&fg| fg).count() as u32;
+        let change_ratio = changed_pixels as f64 / total_pixels.max(1) as f64;
+        let motion_detected = changed_pixels >= self.config.min_change_area;
+        let confidence = self.confidence(changed_pixels, change_ratio, motion_detected);
+        self.mask = mask;
+
+        debug!(
+            "Gaussian mixture analysis: foreground={}, shadows={}, change_ratio={:.3}, motion={}",
+            changed_pixels, shadow_pixels, change_ratio, motion_detected
+        );
+
+        Ok(MotionResult::new(
+            motion_detected,
+            confidence,
+            change_ratio,
+            changed_pixels,
+            total_pixels,
+            start_time.elapsed().as_millis() as u64,
+            self.algorithm_name().to_string(),
+        ))
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        debug!("Resetting Gaussian mixture background model");
+        self.model.clear();
+        self.used.clear();
+        self.mask.clear();
+        self.frame_width = 0;
+        self.frame_height = 0;
+        Ok(())
+    }
+
+    fn algorithm_name(&self) -> &'static str {
+        "GaussianMixture"
+    }
+
+    fn foreground_mask(&self) -> Option<(&[bool], u32, u32)> {
+        if self.mask.is_empty() {
+            return None;
+        }
+        Some((&self.mask, self.frame_width, self.frame_height))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> MotionConfig {
+        MotionConfig {
+            threshold: 0.1,
+            min_change_area: 50,
+            downscale_factor: 1,
+            max_width: 100,
+            max_height: 100,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_model_settings() {
+        let bad = GaussianMixtureConfig {
+            components: 0,
+            ..Default::default()
+        };
+        assert!(GaussianMixtureDetector::with_model_config(config(), bad).is_err());
+        let bad = GaussianMixtureConfig {
+            learning_rate: 1.5,
+            ..Default::default()
+        };
+        assert!(GaussianMixtureDetector::with_model_config(config(), bad).is_err());
+    }
+
+    #[test]
+    fn shadows_are_not_motion() {
+        // A fast learning rate settles the background variance within a few frames
+        let fast = GaussianMixtureConfig {
+            learning_rate: 0.2,
+            ..Default::default()
+        };
+        let background = vec![64u8; 100 * 100];
+        // A region at 60% brightness looks like a cast shadow
+        let shadowed = background
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| if (i % 100) < 40 { 38 } else { v })
+            .collect::<Vec<_>>();
+
+        for (detect_shadows, expect_motion) in [(true, false), (false, true)] {
+            let mut detector = GaussianMixtureDetector::with_model_config(
+                config(),
+                GaussianMixtureConfig {
+                    detect_shadows,
+                    ..fast.clone()
+                },
+            )
+            .unwrap();
+            for _ in 0..20 {
+                detector.detect_motion(&background, 100, 100).unwrap();
+            }
+            let result = detector.detect_motion(&shadowed, 100, 100).unwrap();
+            assert_eq!(result.motion_detected, expect_motion);
+        }
+    }
+
+    #[test]
+    fn speckle_is_removed() {
+        let mut detector = GaussianMixtureDetector::new(MotionConfig {
+            min_change_area: 1,
+            ..config()
+        })
+        .unwrap();
+        let mut frame = vec![64u8; 100 * 100];
+        detector.detect_motion(&frame, 100, 100).unwrap();
+
+        // Scattered single bright pixels, as from sensor noise or rain
+        for i in (0..frame.len()).step_by(37) {
+            frame[i] = 250;
+        }
+        let result = detector.detect_motion(&frame, 100, 100).unwrap();
+        assert_eq!(result.changed_pixels, 0);
+    }
+
+    #[test]
+    fn resolution_change_restarts_the_model() {
+        let mut detector = GaussianMixtureDetector::new(config()).unwrap();
+        detector
+            .detect_motion(&[64u8; 100 * 100], 100, 100)
+            .unwrap();
+        let result = detector.detect_motion(&[200u8; 50 * 50], 50, 50).unwrap();
+        assert!(!result.motion_detected);
+        assert_eq!(result.total_pixels, 2500);
+    }
+}
@@ -0,0 +1,372 @@
+//! ABOUTME: Accumulates per-pixel motion masks into a coarse heat grid per stream
+//! ABOUTME: Renders accumulated activity as a colored PNG overlay on a snapshot
+
+use crate::{
+    GaussianMixtureConfig, GaussianMixtureDetector, MotionAlgorithm, MotionConfig,
+    MotionDetectionService,
+};
+use gl_core::{Error, Result};
+use image::imageops::FilterType;
+use image::{DynamicImage, Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+/// Size of the canvas used when there is no snapshot to draw on
+const BLANK_CANVAS_SIZE: (u32, u32) = (640, 480);
+
+/// Strongest overlay opacity, reached by the busiest cell
+const MAX_ALPHA: f32 = 200.0;
+
+/// Weakest opacity for a cell that saw any motion at all
+const MIN_ALPHA: f32 = 60.0;
+
+/// Per-stream heatmap settings, read from the `heatmap` object of a stream config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HeatmapConfig {
+    /// Accumulate motion for this stream
+    pub enabled: bool,
+    /// Grid columns the frame is divided into
+    pub cols: u32,
+    /// Grid rows the frame is divided into
+    pub rows: u32,
+    /// How often accumulated activity is written out, in seconds
+    pub flush_interval_secs: u64,
+    /// Background model used to find moving pixels
+    pub background_model: GaussianMixtureConfig,
+}
+
+impl Default for HeatmapConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            cols: 64,
+            rows: 48,
+            flush_interval_secs: 300,
+            background_model: GaussianMixtureConfig::default(),
+        }
+    }
+}
+
+/// Number of frames each cell of a frame-sized grid saw motion in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeatGrid {
+    cols: u32,
+    rows: u32,
+    /// Frames accumulated, with or without motion
+    frames: u64,
+    /// Row-major motion counts
+    cells: Vec<u32>,
+}
+
+impl HeatGrid {
+    /// Create an empty grid
+    pub fn new(cols: u32, rows: u32) -> Result<Self> {
+        if cols == 0 || rows == 0 {
+            return Err(Error::Validation(
+                "Heat grid needs at least one column and row".to_string(),
+            ));
+        }
+        Ok(Self {
+            cols,
+            rows,
+            frames: 0,
+            cells: vec![0; (cols * rows) as usize],
+        })
+    }
+
+    /// Rebuild a grid from stored counts
+    pub fn from_parts(cols: u32, rows: u32, frames: u64, cells: Vec<u32>) -> Result<Self> {
+        let mut grid = Self::new(cols, rows)?;
+        if cells.len() != grid.cells.len() {
+            return Err(Error::Validation(format!(
+                "Heat grid of {}x{} needs {} cells, got {}",
+                cols,
+                rows,
+                grid.cells.len(),
+                cells.len()
+            )));
+        }
+        grid.frames = frames;
+        grid.cells = cells;
+        Ok(grid)
+    }
+
+    pub fn cols(&self) -> u32 {
+        self.cols
+    }
+
+    pub fn rows(&self) -> u32 {
+        self.rows
+    }
+
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    pub fn cells(&self) -> &[u32] {
+        &self.cells
+    }
+
+    /// True when no frames have been accumulated
+    pub fn is_empty(&self) -> bool {
+        self.frames == 0
+    }
+
+    /// Count one frame, crediting every cell that contains a foreground pixel
+    pub fn accumulate(&mut self, mask: &[bool], width: u32, height: u32) -> Result<()> {
+        if mask.len() != (width * height) as usize {
+            return Err(Error::Validation(
+                "Motion mask does not match its dimensions".to_string(),
+            ));
+        }
+
+        let mut touched = vec![false; self.cells.len()];
+        for (index, _) in mask.iter().enumerate().filter(|(_, &fg)| fg) {
+            let x = index as u32 % width;
+            let y = index as u32 / width;
+            let col = x * self.cols / width;
+            let row = y * self.rows / height;
+            touched[(row * self.cols + col) as usize] = true;
+        }
+        for (cell, hit) in self.cells.iter_mut().zip(touched) {
+            if hit {
+                *cell = cell.saturating_add(1);
+            }
+        }
+        self.frames += 1;
+        Ok(())
+    }
+
+    /// Add another grid of the same shape into this one
+    pub fn merge(&mut self, other: &HeatGrid) -> Result<()> {
+        if other.cols != self.cols || other.rows != self.rows {
+            return Err(Error::Validation(format!(
+                "Cannot merge a {}x{} heat grid into a {}x{} one",
+                other.cols, other.rows, self.cols, self.rows
+            )));
+        }
+        for (cell, add) in self.cells.iter_mut().zip(&other.cells) {
+            *cell = cell.saturating_add(*add);
+        }
+        self.frames += other.frames;
+        Ok(())
+    }
+
+    /// Draw the grid over a background image, scaled to its size
+    ///
+    /// Counts are normalized against the busiest cell and square-rooted so that paths
+    /// used only occasionally still show up next to a constantly busy doorway.
+    pub fn render_overlay(&self, background: Option<&DynamicImage>) -> RgbaImage {
+        let mut canvas = match background {
+            Some(image) => image.to_rgba8(),
+            None => RgbaImage::from_pixel(
+                BLANK_CANVAS_SIZE.0,
+                BLANK_CANVAS_SIZE.1,
+                Rgba([32, 32, 32, 255]),
+            ),
+        };
+
+        let max = self.cells.iter().copied().max().unwrap_or(0);
+        if max == 0 {
+            return canvas;
+        }
+
+        let heat = RgbaImage::from_fn(self.cols, self.rows, |col, row| {
+            let count = self.cells[(row * self.cols + col) as usize];
+            if count == 0 {
+                return Rgba([0, 0, 0, 0]);
+            }
+            let t = (count as f32 / max as f32).sqrt();
+            let [r, g, b] = colormap(t);
+            Rgba([r, g, b, (MIN_ALPHA + (MAX_ALPHA - MIN_ALPHA) * t) as u8])
+        });
+        let heat =
+            image::imageops::resize(&heat, canvas.width(), canvas.height(), FilterType::Triangle);
+        image::imageops::overlay(&mut canvas, &heat, 0, 0);
+        canvas
+    }
+
+    /// Render the overlay as PNG, decoding the background from JPEG or PNG bytes
+    pub fn render_png(&self, background: Option<&[u8]>) -> Result<Vec<u8>> {
+        let background = background
+            .map(image::load_from_memory)
+            .transpose()
+            .map_err(|e| Error::Validation(format!("Failed to decode background: {}", e)))?;
+
+        let mut buffer = Vec::new();
+        self.render_overlay(background.as_ref())
+            .write_to(
+                &mut std::io::Cursor::new(&mut buffer),
+                image::ImageFormat::Png,
+            )
+            .map_err(|e| Error::Validation(format!("Failed to encode heatmap: {}", e)))?;
+        Ok(buffer)
+    }
+}
+
+/// Blue through cyan, green and yellow to red for 0.0..=1.0
+fn colormap(t: f32) -> [u8; 3] {
+    const STOPS: [[f32; 3]; 5] = [
+        [0.0, 0.0, 255.0],
+        [0.0, 255.0, 255.0],
+        [0.0, 255.0, 0.0],
+        [255.0, 255.0, 0.0],
+        [255.0, 0.0, 0.0],
+    ];
+    let scaled = t.clamp(0.0, 1.0) * (STOPS.len() - 1) as f32;
+    let low = (scaled.floor() as usize).min(STOPS.len() - 2);
+    let frac = scaled - low as f32;
+    let mut color = [0u8; 3];
+    for (channel, value) in color.iter_mut().enumerate() {
+        let from = STOPS[low][channel];
+        let to = STOPS[low + 1][channel];
+        *value = (from + (to - from) * frac).round() as u8;
+    }
+    color
+}
+
+/// Runs a background model over a stream's frames and accumulates where motion happens
+pub struct MotionHeatmapRecorder {
+    detector: MotionDetectionService,
+    grid: HeatGrid,
+}
+
+impl MotionHeatmapRecorder {
+    pub fn new(config: &HeatmapConfig) -> Result<Self> {
+        let motion_config = MotionConfig {
+            algorithm: MotionAlgorithm::GaussianMixture,
+            max_width: 160,
+            max_height: 120,
+            ..MotionConfig::default()
+        };
+        let detector = GaussianMixtureDetector::with_model_config(
+            motion_config.clone(),
+            config.background_model.clone(),
+        )?;
+        Ok(Self {
+            detector: MotionDetectionService::with_detector(motion_config, Box::new(detector)),
+            grid: HeatGrid::new(config.cols, config.rows)?,
+        })
+    }
+
+    /// Feed an encoded frame; returns whether any cell saw motion
+    pub fn observe(&mut self, frame: &[u8]) -> Result<bool> {
+        let result = self.detector.detect_motion_from_bytes(frame)?;
+        // The first frame only seeds the background model
+        let Some((mask, width, height)) = self.detector.foreground_mask() else {
+            return Ok(false);
+        };
+        self.grid.accumulate(mask, width, height)?;
+        Ok(result.changed_pixels > 0)
+    }
+
+    /// Activity accumulated since the last call to `take`
+    pub fn pending(&self) -> &HeatGrid {
+        &self.grid
+    }
+
+    /// Hand over the accumulated activity and start a fresh grid
+    pub fn take(&mut self) -> HeatGrid {
+        let empty = HeatGrid {
+            frames: 0,
+            cells: vec![0; self.grid.cells.len()],
+            ..self.grid.clone()
+        };
+        std::mem::replace(&mut self.grid, empty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{create_test_frame_with_motion, image_to_jpeg_bytes};
+
+    #[test]
+    fn masks_are_binned_into_cells() {
+        let mut grid = HeatGrid::new(4, 2).unwrap();
+        let mut mask = vec![false; 8 * 4];
+        // Top-left and bottom-right pixels
+        mask[0] = true;
+        mask[8 * 4 - 1] = true;
+        grid.accumulate(&mask, 8, 4).unwrap();
+        grid.accumulate(&mask, 8, 4).unwrap();
+        grid.accumulate(&[false; 8 * 4], 8, 4).unwrap();
+
+        assert_eq!(grid.frames(), 3);
+        assert_eq!(grid.cells(), &[2, 0, 0, 0, 0, 0, 0, 2]);
+        assert!(grid.accumulate(&mask, 4, 4).is_err());
+    }
+
+    #[test]
+    fn grids_merge_only_with_the_same_shape() {
+        let mut total = HeatGrid::from_parts(2, 1, 5, vec![1, 2]).unwrap();
+        total
+            .merge(&HeatGrid::from_parts(2, 1, 3, vec![4, 0]).unwrap())
+            .unwrap();
+        assert_eq!(total.cells(), &[5, 2]);
+        assert_eq!(total.frames(), 8);
+
+        assert!(total.merge(&HeatGrid::new(1, 2).unwrap()).is_err());
+        assert!(HeatGrid::from_parts(2, 2, 0, vec![0; 3]).is_err());
+    }
+
+    #[test]
+    fn recorder_marks_where_the_object_moves() {
+        let mut recorder = MotionHeatmapRecorder::new(&HeatmapConfig {
+            cols: 8,
+            rows: 6,
+            ..HeatmapConfig::default()
+        })
+        .unwrap();
+
+        let empty = create_test_frame_with_motion(640, 480, 0, 0, 0, 0, 64);
+        for _ in 0..5 {
+            recorder
+                .observe(&image_to_jpeg_bytes(&empty).unwrap())
+                .unwrap();
+        }
+        // A bright object walking along the top-left of the scene
+        for step in 0..4 {
+            let frame = create_test_frame_with_motion(640, 480, step * 40, 20, 60, 60, 230);
+            assert!(recorder
+                .observe(&image_to_jpeg_bytes(&frame).unwrap())
+                .unwrap());
+        }
+
+        let grid = recorder.take();
+        assert_eq!(grid.frames(), 8);
+        let busiest = grid
+            .cells()
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, &count)| count)
+            .map(|(index, _)| index)
+            .unwrap();
+        assert!(busiest < 8 * 2, "motion should land in the top rows");
+        assert!(grid.cells()[8 * 6 - 1] == 0);
+        assert!(recorder.pending().is_empty());
+    }
+
+    #[test]
+    fn overlay_colors_busy_cells_and_keeps_background_size() {
+        let grid = HeatGrid::from_parts(2, 2, 10, vec![10, 0, 0, 1]).unwrap();
+        let background =
+            image_to_jpeg_bytes(&create_test_frame_with_motion(320, 240, 0, 0, 0, 0, 64)).unwrap();
+
+        let png = grid.render_png(Some(&background)).unwrap();
+        let overlay = image::load_from_memory(&png).unwrap().to_rgba8();
+        assert_eq!(overlay.dimensions(), (320, 240));
+
+        // Busiest cell is red, an idle cell keeps the gray background
+        let hot = overlay.get_pixel(20, 20);
+        assert!(hot[0] > 150 && hot[2] < 100, "{:?}", hot);
+        let idle = overlay.get_pixel(300, 20);
+        assert!(idle[0] == idle[2], "{:?}", idle);
+
+        let blank = grid.render_png(None).unwrap();
+        let blank = image::load_from_memory(&blank).unwrap();
+        assert_eq!((blank.width(), blank.height()), BLANK_CANVAS_SIZE);
+        assert_eq!(colormap(0.0), [0, 0, 255]);
+        assert_eq!(colormap(1.0), [255, 0, 0]);
+    }
+}
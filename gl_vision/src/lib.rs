@@ -1,4 +1,4 @@
-//! ABOUTME: Motion detection with pure-Rust pixel-diff and optional OpenCV MOG2 algorithms
+//! ABOUTME: Motion detection with pure-Rust pixel-diff, Gaussian mixture and optional OpenCV MOG2
 //! ABOUTME: Analyzes video frames for motion with configurable thresholds and runtime selection
 
 use gl_core::Result;
@@ -6,13 +6,30 @@ use image::{GrayImage, ImageBuffer, Luma};
 use serde::{Deserialize, Serialize};
 use tracing::{debug, info, warn};
 
+mod components;
+pub mod frame_quality;
+pub mod gmm_detector;
+pub mod heatmap;
 #[cfg(feature = "heavy_opencv")]
 pub mod opencv_detector;
+pub mod person_detector;
 pub mod pixel_detector;
-
+pub mod plate;
+pub mod plate_ocr;
+pub mod privacy;
+pub mod tamper;
+
+pub use frame_quality::FrameQuality;
+pub use gmm_detector::{GaussianMixtureConfig, GaussianMixtureDetector};
+pub use heatmap::{HeatGrid, HeatmapConfig, MotionHeatmapRecorder};
 #[cfg(feature = "heavy_opencv")]
 pub use opencv_detector::OpenCvDetector;
+pub use person_detector::{PersonBox, PersonDetector, PersonDetectorConfig};
 pub use pixel_detector::PixelDiffDetector;
+pub use plate::{normalize_plate, region_formats, PlateBox, PlateConfig, PlateRead, PlateReader};
+pub use plate_ocr::{PlateOcrConfig, PlateOcrModel};
+pub use privacy::{MaskedFrame, PersonBlurConfig, PrivacyConfig, PrivacyMasker};
+pub use tamper::{TamperAnalysis, TamperConfig, TamperDetector, TamperKind};
 
 // Re-export image types for benchmarks
 pub use image;
@@ -54,6 +71,8 @@ pub enum MotionAlgorithm {
     PixelDiff,
     /// OpenCV MOG2 background subtraction (requires heavy_opencv feature)
     Mog2,
+    /// Pure-Rust per-pixel Gaussian mixture background model with shadow suppression
+    GaussianMixture,
 }
 
 impl Default for MotionAlgorithm {
@@ -119,6 +138,12 @@ pub trait MotionDetector: Send + Sync {
 
     /// Get algorithm name
     fn algorithm_name(&self) -> &'static str;
+
+    /// Row-major foreground mask of the last analyzed frame with its width and height,
+    /// for detectors that classify individual pixels
+    fn foreground_mask(&self) -> Option<(&[bool], u32, u32)> {
+        None
+    }
 }
 
 /// Main motion detection service
@@ -147,11 +172,20 @@ impl MotionDetectionService {
                     Box::new(PixelDiffDetector::new(config.clone())?)
                 }
             }
+            MotionAlgorithm::GaussianMixture => {
+                info!("Creating GaussianMixture motion detector");
+                Box::new(GaussianMixtureDetector::new(config.clone())?)
+            }
         };
 
         Ok(Self { config, detector })
     }
 
+    /// Create a service around an already configured detector
+    pub fn with_detector(config: MotionConfig, detector: Box<dyn MotionDetector>) -> Self {
+        Self { config, detector }
+    }
+
     /// Detect motion in a frame (JPEG/PNG bytes)
     pub fn detect_motion_from_bytes(&mut self, image_data: &[u8]) -> Result<MotionResult> {
         let start_time = std::time::Instant::now();
@@ -231,6 +265,11 @@ impl MotionDetectionService {
         self.detector.reset()
     }
 
+    /// Foreground mask of the last analyzed (downscaled) frame, if the detector keeps one
+    pub fn foreground_mask(&self) -> Option<(&[bool], u32, u32)> {
+        self.detector.foreground_mask()
+    }
+
     /// Get current configuration
     pub fn config(&self) -> &MotionConfig {
         &self.config
@@ -298,6 +337,20 @@ pub mod utils {
         img
     }
 
+    /// Create a synthetic frame with a licence plate showing `text` at (`x`, `y`)
+    pub fn create_test_frame_with_plate(
+        width: u32,
+        height: u32,
+        text: &str,
+        x: u32,
+        y: u32,
+        scale: u32,
+    ) -> GrayImage {
+        let mut img = ImageBuffer::from_pixel(width, height, Luma([110u8]));
+        crate::plate::draw_plate(&mut img, text, x, y, scale);
+        img
+    }
+
     /// Create a synthetic frame pair for testing
     pub fn create_test_frame_pair(width: u32, height: u32) -> (GrayImage, GrayImage) {
         let frame1 = ImageBuffer::from_pixel(width, height, Luma([64u8]));
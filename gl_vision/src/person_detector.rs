@@ -0,0 +1,318 @@
+//! ABOUTME: ONNX person detector used to blur people, still or moving, out of frames
+//! ABOUTME: Runs YOLOv8-style exports: letterboxed image in, box and class scores per anchor out
+
+use gl_core::{Error, Result};
+use image::imageops::FilterType;
+use image::{DynamicImage, Rgb, RgbImage};
+use serde::{Deserialize, Serialize};
+
+/// Person detection model settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PersonDetectorConfig {
+    /// ONNX model taking a `[1, 3, size, size]` RGB image scaled to 0.0-1.0 and returning
+    /// `[1, 4 + classes, anchors]`: centre x, centre y, width and height in input pixels,
+    /// then a score per class
+    pub model_path: String,
+    /// Side of the square input the model was exported at
+    pub input_size: u32,
+    /// Class index people are reported as
+    pub person_class: usize,
+    /// Lowest score treated as a person; kept low so doubtful people are still blurred
+    pub min_score: f32,
+    /// Overlap above which two detections are taken to be the same person
+    pub nms_iou: f32,
+}
+
+impl Default for PersonDetectorConfig {
+    fn default() -> Self {
+        Self {
+            model_path: String::new(),
+            input_size: 640,
+            person_class: 0,
+            min_score: 0.25,
+            nms_iou: 0.5,
+        }
+    }
+}
+
+/// A detected person in frame pixels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PersonBox {
+    pub x0: f32,
+    pub y0: f32,
+    pub x1: f32,
+    pub y1: f32,
+    pub score: f32,
+}
+
+impl PersonBox {
+    fn area(&self) -> f32 {
+        (self.x1 - self.x0).max(0.0) * (self.y1 - self.y0).max(0.0)
+    }
+
+    fn iou(&self, other: &PersonBox) -> f32 {
+        let overlap = PersonBox {
+            x0: self.x0.max(other.x0),
+            y0: self.y0.max(other.y0),
+            x1: self.x1.min(other.x1),
+            y1: self.y1.min(other.y1),
+            score: 0.0,
+        }
+        .area();
+        let union = self.area() + other.area() - overlap;
+        if union > 0.0 {
+            overlap / union
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Finds people in single frames with an ONNX model
+///
+/// Every frame is detected on its own, so people who stand still are found as
+/// reliably as people walking past.
+pub struct PersonDetector {
+    config: PersonDetectorConfig,
+    #[cfg(feature = "onnx")]
+    model: gl_core::onnx::OnnxModel,
+}
+
+impl PersonDetector {
+    /// Load the configured model
+    pub fn load(config: PersonDetectorConfig) -> Result<Self> {
+        if config.model_path.is_empty() {
+            return Err(Error::Config(
+                "Person detection needs a model_path".to_string(),
+            ));
+        }
+        if config.input_size == 0 {
+            return Err(Error::Config(
+                "Person detector input size must not be zero".to_string(),
+            ));
+        }
+        Self::load_model(config)
+    }
+
+    #[cfg(feature = "onnx")]
+    fn load_model(config: PersonDetectorConfig) -> Result<Self> {
+        use tract_onnx::prelude::*;
+        let size = config.input_size as usize;
+        let model = gl_core::onnx::load_model(&config.model_path, &[f32::fact([1, 3, size, size])])?;
+        Ok(Self { config, model })
+    }
+
+    #[cfg(not(feature = "onnx"))]
+    fn load_model(_config: PersonDetectorConfig) -> Result<Self> {
+        Err(Error::Config(
+            "Person detection needs gl_vision built with the onnx feature".to_string(),
+        ))
+    }
+
+    /// People in a frame, most confident first, with overlapping detections merged
+    pub fn detect(&self, image: &RgbImage) -> Result<Vec<PersonBox>> {
+        // Scale to fit the model input and pad the rest with grey, as YOLO was trained
+        let size = self.config.input_size;
+        let scale = (size as f32 / image.width() as f32).min(size as f32 / image.height() as f32);
+        let (width, height) = (
+            ((image.width() as f32 * scale).round() as u32).clamp(1, size),
+            ((image.height() as f32 * scale).round() as u32).clamp(1, size),
+        );
+        let (pad_x, pad_y) = ((size - width) / 2, (size - height) / 2);
+        let resized = DynamicImage::ImageRgb8(image.clone())
+            .resize_exact(width, height, FilterType::Triangle)
+            .to_rgb8();
+        let mut input = RgbImage::from_pixel(size, size, Rgb([114, 114, 114]));
+        image::imageops::replace(&mut input, &resized, pad_x as i64, pad_y as i64);
+
+        let (shape, output) = self.run(&input)?;
+        let [1, attributes, anchors] = shape[..] else {
+            return Err(Error::External(format!(
+                "Person detector output has shape {:?}, expected [1, 4 + classes, anchors]",
+                shape
+            )));
+        };
+        if attributes <= 4 + self.config.person_class {
+            return Err(Error::External(format!(
+                "Person detector reports {} classes, fewer than person class {}",
+                attributes.saturating_sub(4),
+                self.config.person_class
+            )));
+        }
+
+        let value = |attribute: usize, anchor: usize| output[attribute * anchors + anchor];
+        let (max_x, max_y) = (image.width() as f32, image.height() as f32);
+        let mut candidates: Vec<PersonBox> = (0..anchors)
+            .filter_map(|anchor| {
+                let score = value(4 + self.config.person_class, anchor);
+                if score < self.config.min_score {
+                    return None;
+                }
+                let (cx, cy) = (value(0, anchor), value(1, anchor));
+                let (w, h) = (value(2, anchor), value(3, anchor));
+                let to_frame_x = |x: f32| ((x - pad_x as f32) / scale).clamp(0.0, max_x);
+                let to_frame_y = |y: f32| ((y - pad_y as f32) / scale).clamp(0.0, max_y);
+                Some(PersonBox {
+                    x0: to_frame_x(cx - w / 2.0),
+                    y0: to_frame_y(cy - h / 2.0),
+                    x1: to_frame_x(cx + w / 2.0),
+                    y1: to_frame_y(cy + h / 2.0),
+                    score,
+                })
+            })
+            .collect();
+        candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+        let mut people: Vec<PersonBox> = Vec::new();
+        for candidate in candidates {
+            if people
+                .iter()
+                .all(|person| person.iou(&candidate) <= self.config.nms_iou)
+            {
+                people.push(candidate);
+            }
+        }
+        Ok(people)
+    }
+
+    #[cfg(feature = "onnx")]
+    fn run(&self, input: &RgbImage) -> Result<(Vec<usize>, Vec<f32>)> {
+        use tract_onnx::prelude::*;
+        let size = self.config.input_size as usize;
+        let pixels = tract_ndarray::Array4::from_shape_fn((1, 3, size, size), |(_, c, y, x)| {
+            input.get_pixel(x as u32, y as u32).0[c] as f32 / 255.0
+        });
+        gl_core::onnx::run(&self.model, vec![pixels.into()])
+    }
+
+    #[cfg(not(feature = "onnx"))]
+    fn run(&self, _input: &RgbImage) -> Result<(Vec<usize>, Vec<f32>)> {
+        unreachable!("models cannot be loaded without the onnx feature")
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Model that finds a person at (100, 100)-(140, 220) of a 320x320 frame, the same
+    /// person again slightly offset, and one at (235, 60)-(265, 140), all scored by the
+    /// frame's brightness
+    #[cfg(feature = "onnx")]
+    pub(crate) fn brightness_detector(path: &std::path::Path) -> PersonDetectorConfig {
+        use test_support::onnx::{int_attr, ints_attr, DataType, OnnxModelBuilder};
+
+        // Input pixels are a fifth of frame pixels
+        let boxes: [[f32; 4]; 3] = [
+            [24.0, 32.0, 8.0, 24.0],
+            [24.5, 32.0, 8.0, 24.0],
+            [50.0, 20.0, 6.0, 16.0],
+        ];
+        let weights = [1.0, 0.9, 0.8];
+        let anchors = boxes.len();
+        // One row per attribute holding a value per anchor: the box, the person score, then
+        // another class that never wins
+        let mut base: Vec<f32> = (0..4)
+            .flat_map(|attribute| boxes.iter().map(move |b| b[attribute]))
+            .collect();
+        base.resize(6 * anchors, 0.0);
+        let mut scores = vec![0.0; 4 * anchors];
+        scores.extend(weights);
+        scores.resize(6 * anchors, 0.0);
+        OnnxModelBuilder::new()
+            .input("images", DataType::Float, &[-1, 3, -1, -1])
+            .output("output0", DataType::Float, &[-1, 6, anchors as i64])
+            .floats("base", &[1, 6, anchors as i64], base)
+            .floats("scores", &[1, 6, anchors as i64], scores)
+            .node(
+                "ReduceMean",
+                &["images"],
+                &["brightness"],
+                vec![ints_attr("axes", &[1, 2, 3]), int_attr("keepdims", 0)],
+            )
+            .node("Mul", &["brightness", "scores"], &["scored"], vec![])
+            .node("Add", &["scored", "base"], &["output0"], vec![])
+            .save(path);
+        PersonDetectorConfig {
+            model_path: path.to_string_lossy().to_string(),
+            input_size: 64,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn boxes_overlap_by_intersection_over_union() {
+        let a = PersonBox {
+            x0: 0.0,
+            y0: 0.0,
+            x1: 10.0,
+            y1: 10.0,
+            score: 1.0,
+        };
+        let b = PersonBox {
+            x0: 5.0,
+            y0: 0.0,
+            x1: 15.0,
+            y1: 10.0,
+            score: 1.0,
+        };
+        assert!((a.iou(&b) - 1.0 / 3.0).abs() < 1e-6);
+        assert_eq!(
+            a.iou(&PersonBox {
+                x0: 20.0,
+                x1: 30.0,
+                ..b
+            }),
+            0.0
+        );
+    }
+
+    #[test]
+    fn detector_needs_a_model() {
+        assert!(PersonDetector::load(PersonDetectorConfig::default()).is_err());
+        assert!(PersonDetector::load(PersonDetectorConfig {
+            model_path: "/nonexistent/people.onnx".to_string(),
+            ..Default::default()
+        })
+        .is_err());
+    }
+
+    #[cfg(feature = "onnx")]
+    #[test]
+    fn model_detections_are_mapped_to_the_frame() {
+        let path = std::env::temp_dir().join(format!("people_{}.onnx", std::process::id()));
+        let detector = PersonDetector::load(brightness_detector(&path)).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        // The duplicate of the first person is merged away
+        let people = detector
+            .detect(&RgbImage::from_pixel(320, 320, Rgb([230, 230, 230])))
+            .unwrap();
+        assert_eq!(people.len(), 2, "{:?}", people);
+        let first = people[0];
+        assert!(
+            (first.x0 - 100.0).abs() < 0.5 && (first.x1 - 140.0).abs() < 0.5,
+            "{:?}",
+            first
+        );
+        assert!(
+            (first.y0 - 100.0).abs() < 0.5 && (first.y1 - 220.0).abs() < 0.5,
+            "{:?}",
+            first
+        );
+        assert!((people[1].x0 - 235.0).abs() < 0.5, "{:?}", people[1]);
+
+        // Letterboxing is undone for frames of another shape
+        let wide = detector
+            .detect(&RgbImage::from_pixel(640, 320, Rgb([230, 230, 230])))
+            .unwrap();
+        assert!((wide[0].y0 - 40.0).abs() < 1.0, "{:?}", wide[0]);
+
+        // Dark frames score below the threshold
+        assert!(detector
+            .detect(&RgbImage::from_pixel(320, 320, Rgb([20, 20, 20])))
+            .unwrap()
+            .is_empty());
+    }
+}
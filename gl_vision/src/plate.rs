@@ -0,0 +1,760 @@
+//! ABOUTME: Licence plate reading: finds rows of character-like shapes on the CPU, reads them
+//! ABOUTME: with an ONNX recogniser, then normalises the text to regional formats
+
+use crate::components::{connected_shapes, Shape};
+use crate::plate_ocr::{PlateOcrConfig, PlateOcrModel};
+use gl_core::{Error, Result};
+use image::imageops::FilterType;
+use image::{DynamicImage, GrayImage, ImageBuffer, Luma};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+/// How far (0-255) a pixel must differ from its neighbourhood to count as ink
+const INK_OFFSET: u32 = 12;
+
+/// Configuration for plate reading
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlateConfig {
+    /// Frames wider than this are scaled down before searching
+    pub max_width: u32,
+    /// Smallest character height, in pixels after scaling
+    pub min_char_height: u32,
+    /// Largest character height, in pixels after scaling
+    pub max_char_height: u32,
+    /// Fewest characters a plate can have
+    pub min_chars: usize,
+    /// Most characters a plate can have
+    pub max_chars: usize,
+    /// Most plates reported per frame
+    pub max_plates: usize,
+    /// Recognition model that reads located plates; required
+    pub ocr: PlateOcrConfig,
+    /// Width of the JPEG crop kept for each read
+    pub crop_width: u32,
+}
+
+impl Default for PlateConfig {
+    fn default() -> Self {
+        Self {
+            max_width: 1280,
+            min_char_height: 12,
+            max_char_height: 120,
+            min_chars: 4,
+            max_chars: 9,
+            max_plates: 4,
+            ocr: PlateOcrConfig::default(),
+            crop_width: 240,
+        }
+    }
+}
+
+/// Rectangle in frame pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlateBox {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl PlateBox {
+    fn overlaps(&self, other: &PlateBox) -> bool {
+        self.x < other.x + other.width
+            && other.x < self.x + self.width
+            && self.y < other.y + other.height
+            && other.y < self.y + self.height
+    }
+}
+
+/// A plate found in a frame
+#[derive(Debug, Clone)]
+pub struct PlateRead {
+    /// Characters as read, before format normalisation
+    pub text: String,
+    /// Mean character confidence of the recognition model, 0.0 to 1.0
+    pub confidence: f64,
+    /// Plate position in the original frame
+    pub bbox: PlateBox,
+    /// JPEG of the plate region
+    pub crop: Vec<u8>,
+}
+
+/// Finds plates in frames and reads them
+pub struct PlateReader {
+    config: PlateConfig,
+    ocr: PlateOcrModel,
+}
+
+impl PlateReader {
+    /// Create a reader with the configured recognition model
+    ///
+    /// There is no fallback reader, so plate reading refuses to start without a model
+    /// rather than emit reads it cannot make.
+    pub fn new(config: PlateConfig) -> Result<Self> {
+        if config.min_char_height < 4 || config.max_char_height < config.min_char_height {
+            return Err(Error::Validation(
+                "Plate character heights must be at least 4 and min <= max".to_string(),
+            ));
+        }
+        if config.min_chars == 0 || config.max_chars < config.min_chars {
+            return Err(Error::Validation(
+                "Plate character counts must be at least 1 and min <= max".to_string(),
+            ));
+        }
+        if config.max_width < 64 {
+            return Err(Error::Validation(
+                "Plate analysis width must be at least 64".to_string(),
+            ));
+        }
+        if config.ocr.model_path.is_empty() {
+            return Err(Error::Config(
+                "Plate reading needs a recognition model: set ocr.model_path".to_string(),
+            ));
+        }
+        let ocr = PlateOcrModel::load(config.ocr.clone())?;
+        Ok(Self { config, ocr })
+    }
+
+    /// Decode an encoded frame and read its plates
+    pub fn read_bytes(&self, data: &[u8]) -> Result<Vec<PlateRead>> {
+        let image = image::load_from_memory(data)
+            .map_err(|e| Error::Validation(format!("Failed to decode frame: {}", e)))?;
+        Ok(self.read(&image))
+    }
+
+    /// Read the plates in a frame, most confident first
+    pub fn read(&self, frame: &DynamicImage) -> Vec<PlateRead> {
+        let mut gray = frame.to_luma8();
+        let scale = if gray.width() > self.config.max_width {
+            self.config.max_width as f64 / gray.width() as f64
+        } else {
+            1.0
+        };
+        if scale < 1.0 {
+            let height = ((gray.height() as f64 * scale).round() as u32).max(1);
+            gray =
+                image::imageops::resize(&gray, self.config.max_width, height, FilterType::Triangle);
+        }
+
+        // Dark characters on light plates, then light on dark
+        let (dark, light) = ink_masks(&gray, self.config.min_char_height.max(8));
+        let mut located = Vec::new();
+        for mask in [&dark, &light] {
+            let shapes = self.character_shapes(mask, gray.width(), gray.height());
+            for row in group_rows(shapes) {
+                if (self.config.min_chars..=self.config.max_chars).contains(&row.len()) {
+                    located.push(row_bounds(&row, gray.width(), gray.height()));
+                }
+            }
+        }
+        // Back to original frame coordinates, where the model reads the full-resolution crop
+        let mut candidates: Vec<(String, f64, PlateBox)> = located
+            .into_iter()
+            .filter_map(|bbox| {
+                let bbox = PlateBox {
+                    x: (bbox.x as f64 / scale) as u32,
+                    y: (bbox.y as f64 / scale) as u32,
+                    width: ((bbox.width as f64 / scale) as u32).max(1),
+                    height: ((bbox.height as f64 / scale) as u32).max(1),
+                };
+                match self.ocr.read(&region(frame, &bbox)) {
+                    Ok((text, confidence)) => Some((text, confidence, bbox)),
+                    Err(e) => {
+                        warn!(error = %e, "Plate OCR failed");
+                        None
+                    }
+                }
+            })
+            .collect();
+        candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        let mut reads: Vec<PlateRead> = Vec::new();
+        for (text, confidence, bbox) in candidates {
+            if reads.len() >= self.config.max_plates {
+                break;
+            }
+            if reads.iter().any(|read| read.bbox.overlaps(&bbox)) {
+                continue;
+            }
+            reads.push(PlateRead {
+                text,
+                confidence,
+                crop: self.crop(frame, &bbox),
+                bbox,
+            });
+        }
+        debug!(plates = reads.len(), "Plate reading finished");
+        reads
+    }
+
+    /// Connected ink shapes sized and shaped like characters
+    fn character_shapes(&self, mask: &[bool], width: u32, height: u32) -> Vec<Shape> {
+        connected_shapes(mask, width, height)
+            .into_iter()
+            .filter(|shape| {
+                let (w, h) = (shape.width() as f64, shape.height() as f64);
+                let fill = shape.pixels.len() as f64 / (w * h);
+                shape.height() >= self.config.min_char_height
+                    && shape.height() <= self.config.max_char_height
+                    && (0.1..=1.0).contains(&(w / h))
+                    && (0.1..=0.95).contains(&fill)
+            })
+            .collect()
+    }
+
+    /// JPEG of a region of the original frame, scaled to the crop width
+    fn crop(&self, frame: &DynamicImage, bbox: &PlateBox) -> Vec<u8> {
+        let region = region(frame, bbox);
+        let (width, height) = (region.width(), region.height());
+        let crop_height =
+            ((height as f64 * self.config.crop_width as f64 / width as f64).round() as u32).max(1);
+        let region = region.resize_exact(self.config.crop_width, crop_height, FilterType::Triangle);
+
+        let mut buffer = Vec::new();
+        if let Err(e) = DynamicImage::ImageRgb8(region.to_rgb8()).write_to(
+            &mut std::io::Cursor::new(&mut buffer),
+            image::ImageFormat::Jpeg,
+        ) {
+            debug!(error = %e, "Failed to encode plate crop");
+            buffer.clear();
+        }
+        buffer
+    }
+}
+
+/// Bounds of a row of character shapes with a margin around them
+fn row_bounds(row: &[Shape], width: u32, height: u32) -> PlateBox {
+    let char_height = row.iter().map(Shape::height).max().unwrap_or(1) as f64;
+    let margin_x = (char_height * 0.3) as u32;
+    let margin_y = (char_height * 0.25) as u32;
+    let x0 = row
+        .iter()
+        .map(|s| s.x0)
+        .min()
+        .unwrap_or(0)
+        .saturating_sub(margin_x);
+    let y0 = row
+        .iter()
+        .map(|s| s.y0)
+        .min()
+        .unwrap_or(0)
+        .saturating_sub(margin_y);
+    let x1 = (row.iter().map(|s| s.x1).max().unwrap_or(0) + margin_x).min(width - 1);
+    let y1 = (row.iter().map(|s| s.y1).max().unwrap_or(0) + margin_y).min(height - 1);
+    PlateBox {
+        x: x0,
+        y: y0,
+        width: x1 - x0 + 1,
+        height: y1 - y0 + 1,
+    }
+}
+
+/// Part of a frame inside a box, clamped to the frame
+fn region(frame: &DynamicImage, bbox: &PlateBox) -> DynamicImage {
+    let width = bbox.width.min(frame.width().saturating_sub(bbox.x)).max(1);
+    let height = bbox
+        .height
+        .min(frame.height().saturating_sub(bbox.y))
+        .max(1);
+    frame.crop_imm(bbox.x, bbox.y, width, height)
+}
+
+/// Pixels clearly darker and clearly lighter than their surroundings
+fn ink_masks(gray: &GrayImage, radius: u32) -> (Vec<bool>, Vec<bool>) {
+    let (width, height) = gray.dimensions();
+    let stride = width as usize + 1;
+    // Summed-area table with a zero row and column in front
+    let mut integral = vec![0u64; stride * (height as usize + 1)];
+    for y in 0..height as usize {
+        let mut row_sum = 0u64;
+        for x in 0..width as usize {
+            row_sum += gray.get_pixel(x as u32, y as u32)[0] as u64;
+            integral[(y + 1) * stride + x + 1] = integral[y * stride + x + 1] + row_sum;
+        }
+    }
+
+    let mut dark = vec![false; (width * height) as usize];
+    let mut light = vec![false; (width * height) as usize];
+    for y in 0..height {
+        let (top, bottom) = (y.saturating_sub(radius), (y + radius + 1).min(height));
+        for x in 0..width {
+            let (left, right) = (x.saturating_sub(radius), (x + radius + 1).min(width));
+            let sum = integral[bottom as usize * stride + right as usize]
+                + integral[top as usize * stride + left as usize]
+                - integral[top as usize * stride + right as usize]
+                - integral[bottom as usize * stride + left as usize];
+            let mean = (sum / ((right - left) * (bottom - top)) as u64) as u32;
+            let pixel = gray.get_pixel(x, y)[0] as u32;
+            let index = (y * width + x) as usize;
+            dark[index] = pixel + INK_OFFSET < mean;
+            light[index] = pixel > mean + INK_OFFSET;
+        }
+    }
+    (dark, light)
+}
+
+/// Chain shapes of similar height that sit side by side on the same line
+fn group_rows(mut shapes: Vec<Shape>) -> Vec<Vec<Shape>> {
+    shapes.sort_by_key(|shape| shape.x0);
+    let mut rows: Vec<Vec<Shape>> = Vec::new();
+    for shape in shapes {
+        let fits = rows.iter_mut().find(|row| {
+            let last = row.last().expect("rows are never empty");
+            let h = last.height() as f64;
+            let gap = shape.x0 as f64 - last.x1 as f64;
+            (shape.height() as f64 - h).abs() <= h * 0.25
+                && (shape.center_y() - last.center_y()).abs() <= h * 0.3
+                && gap >= -h * 0.1
+                && gap <= h * 1.2
+        });
+        match fits {
+            Some(row) => row.push(shape),
+            None => rows.push(vec![shape]),
+        }
+    }
+    rows
+}
+
+/// Draw a 5x7 font glyph as white ink on black, `scale` pixels per dot
+fn render_glyph(rows: &[&str; 7], scale: u32) -> GrayImage {
+    ImageBuffer::from_fn(5 * scale, 7 * scale, |x, y| {
+        let dot = rows[(y / scale) as usize].as_bytes()[(x / scale) as usize];
+        Luma([if dot == b'1' { 255 } else { 0 }])
+    })
+}
+
+/// Draw a white plate at (`x`, `y`) with `text` in dark built-in font characters
+pub(crate) fn draw_plate(frame: &mut GrayImage, text: &str, x: u32, y: u32, scale: u32) {
+    let (plate_w, plate_h) = ((text.len() as u32 * 6 + 3) * scale, 11 * scale);
+    for py in y..(y + plate_h).min(frame.height()) {
+        for px in x..(x + plate_w).min(frame.width()) {
+            frame.put_pixel(px, py, Luma([235]));
+        }
+    }
+    for (i, ch) in text.chars().enumerate() {
+        let Some((_, rows)) = FONT.iter().find(|(c, _)| *c == ch) else {
+            continue;
+        };
+        let (gx, gy) = (x + (2 + i as u32 * 6) * scale, y + 2 * scale);
+        for (dx, dy, pixel) in render_glyph(rows, scale).enumerate_pixels() {
+            if pixel[0] > 0 && gx + dx < frame.width() && gy + dy < frame.height() {
+                frame.put_pixel(gx + dx, gy + dy, Luma([20]));
+            }
+        }
+    }
+}
+
+/// Plate formats of a region, as patterns of `L` (letter), `N` (digit) and `A` (either)
+///
+/// An empty list accepts any plate of 4 to 8 letters and digits.
+pub fn region_formats(region: &str) -> Option<&'static [&'static str]> {
+    match region.to_ascii_lowercase().as_str() {
+        "gb" | "uk" => Some(&["LLNNLLL"]),
+        "fr" | "it" => Some(&["LLNNNLL"]),
+        "es" => Some(&["NNNNLLL"]),
+        "us" | "any" => Some(&[]),
+        _ => None,
+    }
+}
+
+/// Normalise read text to the first format it fits, fixing look-alike characters by position
+///
+/// Text is upper-cased and stripped of anything but letters and digits. Without formats,
+/// any 4 to 8 characters are accepted as read; with formats, text fitting none is rejected.
+pub fn normalize_plate<S: AsRef<str>>(text: &str, formats: &[S]) -> Option<String> {
+    let chars: Vec<char> = text
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    if formats.is_empty() {
+        return (4..=8)
+            .contains(&chars.len())
+            .then(|| chars.into_iter().collect());
+    }
+
+    formats.iter().find_map(|format| {
+        let pattern: Vec<char> = format
+            .as_ref()
+            .chars()
+            .filter(char::is_ascii_alphanumeric)
+            .collect();
+        if pattern.len() != chars.len() {
+            return None;
+        }
+        chars
+            .iter()
+            .zip(&pattern)
+            .map(|(&c, &slot)| match slot.to_ascii_uppercase() {
+                'L' => as_letter(c),
+                'N' => as_digit(c),
+                _ => Some(c),
+            })
+            .collect()
+    })
+}
+
+/// The letter a character is commonly misread for, or itself
+fn as_letter(c: char) -> Option<char> {
+    match c {
+        'A'..='Z' => Some(c),
+        '0' => Some('O'),
+        '1' => Some('I'),
+        '2' => Some('Z'),
+        '4' => Some('A'),
+        '5' => Some('S'),
+        '6' => Some('G'),
+        '7' => Some('T'),
+        '8' => Some('B'),
+        _ => None,
+    }
+}
+
+/// The digit a character is commonly misread for, or itself
+fn as_digit(c: char) -> Option<char> {
+    match c {
+        '0'..='9' => Some(c),
+        'O' | 'D' | 'Q' => Some('0'),
+        'I' | 'L' => Some('1'),
+        'Z' => Some('2'),
+        'A' => Some('4'),
+        'S' => Some('5'),
+        'G' => Some('6'),
+        'T' => Some('7'),
+        'B' => Some('8'),
+        _ => None,
+    }
+}
+
+/// 5x7 dot font synthetic test plates are drawn in
+const FONT: [(char, [&str; 7]); 36] = [
+    (
+        '0',
+        [
+            "01110", "10001", "10011", "10101", "11001", "10001", "01110",
+        ],
+    ),
+    (
+        '1',
+        [
+            "00100", "01100", "00100", "00100", "00100", "00100", "01110",
+        ],
+    ),
+    (
+        '2',
+        [
+            "01110", "10001", "00001", "00010", "00100", "01000", "11111",
+        ],
+    ),
+    (
+        '3',
+        [
+            "11111", "00010", "00100", "00010", "00001", "10001", "01110",
+        ],
+    ),
+    (
+        '4',
+        [
+            "00010", "00110", "01010", "10010", "11111", "00010", "00010",
+        ],
+    ),
+    (
+        '5',
+        [
+            "11111", "10000", "11110", "00001", "00001", "10001", "01110",
+        ],
+    ),
+    (
+        '6',
+        [
+            "00110", "01000", "10000", "11110", "10001", "10001", "01110",
+        ],
+    ),
+    (
+        '7',
+        [
+            "11111", "00001", "00010", "00100", "01000", "01000", "01000",
+        ],
+    ),
+    (
+        '8',
+        [
+            "01110", "10001", "10001", "01110", "10001", "10001", "01110",
+        ],
+    ),
+    (
+        '9',
+        [
+            "01110", "10001", "10001", "01111", "00001", "00010", "01100",
+        ],
+    ),
+    (
+        'A',
+        [
+            "01110", "10001", "10001", "11111", "10001", "10001", "10001",
+        ],
+    ),
+    (
+        'B',
+        [
+            "11110", "10001", "10001", "11110", "10001", "10001", "11110",
+        ],
+    ),
+    (
+        'C',
+        [
+            "01110", "10001", "10000", "10000", "10000", "10001", "01110",
+        ],
+    ),
+    (
+        'D',
+        [
+            "11100", "10010", "10001", "10001", "10001", "10010", "11100",
+        ],
+    ),
+    (
+        'E',
+        [
+            "11111", "10000", "10000", "11110", "10000", "10000", "11111",
+        ],
+    ),
+    (
+        'F',
+        [
+            "11111", "10000", "10000", "11110", "10000", "10000", "10000",
+        ],
+    ),
+    (
+        'G',
+        [
+            "01110", "10001", "10000", "10111", "10001", "10001", "01111",
+        ],
+    ),
+    (
+        'H',
+        [
+            "10001", "10001", "10001", "11111", "10001", "10001", "10001",
+        ],
+    ),
+    (
+        'I',
+        [
+            "01110", "00100", "00100", "00100", "00100", "00100", "01110",
+        ],
+    ),
+    (
+        'J',
+        [
+            "00111", "00010", "00010", "00010", "00010", "10010", "01100",
+        ],
+    ),
+    (
+        'K',
+        [
+            "10001", "10010", "10100", "11000", "10100", "10010", "10001",
+        ],
+    ),
+    (
+        'L',
+        [
+            "10000", "10000", "10000", "10000", "10000", "10000", "11111",
+        ],
+    ),
+    (
+        'M',
+        [
+            "10001", "11011", "10101", "10101", "10001", "10001", "10001",
+        ],
+    ),
+    (
+        'N',
+        [
+            "10001", "10001", "11001", "10101", "10011", "10001", "10001",
+        ],
+    ),
+    (
+        'O',
+        [
+            "01110", "10001", "10001", "10001", "10001", "10001", "01110",
+        ],
+    ),
+    (
+        'P',
+        [
+            "11110", "10001", "10001", "11110", "10000", "10000", "10000",
+        ],
+    ),
+    (
+        'Q',
+        [
+            "01110", "10001", "10001", "10001", "10101", "10010", "01101",
+        ],
+    ),
+    (
+        'R',
+        [
+            "11110", "10001", "10001", "11110", "10100", "10010", "10001",
+        ],
+    ),
+    (
+        'S',
+        [
+            "01111", "10000", "10000", "01110", "00001", "00001", "11110",
+        ],
+    ),
+    (
+        'T',
+        [
+            "11111", "00100", "00100", "00100", "00100", "00100", "00100",
+        ],
+    ),
+    (
+        'U',
+        [
+            "10001", "10001", "10001", "10001", "10001", "10001", "01110",
+        ],
+    ),
+    (
+        'V',
+        [
+            "10001", "10001", "10001", "10001", "10001", "01010", "00100",
+        ],
+    ),
+    (
+        'W',
+        [
+            "10001", "10001", "10001", "10101", "10101", "10101", "01010",
+        ],
+    ),
+    (
+        'X',
+        [
+            "10001", "10001", "01010", "00100", "01010", "10001", "10001",
+        ],
+    ),
+    (
+        'Y',
+        [
+            "10001", "10001", "01010", "00100", "00100", "00100", "00100",
+        ],
+    ),
+    (
+        'Z',
+        [
+            "11111", "00001", "00010", "00100", "01000", "10000", "11111",
+        ],
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "onnx")]
+    fn frame_with_plate(text: &str, x: u32, y: u32, scale: u32) -> DynamicImage {
+        DynamicImage::ImageLuma8(crate::utils::create_test_frame_with_plate(
+            640, 360, text, x, y, scale,
+        ))
+    }
+
+    /// Reader whose model reads light plates as `LIGHT_PLATE` and dark ones as `DARK_PLATE`
+    #[cfg(feature = "onnx")]
+    fn reader(config: PlateConfig) -> PlateReader {
+        let path = std::env::temp_dir().join(format!(
+            "plate_reader_{}_{}.onnx",
+            std::process::id(),
+            config.max_width
+        ));
+        let ocr = PlateOcrConfig {
+            model_path: path.to_string_lossy().to_string(),
+            ..Default::default()
+        };
+        crate::plate_ocr::tests::brightness_model(&path, &ocr);
+        let reader = PlateReader::new(PlateConfig { ocr, ..config }).unwrap();
+        let _ = std::fs::remove_file(&path);
+        reader
+    }
+
+    #[test]
+    fn reading_needs_a_recognition_model() {
+        assert!(PlateReader::new(PlateConfig::default()).is_err());
+        assert!(PlateReader::new(PlateConfig {
+            ocr: PlateOcrConfig {
+                model_path: "/nonexistent/plates.onnx".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .is_err());
+    }
+
+    #[cfg(feature = "onnx")]
+    #[test]
+    fn located_plates_are_read_by_the_model() {
+        use test_support::onnx::{DARK_PLATE, LIGHT_PLATE};
+
+        let reader = reader(PlateConfig::default());
+        let frame = frame_with_plate("CD34EFG", 100, 200, 4);
+        let reads = reader.read(&frame);
+
+        // The text comes from the model, not from the drawn characters
+        assert_eq!(reads.len(), 1);
+        assert_eq!(reads[0].text, LIGHT_PLATE);
+        assert!(reads[0].confidence > 0.9, "{}", reads[0].confidence);
+        let bbox = reads[0].bbox;
+        assert!(bbox.x >= 100 && bbox.x < 112, "{:?}", bbox);
+        assert!(bbox.y >= 200 && bbox.y < 212, "{:?}", bbox);
+        let crop = image::load_from_memory(&reads[0].crop).unwrap();
+        assert_eq!(crop.width(), 240);
+
+        // Light characters on a dark plate are located too
+        let mut inverted = frame.clone();
+        inverted.invert();
+        let reads = reader.read(&inverted);
+        assert_eq!(reads.len(), 1);
+        assert_eq!(reads[0].text, DARK_PLATE);
+    }
+
+    #[cfg(feature = "onnx")]
+    #[test]
+    fn large_frames_are_scaled_before_locating() {
+        let frame = frame_with_plate("XY98ZPK", 100, 100, 6);
+        let reads = reader(PlateConfig {
+            max_width: 480,
+            ..Default::default()
+        })
+        .read(&frame);
+        assert_eq!(reads.len(), 1);
+        // Reported in original frame coordinates
+        assert!(
+            reads[0].bbox.x >= 90 && reads[0].bbox.x < 112,
+            "{:?}",
+            reads[0].bbox
+        );
+    }
+
+    #[cfg(feature = "onnx")]
+    #[test]
+    fn frames_without_plates_have_no_reads() {
+        let reader = reader(PlateConfig::default());
+        let blank = DynamicImage::ImageLuma8(ImageBuffer::from_pixel(320, 240, Luma([128])));
+        assert!(reader.read(&blank).is_empty());
+        // Too few characters for a plate
+        assert!(reader.read(&frame_with_plate("AB1", 50, 50, 4)).is_empty());
+    }
+
+    #[test]
+    fn plates_are_normalised_to_region_formats() {
+        let gb = region_formats("GB").unwrap();
+        assert_eq!(normalize_plate("a812 cde", gb).as_deref(), Some("AB12CDE"));
+        assert_eq!(normalize_plate("AB1Z-CDE", gb).as_deref(), Some("AB12CDE"));
+        assert_eq!(normalize_plate("AB12CD", gb), None);
+        assert_eq!(normalize_plate("AB12C?E", gb), None);
+
+        let any = region_formats("us").unwrap();
+        assert_eq!(normalize_plate("7abc-123", any).as_deref(), Some("7ABC123"));
+        assert_eq!(normalize_plate("AB1", any), None);
+        assert!(region_formats("atlantis").is_none());
+    }
+}
@@ -0,0 +1,245 @@
+//! ABOUTME: ONNX plate recogniser that reads the characters of a cropped licence plate
+//! ABOUTME: Runs models shaped like fast-plate-ocr exports: image in, per-slot character scores out
+
+use gl_core::{Error, Result};
+use image::imageops::FilterType;
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+
+/// Plate recognition model settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlateOcrConfig {
+    /// ONNX model taking a `[1, height, width, channels]` uint8 image and returning
+    /// `[1, slots, alphabet]` (or flattened) character scores
+    pub model_path: String,
+    /// Characters scored by each output slot, in output order
+    pub alphabet: String,
+    /// Character the model emits for unused slots
+    pub pad_char: char,
+    /// Input width the model was trained at
+    pub width: u32,
+    /// Input height the model was trained at
+    pub height: u32,
+    /// Feed a single grey channel instead of RGB
+    pub grayscale: bool,
+}
+
+impl Default for PlateOcrConfig {
+    fn default() -> Self {
+        Self {
+            model_path: String::new(),
+            alphabet: "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ_".to_string(),
+            pad_char: '_',
+            width: 128,
+            height: 64,
+            grayscale: false,
+        }
+    }
+}
+
+/// Reads plate crops with an ONNX recognition model
+pub struct PlateOcrModel {
+    config: PlateOcrConfig,
+    alphabet: Vec<char>,
+    #[cfg(feature = "onnx")]
+    model: gl_core::onnx::OnnxModel,
+}
+
+impl PlateOcrModel {
+    /// Load the configured model
+    pub fn load(config: PlateOcrConfig) -> Result<Self> {
+        let alphabet: Vec<char> = config.alphabet.chars().collect();
+        if alphabet.is_empty() || !alphabet.contains(&config.pad_char) {
+            return Err(Error::Config(
+                "Plate OCR alphabet must include the pad character".to_string(),
+            ));
+        }
+        if config.width == 0 || config.height == 0 {
+            return Err(Error::Config(
+                "Plate OCR input size must not be zero".to_string(),
+            ));
+        }
+        Self::load_model(config, alphabet)
+    }
+
+    #[cfg(feature = "onnx")]
+    fn load_model(config: PlateOcrConfig, alphabet: Vec<char>) -> Result<Self> {
+        use tract_onnx::prelude::*;
+        let channels = if config.grayscale { 1 } else { 3 };
+        let input = u8::fact([1, config.height as usize, config.width as usize, channels]);
+        let model = gl_core::onnx::load_model(&config.model_path, &[input])?;
+        Ok(Self {
+            config,
+            alphabet,
+            model,
+        })
+    }
+
+    #[cfg(not(feature = "onnx"))]
+    fn load_model(_config: PlateOcrConfig, _alphabet: Vec<char>) -> Result<Self> {
+        Err(Error::Config(
+            "Plate OCR models need gl_vision built with the onnx feature".to_string(),
+        ))
+    }
+
+    /// Read the text of a plate crop with the mean confidence of its characters
+    pub fn read(&self, plate: &DynamicImage) -> Result<(String, f64)> {
+        let resized =
+            plate.resize_exact(self.config.width, self.config.height, FilterType::Triangle);
+        let pixels = if self.config.grayscale {
+            resized.to_luma8().into_raw()
+        } else {
+            resized.to_rgb8().into_raw()
+        };
+        let scores = self.run(pixels)?;
+        decode_slots(&scores, &self.alphabet, self.config.pad_char)
+    }
+
+    #[cfg(feature = "onnx")]
+    fn run(&self, pixels: Vec<u8>) -> Result<Vec<f32>> {
+        use tract_onnx::prelude::*;
+        let channels = if self.config.grayscale { 1 } else { 3 };
+        let input = tract_ndarray::Array4::from_shape_vec(
+            (
+                1,
+                self.config.height as usize,
+                self.config.width as usize,
+                channels,
+            ),
+            pixels,
+        )
+        .map_err(|e| Error::Validation(format!("Invalid plate image: {}", e)))?;
+        let (_, scores) = gl_core::onnx::run(&self.model, vec![input.into()])?;
+        Ok(scores)
+    }
+
+    #[cfg(not(feature = "onnx"))]
+    fn run(&self, _pixels: Vec<u8>) -> Result<Vec<f32>> {
+        unreachable!("models cannot be loaded without the onnx feature")
+    }
+}
+
+/// Best character per output slot, skipping padding, with the mean character confidence
+///
+/// Slots whose scores are not already probabilities are passed through a softmax.
+fn decode_slots(scores: &[f32], alphabet: &[char], pad_char: char) -> Result<(String, f64)> {
+    if scores.is_empty() || scores.len() % alphabet.len() != 0 {
+        return Err(Error::External(format!(
+            "Plate OCR model returned {} scores, not a multiple of its {} character alphabet",
+            scores.len(),
+            alphabet.len()
+        )));
+    }
+
+    let mut text = String::new();
+    let mut total = 0.0;
+    for slot in scores.chunks(alphabet.len()) {
+        let is_distribution = slot.iter().all(|p| (0.0..=1.0).contains(p))
+            && (slot.iter().sum::<f32>() - 1.0).abs() < 0.01;
+        let probabilities: Vec<f32> = if is_distribution {
+            slot.to_vec()
+        } else {
+            let max = slot.iter().copied().fold(f32::MIN, f32::max);
+            let exp: Vec<f32> = slot.iter().map(|s| (s - max).exp()).collect();
+            let sum: f32 = exp.iter().sum();
+            exp.into_iter().map(|e| e / sum).collect()
+        };
+        let (best, probability) = probabilities
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .expect("slots are never empty");
+        if alphabet[best] != pad_char {
+            text.push(alphabet[best]);
+            total += *probability as f64;
+        }
+    }
+    let confidence = if text.is_empty() {
+        0.0
+    } else {
+        total / text.chars().count() as f64
+    };
+    Ok((text, confidence))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    #[test]
+    fn slots_decode_to_text_without_padding() {
+        let alphabet: Vec<char> = "AB1_".chars().collect();
+        let scores = [
+            0.9, 0.05, 0.05, 0.0, // A
+            0.0, 0.0, 0.8, 0.2, // 1
+            0.0, 0.0, 0.0, 1.0, // pad
+        ];
+        let (text, confidence) = decode_slots(&scores, &alphabet, '_').unwrap();
+        assert_eq!(text, "A1");
+        assert!((confidence - 0.85).abs() < 1e-6, "{}", confidence);
+
+        // Raw logits are normalised first
+        let (text, confidence) = decode_slots(&[5.0, 0.0, 0.0, 0.0], &alphabet, '_').unwrap();
+        assert_eq!(text, "A");
+        assert!(confidence > 0.9 && confidence < 1.0, "{}", confidence);
+
+        assert!(decode_slots(&[0.5, 0.5, 0.0], &alphabet, '_').is_err());
+    }
+
+    #[test]
+    fn alphabet_must_contain_padding() {
+        let result = PlateOcrModel::load(PlateOcrConfig {
+            alphabet: "ABC".to_string(),
+            ..Default::default()
+        });
+        assert!(result.is_err());
+    }
+
+    /// Write the `test_support` brightness recogniser in the layout of `config`
+    #[cfg(feature = "onnx")]
+    pub(crate) fn brightness_model(path: &std::path::Path, config: &PlateOcrConfig) {
+        test_support::onnx::brightness_plate_ocr(
+            path,
+            &config.alphabet,
+            config.width,
+            config.height,
+            if config.grayscale { 1 } else { 3 },
+        );
+    }
+
+    #[cfg(feature = "onnx")]
+    #[test]
+    fn model_reads_plate_crops() {
+        let path = std::env::temp_dir().join(format!("plate_ocr_{}.onnx", std::process::id()));
+        let config = PlateOcrConfig {
+            model_path: path.to_string_lossy().to_string(),
+            ..Default::default()
+        };
+        brightness_model(&path, &config);
+        let model = PlateOcrModel::load(config).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        let bright = DynamicImage::ImageRgb8(image::RgbImage::from_pixel(
+            200,
+            50,
+            image::Rgb([230, 230, 220]),
+        ));
+        let (text, confidence) = model.read(&bright).unwrap();
+        assert_eq!(text, "AB12CDE");
+        assert!(confidence > 0.99, "{}", confidence);
+
+        let dark = DynamicImage::ImageRgb8(image::RgbImage::from_pixel(
+            90,
+            40,
+            image::Rgb([30, 30, 40]),
+        ));
+        assert_eq!(model.read(&dark).unwrap().0, "XY98ZPK");
+
+        assert!(PlateOcrModel::load(PlateOcrConfig {
+            model_path: "/nonexistent/plates.onnx".to_string(),
+            ..Default::default()
+        })
+        .is_err());
+    }
+}
@@ -0,0 +1,359 @@
+//! ABOUTME: Privacy masking that burns static polygons and detected people out of frames
+//! ABOUTME: Finds people with a local ONNX detector on every frame and pixelates them
+
+use crate::person_detector::{PersonDetector, PersonDetectorConfig};
+use gl_core::{Error, Result};
+use image::{DynamicImage, Rgb, RgbImage};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// Per-stream privacy settings, read from the `privacy` object of a stream config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrivacyConfig {
+    /// Areas to cover, each a polygon of [x, y] points relative to the frame (0.0-1.0)
+    pub polygons: Vec<Vec<[f64; 2]>>,
+    /// RGB colour polygons are filled with
+    pub fill: [u8; 3],
+    /// Pixelate people found by the person detector
+    pub blur_people: bool,
+    pub people: PersonBlurConfig,
+    /// JPEG quality of masked frames
+    pub jpeg_quality: u8,
+}
+
+impl Default for PrivacyConfig {
+    fn default() -> Self {
+        Self {
+            polygons: Vec::new(),
+            fill: [0, 0, 0],
+            blur_people: false,
+            people: PersonBlurConfig::default(),
+            jpeg_quality: 85,
+        }
+    }
+}
+
+impl PrivacyConfig {
+    /// Whether the settings change frames at all
+    pub fn is_enabled(&self) -> bool {
+        !self.polygons.is_empty() || self.blur_people
+    }
+}
+
+/// How people are found and pixelated
+///
+/// Person blur needs a detector model; without one the masker refuses to start rather
+/// than let people through unblurred.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PersonBlurConfig {
+    #[serde(flatten)]
+    pub detector: PersonDetectorConfig,
+    /// Margin added around each person, as a fraction of its size
+    pub padding: f64,
+    /// Mosaic block size in frame pixels
+    pub block_size: u32,
+}
+
+impl Default for PersonBlurConfig {
+    fn default() -> Self {
+        Self {
+            detector: PersonDetectorConfig::default(),
+            padding: 0.2,
+            block_size: 16,
+        }
+    }
+}
+
+/// A frame after masking
+#[derive(Debug, Clone)]
+pub struct MaskedFrame {
+    /// JPEG of the masked frame
+    pub data: Vec<u8>,
+    /// People pixelated in the frame
+    pub people: usize,
+}
+
+/// Applies a stream's privacy settings to its frames, in capture order
+pub struct PrivacyMasker {
+    config: PrivacyConfig,
+    detector: Option<PersonDetector>,
+}
+
+impl PrivacyMasker {
+    pub fn new(config: PrivacyConfig) -> Result<Self> {
+        for polygon in &config.polygons {
+            if polygon.len() < 3 {
+                return Err(Error::Validation(
+                    "Privacy polygons need at least 3 points".to_string(),
+                ));
+            }
+            if polygon.iter().flatten().any(|v| !(0.0..=1.0).contains(v)) {
+                return Err(Error::Validation(
+                    "Privacy polygon points must be between 0.0 and 1.0".to_string(),
+                ));
+            }
+        }
+        if !(1..=100).contains(&config.jpeg_quality) {
+            return Err(Error::Validation(
+                "Privacy jpeg_quality must be between 1 and 100".to_string(),
+            ));
+        }
+
+        let detector = if config.blur_people {
+            let people = &config.people;
+            if people.block_size == 0 {
+                return Err(Error::Validation(
+                    "Person blur needs a block size".to_string(),
+                ));
+            }
+            if people.detector.model_path.is_empty() {
+                return Err(Error::Validation(
+                    "Person blur needs a person detector model_path".to_string(),
+                ));
+            }
+            Some(PersonDetector::load(people.detector.clone())?)
+        } else {
+            None
+        };
+
+        Ok(Self { config, detector })
+    }
+
+    pub fn config(&self) -> &PrivacyConfig {
+        &self.config
+    }
+
+    /// Decode a frame, mask it and encode it as JPEG
+    pub fn mask_bytes(&mut self, data: &[u8]) -> Result<MaskedFrame> {
+        let mut image = image::load_from_memory(data)
+            .map_err(|e| Error::Validation(format!("Failed to decode frame: {}", e)))?
+            .to_rgb8();
+        let people = self.mask(&mut image)?;
+
+        let mut buffer = Vec::new();
+        let encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(
+            &mut buffer,
+            self.config.jpeg_quality,
+        );
+        DynamicImage::ImageRgb8(image)
+            .write_with_encoder(encoder)
+            .map_err(|e| Error::Validation(format!("Failed to encode masked frame: {}", e)))?;
+        Ok(MaskedFrame {
+            data: buffer,
+            people,
+        })
+    }
+
+    /// Pixelate people and fill the polygons in place; returns how many people were found
+    pub fn mask(&mut self, image: &mut RgbImage) -> Result<usize> {
+        let people = self.find_people(image)?;
+        for &(x, y, w, h) in &people {
+            pixelate(image, x, y, w, h, self.config.people.block_size);
+        }
+        for polygon in &self.config.polygons {
+            fill_polygon(image, polygon, Rgb(self.config.fill));
+        }
+        Ok(people.len())
+    }
+
+    /// Padded boxes (x, y, width, height) around detected people, in frame pixels
+    ///
+    /// Detection errors are returned so the frame is dropped rather than kept unblurred.
+    fn find_people(&mut self, image: &RgbImage) -> Result<Vec<(u32, u32, u32, u32)>> {
+        let Some(detector) = self.detector.as_ref() else {
+            return Ok(Vec::new());
+        };
+        let people = &self.config.people;
+        let boxes: Vec<_> = detector
+            .detect(image)?
+            .into_iter()
+            .map(|person| {
+                let (x, y) = (person.x0 as f64, person.y0 as f64);
+                let (w, h) = (
+                    (person.x1 - person.x0) as f64,
+                    (person.y1 - person.y0) as f64,
+                );
+                let (pad_x, pad_y) = (w * people.padding, h * people.padding);
+                let x0 = (x - pad_x).max(0.0) as u32;
+                let y0 = (y - pad_y).max(0.0) as u32;
+                let x1 = ((x + w + pad_x) as u32).min(image.width());
+                let y1 = ((y + h + pad_y) as u32).min(image.height());
+                (x0, y0, x1.saturating_sub(x0), y1.saturating_sub(y0))
+            })
+            .collect();
+        debug!(people = boxes.len(), "Person blur detection finished");
+        Ok(boxes)
+    }
+}
+
+/// Replace each block of a region with its average colour
+fn pixelate(image: &mut RgbImage, x: u32, y: u32, width: u32, height: u32, block: u32) {
+    let (x_end, y_end) = (
+        (x + width).min(image.width()),
+        (y + height).min(image.height()),
+    );
+    for by in (y..y_end).step_by(block as usize) {
+        for bx in (x..x_end).step_by(block as usize) {
+            let (bw, bh) = ((bx + block).min(x_end) - bx, (by + block).min(y_end) - by);
+            let mut sum = [0u64; 3];
+            for py in by..by + bh {
+                for px in bx..bx + bw {
+                    let pixel = image.get_pixel(px, py);
+                    for (total, &channel) in sum.iter_mut().zip(&pixel.0) {
+                        *total += channel as u64;
+                    }
+                }
+            }
+            let count = (bw * bh).max(1) as u64;
+            let average = Rgb(sum.map(|total| (total / count) as u8));
+            for py in by..by + bh {
+                for px in bx..bx + bw {
+                    image.put_pixel(px, py, average);
+                }
+            }
+        }
+    }
+}
+
+/// Fill a polygon given in relative coordinates, using the even-odd rule at pixel centres
+fn fill_polygon(image: &mut RgbImage, polygon: &[[f64; 2]], color: Rgb<u8>) {
+    let (width, height) = (image.width() as f64, image.height() as f64);
+    let points: Vec<(f64, f64)> = polygon
+        .iter()
+        .map(|[x, y]| (x * width, y * height))
+        .collect();
+
+    let mut crossings = Vec::with_capacity(points.len());
+    for row in 0..image.height() {
+        let cy = row as f64 + 0.5;
+        crossings.clear();
+        for (i, &(x0, y0)) in points.iter().enumerate() {
+            let (x1, y1) = points[(i + 1) % points.len()];
+            if (y0 <= cy) != (y1 <= cy) {
+                crossings.push(x0 + (cy - y0) / (y1 - y0) * (x1 - x0));
+            }
+        }
+        crossings.sort_by(f64::total_cmp);
+        for span in crossings.chunks_exact(2) {
+            // Pixels whose centre lies inside the span
+            let start = (span[0] - 0.5).ceil().max(0.0) as u32;
+            let end = ((span[1] - 0.5).floor() + 1.0).clamp(0.0, width) as u32;
+            for col in start..end {
+                image.put_pixel(col, row, color);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(image: &RgbImage) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        DynamicImage::ImageRgb8(image.clone())
+            .write_to(
+                &mut std::io::Cursor::new(&mut buffer),
+                image::ImageFormat::Png,
+            )
+            .unwrap();
+        buffer
+    }
+
+    #[test]
+    fn polygons_are_burned_in() {
+        let mut masker = PrivacyMasker::new(PrivacyConfig {
+            // The left half, plus a triangle in the bottom right corner
+            polygons: vec![
+                vec![[0.0, 0.0], [0.5, 0.0], [0.5, 1.0], [0.0, 1.0]],
+                vec![[1.0, 0.5], [1.0, 1.0], [0.5, 1.0]],
+            ],
+            fill: [255, 0, 255],
+            ..Default::default()
+        })
+        .unwrap();
+        let mut image = RgbImage::from_pixel(100, 60, Rgb([30, 120, 30]));
+        assert_eq!(masker.mask(&mut image).unwrap(), 0);
+
+        assert_eq!(image.get_pixel(0, 0), &Rgb([255, 0, 255]));
+        assert_eq!(image.get_pixel(49, 59), &Rgb([255, 0, 255]));
+        assert_eq!(image.get_pixel(50, 0), &Rgb([30, 120, 30]));
+        assert_eq!(image.get_pixel(99, 59), &Rgb([255, 0, 255]));
+        assert_eq!(image.get_pixel(99, 0), &Rgb([30, 120, 30]));
+        assert_eq!(image.get_pixel(60, 35), &Rgb([30, 120, 30]));
+
+        let masked = masker.mask_bytes(&encode(&image)).unwrap();
+        let decoded = image::load_from_memory(&masked.data).unwrap().to_rgb8();
+        assert_eq!(decoded.dimensions(), (100, 60));
+        assert!(decoded.get_pixel(10, 10).0[1] < 30);
+    }
+
+    #[test]
+    fn invalid_settings_are_rejected() {
+        let polygon = |points: Vec<[f64; 2]>| PrivacyConfig {
+            polygons: vec![points],
+            ..Default::default()
+        };
+        assert!(PrivacyMasker::new(polygon(vec![[0.0, 0.0], [1.0, 1.0]])).is_err());
+        assert!(PrivacyMasker::new(polygon(vec![[0.0, 0.0], [1.5, 0.0], [1.0, 1.0]])).is_err());
+        assert!(!PrivacyConfig::default().is_enabled());
+        assert!(PrivacyMasker::new(PrivacyConfig {
+            blur_people: true,
+            people: PersonBlurConfig {
+                block_size: 0,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .is_err());
+        // People are never left unblurred for want of a detector
+        assert!(PrivacyMasker::new(PrivacyConfig {
+            blur_people: true,
+            ..Default::default()
+        })
+        .is_err());
+    }
+
+    #[cfg(feature = "onnx")]
+    #[test]
+    fn detected_people_are_pixelated_even_when_still() {
+        let path = std::env::temp_dir().join(format!("privacy_people_{}.onnx", std::process::id()));
+        let mut masker = PrivacyMasker::new(PrivacyConfig {
+            blur_people: true,
+            people: PersonBlurConfig {
+                detector: crate::person_detector::tests::brightness_detector(&path),
+                padding: 0.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        // A striped figure stands in the same place for many frames
+        let mut scene = RgbImage::from_pixel(320, 320, Rgb([220, 220, 220]));
+        for y in 100..220 {
+            for x in 100..140 {
+                let stripe = if (x + y) % 4 < 2 { 250 } else { 10 };
+                scene.put_pixel(x, y, Rgb([stripe, stripe, stripe]));
+            }
+        }
+        for _ in 0..20 {
+            let mut frame = scene.clone();
+            assert_eq!(masker.mask(&mut frame).unwrap(), 2);
+            // The figure's stripes are averaged away
+            let changed = (100..220)
+                .flat_map(|y| (100..140).map(move |x| (x, y)))
+                .filter(|&(x, y)| frame.get_pixel(x, y) != scene.get_pixel(x, y))
+                .count();
+            assert!(changed > 40 * 120 / 2, "{}", changed);
+            assert_eq!(frame.get_pixel(10, 10), &Rgb([220, 220, 220]));
+        }
+
+        // Frames the detector finds nobody in are left alone
+        let mut dark = RgbImage::from_pixel(320, 320, Rgb([20, 20, 20]));
+        assert_eq!(masker.mask(&mut dark).unwrap(), 0);
+    }
+}
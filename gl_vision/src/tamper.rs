@@ -0,0 +1,423 @@
+//! ABOUTME: Camera tamper detection against a learned reference scene
+//! ABOUTME: Flags moved or obstructed views, defocused or covered lenses, and sudden blackouts
+
+use gl_core::{Error, Result};
+use image::imageops::FilterType;
+use image::GrayImage;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+/// Configuration for tamper detection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TamperConfig {
+    /// Frames averaged into the reference scene before detection starts
+    pub learning_frames: u32,
+    /// Per-pixel difference (0-255, after removing overall brightness) that counts as changed
+    pub pixel_threshold: u8,
+    /// Fraction of changed pixels that means the view itself is different
+    pub scene_change_ratio: f64,
+    /// Edge energy below this fraction of the reference means the lens is defocused or covered
+    pub edge_loss_ratio: f64,
+    /// Brightness below this fraction of the reference means the lens is covered
+    pub brightness_loss_ratio: f64,
+    /// Consecutive suspicious frames required before reporting, and clean frames before clearing
+    pub persist_frames: u32,
+    /// Frames after which a moved view is accepted as the new reference; 0 never re-learns
+    pub relearn_after_frames: u32,
+    /// Weight of each clean frame blended into the reference, to follow daylight
+    pub adaptation_rate: f64,
+    /// Analysis resolution
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Default for TamperConfig {
+    fn default() -> Self {
+        Self {
+            learning_frames: 10,
+            pixel_threshold: 40,
+            scene_change_ratio: 0.5,
+            edge_loss_ratio: 0.35,
+            brightness_loss_ratio: 0.25,
+            persist_frames: 3,
+            relearn_after_frames: 0,
+            adaptation_rate: 0.05,
+            width: 160,
+            height: 120,
+        }
+    }
+}
+
+/// What kind of tampering a frame shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TamperKind {
+    /// Most of the picture changed at once: camera moved, or view blocked or painted over
+    SceneChange,
+    /// Edges disappeared: lens defocused, fogged or covered with something translucent
+    Defocus,
+    /// Picture went dark relative to the reference: lens covered
+    BrightnessLoss,
+}
+
+impl TamperKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::SceneChange => "scene_change",
+            Self::Defocus => "defocus",
+            Self::BrightnessLoss => "brightness_loss",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::SceneChange => "Camera view changed: moved or obstructed",
+            Self::Defocus => "Camera image lost focus or detail: lens defocused or covered",
+            Self::BrightnessLoss => "Camera image went dark: lens covered",
+        }
+    }
+}
+
+/// Measurements for one frame compared to the reference
+#[derive(Debug, Clone, PartialEq)]
+pub struct TamperAnalysis {
+    /// Still building the reference scene; no verdict yet
+    pub learning: bool,
+    /// Tampering confirmed on this frame (persisted for `persist_frames`)
+    pub tampered: Option<TamperKind>,
+    /// True only on the frame where `tampered` changed from `None`
+    pub newly_tampered: bool,
+    /// Fraction of pixels that differ from the reference
+    pub changed_ratio: f64,
+    /// Edge energy relative to the reference
+    pub edge_ratio: f64,
+    /// Mean brightness relative to the reference
+    pub brightness_ratio: f64,
+}
+
+/// Reference scene learned from the first frames of a stream
+struct Reference {
+    pixels: Vec<f32>,
+    mean: f64,
+    edges: f64,
+}
+
+/// Compares frames against a learned reference scene; keep one per camera
+pub struct TamperDetector {
+    config: TamperConfig,
+    reference: Option<Reference>,
+    /// Running sum of learning frames
+    learning: Vec<f32>,
+    learned_frames: u32,
+    suspect: Option<(TamperKind, u32)>,
+    tampered: Option<TamperKind>,
+    tampered_frames: u32,
+    clean_frames: u32,
+}
+
+impl TamperDetector {
+    pub fn new(config: TamperConfig) -> Result<Self> {
+        if config.width < 8 || config.height < 8 {
+            return Err(Error::Validation(
+                "Tamper analysis resolution must be at least 8x8".to_string(),
+            ));
+        }
+        Ok(Self {
+            config,
+            reference: None,
+            learning: Vec::new(),
+            learned_frames: 0,
+            suspect: None,
+            tampered: None,
+            tampered_frames: 0,
+            clean_frames: 0,
+        })
+    }
+
+    /// Decode an encoded frame and analyze it
+    pub fn analyze_bytes(&mut self, data: &[u8]) -> Result<TamperAnalysis> {
+        let image = image::load_from_memory(data)
+            .map_err(|e| Error::Validation(format!("Failed to decode frame: {}", e)))?;
+        Ok(self.analyze(&image.to_luma8()))
+    }
+
+    /// Analyze a grayscale frame
+    pub fn analyze(&mut self, frame: &GrayImage) -> TamperAnalysis {
+        let small = image::imageops::resize(
+            frame,
+            self.config.width,
+            self.config.height,
+            FilterType::Triangle,
+        );
+        let pixels: Vec<f32> = small.iter().map(|&p| p as f32).collect();
+
+        let Some(reference) = &self.reference else {
+            self.learn(&pixels);
+            return TamperAnalysis {
+                learning: true,
+                tampered: None,
+                newly_tampered: false,
+                changed_ratio: 0.0,
+                edge_ratio: 1.0,
+                brightness_ratio: 1.0,
+            };
+        };
+
+        let mean = mean(&pixels);
+        let edges = edge_energy(&pixels, self.config.width as usize);
+        // Compare structure, not exposure: a light switching on shifts every pixel equally
+        let threshold = self.config.pixel_threshold as f64;
+        let changed = pixels
+            .iter()
+            .zip(&reference.pixels)
+            .filter(|(&cur, &old)| {
+                ((cur as f64 - mean) - (old as f64 - reference.mean)).abs() > threshold
+            })
+            .count();
+        let changed_ratio = changed as f64 / pixels.len() as f64;
+        let edge_ratio = edges / reference.edges.max(1.0);
+        let brightness_ratio = mean / reference.mean.max(1.0);
+
+        let candidate = if brightness_ratio < self.config.brightness_loss_ratio {
+            Some(TamperKind::BrightnessLoss)
+        } else if edge_ratio < self.config.edge_loss_ratio {
+            Some(TamperKind::Defocus)
+        } else if changed_ratio > self.config.scene_change_ratio {
+            Some(TamperKind::SceneChange)
+        } else {
+            None
+        };
+
+        let was_tampered = self.tampered.is_some();
+        self.update_state(candidate);
+
+        if self.tampered.is_none() && candidate.is_none() {
+            self.adapt(&pixels);
+        } else if self.tampered == Some(TamperKind::SceneChange)
+            && self.config.relearn_after_frames > 0
+            && self.tampered_frames >= self.config.relearn_after_frames
+        {
+            info!("Camera view changed for good; learning the new scene");
+            self.reset();
+            self.learn(&pixels);
+        }
+
+        let analysis = TamperAnalysis {
+            learning: false,
+            tampered: self.tampered,
+            newly_tampered: !was_tampered && self.tampered.is_some(),
+            changed_ratio,
+            edge_ratio,
+            brightness_ratio,
+        };
+        debug!(?analysis, "Tamper analysis");
+        analysis
+    }
+
+    /// Forget the reference scene and start learning again
+    pub fn reset(&mut self) {
+        self.reference = None;
+        self.learning.clear();
+        self.learned_frames = 0;
+        self.suspect = None;
+        self.tampered = None;
+        self.tampered_frames = 0;
+        self.clean_frames = 0;
+    }
+
+    fn learn(&mut self, pixels: &[f32]) {
+        if self.learning.is_empty() {
+            self.learning = vec![0.0; pixels.len()];
+        }
+        for (sum, &p) in self.learning.iter_mut().zip(pixels) {
+            *sum += p;
+        }
+        self.learned_frames += 1;
+
+        if self.learned_frames >= self.config.learning_frames.max(1) {
+            let count = self.learned_frames as f32;
+            let reference: Vec<f32> = self.learning.iter().map(|sum| sum / count).collect();
+            self.reference = Some(Reference {
+                mean: mean(&reference),
+                edges: edge_energy(&reference, self.config.width as usize),
+                pixels: reference,
+            });
+            self.learning.clear();
+            debug!(
+                frames = self.learned_frames,
+                "Tamper reference scene learned"
+            );
+        }
+    }
+
+    fn update_state(&mut self, candidate: Option<TamperKind>) {
+        let persist = self.config.persist_frames.max(1);
+        match candidate {
+            Some(kind) => {
+                self.clean_frames = 0;
+                let count = match self.suspect {
+                    Some((previous, count)) if previous == kind => count + 1,
+                    _ => 1,
+                };
+                self.suspect = Some((kind, count));
+                if self.tampered.is_some() {
+                    self.tampered_frames += 1;
+                } else if count >= persist {
+                    self.tampered = Some(kind);
+                    self.tampered_frames = count;
+                }
+            }
+            None => {
+                self.suspect = None;
+                if self.tampered.is_some() {
+                    self.clean_frames += 1;
+                    if self.clean_frames >= persist {
+                        info!("Camera view matches the reference again");
+                        self.tampered = None;
+                        self.tampered_frames = 0;
+                    }
+                }
+            }
+        }
+    }
+
+    fn adapt(&mut self, pixels: &[f32]) {
+        let Some(reference) = &mut self.reference else {
+            return;
+        };
+        let rate = self.config.adaptation_rate.clamp(0.0, 1.0) as f32;
+        if rate == 0.0 {
+            return;
+        }
+        for (old, &cur) in reference.pixels.iter_mut().zip(pixels) {
+            *old += (cur - *old) * rate;
+        }
+        reference.mean = mean(&reference.pixels);
+        reference.edges = edge_energy(&reference.pixels, self.config.width as usize);
+    }
+}
+
+fn mean(pixels: &[f32]) -> f64 {
+    pixels.iter().map(|&p| p as f64).sum::<f64>() / pixels.len().max(1) as f64
+}
+
+/// Mean absolute horizontal plus vertical gradient
+fn edge_energy(pixels: &[f32], width: usize) -> f64 {
+    let height = pixels.len() / width;
+    let mut total = 0.0;
+    for y in 0..height - 1 {
+        for x in 0..width - 1 {
+            let p = pixels[y * width + x];
+            total += (pixels[y * width + x + 1] - p).abs() as f64;
+            total += (pixels[(y + 1) * width + x] - p).abs() as f64;
+        }
+    }
+    total / ((width - 1) * (height - 1)) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageBuffer, Luma};
+
+    /// Checkerboard with plenty of edges; `offset` shifts the pattern
+    fn scene(offset: u32, brightness: u8) -> GrayImage {
+        ImageBuffer::from_fn(320, 240, |x, y| {
+            if ((x + offset) / 20 + y / 20) % 2 == 0 {
+                Luma([brightness])
+            } else {
+                Luma([brightness / 4])
+            }
+        })
+    }
+
+    fn trained() -> TamperDetector {
+        let mut detector = TamperDetector::new(TamperConfig::default()).unwrap();
+        for _ in 0..10 {
+            assert!(detector.analyze(&scene(0, 200)).learning);
+        }
+        detector
+    }
+
+    #[test]
+    fn unchanged_scene_is_not_tampered() {
+        let mut detector = trained();
+        for _ in 0..5 {
+            let analysis = detector.analyze(&scene(0, 200));
+            assert!(!analysis.learning);
+            assert_eq!(analysis.tampered, None);
+            assert!(analysis.changed_ratio < 0.01);
+        }
+    }
+
+    #[test]
+    fn moved_camera_is_reported_after_persisting() {
+        let mut detector = trained();
+        let moved = scene(20, 200);
+
+        assert_eq!(detector.analyze(&moved).tampered, None);
+        assert_eq!(detector.analyze(&moved).tampered, None);
+        let analysis = detector.analyze(&moved);
+        assert_eq!(analysis.tampered, Some(TamperKind::SceneChange));
+        assert!(analysis.newly_tampered);
+        assert!(!detector.analyze(&moved).newly_tampered);
+
+        // Back in place: cleared after the same number of clean frames
+        for _ in 0..3 {
+            detector.analyze(&scene(0, 200));
+        }
+        assert_eq!(detector.analyze(&scene(0, 200)).tampered, None);
+    }
+
+    #[test]
+    fn covered_and_defocused_lenses_are_distinguished() {
+        let mut detector = trained();
+        let covered = ImageBuffer::from_pixel(320, 240, Luma([5u8]));
+        let mut last = None;
+        for _ in 0..3 {
+            last = Some(detector.analyze(&covered));
+        }
+        assert_eq!(
+            last.take().unwrap().tampered,
+            Some(TamperKind::BrightnessLoss)
+        );
+
+        let mut detector = trained();
+        let blurred = image::imageops::blur(&scene(0, 200), 12.0);
+        for _ in 0..3 {
+            last = Some(detector.analyze(&blurred));
+        }
+        let analysis = last.unwrap();
+        assert_eq!(analysis.tampered, Some(TamperKind::Defocus));
+        assert!(analysis.edge_ratio < 0.35);
+    }
+
+    #[test]
+    fn lighting_changes_are_not_tampering() {
+        let mut detector = trained();
+        for _ in 0..5 {
+            assert_eq!(detector.analyze(&scene(0, 140)).tampered, None);
+        }
+    }
+
+    #[test]
+    fn moved_view_can_be_relearned() {
+        let mut detector = TamperDetector::new(TamperConfig {
+            learning_frames: 2,
+            relearn_after_frames: 4,
+            ..Default::default()
+        })
+        .unwrap();
+        detector.analyze(&scene(0, 200));
+        detector.analyze(&scene(0, 200));
+
+        let moved = scene(20, 200);
+        for _ in 0..4 {
+            detector.analyze(&moved);
+        }
+        // The new view became the reference; learning it takes two frames
+        assert!(detector.analyze(&moved).learning);
+        assert_eq!(detector.analyze(&moved).tampered, None);
+    }
+}
@@ -346,3 +346,109 @@ async fn test_motion_detection_performance() {
         avg_processing_time
     );
 }
+
+fn gaussian_mixture_config() -> MotionConfig {
+    MotionConfig {
+        algorithm: MotionAlgorithm::GaussianMixture,
+        threshold: 0.05,
+        min_change_area: 50,
+        downscale_factor: 1,
+        max_width: 100,
+        max_height: 100,
+    }
+}
+
+/// The Gaussian mixture detector matches PixelDiff on the known-motion fixtures
+#[tokio::test]
+async fn test_gaussian_mixture_known_motion() {
+    let mut service = MotionDetectionService::new(gaussian_mixture_config()).unwrap();
+    let (frame1, frame2) = create_test_frame_pair(100, 100);
+    let frame1_bytes = image_to_jpeg_bytes(&frame1).unwrap();
+    let frame2_bytes = image_to_jpeg_bytes(&frame2).unwrap();
+
+    let result1 = service.detect_motion_from_bytes(&frame1_bytes).unwrap();
+    assert!(!result1.motion_detected);
+    assert_eq!(result1.changed_pixels, 0);
+
+    let result2 = service.detect_motion_from_bytes(&frame2_bytes).unwrap();
+    assert!(result2.motion_detected);
+    assert!(result2.confidence > 0.7);
+    assert_eq!(result2.algorithm_used, "GaussianMixture");
+    // The 50x50 object in the fixture, give or take JPEG edges
+    assert!(
+        (2300..=2700).contains(&result2.changed_pixels),
+        "changed_pixels = {}",
+        result2.changed_pixels
+    );
+
+    let mut service = MotionDetectionService::new(gaussian_mixture_config()).unwrap();
+    let still = image_to_jpeg_bytes(&create_test_frame_with_motion(
+        100, 100, 20, 20, 30, 30, 200,
+    ))
+    .unwrap();
+    service.detect_motion_from_bytes(&still).unwrap();
+    let result = service.detect_motion_from_bytes(&still).unwrap();
+    assert!(!result.motion_detected);
+    assert_eq!(result.changed_pixels, 0);
+}
+
+/// Foliage swaying between two appearances keeps triggering PixelDiff, but becomes part of
+/// the Gaussian mixture background while real objects are still detected
+#[tokio::test]
+async fn test_gaussian_mixture_ignores_swaying_foliage() {
+    let leaves = |frame: usize| {
+        let shade = if frame % 2 == 0 { 90 } else { 150 };
+        create_test_frame_with_motion(100, 100, 0, 0, 40, 40, shade)
+    };
+
+    let mut pixel_diff = MotionDetectionService::new(MotionConfig {
+        algorithm: MotionAlgorithm::PixelDiff,
+        ..gaussian_mixture_config()
+    })
+    .unwrap();
+    let mut mixture = MotionDetectionService::new(gaussian_mixture_config()).unwrap();
+
+    let mut pixel_diff_alerts = 0;
+    let mut mixture_alerts = 0;
+    for frame in 0..60 {
+        let bytes = image_to_jpeg_bytes(&leaves(frame)).unwrap();
+        let diff = pixel_diff.detect_motion_from_bytes(&bytes).unwrap();
+        let gmm = mixture.detect_motion_from_bytes(&bytes).unwrap();
+        // Judge once the mixture has had a chance to learn both appearances
+        if frame >= 30 {
+            pixel_diff_alerts += diff.motion_detected as u32;
+            mixture_alerts += gmm.motion_detected as u32;
+        }
+    }
+    assert_eq!(pixel_diff_alerts, 30);
+    assert_eq!(mixture_alerts, 0);
+
+    // Someone walks into the clear part of the scene
+    let mut intruder = leaves(60);
+    for y in 50..90 {
+        for x in 55..75 {
+            intruder.put_pixel(x, y, gl_vision::image::Luma([220]));
+        }
+    }
+    let result = mixture
+        .detect_motion_from_bytes(&image_to_jpeg_bytes(&intruder).unwrap())
+        .unwrap();
+    assert!(result.motion_detected);
+    assert!(result.changed_pixels >= 600);
+}
+
+/// Selecting MOG2 without OpenCV keeps falling back to PixelDiff; the Gaussian mixture
+/// model is only used when chosen explicitly
+#[cfg(not(feature = "heavy_opencv"))]
+#[tokio::test]
+async fn test_mog2_falls_back_to_pixel_diff() {
+    let mut service = MotionDetectionService::new(MotionConfig {
+        algorithm: MotionAlgorithm::Mog2,
+        ..gaussian_mixture_config()
+    })
+    .unwrap();
+    let frame =
+        image_to_jpeg_bytes(&create_test_frame_with_motion(100, 100, 0, 0, 0, 0, 64)).unwrap();
+    let result = service.detect_motion_from_bytes(&frame).unwrap();
+    assert_eq!(result.algorithm_used, "PixelDiff");
+}
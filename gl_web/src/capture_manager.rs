@@ -3,31 +3,83 @@
 
 use async_trait::async_trait;
 use bytes::Bytes;
-use gl_analysis::{AnalysisConfig, AnalysisService, ProcessorContext, ProcessorInput};
+use chrono::DurationRound;
+use gl_ai::Embedder;
+use gl_analysis::{
+    AnalysisConfig, AnalysisEvent, AnalysisService, ArtifactStore, CaptureControl, DigestJob,
+    HealthConfig, HealthIssue, ProcessorContext, ProcessorInput, SnapshotEmbeddingJob,
+    StreamHealthMonitor, VisualQuestion, VisualQuestionJob,
+};
 use gl_capture::{
     artifact_storage::{ArtifactStorageConfig, ArtifactStorageService},
-    CaptureHandle, CaptureSource, FfmpegConfig, FfmpegSource, FileSource, HardwareAccel,
-    OutputFormat, RtspTransport, SnapshotConfig, YtDlpConfig, YtDlpSource,
+    AccessUnit, CaptureHandle, CaptureSource, FfmpegConfig, FfmpegKeyframeDecoder, FfmpegSource,
+    FileSource, HardwareAccel, HttpAuthScheme, HttpSnapshotConfig, HttpSnapshotSource,
+    InProcessKeyframeDecoder, KeyframeDecoder, NativeRtspConfig, NativeRtspSource, OutputFormat,
+    RtspTransport, SnapshotConfig, WatchFolderConfig, WatchFolderSource, YtDlpConfig, YtDlpSource,
 };
 #[cfg(feature = "website")]
 use gl_capture::{WebsiteConfig, WebsiteSource};
 use gl_config::Config as AppConfig;
 use gl_core::{time::now_iso8601, Error, Result};
-use gl_db::{CreateSnapshotRequest, SnapshotRepository, Stream, StreamRepository};
-use gl_notify::NotificationManager;
+use gl_db::{
+    ApiKeyRepository, CreateOriginalFrame, CreateSnapshotRequest, MotionHeatmapRepository,
+    OriginalFrame, OriginalFrameRepository, SnapshotRepository, Stream, StreamRepository,
+    UserRepository,
+};
+use gl_ingest::{FtpServer, IngestSink, IngestTarget, SmtpServer, Upload};
+use gl_notify::{
+    adapters::{
+        EmailAdapter, PushoverAdapter, SmtpSettings, VapidKeys, WebPushAdapter, WebhookAdapter,
+    },
+    MessageTemplate, NotificationChannel, NotificationManager, NotificationTemplates,
+};
 use gl_scheduler::{CaptureResult, CaptureService};
-use gl_storage::StorageManager;
+use gl_storage::{StorageManager, StorageUri};
+use gl_stream::{RestreamBackend, RestreamFeed, RestreamServer, RestreamServerConfig};
+use gl_vision::{FrameQuality, HeatGrid, HeatmapConfig, MotionHeatmapRecorder};
 use serde_json::Value;
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Weak};
 use std::time::Duration;
 use tokio::sync::{broadcast, oneshot, RwLock};
 use tokio::task::JoinHandle;
 use tokio::time::interval;
 use tracing::{debug, error, info, warn};
 
+use crate::auth::PasswordAuth;
 use crate::background_snapshot_service::BackgroundSnapshotService;
+use crate::privacy::{
+    delete_expired_originals, OriginalPurgeJob, StreamPrivacy, StreamPrivacyConfig,
+};
+
+/// How often a running capture re-checks its health when no frames arrive
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+/// A stream's masking state and the config it was built from
+type PrivacyEntry = (String, Arc<StreamPrivacy>);
+
+/// Health monitor shared between a capture task and status queries
+type SharedHealth = Arc<std::sync::Mutex<StreamHealthMonitor>>;
+
+/// Motion heatmap shared between a capture loop and its frame observations
+type SharedHeatmap = Arc<std::sync::Mutex<HeatmapTrack>>;
+
+/// Motion accumulated for a stream and the hour it belongs to
+struct HeatmapTrack {
+    recorder: MotionHeatmapRecorder,
+    hour_start: chrono::DateTime<chrono::Utc>,
+}
+
+impl HeatmapTrack {
+    /// Hand over the accumulated hour, if any frames were seen
+    fn take(&mut self) -> Option<(chrono::DateTime<chrono::Utc>, HeatGrid)> {
+        let grid = self.recorder.take();
+        (!grid.is_empty()).then_some((self.hour_start, grid))
+    }
+}
 
 /// Status of a running capture
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -58,6 +110,8 @@ pub struct CaptureInfo {
     pub status: CaptureStatus,
     pub started_at: Option<chrono::DateTime<chrono::Utc>>,
     pub last_frame_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Problems the health watchdog currently sees; empty when healthy
+    pub health_issues: Vec<HealthIssue>,
 }
 
 /// Handle for a running capture task with broadcast capabilities
@@ -69,6 +123,25 @@ struct CaptureTask {
     frame_sender: broadcast::Sender<Bytes>,
     /// Latest snapshot data for immediate API responses
     latest_snapshot: Arc<RwLock<Option<Bytes>>>,
+    health: SharedHealth,
+    /// Motion not yet written out, flushed when the capture is stopped
+    heatmap: Option<SharedHeatmap>,
+    /// Scheduler jobs asking this stream's visual questions
+    question_jobs: Vec<String>,
+}
+
+impl CaptureTask {
+    /// Capture info with frame timing and health filled in from the watchdog
+    fn info(&self) -> CaptureInfo {
+        let mut info = self.info.clone();
+        let health = self.health.lock().unwrap();
+        info.last_frame_at = health.last_frame_at();
+        info.health_issues = health.issues();
+        if info.status == CaptureStatus::Starting && info.last_frame_at.is_some() {
+            info.status = CaptureStatus::Active;
+        }
+        info
+    }
 }
 
 impl std::fmt::Debug for CaptureTask {
@@ -82,6 +155,8 @@ impl std::fmt::Debug for CaptureTask {
             )
             .field("subscribers", &self.frame_sender.receiver_count())
             .field("has_latest_snapshot", &"Arc<RwLock<Option<Bytes>>>")
+            .field("health", &self.health)
+            .field("heatmap", &self.heatmap.is_some())
             .finish()
     }
 }
@@ -94,6 +169,15 @@ pub struct CaptureManager {
     storage_config: gl_config::StorageConfig,
     job_scheduler: Arc<RwLock<Option<Arc<gl_scheduler::JobScheduler>>>>,
     background_snapshot_service: Arc<BackgroundSnapshotService>,
+    notification_templates: Arc<NotificationTemplates>,
+    public_base_url: Option<String>,
+    web_push: Arc<RwLock<Option<Arc<WebPushAdapter>>>>,
+    /// Model that indexes snapshots for semantic search, if enabled
+    embedder: Arc<RwLock<Option<Arc<dyn Embedder>>>>,
+    /// Native RTSP sessions opened for restream clients, dropped with their last client
+    rtsp_upstreams: Arc<tokio::sync::Mutex<HashMap<String, Weak<CaptureHandle>>>>,
+    /// Masking state per stream, with the stream config it was built from
+    privacy: Arc<std::sync::Mutex<HashMap<String, PrivacyEntry>>>,
 }
 
 impl CaptureManager {
@@ -109,6 +193,12 @@ impl CaptureManager {
             storage_config: gl_config::StorageConfig::default(),
             job_scheduler: Arc::new(RwLock::new(None)),
             background_snapshot_service,
+            notification_templates: Arc::new(NotificationTemplates::new()),
+            public_base_url: None,
+            web_push: Arc::new(RwLock::new(None)),
+            embedder: Arc::new(RwLock::new(None)),
+            rtsp_upstreams: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+            privacy: Arc::new(std::sync::Mutex::new(HashMap::new())),
         };
 
         // Reset any stale "active" statuses from previous server runs
@@ -137,6 +227,12 @@ impl CaptureManager {
             storage_config,
             job_scheduler: Arc::new(RwLock::new(None)),
             background_snapshot_service,
+            notification_templates: Arc::new(NotificationTemplates::new()),
+            public_base_url: None,
+            web_push: Arc::new(RwLock::new(None)),
+            embedder: Arc::new(RwLock::new(None)),
+            rtsp_upstreams: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+            privacy: Arc::new(std::sync::Mutex::new(HashMap::new())),
         }
     }
 
@@ -147,6 +243,8 @@ impl CaptureManager {
         app_config: &AppConfig,
         background_snapshot_service: Arc<BackgroundSnapshotService>,
     ) -> Result<Self> {
+        let notification_templates = Self::build_notification_templates(&app_config.notifications)?;
+
         let mut manager = Self {
             db_pool: db_pool.clone(),
             running_captures: Arc::new(RwLock::new(HashMap::new())),
@@ -154,6 +252,16 @@ impl CaptureManager {
             storage_config,
             job_scheduler: Arc::new(RwLock::new(None)),
             background_snapshot_service,
+            notification_templates: Arc::new(notification_templates.clone()),
+            public_base_url: app_config
+                .notifications
+                .public_base_url
+                .as_ref()
+                .map(|url| url.trim_end_matches('/').to_string()),
+            web_push: Arc::new(RwLock::new(None)),
+            embedder: Arc::new(RwLock::new(None)),
+            rtsp_upstreams: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+            privacy: Arc::new(std::sync::Mutex::new(HashMap::new())),
         };
 
         // Initialize analysis service if AI is enabled
@@ -166,26 +274,55 @@ impl CaptureManager {
             // When AI features are enabled, ensure online mode is active
             ai_config.use_online = true;
 
-            // Validate that we have an API key for online mode
-            if ai_config.api_key.is_none() || ai_config.api_key.as_ref().unwrap().is_empty() {
+            // OpenAI needs an API key; local model servers usually run without one
+            if ai_config.provider == gl_ai::AiProvider::OpenAi
+                && ai_config.api_key.as_deref().unwrap_or_default().is_empty()
+            {
                 warn!("AI features enabled but no API key provided, falling back to offline mode");
                 ai_config.use_online = false;
             }
 
-            let analysis_config = AnalysisConfig {
+            let mut analysis_config = AnalysisConfig {
                 ai: Some(ai_config),
                 ..Default::default()
             };
+            // Text that arrives with pushed snapshots (email subject/body) becomes an event
+            analysis_config
+                .enabled_processors
+                .insert(0, "message".to_string());
+            analysis_config
+                .enabled_processors
+                .insert(1, "tamper".to_string());
+            // Only reads streams whose config has a `plates` section, and only with a
+            // recognition model to read them
+            if app_config.plates.reader.ocr.model_path.is_empty() {
+                info!("Plate reading disabled: plates.ocr.model_path is not set");
+            } else {
+                analysis_config
+                    .enabled_processors
+                    .insert(2, "plate".to_string());
+                analysis_config.processor_configs.insert(
+                    "plate".to_string(),
+                    serde_json::to_value(&app_config.plates).map_err(|e| {
+                        Error::Config(format!("Invalid plate reading config: {}", e))
+                    })?,
+                );
+            }
 
             // Create notification manager (stub for now, can be enhanced later)
-            let notification_manager = NotificationManager::new();
+            let mut notification_manager = NotificationManager::new();
+            notification_manager.set_templates(notification_templates);
 
             // Create analysis service with persistence
-            let analysis_service = AnalysisService::with_persistence(
+            let mut analysis_service = AnalysisService::with_persistence(
                 analysis_config,
                 gl_db::Db::from_pool(db_pool.clone()),
                 notification_manager,
             )?;
+            // Images attached to events, such as plate crops, go to artifact storage
+            analysis_service.set_artifact_store(Arc::new(EventArtifacts {
+                storage_service: manager.artifact_storage_service(),
+            }));
 
             manager.analysis_service = Some(Arc::new(tokio::sync::Mutex::new(analysis_service)));
             info!("Analysis service initialized successfully");
@@ -205,14 +342,289 @@ impl CaptureManager {
         Ok(manager)
     }
 
+    /// Build notification templates from configuration
+    fn build_notification_templates(
+        config: &gl_config::NotificationsConfig,
+    ) -> Result<NotificationTemplates> {
+        let mut templates = NotificationTemplates::new();
+
+        if let Some(offset) = &config.utc_offset {
+            let parsed = gl_notify::templates::parse_utc_offset(offset).ok_or_else(|| {
+                Error::Config(format!("Invalid notifications.utc_offset: {}", offset))
+            })?;
+            templates.set_utc_offset(Some(parsed));
+        }
+
+        for (channel, template) in &config.channel_templates {
+            templates
+                .set_channel_template(
+                    channel.clone(),
+                    MessageTemplate::new(&template.title, &template.body),
+                )
+                .map_err(|e| {
+                    Error::Config(format!("Invalid template for channel '{}': {}", channel, e))
+                })?;
+        }
+
+        for (name, template) in &config.templates {
+            templates
+                .set_named_template(
+                    name.clone(),
+                    MessageTemplate::new(&template.title, &template.body),
+                )
+                .map_err(|e| {
+                    Error::Config(format!("Invalid notification template '{}': {}", name, e))
+                })?;
+        }
+
+        Ok(templates)
+    }
+
+    /// Get the configured notification templates
+    pub fn notification_templates(&self) -> Arc<NotificationTemplates> {
+        self.notification_templates.clone()
+    }
+
+    /// Set up Web Push delivery to registered browser subscriptions
+    ///
+    /// Uses the configured VAPID key pair, or one generated and stored in the
+    /// database on first start so existing subscriptions stay valid.
+    pub async fn enable_web_push(&self, config: &gl_config::WebPushConfig) -> Result<()> {
+        let repo =
+            gl_db::PushSubscriptionRepository::new(gl_db::Db::from_pool(self.db_pool.clone()));
+        let keys = match &config.vapid_private_key {
+            Some(private_key) => {
+                VapidKeys::from_base64(private_key, config.vapid_public_key.as_deref())
+            }
+            None => VapidKeys::load_or_generate(&repo).await,
+        }
+        .map_err(|e| Error::Config(format!("Invalid web push configuration: {}", e)))?;
+
+        let adapter = Arc::new(
+            WebPushAdapter::new(keys, config.subject.clone())
+                .with_ttl(config.ttl_seconds)
+                .with_subscriptions(repo),
+        );
+
+        if let Some(analysis_service) = &self.analysis_service {
+            analysis_service
+                .lock()
+                .await
+                .register_notification_adapter("webpush".to_string(), adapter.clone());
+        }
+
+        info!(public_key = %adapter.public_key(), "Web push notifications enabled");
+        *self.web_push.write().await = Some(adapter);
+        Ok(())
+    }
+
+    /// Get the Web Push adapter, if enabled
+    pub async fn web_push(&self) -> Option<Arc<WebPushAdapter>> {
+        self.web_push.read().await.clone()
+    }
+
+    /// Index stored snapshots with an embedding model so they can be searched
+    ///
+    /// Schedules the recurring embedding job, so the job scheduler must be set first.
+    pub async fn enable_snapshot_search(&self, config: &gl_ai::EmbeddingConfig) -> Result<()> {
+        let embedder: Arc<dyn Embedder> = Arc::from(gl_ai::create_embedder(config)?);
+
+        let job_scheduler =
+            self.job_scheduler.read().await.clone().ok_or_else(|| {
+                Error::Config("Snapshot search needs the job scheduler".to_string())
+            })?;
+        job_scheduler
+            .register_handler(
+                gl_analysis::embeddings::JOB_TYPE.to_string(),
+                Arc::new(SnapshotEmbeddingJob::new(embedder.clone())),
+            )
+            .await;
+        job_scheduler
+            .schedule_recurring(gl_analysis::embeddings::job_definition(config))
+            .await?;
+
+        info!(model = %embedder.model(), "Snapshot search enabled");
+        *self.embedder.write().await = Some(embedder);
+        Ok(())
+    }
+
+    /// Schedule the recurring purge of unmasked originals past their retention
+    pub async fn enable_original_purge(&self) -> Result<()> {
+        let job_scheduler = self.job_scheduler.read().await.clone().ok_or_else(|| {
+            Error::Config("Purging originals needs the job scheduler".to_string())
+        })?;
+        job_scheduler
+            .register_handler(
+                crate::privacy::PURGE_JOB_TYPE.to_string(),
+                Arc::new(OriginalPurgeJob::new(self.artifact_storage_service())),
+            )
+            .await;
+        job_scheduler
+            .schedule_recurring(crate::privacy::purge_job_definition())
+            .await?;
+        Ok(())
+    }
+
+    /// Get the snapshot embedding model, if search is enabled
+    pub async fn embedder(&self) -> Option<Arc<dyn Embedder>> {
+        self.embedder.read().await.clone()
+    }
+
+    /// Schedule the configured digest reports
+    ///
+    /// Reports go out through webhooks, Pushover (with `PUSHOVER_APP_TOKEN` set), Web Push
+    /// when enabled and email when SMTP is configured. Call after `enable_web_push`.
+    pub async fn enable_digests(
+        &self,
+        config: &gl_config::NotificationsConfig,
+        smtp: Option<&gl_config::SmtpConfig>,
+    ) -> Result<()> {
+        let mut notifications = NotificationManager::new();
+        notifications.register_adapter("webhook".to_string(), Arc::new(WebhookAdapter::new()));
+        if let Ok(app_token) = std::env::var("PUSHOVER_APP_TOKEN") {
+            notifications.register_adapter(
+                "pushover".to_string(),
+                Arc::new(PushoverAdapter::new(app_token)),
+            );
+        }
+        if let Some(web_push) = self.web_push().await {
+            notifications.register_adapter("webpush".to_string(), web_push);
+        }
+        if let Some(smtp) = smtp {
+            let adapter = EmailAdapter::new(SmtpSettings {
+                host: smtp.host.clone(),
+                port: smtp.port,
+                username: smtp.username.clone(),
+                password: smtp.password.clone(),
+                from: smtp.from.clone().unwrap_or_else(|| smtp.username.clone()),
+            })
+            .map_err(|e| Error::Config(format!("Invalid SMTP configuration: {}", e)))?;
+            notifications.register_adapter("email".to_string(), Arc::new(adapter));
+        }
+
+        // Narratives come from the same model as frame analysis when AI is enabled
+        let client = match &self.analysis_service {
+            Some(analysis_service) => {
+                let ai_config = analysis_service.lock().await.ai_config();
+                Some(Arc::from(gl_ai::create_client(ai_config)))
+            }
+            None => None,
+        };
+
+        let job_scheduler =
+            self.job_scheduler.read().await.clone().ok_or_else(|| {
+                Error::Config("Digest reports need the job scheduler".to_string())
+            })?;
+        job_scheduler
+            .register_handler(
+                gl_analysis::digest::JOB_TYPE.to_string(),
+                Arc::new(DigestJob::new(client, notifications)),
+            )
+            .await;
+        for digest in &config.digests {
+            if smtp.is_none()
+                && digest
+                    .channels
+                    .iter()
+                    .any(|channel| matches!(channel, NotificationChannel::Email { .. }))
+            {
+                warn!(name = %digest.name, "Digest emails need external.smtp configured");
+            }
+            job_scheduler
+                .schedule_recurring(digest.job_definition())
+                .await?;
+            info!(name = %digest.name, schedule = %digest.schedule(), "Digest report scheduled");
+        }
+        Ok(())
+    }
+
     /// Set the job scheduler for smart snapshot functionality
     /// This must be called after both CaptureManager and JobScheduler are created
     pub async fn set_job_scheduler(&self, job_scheduler: Arc<gl_scheduler::JobScheduler>) {
+        // Visual questions go through the same AI settings and budgets as frame analysis
+        if let Some(analysis_service) = &self.analysis_service {
+            let ai_config = analysis_service.lock().await.ai_config();
+            let job = VisualQuestionJob::new(
+                Arc::from(gl_ai::create_client(ai_config)),
+                Some(analysis_service.clone()),
+            );
+            job_scheduler
+                .register_handler(gl_analysis::questions::JOB_TYPE.to_string(), Arc::new(job))
+                .await;
+        }
+
         let mut scheduler_lock = self.job_scheduler.write().await;
         *scheduler_lock = Some(job_scheduler);
         info!("Job scheduler set for smart snapshot functionality");
     }
 
+    /// Visual questions from the `questions` array of a stream's config
+    fn visual_questions(stream: &Stream) -> Vec<VisualQuestion> {
+        let config: Value = serde_json::from_str(&stream.config).unwrap_or_default();
+        config
+            .get("questions")
+            .and_then(|v| v.as_array())
+            .map(|questions| {
+                questions
+                    .iter()
+                    .filter_map(|v| match serde_json::from_value(v.clone()) {
+                        Ok(question) => Some(question),
+                        Err(e) => {
+                            warn!(stream_id = %stream.id, error = %e, "Invalid visual question, skipping");
+                            None
+                        }
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Start asking a stream's visual questions on their schedules, returning the job IDs
+    async fn schedule_visual_questions(&self, stream: &Stream) -> Vec<String> {
+        let questions = Self::visual_questions(stream);
+        if questions.is_empty() {
+            return Vec::new();
+        }
+        // Questions are answered by the AI client the analysis service was set up with
+        if self.analysis_service.is_none() {
+            warn!(stream_id = %stream.id, "Visual questions need AI features enabled, skipping");
+            return Vec::new();
+        }
+        let Some(job_scheduler) = self.job_scheduler.read().await.clone() else {
+            warn!(stream_id = %stream.id, "No job scheduler for visual questions, skipping");
+            return Vec::new();
+        };
+
+        let mut job_ids = Vec::new();
+        for question in questions {
+            match job_scheduler
+                .schedule_recurring(question.job_definition(&stream.id))
+                .await
+            {
+                Ok(job_id) => job_ids.push(job_id),
+                Err(e) => {
+                    warn!(stream_id = %stream.id, question_id = %question.id, error = %e, "Failed to schedule visual question")
+                }
+            }
+        }
+        job_ids
+    }
+
+    /// Stop asking a stream's visual questions
+    async fn unschedule_visual_questions(&self, stream_id: &str, job_ids: &[String]) {
+        if job_ids.is_empty() {
+            return;
+        }
+        let Some(job_scheduler) = self.job_scheduler.read().await.clone() else {
+            return;
+        };
+        for job_id in job_ids {
+            if let Err(e) = job_scheduler.unschedule(job_id).await {
+                warn!(stream_id = %stream_id, job_id = %job_id, error = %e, "Failed to unschedule visual question");
+            }
+        }
+    }
+
     /// Execute smart snapshot job for a stream
     async fn execute_smart_snapshot_job(
         job_scheduler: Arc<gl_scheduler::JobScheduler>,
@@ -274,6 +686,8 @@ impl CaptureManager {
             .find_by_id(stream_id)
             .await?
             .ok_or_else(|| Error::NotFound(format!("Stream {} not found", stream_id)))?;
+        // Refuse to run a stream whose privacy settings can't be applied
+        let privacy = self.stream_privacy(&stream)?;
 
         // Use single write lock to atomically check and insert
         let mut captures = self.running_captures.write().await;
@@ -299,7 +713,10 @@ impl CaptureManager {
             status: CaptureStatus::Starting,
             started_at: Some(chrono::Utc::now()),
             last_frame_at: None,
+            health_issues: Vec::new(),
         };
+        let health = Arc::new(std::sync::Mutex::new(Self::health_monitor(&stream)));
+        let heatmap = Self::heatmap_track(&stream);
 
         // Create broadcast channel for real-time frame distribution (capacity of 10 frames)
         let (frame_sender, _) = broadcast::channel(10);
@@ -322,6 +739,10 @@ impl CaptureManager {
         let storage_config_clone = self.storage_config.clone();
         let job_scheduler_clone = self.job_scheduler.clone();
         let db_pool_clone = self.db_pool.clone();
+        let public_base_url = self.public_base_url.clone();
+        let health_clone = health.clone();
+        let heatmap_clone = heatmap.clone();
+        let privacy_clone = privacy.clone();
         let handle = tokio::spawn(async move {
             // Create fresh storage service instance for the async task
             let artifacts_dir = PathBuf::from(&storage_config_clone.artifacts_dir);
@@ -361,6 +782,10 @@ impl CaptureManager {
                 analysis_service_clone,
                 Some(capture_handle_sender),
                 job_scheduler_option,
+                public_base_url,
+                health_clone,
+                heatmap_clone,
+                privacy_clone,
             )
             .await;
 
@@ -401,11 +826,19 @@ impl CaptureManager {
             capture_handle: None, // Will be updated when capture handle is received
             frame_sender,
             latest_snapshot,
+            health,
+            heatmap: heatmap.map(|(track, _)| track),
+            question_jobs: Vec::new(),
         };
 
         captures.insert(stream_id.to_string(), task);
         drop(captures); // Explicitly release lock
 
+        let question_jobs = self.schedule_visual_questions(&stream).await;
+        if let Some(task) = self.running_captures.write().await.get_mut(stream_id) {
+            task.question_jobs = question_jobs;
+        }
+
         // Spawn a task to update the capture handle when it becomes available
         let running_captures_clone = self.running_captures.clone();
         let stream_id_for_handle = stream_id.to_string();
@@ -441,6 +874,12 @@ impl CaptureManager {
 
             // Abort the task and wait briefly for it to finish
             task.handle.abort();
+            self.unschedule_visual_questions(stream_id, &task.question_jobs)
+                .await;
+            if let Some(heatmap) = &task.heatmap {
+                let pending = heatmap.lock().unwrap().take();
+                Self::flush_heatmap(&self.db_pool, stream_id, pending).await;
+            }
 
             // Give the task a moment to cleanup
             tokio::time::sleep(std::time::Duration::from_millis(100)).await;
@@ -461,13 +900,13 @@ impl CaptureManager {
     /// Get status of a capture
     pub async fn get_capture_status(&self, stream_id: &str) -> Option<CaptureInfo> {
         let captures = self.running_captures.read().await;
-        captures.get(stream_id).map(|task| task.info.clone())
+        captures.get(stream_id).map(CaptureTask::info)
     }
 
     /// Get all running captures
     pub async fn get_all_captures(&self) -> Vec<CaptureInfo> {
         let captures = self.running_captures.read().await;
-        captures.values().map(|task| task.info.clone()).collect()
+        captures.values().map(CaptureTask::info).collect()
     }
 
     /// Check if a stream is currently running
@@ -501,6 +940,11 @@ impl CaptureManager {
             debug!(stream_id = %stream_id, "Taking fresh snapshot from running capture handle");
             match capture_handle.snapshot().await {
                 Ok(snapshot_data) => {
+                    let stream = StreamRepository::new(&self.db_pool)
+                        .find_by_id(stream_id)
+                        .await?
+                        .ok_or_else(|| Error::NotFound("Stream not found".to_string()))?;
+                    let snapshot_data = self.protect_stream_frame(&stream, snapshot_data).await?;
                     // Cache the snapshot for future requests
                     if let Some(latest_snapshot) = {
                         let captures = self.running_captures.read().await;
@@ -577,122 +1021,752 @@ impl CaptureManager {
             .ok_or_else(|| Error::Config("Stream config missing 'kind' field".to_string()))?;
 
         // Create a temporary capture source to get snapshot
-        match kind {
+        let snapshot = match kind {
             "file" => self.take_file_snapshot(&config).await,
-            "rtsp" => self.take_rtsp_snapshot(&config).await,
+            "rtsp" => self.take_rtsp_snapshot(stream_id, &config).await,
             "ffmpeg" => self.take_ffmpeg_snapshot(&config).await,
             "website" => self.take_website_snapshot(&config).await,
             "yt" | "youtube" => self.take_yt_snapshot(&config).await,
+            "http" => self.take_http_snapshot(&config).await,
+            "ftp" | "email" => Err(Error::NotFound(format!(
+                "No uploads received yet for stream {}",
+                stream_id
+            ))),
+            "watch" => Err(Error::NotFound(format!(
+                "No files picked up yet for stream {}; start the stream to watch its folder",
+                stream_id
+            ))),
             _ => Err(Error::Config(format!("Unsupported stream kind: {}", kind))),
+        }?;
+        self.protect_stream_frame(&stream, snapshot).await
+    }
+
+    /// Store a snapshot pushed by a camera and analyze it like a polled frame
+    ///
+    /// `metadata` is added to the analysis context (upload protocol, file name, ...) and
+    /// `text_content` is passed to processors as-is. Returns the stored snapshot ID.
+    pub async fn ingest_snapshot(
+        &self,
+        stream_id: &str,
+        data: Bytes,
+        metadata: HashMap<String, String>,
+        text_content: Option<String>,
+    ) -> Result<String> {
+        let stream = StreamRepository::new(&self.db_pool)
+            .find_by_id(stream_id)
+            .await?
+            .ok_or_else(|| Error::NotFound(format!("Stream {} not found", stream_id)))?;
+
+        let data = self.protect_stream_frame(&stream, data).await?;
+        let snapshot_id = self
+            .store_snapshot(stream_id, &stream.user_id, &data)
+            .await?;
+        if let Err(e) = SnapshotRepository::new(&self.db_pool)
+            .cleanup_old_snapshots(stream_id, 20)
+            .await
+        {
+            warn!(stream_id = %stream_id, error = %e, "Failed to cleanup old snapshots");
+        }
+
+        // Feed live viewers if the stream has a running task
+        if let Some(task) = self.running_captures.read().await.get(stream_id) {
+            *task.latest_snapshot.write().await = Some(data.clone());
+            let _ = task.frame_sender.send(data.clone());
+        }
+
+        if let Some(analysis_service) = &self.analysis_service {
+            let mut context = Self::analysis_context(&stream, self.public_base_url.as_deref());
+            for (key, value) in metadata {
+                context = context.with_metadata(key, value);
+            }
+            Self::spawn_analysis(
+                analysis_service.clone(),
+                stream_id,
+                data,
+                text_content,
+                context,
+            );
         }
+
+        Ok(snapshot_id)
     }
 
-    /// Internal method to run a persistent capture task with broadcast capabilities
-    #[allow(clippy::too_many_arguments)]
-    async fn run_persistent_capture_task(
-        db_pool: sqlx::SqlitePool,
-        storage_service: ArtifactStorageService<StorageManager>,
-        stream: Stream,
-        stream_id: String,
-        frame_sender: broadcast::Sender<Bytes>,
-        latest_snapshot: Arc<RwLock<Option<Bytes>>>,
-        analysis_service: Option<Arc<tokio::sync::Mutex<AnalysisService>>>,
-        capture_handle_sender: Option<oneshot::Sender<Arc<CaptureHandle>>>,
-        job_scheduler: Option<Arc<gl_scheduler::JobScheduler>>,
-    ) -> Result<()> {
-        info!(stream_id = %stream_id, "Running persistent capture task with broadcast");
+    /// Start the embedded FTP server that turns camera uploads into snapshots
+    ///
+    /// Must be called once the manager is wrapped in an `Arc`. Returns the bound address.
+    pub async fn start_ftp_ingest(
+        self: &Arc<Self>,
+        config: &gl_config::FtpIngestConfig,
+    ) -> Result<SocketAddr> {
+        let bind_addr = config.bind.parse().map_err(|e| {
+            Error::Config(format!(
+                "Invalid FTP ingest bind address '{}': {}",
+                config.bind, e
+            ))
+        })?;
+        let passive_address = config
+            .passive_address
+            .as_deref()
+            .map(|address| {
+                address.parse().map_err(|e| {
+                    Error::Config(format!("Invalid FTP passive address '{}': {}", address, e))
+                })
+            })
+            .transpose()?;
+
+        let server = FtpServer::bind(
+            gl_ingest::FtpIngestConfig {
+                bind_addr,
+                passive_ports: config.passive_port_start..=config.passive_port_end,
+                passive_address,
+                max_upload_bytes: (config.max_upload_mb * 1024 * 1024) as usize,
+                ..Default::default()
+            },
+            Arc::new(CaptureManagerIngest {
+                manager: Arc::downgrade(self),
+            }),
+        )
+        .await?;
+        let addr = server.local_addr()?;
 
-        // Parse stream config
-        let config: Value = serde_json::from_str(&stream.config)
-            .map_err(|e| Error::Config(format!("Invalid stream config JSON: {}", e)))?;
+        tokio::spawn(async move {
+            if let Err(e) = server.run().await {
+                error!(error = %e, "FTP ingest server stopped");
+            }
+        });
 
-        let kind = config
-            .get("kind")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| Error::Config("Stream config missing 'kind' field".to_string()))?;
+        info!(addr = %addr, "FTP ingest enabled");
+        Ok(addr)
+    }
 
-        // Update status to active with timestamp
-        let stream_repo = StreamRepository::new(&db_pool);
-        stream_repo
-            .update_execution_status(&stream_id, "active", Some(&chrono::Utc::now().to_rfc3339()))
-            .await?;
+    /// Start the embedded SMTP server that turns emailed images into snapshots
+    ///
+    /// Must be called once the manager is wrapped in an `Arc`. Returns the bound address.
+    pub async fn start_smtp_ingest(
+        self: &Arc<Self>,
+        config: &gl_config::SmtpIngestConfig,
+    ) -> Result<SocketAddr> {
+        let bind_addr = config.bind.parse().map_err(|e| {
+            Error::Config(format!(
+                "Invalid SMTP ingest bind address '{}': {}",
+                config.bind, e
+            ))
+        })?;
 
-        // Create and start the capture source based on stream type
-        let capture_handle = match kind {
-            "file" => Self::create_file_capture(&config).await?,
-            "rtsp" => Self::create_rtsp_capture(&config).await?,
-            "ffmpeg" => Self::create_ffmpeg_capture(&config).await?,
-            "website" => Self::create_website_capture(&config).await?,
-            "yt" | "youtube" => Self::create_yt_capture(&config).await?,
-            _ => return Err(Error::Config(format!("Unsupported stream kind: {}", kind))),
-        };
+        let server = SmtpServer::bind(
+            gl_ingest::SmtpIngestConfig {
+                bind_addr,
+                domain: config.domain.clone(),
+                max_message_bytes: (config.max_message_mb * 1024 * 1024) as usize,
+                ..Default::default()
+            },
+            Arc::new(CaptureManagerIngest {
+                manager: Arc::downgrade(self),
+            }),
+        )
+        .await?;
+        let addr = server.local_addr()?;
 
-        // Send the capture handle back to the main thread for efficient snapshotting
-        if let Some(sender) = capture_handle_sender {
-            let _ = sender.send(capture_handle.clone());
-        }
+        tokio::spawn(async move {
+            if let Err(e) = server.run().await {
+                error!(error = %e, "SMTP ingest server stopped");
+            }
+        });
 
-        // Get snapshot interval from config (default: 5 seconds)
-        let snapshot_interval = config
-            .get("snapshot_interval")
-            .and_then(|v| v.as_u64())
-            .unwrap_or(5);
+        info!(addr = %addr, domain = %config.domain, "SMTP ingest enabled");
+        Ok(addr)
+    }
 
-        // Setup snapshot timer
-        let mut snapshot_timer = interval(Duration::from_secs(snapshot_interval));
+    /// Start the passthrough RTSP server that re-serves native RTSP streams
+    pub async fn start_restream(
+        self: &Arc<Self>,
+        config: &gl_config::RestreamConfig,
+    ) -> Result<SocketAddr> {
+        let bind_addr = config.bind.parse().map_err(|e| {
+            Error::Config(format!(
+                "Invalid restream bind address '{}': {}",
+                config.bind, e
+            ))
+        })?;
 
-        // Get duration from config (default: 1 hour, 0 = infinite)
-        let duration = config
-            .get("duration")
-            .and_then(|v| v.as_u64())
-            .unwrap_or(3600); // Default 1 hour
+        let server = RestreamServer::bind(
+            RestreamServerConfig {
+                bind_addr,
+                max_clients: config.max_clients,
+                ..Default::default()
+            },
+            Arc::new(CaptureManagerRestream {
+                manager: Arc::downgrade(self),
+            }),
+        )
+        .await?;
+        let addr = server.local_addr()?;
 
-        // Setup end time for finite duration captures
-        let end_time = if duration > 0 {
-            Some(tokio::time::Instant::now() + Duration::from_secs(duration))
-        } else {
-            None
+        tokio::spawn(async move {
+            if let Err(e) = server.run().await {
+                error!(error = %e, "RTSP restream server stopped");
+            }
+        });
+
+        Ok(addr)
+    }
+
+    /// The stream's RTSP upstream, if a native session is already connected
+    ///
+    /// A running capture's session is preferred over one opened for restream clients.
+    async fn shared_rtsp_upstream(&self, stream_id: &str) -> Option<Arc<CaptureHandle>> {
+        let running = {
+            let captures = self.running_captures.read().await;
+            captures
+                .get(stream_id)
+                .and_then(|task| task.capture_handle.clone())
         };
+        if let Some(handle) = running.filter(|handle| handle.access_units().is_some()) {
+            return Some(handle);
+        }
 
-        // Persistent capture loop with graceful shutdown
-        loop {
-            tokio::select! {
-                _ = snapshot_timer.tick() => {
-                    // Take snapshot
-                    match capture_handle.snapshot().await {
-                        Ok(snapshot_data) => {
-                            debug!(
-                                stream_id = %stream_id,
-                                frame_size = snapshot_data.len(),
-                                subscribers = frame_sender.receiver_count(),
-                                "Captured frame for persistent task"
-                            );
+        self.rtsp_upstreams
+            .lock()
+            .await
+            .get(stream_id)
+            .and_then(Weak::upgrade)
+    }
 
-                            // Store latest snapshot in memory for fast API access
-                            {
-                                let mut snapshot_guard = latest_snapshot.write().await;
-                                *snapshot_guard = Some(snapshot_data.clone());
-                            }
+    /// Attach to a stream's RTSP upstream, connecting a native session if none is open
+    ///
+    /// Returns `None` for streams that don't exist or aren't RTSP. The session closes
+    /// when the last returned handle is dropped.
+    pub async fn attach_rtsp_upstream(
+        &self,
+        stream_id: &str,
+    ) -> Result<Option<Arc<CaptureHandle>>> {
+        let Some(stream) = StreamRepository::new(&self.db_pool)
+            .find_by_id(stream_id)
+            .await?
+        else {
+            return Ok(None);
+        };
+        // Restreams carry the camera's own video, which masking never touches
+        if StreamPrivacyConfig::from_stream(&stream)?.is_some() {
+            return Err(Error::Config(
+                "Streams with privacy masking cannot be restreamed unmasked".to_string(),
+            ));
+        }
+        if let Some(upstream) = self.shared_rtsp_upstream(stream_id).await {
+            return Ok(Some(upstream));
+        }
 
-                            // Broadcast to MJPEG streams (ignore errors if no subscribers)
-                            let _ = frame_sender.send(snapshot_data.clone());
+        let config: Value = serde_json::from_str(&stream.config)
+            .map_err(|e| Error::Config(format!("Invalid stream config JSON: {}", e)))?;
+        if config.get("kind").and_then(|v| v.as_str()) != Some("rtsp") {
+            return Ok(None);
+        }
+        if config
+            .get("backend")
+            .and_then(|v| v.as_str())
+            .is_some_and(|backend| backend.eq_ignore_ascii_case("ffmpeg"))
+        {
+            return Err(Error::Config(
+                "Restreaming needs the native RTSP backend".to_string(),
+            ));
+        }
 
-                            // Store to database and filesystem using smart snapshot job (if available)
-                            if let Some(ref scheduler) = job_scheduler {
-                                match Self::execute_smart_snapshot_job(
-                                    scheduler.clone(),
-                                    &stream_id,
-                                ).await {
-                                    Ok(_) => {
-                                        debug!(
-                                            stream_id = %stream_id,
-                                            "Smart snapshot job executed successfully"
-                                        );
-                                    }
-                                    Err(e) => {
-                                        warn!(
-                                            stream_id = %stream_id,
-                                            error = %e,
+        // Connect under the lock so clients arriving together share one session
+        let mut upstreams = self.rtsp_upstreams.lock().await;
+        if let Some(upstream) = upstreams.get(stream_id).and_then(Weak::upgrade) {
+            return Ok(Some(upstream));
+        }
+        upstreams.retain(|_, upstream| upstream.strong_count() > 0);
+
+        let upstream = Arc::new(Self::native_rtsp_source(&config)?.start().await?);
+        info!(stream_id = %stream_id, "Opened shared RTSP upstream");
+        upstreams.insert(stream_id.to_string(), Arc::downgrade(&upstream));
+        Ok(Some(upstream))
+    }
+
+    /// Connect rule actions (snapshot bursts, recordings) in the analysis service to this manager
+    ///
+    /// Must be called once the manager is wrapped in an `Arc`.
+    pub async fn enable_rule_actions(self: &Arc<Self>) {
+        if let Some(analysis_service) = &self.analysis_service {
+            let control = Arc::new(CaptureManagerControl {
+                manager: Arc::downgrade(self),
+            });
+            analysis_service.lock().await.set_capture_control(control);
+            info!("Rule actions connected to capture manager");
+        }
+    }
+
+    /// Take `count` fresh snapshots from a running stream and store each of them
+    pub async fn snapshot_burst(
+        &self,
+        stream_id: &str,
+        count: u32,
+        interval: Duration,
+    ) -> Result<Vec<String>> {
+        let stream = StreamRepository::new(&self.db_pool)
+            .find_by_id(stream_id)
+            .await?
+            .ok_or_else(|| Error::NotFound(format!("Stream {} not found", stream_id)))?;
+
+        let capture_handle = {
+            let captures = self.running_captures.read().await;
+            captures
+                .get(stream_id)
+                .and_then(|task| task.capture_handle.clone())
+        };
+
+        let mut snapshot_ids = Vec::with_capacity(count as usize);
+        for i in 0..count {
+            if i > 0 {
+                tokio::time::sleep(interval).await;
+            }
+
+            let snapshot_data = match &capture_handle {
+                Some(handle) => {
+                    self.protect_stream_frame(&stream, handle.snapshot().await?)
+                        .await?
+                }
+                None => self.take_stream_snapshot_fallback(stream_id).await?,
+            };
+            let snapshot_id = self
+                .store_snapshot(stream_id, &stream.user_id, &snapshot_data)
+                .await?;
+            snapshot_ids.push(snapshot_id);
+        }
+
+        info!(stream_id = %stream_id, count = snapshot_ids.len(), "Snapshot burst completed");
+        Ok(snapshot_ids)
+    }
+
+    /// Record a stream to an MP4 artifact for `duration` and return its storage URI
+    ///
+    /// Only sources that ffmpeg can read directly (rtsp, ffmpeg, file) can be recorded. A
+    /// running native RTSP capture is recorded from the access units it already receives.
+    pub async fn record_stream(&self, stream_id: &str, duration: Duration) -> Result<String> {
+        let stream = StreamRepository::new(&self.db_pool)
+            .find_by_id(stream_id)
+            .await?
+            .ok_or_else(|| Error::NotFound(format!("Stream {} not found", stream_id)))?;
+        // Recordings are the camera's own video, which masking never touches
+        if StreamPrivacyConfig::from_stream(&stream)?.is_some() {
+            return Err(Error::Config(format!(
+                "Stream {} has privacy masking and cannot be recorded",
+                stream_id
+            )));
+        }
+
+        let config: Value = serde_json::from_str(&stream.config)
+            .map_err(|e| Error::Config(format!("Invalid stream config JSON: {}", e)))?;
+
+        let kind = config
+            .get("kind")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::Config("Stream config missing 'kind' field".to_string()))?;
+
+        if kind == "rtsp" {
+            let units = {
+                let captures = self.running_captures.read().await;
+                captures
+                    .get(stream_id)
+                    .and_then(|task| task.capture_handle.as_ref())
+                    .and_then(|handle| handle.access_units())
+            };
+            if let Some(units) = units {
+                return self.record_access_units(stream_id, units, duration).await;
+            }
+        }
+
+        let mut args: Vec<String> = vec![
+            "-hide_banner".to_string(),
+            "-loglevel".to_string(),
+            "error".to_string(),
+            "-y".to_string(),
+        ];
+
+        let input = match kind {
+            "rtsp" => {
+                let url = config.get("url").and_then(|v| v.as_str()).ok_or_else(|| {
+                    Error::Config("RTSP stream config missing 'url' field".to_string())
+                })?;
+                let transport = config
+                    .get("transport")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("tcp")
+                    .to_lowercase();
+                if transport == "tcp" || transport == "udp" {
+                    args.extend(["-rtsp_transport".to_string(), transport]);
+                }
+                url.to_string()
+            }
+            "ffmpeg" => config
+                .get("source_url")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| {
+                    Error::Config("FFmpeg stream config missing 'source_url' field".to_string())
+                })?
+                .to_string(),
+            "file" => {
+                let file_path = config
+                    .get("file_path")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| {
+                        Error::Config("File stream config missing 'file_path' field".to_string())
+                    })?;
+                if PathBuf::from(file_path)
+                    .components()
+                    .any(|c| matches!(c, std::path::Component::ParentDir))
+                {
+                    return Err(Error::Config("Path traversal not allowed".to_string()));
+                }
+                file_path.to_string()
+            }
+            _ => {
+                return Err(Error::Config(format!(
+                    "Recording is not supported for {} streams",
+                    kind
+                )))
+            }
+        };
+
+        let temp_path = std::env::temp_dir().join(format!(
+            "glimpser_recording_{}_{}.mp4",
+            stream_id,
+            chrono::Utc::now().timestamp_millis()
+        ));
+
+        args.extend([
+            "-i".to_string(),
+            input,
+            "-t".to_string(),
+            duration.as_secs().max(1).to_string(),
+            "-c".to_string(),
+            "copy".to_string(),
+            "-movflags".to_string(),
+            "+faststart".to_string(),
+            temp_path.to_string_lossy().to_string(),
+        ]);
+
+        info!(stream_id = %stream_id, duration_secs = duration.as_secs(), "Starting stream recording");
+
+        let spec = gl_proc::CommandSpec::new("ffmpeg".into())
+            .args(args)
+            .timeout(duration + Duration::from_secs(30));
+        let result = gl_proc::run(spec).await?;
+
+        if !result.success() {
+            let _ = tokio::fs::remove_file(&temp_path).await;
+            return Err(Error::Config(format!(
+                "ffmpeg recording failed with exit code {}: {}",
+                result.exit_code().unwrap_or(-1),
+                result.stderr
+            )));
+        }
+
+        self.store_recording_file(stream_id, &temp_path).await
+    }
+
+    /// Record access units from a running native RTSP capture for `duration`
+    ///
+    /// Recording starts at the next keyframe and the elementary stream is remuxed into
+    /// MP4 without re-encoding or opening another connection to the camera.
+    async fn record_access_units(
+        &self,
+        stream_id: &str,
+        mut units: broadcast::Receiver<AccessUnit>,
+        duration: Duration,
+    ) -> Result<String> {
+        info!(stream_id = %stream_id, duration_secs = duration.as_secs(), "Recording from native RTSP session");
+
+        let mut elementary = Vec::new();
+        let mut codec = None;
+        let mut first_pts = Duration::ZERO;
+        let mut last_pts = Duration::ZERO;
+        let mut unit_count = 0u32;
+        let deadline = tokio::time::Instant::now() + duration.max(Duration::from_secs(1));
+
+        loop {
+            let unit = match tokio::time::timeout_at(deadline, units.recv()).await {
+                Ok(Ok(unit)) => unit,
+                Ok(Err(broadcast::error::RecvError::Lagged(skipped))) => {
+                    warn!(stream_id = %stream_id, skipped, "Recording fell behind the camera");
+                    continue;
+                }
+                Ok(Err(broadcast::error::RecvError::Closed)) | Err(_) => break,
+            };
+            if codec.is_none() {
+                if !unit.keyframe {
+                    continue;
+                }
+                codec = Some(unit.codec);
+                first_pts = unit.pts;
+            }
+            last_pts = unit.pts;
+            elementary.extend_from_slice(&unit.data);
+            unit_count += 1;
+        }
+
+        let codec = codec.ok_or_else(|| {
+            Error::External("No keyframe received from the camera while recording".to_string())
+        })?;
+
+        // Raw elementary streams carry no timing, so derive the rate from the RTP timestamps
+        let span = last_pts.saturating_sub(first_pts);
+        let frame_rate = if unit_count > 1 && !span.is_zero() {
+            (unit_count - 1) as f64 / span.as_secs_f64()
+        } else {
+            unit_count as f64 / duration.as_secs_f64().max(1.0)
+        }
+        .clamp(1.0, 120.0);
+
+        let stamp = chrono::Utc::now().timestamp_millis();
+        let elementary_path = std::env::temp_dir().join(format!(
+            "glimpser_recording_{}_{}.{}",
+            stream_id,
+            stamp,
+            codec.ffmpeg_format()
+        ));
+        let temp_path =
+            std::env::temp_dir().join(format!("glimpser_recording_{}_{}.mp4", stream_id, stamp));
+        tokio::fs::write(&elementary_path, &elementary).await?;
+
+        let args = vec![
+            "-hide_banner".to_string(),
+            "-loglevel".to_string(),
+            "error".to_string(),
+            "-y".to_string(),
+            "-framerate".to_string(),
+            format!("{:.3}", frame_rate),
+            "-f".to_string(),
+            codec.ffmpeg_format().to_string(),
+            "-i".to_string(),
+            elementary_path.to_string_lossy().to_string(),
+            "-c".to_string(),
+            "copy".to_string(),
+            "-movflags".to_string(),
+            "+faststart".to_string(),
+            temp_path.to_string_lossy().to_string(),
+        ];
+        let spec = gl_proc::CommandSpec::new("ffmpeg".into())
+            .args(args)
+            .timeout(Duration::from_secs(60));
+        let result = gl_proc::run(spec).await;
+        let _ = tokio::fs::remove_file(&elementary_path).await;
+        let result = result?;
+
+        if !result.success() {
+            let _ = tokio::fs::remove_file(&temp_path).await;
+            return Err(Error::Config(format!(
+                "ffmpeg remux failed with exit code {}: {}",
+                result.exit_code().unwrap_or(-1),
+                result.stderr
+            )));
+        }
+
+        self.store_recording_file(stream_id, &temp_path).await
+    }
+
+    /// Store a finished MP4 recording as an artifact and remove the temp file
+    async fn store_recording_file(&self, stream_id: &str, temp_path: &Path) -> Result<String> {
+        let data = tokio::fs::read(temp_path)
+            .await
+            .map_err(|e| Error::Config(format!("Failed to read recording: {}", e)));
+        let _ = tokio::fs::remove_file(temp_path).await;
+
+        let stored = self
+            .artifact_storage_service()
+            .store_recording(stream_id, Bytes::from(data?), "mp4")
+            .await?;
+
+        info!(stream_id = %stream_id, uri = %stored.uri, size = stored.size, "Stream recording stored");
+        Ok(stored.uri.to_string())
+    }
+
+    /// Build an artifact storage service rooted at the configured artifacts directory
+    pub(crate) fn artifact_storage_service(&self) -> ArtifactStorageService<StorageManager> {
+        let artifacts_dir = PathBuf::from(&self.storage_config.artifacts_dir);
+        // Local storage canonicalizes its root, so it has to exist before the first write
+        if let Err(e) = std::fs::create_dir_all(&artifacts_dir) {
+            warn!(error = %e, "Failed to create artifacts directory");
+        }
+        let gl_storage_config = gl_storage::StorageConfig {
+            base_dir: Some(artifacts_dir),
+            ..Default::default()
+        };
+        let storage_manager =
+            StorageManager::new(gl_storage_config).expect("Failed to create storage manager");
+        let artifact_config = ArtifactStorageConfig {
+            base_uri: "file:///".to_string(),
+            snapshot_extension: "jpg".to_string(),
+            include_timestamp: true,
+        };
+        ArtifactStorageService::new(storage_manager, artifact_config)
+    }
+
+    /// Internal method to run a persistent capture task with broadcast capabilities
+    #[allow(clippy::too_many_arguments)]
+    async fn run_persistent_capture_task(
+        db_pool: sqlx::SqlitePool,
+        storage_service: ArtifactStorageService<StorageManager>,
+        stream: Stream,
+        stream_id: String,
+        frame_sender: broadcast::Sender<Bytes>,
+        latest_snapshot: Arc<RwLock<Option<Bytes>>>,
+        analysis_service: Option<Arc<tokio::sync::Mutex<AnalysisService>>>,
+        capture_handle_sender: Option<oneshot::Sender<Arc<CaptureHandle>>>,
+        job_scheduler: Option<Arc<gl_scheduler::JobScheduler>>,
+        public_base_url: Option<String>,
+        health: SharedHealth,
+        heatmap: Option<(SharedHeatmap, Duration)>,
+        privacy: Option<Arc<StreamPrivacy>>,
+    ) -> Result<()> {
+        info!(stream_id = %stream_id, "Running persistent capture task with broadcast");
+
+        let analysis_context = Self::analysis_context(&stream, public_base_url.as_deref());
+
+        // Parse stream config
+        let config: Value = serde_json::from_str(&stream.config)
+            .map_err(|e| Error::Config(format!("Invalid stream config JSON: {}", e)))?;
+
+        let kind = config
+            .get("kind")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::Config("Stream config missing 'kind' field".to_string()))?;
+
+        // Update status to active with timestamp
+        let stream_repo = StreamRepository::new(&db_pool);
+        stream_repo
+            .update_execution_status(&stream_id, "active", Some(&chrono::Utc::now().to_rfc3339()))
+            .await?;
+
+        // Watch folders emit frames as files arrive rather than on a timer
+        if kind == "watch" {
+            return Self::run_watch_folder_task(
+                db_pool,
+                storage_service,
+                stream,
+                frame_sender,
+                latest_snapshot,
+                analysis_service,
+                capture_handle_sender,
+                analysis_context,
+                privacy,
+                &config,
+            )
+            .await;
+        }
+
+        // Create and start the capture source based on stream type
+        let capture_handle = match kind {
+            "file" => Self::create_file_capture(&config).await?,
+            "rtsp" => Arc::new(Self::start_rtsp_capture(&config).await?),
+            "ffmpeg" => Self::create_ffmpeg_capture(&config).await?,
+            "website" => Self::create_website_capture(&config).await?,
+            "yt" | "youtube" => Self::create_yt_capture(&config).await?,
+            "http" => Self::create_http_capture(&config).await?,
+            "ftp" | "email" => {
+                return Err(Error::Config(format!(
+                    "{} streams receive uploads from the camera and have no capture to run",
+                    kind
+                )))
+            }
+            _ => return Err(Error::Config(format!("Unsupported stream kind: {}", kind))),
+        };
+
+        // Send the capture handle back to the main thread for efficient snapshotting
+        if let Some(sender) = capture_handle_sender {
+            let _ = sender.send(capture_handle.clone());
+        }
+
+        // Get snapshot interval from config (default: 5 seconds)
+        let snapshot_interval = config
+            .get("snapshot_interval")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(5);
+
+        // Setup snapshot timer
+        let mut snapshot_timer = interval(Duration::from_secs(snapshot_interval));
+        let mut health_timer = interval(HEALTH_CHECK_INTERVAL);
+        let (heatmap, heatmap_flush) = match heatmap {
+            Some((track, flush)) => (Some(track), flush),
+            None => (
+                None,
+                Duration::from_secs(HeatmapConfig::default().flush_interval_secs),
+            ),
+        };
+        let mut heatmap_timer = interval(heatmap_flush);
+
+        // Get duration from config (default: 1 hour, 0 = infinite)
+        let duration = config
+            .get("duration")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(3600); // Default 1 hour
+
+        // Setup end time for finite duration captures
+        let end_time = if duration > 0 {
+            Some(tokio::time::Instant::now() + Duration::from_secs(duration))
+        } else {
+            None
+        };
+
+        // Persistent capture loop with graceful shutdown
+        loop {
+            tokio::select! {
+                _ = snapshot_timer.tick() => {
+                    // Take snapshot
+                    match capture_handle.snapshot().await {
+                        Ok(snapshot_data) => {
+                            // Nothing below may see the frame unmasked
+                            let snapshot_data = match Self::protect_frame(
+                                privacy.as_ref(),
+                                &storage_service,
+                                &db_pool,
+                                &stream_id,
+                                snapshot_data,
+                            ).await {
+                                Ok(masked) => masked,
+                                Err(e) => {
+                                    warn!(
+                                        stream_id = %stream_id,
+                                        error = %e,
+                                        "Dropping frame that could not be masked"
+                                    );
+                                    continue;
+                                }
+                            };
+
+                            debug!(
+                                stream_id = %stream_id,
+                                frame_size = snapshot_data.len(),
+                                subscribers = frame_sender.receiver_count(),
+                                "Captured frame for persistent task"
+                            );
+
+                            // Store latest snapshot in memory for fast API access
+                            {
+                                let mut snapshot_guard = latest_snapshot.write().await;
+                                *snapshot_guard = Some(snapshot_data.clone());
+                            }
+
+                            // Broadcast to MJPEG streams (ignore errors if no subscribers)
+                            let _ = frame_sender.send(snapshot_data.clone());
+
+                            // Store to database and filesystem using smart snapshot job (if available)
+                            if let Some(ref scheduler) = job_scheduler {
+                                match Self::execute_smart_snapshot_job(
+                                    scheduler.clone(),
+                                    &stream_id,
+                                ).await {
+                                    Ok(_) => {
+                                        debug!(
+                                            stream_id = %stream_id,
+                                            "Smart snapshot job executed successfully"
+                                        );
+                                    }
+                                    Err(e) => {
+                                        warn!(
+                                            stream_id = %stream_id,
+                                            error = %e,
                                             "Smart snapshot job failed, falling back to direct storage"
                                         );
                                         // Fallback to direct storage on job failure
@@ -728,44 +1802,31 @@ impl CaptureManager {
                                 }
                             }
 
+                            Self::spawn_health_observation(
+                                health.clone(),
+                                analysis_service.clone(),
+                                analysis_context.clone(),
+                                snapshot_data.clone(),
+                            );
+
+                            if let Some(heatmap) = &heatmap {
+                                Self::spawn_heatmap_observation(
+                                    heatmap.clone(),
+                                    db_pool.clone(),
+                                    stream_id.clone(),
+                                    snapshot_data.clone(),
+                                );
+                            }
+
                             // Process through analysis service if available
                             if let Some(analysis_service) = &analysis_service {
-                                let analysis_clone = analysis_service.clone();
-                                let stream_id_clone = stream_id.clone();
-                                let snapshot_clone = snapshot_data.clone();
-
-                                // Spawn analysis task to avoid blocking capture loop
-                                tokio::spawn(async move {
-                                    let mut service_guard = analysis_clone.lock().await;
-
-                                    let processor_input = ProcessorInput {
-                                        template_id: stream_id_clone.clone(),
-                                        frame_data: Some(snapshot_clone),
-                                        frame_format: Some("jpeg".to_string()), // Most captures are JPEG
-                                        text_content: None,
-                                        context: ProcessorContext::new(stream_id_clone.clone()),
-                                        timestamp: chrono::Utc::now(),
-                                    };
-
-                                    match service_guard.analyze(processor_input).await {
-                                        Ok(events) => {
-                                            if !events.is_empty() {
-                                                debug!(
-                                                    stream_id = %stream_id_clone,
-                                                    event_count = events.len(),
-                                                    "Analysis completed with events"
-                                                );
-                                            }
-                                        }
-                                        Err(e) => {
-                                            warn!(
-                                                stream_id = %stream_id_clone,
-                                                error = %e,
-                                                "Analysis failed for snapshot"
-                                            );
-                                        }
-                                    }
-                                });
+                                Self::spawn_analysis(
+                                    analysis_service.clone(),
+                                    &stream_id,
+                                    snapshot_data.clone(),
+                                    None,
+                                    analysis_context.clone(),
+                                );
                             }
                         }
                         Err(e) => {
@@ -774,7 +1835,27 @@ impl CaptureManager {
                                 error = %e,
                                 "Failed to capture frame in persistent task"
                             );
+                            let events = health.lock().unwrap().observe_failure(chrono::Utc::now());
+                            Self::publish_health_events(&health, &analysis_service, &analysis_context, events);
+                        }
+                    }
+                }
+                _ = health_timer.tick() => {
+                    let now = chrono::Utc::now();
+                    let events = {
+                        let mut monitor = health.lock().unwrap();
+                        let mut events = monitor.check(now);
+                        if let Some(reconnects) = capture_handle.reconnect_count() {
+                            events.extend(monitor.observe_source_reconnects(reconnects, now));
                         }
+                        events
+                    };
+                    Self::publish_health_events(&health, &analysis_service, &analysis_context, events);
+                }
+                _ = heatmap_timer.tick() => {
+                    if let Some(heatmap) = &heatmap {
+                        let pending = heatmap.lock().unwrap().take();
+                        Self::flush_heatmap(&db_pool, &stream_id, pending).await;
                     }
                 }
                 _ = tokio::signal::ctrl_c() => {
@@ -802,10 +1883,385 @@ impl CaptureManager {
             }
         }
 
+        if let Some(heatmap) = &heatmap {
+            let pending = heatmap.lock().unwrap().take();
+            Self::flush_heatmap(&db_pool, &stream_id, pending).await;
+        }
+
         drop(capture_handle);
         Ok(())
     }
 
+    /// Store and analyze every frame a watch-folder source produces, in arrival order
+    #[allow(clippy::too_many_arguments)]
+    async fn run_watch_folder_task(
+        db_pool: sqlx::SqlitePool,
+        storage_service: ArtifactStorageService<StorageManager>,
+        stream: Stream,
+        frame_sender: broadcast::Sender<Bytes>,
+        latest_snapshot: Arc<RwLock<Option<Bytes>>>,
+        analysis_service: Option<Arc<tokio::sync::Mutex<AnalysisService>>>,
+        capture_handle_sender: Option<oneshot::Sender<Arc<CaptureHandle>>>,
+        analysis_context: ProcessorContext,
+        privacy: Option<Arc<StreamPrivacy>>,
+        config: &Value,
+    ) -> Result<()> {
+        let source = WatchFolderSource::new(Self::watch_folder_config(config)?)?;
+        let capture_handle = Arc::new(source.start().await?);
+        if let Some(sender) = capture_handle_sender {
+            let _ = sender.send(capture_handle.clone());
+        }
+
+        // Unlike timed captures, a watch folder runs until stopped unless a duration is set
+        let duration = config.get("duration").and_then(|v| v.as_u64()).unwrap_or(0);
+        let end_time =
+            (duration > 0).then(|| tokio::time::Instant::now() + Duration::from_secs(duration));
+
+        loop {
+            tokio::select! {
+                frame = source.next_frame() => {
+                    let Some(mut frame) = frame else {
+                        warn!(stream_id = %stream.id, "Watch-folder source stopped");
+                        break;
+                    };
+                    frame.data = match Self::protect_frame(
+                        privacy.as_ref(),
+                        &storage_service,
+                        &db_pool,
+                        &stream.id,
+                        frame.data,
+                    ).await {
+                        Ok(masked) => masked,
+                        Err(e) => {
+                            warn!(
+                                stream_id = %stream.id,
+                                path = %frame.source_path.display(),
+                                error = %e,
+                                "Dropping watch-folder frame that could not be masked"
+                            );
+                            continue;
+                        }
+                    };
+
+                    *latest_snapshot.write().await = Some(frame.data.clone());
+                    let _ = frame_sender.send(frame.data.clone());
+
+                    if let Err(e) = Self::store_snapshot_async(
+                        &storage_service,
+                        &db_pool,
+                        &stream.id,
+                        &stream.user_id,
+                        &frame.data,
+                    ).await {
+                        warn!(
+                            stream_id = %stream.id,
+                            path = %frame.source_path.display(),
+                            error = %e,
+                            "Failed to store watch-folder frame"
+                        );
+                    }
+
+                    if let Some(analysis_service) = &analysis_service {
+                        let mut context = analysis_context.clone().with_metadata(
+                            "source_file".to_string(),
+                            frame.source_path.display().to_string(),
+                        );
+                        if let Some(offset) = frame.offset {
+                            context = context.with_metadata(
+                                "source_offset_seconds".to_string(),
+                                offset.as_secs().to_string(),
+                            );
+                        }
+                        Self::spawn_analysis(
+                            analysis_service.clone(),
+                            &stream.id,
+                            frame.data,
+                            None,
+                            context,
+                        );
+                    }
+                }
+                _ = tokio::signal::ctrl_c() => {
+                    info!(stream_id = %stream.id, "Watch-folder capture interrupted by signal");
+                    break;
+                }
+                _ = async {
+                    match end_time {
+                        Some(end_time) => tokio::time::sleep_until(end_time).await,
+                        None => std::future::pending::<()>().await,
+                    }
+                } => {
+                    info!(stream_id = %stream.id, duration = duration, "Watch-folder capture completed after duration limit");
+                    break;
+                }
+            }
+        }
+
+        drop(capture_handle);
+        Ok(())
+    }
+
+    /// Stream details made available to processors and notification templates
+    fn analysis_context(stream: &Stream, public_base_url: Option<&str>) -> ProcessorContext {
+        let mut context = ProcessorContext::new(stream.id.clone())
+            .with_metadata("stream_name".to_string(), stream.name.clone());
+        let config = serde_json::from_str::<Value>(&stream.config).unwrap_or_default();
+        // Lets processors such as tamper detection skip sources that aren't cameras
+        if let Some(kind) = config.get("kind").and_then(Value::as_str) {
+            context = context.with_metadata("stream_kind".to_string(), kind.to_string());
+        }
+        // Per-stream schema for structured AI frame descriptions and plate reading settings
+        let template_config: HashMap<String, Value> = ["output_schema", "plates"]
+            .into_iter()
+            .filter_map(|key| {
+                config
+                    .get(key)
+                    .filter(|v| !v.is_null())
+                    .map(|v| (key.to_string(), v.clone()))
+            })
+            .collect();
+        if !template_config.is_empty() {
+            context = context.with_config(template_config);
+        }
+        if let Some(base_url) = public_base_url {
+            context = context.with_metadata(
+                "snapshot_url".to_string(),
+                format!("{}/api/stream/{}/snapshot", base_url, stream.id),
+            );
+        }
+        context
+    }
+
+    /// Health monitor for a stream, configured from the `health` object of its config
+    fn health_monitor(stream: &Stream) -> StreamHealthMonitor {
+        let config: Value = serde_json::from_str(&stream.config).unwrap_or_default();
+        let snapshot_interval = config
+            .get("snapshot_interval")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(5);
+        let health_config = config
+            .get("health")
+            .and_then(|v| match serde_json::from_value::<HealthConfig>(v.clone()) {
+                Ok(health) => Some(health),
+                Err(e) => {
+                    warn!(stream_id = %stream.id, error = %e, "Invalid health config, using defaults");
+                    None
+                }
+            })
+            .unwrap_or_default();
+
+        StreamHealthMonitor::new(
+            stream.id.clone(),
+            Duration::from_secs(snapshot_interval),
+            health_config,
+            chrono::Utc::now(),
+        )
+    }
+
+    /// Measure a captured frame off the capture loop and feed it to the health monitor
+    fn spawn_health_observation(
+        health: SharedHealth,
+        analysis_service: Option<Arc<tokio::sync::Mutex<AnalysisService>>>,
+        context: ProcessorContext,
+        frame: Bytes,
+    ) {
+        tokio::spawn(async move {
+            let at = chrono::Utc::now();
+            let quality =
+                tokio::task::spawn_blocking(move || FrameQuality::from_image_bytes(&frame)).await;
+            let events = {
+                let mut monitor = health.lock().unwrap();
+                match quality {
+                    Ok(Ok(quality)) => monitor.observe_frame(&quality, at),
+                    Ok(Err(e)) => {
+                        debug!(stream_id = %context.source_id, error = %e, "Captured frame could not be decoded");
+                        // A picture nobody can look at is as good as no picture
+                        monitor.observe_failure(at)
+                    }
+                    Err(e) => {
+                        warn!(error = %e, "Frame quality task failed");
+                        return;
+                    }
+                }
+            };
+            Self::publish_health_events(&health, &analysis_service, &context, events);
+        });
+    }
+
+    /// Motion heatmap for a stream and how often to flush it, configured from the
+    /// `heatmap` object of its config; `None` when disabled
+    fn heatmap_track(stream: &Stream) -> Option<(SharedHeatmap, Duration)> {
+        let config: Value = serde_json::from_str(&stream.config).unwrap_or_default();
+        let heatmap_config = config
+            .get("heatmap")
+            .and_then(|v| match serde_json::from_value::<HeatmapConfig>(v.clone()) {
+                Ok(heatmap) => Some(heatmap),
+                Err(e) => {
+                    warn!(stream_id = %stream.id, error = %e, "Invalid heatmap config, using defaults");
+                    None
+                }
+            })
+            .unwrap_or_default();
+        if !heatmap_config.enabled {
+            return None;
+        }
+
+        match MotionHeatmapRecorder::new(&heatmap_config) {
+            Ok(recorder) => {
+                let track = HeatmapTrack {
+                    recorder,
+                    hour_start: Self::heatmap_hour(chrono::Utc::now()),
+                };
+                Some((
+                    Arc::new(std::sync::Mutex::new(track)),
+                    Duration::from_secs(heatmap_config.flush_interval_secs.max(1)),
+                ))
+            }
+            Err(e) => {
+                warn!(stream_id = %stream.id, error = %e, "Motion heatmap disabled");
+                None
+            }
+        }
+    }
+
+    /// Start of the hour a heatmap sample belongs to
+    fn heatmap_hour(at: chrono::DateTime<chrono::Utc>) -> chrono::DateTime<chrono::Utc> {
+        at.duration_trunc(chrono::Duration::hours(1)).unwrap_or(at)
+    }
+
+    /// Feed a captured frame to the heatmap off the capture loop, writing out the previous
+    /// hour as soon as a frame from the next one arrives
+    fn spawn_heatmap_observation(
+        heatmap: SharedHeatmap,
+        db_pool: sqlx::SqlitePool,
+        stream_id: String,
+        frame: Bytes,
+    ) {
+        tokio::spawn(async move {
+            let finished = tokio::task::spawn_blocking(move || {
+                let mut track = heatmap.lock().unwrap();
+                let hour = Self::heatmap_hour(chrono::Utc::now());
+                let finished = if hour != track.hour_start {
+                    let finished = track.take();
+                    track.hour_start = hour;
+                    finished
+                } else {
+                    None
+                };
+                if let Err(e) = track.recorder.observe(&frame) {
+                    debug!(error = %e, "Frame skipped by motion heatmap");
+                }
+                finished
+            })
+            .await;
+
+            match finished {
+                Ok(finished) => Self::flush_heatmap(&db_pool, &stream_id, finished).await,
+                Err(e) => warn!(error = %e, "Motion heatmap task failed"),
+            }
+        });
+    }
+
+    /// Add accumulated motion to the stored hour
+    async fn flush_heatmap(
+        db_pool: &sqlx::SqlitePool,
+        stream_id: &str,
+        pending: Option<(chrono::DateTime<chrono::Utc>, HeatGrid)>,
+    ) {
+        let Some((hour_start, grid)) = pending else {
+            return;
+        };
+        let repo = MotionHeatmapRepository::new(gl_db::Db::from_pool(db_pool.clone()));
+        if let Err(e) = repo
+            .merge(
+                stream_id,
+                &hour_start.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+                grid.cols(),
+                grid.rows(),
+                grid.frames(),
+                grid.cells(),
+            )
+            .await
+        {
+            warn!(stream_id = %stream_id, error = %e, "Failed to store motion heatmap");
+        }
+    }
+
+    /// Log health transitions and hand them to the analysis service for storage and alerts
+    fn publish_health_events(
+        health: &SharedHealth,
+        analysis_service: &Option<Arc<tokio::sync::Mutex<AnalysisService>>>,
+        context: &ProcessorContext,
+        events: Vec<AnalysisEvent>,
+    ) {
+        if events.is_empty() {
+            return;
+        }
+        for event in &events {
+            if event.event_type == "stream_recovered" {
+                info!(stream_id = %context.source_id, description = %event.description, "Stream health recovered");
+            } else {
+                warn!(stream_id = %context.source_id, event_type = %event.event_type, description = %event.description, "Stream health degraded");
+            }
+        }
+
+        if !health.lock().unwrap().config().enabled {
+            return;
+        }
+        let Some(analysis_service) = analysis_service.clone() else {
+            return;
+        };
+        let context = context.clone();
+        tokio::spawn(async move {
+            let service = analysis_service.lock().await;
+            if let Err(e) = service.publish_events(&context, events).await {
+                warn!(stream_id = %context.source_id, error = %e, "Failed to publish stream health events");
+            }
+        });
+    }
+
+    /// Analyze a frame on a separate task so capture and ingest never wait on processors
+    fn spawn_analysis(
+        analysis_service: Arc<tokio::sync::Mutex<AnalysisService>>,
+        stream_id: &str,
+        frame: Bytes,
+        text_content: Option<String>,
+        context: ProcessorContext,
+    ) {
+        let stream_id = stream_id.to_string();
+        tokio::spawn(async move {
+            let mut service_guard = analysis_service.lock().await;
+
+            let processor_input = ProcessorInput {
+                template_id: stream_id.clone(),
+                frame_data: Some(frame),
+                frame_format: Some("jpeg".to_string()), // Most captures are JPEG
+                text_content,
+                context,
+                timestamp: chrono::Utc::now(),
+            };
+
+            match service_guard.analyze(processor_input).await {
+                Ok(events) => {
+                    if !events.is_empty() {
+                        debug!(
+                            stream_id = %stream_id,
+                            event_count = events.len(),
+                            "Analysis completed with events"
+                        );
+                    }
+                }
+                Err(e) => {
+                    warn!(
+                        stream_id = %stream_id,
+                        error = %e,
+                        "Analysis failed for snapshot"
+                    );
+                }
+            }
+        });
+    }
+
     /// Internal method to run a capture task (legacy method for compatibility)
     #[allow(dead_code)]
     async fn run_capture_task(
@@ -1028,38 +2484,7 @@ impl CaptureManager {
 
     #[allow(dead_code)]
     async fn run_rtsp_capture(&self, config: &Value, stream_id: &str, user_id: &str) -> Result<()> {
-        let rtsp_url = config
-            .get("url")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| Error::Config("RTSP stream config missing 'url' field".to_string()))?;
-
-        // Use FFmpeg to handle RTSP with optimized settings
-        let mut ffmpeg_config = FfmpegConfig {
-            input_url: rtsp_url.to_string(),
-            rtsp_transport: RtspTransport::Tcp, // Default to TCP for reliability
-            ..Default::default()
-        };
-
-        // Parse optional RTSP-specific configuration
-        if let Some(transport) = config.get("transport").and_then(|v| v.as_str()) {
-            ffmpeg_config.rtsp_transport = match transport.to_lowercase().as_str() {
-                "tcp" => RtspTransport::Tcp,
-                "udp" => RtspTransport::Udp,
-                _ => RtspTransport::Auto,
-            };
-        }
-
-        // Parse optional timeout (RTSP streams often need longer timeouts)
-        if let Some(timeout_val) = config.get("timeout").and_then(|v| v.as_u64()) {
-            let safe_timeout = std::cmp::min(timeout_val, u32::MAX as u64) as u32;
-            ffmpeg_config.timeout = Some(safe_timeout);
-        } else {
-            // Default timeout for RTSP
-            ffmpeg_config.timeout = Some(10);
-        }
-
-        let ffmpeg_source = FfmpegSource::new(ffmpeg_config);
-        let handle = ffmpeg_source.start().await?;
+        let handle = Self::start_rtsp_capture(config).await?;
 
         // Setup snapshot capture loop
         let duration = config.get("duration").and_then(|v| v.as_u64()).unwrap_or(0); // Default to continuous for RTSP
@@ -1385,52 +2810,13 @@ impl CaptureManager {
                 "cuda" => HardwareAccel::Cuda,
                 "qsv" => HardwareAccel::Qsv,
                 "videotoolbox" => HardwareAccel::VideoToolbox,
-                _ => HardwareAccel::None,
-            };
-        }
-
-        if let Some(timeout_val) = config.get("timeout").and_then(|v| v.as_u64()) {
-            let safe_timeout = std::cmp::min(timeout_val, u32::MAX as u64) as u32;
-            ffmpeg_config.timeout = Some(safe_timeout);
-        }
-
-        let ffmpeg_source = FfmpegSource::new(ffmpeg_config);
-        let handle = ffmpeg_source.start().await?;
-        let snapshot = handle.snapshot().await?;
-        drop(handle);
-        Ok(snapshot)
-    }
-
-    /// Take a snapshot from an RTSP source using background processing
-    async fn take_rtsp_snapshot(&self, config: &Value) -> Result<bytes::Bytes> {
-        let rtsp_url = config
-            .get("url")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| Error::Config("RTSP stream config missing 'url' field".to_string()))?;
-
-        // Use FFmpeg to handle RTSP with optimized settings
-        let mut ffmpeg_config = FfmpegConfig {
-            input_url: rtsp_url.to_string(),
-            rtsp_transport: RtspTransport::Tcp, // Default to TCP for reliability
-            ..Default::default()
-        };
-
-        // Parse optional RTSP-specific configuration
-        if let Some(transport) = config.get("transport").and_then(|v| v.as_str()) {
-            ffmpeg_config.rtsp_transport = match transport.to_lowercase().as_str() {
-                "tcp" => RtspTransport::Tcp,
-                "udp" => RtspTransport::Udp,
-                _ => RtspTransport::Auto,
+                _ => HardwareAccel::None,
             };
         }
 
-        // Parse optional timeout (RTSP streams often need longer timeouts)
         if let Some(timeout_val) = config.get("timeout").and_then(|v| v.as_u64()) {
             let safe_timeout = std::cmp::min(timeout_val, u32::MAX as u64) as u32;
             ffmpeg_config.timeout = Some(safe_timeout);
-        } else {
-            // Default timeout for RTSP
-            ffmpeg_config.timeout = Some(10);
         }
 
         let ffmpeg_source = FfmpegSource::new(ffmpeg_config);
@@ -1440,6 +2826,18 @@ impl CaptureManager {
         Ok(snapshot)
     }
 
+    /// Take a snapshot from an RTSP source, reusing a shared upstream when one is open
+    async fn take_rtsp_snapshot(&self, stream_id: &str, config: &Value) -> Result<bytes::Bytes> {
+        if let Some(upstream) = self.shared_rtsp_upstream(stream_id).await {
+            return upstream.snapshot().await;
+        }
+
+        let handle = Self::start_rtsp_capture(config).await?;
+        let snapshot = handle.snapshot().await?;
+        drop(handle);
+        Ok(snapshot)
+    }
+
     /// Take a snapshot from a website source
     #[cfg(feature = "website")]
     async fn take_website_snapshot(&self, config: &Value) -> Result<bytes::Bytes> {
@@ -1547,6 +2945,243 @@ impl CaptureManager {
         Ok(snapshot)
     }
 
+    /// Take a snapshot from an HTTP(S) JPEG snapshot URL
+    async fn take_http_snapshot(&self, config: &Value) -> Result<bytes::Bytes> {
+        let source = HttpSnapshotSource::new(Self::http_snapshot_config(config)?)?;
+        let handle = source.start().await?;
+        let snapshot = handle.snapshot().await?;
+        drop(handle);
+        Ok(snapshot)
+    }
+
+    /// Build an HTTP snapshot source config from a stream's JSON config
+    pub(crate) fn http_snapshot_config(config: &Value) -> Result<HttpSnapshotConfig> {
+        let url = config
+            .get("url")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::Config("http stream config missing 'url' field".to_string()))?;
+
+        let mut http_config = HttpSnapshotConfig {
+            url: url.to_string(),
+            username: config
+                .get("username")
+                .and_then(|v| v.as_str())
+                .map(String::from),
+            password: config
+                .get("password")
+                .and_then(|v| v.as_str())
+                .map(String::from),
+            ..Default::default()
+        };
+
+        if let Some(auth) = config.get("auth").and_then(|v| v.as_str()) {
+            http_config.auth_scheme = match auth.to_lowercase().as_str() {
+                "basic" => HttpAuthScheme::Basic,
+                "digest" => HttpAuthScheme::Digest,
+                "auto" => HttpAuthScheme::Auto,
+                other => {
+                    return Err(Error::Config(format!(
+                        "Unsupported http auth scheme: {}",
+                        other
+                    )))
+                }
+            };
+        }
+
+        if let Some(headers) = config.get("headers").and_then(|v| v.as_object()) {
+            for (key, value) in headers {
+                if let Some(value_str) = value.as_str() {
+                    http_config
+                        .headers
+                        .insert(key.clone(), value_str.to_string());
+                }
+            }
+        }
+
+        if let Some(insecure) = config.get("insecure_tls").and_then(|v| v.as_bool()) {
+            http_config.accept_invalid_certs = insecure;
+        }
+
+        if let Some(timeout_val) = config.get("timeout").and_then(|v| v.as_u64()) {
+            http_config.timeout = Duration::from_secs(timeout_val.max(1));
+        }
+
+        Ok(http_config)
+    }
+
+    /// Start an RTSP capture using the stream's `backend` setting
+    ///
+    /// `"native"` demuxes in-process and only decodes keyframes, `"ffmpeg"` keeps a
+    /// long-running ffmpeg, and `"auto"` (the default) tries native first and falls back
+    /// to ffmpeg for cameras the native client can't handle.
+    pub(crate) async fn start_rtsp_capture(config: &Value) -> Result<CaptureHandle> {
+        let backend = config
+            .get("backend")
+            .and_then(|v| v.as_str())
+            .unwrap_or("auto")
+            .to_lowercase();
+
+        match backend.as_str() {
+            "native" => Self::native_rtsp_source(config)?.start().await,
+            "ffmpeg" => {
+                FfmpegSource::new(Self::rtsp_ffmpeg_config(config)?)
+                    .start()
+                    .await
+            }
+            "auto" => match Self::native_rtsp_source(config)?.start().await {
+                Ok(handle) => Ok(handle),
+                Err(e) => {
+                    warn!(error = %e, "Native RTSP client failed, falling back to ffmpeg");
+                    FfmpegSource::new(Self::rtsp_ffmpeg_config(config)?)
+                        .start()
+                        .await
+                }
+            },
+            other => Err(Error::Config(format!(
+                "Unsupported RTSP backend: {}",
+                other
+            ))),
+        }
+    }
+
+    /// Parse the optional RTSP transport, defaulting to TCP for reliability
+    fn rtsp_transport(config: &Value) -> RtspTransport {
+        match config.get("transport").and_then(|v| v.as_str()) {
+            None => RtspTransport::Tcp,
+            Some(transport) => match transport.to_lowercase().as_str() {
+                "tcp" => RtspTransport::Tcp,
+                "udp" => RtspTransport::Udp,
+                _ => RtspTransport::Auto,
+            },
+        }
+    }
+
+    /// RTSP streams often need longer timeouts than the ffmpeg default
+    fn rtsp_timeout_secs(config: &Value) -> u32 {
+        config
+            .get("timeout")
+            .and_then(|v| v.as_u64())
+            .map(|t| std::cmp::min(t, u32::MAX as u64) as u32)
+            .unwrap_or(10)
+    }
+
+    /// Build an ffmpeg config from an "rtsp" stream config
+    fn rtsp_ffmpeg_config(config: &Value) -> Result<FfmpegConfig> {
+        let rtsp_url = config
+            .get("url")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::Config("RTSP stream config missing 'url' field".to_string()))?;
+
+        Ok(FfmpegConfig {
+            input_url: rtsp_url.to_string(),
+            rtsp_transport: Self::rtsp_transport(config),
+            timeout: Some(Self::rtsp_timeout_secs(config)),
+            ..Default::default()
+        })
+    }
+
+    /// Build a native RTSP client with the stream's keyframe decoder
+    ///
+    /// Keyframes are decoded in-process unless `keyframe_decoder` is `"ffmpeg"`, which
+    /// spawns ffmpeg per snapshot and is only needed for codecs such as H.265.
+    fn native_rtsp_source(config: &Value) -> Result<NativeRtspSource> {
+        let native_config = Self::native_rtsp_config(config)?;
+        let decoder: Arc<dyn KeyframeDecoder> = match config
+            .get("keyframe_decoder")
+            .and_then(|v| v.as_str())
+            .map(str::to_lowercase)
+            .as_deref()
+        {
+            None | Some("native") => Arc::new(InProcessKeyframeDecoder),
+            Some("ffmpeg") => Arc::new(FfmpegKeyframeDecoder),
+            Some(other) => {
+                return Err(Error::Config(format!(
+                    "Unsupported keyframe decoder: {}",
+                    other
+                )))
+            }
+        };
+        Ok(NativeRtspSource::with_decoder(native_config, decoder))
+    }
+
+    /// Build a native RTSP client config from an "rtsp" stream config
+    fn native_rtsp_config(config: &Value) -> Result<NativeRtspConfig> {
+        let rtsp_url = config
+            .get("url")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::Config("RTSP stream config missing 'url' field".to_string()))?;
+
+        Ok(NativeRtspConfig {
+            url: rtsp_url.to_string(),
+            username: config
+                .get("username")
+                .and_then(|v| v.as_str())
+                .map(String::from),
+            password: config
+                .get("password")
+                .and_then(|v| v.as_str())
+                .map(String::from),
+            transport: Self::rtsp_transport(config),
+            connect_timeout: Duration::from_secs(Self::rtsp_timeout_secs(config).max(1) as u64),
+            ..Default::default()
+        })
+    }
+
+    /// Build a watch-folder config from a "watch" stream config
+    fn watch_folder_config(config: &Value) -> Result<WatchFolderConfig> {
+        let directory = config
+            .get("directory")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| {
+                Error::Config("watch stream config missing 'directory' field".to_string())
+            })?;
+
+        let mut watch_config = WatchFolderConfig {
+            directory: PathBuf::from(directory),
+            archive_dir: config
+                .get("archive_dir")
+                .and_then(|v| v.as_str())
+                .map(PathBuf::from),
+            ..Default::default()
+        };
+
+        if let Some(pattern) = config.get("pattern").and_then(|v| v.as_str()) {
+            watch_config.pattern = pattern.to_string();
+        }
+        if let Some(recursive) = config.get("recursive").and_then(|v| v.as_bool()) {
+            watch_config.recursive = recursive;
+        }
+        if let Some(existing) = config.get("include_existing").and_then(|v| v.as_bool()) {
+            watch_config.include_existing = existing;
+        }
+        if let Some(seconds) = config.get("sample_interval").and_then(|v| v.as_u64()) {
+            watch_config.video_sample_interval = Duration::from_secs(seconds.max(1));
+        }
+        if let Some(max_frames) = config.get("max_frames_per_video").and_then(|v| v.as_u64()) {
+            watch_config.max_frames_per_video = max_frames.max(1) as usize;
+        }
+
+        // Same rule as file streams: no path traversal
+        let paths = std::iter::once(&watch_config.directory).chain(&watch_config.archive_dir);
+        for path in paths {
+            if path
+                .components()
+                .any(|c| matches!(c, std::path::Component::ParentDir))
+            {
+                return Err(Error::Config("Path traversal not allowed".to_string()));
+            }
+        }
+
+        Ok(watch_config)
+    }
+
+    /// Create HTTP snapshot capture source
+    async fn create_http_capture(config: &Value) -> Result<Arc<CaptureHandle>> {
+        let source = HttpSnapshotSource::new(Self::http_snapshot_config(config)?)?;
+        let handle = source.start().await?;
+        Ok(Arc::new(handle))
+    }
+
     /// Create file capture source
     async fn create_file_capture(config: &Value) -> Result<Arc<CaptureHandle>> {
         let file_path = config
@@ -1606,43 +3241,6 @@ impl CaptureManager {
         Ok(Arc::new(handle))
     }
 
-    /// Create RTSP capture source
-    async fn create_rtsp_capture(config: &Value) -> Result<Arc<CaptureHandle>> {
-        let rtsp_url = config
-            .get("url")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| Error::Config("RTSP stream config missing 'url' field".to_string()))?;
-
-        // Use FFmpeg to handle RTSP with optimized settings
-        let mut ffmpeg_config = FfmpegConfig {
-            input_url: rtsp_url.to_string(),
-            rtsp_transport: RtspTransport::Tcp, // Default to TCP for reliability
-            ..Default::default()
-        };
-
-        // Parse optional RTSP-specific configuration
-        if let Some(transport) = config.get("transport").and_then(|v| v.as_str()) {
-            ffmpeg_config.rtsp_transport = match transport.to_lowercase().as_str() {
-                "tcp" => RtspTransport::Tcp,
-                "udp" => RtspTransport::Udp,
-                _ => RtspTransport::Auto,
-            };
-        }
-
-        // Parse optional timeout (RTSP streams often need longer timeouts)
-        if let Some(timeout_val) = config.get("timeout").and_then(|v| v.as_u64()) {
-            let safe_timeout = std::cmp::min(timeout_val, u32::MAX as u64) as u32;
-            ffmpeg_config.timeout = Some(safe_timeout);
-        } else {
-            // Default timeout for RTSP
-            ffmpeg_config.timeout = Some(10);
-        }
-
-        let ffmpeg_source = FfmpegSource::new(ffmpeg_config);
-        let handle = ffmpeg_source.start().await?;
-        Ok(Arc::new(handle))
-    }
-
     /// Create website capture source
     #[cfg(feature = "website")]
     async fn create_website_capture(config: &Value) -> Result<Arc<CaptureHandle>> {
@@ -1818,28 +3416,132 @@ impl CaptureManager {
         }
     }
 
-    /// Store snapshot using ArtifactStorageService and update database
-    #[allow(dead_code)]
+    /// Masking state for a stream, rebuilt whenever its config changes; `None` when
+    /// the stream has no privacy settings
+    fn stream_privacy(&self, stream: &Stream) -> Result<Option<Arc<StreamPrivacy>>> {
+        let mut cache = self.privacy.lock().unwrap();
+        if let Some((config, privacy)) = cache.get(&stream.id) {
+            if *config == stream.config {
+                return Ok(Some(privacy.clone()));
+            }
+        }
+
+        let Some(config) = StreamPrivacyConfig::from_stream(stream)? else {
+            cache.remove(&stream.id);
+            return Ok(None);
+        };
+        let privacy = Arc::new(StreamPrivacy::new(config)?);
+        cache.insert(stream.id.clone(), (stream.config.clone(), privacy.clone()));
+        Ok(Some(privacy))
+    }
+
+    /// Apply a stream's privacy settings to a frame captured outside its capture task
+    ///
+    /// Every frame served to users must pass through here or a capture task.
+    pub async fn protect_stream_frame(&self, stream: &Stream, frame: Bytes) -> Result<Bytes> {
+        let privacy = self.stream_privacy(stream)?;
+        Self::protect_frame(
+            privacy.as_ref(),
+            &self.artifact_storage_service(),
+            &self.db_pool,
+            &stream.id,
+            frame,
+        )
+        .await
+    }
+
+    /// Mask a frame, keeping its original when one is due
+    ///
+    /// Frames of streams without privacy settings pass through untouched. Failing to
+    /// keep an original only costs the original; failing to mask is an error.
+    async fn protect_frame(
+        privacy: Option<&Arc<StreamPrivacy>>,
+        storage_service: &ArtifactStorageService<StorageManager>,
+        db_pool: &sqlx::SqlitePool,
+        stream_id: &str,
+        frame: Bytes,
+    ) -> Result<Bytes> {
+        let Some(privacy) = privacy else {
+            return Ok(frame);
+        };
+        let masked = privacy.mask(frame.clone()).await?;
+
+        if let Some(originals) = privacy
+            .config()
+            .originals
+            .as_ref()
+            .filter(|_| privacy.take_original_slot())
+        {
+            if let Err(e) = Self::keep_original(
+                storage_service,
+                db_pool,
+                stream_id,
+                frame,
+                originals.retention_hours,
+            )
+            .await
+            {
+                warn!(stream_id = %stream_id, error = %e, "Failed to keep original frame");
+            }
+        }
+        Ok(masked)
+    }
+
+    /// Store an unmasked original that expires after `retention_hours`
+    async fn keep_original(
+        storage_service: &ArtifactStorageService<StorageManager>,
+        db_pool: &sqlx::SqlitePool,
+        stream_id: &str,
+        frame: Bytes,
+        retention_hours: u32,
+    ) -> Result<()> {
+        let captured_at = chrono::Utc::now();
+        let expires_at = captured_at + chrono::Duration::hours(i64::from(retention_hours));
+        let stored_artifact = storage_service
+            .store_snapshot(&format!("original_{}", stream_id), frame)
+            .await?;
+
+        let repo = OriginalFrameRepository::new(gl_db::Db::from_pool(db_pool.clone()));
+        let request = CreateOriginalFrame {
+            stream_id: stream_id.to_string(),
+            file_path: stored_artifact.uri.path().unwrap_or_default().to_string(),
+            storage_uri: stored_artifact.uri.to_string(),
+            file_size: stored_artifact.size as i64,
+            captured_at: captured_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            expires_at: expires_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        };
+        if let Err(e) = repo.create(request).await {
+            if let Err(cleanup_error) = storage_service.delete_artifact(&stored_artifact.uri).await
+            {
+                warn!(
+                    "Failed to clean up original frame after database error: {}",
+                    cleanup_error
+                );
+            }
+            return Err(e);
+        }
+
+        // Piggyback expiry on writes so busy streams don't wait for the purge job
+        delete_expired_originals(storage_service, &repo).await?;
+        Ok(())
+    }
+
+    /// Read a stored original back from artifact storage
+    pub async fn read_original(&self, original: &OriginalFrame) -> Result<Bytes> {
+        let uri = StorageUri::new(original.storage_uri.as_str())
+            .map_err(|e| Error::Storage(e.to_string()))?;
+        self.artifact_storage_service().get_artifact(&uri).await
+    }
+
+    /// Store snapshot using ArtifactStorageService and update database, returning the snapshot ID
     async fn store_snapshot(
         &self,
         stream_id: &str,
         user_id: &str,
         snapshot_data: &[u8],
-    ) -> Result<()> {
+    ) -> Result<String> {
         // Create storage service for this operation
-        let artifacts_dir = PathBuf::from(&self.storage_config.artifacts_dir);
-        let gl_storage_config = gl_storage::StorageConfig {
-            base_dir: Some(artifacts_dir),
-            ..Default::default()
-        };
-        let storage_manager =
-            StorageManager::new(gl_storage_config).expect("Failed to create storage manager");
-        let artifact_config = ArtifactStorageConfig {
-            base_uri: "file:///".to_string(),
-            snapshot_extension: "jpg".to_string(),
-            include_timestamp: true,
-        };
-        let storage_service = ArtifactStorageService::new(storage_manager, artifact_config);
+        let storage_service = self.artifact_storage_service();
 
         let snapshot_bytes = Bytes::from(snapshot_data.to_vec());
 
@@ -1874,7 +3576,7 @@ impl CaptureManager {
                     "Stored snapshot {} for stream {} at {}",
                     snapshot.id, stream_id, stored_artifact.uri
                 );
-                Ok(())
+                Ok(snapshot.id)
             }
             Err(e) => {
                 error!("Failed to store snapshot for stream {}: {}", stream_id, e);
@@ -1917,6 +3619,207 @@ impl Drop for CaptureManager {
     }
 }
 
+/// Rule action capture operations backed by a [`CaptureManager`]
+///
+/// Holds a weak reference because the manager owns the analysis service that
+/// holds this control.
+struct CaptureManagerControl {
+    manager: Weak<CaptureManager>,
+}
+
+impl CaptureManagerControl {
+    fn manager(&self) -> Result<Arc<CaptureManager>> {
+        self.manager
+            .upgrade()
+            .ok_or_else(|| Error::Config("Capture manager is no longer available".to_string()))
+    }
+}
+
+#[async_trait]
+impl CaptureControl for CaptureManagerControl {
+    async fn snapshot_burst(
+        &self,
+        stream_id: &str,
+        count: u32,
+        interval: Duration,
+    ) -> Result<Vec<String>> {
+        self.manager()?
+            .snapshot_burst(stream_id, count, interval)
+            .await
+    }
+
+    async fn record(&self, stream_id: &str, duration: Duration) -> Result<String> {
+        self.manager()?.record_stream(stream_id, duration).await
+    }
+}
+
+/// Artifact storage for images attached to analysis events
+struct EventArtifacts {
+    storage_service: ArtifactStorageService<StorageManager>,
+}
+
+#[async_trait]
+impl ArtifactStore for EventArtifacts {
+    async fn store_image(&self, event: &AnalysisEvent, name: &str, jpeg: Bytes) -> Result<String> {
+        let stored = self
+            .storage_service
+            .store_snapshot(&format!("{}_{}", name, event.source_id), jpeg)
+            .await?;
+        Ok(stored.uri.to_string())
+    }
+}
+
+/// Push ingest sink that matches logins against stream configs and stores through a
+/// [`CaptureManager`]
+struct CaptureManagerIngest {
+    manager: Weak<CaptureManager>,
+}
+
+impl CaptureManagerIngest {
+    fn manager(&self) -> Result<Arc<CaptureManager>> {
+        self.manager
+            .upgrade()
+            .ok_or_else(|| Error::Config("Capture manager is no longer available".to_string()))
+    }
+}
+
+#[async_trait]
+impl IngestSink for CaptureManagerIngest {
+    async fn authenticate(
+        &self,
+        protocol: &str,
+        username: &str,
+        password: &str,
+    ) -> Result<Option<IngestTarget>> {
+        let manager = self.manager()?;
+        let streams = StreamRepository::new(&manager.db_pool)
+            .list_by_kind(protocol)
+            .await?;
+
+        // Compare digests so the check doesn't leak how much of the password matched
+        let offered = Sha256::digest(password.as_bytes());
+        for stream in streams {
+            let Ok(config) = serde_json::from_str::<Value>(&stream.config) else {
+                continue;
+            };
+            if config.get("username").and_then(|v| v.as_str()) != Some(username) {
+                continue;
+            }
+            let expected = config
+                .get("password")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default();
+            if !expected.is_empty() && Sha256::digest(expected.as_bytes()) == offered {
+                return Ok(Some(IngestTarget {
+                    stream_id: stream.id,
+                    user_id: stream.user_id,
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    async fn resolve_mailbox(&self, mailbox: &str) -> Result<Option<IngestTarget>> {
+        let manager = self.manager()?;
+        let streams = StreamRepository::new(&manager.db_pool)
+            .list_by_kind("email")
+            .await?;
+
+        // Mail systems may change the case of the local part
+        Ok(streams
+            .into_iter()
+            .find(|stream| {
+                serde_json::from_str::<Value>(&stream.config)
+                    .ok()
+                    .and_then(|config| config.get("token")?.as_str().map(str::to_string))
+                    .is_some_and(|token| !token.is_empty() && token.eq_ignore_ascii_case(mailbox))
+            })
+            .map(|stream| IngestTarget {
+                stream_id: stream.id,
+                user_id: stream.user_id,
+            }))
+    }
+
+    async fn ingest(&self, upload: Upload) -> Result<String> {
+        let metadata = HashMap::from([
+            ("ingest_protocol".to_string(), upload.protocol.to_string()),
+            ("ingest_filename".to_string(), upload.filename),
+        ]);
+        self.manager()?
+            .ingest_snapshot(&upload.target.stream_id, upload.data, metadata, upload.text)
+            .await
+    }
+}
+
+/// Restream backend that checks user accounts and API keys and shares RTSP upstreams
+/// through a [`CaptureManager`]
+struct CaptureManagerRestream {
+    manager: Weak<CaptureManager>,
+}
+
+impl CaptureManagerRestream {
+    fn manager(&self) -> Result<Arc<CaptureManager>> {
+        self.manager
+            .upgrade()
+            .ok_or_else(|| Error::Config("Capture manager is no longer available".to_string()))
+    }
+}
+
+#[async_trait]
+impl RestreamBackend for CaptureManagerRestream {
+    async fn authorize(&self, stream_id: &str, username: &str, password: &str) -> Result<bool> {
+        let manager = self.manager()?;
+        let pool = &manager.db_pool;
+        if StreamRepository::new(pool)
+            .find_by_id(stream_id)
+            .await?
+            .is_none()
+        {
+            return Ok(false);
+        }
+
+        // Players that can't store an account password can use an API key instead
+        let key_hash = format!("{:x}", Sha256::digest(password.as_bytes()));
+        if let Some(key) = ApiKeyRepository::new(pool).find_by_hash(&key_hash).await? {
+            let expired = key
+                .expires_at
+                .as_deref()
+                .is_some_and(|expires_at| expires_at <= now_iso8601().as_str());
+            if !expired {
+                return Ok(true);
+            }
+        }
+
+        let users = UserRepository::new(pool);
+        let user = match users.find_by_username(username).await? {
+            Some(user) => Some(user),
+            None => users.find_by_email(username).await?,
+        };
+        let Some(user) = user.filter(|user| user.is_active != Some(false)) else {
+            return Ok(false);
+        };
+
+        // Every account can see every stream, so a valid login is enough
+        let password = password.to_string();
+        let hash = user.password_hash;
+        tokio::task::spawn_blocking(move || {
+            PasswordAuth::verify_password(&password, &hash, &gl_config::Argon2Config::default())
+        })
+        .await
+        .map_err(|e| Error::Config(format!("Password check failed: {}", e)))?
+    }
+
+    async fn subscribe(&self, stream_id: &str) -> Result<Option<RestreamFeed>> {
+        let Some(upstream) = self.manager()?.attach_rtsp_upstream(stream_id).await? else {
+            return Ok(None);
+        };
+        Ok(upstream.access_units().map(|units| RestreamFeed {
+            units,
+            upstream: Some(upstream.clone()),
+        }))
+    }
+}
+
 /// Implementation of CaptureService trait for job scheduler integration
 #[async_trait]
 impl CaptureService for CaptureManager {
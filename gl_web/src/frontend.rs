@@ -2594,7 +2594,12 @@ async fn take_snapshot_direct(
             let source_path = PathBuf::from(file_path);
             let file_source = gl_capture::FileSource::new(&source_path);
             let handle = file_source.start().await?;
-            handle.snapshot().await?
+            // Captured outside the capture task, so mask it here
+            frontend_state
+                .app_state
+                .capture_manager
+                .protect_stream_frame(&stream, handle.snapshot().await?)
+                .await?
         }
         "website" => {
             // Website capture - try capture manager first, then trigger manual capture
@@ -20,6 +20,7 @@ pub mod frontend;
 pub mod hybrid_server;
 pub mod middleware;
 pub mod models;
+pub mod privacy;
 
 /// Route handler implementations
 ///
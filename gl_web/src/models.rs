@@ -247,12 +247,25 @@ pub enum StreamConfig {
     File(FileConfig),
     Website(WebsiteConfig),
     Yt(YtConfig),
+    Http(HttpConfig),
+    Ftp(FtpConfig),
+    Email(EmailConfig),
+    Watch(WatchConfig),
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
 pub struct RtspConfig {
     #[validate(length(min = 1))]
     pub url: String,
+    /// "auto" (default) tries the in-process client first, "native" or "ffmpeg" force one
+    #[serde(default)]
+    #[validate(custom(function = "validate_rtsp_backend"))]
+    pub backend: Option<String>,
+    /// "native" (default) decodes snapshot keyframes in-process, "ffmpeg" spawns ffmpeg
+    /// per snapshot for codecs the in-process decoder can't handle, such as H.265
+    #[serde(default)]
+    #[validate(custom(function = "validate_keyframe_decoder"))]
+    pub keyframe_decoder: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
@@ -300,6 +313,98 @@ pub struct YtConfig {
     pub options: Option<serde_json::Map<String, serde_json::Value>>,
 }
 
+/// Camera still-image endpoint polled over HTTP(S)
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct HttpConfig {
+    #[validate(url)]
+    pub url: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    /// "auto" (default), "basic" or "digest"
+    #[serde(default)]
+    pub auth: Option<String>,
+    #[serde(default)]
+    pub headers: Option<std::collections::HashMap<String, String>>,
+    /// Accept self-signed TLS certificates
+    #[serde(default)]
+    pub insecure_tls: Option<bool>,
+    #[serde(default)]
+    pub timeout: Option<u64>,
+}
+
+/// Camera that uploads snapshots to the embedded FTP server
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct FtpConfig {
+    /// Login the camera uses; should be unique across FTP streams
+    #[validate(length(min = 1, max = 64))]
+    pub username: String,
+    #[validate(length(min = 8))]
+    pub password: String,
+}
+
+/// Device that emails snapshots to `<token>@<ingest domain>` on the embedded SMTP server
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct EmailConfig {
+    /// Mailbox name; acts as the shared secret, so it should be long and random
+    #[validate(
+        length(min = 16, max = 64),
+        custom(function = "validate_mailbox_token")
+    )]
+    pub token: String,
+}
+
+/// Directory watched for newly written images and videos
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct WatchConfig {
+    #[validate(length(min = 1))]
+    pub directory: String,
+    /// Glob for files to pick up, e.g. "*.{mp4,mov}" (default "*")
+    #[serde(default)]
+    pub pattern: Option<String>,
+    #[serde(default)]
+    pub recursive: Option<bool>,
+    /// Processed files are moved here when set
+    #[serde(default)]
+    pub archive_dir: Option<String>,
+    /// Process files already present when the stream starts (default true)
+    #[serde(default)]
+    pub include_existing: Option<bool>,
+    /// Seconds between frames sampled from videos (default 5)
+    #[serde(default)]
+    #[validate(range(min = 1))]
+    pub sample_interval: Option<u64>,
+    #[serde(default)]
+    #[validate(range(min = 1, max = 10000))]
+    pub max_frames_per_video: Option<u64>,
+}
+
+fn validate_rtsp_backend(backend: &str) -> Result<(), validator::ValidationError> {
+    match backend.to_lowercase().as_str() {
+        "auto" | "native" | "ffmpeg" => Ok(()),
+        _ => Err(validator::ValidationError::new("rtsp_backend")),
+    }
+}
+
+fn validate_keyframe_decoder(decoder: &str) -> Result<(), validator::ValidationError> {
+    match decoder.to_lowercase().as_str() {
+        "native" | "ffmpeg" => Ok(()),
+        _ => Err(validator::ValidationError::new("keyframe_decoder")),
+    }
+}
+
+fn validate_mailbox_token(token: &str) -> Result<(), validator::ValidationError> {
+    if token
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        Ok(())
+    } else {
+        Err(validator::ValidationError::new("mailbox_token"))
+    }
+}
+
 /// Stream information response matching frontend expectations
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct StreamInfo {
@@ -312,6 +417,9 @@ pub struct StreamInfo {
     pub last_frame_at: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub template_id: Option<String>,
+    /// Problems reported by the stream health watchdog (offline, frozen, black, ...)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub health_issues: Vec<String>,
 }
 
 /// Stream status enumeration
@@ -361,9 +469,43 @@ mod tests {
         let json = r#"{"kind":"rtsp","url":"rtsp://example"}"#;
         let config: StreamConfig = serde_json::from_str(json).unwrap();
         match config {
-            StreamConfig::Rtsp(t) => assert_eq!(t.url, "rtsp://example"),
+            StreamConfig::Rtsp(t) => {
+                assert_eq!(t.url, "rtsp://example");
+                assert!(t.backend.is_none());
+            }
+            _ => panic!("expected rtsp"),
+        }
+    }
+
+    #[test]
+    fn rtsp_backend_is_validated() {
+        let json = r#"{"kind":"rtsp","url":"rtsp://cam","backend":"native"}"#;
+        match serde_json::from_str::<StreamConfig>(json).unwrap() {
+            StreamConfig::Rtsp(t) => {
+                assert_eq!(t.backend.as_deref(), Some("native"));
+                assert!(t.validate().is_ok());
+            }
             _ => panic!("expected rtsp"),
         }
+
+        let unknown = RtspConfig {
+            url: "rtsp://cam".to_string(),
+            backend: Some("gstreamer".to_string()),
+            keyframe_decoder: None,
+        };
+        assert!(unknown.validate().is_err());
+
+        let h265 = RtspConfig {
+            url: "rtsp://cam".to_string(),
+            backend: None,
+            keyframe_decoder: Some("ffmpeg".to_string()),
+        };
+        assert!(h265.validate().is_ok());
+        let unknown_decoder = RtspConfig {
+            keyframe_decoder: Some("vaapi".to_string()),
+            ..h265
+        };
+        assert!(unknown_decoder.validate().is_err());
     }
 
     #[test]
@@ -409,4 +551,66 @@ mod tests {
             _ => panic!("expected yt"),
         }
     }
+
+    #[test]
+    fn deserialize_http_stream_config() {
+        let json = r#"{"kind":"http","url":"https://cam.local/snapshot.jpg","username":"admin","auth":"digest","insecure_tls":true}"#;
+        let config: StreamConfig = serde_json::from_str(json).unwrap();
+        match config {
+            StreamConfig::Http(t) => {
+                assert_eq!(t.url, "https://cam.local/snapshot.jpg");
+                assert_eq!(t.username.as_deref(), Some("admin"));
+                assert_eq!(t.auth.as_deref(), Some("digest"));
+                assert_eq!(t.insecure_tls, Some(true));
+            }
+            _ => panic!("expected http"),
+        }
+    }
+
+    #[test]
+    fn deserialize_ftp_stream_config() {
+        let json = r#"{"kind":"ftp","username":"trailcam","password":"short"}"#;
+        let config: StreamConfig = serde_json::from_str(json).unwrap();
+        match config {
+            StreamConfig::Ftp(t) => {
+                assert_eq!(t.username, "trailcam");
+                assert!(t.validate().is_err());
+            }
+            _ => panic!("expected ftp"),
+        }
+    }
+
+    #[test]
+    fn deserialize_email_stream_config() {
+        let json = r#"{"kind":"email","token":"driveway-k3x9q2m7v8w4"}"#;
+        let config: StreamConfig = serde_json::from_str(json).unwrap();
+        match config {
+            StreamConfig::Email(t) => {
+                assert_eq!(t.token, "driveway-k3x9q2m7v8w4");
+                assert!(t.validate().is_ok());
+            }
+            _ => panic!("expected email"),
+        }
+
+        let spaced = EmailConfig {
+            token: "not a valid mailbox token".to_string(),
+        };
+        assert!(spaced.validate().is_err());
+    }
+
+    #[test]
+    fn deserialize_watch_stream_config() {
+        let json = r#"{"kind":"watch","directory":"/mnt/nas/drone","pattern":"*.{mp4,mov}","archive_dir":"/mnt/nas/drone/done","sample_interval":10}"#;
+        let config: StreamConfig = serde_json::from_str(json).unwrap();
+        match config {
+            StreamConfig::Watch(t) => {
+                assert_eq!(t.directory, "/mnt/nas/drone");
+                assert_eq!(t.pattern.as_deref(), Some("*.{mp4,mov}"));
+                assert_eq!(t.archive_dir.as_deref(), Some("/mnt/nas/drone/done"));
+                assert_eq!(t.sample_interval, Some(10));
+                assert!(t.validate().is_ok());
+            }
+            _ => panic!("expected watch"),
+        }
+    }
 }
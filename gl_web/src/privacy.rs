@@ -0,0 +1,274 @@
+//! ABOUTME: Per-stream privacy: masks frames before they are stored, streamed or analyzed
+//! ABOUTME: and keeps unmasked originals under short retention for listed viewers only
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use gl_capture::artifact_storage::ArtifactStorageService;
+use gl_core::{time::now_iso8601, Error, Result};
+use gl_db::{OriginalFrameRepository, Stream};
+use gl_scheduler::{JobContext, JobDefinition, JobHandler};
+use gl_storage::{StorageManager, StorageUri};
+use gl_vision::{PrivacyConfig, PrivacyMasker};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+/// Scheduler job type that deletes unmasked originals past their retention
+pub const PURGE_JOB_TYPE: &str = "original_frame_purge";
+
+/// Purge schedule; originals outlive their retention by at most this much
+const PURGE_SCHEDULE: &str = "0 */5 * * * *";
+
+/// The `privacy` object of a stream config
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StreamPrivacyConfig {
+    #[serde(flatten)]
+    pub masking: PrivacyConfig,
+    /// Keep unmasked originals; without this they are discarded
+    pub originals: Option<OriginalsConfig>,
+}
+
+/// How unmasked originals are kept and who may see them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OriginalsConfig {
+    /// Hours an original is kept before it is deleted
+    pub retention_hours: u32,
+    /// Shortest time between two kept originals, in seconds
+    pub min_interval_secs: u64,
+    /// Emails or user IDs allowed to view originals; nobody when empty
+    pub viewers: Vec<String>,
+}
+
+impl Default for OriginalsConfig {
+    fn default() -> Self {
+        Self {
+            retention_hours: 24,
+            min_interval_secs: 60,
+            viewers: Vec::new(),
+        }
+    }
+}
+
+impl StreamPrivacyConfig {
+    /// Privacy settings of a stream, if it masks frames
+    ///
+    /// An invalid `privacy` object is an error rather than ignored, so a misconfigured
+    /// stream is never captured unmasked.
+    pub fn from_stream(stream: &Stream) -> Result<Option<Self>> {
+        let config: Value = serde_json::from_str(&stream.config)
+            .map_err(|e| Error::Config(format!("Invalid stream config JSON: {}", e)))?;
+        let Some(privacy) = config.get("privacy").filter(|v| !v.is_null()) else {
+            return Ok(None);
+        };
+        let privacy: Self = serde_json::from_value(privacy.clone())
+            .map_err(|e| Error::Config(format!("Invalid privacy config: {}", e)))?;
+        Ok(privacy.masking.is_enabled().then_some(privacy))
+    }
+
+    /// Whether a user may view this stream's unmasked originals
+    pub fn can_view_originals(&self, user_id: &str, email: &str) -> bool {
+        self.originals.as_ref().is_some_and(|originals| {
+            originals
+                .viewers
+                .iter()
+                .any(|viewer| viewer == user_id || viewer.eq_ignore_ascii_case(email))
+        })
+    }
+}
+
+/// Masking state of one stream, shared by everything that handles its frames
+pub struct StreamPrivacy {
+    config: StreamPrivacyConfig,
+    masker: Mutex<PrivacyMasker>,
+    last_original: Mutex<Option<Instant>>,
+}
+
+impl StreamPrivacy {
+    pub fn new(config: StreamPrivacyConfig) -> Result<Self> {
+        let masker = PrivacyMasker::new(config.masking.clone())?;
+        Ok(Self {
+            config,
+            masker: Mutex::new(masker),
+            last_original: Mutex::new(None),
+        })
+    }
+
+    pub fn config(&self) -> &StreamPrivacyConfig {
+        &self.config
+    }
+
+    /// Mask a frame on the blocking pool; frames that cannot be masked are an error
+    pub async fn mask(self: &Arc<Self>, frame: Bytes) -> Result<Bytes> {
+        let privacy = self.clone();
+        let masked =
+            tokio::task::spawn_blocking(move || privacy.masker.lock().unwrap().mask_bytes(&frame))
+                .await
+                .map_err(|e| Error::Config(format!("Privacy masking task failed: {}", e)))??;
+        if masked.people > 0 {
+            debug!(people = masked.people, "Pixelated people in frame");
+        }
+        Ok(Bytes::from(masked.data))
+    }
+
+    /// Whether the original of the frame being masked now should be kept
+    pub fn take_original_slot(&self) -> bool {
+        let Some(originals) = &self.config.originals else {
+            return false;
+        };
+        let mut last = self.last_original.lock().unwrap();
+        let due =
+            !last.is_some_and(|at| at.elapsed() < Duration::from_secs(originals.min_interval_secs));
+        if due {
+            *last = Some(Instant::now());
+        }
+        due
+    }
+}
+
+/// Delete originals whose retention has passed, returning how many were removed
+///
+/// Rows are removed before files, so an interrupted purge never leaves a servable row.
+pub(crate) async fn delete_expired_originals(
+    storage_service: &ArtifactStorageService<StorageManager>,
+    repo: &OriginalFrameRepository,
+) -> Result<usize> {
+    let expired = repo.take_expired(&now_iso8601()).await?;
+    for original in &expired {
+        let uri = StorageUri::new(original.storage_uri.as_str())
+            .map_err(|e| Error::Storage(e.to_string()))?;
+        if let Err(e) = storage_service.delete_artifact(&uri).await {
+            warn!(frame_id = %original.id, error = %e, "Failed to delete expired original frame");
+        }
+    }
+    Ok(expired.len())
+}
+
+/// Recurring job that purges expired originals
+///
+/// Writing an original also purges, but stopped streams and streams whose masking was
+/// removed write none, so this job is what bounds their retention.
+pub fn purge_job_definition() -> JobDefinition {
+    let mut job = JobDefinition::new(
+        "Original frame purge".to_string(),
+        PURGE_JOB_TYPE.to_string(),
+        PURGE_SCHEDULE.to_string(),
+        serde_json::json!({}),
+        "system".to_string(),
+    )
+    .with_description("Delete unmasked originals past their retention".to_string())
+    .with_max_retries(0)
+    .with_tags(vec![PURGE_JOB_TYPE.to_string()]);
+    job.id = PURGE_JOB_TYPE.to_string();
+    job
+}
+
+/// Job handler that deletes expired originals from the database and artifact storage
+pub struct OriginalPurgeJob {
+    storage_service: ArtifactStorageService<StorageManager>,
+}
+
+impl OriginalPurgeJob {
+    pub fn new(storage_service: ArtifactStorageService<StorageManager>) -> Self {
+        Self { storage_service }
+    }
+}
+
+#[async_trait]
+impl JobHandler for OriginalPurgeJob {
+    async fn execute(&self, context: JobContext) -> Result<serde_json::Value> {
+        let repo = OriginalFrameRepository::new(context.db.clone());
+        let purged = delete_expired_originals(&self.storage_service, &repo).await?;
+        if purged > 0 {
+            info!(purged, "Purged expired original frames");
+        }
+        Ok(serde_json::json!({ "purged": purged }))
+    }
+
+    fn job_type(&self) -> &'static str {
+        PURGE_JOB_TYPE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream(config: Value) -> Stream {
+        Stream {
+            id: "street".to_string(),
+            user_id: "owner".to_string(),
+            name: "Street".to_string(),
+            description: None,
+            config: config.to_string(),
+            is_default: false,
+            execution_status: None,
+            last_executed_at: None,
+            last_error_message: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn privacy_config_is_read_from_the_stream() {
+        assert!(
+            StreamPrivacyConfig::from_stream(&stream(serde_json::json!({"kind": "rtsp"})))
+                .unwrap()
+                .is_none()
+        );
+        // Settings that change nothing don't turn masking on
+        assert!(
+            StreamPrivacyConfig::from_stream(&stream(serde_json::json!({"privacy": {}})))
+                .unwrap()
+                .is_none()
+        );
+        assert!(StreamPrivacyConfig::from_stream(&stream(
+            serde_json::json!({"privacy": {"polygons": "everywhere"}})
+        ))
+        .is_err());
+
+        let config = StreamPrivacyConfig::from_stream(&stream(serde_json::json!({
+            "privacy": {
+                "polygons": [[[0.0, 0.0], [0.2, 0.0], [0.2, 0.2]]],
+                "originals": {"retention_hours": 6, "viewers": ["DPO@example.com", "user-7"]}
+            }
+        })))
+        .unwrap()
+        .unwrap();
+        assert_eq!(config.masking.polygons.len(), 1);
+        let originals = config.originals.as_ref().unwrap();
+        assert_eq!(originals.retention_hours, 6);
+        assert_eq!(originals.min_interval_secs, 60);
+        assert!(config.can_view_originals("someone", "dpo@example.com"));
+        assert!(config.can_view_originals("user-7", "other@example.com"));
+        assert!(!config.can_view_originals("owner", "owner@example.com"));
+    }
+
+    #[test]
+    fn originals_are_kept_at_most_once_per_interval() {
+        let privacy = StreamPrivacy::new(StreamPrivacyConfig {
+            masking: PrivacyConfig {
+                polygons: vec![vec![[0.0, 0.0], [0.5, 0.0], [0.5, 0.5]]],
+                ..Default::default()
+            },
+            originals: Some(OriginalsConfig::default()),
+        })
+        .unwrap();
+        assert!(privacy.take_original_slot());
+        assert!(!privacy.take_original_slot());
+
+        let discarded = StreamPrivacy::new(StreamPrivacyConfig {
+            masking: PrivacyConfig {
+                polygons: vec![vec![[0.0, 0.0], [0.5, 0.0], [0.5, 0.5]]],
+                ..Default::default()
+            },
+            originals: None,
+        })
+        .unwrap();
+        assert!(!discarded.take_original_slot());
+    }
+}
@@ -20,7 +20,8 @@ use crate::frontend::FrontendState;
 pub struct SummarizeApiRequest {
     pub text: String,
     pub max_length: Option<usize>,
-    pub style: Option<String>, // "brief", "detailed", "technical"
+    pub style: Option<String>,     // "brief", "detailed", "technical"
+    pub stream_id: Option<String>, // stream whose AI budget is charged
 }
 
 #[derive(Debug, Serialize)]
@@ -34,9 +35,11 @@ pub struct SummarizeApiResponse {
 #[derive(Debug, Deserialize)]
 pub struct DescribeFrameApiRequest {
     pub image_base64: String,
-    pub image_format: String,         // "jpeg", "png"
-    pub detail_level: Option<String>, // "low", "high", "auto"
-    pub focus: Option<String>,        // "objects", "activity", "scene"
+    pub image_format: String,                     // "jpeg", "png"
+    pub detail_level: Option<String>,             // "low", "high", "auto"
+    pub focus: Option<String>,                    // "objects", "activity", "scene"
+    pub stream_id: Option<String>,                // stream whose AI budget is charged
+    pub output_schema: Option<serde_json::Value>, // JSON schema the analysis must follow
 }
 
 #[derive(Debug, Serialize)]
@@ -45,6 +48,8 @@ pub struct DescribeFrameApiResponse {
     pub objects_detected: Vec<String>,
     pub confidence: Option<f64>,
     pub processing_time_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub structured: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -145,6 +150,7 @@ pub async fn summarize(
         text: request.text.clone(),
         max_length: request.max_length,
         style: request.style.clone(),
+        stream_id: request.stream_id.clone(),
     };
 
     match frontend_state
@@ -224,11 +230,27 @@ pub async fn describe_frame(
             .into_response();
     }
 
+    if let Some(schema) = &request.output_schema {
+        if let Err(e) = gl_ai::structured::check_schema(schema) {
+            return (
+                StatusCode::BAD_REQUEST,
+                ResponseJson(ErrorResponse::new(
+                    "validation_error",
+                    format!("Invalid output schema: {}", e),
+                )),
+            )
+                .into_response();
+        }
+    }
+
     let ai_request = DescribeFrameRequest {
         image_data,
         image_format: request.image_format.clone(),
         detail_level: request.detail_level.clone(),
         focus: request.focus.clone(),
+        stream_id: request.stream_id.clone(),
+        question: None,
+        output_schema: request.output_schema.clone(),
     };
 
     match frontend_state
@@ -251,6 +273,7 @@ pub async fn describe_frame(
                     objects_detected: response.objects_detected,
                     confidence: response.confidence,
                     processing_time_ms: response.processing_time_ms,
+                    structured: response.structured,
                 })),
             )
                 .into_response()
@@ -1,17 +1,28 @@
 //! ABOUTME: Alert and notification API endpoints
 //! ABOUTME: Provides endpoints for testing and managing notification systems
 
-use actix_web::{web, HttpResponse, Result as ActixResult};
+use actix_web::{web, HttpRequest, HttpResponse, Result as ActixResult};
 use gl_cap::profiles::AlertProfiles;
+use gl_db::{
+    CreatePushSubscription, NotificationDelivery, NotificationDeliveryRepository,
+    PushSubscriptionRepository,
+};
 use gl_notify::{
-    adapters::pushover::PushoverAdapter, circuit_breaker::CircuitBreakerWrapper,
-    retry::RetryWrapper, Notification, NotificationChannel, NotificationKind, NotificationManager,
+    adapters::{
+        pushover::PushoverAdapter,
+        webhook::{WebhookAdapter, WebhookPayloadFormat},
+    },
+    circuit_breaker::CircuitBreakerWrapper,
+    retry::RetryWrapper,
+    templates::parse_utc_offset,
+    DispatcherConfig, MessageTemplate, Notification, NotificationChannel, NotificationDispatcher,
+    NotificationKind, NotificationManager, Notifier, TemplateContext,
 };
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tracing::{info, warn};
 
-use crate::models::ApiResponse;
+use crate::{middleware::auth::get_http_auth_user, models::ApiResponse, AppState};
 
 /// Request payload for testing notifications
 #[derive(Debug, Deserialize)]
@@ -27,6 +38,10 @@ pub struct TestNotificationRequest {
     pub pushover_user_key: Option<String>,
     /// Optional webhook URL for testing
     pub webhook_url: Option<String>,
+    /// Optional secret to sign the test webhook with
+    pub webhook_secret: Option<String>,
+    /// Optional webhook body shape (glimpser, slack, teams, discord or a template)
+    pub webhook_payload_format: Option<WebhookPayloadFormat>,
 }
 
 fn default_notification_kind() -> NotificationKind {
@@ -48,6 +63,39 @@ pub struct CapPreviewRequest {
     pub custom_instruction: Option<String>,
 }
 
+/// Request payload for notification template preview
+#[derive(Debug, Deserialize)]
+pub struct TemplatePreviewRequest {
+    /// Channel whose template to render ("webhook", "pushover", ...)
+    #[serde(default = "default_preview_channel")]
+    pub channel: String,
+    /// Named or inline template, as set by `set_notification_template`
+    pub template: Option<String>,
+    /// Title template to try out instead of the configured one
+    pub title: Option<String>,
+    /// Body template to try out instead of the configured one
+    pub body: Option<String>,
+    /// UTC offset for time formatting (e.g. "+02:00")
+    pub utc_offset: Option<String>,
+    /// Event fields overriding the sample event
+    #[serde(default)]
+    pub event: serde_json::Map<String, serde_json::Value>,
+}
+
+fn default_preview_channel() -> String {
+    gl_notify::templates::DEFAULT_CHANNEL.to_string()
+}
+
+/// Response for notification template preview
+#[derive(Debug, Serialize)]
+pub struct TemplatePreviewResponse {
+    pub channel: String,
+    pub title: String,
+    pub body: String,
+    /// Event data the templates were rendered with
+    pub context: TemplateContext,
+}
+
 /// Available CAP alert profiles
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -138,10 +186,13 @@ pub async fn test_notification(
     // Add webhook channel if URL provided
     if let Some(webhook_url) = &payload.webhook_url {
         if let Ok(url) = webhook_url.parse() {
+            manager.register_adapter("webhook".to_string(), Arc::new(WebhookAdapter::new()));
             channels.push(NotificationChannel::Webhook {
                 url,
                 headers: None,
                 method: Some("POST".to_string()),
+                secret: payload.webhook_secret.clone(),
+                payload_format: payload.webhook_payload_format.clone(),
             });
             channel_names.push("webhook".to_string());
         } else {
@@ -311,6 +362,180 @@ pub async fn cap_preview(payload: web::Json<CapPreviewRequest>) -> ActixResult<H
     Ok(HttpResponse::Ok().json(ApiResponse::success(response)))
 }
 
+/// Sample event used when previewing notification templates
+fn sample_template_context() -> TemplateContext {
+    TemplateContext {
+        event_id: "evt_preview".to_string(),
+        event_type: "person_detected".to_string(),
+        severity: "high".to_string(),
+        confidence: 0.92,
+        description: "Person detected near the front entrance".to_string(),
+        source_id: "front_door".to_string(),
+        template_id: "front_door".to_string(),
+        timestamp: chrono::Utc::now(),
+        stream_name: Some("Front Door".to_string()),
+        snapshot_url: Some("/api/stream/front_door/snapshot".to_string()),
+        ai_description: Some("A person in a delivery uniform holding a parcel".to_string()),
+        suggested_actions: vec!["Review the snapshot".to_string()],
+        metadata: Default::default(),
+    }
+}
+
+/// Preview a notification rendered with the configured or supplied templates
+pub async fn template_preview(
+    state: Option<web::Data<AppState>>,
+    payload: web::Json<TemplatePreviewRequest>,
+) -> ActixResult<HttpResponse> {
+    let payload = payload.into_inner();
+    info!(
+        channel = %payload.channel,
+        template = ?payload.template,
+        "Received notification template preview request"
+    );
+
+    let mut templates = state
+        .map(|s| (*s.capture_manager.notification_templates()).clone())
+        .unwrap_or_default();
+
+    if let Some(offset) = &payload.utc_offset {
+        match parse_utc_offset(offset) {
+            Some(parsed) => templates.set_utc_offset(Some(parsed)),
+            None => {
+                return Ok(
+                    HttpResponse::BadRequest().json(ApiResponse::<()>::error(format!(
+                        "Invalid UTC offset: {}",
+                        offset
+                    ))),
+                )
+            }
+        }
+    }
+
+    // Overlay the supplied event fields on the sample event
+    let mut context_json = match serde_json::to_value(sample_template_context()) {
+        Ok(serde_json::Value::Object(map)) => map,
+        _ => serde_json::Map::new(),
+    };
+    context_json.extend(payload.event);
+    let context: TemplateContext =
+        match serde_json::from_value(serde_json::Value::Object(context_json)) {
+            Ok(context) => context,
+            Err(e) => {
+                return Ok(
+                    HttpResponse::BadRequest().json(ApiResponse::<()>::error(format!(
+                        "Invalid event fields: {}",
+                        e
+                    ))),
+                )
+            }
+        };
+
+    let resolved = templates.resolve(&payload.channel, payload.template.as_deref());
+    let template = MessageTemplate {
+        title: payload.title.unwrap_or(resolved.title),
+        body: payload.body.unwrap_or(resolved.body),
+    };
+
+    match templates.render_template(&template, &context) {
+        Ok(rendered) => Ok(HttpResponse::Ok().json(ApiResponse::success(
+            TemplatePreviewResponse {
+                channel: payload.channel,
+                title: rendered.title,
+                body: rendered.body,
+                context,
+            },
+        ))),
+        Err(e) => {
+            warn!(error = %e, "Failed to render notification template preview");
+            Ok(HttpResponse::BadRequest().json(ApiResponse::<()>::error(e.to_string())))
+        }
+    }
+}
+
+/// Query parameters for listing notification deliveries
+#[derive(Debug, Deserialize)]
+pub struct DeliveryListQuery {
+    /// Maximum number of deliveries to return (default 50, max 500)
+    pub limit: Option<i64>,
+    /// Only return deliveries for this analysis event
+    pub event_id: Option<String>,
+}
+
+/// Hide webhook secrets before returning a delivery
+fn redact_delivery(mut delivery: NotificationDelivery) -> NotificationDelivery {
+    if let Some(secret) = delivery.channel_config.get_mut("secret") {
+        *secret = serde_json::Value::String("[REDACTED]".to_string());
+    }
+    delivery
+}
+
+/// List recent notification deliveries
+pub async fn list_deliveries(
+    state: web::Data<AppState>,
+    query: web::Query<DeliveryListQuery>,
+) -> ActixResult<HttpResponse> {
+    let repo = NotificationDeliveryRepository::new(state.db.clone());
+    let result = match &query.event_id {
+        Some(event_id) => repo.get_by_event_id(event_id).await,
+        None => {
+            repo.list_recent(query.limit.unwrap_or(50).clamp(1, 500))
+                .await
+        }
+    };
+
+    match result {
+        Ok(deliveries) => {
+            let deliveries: Vec<_> = deliveries.into_iter().map(redact_delivery).collect();
+            Ok(HttpResponse::Ok().json(ApiResponse::success(deliveries)))
+        }
+        Err(e) => {
+            warn!(error = %e, "Failed to list notification deliveries");
+            Ok(
+                HttpResponse::InternalServerError().json(ApiResponse::<()>::error(format!(
+                    "Failed to list deliveries: {}",
+                    e
+                ))),
+            )
+        }
+    }
+}
+
+/// Send a notification delivery again
+///
+/// The replay is recorded as a new delivery referencing the original one.
+pub async fn redeliver(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> ActixResult<HttpResponse> {
+    let delivery_id = path.into_inner();
+    info!(delivery_id = %delivery_id, "Received notification redelivery request");
+
+    let mut dispatcher = NotificationDispatcher::new(DispatcherConfig::default(), state.db.clone());
+    dispatcher.set_templates((*state.capture_manager.notification_templates()).clone());
+    if let Some(web_push) = state.capture_manager.web_push().await {
+        dispatcher.register_adapter("webpush".to_string(), web_push);
+    }
+
+    match dispatcher.redeliver(&delivery_id).await {
+        Ok(delivery) => {
+            Ok(HttpResponse::Ok().json(ApiResponse::success(redact_delivery(delivery))))
+        }
+        Err(gl_core::Error::NotFound(msg)) => {
+            Ok(HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error(format!("{} not found", msg))))
+        }
+        Err(e) => {
+            warn!(delivery_id = %delivery_id, error = %e, "Failed to redeliver notification");
+            Ok(
+                HttpResponse::InternalServerError().json(ApiResponse::<()>::error(format!(
+                    "Failed to redeliver: {}",
+                    e
+                ))),
+            )
+        }
+    }
+}
+
 /// Get notification system health
 pub async fn notification_health() -> ActixResult<HttpResponse> {
     // TODO: Implement health check for configured notification adapters
@@ -319,7 +544,7 @@ pub async fn notification_health() -> ActixResult<HttpResponse> {
         "adapters": {
             "pushover": "available",
             "webhook": "available",
-            "webpush": "not_implemented"
+            "webpush": "available"
         },
         "cap_profiles": [
             "severe_weather",
@@ -340,13 +565,204 @@ pub async fn notification_health() -> ActixResult<HttpResponse> {
     Ok(HttpResponse::Ok().json(ApiResponse::success(health_info)))
 }
 
+/// Browser push subscription as produced by `PushSubscription.toJSON()`
+#[derive(Debug, Deserialize)]
+pub struct PushSubscriptionRequest {
+    pub endpoint: String,
+    pub keys: PushSubscriptionKeys,
+}
+
+/// Client keys of a browser push subscription
+#[derive(Debug, Deserialize)]
+pub struct PushSubscriptionKeys {
+    pub p256dh: String,
+    pub auth: String,
+}
+
+/// Request to remove a push subscription
+#[derive(Debug, Deserialize)]
+pub struct PushUnsubscribeRequest {
+    pub endpoint: String,
+}
+
+fn web_push_disabled() -> HttpResponse {
+    HttpResponse::ServiceUnavailable().json(ApiResponse::<()>::error(
+        "Web push is not enabled".to_string(),
+    ))
+}
+
+/// Get the VAPID public key the PWA subscribes with
+pub async fn vapid_public_key(state: web::Data<AppState>) -> ActixResult<HttpResponse> {
+    match state.capture_manager.web_push().await {
+        Some(web_push) => Ok(HttpResponse::Ok().json(ApiResponse::success(
+            serde_json::json!({ "public_key": web_push.public_key() }),
+        ))),
+        None => Ok(web_push_disabled()),
+    }
+}
+
+/// Register a push subscription for the authenticated user
+pub async fn subscribe_push(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    payload: web::Json<PushSubscriptionRequest>,
+) -> ActixResult<HttpResponse> {
+    let user = get_http_auth_user(&req)
+        .ok_or_else(|| actix_web::error::ErrorUnauthorized("Authentication required"))?;
+    let request = payload.into_inner();
+
+    if !(request.endpoint.starts_with("https://") || request.endpoint.starts_with("http://")) {
+        return Ok(HttpResponse::BadRequest().json(ApiResponse::<()>::error(
+            "Push endpoint must be an http(s) URL".to_string(),
+        )));
+    }
+    if request.keys.p256dh.is_empty() || request.keys.auth.is_empty() {
+        return Ok(HttpResponse::BadRequest().json(ApiResponse::<()>::error(
+            "Subscription keys p256dh and auth are required".to_string(),
+        )));
+    }
+
+    let user_agent = req
+        .headers()
+        .get("User-Agent")
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string());
+
+    let repo = PushSubscriptionRepository::new(state.db.clone());
+    match repo
+        .upsert(CreatePushSubscription {
+            user_id: user.id.clone(),
+            endpoint: request.endpoint,
+            p256dh: request.keys.p256dh,
+            auth: request.keys.auth,
+            user_agent,
+        })
+        .await
+    {
+        Ok(subscription) => {
+            info!(user_id = %user.id, subscription_id = %subscription.id, "Registered push subscription");
+            Ok(HttpResponse::Created().json(ApiResponse::success(subscription)))
+        }
+        Err(e) => {
+            warn!(error = %e, "Failed to register push subscription");
+            Ok(
+                HttpResponse::InternalServerError().json(ApiResponse::<()>::error(format!(
+                    "Failed to register subscription: {}",
+                    e
+                ))),
+            )
+        }
+    }
+}
+
+/// List the authenticated user's push subscriptions
+pub async fn list_push_subscriptions(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+) -> ActixResult<HttpResponse> {
+    let user = get_http_auth_user(&req)
+        .ok_or_else(|| actix_web::error::ErrorUnauthorized("Authentication required"))?;
+
+    match PushSubscriptionRepository::new(state.db.clone())
+        .list_by_user(&user.id)
+        .await
+    {
+        Ok(subscriptions) => Ok(HttpResponse::Ok().json(ApiResponse::success(subscriptions))),
+        Err(e) => {
+            warn!(error = %e, "Failed to list push subscriptions");
+            Ok(
+                HttpResponse::InternalServerError().json(ApiResponse::<()>::error(format!(
+                    "Failed to list subscriptions: {}",
+                    e
+                ))),
+            )
+        }
+    }
+}
+
+/// Remove one of the authenticated user's push subscriptions
+pub async fn unsubscribe_push(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    payload: web::Json<PushUnsubscribeRequest>,
+) -> ActixResult<HttpResponse> {
+    let user = get_http_auth_user(&req)
+        .ok_or_else(|| actix_web::error::ErrorUnauthorized("Authentication required"))?;
+
+    match PushSubscriptionRepository::new(state.db.clone())
+        .delete_for_user(&user.id, &payload.endpoint)
+        .await
+    {
+        Ok(true) => Ok(HttpResponse::NoContent().finish()),
+        Ok(false) => Ok(HttpResponse::NotFound().json(ApiResponse::<()>::error(
+            "Subscription not found".to_string(),
+        ))),
+        Err(e) => {
+            warn!(error = %e, "Failed to remove push subscription");
+            Ok(
+                HttpResponse::InternalServerError().json(ApiResponse::<()>::error(format!(
+                    "Failed to remove subscription: {}",
+                    e
+                ))),
+            )
+        }
+    }
+}
+
+/// Send a test push to all of the authenticated user's subscriptions
+pub async fn test_push(state: web::Data<AppState>, req: HttpRequest) -> ActixResult<HttpResponse> {
+    let user = get_http_auth_user(&req)
+        .ok_or_else(|| actix_web::error::ErrorUnauthorized("Authentication required"))?;
+    let Some(web_push) = state.capture_manager.web_push().await else {
+        return Ok(web_push_disabled());
+    };
+
+    let notification = Notification::new(
+        NotificationKind::Info,
+        "Glimpser test notification".to_string(),
+        "Push notifications are working".to_string(),
+        vec![NotificationChannel::WebPush {
+            user_id: Some(user.id.clone()),
+        }],
+    );
+
+    match web_push.send(&notification).await {
+        Ok(()) => Ok(
+            HttpResponse::Ok().json(ApiResponse::success(serde_json::json!({
+                "notification_id": notification.id.to_string()
+            }))),
+        ),
+        Err(e) => {
+            warn!(user_id = %user.id, error = %e, "Test push failed");
+            Ok(HttpResponse::BadGateway()
+                .json(ApiResponse::<()>::error(format!("Test push failed: {}", e))))
+        }
+    }
+}
+
 /// Configure alert routes
 pub fn configure_alert_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/alerts")
             .route("/health", web::get().to(notification_health))
             .route("/test", web::post().to(test_notification))
-            .route("/cap/preview", web::post().to(cap_preview)),
+            .route("/cap/preview", web::post().to(cap_preview))
+            .route("/templates/preview", web::post().to(template_preview))
+            .service(
+                web::scope("/deliveries")
+                    .wrap(crate::middleware::auth::RequireAuth::new())
+                    .route("", web::get().to(list_deliveries))
+                    .route("/{delivery_id}/redeliver", web::post().to(redeliver)),
+            )
+            .route("/push/vapid-public-key", web::get().to(vapid_public_key))
+            .service(
+                web::scope("/push")
+                    .wrap(crate::middleware::auth::RequireAuth::new())
+                    .route("/subscriptions", web::get().to(list_push_subscriptions))
+                    .route("/subscriptions", web::post().to(subscribe_push))
+                    .route("/subscriptions", web::delete().to(unsubscribe_push))
+                    .route("/test", web::post().to(test_push)),
+            ),
     );
 }
 
@@ -378,6 +794,8 @@ mod tests {
             body: "Test body".to_string(),
             pushover_user_key: None,
             webhook_url: None,
+            webhook_secret: None,
+            webhook_payload_format: None,
         };
 
         // This would fail validation in the actual handler
@@ -434,4 +852,36 @@ mod tests {
         assert!(json.contains("profile_used"));
         assert!(json.contains("metadata"));
     }
+
+    #[actix_web::test]
+    async fn test_template_preview_endpoint() {
+        let app = test::init_service(
+            App::new().route("/templates/preview", web::post().to(template_preview)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/templates/preview")
+            .set_json(json!({
+                "channel": "pushover",
+                "body": "{{ stream_name }} um {{ timestamp | time:\"%H:%M\" }} Uhr",
+                "utc_offset": "+02:00",
+                "event": {
+                    "stream_name": "Garage",
+                    "timestamp": "2024-06-01T08:30:00Z"
+                }
+            }))
+            .to_request();
+        let resp: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+        assert_eq!(resp["data"]["title"], "Person Detected on Garage");
+        assert_eq!(resp["data"]["body"], "Garage um 10:30 Uhr");
+
+        let req = test::TestRequest::post()
+            .uri("/templates/preview")
+            .set_json(json!({ "body": "{% if stream_name %}unclosed" }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 400);
+    }
 }
@@ -6,7 +6,10 @@ pub mod ai;
 pub mod ai_axum;
 pub mod alerts;
 pub mod auth;
+pub mod originals;
 pub mod public;
+pub mod reports;
+pub mod search;
 pub mod static_files;
 pub mod stream;
 pub mod streams;
@@ -0,0 +1,159 @@
+//! ABOUTME: Endpoints for the unmasked originals of privacy-masked streams
+//! ABOUTME: Only viewers listed in the stream's privacy config get access, and every view is logged
+
+use actix_web::{web, HttpRequest, HttpResponse, Result as ActixResult};
+use gl_db::{OriginalFrameRepository, StreamRepository};
+use serde::Deserialize;
+use tracing::{error, info, warn};
+
+use crate::{
+    middleware::auth::{get_http_auth_user, AuthUser},
+    models::{ApiResponse, ErrorResponse},
+    privacy::StreamPrivacyConfig,
+    AppState,
+};
+
+/// Original listing parameters
+#[derive(Debug, Deserialize)]
+pub struct OriginalListParams {
+    pub stream_id: String,
+    /// Maximum number of originals (default 50, max 500)
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// Check that the user may view a stream's originals, returning the refusal otherwise
+async fn authorize(state: &AppState, user: &AuthUser, stream_id: &str) -> Result<(), HttpResponse> {
+    let stream = match StreamRepository::new(state.db.pool())
+        .find_by_id(stream_id)
+        .await
+    {
+        Ok(Some(stream)) => stream,
+        Ok(None) => {
+            return Err(
+                HttpResponse::NotFound().json(ErrorResponse::new("not_found", "Stream not found"))
+            )
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to load stream");
+            return Err(HttpResponse::InternalServerError()
+                .json(ErrorResponse::new("database_error", e.to_string())));
+        }
+    };
+
+    let allowed = match StreamPrivacyConfig::from_stream(&stream) {
+        Ok(privacy) => {
+            privacy.is_some_and(|privacy| privacy.can_view_originals(&user.id, &user.email))
+        }
+        Err(e) => {
+            warn!(stream_id = %stream_id, error = %e, "Invalid privacy config");
+            false
+        }
+    };
+    if !allowed {
+        warn!(user_id = %user.id, stream_id = %stream_id, "Refused access to original frames");
+        return Err(HttpResponse::Forbidden().json(ErrorResponse::new(
+            "forbidden",
+            "Not allowed to view original frames of this stream",
+        )));
+    }
+    Ok(())
+}
+
+/// List a stream's unexpired originals, newest first
+pub async fn list_originals(
+    state: web::Data<AppState>,
+    params: web::Query<OriginalListParams>,
+    http: HttpRequest,
+) -> ActixResult<HttpResponse> {
+    let Some(user) = get_http_auth_user(&http) else {
+        return Ok(HttpResponse::Unauthorized().json(ErrorResponse::new(
+            "unauthorized",
+            "Authentication required",
+        )));
+    };
+    if let Err(refusal) = authorize(&state, &user, &params.stream_id).await {
+        return Ok(refusal);
+    }
+
+    match OriginalFrameRepository::new(state.db.clone())
+        .list_by_stream(
+            &params.stream_id,
+            params.limit.unwrap_or(50).clamp(1, 500),
+            params.offset.unwrap_or(0).max(0),
+        )
+        .await
+    {
+        Ok(originals) => {
+            info!(user_id = %user.id, stream_id = %params.stream_id, count = originals.len(), "Listed original frames");
+            Ok(HttpResponse::Ok().json(ApiResponse::success(originals)))
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to list original frames");
+            Ok(HttpResponse::InternalServerError()
+                .json(ErrorResponse::new("database_error", e.to_string())))
+        }
+    }
+}
+
+/// Serve an unexpired original as JPEG
+pub async fn get_original(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+    http: HttpRequest,
+) -> ActixResult<HttpResponse> {
+    let Some(user) = get_http_auth_user(&http) else {
+        return Ok(HttpResponse::Unauthorized().json(ErrorResponse::new(
+            "unauthorized",
+            "Authentication required",
+        )));
+    };
+    let original = match OriginalFrameRepository::new(state.db.clone())
+        .get_by_id(&path)
+        .await
+    {
+        Ok(Some(original)) => original,
+        Ok(None) => {
+            return Ok(HttpResponse::NotFound()
+                .json(ErrorResponse::new("not_found", "Original frame not found")))
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to load original frame");
+            return Ok(HttpResponse::InternalServerError()
+                .json(ErrorResponse::new("database_error", e.to_string())));
+        }
+    };
+    if let Err(refusal) = authorize(&state, &user, &original.stream_id).await {
+        return Ok(refusal);
+    }
+
+    match state.capture_manager.read_original(&original).await {
+        Ok(data) => {
+            info!(
+                user_id = %user.id,
+                email = %user.email,
+                stream_id = %original.stream_id,
+                frame_id = %original.id,
+                "Served original frame"
+            );
+            Ok(HttpResponse::Ok()
+                .content_type("image/jpeg")
+                .insert_header(("Cache-Control", "no-store"))
+                .body(data))
+        }
+        Err(e) => {
+            error!(frame_id = %original.id, error = %e, "Failed to read original frame");
+            Ok(HttpResponse::NotFound()
+                .json(ErrorResponse::new("not_found", "Original frame not found")))
+        }
+    }
+}
+
+pub fn configure_original_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/originals")
+            .wrap(crate::middleware::auth::RequireAuth::new())
+            .route("", web::get().to(list_originals))
+            .route("/{id}", web::get().to(get_original)),
+    );
+}
@@ -163,13 +163,19 @@ pub async fn streams(state: web::Data<AppState>) -> Result<HttpResponse> {
                 let fps = get_fps_for_stream_type(&config);
 
                 // Get last frame time from capture manager if running
-                let last_frame_at = if let Some(capture_info) =
-                    state.capture_manager.get_capture_status(&stream.id).await
-                {
-                    capture_info.last_frame_at.map(|dt| dt.to_rfc3339())
-                } else {
-                    stream.last_executed_at.clone()
+                let capture_info = state.capture_manager.get_capture_status(&stream.id).await;
+                let last_frame_at = match &capture_info {
+                    Some(capture_info) => capture_info.last_frame_at.map(|dt| dt.to_rfc3339()),
+                    None => stream.last_executed_at.clone(),
                 };
+                let health_issues = capture_info
+                    .map(|info| {
+                        info.health_issues
+                            .iter()
+                            .map(|issue| issue.as_str().to_string())
+                            .collect()
+                    })
+                    .unwrap_or_default();
 
                 stream_infos.push(StreamInfo {
                     id: stream.id.clone(),
@@ -180,6 +186,7 @@ pub async fn streams(state: web::Data<AppState>) -> Result<HttpResponse> {
                     fps,
                     last_frame_at,
                     template_id: Some(stream.id),
+                    health_issues,
                 });
             }
 
@@ -208,6 +215,11 @@ fn extract_source_from_stream_config(config: &StreamConfig) -> Option<String> {
         StreamConfig::Ffmpeg(c) => Some(c.source_url.clone()),
         StreamConfig::File(c) => Some(c.file_path.clone()),
         StreamConfig::Yt(c) => Some(c.url.clone()),
+        StreamConfig::Http(c) => Some(c.url.clone()),
+        StreamConfig::Ftp(c) => Some(format!("ftp://{}@", c.username)),
+        // The mailbox token is the credential, so it is not exposed
+        StreamConfig::Email(_) => Some("email://".to_string()),
+        StreamConfig::Watch(c) => Some(c.directory.clone()),
     }
 }
 
@@ -230,6 +242,10 @@ fn get_fps_for_stream_type(config: &StreamConfig) -> u32 {
         StreamConfig::Ffmpeg(_) => 30, // FFmpeg streams are usually 30 FPS
         StreamConfig::File(_) => 24,   // Video files often 24 FPS
         StreamConfig::Yt(_) => 30,     // YouTube streams typically 30 FPS
+        StreamConfig::Http(_) => 1,    // Snapshot URLs are polled once per interval
+        StreamConfig::Ftp(_) => 1,     // Cameras upload a still when triggered
+        StreamConfig::Email(_) => 1,   // Devices email a still when triggered
+        StreamConfig::Watch(_) => 1,   // Files are sampled as they arrive
     }
 }
 
@@ -250,6 +266,8 @@ mod tests {
     fn extract_source_from_rtsp_config() {
         let config = StreamConfig::Rtsp(RtspConfig {
             url: "rtsp://camera".into(),
+            backend: None,
+            keyframe_decoder: None,
         });
         assert_eq!(
             extract_source_from_stream_config(&config).as_deref(),
@@ -0,0 +1,98 @@
+//! ABOUTME: Endpoints for browsing stored digest reports
+//! ABOUTME: Lists reports and serves each one's narrative, counts and rendered HTML
+
+use actix_web::{web, HttpResponse, Result as ActixResult};
+use gl_db::DigestReportRepository;
+use serde::Deserialize;
+use tracing::error;
+
+use crate::{
+    models::{ApiResponse, ErrorResponse},
+    AppState,
+};
+
+/// Report listing parameters
+#[derive(Debug, Deserialize)]
+pub struct ListReportsParams {
+    /// Only reports of this digest
+    pub name: Option<String>,
+    /// Maximum number of reports (default 20, max 100)
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// List digest reports, newest first
+pub async fn list_reports(
+    state: web::Data<AppState>,
+    params: web::Query<ListReportsParams>,
+) -> ActixResult<HttpResponse> {
+    let repo = DigestReportRepository::new(state.db.clone());
+    match repo
+        .list(
+            params.name.as_deref(),
+            params.limit.unwrap_or(20).clamp(1, 100),
+            params.offset.unwrap_or(0).max(0),
+        )
+        .await
+    {
+        Ok(reports) => Ok(HttpResponse::Ok().json(ApiResponse::success(reports))),
+        Err(e) => {
+            error!(error = %e, "Failed to list digest reports");
+            Ok(HttpResponse::InternalServerError()
+                .json(ErrorResponse::new("database_error", e.to_string())))
+        }
+    }
+}
+
+async fn find_report(state: &AppState, id: &str) -> Result<gl_db::DigestReport, HttpResponse> {
+    match DigestReportRepository::new(state.db.clone())
+        .get_by_id(id)
+        .await
+    {
+        Ok(Some(report)) => Ok(report),
+        Ok(None) => Err(HttpResponse::NotFound().json(ErrorResponse::new(
+            "not_found",
+            format!("Report {} not found", id),
+        ))),
+        Err(e) => {
+            error!(report_id = %id, error = %e, "Failed to get digest report");
+            Err(HttpResponse::InternalServerError()
+                .json(ErrorResponse::new("database_error", e.to_string())))
+        }
+    }
+}
+
+/// Get a report's narrative and counts
+pub async fn get_report(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> ActixResult<HttpResponse> {
+    match find_report(&state, &path).await {
+        Ok(report) => Ok(HttpResponse::Ok().json(ApiResponse::success(report))),
+        Err(response) => Ok(response),
+    }
+}
+
+/// Get a report as the HTML page that was sent
+pub async fn get_report_html(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> ActixResult<HttpResponse> {
+    match find_report(&state, &path).await {
+        Ok(report) => Ok(HttpResponse::Ok()
+            .content_type("text/html; charset=utf-8")
+            .body(report.html)),
+        Err(response) => Ok(response),
+    }
+}
+
+/// Configure report routes
+pub fn configure_report_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/reports")
+            .wrap(crate::middleware::auth::RequireAuth::new())
+            .route("", web::get().to(list_reports))
+            .route("/{id}", web::get().to(get_report))
+            .route("/{id}/html", web::get().to(get_report_html)),
+    );
+}
@@ -0,0 +1,230 @@
+//! ABOUTME: Search endpoints for full-text search and the local snapshot embedding index
+//! ABOUTME: Finds events, alerts and streams by keyword, plate reads by plate and snapshots by example
+
+use actix_web::{web, HttpResponse, Result as ActixResult};
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, SecondsFormat, Utc};
+use gl_analysis::{plate_key, search_snapshots, SearchQuery};
+use gl_db::{
+    AnalysisEventRepository, EmbeddingQuery, SearchKind, SearchRepository, TextSearchQuery,
+};
+use serde::Deserialize;
+use tracing::{debug, error};
+
+use crate::{
+    models::{ApiResponse, ErrorResponse},
+    AppState,
+};
+
+/// Full-text search parameters
+#[derive(Debug, Deserialize)]
+pub struct TextSearchParams {
+    /// Words to look for
+    pub q: String,
+    /// Comma-separated kinds to search: event, alert, stream (default: all)
+    pub kinds: Option<String>,
+    pub stream_id: Option<String>,
+    /// Earliest time, RFC3339
+    pub from: Option<DateTime<Utc>>,
+    /// Latest time, RFC3339
+    pub to: Option<DateTime<Utc>>,
+    /// Maximum number of hits (default 20, max 200)
+    pub limit: Option<i64>,
+}
+
+fn parse_kinds(kinds: Option<&str>) -> Result<Vec<SearchKind>, String> {
+    let Some(kinds) = kinds else {
+        return Ok(Vec::new());
+    };
+    kinds
+        .split(',')
+        .map(str::trim)
+        .filter(|kind| !kind.is_empty())
+        .map(|kind| {
+            SearchKind::parse(kind).ok_or_else(|| format!("Unknown search kind '{}'", kind))
+        })
+        .collect()
+}
+
+/// Search analysis events, alerts and streams by keyword
+pub async fn search(
+    state: web::Data<AppState>,
+    params: web::Query<TextSearchParams>,
+) -> ActixResult<HttpResponse> {
+    let kinds = match parse_kinds(params.kinds.as_deref()) {
+        Ok(kinds) => kinds,
+        Err(msg) => {
+            return Ok(HttpResponse::BadRequest().json(ErrorResponse::new("validation_error", msg)))
+        }
+    };
+    let query = TextSearchQuery {
+        text: params.q.clone(),
+        kinds,
+        stream_id: params.stream_id.clone(),
+        from: params
+            .from
+            .map(|from| from.to_rfc3339_opts(SecondsFormat::Secs, true)),
+        to: params
+            .to
+            .map(|to| to.to_rfc3339_opts(SecondsFormat::Secs, true)),
+        limit: params.limit.unwrap_or(20).clamp(1, 200),
+    };
+    debug!(text = %query.text, kinds = ?query.kinds, "Full-text search");
+
+    match SearchRepository::new(state.db.clone()).search(&query).await {
+        Ok(results) => Ok(HttpResponse::Ok().json(ApiResponse::success(results))),
+        Err(gl_core::Error::Validation(msg)) => {
+            Ok(HttpResponse::BadRequest().json(ErrorResponse::new("validation_error", msg)))
+        }
+        Err(e) => {
+            error!(error = %e, "Full-text search failed");
+            Ok(HttpResponse::InternalServerError()
+                .json(ErrorResponse::new("search_error", e.to_string())))
+        }
+    }
+}
+
+/// Plate search parameters
+#[derive(Debug, Deserialize)]
+pub struct PlateSearchParams {
+    /// Whole or partial plate; spaces, dashes and case are ignored
+    pub q: String,
+    pub stream_id: Option<String>,
+    /// Earliest time, RFC3339
+    pub from: Option<DateTime<Utc>>,
+    /// Latest time, RFC3339
+    pub to: Option<DateTime<Utc>>,
+    /// Maximum number of reads (default 50, max 500)
+    pub limit: Option<i64>,
+}
+
+/// Find plate reads by plate, newest first
+pub async fn search_plates(
+    state: web::Data<AppState>,
+    params: web::Query<PlateSearchParams>,
+) -> ActixResult<HttpResponse> {
+    let plate = plate_key(&params.q);
+    if plate.is_empty() {
+        return Ok(HttpResponse::BadRequest().json(ErrorResponse::new(
+            "validation_error",
+            "Plate query must contain letters or digits",
+        )));
+    }
+    let from = params
+        .from
+        .map(|from| from.to_rfc3339_opts(SecondsFormat::Secs, true));
+    let to = params
+        .to
+        .map(|to| to.to_rfc3339_opts(SecondsFormat::Secs, true));
+    debug!(plate = %plate, "Plate search");
+
+    match AnalysisEventRepository::new(state.db.clone())
+        .search_plates(
+            &plate,
+            params.stream_id.as_deref(),
+            from.as_deref(),
+            to.as_deref(),
+            params.limit.unwrap_or(50).clamp(1, 500),
+        )
+        .await
+    {
+        Ok(reads) => Ok(HttpResponse::Ok().json(ApiResponse::success(reads))),
+        Err(e) => {
+            error!(error = %e, "Plate search failed");
+            Ok(HttpResponse::InternalServerError()
+                .json(ErrorResponse::new("search_error", e.to_string())))
+        }
+    }
+}
+
+/// Semantic snapshot search request; exactly one of `text`, `image_base64` and `snapshot_id`
+#[derive(Debug, Deserialize)]
+pub struct SnapshotSearchRequest {
+    /// Description such as "red truck at the gate"
+    pub text: Option<String>,
+    /// Example image, JPEG or PNG
+    pub image_base64: Option<String>,
+    /// Find snapshots similar to this one
+    pub snapshot_id: Option<String>,
+    pub stream_id: Option<String>,
+    /// Earliest capture time, RFC3339
+    pub from: Option<DateTime<Utc>>,
+    /// Latest capture time, RFC3339
+    pub to: Option<DateTime<Utc>>,
+    /// Maximum number of matches (default 20, max 200)
+    pub limit: Option<usize>,
+}
+
+impl SnapshotSearchRequest {
+    fn query(&self) -> Result<SearchQuery, String> {
+        match (&self.text, &self.image_base64, &self.snapshot_id) {
+            (Some(text), None, None) if !text.trim().is_empty() => {
+                Ok(SearchQuery::Text(text.trim().to_string()))
+            }
+            (None, Some(image), None) => general_purpose::STANDARD
+                .decode(image)
+                .map(SearchQuery::Image)
+                .map_err(|e| format!("Invalid base64 image: {}", e)),
+            (None, None, Some(snapshot_id)) => Ok(SearchQuery::Snapshot(snapshot_id.clone())),
+            _ => Err("Give exactly one of text, image_base64 or snapshot_id".to_string()),
+        }
+    }
+}
+
+/// Rank stored snapshots by similarity to a text, image or snapshot
+pub async fn search_snapshots_handler(
+    state: web::Data<AppState>,
+    request: web::Json<SnapshotSearchRequest>,
+) -> ActixResult<HttpResponse> {
+    let Some(embedder) = state.capture_manager.embedder().await else {
+        return Ok(HttpResponse::ServiceUnavailable().json(ErrorResponse::new(
+            "search_disabled",
+            "Snapshot search is not enabled",
+        )));
+    };
+
+    let query = match request.query() {
+        Ok(query) => query,
+        Err(msg) => {
+            return Ok(HttpResponse::BadRequest().json(ErrorResponse::new("validation_error", msg)))
+        }
+    };
+    let filters = EmbeddingQuery {
+        stream_id: request.stream_id.clone(),
+        from: request
+            .from
+            .map(|from| from.to_rfc3339_opts(SecondsFormat::Secs, true)),
+        to: request
+            .to
+            .map(|to| to.to_rfc3339_opts(SecondsFormat::Secs, true)),
+        limit: request.limit.unwrap_or(20).clamp(1, 200),
+        ..Default::default()
+    };
+    debug!(stream_id = ?filters.stream_id, limit = filters.limit, "Searching snapshots");
+
+    match search_snapshots(embedder.as_ref(), &state.db, query, filters).await {
+        Ok(matches) => Ok(HttpResponse::Ok().json(ApiResponse::success(matches))),
+        Err(gl_core::Error::NotFound(msg)) => {
+            Ok(HttpResponse::NotFound().json(ErrorResponse::new("not_found", msg)))
+        }
+        Err(gl_core::Error::Validation(msg)) => {
+            Ok(HttpResponse::BadRequest().json(ErrorResponse::new("validation_error", msg)))
+        }
+        Err(e) => {
+            error!(error = %e, "Snapshot search failed");
+            Ok(HttpResponse::InternalServerError()
+                .json(ErrorResponse::new("search_error", e.to_string())))
+        }
+    }
+}
+
+/// Configure search routes
+pub fn configure_search_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/search")
+            .wrap(crate::middleware::auth::RequireAuth::new())
+            .route("", web::get().to(search))
+            .route("/plates", web::get().to(search_plates))
+            .route("/snapshots", web::post().to(search_snapshots_handler)),
+    );
+}
@@ -2,15 +2,17 @@
 //! ABOUTME: Handles video stream snapshot generation from streams
 
 use actix_web::{web, HttpRequest, HttpResponse, Result as ActixResult};
+use chrono::{DateTime, DurationRound, SecondsFormat, Utc};
 use gl_capture::{
-    CaptureSource, FfmpegConfig, FfmpegSource, FileSource, HardwareAccel, JobStatus, OutputFormat,
-    RtspTransport, SnapshotConfig, YtDlpConfig, YtDlpSource,
+    CaptureSource, FfmpegConfig, FfmpegSource, FileSource, HardwareAccel, HttpSnapshotSource,
+    JobStatus, OutputFormat, SnapshotConfig, YtDlpConfig, YtDlpSource,
 };
 #[cfg(feature = "website")]
 use gl_capture::{WebsiteConfig, WebsiteSource};
 use gl_core::{Error, Id, Result};
-use gl_db::StreamRepository;
+use gl_db::{MotionHeatmapRepository, StreamRepository};
 use gl_stream::{MjpegStream, StreamConfig, StreamSession};
+use gl_vision::HeatGrid;
 use hex;
 use serde_json::Value;
 use sha2::{Digest, Sha256};
@@ -75,7 +77,7 @@ pub struct JobStatusResponse {
 
 #[derive(OpenApi)]
 #[openapi(
-    paths(snapshot, snapshot_async, job_status, job_result, recent_snapshots, mjpeg_stream, start_stream, stop_stream),
+    paths(snapshot, snapshot_async, job_status, job_result, recent_snapshots, heatmap, mjpeg_stream, start_stream, stop_stream),
     components(schemas(SnapshotJobResponse, JobStatusResponse)),
     tags((name = "stream", description = "Stream snapshot, MJPEG streaming, and lifecycle operations"))
 )]
@@ -356,6 +358,126 @@ pub async fn recent_snapshots(
     }
 }
 
+/// Time range for a motion heatmap
+#[derive(Debug, serde::Deserialize)]
+pub struct HeatmapQuery {
+    /// Start of the range, RFC3339 (default: 24 hours before `to`)
+    pub from: Option<DateTime<Utc>>,
+    /// End of the range, RFC3339 (default: now)
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Render where motion happened in a time range as an overlay on the latest snapshot
+#[utoipa::path(
+    get,
+    path = "/api/stream/{stream_id}/heatmap",
+    params(
+        ("stream_id" = String, Path, description = "Stream ID"),
+        ("from" = Option<String>, Query, description = "Range start, RFC3339 (default: 24 hours ago)"),
+        ("to" = Option<String>, Query, description = "Range end, RFC3339 (default: now)")
+    ),
+    responses(
+        (status = 200, description = "Heatmap overlay", content_type = "image/png"),
+        (status = 400, description = "Invalid time range", body = ErrorResponse),
+        (status = 404, description = "Stream not found"),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    security(("jwt_auth" = []), ("api_key" = []))
+)]
+#[actix_web::get("/{stream_id}/heatmap")]
+pub async fn heatmap(
+    path: web::Path<String>,
+    query: web::Query<HeatmapQuery>,
+    state: web::Data<AppState>,
+) -> ActixResult<HttpResponse> {
+    let stream_id = path.into_inner();
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query.from.unwrap_or(to - chrono::Duration::hours(24));
+    if from >= to {
+        return Ok(HttpResponse::BadRequest().json(ErrorResponse::new(
+            "invalid_range",
+            "'from' must be before 'to'",
+        )));
+    }
+
+    match render_heatmap(&stream_id, from, to, &state).await {
+        Ok((png, frames)) => Ok(HttpResponse::Ok()
+            .content_type("image/png")
+            .insert_header(("cache-control", "private, max-age=60"))
+            .insert_header(("x-heatmap-frames", frames.to_string()))
+            .body(png)),
+        Err(Error::NotFound(msg)) => {
+            Ok(HttpResponse::NotFound().json(ErrorResponse::new("stream_not_found", &msg)))
+        }
+        Err(e) => {
+            error!(error = %e, stream_id = stream_id, "Failed to render motion heatmap");
+            Ok(HttpResponse::InternalServerError()
+                .json(ErrorResponse::new("heatmap_error", e.to_string())))
+        }
+    }
+}
+
+/// Sum the stored hours overlapping the range and draw them; returns the PNG and the
+/// number of frames it covers
+async fn render_heatmap(
+    stream_id: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    state: &AppState,
+) -> Result<(Vec<u8>, u64)> {
+    StreamRepository::new(state.db.pool())
+        .find_by_id(stream_id)
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("Stream {} not found", stream_id)))?;
+
+    // Hours are stored by their start, so include the one `from` falls in
+    let first_hour = from
+        .duration_trunc(chrono::Duration::hours(1))
+        .unwrap_or(from);
+    let hours = MotionHeatmapRepository::new(state.db.clone())
+        .list_range(
+            stream_id,
+            &first_hour.to_rfc3339_opts(SecondsFormat::Secs, true),
+            &to.to_rfc3339_opts(SecondsFormat::Secs, true),
+        )
+        .await?;
+
+    // Hours recorded before a grid size change can't be lined up, so the newest shape wins
+    let mut total: Option<HeatGrid> = None;
+    for hour in hours.into_iter().rev() {
+        let grid = HeatGrid::from_parts(hour.cols, hour.rows, hour.frames, hour.cells)?;
+        match &mut total {
+            Some(total) => {
+                if total.merge(&grid).is_err() {
+                    debug!(stream_id = %stream_id, hour_start = %hour.hour_start, "Skipping heatmap hour with a different grid size");
+                }
+            }
+            None => total = Some(grid),
+        }
+    }
+    let total = match total {
+        Some(total) => total,
+        None => HeatGrid::new(1, 1)?,
+    };
+    let frames = total.frames();
+
+    let background = state
+        .capture_manager
+        .get_latest_snapshot(stream_id)
+        .await
+        .ok();
+    let png = tokio::task::spawn_blocking(move || {
+        total
+            .render_png(background.as_deref())
+            // A snapshot we can't decode shouldn't hide the heatmap
+            .or_else(|_| total.render_png(None))
+    })
+    .await
+    .map_err(|e| Error::External(format!("Heatmap rendering task failed: {}", e)))??;
+
+    Ok((png, frames))
+}
+
 /// Get live stream (alias for snapshot for now)
 #[utoipa::path(
     get,
@@ -622,37 +744,14 @@ async fn take_snapshot_impl(stream_id: String, state: &AppState) -> Result<Vec<u
             handle.snapshot().await?
         }
         "rtsp" => {
-            // RTSP stream handling
-            let rtsp_url = config.get("url").and_then(|v| v.as_str()).ok_or_else(|| {
-                Error::Config("RTSP stream config missing 'url' field".to_string())
-            })?;
-
-            // Use FFmpeg to handle RTSP with optimized settings
-            let mut ffmpeg_config = FfmpegConfig {
-                input_url: rtsp_url.to_string(),
-                rtsp_transport: RtspTransport::Tcp, // Default to TCP for reliability
-                ..Default::default()
-            };
-
-            // Parse optional RTSP-specific configuration
-            if let Some(transport) = config.get("transport").and_then(|v| v.as_str()) {
-                ffmpeg_config.rtsp_transport = match transport.to_lowercase().as_str() {
-                    "tcp" => RtspTransport::Tcp,
-                    "udp" => RtspTransport::Udp,
-                    _ => RtspTransport::Auto,
-                };
-            }
-
-            // Parse optional timeout (RTSP streams often need longer timeouts)
-            if let Some(timeout_val) = config.get("timeout").and_then(|v| v.as_u64()) {
-                ffmpeg_config.timeout = Some(timeout_val as u32);
-            } else {
-                // Default timeout for RTSP
-                ffmpeg_config.timeout = Some(10);
-            }
-
-            let ffmpeg_source = FfmpegSource::new(ffmpeg_config);
-            let handle = ffmpeg_source.start().await?;
+            let handle =
+                crate::capture_manager::CaptureManager::start_rtsp_capture(&config).await?;
+            handle.snapshot().await?
+        }
+        "http" => {
+            let http_config =
+                crate::capture_manager::CaptureManager::http_snapshot_config(&config)?;
+            let handle = HttpSnapshotSource::new(http_config)?.start().await?;
             handle.snapshot().await?
         }
         _ => {
@@ -660,6 +759,12 @@ async fn take_snapshot_impl(stream_id: String, state: &AppState) -> Result<Vec<u
         }
     };
 
+    // Frames captured here bypass the capture task, so mask them the same way
+    let jpeg_bytes = state
+        .capture_manager
+        .protect_stream_frame(&stream, jpeg_bytes)
+        .await?;
+
     Ok(jpeg_bytes.to_vec())
 }
 
@@ -1092,6 +1197,11 @@ fn create_capture_source_from_stream(
 
             Ok(Box::new(YtDlpSource::new(ytdlp_config)))
         }
+        "http" => {
+            let http_config =
+                crate::capture_manager::CaptureManager::http_snapshot_config(&config)?;
+            Ok(Box::new(HttpSnapshotSource::new(http_config)?))
+        }
         _ => Err(Error::Config(format!("Unsupported stream kind: {}", kind))),
     }
 }
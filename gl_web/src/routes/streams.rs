@@ -77,6 +77,10 @@ fn validate_stream_config(config: &StreamConfig) -> Result<(), String> {
         StreamConfig::File(c) => c.validate(),
         StreamConfig::Website(c) => c.validate(),
         StreamConfig::Yt(c) => c.validate(),
+        StreamConfig::Http(c) => c.validate(),
+        StreamConfig::Ftp(c) => c.validate(),
+        StreamConfig::Email(c) => c.validate(),
+        StreamConfig::Watch(c) => c.validate(),
     }
     .map_err(|e| e.to_string())
 }
@@ -5,7 +5,10 @@ pub mod admin;
 
 use crate::{
     middleware, models,
-    routes::{ai, alerts, auth as auth_routes, public, static_files, stream, streams},
+    routes::{
+        ai, alerts, auth as auth_routes, originals, public, reports, search, static_files, stream,
+        streams,
+    },
     AppState,
 };
 use actix_web::{web, App, HttpRequest, HttpResponse};
@@ -110,6 +113,7 @@ pub fn create_app(
                         .wrap(middleware::auth::RequireAuth::new())
                         .service(stream::snapshot)
                         .service(stream::recent_snapshots)
+                        .service(stream::heatmap)
                         .service(stream::mjpeg_stream)
                         .service(stream::start_stream)
                         .service(stream::stop_stream)
@@ -128,6 +132,9 @@ pub fn create_app(
                 )
                 .configure(alerts::configure_alert_routes)
                 .configure(ai::configure_ai_routes)
+                .configure(search::configure_search_routes)
+                .configure(reports::configure_report_routes)
+                .configure(originals::configure_original_routes)
                 .service(
                     web::scope("/debug").route(
                         "/test",
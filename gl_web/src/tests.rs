@@ -1134,3 +1134,1385 @@ mod auth_security_tests {
         assert!(true, "No sessionStorage usage verified in codebase");
     }
 }
+
+#[actix_web::test]
+async fn test_notification_delivery_redelivery() {
+    let state = create_test_app_state().await;
+    let user = create_test_user(&state, "alerts@example.com", "password123").await;
+    let token = crate::auth::JwtAuth::create_token(
+        &user.id,
+        &user.email,
+        &state.security_config.jwt_secret,
+        &state.security_config.jwt_issuer,
+    )
+    .expect("Failed to create token");
+
+    let stream = gl_db::StreamRepository::new(state.db.pool())
+        .create(gl_db::CreateStreamRequest {
+            user_id: user.id.clone(),
+            name: "Porch".to_string(),
+            description: None,
+            config: r#"{"kind":"file","file_path":"/dev/null"}"#.to_string(),
+            is_default: false,
+        })
+        .await
+        .expect("Failed to create stream");
+    let event = gl_db::AnalysisEventRepository::new(state.db.clone())
+        .create(gl_db::CreateAnalysisEvent {
+            template_id: stream.id.clone(),
+            event_type: "motion".to_string(),
+            severity: "medium".to_string(),
+            confidence: 0.8,
+            description: "Motion on the porch".to_string(),
+            metadata: None,
+            processor_name: "motion".to_string(),
+            source_id: stream.id.clone(),
+            should_notify: true,
+            suggested_actions: None,
+        })
+        .await
+        .expect("Failed to create event");
+    let delivery = gl_db::NotificationDeliveryRepository::new(state.db.clone())
+        .create(gl_db::CreateNotificationDelivery {
+            analysis_event_id: event.id.clone(),
+            channel_type: "webhook".to_string(),
+            // Nothing listens on the discard port, so the replay fails fast
+            channel_config: std::collections::HashMap::from([
+                ("url".to_string(), json!("http://127.0.0.1:9/hook")),
+                ("secret".to_string(), json!("s3cret")),
+            ]),
+            max_attempts: Some(1),
+            scheduled_at: None,
+            metadata: None,
+        })
+        .await
+        .expect("Failed to create delivery");
+
+    let app = test::init_service(create_app(state)).await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/alerts/deliveries")
+        .to_request();
+    let status = match test::try_call_service(&app, req).await {
+        Ok(resp) => resp.status(),
+        Err(e) => e.as_response_error().status_code(),
+    };
+    assert_eq!(status, 401);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/alerts/deliveries?event_id={}", event.id))
+        .insert_header(("authorization", format!("Bearer {}", token)))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["data"][0]["id"], json!(delivery.id));
+    assert_eq!(body["data"][0]["channel_config"]["secret"], "[REDACTED]");
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/alerts/deliveries/{}/redeliver", delivery.id))
+        .insert_header(("authorization", format!("Bearer {}", token)))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_ne!(body["data"]["id"], json!(delivery.id));
+    assert_eq!(body["data"]["status"], "Failed");
+    assert_eq!(
+        body["data"]["metadata"]["redelivery_of"],
+        json!(delivery.id)
+    );
+
+    let req = test::TestRequest::post()
+        .uri("/api/alerts/deliveries/missing/redeliver")
+        .insert_header(("authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404);
+}
+
+#[actix_web::test]
+async fn test_push_subscription_endpoints() {
+    let state = create_test_app_state().await;
+    let user = create_test_user(&state, "push@example.com", "password123").await;
+    let token = crate::auth::JwtAuth::create_token(
+        &user.id,
+        &user.email,
+        &state.security_config.jwt_secret,
+        &state.security_config.jwt_issuer,
+    )
+    .expect("Failed to create token");
+    state
+        .capture_manager
+        .enable_web_push(&gl_config::WebPushConfig::default())
+        .await
+        .expect("Failed to enable web push");
+
+    let app = test::init_service(create_app(state)).await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/alerts/push/vapid-public-key")
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let public_key = body["data"]["public_key"].as_str().unwrap();
+    assert_eq!(public_key.len(), 87); // 65 bytes, unpadded base64url
+
+    let subscription = json!({
+        "endpoint": "https://push.example.com/send/abc",
+        "keys": { "p256dh": "BOrj9X2P", "auth": "c2VjcmV0" }
+    });
+    let req = test::TestRequest::post()
+        .uri("/api/alerts/push/subscriptions")
+        .set_json(&subscription)
+        .to_request();
+    let status = match test::try_call_service(&app, req).await {
+        Ok(resp) => resp.status(),
+        Err(e) => e.as_response_error().status_code(),
+    };
+    assert_eq!(status, 401);
+
+    let req = test::TestRequest::post()
+        .uri("/api/alerts/push/subscriptions")
+        .insert_header(("authorization", format!("Bearer {}", token)))
+        .set_json(&subscription)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 201);
+
+    let req = test::TestRequest::post()
+        .uri("/api/alerts/push/subscriptions")
+        .insert_header(("authorization", format!("Bearer {}", token)))
+        .set_json(json!({ "endpoint": "ftp://nope", "keys": { "p256dh": "a", "auth": "b" } }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+
+    let req = test::TestRequest::get()
+        .uri("/api/alerts/push/subscriptions")
+        .insert_header(("authorization", format!("Bearer {}", token)))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["data"].as_array().unwrap().len(), 1);
+    assert_eq!(body["data"][0]["endpoint"], subscription["endpoint"]);
+
+    for expected in [204, 404] {
+        let req = test::TestRequest::delete()
+            .uri("/api/alerts/push/subscriptions")
+            .insert_header(("authorization", format!("Bearer {}", token)))
+            .set_json(json!({ "endpoint": subscription["endpoint"] }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), expected);
+    }
+}
+
+#[actix_web::test]
+async fn test_ftp_upload_becomes_snapshot() {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpStream;
+
+    let state = create_test_app_state().await;
+    let user = create_test_user(&state, "ftp@example.com", "password123").await;
+    let stream = gl_db::StreamRepository::new(state.db.pool())
+        .create(gl_db::CreateStreamRequest {
+            user_id: user.id.clone(),
+            name: "Trail cam".to_string(),
+            description: None,
+            config: r#"{"kind":"ftp","username":"trailcam","password":"s3cret-pass"}"#.to_string(),
+            is_default: false,
+        })
+        .await
+        .expect("Failed to create stream");
+
+    let artifacts_dir = std::env::temp_dir().join(format!("glimpser_ftp_{}", Id::new()));
+    let manager = Arc::new(capture_manager::CaptureManager::with_storage_config(
+        state.db.pool().clone(),
+        gl_config::StorageConfig {
+            artifacts_dir: artifacts_dir.to_string_lossy().to_string(),
+            ..Default::default()
+        },
+        state.background_snapshot_service.clone(),
+    ));
+    let addr = manager
+        .start_ftp_ingest(&gl_config::FtpIngestConfig {
+            bind: "127.0.0.1:0".to_string(),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to start FTP ingest");
+
+    async fn reply(
+        lines: &mut tokio::io::Lines<BufReader<tokio::net::tcp::OwnedReadHalf>>,
+        writer: &mut tokio::net::tcp::OwnedWriteHalf,
+        command: Option<&str>,
+    ) -> String {
+        if let Some(command) = command {
+            writer
+                .write_all(format!("{}\r\n", command).as_bytes())
+                .await
+                .unwrap();
+        }
+        lines.next_line().await.unwrap().unwrap()
+    }
+
+    let (read_half, mut w) = TcpStream::connect(addr).await.unwrap().into_split();
+    let mut r = BufReader::new(read_half).lines();
+
+    assert!(reply(&mut r, &mut w, None).await.starts_with("220"));
+    assert!(reply(&mut r, &mut w, Some("USER trailcam"))
+        .await
+        .starts_with("331"));
+    assert!(reply(&mut r, &mut w, Some("PASS wrong"))
+        .await
+        .starts_with("530"));
+    assert!(reply(&mut r, &mut w, Some("USER trailcam"))
+        .await
+        .starts_with("331"));
+    assert!(reply(&mut r, &mut w, Some("PASS s3cret-pass"))
+        .await
+        .starts_with("230"));
+
+    let epsv = reply(&mut r, &mut w, Some("EPSV")).await;
+    let port: u16 = epsv.split('|').nth(3).unwrap().parse().unwrap();
+    let mut data = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    assert!(reply(&mut r, &mut w, Some("STOR IMG_0001.JPG"))
+        .await
+        .starts_with("150"));
+    data.write_all(&[0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10, 0xFF, 0xD9])
+        .await
+        .unwrap();
+    drop(data);
+    assert!(reply(&mut r, &mut w, None).await.starts_with("226"));
+
+    let snapshot = gl_db::SnapshotRepository::new(state.db.pool())
+        .get_latest_by_template(&stream.id)
+        .await
+        .unwrap()
+        .expect("Upload should be stored as a snapshot");
+    assert_eq!(snapshot.user_id, user.id);
+    assert_eq!(snapshot.file_size, 8);
+
+    let _ = std::fs::remove_dir_all(artifacts_dir);
+}
+
+#[actix_web::test]
+async fn test_emailed_snapshot_routes_by_mailbox_token() {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpStream;
+
+    let state = create_test_app_state().await;
+    let user = create_test_user(&state, "smtp@example.com", "password123").await;
+    let stream = gl_db::StreamRepository::new(state.db.pool())
+        .create(gl_db::CreateStreamRequest {
+            user_id: user.id.clone(),
+            name: "Alarm panel".to_string(),
+            description: None,
+            config: r#"{"kind":"email","token":"panel-k3x9q2m7v8w4"}"#.to_string(),
+            is_default: false,
+        })
+        .await
+        .expect("Failed to create stream");
+
+    let artifacts_dir = std::env::temp_dir().join(format!("glimpser_smtp_{}", Id::new()));
+    let manager = Arc::new(capture_manager::CaptureManager::with_storage_config(
+        state.db.pool().clone(),
+        gl_config::StorageConfig {
+            artifacts_dir: artifacts_dir.to_string_lossy().to_string(),
+            ..Default::default()
+        },
+        state.background_snapshot_service.clone(),
+    ));
+    let addr = manager
+        .start_smtp_ingest(&gl_config::SmtpIngestConfig {
+            bind: "127.0.0.1:0".to_string(),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to start SMTP ingest");
+
+    async fn reply(
+        lines: &mut tokio::io::Lines<BufReader<tokio::net::tcp::OwnedReadHalf>>,
+        writer: &mut tokio::net::tcp::OwnedWriteHalf,
+        command: Option<&str>,
+    ) -> String {
+        if let Some(command) = command {
+            writer
+                .write_all(format!("{}\r\n", command).as_bytes())
+                .await
+                .unwrap();
+        }
+        lines.next_line().await.unwrap().unwrap()
+    }
+
+    let (read_half, mut w) = TcpStream::connect(addr).await.unwrap().into_split();
+    let mut r = BufReader::new(read_half).lines();
+
+    assert!(reply(&mut r, &mut w, None).await.starts_with("220"));
+    assert!(reply(&mut r, &mut w, Some("HELO panel"))
+        .await
+        .starts_with("250"));
+    assert!(reply(&mut r, &mut w, Some("MAIL FROM:<panel@example.com>"))
+        .await
+        .starts_with("250"));
+    assert!(reply(
+        &mut r,
+        &mut w,
+        Some("RCPT TO:<unknown-mailbox@glimpser.local>")
+    )
+    .await
+    .starts_with("550"));
+    // Mailbox tokens match case-insensitively
+    assert!(reply(
+        &mut r,
+        &mut w,
+        Some("RCPT TO:<PANEL-K3X9Q2M7V8W4@glimpser.local>")
+    )
+    .await
+    .starts_with("250"));
+    assert!(reply(&mut r, &mut w, Some("DATA")).await.starts_with("354"));
+
+    let message = concat!(
+        "Subject: Zone 3 alarm\r\n",
+        "Content-Type: multipart/mixed; boundary=\"b\"\r\n",
+        "\r\n",
+        "--b\r\n",
+        "Content-Type: text/plain\r\n",
+        "\r\n",
+        "Intrusion detected\r\n",
+        "--b\r\n",
+        "Content-Type: image/jpeg\r\n",
+        "Content-Transfer-Encoding: base64\r\n",
+        "Content-Disposition: attachment; filename=\"zone3.jpg\"\r\n",
+        "\r\n",
+        "/9j/4AAQ/9k=\r\n",
+        "--b--\r\n",
+    );
+    w.write_all(message.as_bytes()).await.unwrap();
+    assert!(reply(&mut r, &mut w, Some(".")).await.starts_with("250"));
+
+    let snapshot = gl_db::SnapshotRepository::new(state.db.pool())
+        .get_latest_by_template(&stream.id)
+        .await
+        .unwrap()
+        .expect("Attachment should be stored as a snapshot");
+    assert_eq!(snapshot.user_id, user.id);
+    assert_eq!(snapshot.file_size, 8);
+
+    let _ = std::fs::remove_dir_all(artifacts_dir);
+}
+
+#[actix_web::test]
+async fn test_watch_folder_stream_stores_dropped_files() {
+    let state = create_test_app_state().await;
+    let user = create_test_user(&state, "watch@example.com", "password123").await;
+
+    let drop_dir = std::env::temp_dir().join(format!("glimpser_watch_drop_{}", Id::new()));
+    std::fs::create_dir_all(&drop_dir).unwrap();
+    std::fs::write(
+        drop_dir.join("frame.jpg"),
+        [0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10, 0xFF, 0xD9],
+    )
+    .unwrap();
+
+    let stream = gl_db::StreamRepository::new(state.db.pool())
+        .create(gl_db::CreateStreamRequest {
+            user_id: user.id.clone(),
+            name: "Drone drops".to_string(),
+            description: None,
+            config: json!({
+                "kind": "watch",
+                "directory": drop_dir.to_string_lossy(),
+                "pattern": "*.jpg",
+                "archive_dir": drop_dir.join("done").to_string_lossy(),
+            })
+            .to_string(),
+            is_default: false,
+        })
+        .await
+        .expect("Failed to create stream");
+
+    let artifacts_dir = std::env::temp_dir().join(format!("glimpser_watch_{}", Id::new()));
+    let manager = capture_manager::CaptureManager::with_storage_config(
+        state.db.pool().clone(),
+        gl_config::StorageConfig {
+            artifacts_dir: artifacts_dir.to_string_lossy().to_string(),
+            ..Default::default()
+        },
+        state.background_snapshot_service.clone(),
+    );
+    manager.start_stream(&stream.id).await.unwrap();
+
+    let snapshots = gl_db::SnapshotRepository::new(state.db.pool());
+    let mut snapshot = None;
+    for _ in 0..100 {
+        snapshot = snapshots.get_latest_by_template(&stream.id).await.unwrap();
+        if snapshot.is_some() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    let snapshot = snapshot.expect("Dropped file should be stored as a snapshot");
+    assert_eq!(snapshot.user_id, user.id);
+    assert_eq!(snapshot.file_size, 8);
+
+    // The processed file is archived once its frames are handed off
+    for _ in 0..50 {
+        if drop_dir.join("done/frame.jpg").exists() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    assert!(drop_dir.join("done/frame.jpg").exists());
+
+    manager.stop_stream(&stream.id).await.unwrap();
+    let _ = std::fs::remove_dir_all(drop_dir);
+    let _ = std::fs::remove_dir_all(artifacts_dir);
+}
+
+#[actix_web::test]
+async fn test_rtsp_backend_selection() {
+    let unknown = json!({"kind": "rtsp", "url": "rtsp://127.0.0.1/cam", "backend": "vlc"});
+    let err = capture_manager::CaptureManager::start_rtsp_capture(&unknown)
+        .await
+        .err()
+        .expect("unknown backend should be rejected");
+    assert!(err.to_string().contains("Unsupported RTSP backend"));
+
+    let unknown_decoder = json!({
+        "kind": "rtsp",
+        "url": "rtsp://127.0.0.1/cam",
+        "backend": "native",
+        "keyframe_decoder": "vaapi"
+    });
+    let err = capture_manager::CaptureManager::start_rtsp_capture(&unknown_decoder)
+        .await
+        .err()
+        .expect("unknown keyframe decoder should be rejected");
+    assert!(err.to_string().contains("Unsupported keyframe decoder"));
+
+    // Forcing the native client reports its own failure instead of falling back to ffmpeg
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    drop(listener);
+    let native = json!({
+        "kind": "rtsp",
+        "url": format!("rtsp://127.0.0.1:{}/cam", port),
+        "backend": "native",
+        "timeout": 2
+    });
+    let err = capture_manager::CaptureManager::start_rtsp_capture(&native)
+        .await
+        .err()
+        .expect("nothing is listening");
+    assert!(err.to_string().contains("RTSP DESCRIBE failed"));
+}
+
+#[actix_web::test]
+async fn test_restream_requires_account_credentials() {
+    use base64::Engine as _;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    let state = create_test_app_state().await;
+    let user = create_test_user(&state, "viewer@example.com", "password123").await;
+
+    // Nothing listens on the camera's port, so an authorized client gets 503
+    let camera = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let camera_port = camera.local_addr().unwrap().port();
+    drop(camera);
+    let stream = gl_db::StreamRepository::new(state.db.pool())
+        .create(gl_db::CreateStreamRequest {
+            user_id: user.id.clone(),
+            name: "Porch".to_string(),
+            description: None,
+            config: format!(
+                r#"{{"kind":"rtsp","url":"rtsp://127.0.0.1:{}/live","backend":"native","timeout":1}}"#,
+                camera_port
+            ),
+            is_default: false,
+        })
+        .await
+        .expect("Failed to create stream");
+
+    let manager = Arc::new(capture_manager::CaptureManager::new(
+        state.db.pool().clone(),
+        state.background_snapshot_service.clone(),
+    ));
+    let addr = manager
+        .start_restream(&gl_config::RestreamConfig {
+            bind: "127.0.0.1:0".to_string(),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to start restream server");
+
+    async fn describe(addr: std::net::SocketAddr, path: &str, login: Option<&str>) -> String {
+        let mut socket = TcpStream::connect(addr).await.unwrap();
+        let mut request = format!("DESCRIBE rtsp://{}/{} RTSP/1.0\r\nCSeq: 2\r\n", addr, path);
+        if let Some(login) = login {
+            request.push_str(&format!(
+                "Authorization: Basic {}\r\n",
+                base64::engine::general_purpose::STANDARD.encode(login)
+            ));
+        }
+        request.push_str("\r\n");
+        socket.write_all(request.as_bytes()).await.unwrap();
+
+        let mut response = vec![0u8; 1024];
+        let n = socket.read(&mut response).await.unwrap();
+        String::from_utf8_lossy(&response[..n]).to_string()
+    }
+
+    let anonymous = describe(addr, &stream.id, None).await;
+    assert!(anonymous.starts_with("RTSP/1.0 401"));
+    assert!(anonymous.contains("WWW-Authenticate: Basic realm=\"glimpser\""));
+    assert!(describe(addr, &stream.id, Some("viewer:wrong"))
+        .await
+        .starts_with("RTSP/1.0 401"));
+    assert!(describe(addr, "no-such-stream", Some("viewer:password123"))
+        .await
+        .starts_with("RTSP/1.0 401"));
+    assert!(
+        describe(addr, &stream.id, Some("viewer@example.com:password123"))
+            .await
+            .starts_with("RTSP/1.0 503")
+    );
+}
+
+#[actix_web::test]
+async fn test_health_watchdog_flags_black_camera() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let state = create_test_app_state().await;
+    let user = create_test_user(&state, "health@example.com", "password123").await;
+
+    // A camera that answers every request with a black picture
+    let mut black = Vec::new();
+    image::DynamicImage::ImageLuma8(image::GrayImage::new(64, 48))
+        .to_rgb8()
+        .write_to(
+            &mut std::io::Cursor::new(&mut black),
+            image::ImageFormat::Jpeg,
+        )
+        .unwrap();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let camera_url = format!("http://{}/snapshot.jpg", listener.local_addr().unwrap());
+    let camera = tokio::spawn(async move {
+        loop {
+            let Ok((mut socket, _)) = listener.accept().await else {
+                break;
+            };
+            let body = black.clone();
+            tokio::spawn(async move {
+                let mut request = [0u8; 2048];
+                let _ = socket.read(&mut request).await;
+                let header = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: image/jpeg\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                );
+                let _ = socket.write_all(header.as_bytes()).await;
+                let _ = socket.write_all(&body).await;
+            });
+        }
+    });
+
+    let stream = gl_db::StreamRepository::new(state.db.pool())
+        .create(gl_db::CreateStreamRequest {
+            user_id: user.id.clone(),
+            name: "Lens cap".to_string(),
+            description: None,
+            config: json!({
+                "kind": "http",
+                "url": camera_url,
+                "snapshot_interval": 1,
+                "health": {"exposure_after_secs": 0}
+            })
+            .to_string(),
+            is_default: false,
+        })
+        .await
+        .expect("Failed to create stream");
+
+    let artifacts_dir = std::env::temp_dir().join(format!("glimpser_health_{}", Id::new()));
+    let manager = capture_manager::CaptureManager::with_storage_config(
+        state.db.pool().clone(),
+        gl_config::StorageConfig {
+            artifacts_dir: artifacts_dir.to_string_lossy().to_string(),
+            ..Default::default()
+        },
+        state.background_snapshot_service.clone(),
+    );
+    manager.start_stream(&stream.id).await.unwrap();
+
+    let mut info = None;
+    for _ in 0..100 {
+        info = manager.get_capture_status(&stream.id).await;
+        if info
+            .as_ref()
+            .is_some_and(|info| !info.health_issues.is_empty())
+        {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    let info = info.expect("capture should be running");
+    assert_eq!(info.health_issues, [gl_analysis::HealthIssue::Black]);
+    assert!(info.last_frame_at.is_some());
+    assert_eq!(info.status, capture_manager::CaptureStatus::Active);
+
+    manager.stop_stream(&stream.id).await.unwrap();
+    camera.abort();
+    let _ = std::fs::remove_dir_all(artifacts_dir);
+}
+
+#[actix_web::test]
+async fn test_motion_heatmap_endpoint() {
+    let state = create_test_app_state().await;
+    let user = create_test_user(&state, "heatmap@example.com", "password123").await;
+    let token = crate::auth::JwtAuth::create_token(
+        &user.id,
+        &user.email,
+        &state.security_config.jwt_secret,
+        &state.security_config.jwt_issuer,
+    )
+    .expect("Failed to create token");
+
+    let stream = gl_db::StreamRepository::new(state.db.pool())
+        .create(gl_db::CreateStreamRequest {
+            user_id: user.id.clone(),
+            name: "Lobby".to_string(),
+            description: None,
+            config: json!({"kind": "file", "file_path": "/tmp/lobby.mp4"}).to_string(),
+            is_default: false,
+        })
+        .await
+        .expect("Failed to create stream");
+    let heatmaps = gl_db::MotionHeatmapRepository::new(state.db.clone());
+    for (hour, frames) in [
+        ("2026-10-18T08:00:00Z", 100),
+        ("2026-10-18T09:00:00Z", 50),
+        ("2026-10-18T12:00:00Z", 7),
+    ] {
+        heatmaps
+            .merge(&stream.id, hour, 2, 2, frames, &[frames as u32, 0, 0, 1])
+            .await
+            .unwrap();
+    }
+
+    let app = test::init_service(create_app(state)).await;
+
+    // The hour containing `from` counts; hours starting at or after `to` don't
+    let req = test::TestRequest::get()
+        .uri(&format!(
+            "/api/stream/{}/heatmap?from=2026-10-18T08:30:00Z&to=2026-10-18T12:00:00Z",
+            stream.id
+        ))
+        .insert_header(("authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers().get("content-type").unwrap(), "image/png");
+    assert_eq!(resp.headers().get("x-heatmap-frames").unwrap(), "150");
+    let body = test::read_body(resp).await;
+    let overlay = image::load_from_memory(&body).unwrap().to_rgba8();
+    // No snapshot yet, so the heat is drawn on a blank canvas with the busy corner in red
+    let hot = overlay.get_pixel(10, 10);
+    assert!(hot[0] > 150 && hot[2] < 100, "{:?}", hot);
+
+    let req = test::TestRequest::get()
+        .uri(&format!(
+            "/api/stream/{}/heatmap?from=2026-10-18T12:00:00Z&to=2026-10-18T08:00:00Z",
+            stream.id
+        ))
+        .insert_header(("authorization", format!("Bearer {}", token)))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 400);
+
+    let req = test::TestRequest::get()
+        .uri("/api/stream/missing/heatmap")
+        .insert_header(("authorization", format!("Bearer {}", token)))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 404);
+}
+
+#[actix_web::test]
+async fn test_full_text_search_endpoint() {
+    let state = create_test_app_state().await;
+    let user = create_test_user(&state, "fts@example.com", "password123").await;
+    let token = crate::auth::JwtAuth::create_token(
+        &user.id,
+        &user.email,
+        &state.security_config.jwt_secret,
+        &state.security_config.jwt_issuer,
+    )
+    .expect("Failed to create token");
+
+    let stream = gl_db::StreamRepository::new(state.db.pool())
+        .create(gl_db::CreateStreamRequest {
+            user_id: user.id.clone(),
+            name: "Loading dock".to_string(),
+            description: None,
+            config: json!({"kind": "file", "file_path": "/tmp/dock.mp4"}).to_string(),
+            is_default: false,
+        })
+        .await
+        .expect("Failed to create stream");
+    gl_db::AnalysisEventRepository::new(state.db.clone())
+        .create(gl_db::CreateAnalysisEvent {
+            template_id: stream.id.clone(),
+            event_type: "frame_described".to_string(),
+            severity: "info".to_string(),
+            confidence: 0.8,
+            description: "A forklift moves pallets across the dock".to_string(),
+            metadata: None,
+            processor_name: "ai_description".to_string(),
+            source_id: stream.id.clone(),
+            should_notify: false,
+            suggested_actions: None,
+        })
+        .await
+        .unwrap();
+
+    let app = test::init_service(create_app(state)).await;
+    let search = |uri: &str| {
+        test::TestRequest::get()
+            .uri(uri)
+            .insert_header(("authorization", format!("Bearer {}", token)))
+            .to_request()
+    };
+
+    let resp = test::call_service(&app, search("/api/search?q=dock")).await;
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"]["total"], 2);
+    assert_eq!(body["data"]["facets"]["streams"][0]["count"], 2);
+
+    let resp = test::call_service(&app, search("/api/search?q=forklift&kinds=event")).await;
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"]["hits"][0]["stream_name"], "Loading dock");
+    assert_eq!(
+        body["data"]["hits"][0]["snippet"],
+        "A <mark>forklift</mark> moves pallets across the dock"
+    );
+
+    let resp = test::call_service(&app, search("/api/search?q=dock&kinds=camera")).await;
+    assert_eq!(resp.status(), 400);
+    let resp = test::call_service(&app, search("/api/search?q=%2A")).await;
+    assert_eq!(resp.status(), 400);
+    let req = test::TestRequest::get()
+        .uri("/api/search?q=dock")
+        .to_request();
+    let status = match test::try_call_service(&app, req).await {
+        Ok(resp) => resp.status(),
+        Err(e) => e.as_response_error().status_code(),
+    };
+    assert_eq!(status, 401);
+}
+
+#[actix_web::test]
+async fn test_plate_search_endpoint() {
+    let state = create_test_app_state().await;
+    let user = create_test_user(&state, "plates@example.com", "password123").await;
+    let token = crate::auth::JwtAuth::create_token(
+        &user.id,
+        &user.email,
+        &state.security_config.jwt_secret,
+        &state.security_config.jwt_issuer,
+    )
+    .expect("Failed to create token");
+
+    let stream = gl_db::StreamRepository::new(state.db.pool())
+        .create(gl_db::CreateStreamRequest {
+            user_id: user.id.clone(),
+            name: "Gate".to_string(),
+            description: None,
+            config: json!({"kind": "rtsp", "url": "rtsp://gate", "plates": {"region": "gb"}})
+                .to_string(),
+            is_default: false,
+        })
+        .await
+        .expect("Failed to create stream");
+    gl_db::AnalysisEventRepository::new(state.db.clone())
+        .create(gl_db::CreateAnalysisEvent {
+            template_id: stream.id.clone(),
+            event_type: "plate_read".to_string(),
+            severity: "high".to_string(),
+            confidence: 0.92,
+            description: "Plate AB12CDE read (denied vehicle)".to_string(),
+            metadata: Some(std::collections::HashMap::from([
+                ("plate".to_string(), json!("AB12CDE")),
+                ("plate_list".to_string(), json!("denied")),
+            ])),
+            processor_name: "plate".to_string(),
+            source_id: stream.id.clone(),
+            should_notify: true,
+            suggested_actions: None,
+        })
+        .await
+        .unwrap();
+
+    let app = test::init_service(create_app(state)).await;
+    let search = |uri: &str| {
+        test::TestRequest::get()
+            .uri(uri)
+            .insert_header(("authorization", format!("Bearer {}", token)))
+            .to_request()
+    };
+
+    let resp = test::call_service(&app, search("/api/search/plates?q=ab12%20c")).await;
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"].as_array().unwrap().len(), 1);
+    assert_eq!(body["data"][0]["metadata"]["plate_list"], "denied");
+
+    let resp = test::call_service(&app, search("/api/search/plates?q=ZZ99")).await;
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert!(body["data"].as_array().unwrap().is_empty());
+
+    let resp = test::call_service(&app, search("/api/search/plates?q=-%20-")).await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[actix_web::test]
+async fn test_snapshot_search_endpoint() {
+    let state = create_test_app_state().await;
+    let user = create_test_user(&state, "search@example.com", "password123").await;
+    let token = crate::auth::JwtAuth::create_token(
+        &user.id,
+        &user.email,
+        &state.security_config.jwt_secret,
+        &state.security_config.jwt_issuer,
+    )
+    .expect("Failed to create token");
+
+    let stream = gl_db::StreamRepository::new(state.db.pool())
+        .create(gl_db::CreateStreamRequest {
+            user_id: user.id.clone(),
+            name: "Gate".to_string(),
+            description: None,
+            config: json!({"kind": "file", "file_path": "/tmp/gate.mp4"}).to_string(),
+            is_default: false,
+        })
+        .await
+        .expect("Failed to create stream");
+    let embeddings = gl_db::SnapshotEmbeddingRepository::new(state.db.clone());
+    let mut ids = Vec::new();
+    for (captured_at, vector) in [
+        ("2026-10-18T08:00:00Z", [0.9, 0.1, 0.1]),
+        ("2026-10-18T09:00:00Z", [0.1, 0.1, 0.9]),
+    ] {
+        let snapshot = gl_db::SnapshotRepository::new(state.db.pool())
+            .create(gl_db::CreateSnapshotRequest {
+                stream_id: stream.id.clone(),
+                user_id: user.id.clone(),
+                file_path: format!("/tmp/{}.jpg", captured_at),
+                storage_uri: String::new(),
+                content_type: "image/jpeg".to_string(),
+                width: None,
+                height: None,
+                file_size: 1,
+                checksum: None,
+                etag: None,
+                captured_at: captured_at.to_string(),
+                perceptual_hash: None,
+            })
+            .await
+            .unwrap();
+        embeddings
+            .upsert(&snapshot.id, "colour-clip", &vector)
+            .await
+            .unwrap();
+        ids.push(snapshot.id);
+    }
+
+    let capture_manager = state.capture_manager.clone();
+    let app = test::init_service(create_app(state.clone())).await;
+    let search = |body: serde_json::Value| {
+        test::TestRequest::post()
+            .uri("/api/search/snapshots")
+            .insert_header(("authorization", format!("Bearer {}", token)))
+            .set_json(body)
+            .to_request()
+    };
+
+    let resp = test::call_service(&app, search(json!({"text": "red truck"}))).await;
+    assert_eq!(resp.status(), 503);
+
+    capture_manager
+        .set_job_scheduler(state.job_scheduler.clone())
+        .await;
+    let models = tempfile::tempdir().unwrap();
+    let clip = test_support::onnx::colour_clip(models.path());
+    capture_manager
+        .enable_snapshot_search(&gl_ai::EmbeddingConfig {
+            enabled: true,
+            model: "colour-clip".to_string(),
+            image_model_path: clip.image_model.to_string_lossy().to_string(),
+            text_model_path: clip.text_model.to_string_lossy().to_string(),
+            tokenizer_path: clip.tokenizer.to_string_lossy().to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    let resp = test::call_service(&app, search(json!({"text": "red truck"}))).await;
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    let matches = body["data"].as_array().unwrap();
+    assert_eq!(matches.len(), 2);
+    assert_eq!(matches[0]["snapshot_id"], ids[0].as_str());
+
+    let resp = test::call_service(
+        &app,
+        search(json!({
+            "snapshot_id": ids[0],
+            "stream_id": stream.id,
+            "from": "2026-10-18T08:30:00Z",
+        })),
+    )
+    .await;
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"].as_array().unwrap().len(), 1);
+    assert_eq!(body["data"][0]["snapshot_id"], ids[1].as_str());
+
+    let resp =
+        test::call_service(&app, search(json!({"text": "red", "snapshot_id": ids[0]}))).await;
+    assert_eq!(resp.status(), 400);
+    let resp = test::call_service(&app, search(json!({"snapshot_id": "missing"}))).await;
+    assert_eq!(resp.status(), 404);
+}
+
+#[tokio::test]
+async fn test_capture_records_motion_heatmap() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let state = create_test_app_state().await;
+    let user = create_test_user(&state, "heat-capture@example.com", "password123").await;
+
+    // A camera watching a bright square move along the top of the picture
+    let frames: Vec<Vec<u8>> = (0..4)
+        .map(|step| {
+            gl_vision::utils::image_to_jpeg_bytes(&gl_vision::utils::create_test_frame_with_motion(
+                160,
+                120,
+                step * 30,
+                0,
+                30,
+                30,
+                230,
+            ))
+            .unwrap()
+        })
+        .collect();
+    let requests = Arc::new(AtomicUsize::new(0));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let camera_url = format!("http://{}/snapshot.jpg", listener.local_addr().unwrap());
+    let camera = tokio::spawn(async move {
+        loop {
+            let Ok((mut socket, _)) = listener.accept().await else {
+                break;
+            };
+            let body = frames[requests.fetch_add(1, Ordering::SeqCst) % frames.len()].clone();
+            tokio::spawn(async move {
+                let mut request = [0u8; 2048];
+                let _ = socket.read(&mut request).await;
+                let header = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: image/jpeg\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                );
+                let _ = socket.write_all(header.as_bytes()).await;
+                let _ = socket.write_all(&body).await;
+            });
+        }
+    });
+
+    let stream = gl_db::StreamRepository::new(state.db.pool())
+        .create(gl_db::CreateStreamRequest {
+            user_id: user.id.clone(),
+            name: "Hallway".to_string(),
+            description: None,
+            config: json!({
+                "kind": "http",
+                "url": camera_url,
+                "snapshot_interval": 1,
+                "heatmap": {"cols": 4, "rows": 4}
+            })
+            .to_string(),
+            is_default: false,
+        })
+        .await
+        .expect("Failed to create stream");
+
+    let artifacts_dir = std::env::temp_dir().join(format!("glimpser_heatmap_{}", Id::new()));
+    let manager = capture_manager::CaptureManager::with_storage_config(
+        state.db.pool().clone(),
+        gl_config::StorageConfig {
+            artifacts_dir: artifacts_dir.to_string_lossy().to_string(),
+            ..Default::default()
+        },
+        state.background_snapshot_service.clone(),
+    );
+    manager.start_stream(&stream.id).await.unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(4500)).await;
+    // Stopping writes out whatever the hour has accumulated so far
+    manager.stop_stream(&stream.id).await.unwrap();
+    camera.abort();
+
+    let hours = gl_db::MotionHeatmapRepository::new(state.db.clone())
+        .list_range(&stream.id, "2000-01-01T00:00:00Z", "2100-01-01T00:00:00Z")
+        .await
+        .unwrap();
+    assert_eq!(hours.len(), 1);
+    assert_eq!((hours[0].cols, hours[0].rows), (4, 4));
+    assert!(hours[0].frames >= 2, "{:?}", hours[0]);
+    // Only the top row of cells ever saw the square
+    assert!(hours[0].cells[..4].iter().any(|&count| count > 0));
+    assert!(hours[0].cells[4..].iter().all(|&count| count == 0));
+    let _ = std::fs::remove_dir_all(artifacts_dir);
+}
+
+#[actix_web::test]
+async fn test_digest_report_endpoints() {
+    let state = create_test_app_state().await;
+    let user = create_test_user(&state, "reports@example.com", "password123").await;
+    let token = crate::auth::JwtAuth::create_token(
+        &user.id,
+        &user.email,
+        &state.security_config.jwt_secret,
+        &state.security_config.jwt_issuer,
+    )
+    .expect("Failed to create token");
+
+    let report = gl_db::DigestReportRepository::new(state.db.clone())
+        .create(gl_db::CreateDigestReport {
+            name: "morning".to_string(),
+            period: "daily".to_string(),
+            period_start: "2026-10-17T07:00:00Z".to_string(),
+            period_end: "2026-10-18T07:00:00Z".to_string(),
+            summary: "1 of 1 streams recorded 4 events.".to_string(),
+            html: "<h1>Glimpser daily digest: morning</h1>".to_string(),
+            stats: json!({"total_events": 4, "streams": []}),
+        })
+        .await
+        .unwrap();
+
+    let app = test::init_service(create_app(state)).await;
+    let get = |uri: String| {
+        test::TestRequest::get()
+            .uri(&uri)
+            .insert_header(("authorization", format!("Bearer {}", token)))
+            .to_request()
+    };
+
+    let resp = test::call_service(&app, get("/api/reports?name=morning".to_string())).await;
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"][0]["id"], report.id.as_str());
+    assert_eq!(body["data"][0]["stats"]["total_events"], 4);
+    assert!(body["data"][0].get("html").is_none());
+
+    let resp = test::call_service(&app, get(format!("/api/reports/{}", report.id))).await;
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"]["summary"], "1 of 1 streams recorded 4 events.");
+
+    let resp = test::call_service(&app, get(format!("/api/reports/{}/html", report.id))).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(
+        resp.headers().get("content-type").unwrap(),
+        "text/html; charset=utf-8"
+    );
+    let body = test::read_body(resp).await;
+    assert_eq!(&body[..], b"<h1>Glimpser daily digest: morning</h1>");
+
+    let resp = test::call_service(&app, get("/api/reports/missing".to_string())).await;
+    assert_eq!(resp.status(), 404);
+}
+
+#[actix_web::test]
+async fn test_privacy_masking_and_originals() {
+    let state = create_test_app_state().await;
+    let owner = create_test_user(&state, "owner@example.com", "password123").await;
+    let officer = create_test_user(&state, "dpo@example.com", "password123").await;
+    let token = |user: &gl_db::User| {
+        crate::auth::JwtAuth::create_token(
+            &user.id,
+            &user.email,
+            &state.security_config.jwt_secret,
+            &state.security_config.jwt_issuer,
+        )
+        .expect("Failed to create token")
+    };
+    let (owner_token, officer_token) = (token(&owner), token(&officer));
+
+    let stream = gl_db::StreamRepository::new(state.db.pool())
+        .create(gl_db::CreateStreamRequest {
+            user_id: owner.id.clone(),
+            name: "Front garden".to_string(),
+            description: None,
+            config: json!({
+                "kind": "file",
+                "file_path": "/tmp/garden.mp4",
+                "privacy": {
+                    // The neighbour's window, top left quarter of the picture
+                    "polygons": [[[0.0, 0.0], [0.5, 0.0], [0.5, 0.5], [0.0, 0.5]]],
+                    "fill": [0, 0, 0],
+                    "originals": {"retention_hours": 1, "viewers": ["DPO@example.com"]}
+                }
+            })
+            .to_string(),
+            is_default: false,
+        })
+        .await
+        .expect("Failed to create stream");
+
+    let artifacts_dir = std::env::temp_dir().join(format!("glimpser_privacy_{}", Id::new()));
+    let manager = Arc::new(capture_manager::CaptureManager::with_storage_config(
+        state.db.pool().clone(),
+        gl_config::StorageConfig {
+            artifacts_dir: artifacts_dir.to_string_lossy().to_string(),
+            ..Default::default()
+        },
+        state.background_snapshot_service.clone(),
+    ));
+
+    let frame = gl_vision::utils::image_to_jpeg_bytes(&image::GrayImage::from_pixel(
+        160,
+        120,
+        image::Luma([200]),
+    ))
+    .unwrap();
+    let snapshot_id = manager
+        .ingest_snapshot(
+            &stream.id,
+            bytes::Bytes::from(frame),
+            std::collections::HashMap::new(),
+            None,
+        )
+        .await
+        .unwrap();
+
+    // Only the masked frame is stored as a snapshot
+    let snapshot = gl_db::SnapshotRepository::new(state.db.pool())
+        .find_by_id(&snapshot_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let stored = image::open(artifacts_dir.join(snapshot.file_path.trim_start_matches('/')))
+        .unwrap()
+        .to_luma8();
+    assert!(stored.get_pixel(20, 20)[0] < 30);
+    assert!(stored.get_pixel(120, 90)[0] > 170);
+
+    let originals = gl_db::OriginalFrameRepository::new(state.db.clone())
+        .list_by_stream(&stream.id, 10, 0)
+        .await
+        .unwrap();
+    assert_eq!(originals.len(), 1);
+    let original = manager.read_original(&originals[0]).await.unwrap();
+    let decoded = image::load_from_memory(&original).unwrap().to_luma8();
+    assert!(decoded.get_pixel(20, 20)[0] > 170);
+
+    // Recording would hand out the camera's unmasked video
+    assert!(manager
+        .record_stream(&stream.id, std::time::Duration::from_secs(5))
+        .await
+        .is_err());
+
+    let state = AppState {
+        capture_manager: manager,
+        ..state
+    };
+    let app = test::init_service(create_app(state)).await;
+    let get = |uri: String, token: &str| {
+        test::TestRequest::get()
+            .uri(&uri)
+            .insert_header(("authorization", format!("Bearer {}", token)))
+            .to_request()
+    };
+
+    let list = format!("/api/originals?stream_id={}", stream.id);
+    let resp = test::call_service(&app, get(list.clone(), &officer_token)).await;
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"][0]["id"], originals[0].id.as_str());
+
+    let frame_uri = format!("/api/originals/{}", originals[0].id);
+    let resp = test::call_service(&app, get(frame_uri.clone(), &officer_token)).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers().get("cache-control").unwrap(), "no-store");
+    let body = test::read_body(resp).await;
+    assert_eq!(body, original);
+
+    // Owning the stream is not enough to see what the mask hides
+    let resp = test::call_service(&app, get(list, &owner_token)).await;
+    assert_eq!(resp.status(), 403);
+    let resp = test::call_service(&app, get(frame_uri, &owner_token)).await;
+    assert_eq!(resp.status(), 403);
+    let _ = std::fs::remove_dir_all(artifacts_dir);
+}
+
+#[tokio::test]
+async fn test_expired_originals_are_purged_without_new_writes() {
+    use gl_scheduler::JobHandler;
+
+    let state = create_test_app_state().await;
+    let user = create_test_user(&state, "purge@example.com", "password123").await;
+    let stream = gl_db::StreamRepository::new(state.db.pool())
+        .create(gl_db::CreateStreamRequest {
+            user_id: user.id.clone(),
+            name: "Car park".to_string(),
+            description: None,
+            config: json!({
+                "kind": "file",
+                "file_path": "/tmp/car_park.mp4",
+                "privacy": {
+                    "polygons": [[[0.0, 0.0], [0.5, 0.0], [0.5, 0.5]]],
+                    "originals": {"retention_hours": 1}
+                }
+            })
+            .to_string(),
+            is_default: false,
+        })
+        .await
+        .expect("Failed to create stream");
+
+    let artifacts_dir = std::env::temp_dir().join(format!("glimpser_purge_{}", Id::new()));
+    let manager = Arc::new(capture_manager::CaptureManager::with_storage_config(
+        state.db.pool().clone(),
+        gl_config::StorageConfig {
+            artifacts_dir: artifacts_dir.to_string_lossy().to_string(),
+            ..Default::default()
+        },
+        state.background_snapshot_service.clone(),
+    ));
+    let frame = gl_vision::utils::image_to_jpeg_bytes(&image::GrayImage::from_pixel(
+        64,
+        48,
+        image::Luma([200]),
+    ))
+    .unwrap();
+    manager
+        .ingest_snapshot(
+            &stream.id,
+            bytes::Bytes::from(frame),
+            std::collections::HashMap::new(),
+            None,
+        )
+        .await
+        .unwrap();
+
+    let repo = gl_db::OriginalFrameRepository::new(state.db.clone());
+    let original = repo
+        .list_by_stream(&stream.id, 10, 0)
+        .await
+        .unwrap()
+        .remove(0);
+    let job = crate::privacy::OriginalPurgeJob::new(manager.artifact_storage_service());
+    let run = || {
+        job.execute(gl_scheduler::JobContext::new(
+            crate::privacy::PURGE_JOB_TYPE.to_string(),
+            json!({}),
+            state.db.clone(),
+            manager.clone(),
+        ))
+    };
+
+    // Originals still within their retention are kept
+    assert_eq!(run().await.unwrap()["purged"], 0);
+    assert!(manager.read_original(&original).await.is_ok());
+
+    // The stream has stopped; the original expires with nothing written after it
+    sqlx::query("UPDATE original_frames SET expires_at = '2000-01-01T00:00:00Z'")
+        .execute(state.db.pool())
+        .await
+        .unwrap();
+    assert_eq!(run().await.unwrap()["purged"], 1);
+    assert!(repo
+        .list_by_stream(&stream.id, 10, 0)
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(manager.read_original(&original).await.is_err());
+    let _ = std::fs::remove_dir_all(artifacts_dir);
+}
+
+#[actix_web::test]
+async fn test_on_demand_snapshots_of_stopped_streams_are_masked() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let state = create_test_app_state().await;
+    let user = create_test_user(&state, "masked-live@example.com", "password123").await;
+    let token = crate::auth::JwtAuth::create_token(
+        &user.id,
+        &user.email,
+        &state.security_config.jwt_secret,
+        &state.security_config.jwt_issuer,
+    )
+    .expect("Failed to create token");
+
+    let frame = gl_vision::utils::image_to_jpeg_bytes(&image::GrayImage::from_pixel(
+        160,
+        120,
+        image::Luma([200]),
+    ))
+    .unwrap();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let camera_url = format!("http://{}/snapshot.jpg", listener.local_addr().unwrap());
+    let camera = tokio::spawn(async move {
+        loop {
+            let Ok((mut socket, _)) = listener.accept().await else {
+                break;
+            };
+            let body = frame.clone();
+            tokio::spawn(async move {
+                let mut request = [0u8; 2048];
+                let _ = socket.read(&mut request).await;
+                let header = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: image/jpeg\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                );
+                let _ = socket.write_all(header.as_bytes()).await;
+                let _ = socket.write_all(&body).await;
+            });
+        }
+    });
+
+    // Never started, so every request captures straight from the camera
+    let stream = gl_db::StreamRepository::new(state.db.pool())
+        .create(gl_db::CreateStreamRequest {
+            user_id: user.id.clone(),
+            name: "Shop front".to_string(),
+            description: None,
+            config: json!({
+                "kind": "http",
+                "url": camera_url,
+                "privacy": {
+                    "polygons": [[[0.0, 0.0], [0.5, 0.0], [0.5, 0.5], [0.0, 0.5]]],
+                    "fill": [0, 0, 0]
+                }
+            })
+            .to_string(),
+            is_default: false,
+        })
+        .await
+        .expect("Failed to create stream");
+
+    let app = test::init_service(create_app(state)).await;
+    for route in ["snapshot", "live"] {
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/stream/{}/{}", stream.id, route))
+            .insert_header(("authorization", format!("Bearer {}", token)))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 200, "{}", route);
+        let body = test::read_body(resp).await;
+        let served = image::load_from_memory(&body).unwrap().to_luma8();
+        assert!(
+            served.get_pixel(20, 20)[0] < 30,
+            "{} served an unmasked frame",
+            route
+        );
+        assert!(served.get_pixel(120, 90)[0] > 170, "{}", route);
+    }
+    camera.abort();
+}
@@ -10,3 +10,6 @@ pub fn create_test_id() -> String {
 pub fn temp_dir_path() -> std::path::PathBuf {
     std::env::temp_dir().join("glimpser-test")
 }
+
+#[cfg(feature = "onnx")]
+pub mod onnx;
@@ -0,0 +1,332 @@
+//! ABOUTME: Builder for small ONNX models used to test in-process inference
+//! ABOUTME: Writes real ONNX protobuf files so tests exercise the same loading path as models
+
+use prost::Message;
+use std::path::{Path, PathBuf};
+use tract_onnx::pb::{
+    attribute_proto::AttributeType, tensor_shape_proto::dimension::Value as DimValue,
+    tensor_shape_proto::Dimension, type_proto, AttributeProto, GraphProto, ModelProto, NodeProto,
+    OperatorSetIdProto, TensorProto, TensorShapeProto, TypeProto, ValueInfoProto,
+};
+
+pub use tract_onnx::pb::tensor_proto::DataType;
+
+/// ONNX model assembled node by node
+#[derive(Default)]
+pub struct OnnxModelBuilder {
+    graph: GraphProto,
+}
+
+impl OnnxModelBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declare a graph input; a negative dimension is left symbolic
+    pub fn input(mut self, name: &str, data_type: DataType, dims: &[i64]) -> Self {
+        self.graph.input.push(value_info(name, data_type, dims));
+        self
+    }
+
+    /// Declare a graph output
+    pub fn output(mut self, name: &str, data_type: DataType, dims: &[i64]) -> Self {
+        self.graph.output.push(value_info(name, data_type, dims));
+        self
+    }
+
+    /// Add a constant float tensor
+    pub fn floats(mut self, name: &str, dims: &[i64], data: Vec<f32>) -> Self {
+        self.graph.initializer.push(TensorProto {
+            name: name.to_string(),
+            dims: dims.to_vec(),
+            data_type: DataType::Float as i32,
+            float_data: data,
+            ..Default::default()
+        });
+        self
+    }
+
+    /// Add a constant int64 tensor
+    pub fn ints(mut self, name: &str, dims: &[i64], data: Vec<i64>) -> Self {
+        self.graph.initializer.push(TensorProto {
+            name: name.to_string(),
+            dims: dims.to_vec(),
+            data_type: DataType::Int64 as i32,
+            int64_data: data,
+            ..Default::default()
+        });
+        self
+    }
+
+    /// Add an operator
+    pub fn node(
+        mut self,
+        op_type: &str,
+        inputs: &[&str],
+        outputs: &[&str],
+        attributes: Vec<AttributeProto>,
+    ) -> Self {
+        self.graph.node.push(NodeProto {
+            op_type: op_type.to_string(),
+            name: format!("{}_{}", op_type, self.graph.node.len()),
+            input: inputs.iter().map(|s| s.to_string()).collect(),
+            output: outputs.iter().map(|s| s.to_string()).collect(),
+            attribute: attributes,
+            ..Default::default()
+        });
+        self
+    }
+
+    /// Encode the model as an opset 13 ONNX file
+    pub fn to_bytes(&self) -> Vec<u8> {
+        ModelProto {
+            ir_version: 7,
+            producer_name: "test_support".to_string(),
+            opset_import: vec![OperatorSetIdProto {
+                domain: String::new(),
+                version: 13,
+            }],
+            graph: Some(GraphProto {
+                name: "test".to_string(),
+                ..self.graph.clone()
+            }),
+            ..Default::default()
+        }
+        .encode_to_vec()
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) {
+        std::fs::write(path, self.to_bytes()).expect("Failed to write ONNX model");
+    }
+}
+
+/// Files of a tiny CLIP-style model written by `colour_clip`
+pub struct ColourClip {
+    pub image_model: PathBuf,
+    pub text_model: PathBuf,
+    pub tokenizer: PathBuf,
+}
+
+/// Colour words the `colour_clip` text encoder knows, with their RGB vectors
+pub const CLIP_COLOURS: [(&str, [f32; 3]); 6] = [
+    ("red", [1.0, 0.0, 0.0]),
+    ("green", [0.0, 1.0, 0.0]),
+    ("blue", [0.0, 0.0, 1.0]),
+    ("yellow", [1.0, 1.0, 0.0]),
+    ("white", [1.0, 1.0, 1.0]),
+    ("black", [0.01, 0.01, 0.01]),
+];
+
+/// Write a CLIP-style image encoder, text encoder and tokenizer into a directory
+///
+/// Images embed to their average colour, undoing the standard CLIP normalisation, and
+/// text to the sum of the colours it names, so "red truck" matches red snapshots.
+pub fn colour_clip(dir: impl AsRef<Path>) -> ColourClip {
+    const MEAN: [f32; 3] = [0.481_454_66, 0.457_827_5, 0.408_210_73];
+    const STD: [f32; 3] = [0.268_629_54, 0.261_302_6, 0.275_777_1];
+    let dir = dir.as_ref();
+    let clip = ColourClip {
+        image_model: dir.join("image.onnx"),
+        text_model: dir.join("text.onnx"),
+        tokenizer: dir.join("tokenizer.json"),
+    };
+
+    OnnxModelBuilder::new()
+        .input("pixel_values", DataType::Float, &[-1, 3, -1, -1])
+        .output("image_embeds", DataType::Float, &[-1, 3])
+        .floats("std", &[1, 3, 1, 1], STD.to_vec())
+        .floats("mean", &[1, 3, 1, 1], MEAN.to_vec())
+        .node("Mul", &["pixel_values", "std"], &["scaled"], vec![])
+        .node("Add", &["scaled", "mean"], &["pixels"], vec![])
+        .node(
+            "ReduceMean",
+            &["pixels"],
+            &["image_embeds"],
+            vec![ints_attr("axes", &[2, 3]), int_attr("keepdims", 0)],
+        )
+        .save(&clip.image_model);
+
+    // Token 0 pads and token 1 is unknown; neither has a colour
+    let mut vocab = serde_json::Map::new();
+    vocab.insert("<|endoftext|>".to_string(), 0.into());
+    vocab.insert("<unk>".to_string(), 1.into());
+    let mut colours = vec![0.0; 6];
+    for (index, (name, colour)) in CLIP_COLOURS.iter().enumerate() {
+        vocab.insert(name.to_string(), (index + 2).into());
+        colours.extend_from_slice(colour);
+    }
+    OnnxModelBuilder::new()
+        .input("input_ids", DataType::Int64, &[-1, -1])
+        .input("attention_mask", DataType::Int64, &[-1, -1])
+        .output("text_embeds", DataType::Float, &[-1, 3])
+        .floats("colours", &[CLIP_COLOURS.len() as i64 + 2, 3], colours)
+        .ints("last_axis", &[1], vec![2])
+        .ints("token_axis", &[1], vec![1])
+        .node(
+            "Gather",
+            &["colours", "input_ids"],
+            &["token_colours"],
+            vec![],
+        )
+        .node(
+            "Cast",
+            &["attention_mask"],
+            &["mask"],
+            vec![int_attr("to", 1)],
+        )
+        .node(
+            "Unsqueeze",
+            &["mask", "last_axis"],
+            &["mask_column"],
+            vec![],
+        )
+        .node(
+            "Mul",
+            &["token_colours", "mask_column"],
+            &["masked"],
+            vec![],
+        )
+        .node(
+            "ReduceSum",
+            &["masked", "token_axis"],
+            &["text_embeds"],
+            vec![int_attr("keepdims", 0)],
+        )
+        .save(&clip.text_model);
+
+    let tokenizer = serde_json::json!({
+        "version": "1.0",
+        "truncation": null,
+        "padding": null,
+        "added_tokens": [],
+        "normalizer": {"type": "Lowercase"},
+        "pre_tokenizer": {"type": "Whitespace"},
+        "post_processor": null,
+        "decoder": null,
+        "model": {"type": "WordLevel", "vocab": vocab, "unk_token": "<unk>"},
+    });
+    std::fs::write(&clip.tokenizer, tokenizer.to_string()).expect("Failed to write tokenizer");
+    clip
+}
+
+/// Plate `brightness_plate_ocr` reads from light plates with dark characters
+pub const LIGHT_PLATE: &str = "AB12CDE";
+/// Plate `brightness_plate_ocr` reads from dark plates with light characters
+pub const DARK_PLATE: &str = "XY98ZPK";
+
+/// Write a plate recogniser shaped like a fast-plate-ocr export
+///
+/// It takes a `[1, height, width, channels]` uint8 crop and returns nine slots of scores
+/// over `alphabet`, whose last character pads. Crops brighter than mid-grey read as
+/// `LIGHT_PLATE` and darker ones as `DARK_PLATE`.
+pub fn brightness_plate_ocr(
+    path: impl AsRef<Path>,
+    alphabet: &str,
+    width: u32,
+    height: u32,
+    channels: u32,
+) {
+    let alphabet: Vec<char> = alphabet.chars().collect();
+    let pad = *alphabet.last().expect("alphabet must not be empty");
+    let slots = 9;
+    let padded = |plate: &str| -> Vec<char> {
+        plate
+            .chars()
+            .chain(std::iter::repeat(pad))
+            .take(slots)
+            .collect()
+    };
+    let (light, dark) = (padded(LIGHT_PLATE), padded(DARK_PLATE));
+    let (mut weights, mut bias) = (Vec::new(), Vec::new());
+    for (&light_ch, &dark_ch) in light.iter().zip(&dark) {
+        for &ch in &alphabet {
+            let (w, b) = if ch == light_ch && ch == dark_ch {
+                (0.0, 10.0)
+            } else if ch == light_ch {
+                (40.0, -20.0)
+            } else if ch == dark_ch {
+                (-40.0, 20.0)
+            } else {
+                (0.0, -10.0)
+            };
+            weights.push(w);
+            bias.push(b);
+        }
+    }
+    let size = (slots * alphabet.len()) as i64;
+    OnnxModelBuilder::new()
+        .input(
+            "image",
+            DataType::Uint8,
+            &[-1, height as i64, width as i64, channels as i64],
+        )
+        .output(
+            "scores",
+            DataType::Float,
+            &[-1, slots as i64, alphabet.len() as i64],
+        )
+        .floats("scale", &[], vec![1.0 / 255.0])
+        .floats("weights", &[1, size], weights)
+        .floats("bias", &[size], bias)
+        .ints("flat", &[2], vec![1, 1])
+        .ints("slots", &[3], vec![1, slots as i64, alphabet.len() as i64])
+        .node("Cast", &["image"], &["pixels"], vec![int_attr("to", 1)])
+        .node("Mul", &["pixels", "scale"], &["unit"], vec![])
+        .node(
+            "ReduceMean",
+            &["unit"],
+            &["brightness"],
+            vec![ints_attr("axes", &[1, 2, 3])],
+        )
+        .node("Reshape", &["brightness", "flat"], &["column"], vec![])
+        .node("MatMul", &["column", "weights"], &["weighted"], vec![])
+        .node("Add", &["weighted", "bias"], &["logits"], vec![])
+        .node("Reshape", &["logits", "slots"], &["scores"], vec![])
+        .save(path);
+}
+
+/// Integer attribute
+pub fn int_attr(name: &str, value: i64) -> AttributeProto {
+    AttributeProto {
+        name: name.to_string(),
+        r#type: AttributeType::Int as i32,
+        i: value,
+        ..Default::default()
+    }
+}
+
+/// Integer list attribute
+pub fn ints_attr(name: &str, values: &[i64]) -> AttributeProto {
+    AttributeProto {
+        name: name.to_string(),
+        r#type: AttributeType::Ints as i32,
+        ints: values.to_vec(),
+        ..Default::default()
+    }
+}
+
+fn value_info(name: &str, data_type: DataType, dims: &[i64]) -> ValueInfoProto {
+    let dim = dims
+        .iter()
+        .enumerate()
+        .map(|(i, &d)| Dimension {
+            value: Some(if d < 0 {
+                DimValue::DimParam(format!("{}_{}", name, i))
+            } else {
+                DimValue::DimValue(d)
+            }),
+            ..Default::default()
+        })
+        .collect();
+    ValueInfoProto {
+        name: name.to_string(),
+        r#type: Some(TypeProto {
+            value: Some(type_proto::Value::TensorType(type_proto::Tensor {
+                elem_type: data_type as i32,
+                shape: Some(TensorShapeProto { dim }),
+            })),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}